@@ -38,6 +38,7 @@ var textures: binding_array<texture_2d<f32>>;
 var nearest_sampler: sampler;
 
 struct FragmentInput {
+	@builtin(position) frag_pos: vec4<f32>,
 	@location(0) uv: vec2<f32>,
 	@location(1) tex_ix: u32,
 }
@@ -47,5 +48,36 @@ fn fragment(input: FragmentInput) -> @location(0) vec4<f32> {
     // Select the texture to sample from using non-uniform UV coordinates.
     let coords = clamp(vec2<u32>(input.uv), vec2<u32>(0u), vec2<u32>(3u));
     let inner_uv = fract(input.uv);
-    return textureSample(textures[input.tex_ix], nearest_sampler, inner_uv);
+    var color = textureSample(textures[input.tex_ix], nearest_sampler, inner_uv);
+
+#ifdef STYLE_STENCIL
+	let stencil = #{STYLE_STENCIL}u;
+	color = vec4<f32>(
+		f32((stencil >> 16u) & 0xFFu) / 255.0,
+		f32((stencil >> 8u) & 0xFFu) / 255.0,
+		f32(stencil & 0xFFu) / 255.0,
+		color.a,
+	);
+#endif
+
+#ifdef STYLE_FUZZ
+	// Dithered alpha; see `RenderStyle::Shadow`.
+	let texel = vec2<u32>(input.frag_pos.xy);
+
+	if ((texel.x + texel.y) % 2u) == 0u {
+		discard;
+	}
+
+	color = vec4<f32>(color.rgb * 0.1, color.a);
+#endif
+
+#ifdef STYLE_ALPHA
+	color.a *= f32(#{STYLE_ALPHA}u) / 255.0;
+#endif
+
+#ifdef STYLE_ADDITIVE
+	color = vec4<f32>(color.rgb * color.a, 0.0);
+#endif
+
+    return color;
 }
@@ -13,9 +13,13 @@ use bevy::{
 };
 use indoc::formatdoc;
 use viletech::{
+	bench::BenchSpec,
 	console::MessageKind,
+	sim::interp::Interpolation,
 	terminal::{self, CommandArgs},
 	tracing::{error, info},
+	user::UserCore,
+	VirtualFs,
 };
 
 use crate::dgui::Console;
@@ -125,6 +129,51 @@ pub(crate) fn ccmd_args(args: CommandArgs) -> Request {
 	Request::None
 }
 
+/// Runs a benchmark of the sim in the current world; see [`viletech::bench`].
+pub(crate) fn ccmd_bench(args: CommandArgs) -> Request {
+	if args.name_only() || args.help_requested() {
+		return req_console_write_help(formatdoc! {"
+Benchmark the sim and check it for determinism. Replaces any running sim.
+
+Usage: {} <spec>
+
+The spec is a list of `;`-separated `key=value` pairs, such as:
+`load=doom2.wad,mymod.pk3;ticks=350;seed=1`
+
+Either `ticks` or `demo` is required. A JSON report gets written to the
+`bench` folder in the user directory.",
+			args.command_name()
+		});
+	}
+
+	let spec = match args.concat(1).parse::<BenchSpec>() {
+		Ok(s) => s,
+		Err(err) => {
+			error!("{err}");
+			return Request::None;
+		}
+	};
+
+	req_callback(move |eworld| {
+		let report = match viletech::bench::run(eworld, &spec) {
+			Ok(r) => r,
+			Err(err) => {
+				error!("Benchmark failed: {err}");
+				return;
+			}
+		};
+
+		info!("{report}");
+
+		let dir = eworld.resource::<UserCore>().bench_dir();
+
+		match report.write_json(&dir) {
+			Ok(path) => info!("Benchmark report written to: {}", path.display()),
+			Err(err) => error!("Failed to write benchmark report: {err}"),
+		}
+	})
+}
+
 /// Clears the console's message history.
 pub(crate) fn ccmd_clear(args: CommandArgs) -> Request {
 	if args.help_requested() {
@@ -150,6 +199,25 @@ pub(crate) fn ccmd_exit(args: CommandArgs) -> Request {
 	})
 }
 
+/// Runs each line of a file as a console command.
+pub(crate) fn ccmd_exec(args: CommandArgs) -> Request {
+	if args.name_only() || args.help_requested() {
+		return req_console_write_help(formatdoc! {"
+Runs each line of a file as a console command.
+
+Usage: {} <path>
+
+The path is looked up in the virtual file system first. Absolute paths which
+are not found there get read from the real file system instead.
+Lines which are blank or start with `//` are skipped.",
+			args.command_name()
+		});
+	}
+
+	let path = args[1].to_string();
+	req_callback(move |eworld| exec_file(eworld, &path))
+}
+
 /// Clears the console's history of submitted input strings.
 pub(crate) fn ccmd_hclear(args: CommandArgs) -> Request {
 	if args.help_requested() {
@@ -213,6 +281,50 @@ pub(crate) fn ccmd_help(args: CommandArgs) -> Request {
 	})
 }
 
+/// Toggles the smoothing of motion between sim ticks, or reports whether it's on.
+pub(crate) fn ccmd_interp(args: CommandArgs) -> Request {
+	if args.help_requested() {
+		return req_console_write_help(formatdoc! {"
+Enable or disable the smoothing of motion between sim ticks.
+
+Usage: {} [on|off]
+
+If neither `on` nor `off` is provided, prints whether smoothing is enabled.",
+			args.command_name()
+		});
+	}
+
+	let enable = if args.name_only() {
+		None
+	} else {
+		match args[1] {
+			"on" | "1" | "true" => Some(true),
+			"off" | "0" | "false" => Some(false),
+			other => {
+				error!("Expected `on` or `off`, but got: {other}");
+				return Request::None;
+			}
+		}
+	};
+
+	req_callback(move |eworld| {
+		let mut interp = eworld.resource_mut::<Interpolation>();
+
+		if let Some(e) = enable {
+			interp.enabled = e;
+		}
+
+		info!(
+			"Interpolation is {}.",
+			if interp.enabled {
+				"enabled"
+			} else {
+				"disabled"
+			}
+		);
+	})
+}
+
 /// Prints the full version information of the engine and client.
 pub(crate) fn ccmd_version(args: CommandArgs) -> Request {
 	if args.help_requested() {
@@ -323,6 +435,29 @@ pub(crate) fn ccmd_wgpudiag(args: CommandArgs) -> Request {
 
 // Helpers /////////////////////////////////////////////////////////////////////
 
+/// Runs the user's `autoexec.cfg`, if they have one.
+pub(crate) fn autoexec(eworld: &mut World) {
+	let path = eworld.resource::<UserCore>().autoexec_path();
+
+	if !path.exists() {
+		return;
+	}
+
+	info!("Running: {}", path.display());
+	exec_file(eworld, &path.to_string_lossy());
+}
+
+fn exec_file(eworld: &mut World, path: &str) {
+	eworld.resource_scope(|eworld, mut console: Mut<Console>| {
+		let vfs = eworld.resource::<VirtualFs>();
+
+		match terminal::read_exec_file(vfs, path) {
+			Ok(source) => console.exec_lines(&source, path, |p| terminal::read_exec_file(vfs, p)),
+			Err(err) => error!("Failed to read `{path}`: {err}"),
+		}
+	});
+}
+
 #[must_use]
 #[allow(unused)]
 fn req_console_write_invalidopt(opt: &str) -> Request {
@@ -1,21 +1,27 @@
+use std::time::SystemTime;
+
 use bevy::{
 	ecs::system::{SystemParam, SystemState},
 	input::mouse::MouseMotion,
 	prelude::*,
-	window::ApplicationLifetime,
+	render::view::screenshot::ScreenshotManager,
+	window::{ApplicationLifetime, PrimaryWindow},
 	winit::WinitWindows,
 };
 use bevy_egui::{systems::InputEvents, EguiContexts};
 use viletech::{
 	audio::AudioCore,
+	gfx::hud::HudMessage,
 	image,
+	script::{self, EventBridge},
+	user::UserCore,
 	vfs::{self, VPath},
 	VirtualFs,
 };
 
 use crate::{
 	ccmd,
-	dgui::{self, Console, DevGui},
+	dgui::{self, Console, DevGuiWindow},
 };
 
 #[derive(SystemParam)]
@@ -29,7 +35,7 @@ pub(crate) struct InputParam<'w, 's> {
 #[derive(SystemParam)]
 pub(crate) struct ClientCommon<'w, 's> {
 	pub(crate) vfs: ResMut<'w, VirtualFs>,
-	pub(crate) _input: InputParam<'w, 's>,
+	pub(crate) input: InputParam<'w, 's>,
 	pub(crate) _audio: ResMut<'w, AudioCore>,
 	pub(crate) _console: ResMut<'w, Console>,
 	pub(crate) egui: EguiContexts<'w, 's>,
@@ -54,6 +60,16 @@ pub(crate) fn update(world: &mut World) {
 		}
 	}
 
+	{
+		let mut console = world.get_resource_mut::<Console>().unwrap();
+		let lines: Vec<_> = console.submissions.drain(..).collect();
+		let mut bridge = world.get_resource_mut::<EventBridge>().unwrap();
+
+		for line in lines {
+			bridge.push(script::Event::ConsoleLine { line });
+		}
+	}
+
 	let mut sys: SystemState<(
 		Commands,
 		ClientCommon,
@@ -81,27 +97,102 @@ pub(crate) fn update(world: &mut World) {
 }
 
 pub(crate) fn pre_update(
-	windows: Query<(&Window, &DevGui)>,
+	windows: Query<(&Window, &DevGuiWindow)>,
 	mut console: ResMut<Console>,
-	input: InputParam,
+	mut keys: ResMut<Input<KeyCode>>,
 ) {
-	if !windows
+	let focused = windows.iter().any(|(window, _)| window.focused);
+	let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
+	// Ctrl + backquote is reserved for toggling the developer GUI.
+	// TODO: Bind this through the action map once the client has one.
+	if focused && !ctrl && keys.just_pressed(KeyCode::Grave) {
+		console.toggle_overlay();
+	}
+
+	let dgui_open = windows
 		.iter()
-		.any(|(window, dgui)| window.focused && dgui.open)
-	{
+		.any(|(window, dgui)| window.focused && dgui.open);
+
+	if !dgui_open && !console.captures_input() {
 		return;
 	}
 
-	let up_pressed = input.keys.just_pressed(KeyCode::Up);
-	let down_pressed = input.keys.just_pressed(KeyCode::Down);
-	let esc_pressed = input.keys.just_pressed(KeyCode::Escape);
-	let enter_pressed = input.keys.just_pressed(KeyCode::Return);
-
-	console.key_input(up_pressed, down_pressed, esc_pressed, enter_pressed);
+	let up_pressed = keys.just_pressed(KeyCode::Up);
+	let down_pressed = keys.just_pressed(KeyCode::Down);
+	let esc_pressed = keys.just_pressed(KeyCode::Escape);
+	let enter_pressed = keys.just_pressed(KeyCode::Return);
+	let tab_pressed = keys.just_pressed(KeyCode::Tab);
+
+	console.key_input(
+		up_pressed,
+		down_pressed,
+		esc_pressed,
+		enter_pressed,
+		tab_pressed,
+	);
+
+	if console.captures_input() {
+		// Keyboard input goes to the console's text edit via egui's own events;
+		// everything downstream which reads key state should see nothing.
+		keys.reset_all();
+	}
 }
 
 pub(crate) fn post_update() {}
 
+/// Bevy calls this key "Snapshot"; it is Print Screen on most keyboards.
+pub(crate) const SCREENSHOT_KEY: KeyCode = KeyCode::Snapshot;
+
+pub(crate) fn request_screenshot(
+	keys: Res<Input<KeyCode>>,
+	user: Res<UserCore>,
+	mut screenshots: ResMut<ScreenshotManager>,
+	mut counter: Local<u32>,
+	windows: Query<Entity, With<PrimaryWindow>>,
+	mut messages: EventWriter<HudMessage>,
+) {
+	if !keys.just_pressed(SCREENSHOT_KEY) {
+		return;
+	}
+
+	let Ok(window) = windows.get_single() else {
+		return;
+	};
+
+	let dir = user.screenshot_dir();
+
+	if let Err(err) = std::fs::create_dir_all(&dir) {
+		error!(
+			"Failed to create screenshot directory: {} ({err})",
+			dir.display()
+		);
+		return;
+	}
+
+	let path = dir.join(screenshot_filename(SystemTime::now(), *counter));
+	*counter = counter.wrapping_add(1);
+
+	match screenshots.save_screenshot_to_disk(window, &path) {
+		Ok(()) => {
+			info!("Saving screenshot: {}", path.display());
+			messages.send(HudMessage("Screenshot saved".to_string()));
+		}
+		Err(err) => error!("Failed to take screenshot: {err}"),
+	}
+}
+
+/// The counter disambiguates screenshots taken within the same second.
+#[must_use]
+pub(crate) fn screenshot_filename(time: SystemTime, counter: u32) -> String {
+	let secs = time
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs();
+
+	format!("viletech_{secs}_{counter:04}.png")
+}
+
 pub(crate) fn set_window_icon(vfs: &VirtualFs, window: &winit::window::Window) {
 	let path = VPath::new("/viletech/viletech.png");
 
@@ -1,149 +1,133 @@
 //! The developer/debug GUI, which pairs the console with other useful functions.
 
-use bevy::{
-	ecs::system::{EntityCommands, SystemParam},
-	prelude::*,
-};
-use bevy_egui::{egui, EguiContexts};
+use bevy::{app::AppExit, ecs::system::EntityCommands, prelude::*};
+use bevy_egui::{egui, EguiContext, EguiContexts};
 use viletech::{
-	audio::AudioCore,
+	audio::AudioPanel,
+	devgui::{DevGui, DevPanel, DockSlot, DuplicatePanel, PanelState},
+	user::UserCore,
 	util::{self, string::subdivide_file_len},
-	vfs::{self, VPathBuf},
+	vfs::{self, VPath, VPathBuf},
 	VirtualFs,
 };
 
-use crate::{ccmd, common::InputParam, playground::Playground, AppUptime};
+use crate::{ccmd, playground::Playground, AppUptime};
 
 pub(crate) type Console = viletech::console::Console<ccmd::Command>;
 
-/// The developer/debug GUI, which pairs the console with other useful functions.
+/// Whether the developer GUI is open in a window.
 #[derive(Debug, Component)]
-pub(crate) struct DevGui {
+pub(crate) struct DevGuiWindow {
 	pub(crate) open: bool,
-	pub(crate) side: SideMenu,
 }
 
-#[derive(Debug, Resource)]
-pub(crate) struct State {
-	pub(crate) vfs_selection: vfs::Slot,
+/// Registers every panel, then restores the user's last arrangement of them.
+pub(crate) fn devgui(vfs_root: vfs::FolderSlot, user: &UserCore) -> Result<DevGui, DuplicatePanel> {
+	let mut devgui = DevGui::default();
+	devgui.register(VfsPanel::new(vfs_root), DockSlot::Left)?;
+	devgui.register(ConsolePanel, DockSlot::Bottom)?;
+	devgui.register(AudioPanel, DockSlot::Central)?;
+	devgui.register(PlaygroundPanel, DockSlot::Central)?;
+	devgui.restore(&user.globalcfg().devgui);
+	Ok(devgui)
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum SideMenu {
-	Audio,
-	Lith,
-	#[default]
-	Vfs,
-}
+pub(crate) fn draw(world: &mut World) {
+	let toggle_key = {
+		let keys = world.resource::<Input<KeyCode>>();
 
-impl std::fmt::Display for SideMenu {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		match self {
-			Self::Audio => write!(f, "Audio"),
-			Self::Lith => write!(f, "Script Playground"),
-			Self::Vfs => write!(f, "VFS"),
-		}
-	}
-}
-
-#[derive(SystemParam)]
-pub(crate) struct SysParam<'w, 's> {
-	pub(crate) uptime: Res<'w, AppUptime>,
-	pub(crate) egui: EguiContexts<'w, 's>,
-	pub(crate) dguis: Query<'w, 's, (Entity, &'static Window, &'static mut DevGui)>,
-
-	pub(crate) audio: ResMut<'w, AudioCore>,
-	pub(crate) console: ResMut<'w, Console>,
-	pub(crate) input: InputParam<'w, 's>,
-	pub(crate) playground: ResMut<'w, Playground>,
-	pub(crate) vfs: ResMut<'w, VirtualFs>,
-}
+		keys.just_pressed(KeyCode::Grave)
+			&& keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+	};
 
-pub(crate) fn draw(mut param: SysParam, mut state: ResMut<State>) {
-	let toggle_key = param.input.keys.just_pressed(KeyCode::Grave);
+	let mut windows = world.query::<(Entity, &Window, &mut DevGuiWindow)>();
+	let mut to_draw = vec![];
 
-	for (e_window, window, mut dgui) in &mut param.dguis {
+	for (e_window, window, mut dgui) in windows.iter_mut(world) {
 		if window.focused && toggle_key {
 			dgui.open = !dgui.open;
 		}
 
-		if !dgui.open {
-			continue;
+		if dgui.open {
+			to_draw.push(e_window);
 		}
+	}
+
+	let uptime = world.resource::<AppUptime>().elapsed();
 
-		let ctx = param.egui.ctx_for_window_mut(e_window);
+	for e_window in to_draw {
+		let Some(mut ectx) = world.get_mut::<EguiContext>(e_window) else {
+			continue;
+		};
+
+		let ctx = ectx.get_mut().clone();
 		let screen_rect = ctx.input(|inps| inps.screen_rect);
-		let mut dgui_open = dgui.open;
-
-		egui::Window::new("Developer Tools")
-			.id(egui::Id::new("viletech_dgui"))
-			.anchor(egui::Align2::CENTER_TOP, [0.0, 0.0])
-			.fixed_pos([0.0, 0.0])
-			.collapsible(false)
-			.resizable(true)
-			.min_width(screen_rect.width())
-			.min_height(screen_rect.height() * 0.1)
-			.frame(egui::Frame::window(&ctx.style()).multiply_with_opacity(0.8))
-			.open(&mut dgui_open)
-			.show(ctx, |ui| {
-				// Prevent window from overflowing off the screen's sides.
-				ui.set_max_width(screen_rect.width());
-
-				egui::menu::bar(ui, |ui| {
-					let uptime = param.uptime.elapsed();
-					let (hh, mm, ss) = util::duration_to_hhmmss(uptime);
-
-					ui.label(format!("{hh:02}:{mm:02}:{ss:02}"))
-						.on_hover_ui(|ui| {
-							ui.label(
-								format!("Engine has been running for {hh:02} hours, {mm:02} minutes, {ss:02} seconds")
-							);
-						});
-
-					ui.separator();
-
-					side_menu_selector(
-						&mut dgui,
-						ui,
-						&[
-							(SideMenu::Audio, "Audio"),
-							(SideMenu::Lith, "Lithica Playground"),
-							(SideMenu::Vfs, "VFS"),
-						],
-					);
+		let mut dgui_open = true;
+
+		world.resource_scope(|world, mut devgui: Mut<DevGui>| {
+			egui::Window::new("Developer Tools")
+				.id(egui::Id::new("viletech_dgui"))
+				.anchor(egui::Align2::CENTER_TOP, [0.0, 0.0])
+				.fixed_pos([0.0, 0.0])
+				.collapsible(false)
+				.resizable(true)
+				.min_width(screen_rect.width())
+				.min_height(screen_rect.height() * 0.1)
+				.frame(egui::Frame::window(&ctx.style()).multiply_with_opacity(0.8))
+				.open(&mut dgui_open)
+				.show(&ctx, |ui| {
+					// Prevent window from overflowing off the screen's sides.
+					ui.set_max_width(screen_rect.width());
+
+					egui::menu::bar(ui, |ui| {
+						let (hh, mm, ss) = util::duration_to_hhmmss(uptime);
+
+						ui.label(format!("{hh:02}:{mm:02}:{ss:02}"))
+							.on_hover_ui(|ui| {
+								ui.label(
+									format!("Engine has been running for {hh:02} hours, {mm:02} minutes, {ss:02} seconds")
+								);
+							});
+
+						ui.separator();
+						devgui.ui_windows_menu(ui);
+					});
+
+					devgui.ui(&ctx, ui, world);
 				});
+		});
 
-				egui::SidePanel::left("viletech_dgui_console")
-					.default_width(screen_rect.width() * 0.5)
-					.resizable(true)
-					.width_range((screen_rect.width() * 0.1)..=(screen_rect.width() * 0.9))
-					.frame(egui::Frame::side_top_panel(&ctx.style()).multiply_with_opacity(0.8))
-					.show_inside(ui, |ui| {
-						param.console.ui(ctx, ui);
-					});
+		if !dgui_open {
+			world.get_mut::<DevGuiWindow>(e_window).unwrap().open = false;
+		}
+	}
+}
 
-				egui::CentralPanel::default()
-					.frame(egui::Frame::central_panel(&ctx.style()).multiply_with_opacity(0.8))
-					.show_inside(ui, |ui| match dgui.side {
-						SideMenu::Audio => {
-							param.audio.ui(ctx, ui, &param.vfs);
-						}
-						SideMenu::Lith => {
-							param.playground.ui(ctx, ui);
-						}
-						SideMenu::Vfs => {
-							ui_vfs(ui, &mut state, &mut param.vfs);
-						}
-					});
-			});
+/// Draws the "Quake-style" drop-down console to the primary window.
+pub(crate) fn draw_console_overlay(mut egui: EguiContexts, mut console: ResMut<Console>) {
+	console.ui_overlay(egui.ctx_mut());
+}
 
-		dgui.open = dgui_open;
+/// Writes the developer GUI's layout to the user's global config upon exit.
+pub(crate) fn save_layout(
+	mut exit: EventReader<AppExit>,
+	devgui: Res<DevGui>,
+	mut user: ResMut<UserCore>,
+) {
+	if exit.read().next().is_none() {
+		return;
+	}
+
+	user.globalcfg_mut().devgui = devgui.save();
+
+	if let Err(err) = user.write_global_cfg() {
+		error!("Failed to save developer GUI layout: {err}");
 	}
 }
 
 pub(crate) fn on_app_startup(
 	mut cmds: Commands,
-	windows: Query<Entity, (With<Window>, Without<DevGui>)>,
+	windows: Query<Entity, (With<Window>, Without<DevGuiWindow>)>,
 ) {
 	for window in &windows {
 		add_to_window(cmds.entity(window));
@@ -151,17 +135,118 @@ pub(crate) fn on_app_startup(
 }
 
 pub(crate) fn add_to_window(mut ecmds: EntityCommands) {
-	ecmds.insert(DevGui {
+	ecmds.insert(DevGuiWindow {
 		#[cfg(debug_assertions)]
 		open: true,
 		#[cfg(not(debug_assertions))]
 		open: false,
-		side: SideMenu::default(),
 	});
 }
 
-fn ui_vfs(ui: &mut egui::Ui, state: &mut State, vfs: &mut VirtualFs) {
-	fn nav(ui: &mut egui::Ui, state: &mut State, fref: vfs::Ref) {
+// Panels //////////////////////////////////////////////////////////////////////
+
+struct ConsolePanel;
+
+impl DevPanel for ConsolePanel {
+	fn name(&self) -> &'static str {
+		"Console"
+	}
+
+	fn ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, world: &mut World) {
+		world.resource_mut::<Console>().ui(ctx, ui);
+	}
+}
+
+struct PlaygroundPanel;
+
+impl DevPanel for PlaygroundPanel {
+	fn name(&self) -> &'static str {
+		"Lithica Playground"
+	}
+
+	fn ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, world: &mut World) {
+		world.resource_mut::<Playground>().ui(ctx, ui);
+	}
+}
+
+#[derive(Debug)]
+struct VfsPanel {
+	vfs_selection: vfs::Slot,
+	vfs_sort: StatSort,
+	/// Taking statistics walks a whole subtree, so they are only re-taken upon
+	/// selecting another folder or at the user's request.
+	vfs_stats: Option<VfsStats>,
+	/// The path of `vfs_selection` as of the last draw, for persistence.
+	vfs_path: String,
+	/// A persisted path, resolved upon the next draw. If nothing exists there
+	/// anymore, the selection stays at the root.
+	restored: Option<String>,
+}
+
+impl VfsPanel {
+	#[must_use]
+	fn new(root: vfs::FolderSlot) -> Self {
+		Self {
+			vfs_selection: vfs::Slot::Folder(root),
+			vfs_sort: StatSort::default(),
+			vfs_stats: None,
+			vfs_path: String::new(),
+			restored: None,
+		}
+	}
+}
+
+impl DevPanel for VfsPanel {
+	fn name(&self) -> &'static str {
+		"VFS"
+	}
+
+	fn ui(&mut self, _: &egui::Context, ui: &mut egui::Ui, world: &mut World) {
+		let mut vfs = world.resource_mut::<VirtualFs>();
+
+		if let Some(path) = self.restored.take() {
+			if let Some(fref) = vfs.lookup(VPath::new(&path)) {
+				self.vfs_selection = fref.slot();
+			}
+		}
+
+		ui_vfs(ui, self, &mut vfs);
+
+		self.vfs_path = match self.vfs_selection {
+			vfs::Slot::File(islot) => vfs.get_file(islot).unwrap().path().to_string(),
+			vfs::Slot::Folder(oslot) => vfs.get_folder(oslot).unwrap().path().to_string(),
+		};
+	}
+
+	fn save(&self, state: &mut PanelState) {
+		if !self.vfs_path.is_empty() {
+			state.insert("selection".to_string(), self.vfs_path.clone());
+		}
+	}
+
+	fn load(&mut self, state: &PanelState) {
+		self.restored = state.get("selection").cloned();
+	}
+}
+
+/// Which column the VFS statistics table gets sorted by, in descending order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum StatSort {
+	#[default]
+	Size,
+	Files,
+}
+
+#[derive(Debug)]
+struct VfsStats {
+	folder: vfs::FolderSlot,
+	total: vfs::DirStats,
+	/// One per mount if `folder` is the root; otherwise one per subfolder.
+	rows: Vec<(String, vfs::Slot, vfs::DirStats)>,
+}
+
+fn ui_vfs(ui: &mut egui::Ui, state: &mut VfsPanel, vfs: &mut VirtualFs) {
+	fn nav(ui: &mut egui::Ui, state: &mut VfsPanel, fref: vfs::Ref) {
 		fn component_label(
 			ui: &mut egui::Ui,
 			fref: vfs::Ref,
@@ -277,6 +362,8 @@ fn ui_vfs(ui: &mut egui::Ui, state: &mut State, vfs: &mut VirtualFs) {
 				}
 			});
 
+			ui_vfs_stats(ui, state, vfolder);
+
 			for child in vfolder.children() {
 				ui.horizontal(|ui| {
 					if child.is_folder() {
@@ -302,14 +389,135 @@ fn ui_vfs(ui: &mut egui::Ui, state: &mut State, vfs: &mut VirtualFs) {
 	});
 }
 
-// Helpers /////////////////////////////////////////////////////////////////////
+fn ui_vfs_stats(ui: &mut egui::Ui, state: &mut VfsPanel, vfolder: vfs::FolderRef) {
+	let stale = state
+		.vfs_stats
+		.as_ref()
+		.map_or(true, |stats| stats.folder != vfolder.slot());
 
-fn side_menu_selector(dgui: &mut DevGui, ui: &mut egui::Ui, choices: &[(SideMenu, &'static str)]) {
-	egui::ComboBox::new("viletech_dgui_selector", "Side Menu")
-		.selected_text(format!("{}", dgui.side))
-		.show_ui(ui, |ui| {
-			for (choice, label) in choices.iter().copied() {
-				ui.selectable_value(&mut dgui.side, choice, label);
-			}
+	if stale {
+		state.vfs_stats = None;
+	}
+
+	let stats = state
+		.vfs_stats
+		.get_or_insert_with(|| take_vfs_stats(vfolder));
+
+	let total = &stats.total;
+	let mut refresh = false;
+
+	ui.horizontal(|ui| {
+		ui.label(format!(
+			"{} in {} files",
+			subdivide_file_len(total.bytes),
+			total.files
+		));
+
+		ui.separator();
+
+		ui.label(format!(
+			"{} in memory",
+			subdivide_file_len(total.bytes_in_memory)
+		));
+
+		if let Some((path, size)) = &total.largest {
+			ui.separator();
+			ui.label(format!("Largest: {path} ({})", subdivide_file_len(*size)));
+		}
+
+		ui.separator();
+
+		refresh = ui.button("Refresh").clicked();
+	});
+
+	ui.label(format!(
+		"Text: {} / Binary: {} / Image: {} / Audio: {}",
+		total.kinds.text, total.kinds.binary, total.kinds.image, total.kinds.audio
+	));
+
+	match state.vfs_sort {
+		StatSort::Size => stats
+			.rows
+			.sort_by_key(|(_, _, s)| std::cmp::Reverse(s.bytes)),
+		StatSort::Files => stats
+			.rows
+			.sort_by_key(|(_, _, s)| std::cmp::Reverse(s.files)),
+	}
+
+	let mut goto = None;
+
+	egui::CollapsingHeader::new("Breakdown")
+		.id_source("viletech_dgui_vfs_stats")
+		.show(ui, |ui| {
+			egui::Grid::new("viletech_dgui_vfs_stats_grid")
+				.striped(true)
+				.show(ui, |ui| {
+					ui.label("Name");
+					ui.selectable_value(&mut state.vfs_sort, StatSort::Size, "Size");
+					ui.selectable_value(&mut state.vfs_sort, StatSort::Files, "Files");
+					ui.label("Folders");
+					ui.label("In Memory");
+					ui.end_row();
+
+					for (name, slot, s) in &stats.rows {
+						let resp = ui.add(egui::Label::new(name).sense(egui::Sense::click()));
+
+						let resp = if resp.hovered() {
+							resp.highlight()
+						} else {
+							resp
+						};
+
+						if resp.clicked() {
+							goto = Some(*slot);
+						}
+
+						resp.on_hover_text("View");
+
+						ui.label(subdivide_file_len(s.bytes));
+						ui.label(s.files.to_string());
+						ui.label(s.folders.to_string());
+						ui.label(subdivide_file_len(s.bytes_in_memory));
+						ui.end_row();
+					}
+				});
 		});
+
+	if let Some(slot) = goto {
+		state.vfs_selection = slot;
+	}
+
+	if refresh {
+		state.vfs_stats = None;
+	}
+}
+
+#[must_use]
+fn take_vfs_stats(vfolder: vfs::FolderRef) -> VfsStats {
+	let vfs = vfolder.vfs();
+
+	let rows = if vfolder.slot() == vfs.root().slot() {
+		vfs.mount_stats()
+			.into_iter()
+			.zip(vfs.mounts())
+			.map(|(mstats, mntinfo)| (mstats.mount_point.to_string(), mntinfo.root, mstats.stats))
+			.collect()
+	} else {
+		vfolder
+			.subfolders()
+			.map(|sub| {
+				(
+					sub.name().to_string(),
+					vfs::Slot::Folder(sub.slot()),
+					sub.stats(),
+				)
+			})
+			.collect()
+	};
+
+	VfsStats {
+		folder: vfolder.slot(),
+		total: vfolder.stats(),
+		rows,
+	}
 }
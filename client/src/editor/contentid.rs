@@ -463,7 +463,7 @@ impl ContentId {
 			}
 		}
 
-		if viletech::mus::is_dmxmus(bytes) {
+		if viletech::util::io::is_dmx_mus(bytes) {
 			return Self::DmxMus;
 		}
 
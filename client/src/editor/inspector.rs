@@ -4,7 +4,7 @@ use bevy_egui::{
 	EguiContexts,
 };
 use viletech::{
-	data::gfx::{ColorMapSet, PaletteSet},
+	data::gfx::{ColorMapSet, PaletteSet, Rgb8},
 	vfs::FileSlot,
 	VirtualFs,
 };
@@ -25,7 +25,8 @@ pub(crate) enum Inspected {
 	Colormap(ColorMapSet<'static>),
 	Image(Handle<Image>, TextureId),
 	Marker,
-	PlayPal(PaletteSet<'static>, usize),
+	/// The index of the palette being shown, and whether to show all 14 instead.
+	PlayPal(PaletteSet<'static>, usize, bool),
 	Text(String),
 	Unsupported,
 }
@@ -151,13 +152,14 @@ fn ui_inspect_picture(ed: &mut Editor, ui: &mut egui::Ui, param: SysParam) {
 }
 
 fn ui_inspect_playpal(ed: &mut Editor, ui: &mut egui::Ui, mut param: SysParam) {
-	let Inspected::PlayPal(palset, index) = &mut ed.inspectors[ed.cur_inspector.unwrap()].inspected
+	let Inspected::PlayPal(palset, index, show_all) =
+		&mut ed.inspectors[ed.cur_inspector.unwrap()].inspected
 	else {
 		unreachable!()
 	};
 
 	menu_bar(&mut param, ui, |ui| {
-		ui.add_enabled_ui(*index > 0, |ui| {
+		ui.add_enabled_ui(!*show_all && *index > 0, |ui| {
 			if ui
 				.button("\u{23EE}")
 				.on_hover_text("First Palette")
@@ -175,9 +177,13 @@ fn ui_inspect_playpal(ed: &mut Editor, ui: &mut egui::Ui, mut param: SysParam) {
 			}
 		});
 
-		ui.label(&format!("{}/14", *index + 1));
+		if *show_all {
+			ui.label("All");
+		} else {
+			ui.label(&format!("{}/14", *index + 1));
+		}
 
-		ui.add_enabled_ui(*index < 13, |ui| {
+		ui.add_enabled_ui(!*show_all && *index < 13, |ui| {
 			if ui
 				.button("\u{27A1}")
 				.on_hover_text("Next Palette")
@@ -194,35 +200,60 @@ fn ui_inspect_playpal(ed: &mut Editor, ui: &mut egui::Ui, mut param: SysParam) {
 				*index = 13;
 			}
 		});
-	});
 
-	ui.horizontal_wrapped(|ui| {
-		for (i, color) in palset[*index].0.iter().enumerate() {
-			let (rect, _resp) =
-				ui.allocate_at_least(egui::Vec2::new(32.0, 32.0), egui::Sense::hover());
+		ui.toggle_value(show_all, "Show All")
+			.on_hover_text("Show all 14 palettes at once");
 
-			ui.painter().rect(
-				rect,
-				1.0,
-				egui::Color32::from_rgb(color.r, color.g, color.b),
-				egui::Stroke::new(0.0, egui::Color32::TRANSPARENT),
-			);
+		if ui
+			.button("Copy as hex")
+			.on_hover_text("Copy every entry of every palette as `#RRGGBB`, one per line")
+			.clicked()
+		{
+			let mut hex = String::with_capacity(14 * 256 * 8);
 
-			if ui
-				.ctx()
-				.pointer_hover_pos()
-				.is_some_and(|p| rect.contains(p))
-			{
-				egui::show_tooltip_at_pointer(
-					ui.ctx(),
-					egui::Id::new("viletech_ed_colormap_tt"),
-					|ui| {
-						ui.label(&format!("{i}: R {}, G {}, B {}", color.r, color.g, color.b));
-					},
-				);
+			for p in 0..14 {
+				for color in palset[p].0.iter() {
+					hex.push_str(&format!("#{:02X}{:02X}{:02X}\n", color.r, color.g, color.b));
+				}
 			}
+
+			hex.pop();
+			ui.output_mut(|output| output.copied_text = hex);
 		}
 	});
+
+	if !*show_all {
+		ui.horizontal_wrapped(|ui| {
+			for (i, color) in palset[*index].0.iter().enumerate() {
+				palette_swatch(ui, egui::Vec2::new(32.0, 32.0), i, color);
+			}
+		});
+
+		return;
+	}
+
+	egui::ScrollArea::vertical().show(ui, |ui| {
+		egui::Grid::new("viletech_ed_playpal_grid")
+			.spacing(egui::Vec2::new(2.0, 2.0))
+			.show(ui, |ui| {
+				for p in 0..14 {
+					// Each row holds 16 entries; each palette takes up 16 rows.
+					for (r, row) in palset[p].0.chunks(16).enumerate() {
+						if r == 0 {
+							ui.label(&format!("{}", p + 1));
+						} else {
+							ui.label("");
+						}
+
+						for (c, color) in row.iter().enumerate() {
+							palette_swatch(ui, egui::Vec2::new(16.0, 16.0), r * 16 + c, color);
+						}
+
+						ui.end_row();
+					}
+				}
+			});
+	});
 }
 
 fn ui_inspect_text(ed: &mut Editor, ui: &mut egui::Ui, _: SysParam) {
@@ -349,7 +380,7 @@ pub(super) fn open(
 				}
 			};
 
-			Inspected::PlayPal(PaletteSet::Owned(Box::new(palset.into_owned())), 0)
+			Inspected::PlayPal(PaletteSet::Owned(Box::new(palset.into_owned())), 0, false)
 		}
 		ContentId::Marker => Inspected::Marker,
 		_ => Inspected::Unsupported,
@@ -381,7 +412,7 @@ pub(super) fn close(ed: &mut Editor, egui: &mut EguiContexts, _: &mut SysParam,
 		}
 		Inspected::Marker
 		| Inspected::Colormap(_)
-		| Inspected::PlayPal(_, _)
+		| Inspected::PlayPal(_, _, _)
 		| Inspected::Text(_)
 		| Inspected::Unsupported => {}
 	}
@@ -389,6 +420,31 @@ pub(super) fn close(ed: &mut Editor, egui: &mut EguiContexts, _: &mut SysParam,
 
 // Details /////////////////////////////////////////////////////////////////////
 
+/// Draws one color of a palette and shows its index and components on hover.
+fn palette_swatch(ui: &mut egui::Ui, size: egui::Vec2, index: usize, color: &Rgb8) {
+	let (rect, _resp) = ui.allocate_at_least(size, egui::Sense::hover());
+
+	ui.painter().rect(
+		rect,
+		1.0,
+		egui::Color32::from_rgb(color.r, color.g, color.b),
+		egui::Stroke::new(0.0, egui::Color32::TRANSPARENT),
+	);
+
+	if ui
+		.ctx()
+		.pointer_hover_pos()
+		.is_some_and(|p| rect.contains(p))
+	{
+		egui::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("viletech_ed_colormap_tt"), |ui| {
+			ui.label(&format!(
+				"{index}: R {}, G {}, B {} (#{:02X}{:02X}{:02X})",
+				color.r, color.g, color.b, color.r, color.g, color.b
+			));
+		});
+	}
+}
+
 fn menu_bar<F, R>(
 	_: &mut SysParam,
 	ui: &mut egui::Ui,
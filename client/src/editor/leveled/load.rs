@@ -6,7 +6,7 @@ use bevy::{
 };
 use viletech::{
 	gfx::{self, ImageSlot, TerrainMaterial},
-	level::{read::prelude::*, RawLevel, RawThings},
+	level::{read::prelude::*, RawLevel, RawLineDefs, RawThings},
 	rayon::prelude::*,
 	types::FxDashMap,
 	util::{string::ZString, SmallString},
@@ -223,7 +223,7 @@ pub(crate) fn load(ed: &mut Editor, mut param: SysParam, marker_slot: FileSlot)
 	);
 
 	let raw = RawLevel {
-		linedefs,
+		linedefs: RawLineDefs::Doom(linedefs),
 		nodes: nodedefs,
 		sectors: sectordefs,
 		segs: segdefs,
@@ -260,7 +260,7 @@ pub(crate) fn load(ed: &mut Editor, mut param: SysParam, marker_slot: FileSlot)
 	viletech::world::mesh::triangulate(raw, |ss_poly| {
 		let subsect = &raw.subsectors[ss_poly.subsector];
 		let seg = &raw.segs[subsect.first_seg() as usize];
-		let line = &raw.linedefs[seg.linedef() as usize];
+		let line = &linedefs[seg.linedef() as usize];
 
 		let side = match seg.direction() {
 			SegDirection::Front => &raw.sidedefs[line.right_side() as usize],
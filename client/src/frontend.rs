@@ -1,8 +1,9 @@
 //! Functions run when entering, updating, and leaving [`AppState::Frontend`].
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use bevy::{app::AppExit, prelude::*};
+use bevy_egui::egui;
 use viletech::{
 	frontend::{FrontendMenu, LoadOrderEntryKind, Outcome},
 	user::UserCore,
@@ -20,7 +21,13 @@ pub(crate) fn update(
 	mut frontend: ResMut<FrontendMenu>,
 	user: ResMut<UserCore>,
 	mut exit: EventWriter<AppExit>,
+	mut iwad_prompt_dismissed: Local<bool>,
 ) {
+	if !*iwad_prompt_dismissed && frontend.iwads().known().is_empty() {
+		*iwad_prompt_dismissed = iwad_prompt(core.egui.ctx_mut(), &mut frontend);
+		return;
+	}
+
 	let action = frontend.ui(core.egui.ctx_mut());
 
 	match action {
@@ -28,6 +35,7 @@ pub(crate) fn update(
 		Outcome::StartGame => {
 			if validate_load_order(&frontend) {
 				let to_mount = frontend.to_mount();
+				note_load_order(&to_mount);
 				let to_mount = to_mount.into_iter().map(|p| p.to_path_buf()).collect();
 
 				cmds.insert_resource(
@@ -42,6 +50,7 @@ pub(crate) fn update(
 		Outcome::StartEditor => {
 			if validate_load_order(&frontend) {
 				let to_mount = frontend.to_mount();
+				note_load_order(&to_mount);
 
 				for path in to_mount {
 					let fname = path.file_name().unwrap(/* TODO */);
@@ -65,12 +74,15 @@ pub(crate) fn update(
 
 pub(crate) fn on_enter(mut cmds: Commands, user: ResMut<UserCore>) {
 	let globalcfg = user.globalcfg();
+	let mut iwads = globalcfg.iwads.clone();
+	iwads.rescan();
 
 	cmds.insert_resource(FrontendMenu::new(
 		Some((
 			globalcfg.load_order_presets.clone(),
 			globalcfg.cur_load_order_preset,
 		)),
+		iwads,
 		globalcfg.dev_mode,
 	));
 }
@@ -88,6 +100,7 @@ pub(crate) fn on_exit(
 	let (loadord_presets, cur_preset) = frontend.consume();
 	globalcfg.load_order_presets = loadord_presets;
 	globalcfg.cur_load_order_preset = cur_preset;
+	globalcfg.iwads = frontend.iwads().clone();
 
 	if let Err(err) = user.write_global_cfg() {
 		error!(
@@ -99,6 +112,66 @@ pub(crate) fn on_exit(
 
 // Details /////////////////////////////////////////////////////////////////////
 
+/// Shown in place of the frontend for as long as no IWADs are known,
+/// unless the user chooses to skip it. Returns `true` if it should be hidden.
+#[must_use]
+fn iwad_prompt(ctx: &mut egui::Context, frontend: &mut FrontendMenu) -> bool {
+	// TODO: Localize these strings.
+
+	let mut ret = false;
+
+	egui::Window::new("No IWADs Found")
+		.id("viletech_iwad_prompt".into())
+		.collapsible(false)
+		.show(ctx, |ui| {
+			ui.label(
+				"An IWAD (e.g. DOOM2.WAD or freedoom1.wad) is needed to play. \
+				None were found in any of the following places:",
+			);
+
+			for dir in &frontend.iwads().search_dirs {
+				ui.code(dir.to_string_lossy().as_ref());
+			}
+
+			ui.separator();
+
+			ui.horizontal(|ui| {
+				if ui.button("Add a Directory...").clicked() {
+					if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+						let iwads = frontend.iwads_mut();
+						iwads.search_dirs.push(dir);
+						iwads.rescan();
+
+						if let Some(first) = iwads.known().first() {
+							let path = first.path.clone();
+							frontend.set_base_iwad(Some(path));
+						} else {
+							info!("No IWADs were found in the chosen directory.");
+						}
+					}
+				}
+
+				if ui.button("Browse to a File...").clicked() {
+					if let Some(file) = rfd::FileDialog::new()
+						.add_filter("WAD", &["wad", "WAD", "iwad", "IWAD"])
+						.pick_file()
+					{
+						match frontend.iwads_mut().add_file(&file) {
+							Ok(_) => frontend.set_base_iwad(Some(file)),
+							Err(err) => info!("{err}"),
+						}
+					}
+				}
+
+				if ui.button("Skip").clicked() {
+					ret = true;
+				}
+			});
+		});
+
+	ret
+}
+
 #[must_use]
 fn validate_load_order(frontend: &FrontendMenu) -> bool {
 	let mut all_valid = true;
@@ -124,6 +197,33 @@ fn validate_load_order(frontend: &FrontendMenu) -> bool {
 	all_valid
 }
 
+/// Lists `to_mount` in crash reports, fingerprinting each file by its size and
+/// modification time so a report can tell if a file changed since the crash.
+fn note_load_order(to_mount: &[&Path]) {
+	let lines: Vec<_> = to_mount
+		.iter()
+		.map(|path| {
+			let Ok(meta) = path.metadata() else {
+				return format!("{} (unreadable)", path.display());
+			};
+
+			let mtime = meta
+				.modified()
+				.ok()
+				.and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+				.map_or(0, |d| d.as_secs());
+
+			format!(
+				"{} ({} bytes, modified {mtime})",
+				path.display(),
+				meta.len()
+			)
+		})
+		.collect();
+
+	viletech::crash::set_note("Load Order", lines.join("\n"));
+}
+
 fn start_load(_: &ClientCommon, _: Vec<PathBuf>, _: bool) -> Result<GameLoad, String> {
 	unimplemented!("new game loading scheme under development")
 
@@ -133,6 +233,7 @@ fn start_load(_: &ClientCommon, _: Vec<PathBuf>, _: bool) -> Result<GameLoad, St
 	let catalog = core.catalog();
 	let tracker_m = Arc::new(SendTracker::default());
 	let tracker_p = Arc::new(SendTracker::default());
+	let early = Arc::new(EarlyAssets::default());
 
 	to_mount.dedup();
 
@@ -176,6 +277,7 @@ fn start_load(_: &ClientCommon, _: Vec<PathBuf>, _: bool) -> Result<GameLoad, St
 	let load_order_sent = load_order.clone();
 	let tracker_m_sent = tracker_m.clone();
 	let tracker_p_sent = tracker_p.clone();
+	let early_sent = early.clone();
 
 	let thread = std::thread::spawn(move || {
 		let request = LoadRequest {
@@ -195,6 +297,8 @@ fn start_load(_: &ClientCommon, _: Vec<PathBuf>, _: bool) -> Result<GameLoad, St
 		thread: Some(thread),
 		tracker_m,
 		tracker_p,
+		early,
+		splash: None,
 		start_time,
 		load_order,
 	})
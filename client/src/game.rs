@@ -2,15 +2,33 @@
 
 use bevy::prelude::*;
 use bevy_egui::egui;
+use viletech::{
+	player::predict::Predictor,
+	sim::{
+		interp,
+		movement::{PawnState, TicCmd, ANGLE_TURN, FORWARD_MOVE, SIDE_MOVE},
+	},
+};
 
 use crate::{common::ClientCommon, AppState};
 
+/// How long it takes for half of a misprediction's correction offset to be
+/// smoothed away, in seconds.
+const ERROR_HALF_LIFE: f32 = 1.0 / 35.0;
+
 pub(crate) fn update(
 	mut core: ClientCommon,
 	mut _next_state: ResMut<NextState<AppState>>,
-	mut cameras: Query<&Transform, With<Camera>>,
+	time: Res<Time>,
+	mut cameras: Query<&mut Transform, With<Camera>>,
+	mut predictor: ResMut<Predictor>,
 ) {
-	let camera = cameras.get_single_mut().unwrap();
+	let mut camera = cameras.get_single_mut().unwrap();
+
+	predictor.decay(0.5_f32.powf(time.delta_seconds() / ERROR_HALF_LIFE));
+	let visual = predictor.visual();
+	camera.translation = interp::doom_to_bevy(visual.pos);
+	camera.rotation = interp::yaw_to_bevy(visual.yaw);
 
 	egui::Window::new("")
 		.id("viletech_devoverlay_pos".into())
@@ -18,15 +36,71 @@ pub(crate) fn update(
 		.show(core.egui.ctx_mut(), |ui| {
 			ui.label(format!(
 				"{} {} {}",
-				camera.translation.x, camera.translation.y, camera.translation.z
+				visual.pos.x, visual.pos.y, visual.pos.z
 			));
 		});
 }
 
-pub(crate) fn on_enter() {
+/// Run on the fixed-time update loop, once per tick.
+pub(crate) fn tick(keys: Res<Input<KeyCode>>, mut predictor: ResMut<Predictor>) {
+	// Local input is applied immediately; when a server connection exists,
+	// `Predictor::unacknowledged` gets sent to it and its authoritative state
+	// gets passed to `Predictor::reconcile`.
+	let _ = predictor.predict(local_cmd(&keys));
+}
+
+pub(crate) fn on_enter(mut cmds: Commands, cameras: Query<&Transform, With<Camera>>) {
 	// TODO: add `Sim` resource.
+	let (pos, yaw) = cameras
+		.get_single()
+		.map(|t| {
+			(
+				interp::bevy_to_doom(t.translation),
+				interp::yaw_from_bevy(t.rotation),
+			)
+		})
+		.unwrap_or_default();
+
+	cmds.insert_resource(Predictor::new(PawnState {
+		pos,
+		yaw,
+		..Default::default()
+	}));
 }
 
-pub(crate) fn on_exit(_: Commands) {
+pub(crate) fn on_exit(mut cmds: Commands) {
 	// TODO: remove `Sim` resource.
+	cmds.remove_resource::<Predictor>();
+}
+
+#[must_use]
+fn local_cmd(keys: &Input<KeyCode>) -> TicCmd {
+	let speed = usize::from(keys.pressed(KeyCode::ShiftLeft));
+	let mut cmd = TicCmd::default();
+
+	if keys.pressed(KeyCode::W) {
+		cmd.forward += FORWARD_MOVE[speed];
+	}
+
+	if keys.pressed(KeyCode::S) {
+		cmd.forward -= FORWARD_MOVE[speed];
+	}
+
+	if keys.pressed(KeyCode::D) {
+		cmd.side += SIDE_MOVE[speed];
+	}
+
+	if keys.pressed(KeyCode::A) {
+		cmd.side -= SIDE_MOVE[speed];
+	}
+
+	if keys.pressed(KeyCode::Left) {
+		cmd.turn += ANGLE_TURN[speed];
+	}
+
+	if keys.pressed(KeyCode::Right) {
+		cmd.turn -= ANGLE_TURN[speed];
+	}
+
+	cmd
 }
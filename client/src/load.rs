@@ -4,7 +4,13 @@ use std::{path::PathBuf, sync::Arc, thread::JoinHandle, time::Instant};
 
 use bevy::prelude::*;
 use bevy_egui::egui;
-use viletech::util::SendTracker;
+use viletech::{
+	load::{EarlyAssets, Splash},
+	script::{self, EventBridge},
+	util::{duration_to_hhmmss, SendTracker},
+	vfs::{self, VPath},
+	VirtualFs,
+};
 
 use crate::{common::ClientCommon, AppState};
 
@@ -18,28 +24,87 @@ pub(crate) struct GameLoad {
 	pub(crate) tracker_m: Arc<SendTracker>,
 	/// How far along the load prep process is `thread`?
 	pub(crate) tracker_p: Arc<SendTracker>,
+	/// The mount thread publishes mounts' title screens here as it finds them.
+	pub(crate) early: Arc<EarlyAssets>,
+	/// What is currently being drawn behind the progress window. Its RGBA buffer
+	/// gets moved into the texture, so only its dimensions remain.
+	pub(crate) splash: Option<(egui::TextureHandle, Splash)>,
 	/// Print to the log how long the mount takes for diagnostic purposes.
-	pub(crate) _start_time: Instant,
+	/// Also used for estimating how much longer the load will take.
+	pub(crate) start_time: Instant,
 	pub(crate) _load_order: Vec<(PathBuf, PathBuf)>,
 }
 
 pub(crate) fn update(
 	mut core: ClientCommon,
-	loader: ResMut<GameLoad>,
+	mut loader: ResMut<GameLoad>,
 	_: ResMut<NextState<AppState>>,
+	mut backdrop_tried: Local<bool>,
 ) {
 	// TODO: Localize these strings.
 
+	let new_splash = loader.early.take().or_else(|| {
+		if loader.splash.is_some() || *backdrop_tried {
+			return None;
+		}
+
+		// Nothing from the mount thread yet; fall back to the engine's own.
+		*backdrop_tried = true;
+		backdrop(&core.vfs)
+	});
+
+	if let Some(mut splash) = new_splash {
+		let rgba = std::mem::take(&mut splash.rgba);
+
+		let image = egui::ColorImage::from_rgba_unmultiplied(
+			[splash.width as usize, splash.height as usize],
+			&rgba,
+		);
+
+		let texture = core.egui.ctx_mut().load_texture(
+			"viletech_gameload_splash",
+			image,
+			egui::TextureOptions::NEAREST,
+		);
+
+		loader.splash = Some((texture, splash));
+	}
+
+	if let Some((texture, splash)) = &loader.splash {
+		let ctx = core.egui.ctx_mut();
+		let screen = ctx.screen_rect();
+		let scale = splash.scale_to_fit([screen.width(), screen.height()]);
+		let size = egui::vec2(splash.width as f32 * scale, splash.height as f32 * scale);
+		let painter = ctx.layer_painter(egui::LayerId::background());
+
+		painter.rect_filled(screen, 0.0, egui::Color32::BLACK);
+
+		painter.image(
+			texture.id(),
+			egui::Rect::from_center_size(screen.center(), size),
+			egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+			egui::Color32::WHITE,
+		);
+	}
+
 	let m_pct = loader.tracker_m.progress_percent() * 100.0;
 	let p_pct = loader.tracker_p.progress_percent() * 100.0;
+	let eta = estimate_remaining(loader.start_time, (m_pct + p_pct) / 200.0);
 	let mut cancelled = false;
 
 	egui::Window::new("Loading...")
 		.id(egui::Id::new("viletech_gameload"))
+		.anchor(egui::Align2::CENTER_BOTTOM, [0.0, -16.0])
+		.collapsible(false)
+		.resizable(false)
 		.show(core.egui.ctx_mut(), |ui| {
 			ui.label(&format!("File Mounting: {m_pct:.1}%"));
 			ui.label(&format!("Preparing: {p_pct:.1}%"));
 
+			if let Some((hh, mm, ss)) = eta {
+				ui.label(&format!("Time remaining: {hh:02}:{mm:02}:{ss:02}"));
+			}
+
 			if ui.button("Cancel").clicked() {
 				cancelled = true;
 			}
@@ -111,6 +176,33 @@ pub(crate) fn update(
 	} */
 }
 
+/// Shown while loading until a mount's splash is available.
+#[must_use]
+fn backdrop(vfs: &VirtualFs) -> Option<Splash> {
+	let vfs::Ref::File(fref) = vfs.lookup(VPath::new("/viletech/viletech.png"))? else {
+		return None;
+	};
+
+	let mut guard = fref.lock();
+	let bytes = guard.read().ok()?;
+	Splash::decode(0, &bytes, None)
+}
+
+/// Extrapolates from how long the load has taken so far.
+/// `progress` is in the range `[0.0, 1.0]`.
+#[must_use]
+fn estimate_remaining(start_time: Instant, progress: f64) -> Option<(u64, u64, u64)> {
+	if progress <= 0.0 || progress >= 1.0 {
+		return None;
+	}
+
+	let elapsed = start_time.elapsed().as_secs_f64();
+	let remaining = elapsed * ((1.0 - progress) / progress);
+	Some(duration_to_hhmmss(std::time::Duration::from_secs_f64(
+		remaining,
+	)))
+}
+
 /*
 
 #[must_use]
@@ -145,6 +237,12 @@ fn error_message(real_path: &Path, mount: &[MountError], prep: &[PrepError]) ->
 
 */
 
-pub(crate) fn on_exit(mut cmds: Commands) {
+pub(crate) fn on_exit(mut cmds: Commands, loader: Res<GameLoad>, mut bridge: ResMut<EventBridge>) {
+	if !loader.tracker_m.is_cancelled() && !loader.tracker_p.is_cancelled() {
+		bridge.push(script::Event::LoadComplete {
+			mounts: loader._load_order.len(),
+		});
+	}
+
 	cmds.remove_resource::<GameLoad>();
 }
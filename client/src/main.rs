@@ -11,16 +11,20 @@ mod load;
 mod playground;
 mod setup;
 
-use std::time::{Duration, Instant};
+use std::{
+	path::Path,
+	time::{Duration, Instant},
+};
 
 use bevy::{
 	diagnostic::LogDiagnosticsPlugin, ecs::schedule::Condition, input::InputSystem,
-	pbr::wireframe::WireframePlugin, prelude::*,
+	pbr::wireframe::WireframePlugin, prelude::*, render::renderer::RenderAdapterInfo,
 };
 use bevy_egui::EguiPlugin;
 use clap::Parser;
 use viletech::{
-	audio::AudioCore,
+	audio::{AudioCore, AudioPlugin},
+	bench::BenchSpec,
 	crossbeam,
 	tracing::info,
 	user::UserCore,
@@ -43,8 +47,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 		return Ok(());
 	}
 
+	viletech::crash::install(viletech::crash::default_dir(), crash_dialog);
 	viletech::thread_pool_init(args.threads);
 
+	if let Some(spec) = &args.bench {
+		return bench(spec);
+	}
+
 	let mut app = App::new();
 
 	// Common //////////////////////////////////////////////////////////////////
@@ -74,7 +83,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 		.add_plugins((WireframePlugin, viletech::gfx::GraphicsPlugin, EguiPlugin));
 
 	app.add_event::<editor::Event>()
-		.add_event::<common::NewWindow>();
+		.add_event::<common::NewWindow>()
+		.add_event::<viletech::gfx::hud::HudMessage>()
+		.init_resource::<viletech::gfx::hud::HudMessages>();
 
 	let user_dir_portable = viletech::user::user_dir_portable();
 	let user_dir_home = viletech::user::user_dir_home();
@@ -92,23 +103,42 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 		UserCore::uninit()
 	};
 
-	app.insert_resource(dgui::State {
-		vfs_selection: vfs::Slot::Folder(vfs_root_slot),
-	});
-
+	app.insert_resource(dgui::devgui(vfs_root_slot, &user)?);
 	app.insert_resource(user);
 	info!("User info initialized.");
 	app.insert_resource(setup::console(log_receiver));
 	info!("Developer console initialized.");
+
+	if !args.no_autoexec && !app.world.contains_resource::<FirstStartup>() {
+		ccmd::autoexec(&mut app.world);
+	}
+
 	app.insert_resource(AudioCore::new(None)?);
+	app.add_plugins(AudioPlugin);
 	info!("Audio manager initialized.");
 	app.insert_resource(Playground::default());
 	info!("Lithica scripting playground initialized.");
+	app.insert_resource(viletech::script::EventBridge::default());
+	info!("Script event bridge initialized.");
 
-	app.add_systems(Startup, dgui::on_app_startup)
+	app.add_systems(Startup, (dgui::on_app_startup, crash_note_adapter))
+		.add_systems(Update, crash_note_state.run_if(state_changed::<AppState>()))
 		.add_systems(Update, common::update)
-		.add_systems(PreUpdate, common::pre_update.in_set(InputSystem))
+		.add_systems(Update, viletech::script::pump.after(common::update))
+		.add_systems(PreUpdate, common::pre_update.after(InputSystem))
 		.add_systems(PostUpdate, common::post_update)
+		.add_systems(
+			Last,
+			dgui::save_layout.run_if(
+				not(in_state(AppState::Init)).and_then(not(in_state(AppState::FirstStartup))),
+			),
+		)
+		.add_systems(
+			PostUpdate,
+			common::request_screenshot.run_if(
+				not(in_state(AppState::Init)).and_then(not(in_state(AppState::FirstStartup))),
+			),
+		)
 		.add_systems(OnEnter(AppState::Init), first::init_on_enter)
 		.add_systems(
 			Update,
@@ -119,6 +149,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 				.after(frontend::update)
 				.after(game::update)
 				.after(editor::update),
+		)
+		.add_systems(
+			Update,
+			dgui::draw_console_overlay
+				.run_if(
+					not(in_state(AppState::Init)).and_then(not(in_state(AppState::FirstStartup))),
+				)
+				.after(dgui::draw),
 		);
 
 	// First-time startup //////////////////////////////////////////////////////
@@ -148,6 +186,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 		1.0 / 35.0,
 	)));
 	app.add_systems(Update, game::update.run_if(in_state(AppState::Game)));
+	app.add_systems(FixedUpdate, game::tick.run_if(in_state(AppState::Game)));
+
+	app.init_resource::<viletech::sim::interp::Interpolation>();
+	app.add_systems(
+		FixedUpdate,
+		viletech::sim::interp::snapshot.run_if(in_state(AppState::Game)),
+	);
+	app.add_systems(
+		PostUpdate,
+		viletech::sim::interp::interpolate.run_if(in_state(AppState::Game)),
+	);
 
 	/*
 	app.add_systems(
@@ -171,6 +220,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 	app.add_systems(OnEnter(AppState::Editor), editor::on_enter);
 	app.add_systems(OnExit(AppState::Editor), editor::on_exit);
 
+	// Netplay /////////////////////////////////////////////////////////////////
+
+	if let Some(conn) = &args.connect {
+		// TODO: actually connect once the client has a network transport.
+		info!("Server to connect to: {}:{}", conn.host, conn.port);
+	}
+
 	// Run /////////////////////////////////////////////////////////////////////
 
 	viletech::log::init_diag(&version_string())?;
@@ -216,6 +272,56 @@ impl Drop for AppUptime {
 	}
 }
 
+/// For the `--bench` launch argument.
+fn bench(spec: &BenchSpec) -> Result<(), Box<dyn std::error::Error>> {
+	let report = viletech::bench::run_headless(spec)?;
+	println!("{report}");
+
+	let user_dir_portable = viletech::user::user_dir_portable();
+	let user_dir_home = viletech::user::user_dir_home();
+
+	let dir = match viletech::user::select_user_dir(&user_dir_portable, &user_dir_home) {
+		Some(udir) => udir.join("bench"),
+		None => std::env::current_dir()?,
+	};
+
+	let path = report.write_json(&dir)?;
+	println!("Report written to: {}", path.display());
+	Ok(())
+}
+
+// Crash reporting ///////////////////////////////////////////////////////////
+
+fn crash_dialog(path: &Path) {
+	rfd::MessageDialog::new()
+		.set_level(rfd::MessageLevel::Error)
+		.set_title("VileTech Client has crashed")
+		.set_description(&format!(
+			"A crash report has been written to:\n{}",
+			path.display()
+		))
+		.set_buttons(rfd::MessageButtons::Ok)
+		.show();
+}
+
+fn crash_note_adapter(adapter: Option<Res<RenderAdapterInfo>>) {
+	let Some(adapter) = adapter else {
+		return;
+	};
+
+	viletech::crash::set_note(
+		"GPU Adapter",
+		format!(
+			"{} ({:?}, {:?})\nDriver: {} {}",
+			adapter.name, adapter.device_type, adapter.backend, adapter.driver, adapter.driver_info
+		),
+	);
+}
+
+fn crash_note_state(state: Res<State<AppState>>) {
+	viletech::crash::set_note("App State", format!("{:?}", state.get()));
+}
+
 #[must_use]
 fn version_string() -> String {
 	format!("VileTech Client {}", env!("CARGO_PKG_VERSION"))
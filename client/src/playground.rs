@@ -114,6 +114,8 @@ impl Playground {
 				lith::OptLevel::SpeedAndSize
 			},
 			hotswap: false,
+			budget_checks: true,
+			enable_verifier: None,
 		});
 
 		let result = compiler.register_lib(
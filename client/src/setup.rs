@@ -14,9 +14,11 @@ use bevy::{
 	winit::{UpdateMode, WinitSettings},
 };
 use viletech::{
+	bench::BenchSpec,
 	console::{self, Console},
 	crossbeam::channel::{Receiver, Sender},
 	log::TracingPlugin,
+	net::ConnectionParams,
 };
 
 use crate::ccmd;
@@ -50,6 +52,22 @@ pub(crate) struct LaunchArgs {
 	/// Possible values: ERROR, WARN, INFO, DEBUG, or TRACE.
 	#[arg(short, long, default_value_t = viletech::log::Level::INFO)]
 	pub(crate) verbosity: viletech::log::Level,
+	/// Connects to a server upon startup.
+	///
+	/// Expects a URI of the form `viletech://host[:port][/password]`.
+	/// If no port is given, 6666 is used.
+	#[arg(long, value_parser = viletech::net::parse_connection_string)]
+	pub(crate) connect: Option<ConnectionParams>,
+	/// Runs a benchmark without opening a window, then exits.
+	///
+	/// Expects `;`-separated `key=value` pairs, such as
+	/// `load=doom2.wad,mymod.pk3;ticks=350;seed=1`.
+	/// Either `ticks` or `demo` is required.
+	#[arg(long)]
+	pub(crate) bench: Option<BenchSpec>,
+	/// Skips running `autoexec.cfg` from the user directory at startup.
+	#[arg(long)]
+	pub(crate) no_autoexec: bool,
 }
 
 #[must_use]
@@ -125,6 +143,14 @@ pub(crate) fn console(receiver: Receiver<console::Message>) -> Console<ccmd::Com
 		true,
 	);
 
+	console.register_command(
+		"bench",
+		ccmd::Command {
+			func: ccmd::ccmd_bench,
+		},
+		true,
+	);
+
 	console.register_command(
 		"clear",
 		ccmd::Command {
@@ -133,6 +159,14 @@ pub(crate) fn console(receiver: Receiver<console::Message>) -> Console<ccmd::Com
 		true,
 	);
 
+	console.register_command(
+		"exec",
+		ccmd::Command {
+			func: ccmd::ccmd_exec,
+		},
+		true,
+	);
+
 	console.register_command(
 		"exit",
 		ccmd::Command {
@@ -157,6 +191,14 @@ pub(crate) fn console(receiver: Receiver<console::Message>) -> Console<ccmd::Com
 		true,
 	);
 
+	console.register_command(
+		"interp",
+		ccmd::Command {
+			func: ccmd::ccmd_interp,
+		},
+		true,
+	);
+
 	console.register_command(
 		"version",
 		ccmd::Command {
@@ -175,5 +217,8 @@ pub(crate) fn console(receiver: Receiver<console::Message>) -> Console<ccmd::Com
 
 	console.register_alias("quit".to_string(), "exit".to_string());
 
+	// TODO: cvar get/set, `vfs.*`, `demo.*`, and cheats gated behind developer
+	// flags. The engine does not expose any of these as command sets yet.
+
 	console
 }
@@ -0,0 +1,7 @@
+#![no_main]
+
+libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+	if let Ok(source) = std::str::from_utf8(data) {
+		doomfront::testing::fuzz_cvarinfo_parse(source);
+	}
+});
@@ -0,0 +1,206 @@
+//! See [`ParseCache`] and [`parse_cached`].
+
+use std::{
+	hash::{Hash, Hasher},
+	sync::Arc,
+};
+
+use rustc_hash::{FxHashMap, FxHasher};
+
+use crate::{parser, LangExt, ParseTree};
+
+/// A bounded store of [parse trees](ParseTree) keyed on a hash of their source,
+/// so that unchanged inputs (e.g. the same file `#include`d across a project)
+/// only get parsed once.
+///
+/// Each cache is tied to one parsing function and lexer context, since the same
+/// source would produce a different tree given either of those. Tokens of all
+/// trees produced through one cache are interned via a shared [`rowan::NodeCache`].
+///
+/// When full, the least-recently-used tree is evicted.
+pub struct ParseCache<L: LangExt, E> {
+	function: fn(&mut parser::Parser<L>),
+	lexer_ctx: E,
+	capacity: usize,
+	entries: FxHashMap<u64, Entry<L>>,
+	nodes: rowan::NodeCache,
+	/// Incremented upon every lookup; used to find the least-recently-used entry.
+	clock: u64,
+	hits: usize,
+	misses: usize,
+}
+
+struct Entry<L: LangExt> {
+	tree: Arc<ParseTree<L>>,
+	last_used: u64,
+}
+
+impl<L, E> ParseCache<L, E>
+where
+	L: LangExt,
+	L::Token: for<'i> logos::Logos<'i, Source = str, Error = L::Token, Extras = E>,
+	E: Clone,
+{
+	/// Panics if `capacity` is 0.
+	#[must_use]
+	pub fn new(capacity: usize, function: fn(&mut parser::Parser<L>), lexer_ctx: E) -> Self {
+		assert!(capacity > 0, "a `ParseCache` needs a non-zero capacity");
+
+		Self {
+			function,
+			lexer_ctx,
+			capacity,
+			entries: FxHashMap::default(),
+			nodes: rowan::NodeCache::default(),
+			clock: 0,
+			hits: 0,
+			misses: 0,
+		}
+	}
+
+	/// How many trees are currently stored.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	#[must_use]
+	pub fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// How many calls to [`parse_cached`] have returned a stored tree.
+	#[must_use]
+	pub fn hits(&self) -> usize {
+		self.hits
+	}
+
+	/// How many calls to [`parse_cached`] have had to parse their source.
+	#[must_use]
+	pub fn misses(&self) -> usize {
+		self.misses
+	}
+
+	/// Drops all stored trees, but keeps interned tokens and hit/miss counts.
+	pub fn clear(&mut self) {
+		self.entries.clear();
+	}
+
+	fn evict_lru(&mut self) {
+		let Some(key) = self
+			.entries
+			.iter()
+			.min_by_key(|(_, entry)| entry.last_used)
+			.map(|(key, _)| *key)
+		else {
+			return;
+		};
+
+		let _ = self.entries.remove(&key);
+	}
+}
+
+impl<L: LangExt, E: std::fmt::Debug> std::fmt::Debug for ParseCache<L, E> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("ParseCache")
+			.field("lexer_ctx", &self.lexer_ctx)
+			.field("capacity", &self.capacity)
+			.field("len", &self.entries.len())
+			.field("hits", &self.hits)
+			.field("misses", &self.misses)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Like [`crate::parse`], but returns a shared tree from `cache` if `source`
+/// has been parsed through it before (and has not since been evicted).
+///
+/// Hash collisions are guarded against by comparing the text of the stored tree
+/// to `source` before returning it, which is still much cheaper than a re-parse.
+pub fn parse_cached<L, E>(source: &str, cache: &mut ParseCache<L, E>) -> Arc<ParseTree<L>>
+where
+	L: LangExt,
+	L::Token: for<'i> logos::Logos<'i, Source = str, Error = L::Token, Extras = E>,
+	E: Clone,
+{
+	let key = {
+		let mut hasher = FxHasher::default();
+		source.hash(&mut hasher);
+		hasher.finish()
+	};
+
+	cache.clock += 1;
+
+	if let Some(entry) = cache.entries.get_mut(&key) {
+		if entry.tree.cursor().text() == source {
+			entry.last_used = cache.clock;
+			cache.hits += 1;
+			return entry.tree.clone();
+		}
+	}
+
+	cache.misses += 1;
+
+	let tree = Arc::new(crate::parse_with_cache(
+		source,
+		cache.function,
+		cache.lexer_ctx.clone(),
+		&mut cache.nodes,
+	));
+
+	if cache.entries.len() >= cache.capacity && !cache.entries.contains_key(&key) {
+		cache.evict_lru();
+	}
+
+	cache.entries.insert(
+		key,
+		Entry {
+			tree: tree.clone(),
+			last_used: cache.clock,
+		},
+	);
+
+	tree
+}
+
+#[cfg(all(test, feature = "zdoom"))]
+mod test {
+	use crate::zdoom::{self, zscript};
+
+	use super::*;
+
+	#[test]
+	fn hit_and_evict() {
+		const SOURCE_A: &str = "const A = 1;";
+		const SOURCE_B: &str = "const B = 2;";
+		const SOURCE_C: &str = "const C = 3;";
+
+		let mut cache =
+			ParseCache::new(2, zscript::parse::file, zdoom::lex::Context::ZSCRIPT_LATEST);
+
+		let a0 = parse_cached(SOURCE_A, &mut cache);
+		assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+		let a1 = parse_cached(SOURCE_A, &mut cache);
+		assert_eq!((cache.hits(), cache.misses()), (1, 1));
+		assert!(Arc::ptr_eq(&a0, &a1));
+		assert!(!a1.any_errors());
+
+		// A was used more recently than B, so C evicts B.
+		let _ = parse_cached(SOURCE_B, &mut cache);
+		let _ = parse_cached(SOURCE_A, &mut cache);
+		let _ = parse_cached(SOURCE_C, &mut cache);
+		assert_eq!(cache.len(), 2);
+
+		let _ = parse_cached(SOURCE_A, &mut cache);
+		assert_eq!((cache.hits(), cache.misses()), (3, 3));
+
+		let _ = parse_cached(SOURCE_B, &mut cache);
+		assert_eq!((cache.hits(), cache.misses()), (3, 4));
+	}
+}
@@ -30,6 +30,7 @@
 pub extern crate logos;
 pub extern crate rowan;
 
+pub mod cache;
 pub mod formatting;
 pub mod parser;
 pub mod testing;
@@ -130,6 +131,15 @@ impl<L: LangExt> ParseTree<L> {
 	pub fn into_errors(self) -> Vec<ParseError<L>> {
 		self.errors
 	}
+
+	/// Bridges the [`rowan::TextRange`] of an AST node to a [`logos::Span`],
+	/// which is what [`ParseError`]s use, so that both can be reported alike.
+	/// The inverse of [`parser::Lexeme::text_range`].
+	#[must_use]
+	pub fn text_range_of<N: rowan::ast::AstNode<Language = L>>(node: &N) -> logos::Span {
+		let range = node.syntax().text_range();
+		(u32::from(range.start()) as usize)..(u32::from(range.end()) as usize)
+	}
 }
 
 impl<L: LangExt> std::fmt::Debug for ParseTree<L>
@@ -144,7 +154,7 @@ where
 	}
 }
 
-/// Also see [`parse_with_cache`].
+/// Also see [`parse_with_cache`] and [`cache::parse_cached`].
 #[must_use]
 pub fn parse<'i, L: LangExt>(
 	source: &'i str,
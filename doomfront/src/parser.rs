@@ -17,11 +17,16 @@ pub struct Parser<'i, L: LangExt> {
 	tokens: Vec<Lexeme<L>>,
 	pos: usize,
 	fuel: Cell<u32>,
+	depth: u16,
+	low_water: u16,
 	events: Vec<Event>,
 	errors: Vec<Error<L>>,
 }
 
 impl<'i, L: LangExt> Parser<'i, L> {
+	/// See [`Self::nest`].
+	pub const MAX_DEPTH: u16 = 256;
+
 	#[must_use]
 	pub fn new(
 		source: &'i str,
@@ -38,6 +43,8 @@ impl<'i, L: LangExt> Parser<'i, L> {
 				.collect(),
 			pos: 0,
 			fuel: Cell::new(256),
+			depth: 0,
+			low_water: 0,
 			events: vec![],
 			errors: vec![],
 		}
@@ -77,10 +84,24 @@ impl<'i, L: LangExt> Parser<'i, L> {
 	pub fn advance(&mut self, syn: L::Kind) {
 		assert!(!self.eof());
 		self.fuel.set(256);
+		self.low_water = self.depth;
 		self.events.push(Event::Advance(L::kind_to_raw(syn)));
 		self.pos += 1;
 	}
 
+	/// [Advances](Self::advance) the current token as `ident_syn`, even if it
+	/// was lexed as a keyword. For use in grammar positions which demand an
+	/// identifier but where a language reuses keywords contextually, such as a
+	/// field named `color`. Unlike [`Self::advance`], this is a no-op at the
+	/// end of input.
+	pub fn advance_as_ident(&mut self, ident_syn: L::Kind) {
+		if self.eof() {
+			return;
+		}
+
+		self.advance(ident_syn);
+	}
+
 	pub fn advance_n(&mut self, syn: L::Kind, tokens: u8) {
 		assert!(
 			tokens >= 1,
@@ -89,10 +110,57 @@ impl<'i, L: LangExt> Parser<'i, L> {
 			self.nth_slice(0)
 		);
 
+		// (RAT) Clamping here rather than asserting means that a grammar
+		// function which assumes more tokens than the input has left degrades
+		// into a truncated token instead of a panic in `Self::finish`.
+		let tokens = (self.tokens.len() - self.pos).min(tokens as usize);
+
+		if tokens == 0 {
+			return;
+		}
+
 		self.fuel.set(256);
+		self.low_water = self.depth;
 		self.events
-			.push(Event::AdvanceN(L::kind_to_raw(syn), tokens));
-		self.pos += tokens as usize;
+			.push(Event::AdvanceN(L::kind_to_raw(syn), tokens as u8));
+		self.pos += tokens;
+	}
+
+	/// Grammar functions for constructs which can nest arbitrarily deeply
+	/// (e.g. expressions and statements) should call this before recurring.
+	///
+	/// If this returns `false`, the input has nested past [`Self::MAX_DEPTH`];
+	/// an error has been raised, the current token (if any) has been consumed
+	/// with `syn`, and the caller should return without recurring. Otherwise,
+	/// [`Self::unnest`] must be called once the nested construct is done.
+	///
+	/// (RAT) Recursive descent on pathological input like ten thousand `(`
+	/// would otherwise overflow the stack, which cannot be caught.
+	#[must_use]
+	pub fn nest(&mut self, syn: L::Kind) -> bool {
+		if self.depth >= Self::MAX_DEPTH {
+			self.advance_with_error(syn, &[&["less deeply nested input"]]);
+			return false;
+		}
+
+		self.depth += 1;
+		true
+	}
+
+	/// See [`Self::nest`].
+	pub fn unnest(&mut self) {
+		debug_assert!(self.depth > 0, "`unnest` called without a matching `nest`");
+		self.depth = self.depth.saturating_sub(1);
+
+		// (RAT) Unwinding out of deeply-nested input at the end of input makes
+		// far more lookaheads than there is fuel, without advancing. Fuel is
+		// restored only upon reaching a depth lower than any since the last
+		// advance, so a loop which nests and unnests at the same depth without
+		// advancing still gets caught.
+		if self.depth < self.low_water {
+			self.low_water = self.depth;
+			self.fuel.set(256);
+		}
 	}
 
 	#[must_use]
@@ -103,10 +171,7 @@ impl<'i, L: LangExt> Parser<'i, L> {
 	#[must_use]
 	pub fn nth(&self, lookahead: usize) -> L::Token {
 		if self.fuel.get() == 0 {
-			panic!(
-				"parser is not advancing (stuck at {:?})",
-				self.tokens[self.pos].span
-			)
+			panic!("parser is not advancing (stuck at {:?})", self.nth_span(0))
 		}
 
 		self.fuel.set(self.fuel.get() - 1);
@@ -116,14 +181,20 @@ impl<'i, L: LangExt> Parser<'i, L> {
 			.map_or(L::EOF, |lexeme| lexeme.kind)
 	}
 
+	/// Returns an empty string if looking past the end of input.
 	#[must_use]
 	pub fn nth_slice(&self, lookahead: usize) -> &str {
-		&self.source[self.tokens[self.pos + lookahead].span.clone()]
+		&self.source[self.nth_span(lookahead)]
 	}
 
+	/// Returns an empty span at the end of the source if looking past the end of input.
 	#[must_use]
 	pub fn nth_span(&self, lookahead: usize) -> logos::Span {
-		self.tokens[self.pos + lookahead].span.clone()
+		self.tokens
+			.get(self.pos + lookahead)
+			.map_or(self.source.len()..self.source.len(), |lexeme| {
+				lexeme.span.clone()
+			})
 	}
 
 	/// Shorthand for `self.nth(0) == token`.
@@ -285,14 +356,15 @@ impl<'i, L: LangExt> Parser<'i, L> {
 	) {
 		let mut n = 0;
 
-		loop {
-			let token = self.nth(n);
-
-			if advance_if(token) {
-				n += 1;
-			} else {
-				break;
-			}
+		// (RAT) `Event::AdvanceN` can only hold `u8::MAX` tokens. The end of
+		// input is never merged, even if `advance_if` would accept it.
+		while n < (u8::MAX as usize)
+			&& self
+				.tokens
+				.get(self.pos + n)
+				.is_some_and(|lexeme| advance_if(lexeme.kind))
+		{
+			n += 1;
 		}
 
 		if n > 0 {
@@ -306,12 +378,11 @@ impl<'i, L: LangExt> Parser<'i, L> {
 	/// returns `true`. If `0` is passed, this starts at the current token.
 	#[must_use]
 	pub fn find(&self, offset: usize, predicate: fn(L::Token) -> bool) -> L::Token {
-		if self.pos >= self.tokens.len() {
+		let Some(rest) = self.tokens.get((self.pos + offset)..) else {
 			return L::EOF;
-		}
+		};
 
-		self.tokens[(self.pos + offset)..]
-			.iter()
+		rest.iter()
 			.find_map(|t| {
 				if predicate(t.kind) {
 					Some(t.kind)
@@ -501,7 +572,11 @@ pub struct OpenMark(usize);
 
 impl Drop for OpenMark {
 	fn drop(&mut self) {
-		panic!("an `OpenMark` was not consumed")
+		// (RAT) Panicking while already unwinding aborts the process, which
+		// would hide the original panic's message.
+		if !std::thread::panicking() {
+			panic!("an `OpenMark` was not consumed")
+		}
 	}
 }
 
@@ -194,6 +194,76 @@ pub fn read_sample_data(env_var_name: &'static str) -> Result<(PathBuf, String),
 	Ok((path, sample))
 }
 
+/// Checks the properties that any parse must uphold, regardless of how malformed
+/// `source` is. Intended for fuzzing and for replaying fuzzer-found inputs:
+/// - the green tree is lossless, i.e. its text is exactly `source`
+/// - every error's span lies within `source` on character boundaries
+///
+/// That every token was consumed is already asserted by [`crate::parser::Parser::finish`].
+pub fn assert_parse_invariants<L>(source: &str, ptree: &ParseTree<L>)
+where
+	L: LangExt,
+	L::Token: std::fmt::Debug,
+{
+	let text_len: usize = ptree.root.text_len().into();
+
+	assert_eq!(
+		text_len,
+		source.len(),
+		"green tree text length is {text_len}, source length is {}",
+		source.len()
+	);
+
+	assert!(
+		ptree.cursor().text() == source,
+		"green tree text does not match the source"
+	);
+
+	for error in &ptree.errors {
+		let span = error.found().span();
+
+		assert!(
+			span.start <= span.end
+				&& span.end <= source.len()
+				&& source.is_char_boundary(span.start)
+				&& source.is_char_boundary(span.end),
+			"error span {span:?} is out of bounds (source length: {})",
+			source.len()
+		);
+	}
+}
+
+/// Checks that lexing `source` yields contiguous, in-bounds spans that cover
+/// the whole input (which the parser relies on to build a lossless tree).
+pub fn assert_lex_invariants<'i, T>(source: &'i str, extras: T::Extras)
+where
+	T: logos::Logos<'i, Source = str>,
+{
+	let mut end = 0;
+
+	for (_, span) in T::lexer_with_extras(source, extras).spanned() {
+		assert_eq!(
+			span.start, end,
+			"lexeme span {span:?} does not begin where the last one ended ({end})"
+		);
+
+		assert!(
+			span.start < span.end && source.get(span.clone()).is_some(),
+			"lexeme span {span:?} is empty or out of bounds (source length: {})",
+			source.len()
+		);
+
+		end = span.end;
+	}
+
+	assert_eq!(
+		end,
+		source.len(),
+		"lexemes end at {end}, but the source length is {}",
+		source.len()
+	);
+}
+
 /// `Err` variants contain the reason the read failed. This can happen because:
 /// - the environment variable behind `env_var_name` could not be retrieved
 /// - the path at the environment variable is to a non-existent directory
@@ -219,6 +289,126 @@ pub fn check_sample_dir(env_var_name: &'static str) -> Result<PathBuf, String> {
 	Ok(path)
 }
 
+// Fuzzing /////////////////////////////////////////////////////////////////////
+
+/// Maps the name of a fuzz target (see the `fuzz` directory beside this crate's
+/// manifest) to the function it runs. The corpus regression test uses this to
+/// replay each file under `tests/corpus/<target>/` exactly as the fuzzer would.
+#[cfg(feature = "zdoom")]
+#[must_use]
+pub fn fuzz_target(name: &str) -> Option<fn(&str)> {
+	match name {
+		"zdoom_lex" => Some(fuzz_zdoom_lex),
+		"zscript_parse" => Some(fuzz_zscript_parse),
+		"decorate_parse" => Some(fuzz_decorate_parse),
+		"cvarinfo_parse" => Some(fuzz_cvarinfo_parse),
+		"language_parse" => Some(fuzz_language_parse),
+		"mapinfo_parse" => Some(fuzz_mapinfo_parse),
+		_ => None,
+	}
+}
+
+#[cfg(feature = "zdoom")]
+pub fn fuzz_zdoom_lex(source: &str) {
+	use crate::zdoom::{lex::Context, Token};
+
+	assert_lex_invariants::<Token>(source, Context::NON_ZSCRIPT);
+	assert_lex_invariants::<Token>(source, Context::ZSCRIPT_LATEST);
+}
+
+/// Also exercises the AST accessors which interpret token text, since those
+/// see the same malformed input as the parser.
+#[cfg(feature = "zdoom")]
+pub fn fuzz_zscript_parse(source: &str) {
+	use rowan::ast::AstNode;
+
+	use crate::zdoom::{self, ast::LitToken, zscript};
+
+	let ptree: zscript::ParseTree = crate::parse(
+		source,
+		zscript::parse::file,
+		zdoom::lex::Context::ZSCRIPT_LATEST,
+	);
+	assert_parse_invariants(source, &ptree);
+
+	for elem in ptree.cursor().descendants_with_tokens() {
+		match elem {
+			rowan::NodeOrToken::Node(node) => {
+				if let Some(directive) = zscript::ast::VersionDirective::cast(node) {
+					let _ = directive.version();
+				}
+			}
+			rowan::NodeOrToken::Token(token) => {
+				let lit = LitToken::new(token);
+				let _ = lit.float();
+				let _ = lit.int();
+				let _ = lit.name();
+				let _ = lit.string();
+			}
+		}
+	}
+}
+
+#[cfg(feature = "zdoom")]
+pub fn fuzz_decorate_parse(source: &str) {
+	use crate::zdoom::{self, ast::LitToken, decorate};
+
+	let ptree: decorate::ParseTree = crate::parse(
+		source,
+		decorate::parse::file,
+		zdoom::lex::Context::NON_ZSCRIPT,
+	);
+	assert_parse_invariants(source, &ptree);
+
+	for token in ptree
+		.cursor()
+		.descendants_with_tokens()
+		.filter_map(|elem| elem.into_token())
+	{
+		let lit = LitToken::new(token);
+		let _ = lit.float();
+		let _ = lit.int();
+		let _ = lit.name();
+		let _ = lit.string();
+	}
+}
+
+#[cfg(feature = "zdoom")]
+pub fn fuzz_cvarinfo_parse(source: &str) {
+	use crate::zdoom::{self, cvarinfo};
+
+	let ptree: cvarinfo::ParseTree = crate::parse(
+		source,
+		cvarinfo::parse::file,
+		zdoom::lex::Context::NON_ZSCRIPT,
+	);
+	assert_parse_invariants(source, &ptree);
+}
+
+#[cfg(feature = "zdoom")]
+pub fn fuzz_language_parse(source: &str) {
+	use crate::zdoom::{self, language};
+
+	let ptree: language::ParseTree = crate::parse(
+		source,
+		language::parse::file,
+		zdoom::lex::Context::NON_ZSCRIPT,
+	);
+	assert_parse_invariants(source, &ptree);
+}
+
+#[cfg(feature = "zdoom")]
+pub fn fuzz_mapinfo_parse(source: &str) {
+	use crate::zdoom::{self, mapinfo};
+
+	let ptree: mapinfo::ParseTree = crate::parse(
+		source,
+		mapinfo::parse::file,
+		zdoom::lex::Context::NON_ZSCRIPT,
+	);
+	assert_parse_invariants(source, &ptree);
+}
+
 // Details /////////////////////////////////////////////////////////////////////
 
 #[must_use]
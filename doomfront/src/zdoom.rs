@@ -6,6 +6,8 @@ pub mod ast;
 pub mod inctree;
 pub mod lex;
 
+#[cfg(feature = "acs")]
+pub mod acs;
 pub mod cvarinfo;
 pub mod decorate;
 pub mod language;
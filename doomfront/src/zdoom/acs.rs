@@ -0,0 +1,17 @@
+//! [ACS] source, in the dialect accepted by [ACC], ZDoom's reference compiler.
+//!
+//! [ACS]: https://zdoom.org/wiki/ACS
+//! [ACC]: https://github.com/ZDoom/acc
+
+pub mod ast;
+pub mod lex;
+pub mod parse;
+mod syntax;
+
+pub use lex::Token;
+pub use syntax::Syntax;
+
+pub type ParseTree = crate::ParseTree<Syntax>;
+pub type SyntaxNode = rowan::SyntaxNode<Syntax>;
+pub type SyntaxToken = rowan::SyntaxToken<Syntax>;
+pub type SyntaxElem = rowan::SyntaxElement<Syntax>;
@@ -0,0 +1,243 @@
+//! Abstract syntax tree nodes.
+//!
+//! Only a subset of the tree currently has wrappers.
+
+use rowan::ast::AstNode;
+
+use crate::{simple_astnode, AstError, AstResult};
+
+use super::{Syntax, SyntaxNode, SyntaxToken};
+
+/// Wraps a node tagged [`Syntax::ScriptDef`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScriptDef(SyntaxNode);
+
+simple_astnode!(Syntax, ScriptDef, Syntax::ScriptDef);
+
+impl ScriptDef {
+	/// The token following the `script` keyword, tagged with one of the following:
+	/// - [`Syntax::IntLit`]
+	/// - [`Syntax::StringLit`]
+	/// - [`Syntax::Ident`], for a number given by a `#define`d constant.
+	pub fn id(&self) -> AstResult<SyntaxToken> {
+		self.0
+			.children_with_tokens()
+			.find_map(|elem| {
+				elem.into_token().filter(|token| {
+					matches!(
+						token.kind(),
+						Syntax::IntLit | Syntax::StringLit | Syntax::Ident
+					)
+				})
+			})
+			.ok_or(AstError::Missing)
+	}
+
+	/// Returns `None` if this script is named or numbered by a constant,
+	/// or if its number literal is malformed.
+	#[must_use]
+	pub fn number(&self) -> Option<i32> {
+		let token = self.id().ok()?;
+
+		if token.kind() != Syntax::IntLit {
+			return None;
+		}
+
+		parse_int(token.text())
+	}
+
+	/// The returned token is tagged [`Syntax::StringLit`] and includes its quotation marks.
+	/// Returns `None` if this script is numbered.
+	#[must_use]
+	pub fn name(&self) -> Option<SyntaxToken> {
+		self.id()
+			.ok()
+			.filter(|token| token.kind() == Syntax::StringLit)
+	}
+
+	#[must_use]
+	pub fn kind(&self) -> ScriptKind {
+		for elem in self.0.children_with_tokens() {
+			let Some(token) = elem.into_token() else {
+				continue;
+			};
+
+			match token.kind() {
+				Syntax::KwOpen => return ScriptKind::Open,
+				Syntax::KwEnter => return ScriptKind::Enter,
+				Syntax::KwRespawn => return ScriptKind::Respawn,
+				Syntax::KwDeath => return ScriptKind::Death,
+				Syntax::KwLightning => return ScriptKind::Lightning,
+				Syntax::KwUnloading => return ScriptKind::Unloading,
+				Syntax::KwDisconnect => return ScriptKind::Disconnect,
+				Syntax::KwReturn => return ScriptKind::Return,
+				Syntax::KwReopen => return ScriptKind::Reopen,
+				Syntax::KwKill => return ScriptKind::Kill,
+				Syntax::KwEvent => return ScriptKind::Event,
+				_ => {}
+			}
+		}
+
+		ScriptKind::Closed
+	}
+
+	#[must_use]
+	pub fn params(&self) -> Option<ParamList> {
+		self.0.children().find_map(ParamList::cast)
+	}
+
+	/// Zero if this script has no parameter list, or if that list is `(void)`.
+	#[must_use]
+	pub fn arg_count(&self) -> usize {
+		self.params().map_or(0, |params| params.iter().count())
+	}
+
+	#[must_use]
+	pub fn is_net(&self) -> bool {
+		self.0
+			.children_with_tokens()
+			.any(|elem| elem.kind() == Syntax::KwNet)
+	}
+
+	#[must_use]
+	pub fn is_clientside(&self) -> bool {
+		self.0
+			.children_with_tokens()
+			.any(|elem| elem.kind() == Syntax::KwClientSide)
+	}
+}
+
+/// What causes a [`ScriptDef`] to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScriptKind {
+	/// No script type keyword. Only runs when executed by a line special or another script.
+	Closed,
+	Death,
+	Disconnect,
+	Enter,
+	Event,
+	Kill,
+	Lightning,
+	Open,
+	Reopen,
+	Respawn,
+	Return,
+	Unloading,
+}
+
+/// Wraps a node tagged [`Syntax::ParamList`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParamList(SyntaxNode);
+
+simple_astnode!(Syntax, ParamList, Syntax::ParamList);
+
+impl ParamList {
+	/// Yields nothing for `(void)`.
+	pub fn iter(&self) -> impl Iterator<Item = Parameter> {
+		self.0.children().filter_map(Parameter::cast)
+	}
+}
+
+/// Wraps a node tagged [`Syntax::Parameter`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Parameter(SyntaxNode);
+
+simple_astnode!(Syntax, Parameter, Syntax::Parameter);
+
+impl Parameter {
+	/// The returned token is tagged [`Syntax::KwInt`], [`Syntax::KwStr`], or [`Syntax::KwBool`].
+	pub fn type_spec(&self) -> AstResult<SyntaxToken> {
+		type_spec(&self.0)
+	}
+
+	/// The returned token is tagged [`Syntax::Ident`].
+	pub fn name(&self) -> AstResult<SyntaxToken> {
+		self.0
+			.children_with_tokens()
+			.find_map(|elem| elem.into_token().filter(|t| t.kind() == Syntax::Ident))
+			.ok_or(AstError::Missing)
+	}
+}
+
+/// Wraps a node tagged [`Syntax::MapVarDecl`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MapVarDecl(SyntaxNode);
+
+simple_astnode!(Syntax, MapVarDecl, Syntax::MapVarDecl);
+
+impl MapVarDecl {
+	/// The returned token is tagged [`Syntax::KwInt`], [`Syntax::KwStr`], or [`Syntax::KwBool`].
+	pub fn type_spec(&self) -> AstResult<SyntaxToken> {
+		type_spec(&self.0)
+	}
+
+	pub fn names(&self) -> impl Iterator<Item = VarName> {
+		self.0.children().filter_map(VarName::cast)
+	}
+}
+
+/// Wraps a node tagged [`Syntax::VarName`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VarName(SyntaxNode);
+
+simple_astnode!(Syntax, VarName, Syntax::VarName);
+
+impl VarName {
+	/// The returned token is tagged [`Syntax::Ident`].
+	pub fn ident(&self) -> AstResult<SyntaxToken> {
+		self.0
+			.first_token()
+			.filter(|token| token.kind() == Syntax::Ident)
+			.ok_or(AstError::Missing)
+	}
+
+	#[must_use]
+	pub fn is_array(&self) -> bool {
+		self.0
+			.children()
+			.any(|node| node.kind() == Syntax::ArrayLen)
+	}
+
+	/// Whether this is followed by `=` and an expression or array initializer.
+	#[must_use]
+	pub fn is_initialized(&self) -> bool {
+		self.0
+			.children_with_tokens()
+			.any(|elem| elem.kind() == Syntax::Eq)
+	}
+}
+
+// Details /////////////////////////////////////////////////////////////////////
+
+fn type_spec(node: &SyntaxNode) -> AstResult<SyntaxToken> {
+	node.first_token()
+		.filter(|token| matches!(token.kind(), Syntax::KwInt | Syntax::KwStr | Syntax::KwBool))
+		.ok_or(AstError::Missing)
+}
+
+/// Handles decimal, hexadecimal (`0x`-prefixed), and radix (e.g. `2_1010`)
+/// literals. Wraps around like ACC does when a literal exceeds [`i32::MAX`].
+#[must_use]
+fn parse_int(text: &str) -> Option<i32> {
+	let (digits, radix) =
+		if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+			(hex, 16)
+		} else if let Some((radix, digits)) = text.split_once('_') {
+			let radix = radix.parse::<u32>().ok()?;
+
+			if !(2..=36).contains(&radix) {
+				return None;
+			}
+
+			(digits, radix)
+		} else {
+			(text, 10)
+		};
+
+	u32::from_str_radix(digits, radix).ok().map(|u| u as i32)
+}
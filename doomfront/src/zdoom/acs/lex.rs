@@ -0,0 +1,349 @@
+//! The scanner of [ACC], ZDoom's reference ACS compiler, re-implemented via [Logos](logos).
+//!
+//! [ACC]: https://github.com/ZDoom/acc
+
+#[derive(logos::Logos, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[logos(error = Token)]
+pub enum Token {
+	// Literals ////////////////////////////////////////////////////////////////
+	#[regex(r"'([^'\\\n]|\\.)'")]
+	CharLit,
+	/// ACC has no floating-point numbers; a number with a decimal point becomes
+	/// a 16.16 fixed-point value.
+	#[regex(r"[0-9]+\.[0-9]*", priority = 3)]
+	FixedLit,
+	/// Also includes ACC's radix notation, e.g. `2_1010` or `16_FF`.
+	#[regex("0[xX][a-fA-F0-9]+", priority = 4)]
+	#[regex("[0-9]+_[a-zA-Z0-9]+", priority = 3)]
+	#[regex("[0-9]+", priority = 2)]
+	IntLit,
+	#[regex(r#""(([\\]["])|[^"])*""#, priority = 3)]
+	StringLit,
+	// Keywords ////////////////////////////////////////////////////////////////
+	#[doc(hidden)]
+	__FirstKw,
+	#[regex("(?i)bool", priority = 5)]
+	KwBool,
+	#[regex("(?i)break", priority = 5)]
+	KwBreak,
+	#[regex("(?i)case", priority = 5)]
+	KwCase,
+	#[regex("(?i)clientside", priority = 5)]
+	KwClientSide,
+	#[regex("(?i)const", priority = 5)]
+	KwConst,
+	#[regex("(?i)continue", priority = 5)]
+	KwContinue,
+	#[regex("(?i)death", priority = 5)]
+	KwDeath,
+	#[regex("(?i)default", priority = 5)]
+	KwDefault,
+	#[regex("(?i)disconnect", priority = 5)]
+	KwDisconnect,
+	#[regex("(?i)do", priority = 5)]
+	KwDo,
+	#[regex("(?i)else", priority = 5)]
+	KwElse,
+	#[regex("(?i)enter", priority = 5)]
+	KwEnter,
+	#[regex("(?i)event", priority = 5)]
+	KwEvent,
+	#[regex("(?i)for", priority = 5)]
+	KwFor,
+	#[regex("(?i)function", priority = 5)]
+	KwFunction,
+	#[regex("(?i)global", priority = 5)]
+	KwGlobal,
+	#[regex("(?i)hudmessage", priority = 5)]
+	KwHudMessage,
+	#[regex("(?i)hudmessagebold", priority = 5)]
+	KwHudMessageBold,
+	#[regex("(?i)if", priority = 5)]
+	KwIf,
+	#[regex("(?i)int", priority = 5)]
+	KwInt,
+	#[regex("(?i)kill", priority = 5)]
+	KwKill,
+	#[regex("(?i)lightning", priority = 5)]
+	KwLightning,
+	#[regex("(?i)log", priority = 5)]
+	KwLog,
+	#[regex("(?i)net", priority = 5)]
+	KwNet,
+	#[regex("(?i)open", priority = 5)]
+	KwOpen,
+	#[regex("(?i)print", priority = 5)]
+	KwPrint,
+	#[regex("(?i)printbold", priority = 5)]
+	KwPrintBold,
+	#[regex("(?i)reopen", priority = 5)]
+	KwReopen,
+	#[regex("(?i)respawn", priority = 5)]
+	KwRespawn,
+	#[regex("(?i)restart", priority = 5)]
+	KwRestart,
+	#[regex("(?i)return", priority = 5)]
+	KwReturn,
+	#[regex("(?i)script", priority = 5)]
+	KwScript,
+	#[regex("(?i)special", priority = 5)]
+	KwSpecial,
+	#[regex("(?i)str", priority = 5)]
+	KwStr,
+	#[regex("(?i)strparam", priority = 5)]
+	KwStrParam,
+	#[regex("(?i)suspend", priority = 5)]
+	KwSuspend,
+	#[regex("(?i)switch", priority = 5)]
+	KwSwitch,
+	#[regex("(?i)terminate", priority = 5)]
+	KwTerminate,
+	#[regex("(?i)unloading", priority = 5)]
+	KwUnloading,
+	#[regex("(?i)until", priority = 5)]
+	KwUntil,
+	#[regex("(?i)void", priority = 5)]
+	KwVoid,
+	#[regex("(?i)while", priority = 5)]
+	KwWhile,
+	#[regex("(?i)world", priority = 5)]
+	KwWorld,
+	#[doc(hidden)]
+	__LastKw,
+	// Directives //////////////////////////////////////////////////////////////
+	#[regex("(?i)#define", priority = 5)]
+	DirDefine,
+	#[regex("(?i)#encryptstrings", priority = 5)]
+	DirEncryptStrings,
+	#[regex("(?i)#import", priority = 5)]
+	DirImport,
+	#[regex("(?i)#include", priority = 5)]
+	DirInclude,
+	#[regex("(?i)#libdefine", priority = 5)]
+	DirLibDefine,
+	#[regex("(?i)#library", priority = 5)]
+	DirLibrary,
+	#[regex("(?i)#nocompact", priority = 5)]
+	DirNoCompact,
+	#[regex("(?i)#nowadauthor", priority = 5)]
+	DirNoWadAuthor,
+	#[regex("(?i)#wadauthor", priority = 5)]
+	DirWadAuthor,
+	// Glyphs //////////////////////////////////////////////////////////////////
+	#[token("&")]
+	Ampersand,
+	#[token("&&")]
+	Ampersand2,
+	#[token("&=")]
+	AmpersandEq,
+	#[token("<")]
+	AngleL,
+	#[token("<<")]
+	AngleL2,
+	#[token("<<=")]
+	AngleL2Eq,
+	#[token("<=")]
+	AngleLEq,
+	#[token(">")]
+	AngleR,
+	#[token(">>")]
+	AngleR2,
+	#[token(">>=")]
+	AngleR2Eq,
+	#[token(">=")]
+	AngleREq,
+	#[token("*")]
+	Asterisk,
+	#[token("*=")]
+	AsteriskEq,
+	#[token("!")]
+	Bang,
+	#[token("!=")]
+	BangEq,
+	#[token("{")]
+	BraceL,
+	#[token("}")]
+	BraceR,
+	#[token("[")]
+	BracketL,
+	#[token("]")]
+	BracketR,
+	#[token("^")]
+	Caret,
+	#[token("^=")]
+	CaretEq,
+	#[token(":")]
+	Colon,
+	#[token(",")]
+	Comma,
+	#[token("=")]
+	Eq,
+	#[token("==")]
+	Eq2,
+	#[token("-")]
+	Minus,
+	#[token("--")]
+	Minus2,
+	#[token("-=")]
+	MinusEq,
+	#[token("(")]
+	ParenL,
+	#[token(")")]
+	ParenR,
+	#[token("%")]
+	Percent,
+	#[token("%=")]
+	PercentEq,
+	#[token("|")]
+	Pipe,
+	#[token("||")]
+	Pipe2,
+	#[token("|=")]
+	PipeEq,
+	#[token("+")]
+	Plus,
+	#[token("++")]
+	Plus2,
+	#[token("+=")]
+	PlusEq,
+	#[token("#")]
+	Pound,
+	#[token("?")]
+	Question,
+	#[token(";")]
+	Semicolon,
+	#[token("/")]
+	Slash,
+	#[token("/=")]
+	SlashEq,
+	#[token("~")]
+	Tilde,
+	// Miscellaneous ///////////////////////////////////////////////////////////
+	#[regex("//[^\n]*")]
+	#[regex(r"/[*]([^*]|([*][^/]))*(?:[*]+/)?")]
+	Comment,
+	/// A dummy token for [`LangExt`](crate::LangExt).
+	Eof,
+	#[regex("[a-zA-Z_][a-zA-Z0-9_]*", priority = 4)]
+	Ident,
+	/// A heterogenous span of any character between NUL and ASCII 32.
+	#[regex("[\0- ]+")]
+	Whitespace,
+	#[default]
+	Unknown,
+	#[doc(hidden)]
+	__Last,
+}
+
+impl Token {
+	#[must_use]
+	pub fn is_keyword(self) -> bool {
+		let u = self as u8;
+		u > (Self::__FirstKw as u8) && u < (Self::__LastKw as u8)
+	}
+
+	/// Directives are every token between the keywords and the glyphs.
+	#[must_use]
+	pub fn is_directive(self) -> bool {
+		let u = self as u8;
+		u > (Self::__LastKw as u8) && u < (Self::Ampersand as u8)
+	}
+
+	/// Glyphs are every token between the directives and [`Self::Comment`].
+	#[must_use]
+	pub fn is_glyph(self) -> bool {
+		let u = self as u8;
+		u >= (Self::Ampersand as u8) && u < (Self::Comment as u8)
+	}
+
+	#[must_use]
+	pub fn is_literal(self) -> bool {
+		matches!(
+			self,
+			Self::CharLit | Self::FixedLit | Self::IntLit | Self::StringLit
+		)
+	}
+
+	#[must_use]
+	pub fn is_trivia(self) -> bool {
+		matches!(self, Self::Whitespace | Self::Comment)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use logos::Logos;
+
+	use super::*;
+
+	#[test]
+	fn smoke() {
+		const SAMPLE: &str = r#"
+#include "zcommon.acs"
+#define MAX_COUNT 16_FF
+
+script "Lorem" OPEN /* ipsum */ {
+	int i = 0x1F + 'a';
+	hudmessage(s:"Dolor ", d:i; HUDMSG_PLAIN, 0, CR_GOLD, 0.5, 1.0, 3.5);
+}
+"#;
+
+		let mut lexer = Token::lexer(SAMPLE);
+
+		while let Some(result) = lexer.next() {
+			let token = match result {
+				Ok(t) | Err(t) => t,
+			};
+
+			assert_ne!(token, Token::Unknown, "unknown token: `{}`", lexer.slice());
+		}
+	}
+
+	#[test]
+	fn case_insensitivity() {
+		let mut lexer = Token::lexer("SCRIPT Script #LIBRARY scripts");
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::KwScript);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::Whitespace);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::KwScript);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::Whitespace);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::DirLibrary);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::Whitespace);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::Ident);
+	}
+
+	#[test]
+	fn numbers() {
+		let mut lexer = Token::lexer("35 0xFF 2_1010 1.5 0.");
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::IntLit);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::Whitespace);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::IntLit);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::Whitespace);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::IntLit);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::Whitespace);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::FixedLit);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::Whitespace);
+		assert_eq!(lexer.next().unwrap().unwrap(), Token::FixedLit);
+	}
+
+	#[test]
+	fn predicates() {
+		assert!(Token::KwBool.is_keyword());
+		assert!(Token::KwWorld.is_keyword());
+		assert!(!Token::Ident.is_keyword());
+		assert!(!Token::DirDefine.is_keyword());
+
+		assert!(Token::DirDefine.is_directive());
+		assert!(Token::DirWadAuthor.is_directive());
+		assert!(!Token::Pound.is_directive());
+
+		assert!(Token::Ampersand.is_glyph());
+		assert!(Token::Tilde.is_glyph());
+		assert!(!Token::DirWadAuthor.is_glyph());
+		assert!(!Token::Comment.is_glyph());
+
+		assert!(Token::CharLit.is_literal());
+		assert!(Token::StringLit.is_literal());
+		assert!(!Token::Ident.is_literal());
+	}
+}
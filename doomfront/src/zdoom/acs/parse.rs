@@ -0,0 +1,1086 @@
+use crate::parser::{CloseMark, Parser};
+
+use super::{Syntax, Token};
+
+/// Builds a [`Syntax::Root`] node.
+pub fn file(p: &mut Parser<Syntax>) {
+	let root = p.open();
+
+	while !p.eof() {
+		if trivia(p) {
+			continue;
+		}
+
+		let token = p.nth(0);
+
+		match token {
+			Token::KwScript => script_def(p),
+			Token::KwFunction => function_decl(p),
+			Token::KwInt | Token::KwStr | Token::KwBool => var_decl(p, Syntax::MapVarDecl),
+			Token::KwWorld | Token::KwGlobal => scoped_var_decl(p),
+			Token::KwSpecial => special_decl(p),
+			Token::DirDefine | Token::DirLibDefine => define_directive(p),
+			Token::DirInclude | Token::DirImport => {
+				string_directive(p, Syntax::IncludeDirective);
+			}
+			Token::DirLibrary => string_directive(p, Syntax::LibraryDirective),
+			Token::DirEncryptStrings
+			| Token::DirNoCompact
+			| Token::DirNoWadAuthor
+			| Token::DirWadAuthor => {
+				let node = p.open();
+				p.advance(Syntax::from(token));
+				p.close(node, Syntax::FlagDirective);
+			}
+			other => {
+				p.advance_with_error(
+					Syntax::from(other),
+					&[&[
+						"`script`",
+						"`function`",
+						"`int`",
+						"`str`",
+						"`bool`",
+						"`world`",
+						"`global`",
+						"`special`",
+						"a directive",
+					]],
+				);
+			}
+		}
+	}
+
+	p.close(root, Syntax::Root);
+}
+
+// Top-level items ////////////////////////////////////////////////////////////
+
+/// Builds a [`Syntax::DefineDirective`] node.
+fn define_directive(p: &mut Parser<Syntax>) {
+	p.debug_assert_at_any(&[Token::DirDefine, Token::DirLibDefine]);
+	let node = p.open();
+	p.advance(Syntax::from(p.nth(0)));
+	trivia_0plus(p);
+	p.expect(Token::Ident, Syntax::Ident, &[&["an identifier"]]);
+	trivia_0plus(p);
+	expr(p);
+	p.close(node, Syntax::DefineDirective);
+}
+
+/// Builds a [`Syntax::FunctionDecl`] node.
+fn function_decl(p: &mut Parser<Syntax>) {
+	p.debug_assert_at(Token::KwFunction);
+	let node = p.open();
+	p.advance(Syntax::KwFunction);
+	trivia_0plus(p);
+
+	p.expect_any(
+		&[
+			(Token::KwInt, Syntax::KwInt),
+			(Token::KwStr, Syntax::KwStr),
+			(Token::KwBool, Syntax::KwBool),
+			(Token::KwVoid, Syntax::KwVoid),
+		],
+		&[&["`int`", "`str`", "`bool`", "`void`"]],
+	);
+
+	trivia_0plus(p);
+	p.expect(Token::Ident, Syntax::Ident, &[&["an identifier"]]);
+	trivia_0plus(p);
+
+	if p.at(Token::ParenL) {
+		param_list(p);
+	} else {
+		p.expect(Token::ParenL, Syntax::ParenL, &[&["`(`"]]);
+	}
+
+	trivia_0plus(p);
+	compound_stat(p);
+	p.close(node, Syntax::FunctionDecl);
+}
+
+/// Builds a [`Syntax::ScopedVarDecl`] node.
+fn scoped_var_decl(p: &mut Parser<Syntax>) {
+	p.debug_assert_at_any(&[Token::KwWorld, Token::KwGlobal]);
+	let node = p.open();
+	p.advance(Syntax::from(p.nth(0)));
+	trivia_0plus(p);
+	type_name(p);
+
+	loop {
+		trivia_0plus(p);
+		p.expect(Token::IntLit, Syntax::IntLit, &[&["an integer"]]);
+		trivia_0plus(p);
+		p.expect(Token::Colon, Syntax::Colon, &[&["`:`"]]);
+		trivia_0plus(p);
+		var_name(p);
+		trivia_0plus(p);
+
+		if !p.eat(Token::Comma, Syntax::Comma) {
+			break;
+		}
+	}
+
+	p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`", "`,`"]]);
+	p.close(node, Syntax::ScopedVarDecl);
+}
+
+/// Builds a [`Syntax::ScriptDef`] node.
+fn script_def(p: &mut Parser<Syntax>) {
+	p.debug_assert_at(Token::KwScript);
+	let node = p.open();
+	p.advance(Syntax::KwScript);
+	trivia_0plus(p);
+
+	p.expect_any(
+		&[
+			(Token::IntLit, Syntax::IntLit),
+			(Token::StringLit, Syntax::StringLit),
+			(Token::Ident, Syntax::Ident),
+		],
+		&[&["an integer", "a string", "an identifier"]],
+	);
+
+	trivia_0plus(p);
+
+	if p.at(Token::ParenL) {
+		param_list(p);
+		trivia_0plus(p);
+	}
+
+	if p.eat_any(&[
+		(Token::KwOpen, Syntax::KwOpen),
+		(Token::KwEnter, Syntax::KwEnter),
+		(Token::KwRespawn, Syntax::KwRespawn),
+		(Token::KwDeath, Syntax::KwDeath),
+		(Token::KwLightning, Syntax::KwLightning),
+		(Token::KwUnloading, Syntax::KwUnloading),
+		(Token::KwDisconnect, Syntax::KwDisconnect),
+		(Token::KwReturn, Syntax::KwReturn),
+		(Token::KwReopen, Syntax::KwReopen),
+		(Token::KwKill, Syntax::KwKill),
+		(Token::KwEvent, Syntax::KwEvent),
+	]) {
+		trivia_0plus(p);
+	}
+
+	while p.eat_any(&[
+		(Token::KwNet, Syntax::KwNet),
+		(Token::KwClientSide, Syntax::KwClientSide),
+	]) {
+		trivia_0plus(p);
+	}
+
+	compound_stat(p);
+	p.close(node, Syntax::ScriptDef);
+}
+
+/// Builds a [`Syntax::SpecialDecl`] node.
+fn special_decl(p: &mut Parser<Syntax>) {
+	p.debug_assert_at(Token::KwSpecial);
+	let node = p.open();
+	p.advance(Syntax::KwSpecial);
+
+	loop {
+		trivia_0plus(p);
+		p.eat(Token::Minus, Syntax::Minus);
+		p.expect(Token::IntLit, Syntax::IntLit, &[&["an integer"]]);
+		trivia_0plus(p);
+		p.expect(Token::Colon, Syntax::Colon, &[&["`:`"]]);
+		trivia_0plus(p);
+		p.expect(Token::Ident, Syntax::Ident, &[&["an identifier"]]);
+		trivia_0plus(p);
+		p.expect(Token::ParenL, Syntax::ParenL, &[&["`(`"]]);
+		trivia_0plus(p);
+		p.expect(Token::IntLit, Syntax::IntLit, &[&["an integer"]]);
+		trivia_0plus(p);
+
+		if p.eat(Token::Comma, Syntax::Comma) {
+			trivia_0plus(p);
+			p.expect(Token::IntLit, Syntax::IntLit, &[&["an integer"]]);
+			trivia_0plus(p);
+		}
+
+		p.expect(Token::ParenR, Syntax::ParenR, &[&["`)`", "`,`"]]);
+		trivia_0plus(p);
+
+		if !p.eat(Token::Comma, Syntax::Comma) {
+			break;
+		}
+	}
+
+	p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`", "`,`"]]);
+	p.close(node, Syntax::SpecialDecl);
+}
+
+/// Builds a node tagged `syn` from a directive followed by a string literal.
+fn string_directive(p: &mut Parser<Syntax>, syn: Syntax) {
+	let node = p.open();
+	p.advance(Syntax::from(p.nth(0)));
+	trivia_0plus(p);
+	p.expect(Token::StringLit, Syntax::StringLit, &[&["a string"]]);
+	p.close(node, syn);
+}
+
+// Statements //////////////////////////////////////////////////////////////////
+
+/// Builds a node tagged with one of the following:
+/// - [`Syntax::BreakStat`]
+/// - [`Syntax::CaseStat`]
+/// - [`Syntax::CompoundStat`]
+/// - [`Syntax::ContinueStat`]
+/// - [`Syntax::DefaultStat`]
+/// - [`Syntax::DoUntilStat`]
+/// - [`Syntax::DoWhileStat`]
+/// - [`Syntax::EmptyStat`]
+/// - [`Syntax::ExprStat`]
+/// - [`Syntax::ForStat`]
+/// - [`Syntax::IfStat`]
+/// - [`Syntax::LocalStat`]
+/// - [`Syntax::RestartStat`]
+/// - [`Syntax::ReturnStat`]
+/// - [`Syntax::SuspendStat`]
+/// - [`Syntax::SwitchStat`]
+/// - [`Syntax::TerminateStat`]
+/// - [`Syntax::UntilStat`]
+/// - [`Syntax::WhileStat`]
+pub fn statement(p: &mut Parser<Syntax>) {
+	if !p.nest(Syntax::Unknown) {
+		return;
+	}
+
+	statement_nested(p);
+	p.unnest();
+}
+
+fn statement_nested(p: &mut Parser<Syntax>) {
+	let token = p.nth(0);
+
+	match token {
+		Token::Semicolon => {
+			let stat = p.open();
+			p.advance(Syntax::Semicolon);
+			p.close(stat, Syntax::EmptyStat);
+		}
+		Token::BraceL => compound_stat(p),
+		Token::KwInt | Token::KwStr | Token::KwBool => var_decl(p, Syntax::LocalStat),
+		Token::KwBreak => keyword_stat(p, Syntax::KwBreak, Syntax::BreakStat),
+		Token::KwContinue => keyword_stat(p, Syntax::KwContinue, Syntax::ContinueStat),
+		Token::KwRestart => keyword_stat(p, Syntax::KwRestart, Syntax::RestartStat),
+		Token::KwSuspend => keyword_stat(p, Syntax::KwSuspend, Syntax::SuspendStat),
+		Token::KwTerminate => keyword_stat(p, Syntax::KwTerminate, Syntax::TerminateStat),
+		Token::KwCase => {
+			let stat = p.open();
+			p.advance(Syntax::KwCase);
+			trivia_0plus(p);
+			expr(p);
+			trivia_0plus(p);
+			p.expect(Token::Colon, Syntax::Colon, &[&["`:`"]]);
+			p.close(stat, Syntax::CaseStat);
+		}
+		Token::KwDefault => {
+			let stat = p.open();
+			p.advance(Syntax::KwDefault);
+			trivia_0plus(p);
+			p.expect(Token::Colon, Syntax::Colon, &[&["`:`"]]);
+			p.close(stat, Syntax::DefaultStat);
+		}
+		Token::KwIf => {
+			let stat = p.open();
+			p.advance(Syntax::KwIf);
+			trivia_0plus(p);
+			condition(p);
+			trivia_0plus(p);
+			statement(p);
+
+			if p.find(0, |token| !token.is_trivia()) == Token::KwElse {
+				trivia_0plus(p);
+				p.advance(Syntax::KwElse);
+				trivia_0plus(p);
+				statement(p);
+			}
+
+			p.close(stat, Syntax::IfStat);
+		}
+		t @ (Token::KwWhile | Token::KwUntil | Token::KwSwitch) => {
+			let stat = p.open();
+			p.advance(Syntax::from(t));
+			trivia_0plus(p);
+			condition(p);
+			trivia_0plus(p);
+			statement(p);
+
+			p.close(
+				stat,
+				match t {
+					Token::KwWhile => Syntax::WhileStat,
+					Token::KwUntil => Syntax::UntilStat,
+					_ => Syntax::SwitchStat,
+				},
+			);
+		}
+		Token::KwDo => {
+			let stat = p.open();
+			p.advance(Syntax::KwDo);
+			trivia_0plus(p);
+			statement(p);
+			trivia_0plus(p);
+
+			let syn = if p.eat(Token::KwWhile, Syntax::KwWhile) {
+				Syntax::DoWhileStat
+			} else {
+				p.expect(Token::KwUntil, Syntax::KwUntil, &[&["`while`", "`until`"]]);
+				Syntax::DoUntilStat
+			};
+
+			trivia_0plus(p);
+			condition(p);
+			trivia_0plus(p);
+			p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
+			p.close(stat, syn);
+		}
+		Token::KwFor => for_stat(p),
+		Token::KwReturn => {
+			let stat = p.open();
+			p.advance(Syntax::KwReturn);
+			trivia_0plus(p);
+
+			if !p.at(Token::Semicolon) {
+				expr(p);
+			}
+
+			p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
+			p.close(stat, Syntax::ReturnStat);
+		}
+		t if in_first_set(t) => {
+			let stat = p.open();
+			expr(p);
+			p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
+			p.close(stat, Syntax::ExprStat);
+		}
+		t if t == Token::BraceR || is_item_start(t) => {
+			// (RAT) Raise an error without consuming anything, so that the
+			// enclosing block or script can recover at its own boundary.
+			// `;` is known to not be here, since it would be an empty statement.
+			p.expect(Token::Semicolon, Syntax::Semicolon, &[&["a statement"]]);
+		}
+		other => {
+			p.advance_with_error(Syntax::from(other), &[&["a statement"]]);
+		}
+	}
+}
+
+/// Builds a [`Syntax::CompoundStat`] node.
+///
+/// Stops early upon reaching the start of a top-level item (e.g. another script),
+/// so that a missing closing brace does not swallow the rest of the file.
+fn compound_stat(p: &mut Parser<Syntax>) {
+	let stat = p.open();
+	p.expect(Token::BraceL, Syntax::BraceL, &[&["`{`"]]);
+
+	loop {
+		trivia_0plus(p);
+
+		if p.at(Token::BraceR) || p.eof() || p.at_if(is_item_start) {
+			break;
+		}
+
+		statement(p);
+	}
+
+	p.expect(Token::BraceR, Syntax::BraceR, &[&["`}`", "a statement"]]);
+	p.close(stat, Syntax::CompoundStat);
+}
+
+/// Builds a [`Syntax::ForStat`] node.
+fn for_stat(p: &mut Parser<Syntax>) {
+	p.debug_assert_at(Token::KwFor);
+	let stat = p.open();
+	p.advance(Syntax::KwFor);
+	trivia_0plus(p);
+	p.expect(Token::ParenL, Syntax::ParenL, &[&["`(`"]]);
+	trivia_0plus(p);
+
+	if p.at_any(&[Token::KwInt, Token::KwStr, Token::KwBool]) {
+		var_decl(p, Syntax::LocalStat);
+	} else {
+		if !p.at(Token::Semicolon) {
+			expr_list(p);
+		}
+
+		p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
+	}
+
+	trivia_0plus(p);
+
+	if !p.at(Token::Semicolon) {
+		expr(p);
+	}
+
+	p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
+	trivia_0plus(p);
+
+	if !p.at(Token::ParenR) {
+		expr_list(p);
+	}
+
+	p.expect(Token::ParenR, Syntax::ParenR, &[&["`)`"]]);
+	trivia_0plus(p);
+	statement(p);
+	p.close(stat, Syntax::ForStat);
+}
+
+fn keyword_stat(p: &mut Parser<Syntax>, kw: Syntax, syn: Syntax) {
+	let stat = p.open();
+	p.advance(kw);
+	trivia_0plus(p);
+	p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
+	p.close(stat, syn);
+}
+
+/// `'(' expr ')'`, as in the header of an `if`, `while`, `until`, or `switch`.
+fn condition(p: &mut Parser<Syntax>) {
+	p.expect(Token::ParenL, Syntax::ParenL, &[&["`(`"]]);
+	trivia_0plus(p);
+	expr(p);
+	trivia_0plus(p);
+	p.expect(Token::ParenR, Syntax::ParenR, &[&["`)`"]]);
+}
+
+// Variables ///////////////////////////////////////////////////////////////////
+
+/// Builds a [`Syntax::MapVarDecl`] or [`Syntax::LocalStat`] node, per `syn`.
+fn var_decl(p: &mut Parser<Syntax>, syn: Syntax) {
+	let node = p.open();
+	type_name(p);
+
+	loop {
+		trivia_0plus(p);
+		var_name(p);
+		trivia_0plus(p);
+
+		if !p.eat(Token::Comma, Syntax::Comma) {
+			break;
+		}
+	}
+
+	p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`", "`,`"]]);
+	p.close(node, syn);
+}
+
+/// Builds a [`Syntax::VarName`] node.
+fn var_name(p: &mut Parser<Syntax>) {
+	let node = p.open();
+	p.expect(Token::Ident, Syntax::Ident, &[&["an identifier"]]);
+
+	while p.find(0, |token| !token.is_trivia()) == Token::BracketL {
+		trivia_0plus(p);
+		let len = p.open();
+		p.advance(Syntax::BracketL);
+		trivia_0plus(p);
+
+		if !p.at(Token::BracketR) {
+			expr(p);
+		}
+
+		p.expect(Token::BracketR, Syntax::BracketR, &[&["`]`"]]);
+		p.close(len, Syntax::ArrayLen);
+	}
+
+	if p.find(0, |token| !token.is_trivia()) == Token::Eq {
+		trivia_0plus(p);
+		p.advance(Syntax::Eq);
+		trivia_0plus(p);
+
+		if p.at(Token::BraceL) {
+			array_init(p);
+		} else {
+			expr(p);
+		}
+	}
+
+	p.close(node, Syntax::VarName);
+}
+
+/// Builds a [`Syntax::ArrayInit`] node.
+fn array_init(p: &mut Parser<Syntax>) {
+	p.debug_assert_at(Token::BraceL);
+
+	if !p.nest(Syntax::Unknown) {
+		return;
+	}
+
+	let node = p.open();
+	p.advance(Syntax::BraceL);
+	trivia_0plus(p);
+
+	while !p.at(Token::BraceR) && !p.eof() {
+		if p.at(Token::BraceL) {
+			array_init(p);
+		} else {
+			expr(p);
+		}
+
+		trivia_0plus(p);
+
+		if !p.eat(Token::Comma, Syntax::Comma) {
+			break;
+		}
+
+		trivia_0plus(p);
+	}
+
+	p.expect(Token::BraceR, Syntax::BraceR, &[&["`}`", "`,`"]]);
+	p.close(node, Syntax::ArrayInit);
+	p.unnest();
+}
+
+/// Builds a [`Syntax::ParamList`] node. Includes delimiting parentheses.
+fn param_list(p: &mut Parser<Syntax>) {
+	p.debug_assert_at(Token::ParenL);
+	let node = p.open();
+	p.advance(Syntax::ParenL);
+	trivia_0plus(p);
+
+	if p.eat(Token::KwVoid, Syntax::KwVoid) {
+		trivia_0plus(p);
+	} else {
+		while !p.at(Token::ParenR) && !p.eof() {
+			let param = p.open();
+			type_name(p);
+			trivia_0plus(p);
+			p.expect(Token::Ident, Syntax::Ident, &[&["an identifier"]]);
+			p.close(param, Syntax::Parameter);
+			trivia_0plus(p);
+
+			if !p.eat(Token::Comma, Syntax::Comma) {
+				break;
+			}
+
+			trivia_0plus(p);
+		}
+	}
+
+	p.expect(Token::ParenR, Syntax::ParenR, &[&["`)`", "`,`"]]);
+	p.close(node, Syntax::ParamList);
+}
+
+fn type_name(p: &mut Parser<Syntax>) {
+	p.expect_any(
+		&[
+			(Token::KwInt, Syntax::KwInt),
+			(Token::KwStr, Syntax::KwStr),
+			(Token::KwBool, Syntax::KwBool),
+		],
+		&[&["`int`", "`str`", "`bool`"]],
+	);
+}
+
+// Expressions /////////////////////////////////////////////////////////////////
+
+/// Builds a node tagged with one of the following:
+/// - [`Syntax::BinExpr`]
+/// - [`Syntax::CallExpr`]
+/// - [`Syntax::GroupExpr`]
+/// - [`Syntax::IdentExpr`]
+/// - [`Syntax::IndexExpr`]
+/// - [`Syntax::Literal`]
+/// - [`Syntax::PostfixExpr`]
+/// - [`Syntax::PrefixExpr`]
+/// - [`Syntax::PrintExpr`]
+pub fn expr(p: &mut Parser<Syntax>) {
+	recur(p, Token::Eof);
+}
+
+fn recur(p: &mut Parser<Syntax>, left: Token) {
+	if !p.nest(Syntax::Unknown) {
+		return;
+	}
+
+	let mut lhs = primary_expr(p);
+
+	loop {
+		trivia_0plus(p);
+
+		let right = p.nth(0);
+
+		match right {
+			t @ (Token::Minus2 | Token::Plus2) => {
+				let m = p.open_before(lhs);
+				p.advance(Syntax::from(t));
+				lhs = p.close(m, Syntax::PostfixExpr);
+				continue;
+			}
+			Token::ParenL => {
+				let m = p.open_before(lhs);
+				arg_list(p);
+				lhs = p.close(m, Syntax::CallExpr);
+				continue;
+			}
+			Token::BracketL => {
+				let m = p.open_before(lhs);
+				p.advance(Syntax::BracketL);
+				trivia_0plus(p);
+				expr(p);
+				trivia_0plus(p);
+				p.expect(Token::BracketR, Syntax::BracketR, &[&["`]`"]]);
+				lhs = p.close(m, Syntax::IndexExpr);
+				continue;
+			}
+			// Only ever prefix operators.
+			Token::Bang | Token::Tilde => break,
+			_ => {}
+		}
+
+		if crate::parser::pratt::<Syntax>(left, right, PRATT_PRECEDENCE) {
+			let m = p.open_before(lhs);
+			p.advance(Syntax::from(right));
+			trivia_0plus(p);
+			recur(p, right);
+			lhs = p.close(m, Syntax::BinExpr);
+		} else {
+			break;
+		}
+	}
+
+	p.unnest();
+}
+
+fn primary_expr(p: &mut Parser<Syntax>) -> CloseMark {
+	const EXPECTED: &[&str] = &[
+		"an identifier",
+		"an integer",
+		"a fixed-point number",
+		"a string",
+		"a character",
+		"`(`",
+		"`!`",
+		"`~`",
+		"`-`",
+		"`++`",
+		"`--`",
+	];
+
+	let ex = p.open();
+	let token = p.nth(0);
+
+	match token {
+		Token::Ident => {
+			p.advance(Syntax::Ident);
+			p.close(ex, Syntax::IdentExpr)
+		}
+		t @ (Token::IntLit | Token::FixedLit | Token::StringLit | Token::CharLit) => {
+			p.advance(Syntax::from(t));
+			p.close(ex, Syntax::Literal)
+		}
+		Token::ParenL => {
+			p.advance(Syntax::ParenL);
+			trivia_0plus(p);
+			expr(p);
+			trivia_0plus(p);
+			p.expect(Token::ParenR, Syntax::ParenR, &[&["`)`"]]);
+			p.close(ex, Syntax::GroupExpr)
+		}
+		t @ (Token::Bang | Token::Tilde | Token::Minus | Token::Minus2 | Token::Plus2) => {
+			p.advance(Syntax::from(t));
+			trivia_0plus(p);
+			recur(p, t);
+			p.close(ex, Syntax::PrefixExpr)
+		}
+		t if is_print_keyword(t) => {
+			p.advance(Syntax::from(t));
+			trivia_0plus(p);
+			print_args(p);
+			p.close(ex, Syntax::PrintExpr)
+		}
+		t if is_recovery_point(t) => {
+			// (RAT) Raise an error without consuming anything, so that the
+			// enclosing statement can still find its terminator.
+			p.expect_if(in_first_set, Syntax::Unknown, &[EXPECTED]);
+			p.close(ex, Syntax::Error)
+		}
+		_ => p.advance_err_and_close(ex, Syntax::from(token), Syntax::Error, &[EXPECTED]),
+	}
+}
+
+/// Builds a [`Syntax::ArgList`] node. Includes delimiting parentheses.
+fn arg_list(p: &mut Parser<Syntax>) {
+	p.debug_assert_at(Token::ParenL);
+	let arglist = p.open();
+	p.advance(Syntax::ParenL);
+	trivia_0plus(p);
+
+	if !p.at(Token::ParenR) {
+		expr_list(p);
+	}
+
+	p.expect(Token::ParenR, Syntax::ParenR, &[&["`)`", "`,`"]]);
+	p.close(arglist, Syntax::ArgList);
+}
+
+/// The parenthesized part of a [`Syntax::PrintExpr`], with its
+/// [`Syntax::FormatItem`]s and (for `hudmessage`) optional trailing arguments.
+fn print_args(p: &mut Parser<Syntax>) {
+	p.expect(Token::ParenL, Syntax::ParenL, &[&["`(`"]]);
+	trivia_0plus(p);
+
+	while !p.at_any(&[Token::ParenR, Token::Semicolon]) && !p.eof() {
+		let item = p.open();
+		p.expect(Token::Ident, Syntax::Ident, &[&["a format specifier"]]);
+		trivia_0plus(p);
+		p.expect(Token::Colon, Syntax::Colon, &[&["`:`"]]);
+		trivia_0plus(p);
+		expr(p);
+		p.close(item, Syntax::FormatItem);
+		trivia_0plus(p);
+
+		if !p.eat(Token::Comma, Syntax::Comma) {
+			break;
+		}
+
+		trivia_0plus(p);
+	}
+
+	if p.eat(Token::Semicolon, Syntax::Semicolon) {
+		trivia_0plus(p);
+		expr_list(p);
+	}
+
+	p.expect(Token::ParenR, Syntax::ParenR, &[&["`)`", "`,`", "`;`"]]);
+}
+
+/// Expects the current position to be the start of at least one expression.
+fn expr_list(p: &mut Parser<Syntax>) {
+	expr(p);
+	trivia_0plus(p);
+
+	while p.eat(Token::Comma, Syntax::Comma) {
+		trivia_0plus(p);
+		expr(p);
+		trivia_0plus(p);
+	}
+}
+
+/// i.e. can `token` begin a primary expression?
+#[must_use]
+fn in_first_set(token: Token) -> bool {
+	is_print_keyword(token)
+		|| matches!(
+			token,
+			Token::Ident
+				| Token::IntLit
+				| Token::FixedLit
+				| Token::StringLit
+				| Token::CharLit
+				| Token::ParenL
+				| Token::Bang
+				| Token::Tilde
+				| Token::Minus
+				| Token::Minus2
+				| Token::Plus2
+		)
+}
+
+#[must_use]
+fn is_print_keyword(token: Token) -> bool {
+	matches!(
+		token,
+		Token::KwPrint
+			| Token::KwPrintBold
+			| Token::KwLog
+			| Token::KwHudMessage
+			| Token::KwHudMessageBold
+			| Token::KwStrParam
+	)
+}
+
+/// Tokens which close or delimit an expression, or begin a new top-level item,
+/// at which a malformed expression should stop without consuming anything.
+#[must_use]
+fn is_recovery_point(token: Token) -> bool {
+	is_item_start(token)
+		|| matches!(
+			token,
+			Token::Semicolon
+				| Token::Colon
+				| Token::Comma
+				| Token::BraceR
+				| Token::BracketR
+				| Token::ParenR
+				| Token::Eof
+		)
+}
+
+/// Type names are excluded, since they can also begin a statement.
+#[must_use]
+fn is_item_start(token: Token) -> bool {
+	token.is_directive()
+		|| matches!(
+			token,
+			Token::KwScript
+				| Token::KwFunction
+				| Token::KwWorld
+				| Token::KwGlobal
+				| Token::KwSpecial
+		)
+}
+
+const PRATT_PRECEDENCE: &[&[Token]] = &[
+	&[
+		Token::Eq,
+		Token::AsteriskEq,
+		Token::SlashEq,
+		Token::PercentEq,
+		Token::PlusEq,
+		Token::MinusEq,
+		Token::AngleL2Eq,
+		Token::AngleR2Eq,
+		Token::AmpersandEq,
+		Token::PipeEq,
+		Token::CaretEq,
+	],
+	&[Token::Pipe2],
+	&[Token::Ampersand2],
+	&[Token::Pipe],
+	&[Token::Caret],
+	&[Token::Ampersand],
+	&[Token::Eq2, Token::BangEq],
+	&[
+		Token::AngleL,
+		Token::AngleR,
+		Token::AngleLEq,
+		Token::AngleREq,
+	],
+	&[Token::AngleL2, Token::AngleR2],
+	&[Token::Plus, Token::Minus],
+	&[Token::Asterisk, Token::Slash, Token::Percent],
+	&[Token::Minus2, Token::Plus2, Token::Bang, Token::Tilde],
+];
+
+// Common //////////////////////////////////////////////////////////////////////
+
+fn trivia(p: &mut Parser<Syntax>) -> bool {
+	p.eat_any(&[
+		(Token::Whitespace, Syntax::Whitespace),
+		(Token::Comment, Syntax::Comment),
+	])
+}
+
+fn trivia_0plus(p: &mut Parser<Syntax>) {
+	while trivia(p) {}
+}
+
+#[cfg(test)]
+mod test {
+	use rowan::ast::AstNode;
+
+	use crate::{
+		testing::*,
+		zdoom::acs::{ast, ParseTree, SyntaxNode},
+	};
+
+	use super::*;
+
+	const SAMPLE: &str = r#"
+#include "zcommon.acs"
+#library "lorem"
+#nocompact
+
+#define MAX_WAVES 5
+#libdefine WAVE_DELAY (35 * 3)
+
+int waves = 0, kills;
+str names[3] = { "Lorem", "Ipsum", "Dolor" };
+world int 1:secrets;
+global int 2:scores[];
+
+function int Clamp (int x, int lo, int hi)
+{
+	if (x < lo)
+		return lo;
+	else if (x > hi)
+		return hi;
+
+	return x;
+}
+
+// Runs as soon as the level starts.
+script 1 OPEN
+{
+	while (waves < MAX_WAVES)
+	{
+		Delay(WAVE_DELAY);
+		waves++;
+	}
+}
+
+script "Greet" ENTER NET
+{
+	print(s:"Welcome, ", n:0, s:"!");
+	hudmessage(s:"Wave ", d:waves; HUDMSG_FADEOUT, 1, CR_GOLD, 0.5, 0.25, 3.0, 1.0);
+}
+
+script 2 (int tid, int amount)
+{
+	for (int i = 0; i < amount; i++)
+		Thing_Damage(tid, Clamp(amount, 1, 100) * ~0x0F, 0);
+
+	switch (amount)
+	{
+	case 0:
+		terminate;
+	default:
+		break;
+	}
+}
+
+script 3 (void) CLIENTSIDE
+{
+	do
+	{
+		waves -= 1;
+		names[waves % 3] = strparam(s:names[0], c:'!');
+	} until (waves <= 0);
+
+	restart;
+}
+"#;
+
+	#[must_use]
+	fn script_defs(root: &SyntaxNode) -> Vec<ast::ScriptDef> {
+		root.children().filter_map(ast::ScriptDef::cast).collect()
+	}
+
+	#[test]
+	fn smoke() {
+		let ptree: ParseTree = crate::parse(SAMPLE, file, ());
+		assert_no_errors(&ptree);
+		prettyprint_maybe(ptree.cursor());
+
+		let root = ptree.cursor();
+		assert_eq!(script_defs(&root).len(), 4);
+
+		assert_eq!(
+			root.children()
+				.filter(|node| node.kind() == Syntax::DefineDirective)
+				.count(),
+			2
+		);
+	}
+
+	#[test]
+	fn script_ast() {
+		let ptree: ParseTree = crate::parse(SAMPLE, file, ());
+		assert_no_errors(&ptree);
+		let scripts = script_defs(&ptree.cursor());
+
+		assert_eq!(scripts[0].number(), Some(1));
+		assert_eq!(scripts[0].kind(), ast::ScriptKind::Open);
+		assert_eq!(scripts[0].arg_count(), 0);
+		assert!(!scripts[0].is_net());
+
+		assert!(scripts[1].number().is_none());
+		assert_eq!(scripts[1].name().unwrap().text(), "\"Greet\"");
+		assert_eq!(scripts[1].kind(), ast::ScriptKind::Enter);
+		assert!(scripts[1].is_net());
+
+		assert_eq!(scripts[2].number(), Some(2));
+		assert_eq!(scripts[2].kind(), ast::ScriptKind::Closed);
+		assert_eq!(scripts[2].arg_count(), 2);
+
+		let params = scripts[2].params().unwrap().iter().collect::<Vec<_>>();
+		assert_eq!(params[0].name().unwrap().text(), "tid");
+		assert_eq!(params[1].type_spec().unwrap().kind(), Syntax::KwInt);
+
+		assert_eq!(scripts[3].arg_count(), 0);
+		assert!(scripts[3].is_clientside());
+	}
+
+	#[test]
+	fn map_var_ast() {
+		let ptree: ParseTree = crate::parse(SAMPLE, file, ());
+		assert_no_errors(&ptree);
+
+		let decls = ptree
+			.cursor()
+			.children()
+			.filter_map(ast::MapVarDecl::cast)
+			.collect::<Vec<_>>();
+
+		assert_eq!(decls.len(), 2);
+		assert_eq!(decls[0].type_spec().unwrap().kind(), Syntax::KwInt);
+
+		let names = decls[0].names().collect::<Vec<_>>();
+		assert_eq!(names.len(), 2);
+		assert_eq!(names[0].ident().unwrap().text(), "waves");
+		assert_eq!(names[1].ident().unwrap().text(), "kills");
+		assert!(!names[1].is_array());
+
+		let names = decls[1].names().collect::<Vec<_>>();
+		assert_eq!(names[0].ident().unwrap().text(), "names");
+		assert!(names[0].is_array());
+	}
+
+	#[test]
+	fn recovery() {
+		const SAMPLE: &str = r#"
+script 1 OPEN
+{
+	int x = ;
+	print(s:"after");
+}
+
+script 2 ENTER
+{
+	x = (1 + ;
+	int y = 2;
+
+script 3 RESPAWN
+{
+	Delay(1);
+}
+"#;
+
+		let ptree: ParseTree = crate::parse(SAMPLE, file, ());
+		assert!(ptree.any_errors());
+
+		let scripts = script_defs(&ptree.cursor());
+		assert_eq!(scripts.len(), 3);
+		assert_eq!(scripts[2].kind(), ast::ScriptKind::Respawn);
+
+		let kinds = |script: &ast::ScriptDef| {
+			script
+				.syntax()
+				.descendants()
+				.skip(1)
+				.map(|node| node.kind())
+				.collect::<Vec<_>>()
+		};
+
+		// The statement after the broken one is intact...
+		assert!(kinds(&scripts[0]).contains(&Syntax::PrintExpr));
+		// ...as is the declaration after a broken expression statement...
+		assert!(kinds(&scripts[1]).contains(&Syntax::LocalStat));
+		// ...and a script missing its closing brace doesn't swallow the next one.
+		assert!(!kinds(&scripts[1]).contains(&Syntax::ScriptDef));
+		assert!(kinds(&scripts[2]).contains(&Syntax::CallExpr));
+		assert!(!kinds(&scripts[2]).contains(&Syntax::Error));
+	}
+
+	#[test]
+	#[ignore]
+	fn with_sample_data() {
+		let (_, sample) = match read_sample_data("DOOMFRONT_ACS_SAMPLE") {
+			Ok(s) => s,
+			Err(err) => {
+				eprintln!("Skipping ACS sample data-based unit test. Reason: {err}");
+				return;
+			}
+		};
+
+		let ptree: ParseTree = crate::parse(&sample, file, ());
+		assert_no_errors(&ptree);
+		prettyprint_maybe(ptree.cursor());
+	}
+}
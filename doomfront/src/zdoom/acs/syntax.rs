@@ -0,0 +1,427 @@
+//! Tags representing syntax nodes, from low-level primitives to high-level composites.
+
+use crate::LangExt;
+
+use super::Token;
+
+/// Tags representing syntax nodes, from low-level primitives to high-level composites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Syntax {
+	/// A sequence of tokens that did not form a valid syntax element.
+	Error,
+	/// The top-level node, representing the whole file.
+	Root,
+	// Nodes: high-level composites ////////////////////////////////////////////
+	/// `'(' (expr (',' expr)*)? ')'`
+	ArgList,
+	/// `'{' (expr | arrayinit) (',' (expr | arrayinit))* '}'`
+	ArrayInit,
+	/// `'[' expr? ']'`
+	ArrayLen,
+	/// `('#define' | '#libdefine') ident expr`
+	///
+	/// Note that unlike C's, ACC's macros are named constant expressions
+	/// rather than textual substitutions.
+	DefineDirective,
+	/// `'#nocompact' | '#wadauthor' | '#nowadauthor' | '#encryptstrings'`
+	FlagDirective,
+	/// `ident ':' expr`, wherein `ident` is a format specifier such as `s` or `d`.
+	///
+	/// Always a child of a [`Syntax::PrintExpr`].
+	FormatItem,
+	/// `'function' typename ident paramlist compoundstat`
+	FunctionDecl,
+	/// `('#include' | '#import') string`
+	IncludeDirective,
+	/// `'#library' string`
+	LibraryDirective,
+	/// `typename varname (',' varname)* ';'`, outside of any script or function.
+	MapVarDecl,
+	/// `typename ident`
+	Parameter,
+	/// `'(' ('void' | parameter (',' parameter)*)? ')'`
+	ParamList,
+	/// `('world' | 'global') typename int ':' varname (',' int ':' varname)* ';'`
+	ScopedVarDecl,
+	/// `'script' (int | string | ident) paramlist? scripttype? ('net' | 'clientside')* compoundstat`
+	ScriptDef,
+	/// `'special' ('-'? int ':' ident '(' int (',' int)? ')')+ ';'`
+	SpecialDecl,
+	/// `ident arraylen* ('=' (expr | arrayinit))?`
+	VarName,
+	// Nodes: expressions //////////////////////////////////////////////////////
+	BinExpr,
+	/// `primaryexpr arglist`
+	CallExpr,
+	/// `'(' expr ')'`
+	GroupExpr,
+	/// `ident`
+	IdentExpr,
+	/// `primaryexpr '[' expr ']'`
+	IndexExpr,
+	/// Will have one of the following tokens as a child:
+	/// - [`Syntax::CharLit`]
+	/// - [`Syntax::FixedLit`]
+	/// - [`Syntax::IntLit`]
+	/// - [`Syntax::StringLit`]
+	Literal,
+	/// `primaryexpr operator`
+	PostfixExpr,
+	/// `operator primaryexpr`
+	PrefixExpr,
+	/// `printkw '(' formatitem (',' formatitem)* (';' expr (',' expr)*)? ')'`
+	///
+	/// Where `printkw` is one of `print`, `printbold`, `log`, `hudmessage`,
+	/// `hudmessagebold`, or `strparam`.
+	PrintExpr,
+	// Nodes: statements ///////////////////////////////////////////////////////
+	/// `'break' ';'`
+	BreakStat,
+	/// `'case' expr ':'`
+	CaseStat,
+	/// `'{' statement* '}'`
+	CompoundStat,
+	/// `'continue' ';'`
+	ContinueStat,
+	/// `'default' ':'`
+	DefaultStat,
+	/// `'do' statement 'until' '(' expr ')' ';'`
+	DoUntilStat,
+	/// `'do' statement 'while' '(' expr ')' ';'`
+	DoWhileStat,
+	/// `';'`
+	EmptyStat,
+	/// An expression followed by a semicolon.
+	ExprStat,
+	/// `'for' '(' (localstat | expr? ';') expr? ';' expr? ')' statement`
+	ForStat,
+	/// `'if' '(' expr ')' statement ('else' statement)?`
+	IfStat,
+	/// `typename varname (',' varname)* ';'`, inside of a script or function.
+	LocalStat,
+	/// `'restart' ';'`
+	RestartStat,
+	/// `'return' expr? ';'`
+	ReturnStat,
+	/// `'suspend' ';'`
+	SuspendStat,
+	/// `'switch' '(' expr ')' statement`
+	SwitchStat,
+	/// `'terminate' ';'`
+	TerminateStat,
+	/// `'until' '(' expr ')' statement`
+	UntilStat,
+	/// `'while' '(' expr ')' statement`
+	WhileStat,
+	// Tokens: literals ////////////////////////////////////////////////////////
+	/// See [`Token::CharLit`].
+	CharLit,
+	/// See [`Token::FixedLit`].
+	FixedLit,
+	/// See [`Token::IntLit`].
+	IntLit,
+	/// See [`Token::StringLit`].
+	StringLit,
+	// Tokens: keywords ////////////////////////////////////////////////////////
+	KwBool,
+	KwBreak,
+	KwCase,
+	KwClientSide,
+	KwConst,
+	KwContinue,
+	KwDeath,
+	KwDefault,
+	KwDisconnect,
+	KwDo,
+	KwElse,
+	KwEnter,
+	KwEvent,
+	KwFor,
+	KwFunction,
+	KwGlobal,
+	KwHudMessage,
+	KwHudMessageBold,
+	KwIf,
+	KwInt,
+	KwKill,
+	KwLightning,
+	KwLog,
+	KwNet,
+	KwOpen,
+	KwPrint,
+	KwPrintBold,
+	KwReopen,
+	KwRespawn,
+	KwRestart,
+	KwReturn,
+	KwScript,
+	KwSpecial,
+	KwStr,
+	KwStrParam,
+	KwSuspend,
+	KwSwitch,
+	KwTerminate,
+	KwUnloading,
+	KwUntil,
+	KwVoid,
+	KwWhile,
+	KwWorld,
+	// Tokens: directives //////////////////////////////////////////////////////
+	/// `#define`
+	DirDefine,
+	/// `#encryptstrings`
+	DirEncryptStrings,
+	/// `#import`
+	DirImport,
+	/// `#include`
+	DirInclude,
+	/// `#libdefine`
+	DirLibDefine,
+	/// `#library`
+	DirLibrary,
+	/// `#nocompact`
+	DirNoCompact,
+	/// `#nowadauthor`
+	DirNoWadAuthor,
+	/// `#wadauthor`
+	DirWadAuthor,
+	// Tokens: glyphs //////////////////////////////////////////////////////////
+	/// `&`
+	Ampersand,
+	/// `&&`
+	Ampersand2,
+	/// `&=`
+	AmpersandEq,
+	/// `<`
+	AngleL,
+	/// `<<`
+	AngleL2,
+	/// `<<=`
+	AngleL2Eq,
+	/// `<=`
+	AngleLEq,
+	/// `>`
+	AngleR,
+	/// `>>`
+	AngleR2,
+	/// `>>=`
+	AngleR2Eq,
+	/// `>=`
+	AngleREq,
+	/// `*`
+	Asterisk,
+	/// `*=`
+	AsteriskEq,
+	/// `!`
+	Bang,
+	/// `!=`
+	BangEq,
+	/// `{`
+	BraceL,
+	/// `}`
+	BraceR,
+	/// `[`
+	BracketL,
+	/// `]`
+	BracketR,
+	/// `^`
+	Caret,
+	/// `^=`
+	CaretEq,
+	/// `:`
+	Colon,
+	/// `,`
+	Comma,
+	/// `=`
+	Eq,
+	/// `==`
+	Eq2,
+	/// `-`
+	Minus,
+	/// `--`
+	Minus2,
+	/// `-=`
+	MinusEq,
+	/// `(`
+	ParenL,
+	/// `)`
+	ParenR,
+	/// `%`
+	Percent,
+	/// `%=`
+	PercentEq,
+	/// `|`
+	Pipe,
+	/// `||`
+	Pipe2,
+	/// `|=`
+	PipeEq,
+	/// `+`
+	Plus,
+	/// `++`
+	Plus2,
+	/// `+=`
+	PlusEq,
+	/// `#`
+	Pound,
+	/// `?`
+	Question,
+	/// `;`
+	Semicolon,
+	/// `/`
+	Slash,
+	/// `/=`
+	SlashEq,
+	/// `~`
+	Tilde,
+	// Tokens: miscellaneous ///////////////////////////////////////////////////
+	/// Either single-line or multi-line.
+	Comment,
+	/// A C-style identifier.
+	Ident,
+	/// Lexer input rolled up under [`Syntax::Error`].
+	Unknown,
+	/// Spaces, newlines, carriage returns, or tabs.
+	Whitespace,
+	#[doc(hidden)]
+	__Last,
+}
+
+impl From<Syntax> for rowan::SyntaxKind {
+	fn from(value: Syntax) -> Self {
+		Self(value as u16)
+	}
+}
+
+impl rowan::Language for Syntax {
+	type Kind = Self;
+
+	fn kind_from_raw(raw: rowan::SyntaxKind) -> Self::Kind {
+		assert!(raw.0 < Self::__Last as u16);
+		unsafe { std::mem::transmute::<u16, Syntax>(raw.0) }
+	}
+
+	fn kind_to_raw(kind: Self::Kind) -> rowan::SyntaxKind {
+		kind.into()
+	}
+}
+
+impl LangExt for Syntax {
+	type Token = Token;
+	const EOF: Self::Token = Token::Eof;
+	const ERR_NODE: Self::Kind = Self::Error;
+}
+
+impl From<Token> for Syntax {
+	fn from(value: Token) -> Self {
+		match value {
+			Token::CharLit => Self::CharLit,
+			Token::FixedLit => Self::FixedLit,
+			Token::IntLit => Self::IntLit,
+			Token::StringLit => Self::StringLit,
+			Token::KwBool => Self::KwBool,
+			Token::KwBreak => Self::KwBreak,
+			Token::KwCase => Self::KwCase,
+			Token::KwClientSide => Self::KwClientSide,
+			Token::KwConst => Self::KwConst,
+			Token::KwContinue => Self::KwContinue,
+			Token::KwDeath => Self::KwDeath,
+			Token::KwDefault => Self::KwDefault,
+			Token::KwDisconnect => Self::KwDisconnect,
+			Token::KwDo => Self::KwDo,
+			Token::KwElse => Self::KwElse,
+			Token::KwEnter => Self::KwEnter,
+			Token::KwEvent => Self::KwEvent,
+			Token::KwFor => Self::KwFor,
+			Token::KwFunction => Self::KwFunction,
+			Token::KwGlobal => Self::KwGlobal,
+			Token::KwHudMessage => Self::KwHudMessage,
+			Token::KwHudMessageBold => Self::KwHudMessageBold,
+			Token::KwIf => Self::KwIf,
+			Token::KwInt => Self::KwInt,
+			Token::KwKill => Self::KwKill,
+			Token::KwLightning => Self::KwLightning,
+			Token::KwLog => Self::KwLog,
+			Token::KwNet => Self::KwNet,
+			Token::KwOpen => Self::KwOpen,
+			Token::KwPrint => Self::KwPrint,
+			Token::KwPrintBold => Self::KwPrintBold,
+			Token::KwReopen => Self::KwReopen,
+			Token::KwRespawn => Self::KwRespawn,
+			Token::KwRestart => Self::KwRestart,
+			Token::KwReturn => Self::KwReturn,
+			Token::KwScript => Self::KwScript,
+			Token::KwSpecial => Self::KwSpecial,
+			Token::KwStr => Self::KwStr,
+			Token::KwStrParam => Self::KwStrParam,
+			Token::KwSuspend => Self::KwSuspend,
+			Token::KwSwitch => Self::KwSwitch,
+			Token::KwTerminate => Self::KwTerminate,
+			Token::KwUnloading => Self::KwUnloading,
+			Token::KwUntil => Self::KwUntil,
+			Token::KwVoid => Self::KwVoid,
+			Token::KwWhile => Self::KwWhile,
+			Token::KwWorld => Self::KwWorld,
+			Token::DirDefine => Self::DirDefine,
+			Token::DirEncryptStrings => Self::DirEncryptStrings,
+			Token::DirImport => Self::DirImport,
+			Token::DirInclude => Self::DirInclude,
+			Token::DirLibDefine => Self::DirLibDefine,
+			Token::DirLibrary => Self::DirLibrary,
+			Token::DirNoCompact => Self::DirNoCompact,
+			Token::DirNoWadAuthor => Self::DirNoWadAuthor,
+			Token::DirWadAuthor => Self::DirWadAuthor,
+			Token::Ampersand => Self::Ampersand,
+			Token::Ampersand2 => Self::Ampersand2,
+			Token::AmpersandEq => Self::AmpersandEq,
+			Token::AngleL => Self::AngleL,
+			Token::AngleL2 => Self::AngleL2,
+			Token::AngleL2Eq => Self::AngleL2Eq,
+			Token::AngleLEq => Self::AngleLEq,
+			Token::AngleR => Self::AngleR,
+			Token::AngleR2 => Self::AngleR2,
+			Token::AngleR2Eq => Self::AngleR2Eq,
+			Token::AngleREq => Self::AngleREq,
+			Token::Asterisk => Self::Asterisk,
+			Token::AsteriskEq => Self::AsteriskEq,
+			Token::Bang => Self::Bang,
+			Token::BangEq => Self::BangEq,
+			Token::BraceL => Self::BraceL,
+			Token::BraceR => Self::BraceR,
+			Token::BracketL => Self::BracketL,
+			Token::BracketR => Self::BracketR,
+			Token::Caret => Self::Caret,
+			Token::CaretEq => Self::CaretEq,
+			Token::Colon => Self::Colon,
+			Token::Comma => Self::Comma,
+			Token::Eq => Self::Eq,
+			Token::Eq2 => Self::Eq2,
+			Token::Minus => Self::Minus,
+			Token::Minus2 => Self::Minus2,
+			Token::MinusEq => Self::MinusEq,
+			Token::ParenL => Self::ParenL,
+			Token::ParenR => Self::ParenR,
+			Token::Percent => Self::Percent,
+			Token::PercentEq => Self::PercentEq,
+			Token::Pipe => Self::Pipe,
+			Token::Pipe2 => Self::Pipe2,
+			Token::PipeEq => Self::PipeEq,
+			Token::Plus => Self::Plus,
+			Token::Plus2 => Self::Plus2,
+			Token::PlusEq => Self::PlusEq,
+			Token::Pound => Self::Pound,
+			Token::Question => Self::Question,
+			Token::Semicolon => Self::Semicolon,
+			Token::Slash => Self::Slash,
+			Token::SlashEq => Self::SlashEq,
+			Token::Tilde => Self::Tilde,
+			Token::Comment => Self::Comment,
+			Token::Ident => Self::Ident,
+			Token::Whitespace => Self::Whitespace,
+			Token::Unknown | Token::Eof => Self::Unknown,
+			Token::__FirstKw | Token::__LastKw | Token::__Last => unreachable!(),
+		}
+	}
+}
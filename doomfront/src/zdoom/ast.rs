@@ -1,6 +1,6 @@
 //! Abstract syntax tree elements shared between multiple languages.
 
-use std::num::{ParseFloatError, ParseIntError};
+use std::num::{IntErrorKind, ParseFloatError, ParseIntError};
 
 use rowan::SyntaxToken;
 
@@ -60,6 +60,23 @@ impl LitToken<zscript::Syntax> {
 			_ => None,
 		}
 	}
+
+	/// Strings get [unescaped](unescape_string). Mind that a string literal
+	/// expression may consist of multiple tokens; see [`zscript::ast::Literal::value`].
+	pub fn value(&self) -> Result<LiteralValue, LiteralParseError> {
+		match self.0.kind() {
+			zscript::Syntax::IntLit => self.int_value(),
+			zscript::Syntax::FloatLit => self.float_value(),
+			zscript::Syntax::StringLit => {
+				Ok(LiteralValue::String(unescape_string(self.get_string())))
+			}
+			zscript::Syntax::NameLit => Ok(LiteralValue::Name(self.get_name().to_string())),
+			zscript::Syntax::KwTrue => Ok(LiteralValue::Bool(true)),
+			zscript::Syntax::KwFalse => Ok(LiteralValue::Bool(false)),
+			zscript::Syntax::NullLit => Ok(LiteralValue::Null),
+			_ => Err(LiteralParseError::NotLiteral),
+		}
+	}
 }
 
 impl LitToken<decorate::Syntax> {
@@ -105,6 +122,21 @@ impl LitToken<decorate::Syntax> {
 			_ => None,
 		}
 	}
+
+	/// Strings get [unescaped](unescape_string).
+	pub fn value(&self) -> Result<LiteralValue, LiteralParseError> {
+		match self.0.kind() {
+			decorate::Syntax::IntLit => self.int_value(),
+			decorate::Syntax::FloatLit => self.float_value(),
+			decorate::Syntax::StringLit => {
+				Ok(LiteralValue::String(unescape_string(self.get_string())))
+			}
+			decorate::Syntax::NameLit => Ok(LiteralValue::Name(self.get_name().to_string())),
+			decorate::Syntax::KwTrue => Ok(LiteralValue::Bool(true)),
+			decorate::Syntax::KwFalse => Ok(LiteralValue::Bool(false)),
+			_ => Err(LiteralParseError::NotLiteral),
+		}
+	}
 }
 
 impl LitToken<cvarinfo::Syntax> {
@@ -203,21 +235,172 @@ impl<L: LangExt> LitToken<L> {
 		u64::from_str_radix(&text[start..end], radix).map(|u| (u, suffix))
 	}
 
+	/// Unlike [`Self::parse_int`], this respects C-style octal literals
+	/// (i.e. those with a leading zero), and ignores suffixes.
+	fn int_value(&self) -> Result<LiteralValue, LiteralParseError> {
+		let text = self.0.text().trim_end_matches(['u', 'U', 'l', 'L']);
+
+		let (digits, radix) =
+			if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+				(hex, 16)
+			} else if text.len() > 1 && text.starts_with('0') {
+				(&text[1..], 8)
+			} else {
+				(text, 10)
+			};
+
+		match i64::from_str_radix(digits, radix) {
+			Ok(i) => Ok(LiteralValue::Int(i)),
+			Err(err) => match err.kind() {
+				IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+					Err(LiteralParseError::Overflow)
+				}
+				_ => Err(LiteralParseError::Int(err)),
+			},
+		}
+	}
+
+	fn float_value(&self) -> Result<LiteralValue, LiteralParseError> {
+		self.parse_float()
+			.map(LiteralValue::Float)
+			.map_err(LiteralParseError::Float)
+	}
+
 	#[must_use]
 	fn get_name(&self) -> &str {
-		let text = self.0.text();
-		let start = text.chars().position(|c| c == '\'').unwrap();
-		let end = text.chars().rev().position(|c| c == '\'').unwrap();
-		text.get((start + 1)..(text.len() - end - 1)).unwrap()
+		Self::trim_delimiters(self.0.text(), '\'')
 	}
 
 	#[must_use]
 	fn get_string(&self) -> &str {
-		let text = self.0.text();
-		let start = text.chars().position(|c| c == '"').unwrap();
-		let end = text.chars().rev().position(|c| c == '"').unwrap();
-		text.get((start + 1)..(text.len() - end - 1)).unwrap()
+		Self::trim_delimiters(self.0.text(), '"')
 	}
+
+	/// Yields an empty string if `text` is not delimited by two `delim`s,
+	/// which a well-formed syntax tree never produces.
+	#[must_use]
+	fn trim_delimiters(text: &str, delim: char) -> &str {
+		match (text.find(delim), text.rfind(delim)) {
+			(Some(start), Some(end)) if start < end => &text[(start + 1)..end],
+			_ => "",
+		}
+	}
+}
+
+/// See [`zscript::ast::Literal::value`] and [`decorate::ast::Literal::value`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiteralValue {
+	Int(i64),
+	Float(f64),
+	/// Already [unescaped](unescape_string).
+	String(String),
+	/// Without delimiting single quotes.
+	Name(String),
+	Bool(bool),
+	Null,
+}
+
+/// See [`LiteralValue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiteralParseError {
+	/// An integer literal's value does not fit into an [`i64`].
+	Overflow,
+	Int(ParseIntError),
+	Float(ParseFloatError),
+	/// The token is not a literal, which a well-formed syntax tree never produces.
+	NotLiteral,
+}
+
+impl std::error::Error for LiteralParseError {}
+
+impl std::fmt::Display for LiteralParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Overflow => write!(f, "integer literal is too large"),
+			Self::Int(err) => write!(f, "malformed integer literal: {err}"),
+			Self::Float(err) => write!(f, "malformed float literal: {err}"),
+			Self::NotLiteral => write!(f, "token is not a literal"),
+		}
+	}
+}
+
+/// Resolves escape sequences in the contents of a string literal
+/// (i.e. with its delimiting double quotes already removed), as per GZDoom's
+/// [`strbin`](https://github.com/ZDoom/gzdoom/blob/master/src/common/utility/cmdlib.cpp).
+///
+/// `\c` becomes the text color escape character (`\x1C`). Octal escapes take up to
+/// three digits, and hexadecimal escapes (`\x`) up to two. Unrecognized escape
+/// sequences are left as-is.
+#[must_use]
+pub fn unescape_string(text: &str) -> String {
+	let mut ret = String::with_capacity(text.len());
+	let mut chars = text.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			ret.push(c);
+			continue;
+		}
+
+		let Some(escaped) = chars.next() else {
+			ret.push('\\');
+			break;
+		};
+
+		match escaped {
+			'a' => ret.push('\x07'),
+			'b' => ret.push('\x08'),
+			'c' => ret.push('\x1C'),
+			'f' => ret.push('\x0C'),
+			'n' => ret.push('\n'),
+			'r' => ret.push('\r'),
+			't' => ret.push('\t'),
+			'v' => ret.push('\x0B'),
+			'?' | '\\' | '\'' | '"' => ret.push(escaped),
+			'x' => {
+				let mut val = 0;
+				let mut digits = 0;
+
+				while let Some(d) = chars.peek().and_then(|c| c.to_digit(16)) {
+					if digits == 2 {
+						break;
+					}
+
+					val = (val * 16) + d;
+					digits += 1;
+					chars.next();
+				}
+
+				if digits == 0 {
+					ret.push_str("\\x");
+				} else {
+					ret.push(char::from(val as u8));
+				}
+			}
+			'0'..='7' => {
+				let mut val = escaped.to_digit(8).unwrap();
+				let mut digits = 1;
+
+				while let Some(d) = chars.peek().and_then(|c| c.to_digit(8)) {
+					if digits == 3 {
+						break;
+					}
+
+					val = (val * 8) + d;
+					digits += 1;
+					chars.next();
+				}
+
+				ret.push(char::from(val as u8));
+			}
+			other => {
+				ret.push('\\');
+				ret.push(other);
+			}
+		}
+	}
+
+	ret
 }
 
 /// See [`LitToken::int`].
@@ -264,4 +447,47 @@ mod test {
 
 		assert_eq!(lit_tok.int(), Some(Ok((1234567890, IntSuffix::UL))));
 	}
+
+	#[test]
+	fn literal_values() {
+		fn value(source: &str) -> Result<LiteralValue, LiteralParseError> {
+			let ptree = crate::parse(
+				source,
+				zdoom::zscript::parse::expr,
+				zdoom::lex::Context::ZSCRIPT_LATEST,
+			);
+
+			crate::testing::assert_no_errors(&ptree);
+			ast::Literal::cast(ptree.cursor()).unwrap().value()
+		}
+
+		assert_eq!(value("1234"), Ok(LiteralValue::Int(1234)));
+		assert_eq!(value("0x7Fu"), Ok(LiteralValue::Int(127)));
+		assert_eq!(value("0755"), Ok(LiteralValue::Int(493)));
+		assert_eq!(value("0"), Ok(LiteralValue::Int(0)));
+		assert_eq!(
+			value("0xFFFFFFFFFFFFFFFF"),
+			Err(LiteralParseError::Overflow)
+		);
+		assert_eq!(value("0.5f"), Ok(LiteralValue::Float(0.5)));
+		assert_eq!(
+			value("'Actor'"),
+			Ok(LiteralValue::Name("Actor".to_string()))
+		);
+		assert_eq!(value("true"), Ok(LiteralValue::Bool(true)));
+		assert_eq!(value("null"), Ok(LiteralValue::Null));
+
+		assert_eq!(
+			value(r#""lorem\tipsum\"" "\x41\101\cD""#),
+			Ok(LiteralValue::String("lorem\tipsum\"AA\x1CD".to_string()))
+		);
+	}
+
+	#[test]
+	fn unescape() {
+		assert_eq!(unescape_string(r"\\\n\?"), "\\\n?");
+		assert_eq!(unescape_string(r"\x4a\x4"), "J\x04");
+		assert_eq!(unescape_string(r"\xZ\q\"), "\\xZ\\q\\");
+		assert_eq!(unescape_string(r"\0101"), "\x081");
+	}
 }
@@ -35,14 +35,9 @@ pub fn definition(p: &mut Parser<Syntax>) {
 	flag(p);
 	trivia_1plus(p);
 
-	while !p.at_any(&[
-		Token::KwInt,
-		Token::KwFloat,
-		Token::KwBool,
-		Token::KwColor,
-		Token::KwString,
-	]) && !p.eof()
-	{
+	// (RAT) Type keywords are not identifiers, and `flag` always advances
+	// when looking at an identifier, so this loop always terminates.
+	while p.at(Token::Ident) {
 		flag(p);
 		trivia_1plus(p);
 	}
@@ -72,23 +67,32 @@ pub fn definition(p: &mut Parser<Syntax>) {
 	p.close(def, Syntax::Definition);
 }
 
+/// Raises an error and advances if not looking at a flag keyword.
 fn flag(p: &mut Parser<Syntax>) {
-	p.expect_any_str_nc(
-		&[
-			(Token::Ident, "server", Syntax::KwServer),
-			(Token::Ident, "user", Syntax::KwUser),
-			(Token::Ident, "nosave", Syntax::KwNoSave),
-			(Token::Ident, "noarchive", Syntax::KwNoArchive),
-			(Token::Ident, "cheat", Syntax::KwCheat),
-			(Token::Ident, "latch", Syntax::KwLatch),
-		],
+	const FLAGS: &[(Token, &str, Syntax)] = &[
+		(Token::Ident, "server", Syntax::KwServer),
+		(Token::Ident, "user", Syntax::KwUser),
+		(Token::Ident, "nosave", Syntax::KwNoSave),
+		(Token::Ident, "noarchive", Syntax::KwNoArchive),
+		(Token::Ident, "cheat", Syntax::KwCheat),
+		(Token::Ident, "latch", Syntax::KwLatch),
+	];
+
+	for &(token, string, syn) in FLAGS {
+		if p.eat_str_nc(token, string, syn) {
+			return;
+		}
+	}
+
+	p.advance_with_error(
+		Syntax::from(p.nth(0)),
 		&[&[
 			"`server` or `user` or `nosave`",
 			"`nosave` or `noarchive` or `cheat` or `latch`",
 			"whitespace",
 			"a comment",
 		]],
-	)
+	);
 }
 
 /// Builds a [`Syntax::DefaultDef`] node.
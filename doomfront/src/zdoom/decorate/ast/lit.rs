@@ -1,6 +1,9 @@
 //! AST nodes for representing literals.
 
-use crate::{simple_astnode, zdoom::ast::LitToken};
+use crate::{
+	simple_astnode,
+	zdoom::ast::{LitToken, LiteralParseError, LiteralValue},
+};
 
 use super::{Syntax, SyntaxNode};
 
@@ -16,4 +19,8 @@ impl Literal {
 	pub fn token(&self) -> LitToken<Syntax> {
 		LitToken::new(self.0.first_token().unwrap())
 	}
+
+	pub fn value(&self) -> Result<LiteralValue, LiteralParseError> {
+		self.token().value()
+	}
 }
@@ -231,7 +231,12 @@ fn states_usage(p: &mut Parser<Syntax>) {
 	trivia_0plus(p);
 
 	while !p.at(Token::ParenR) && !p.eof() {
-		p.expect(Token::Comma, Syntax::Comma, &[&["`,`", "`)`"]]);
+		if !p.eat(Token::Comma, Syntax::Comma) {
+			p.advance_with_error(Syntax::from(p.nth(0)), &[&["`,`", "`)`"]]);
+			trivia_0plus(p);
+			continue;
+		}
+
 		trivia_0plus(p);
 		actor_item_overlay_weapon(p);
 		trivia_0plus(p);
@@ -259,10 +264,9 @@ fn stateflow_goto(p: &mut Parser<Syntax>) {
 }
 
 /// Builds a [`Syntax::StateDef`] node.
-/// Note that this starts at the trivia after the sprite.
+/// Note that this starts at the trivia after the sprite (or, given malformed
+/// input, wherever the sprite's merged token ended).
 fn state_def(p: &mut Parser<Syntax>, state: OpenMark) {
-	p.assert_at_if(|token| token.is_trivia());
-
 	trivia_0plus(p);
 	state_frames(p);
 	trivia_0plus(p);
@@ -321,7 +325,12 @@ fn state_duration(p: &mut Parser<Syntax>) {
 	if p.at_str_nc(Token::Ident, "random") {
 		p.advance(Syntax::Ident);
 		trivia_0plus(p);
-		super::expr::arg_list(p);
+
+		if p.at(Token::ParenL) {
+			super::expr::arg_list(p);
+		} else {
+			p.expect(Token::ParenL, Syntax::ParenL, &[&["`(`"]]);
+		}
 	} else {
 		sign_lit(p);
 	}
@@ -448,6 +457,10 @@ fn action_function(p: &mut Parser<Syntax>) {
 }
 
 pub(super) fn statement(p: &mut Parser<Syntax>) {
+	if !p.nest(Syntax::Unknown) {
+		return;
+	}
+
 	match p.nth(0) {
 		Token::KwFor => {
 			let mark = p.open();
@@ -514,6 +527,8 @@ pub(super) fn statement(p: &mut Parser<Syntax>) {
 			p.close(mark, Syntax::ExprStat);
 		}
 	}
+
+	p.unnest();
 }
 
 fn compound_statement(p: &mut Parser<Syntax>) {
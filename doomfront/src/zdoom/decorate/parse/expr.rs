@@ -11,6 +11,10 @@ pub(super) fn expr(p: &mut Parser<Syntax>) {
 }
 
 fn recur(p: &mut Parser<Syntax>, left: Token) {
+	if !p.nest(Syntax::Unknown) {
+		return;
+	}
+
 	let mut lhs = primary(p);
 
 	loop {
@@ -77,6 +81,8 @@ fn recur(p: &mut Parser<Syntax>, left: Token) {
 			break;
 		}
 	}
+
+	p.unnest();
 }
 
 fn primary(p: &mut Parser<Syntax>) -> CloseMark {
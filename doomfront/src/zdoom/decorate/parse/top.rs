@@ -66,6 +66,12 @@ pub(super) fn enum_def(p: &mut Parser<Syntax>) {
 	let mut trailing_comma = false;
 
 	while !p.at(Token::BraceR) && !p.eof() {
+		if !p.at_if(is_ident_lax) {
+			p.advance_with_error(Syntax::from(p.nth(0)), &[&["an identifier", "`}`"]]);
+			trivia_0plus(p);
+			continue;
+		}
+
 		variant(p);
 		trivia_0plus(p);
 
@@ -108,6 +114,12 @@ pub(super) fn damage_type(p: &mut Parser<Syntax>) {
 	trivia_0plus(p);
 
 	while !p.at(Token::BraceR) && !p.eof() {
+		if !p.at_if(is_ident_xlax) {
+			p.advance_with_error(Syntax::from(p.nth(0)), &[&["an identifier", "`}`"]]);
+			trivia_0plus(p);
+			continue;
+		}
+
 		let kvp = p.open();
 		ident_xlax(p);
 		trivia_0plus(p);
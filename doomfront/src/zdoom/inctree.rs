@@ -19,6 +19,8 @@ pub struct IncludeTree<L: LangExt<Token = Token>> {
 	pub files: Vec<FileParseTree<L>>,
 	/// Paths of files that were included, but could not be found.
 	pub missing: Vec<PathBuf>,
+	/// The path passed to the constructor, even if it was [missing](Self::missing).
+	root: PathBuf,
 }
 
 impl<L: LangExt<Token = Token>> IncludeTree<L> {
@@ -37,7 +39,8 @@ impl<L: LangExt<Token = Token>> IncludeTree<L> {
 	{
 		let mut all_files = vec![];
 		let mut missing = vec![];
-		let mut queue = VecDeque::from([path.as_ref().to_path_buf()]);
+		let root = path.as_ref().to_path_buf();
+		let mut queue = VecDeque::from([root.clone()]);
 
 		while let Some(queued) = queue.pop_front() {
 			let source = match filesystem(&queued) {
@@ -84,6 +87,7 @@ impl<L: LangExt<Token = Token>> IncludeTree<L> {
 		Self {
 			files: all_files,
 			missing,
+			root,
 		}
 	}
 
@@ -105,8 +109,9 @@ impl<L: LangExt<Token = Token>> IncludeTree<L> {
 		use parking_lot::Mutex;
 		use rayon::prelude::*;
 
+		let root = path.as_ref().to_path_buf();
 		let queue = SegQueue::<PathBuf>::default();
-		queue.push(path.as_ref().to_path_buf());
+		queue.push(root.clone());
 		let missing = Mutex::new(vec![]);
 		let all_files = Mutex::new(vec![]);
 
@@ -169,9 +174,29 @@ impl<L: LangExt<Token = Token>> IncludeTree<L> {
 		Self {
 			files: all_files.into_inner(),
 			missing: missing.into_inner(),
+			root,
 		}
 	}
 
+	/// The path of the file from which traversal started.
+	#[must_use]
+	pub fn root_file_path(&self) -> &Path {
+		&self.root
+	}
+
+	/// Does not count [missing](Self::missing) files.
+	#[must_use]
+	pub fn file_count(&self) -> usize {
+		self.files.len()
+	}
+
+	/// Yields the paths of all [`Self::files`] in the order they were parsed.
+	/// The root always comes first (if it was found), but note that the rest
+	/// are not in include order if this tree was built by [`Self::new_par`].
+	pub fn file_paths(&self) -> impl Iterator<Item = &Path> {
+		self.files.iter().map(|fptree| fptree.path())
+	}
+
 	#[must_use]
 	pub fn any_errors(&self) -> bool {
 		self.files.iter().any(|fptree| fptree.errors.is_empty())
@@ -246,6 +271,14 @@ mod test {
 		);
 
 		assert!(inctree.missing.is_empty());
+		assert_eq!(inctree.root_file_path(), Path::new("file/a.zs"));
+		assert_eq!(inctree.file_count(), 3);
+
+		let paths: Vec<_> = inctree.file_paths().collect();
+		assert_eq!(
+			paths,
+			["file/a.zs", "file/b.zs", "file/c.zs"].map(Path::new)
+		);
 	}
 
 	#[test]
@@ -261,5 +294,7 @@ mod test {
 		);
 
 		assert!(inctree.missing.is_empty());
+		assert_eq!(inctree.file_count(), 3);
+		assert_eq!(inctree.file_paths().next(), Some(Path::new("file/a.zs")));
 	}
 }
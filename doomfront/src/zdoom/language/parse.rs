@@ -33,7 +33,7 @@ pub fn file(p: &mut Parser<Syntax>) {
 
 /// Builds a [`Syntax::KeyValuePair`] node.
 pub fn key_val_pair(p: &mut Parser<Syntax>) {
-	p.assert_at_if(|t| matches!(t, Token::Ident | Token::Dollar));
+	p.assert_at_if(|t| matches!(t, Token::Ident | Token::Dollar) || t.is_keyword());
 	let kvp = p.open();
 
 	if p.at(Token::Dollar) {
@@ -49,11 +49,7 @@ pub fn key_val_pair(p: &mut Parser<Syntax>) {
 	p.expect(Token::Eq, Syntax::Eq, &[&["`=`"]]);
 	string(p);
 
-	loop {
-		if p.at_any(&[Token::Eof, Token::Semicolon, Token::Ident, Token::BracketL]) {
-			break;
-		}
-
+	while p.find(0, |t| !t.is_trivia()) == Token::StringLit {
 		string(p);
 	}
 
@@ -115,10 +111,12 @@ pub fn header(p: &mut Parser<Syntax>) {
 					break;
 				}
 
-				return p.advance_with_error(
+				p.advance_with_error(
 					Syntax::from(t),
 					&[&["`~`", "`*`", "`default`", "an identifier"]],
 				);
+
+				break;
 			}
 		}
 	}
@@ -378,6 +378,21 @@ impl Token {
 		u > (Self::__FirstKw as u8) && u < (Self::__LastKw as u8)
 	}
 
+	/// Glyphs are every token between the keywords and [`Self::Comment`].
+	#[must_use]
+	pub fn is_glyph(self) -> bool {
+		let u = self as u8;
+		u > (Self::__LastKw as u8) && u < (Self::Comment as u8)
+	}
+
+	#[must_use]
+	pub fn is_literal(self) -> bool {
+		matches!(
+			self,
+			Self::FloatLit | Self::IntLit | Self::NameLit | Self::StringLit
+		)
+	}
+
 	#[must_use]
 	pub fn is_trivia(self) -> bool {
 		matches!(
@@ -523,6 +538,23 @@ States (actor, overlay) {
 		assert_eq!(lexer.next().unwrap().unwrap(), Token::DocComment);
 	}
 
+	#[test]
+	fn predicates() {
+		assert!(Token::KwAbstract.is_keyword());
+		assert!(Token::KwWhile.is_keyword());
+		assert!(!Token::Ident.is_keyword());
+
+		assert!(Token::Ampersand.is_glyph());
+		assert!(Token::ThinArrow.is_glyph());
+		assert!(!Token::KwWhile.is_glyph());
+		assert!(!Token::Comment.is_glyph());
+
+		assert!(Token::NameLit.is_literal());
+		assert!(Token::StringLit.is_literal());
+		assert!(!Token::Ident.is_literal());
+		assert!(!Token::KwTrue.is_literal());
+	}
+
 	#[test]
 	fn unterminated_multiline_comment() {
 		const SAMPLE: &str = "/*";
@@ -2,6 +2,7 @@
 //!
 //! [MAPINFO]: https://zdoom.org/wiki/MAPINFO
 
+pub mod ast;
 pub mod parse;
 mod syntax;
 
@@ -0,0 +1,90 @@
+//! Abstract syntax tree nodes.
+//!
+//! Only a subset of the tree currently has wrappers.
+
+use rowan::ast::AstNode;
+
+use crate::simple_astnode;
+
+use super::{Syntax, SyntaxNode, SyntaxToken};
+
+/// Wraps a node tagged [`Syntax::SkillDef`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SkillDef(SyntaxNode);
+
+simple_astnode!(Syntax, SkillDef, Syntax::SkillDef);
+
+impl SkillDef {
+	/// The identifier following the `skill` keyword.
+	/// The returned token is tagged [`Syntax::Ident`].
+	#[must_use]
+	pub fn name(&self) -> Option<SyntaxToken> {
+		self.0.children_with_tokens().find_map(|elem| {
+			elem.into_token()
+				.filter(|token| token.kind() == Syntax::Ident)
+		})
+	}
+
+	pub fn properties(&self) -> impl Iterator<Item = Property> {
+		self.0.children().filter_map(Property::cast)
+	}
+}
+
+/// Wraps a node tagged [`Syntax::Property`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Property(SyntaxNode);
+
+simple_astnode!(Syntax, Property, Syntax::Property);
+
+impl Property {
+	/// The returned token is tagged [`Syntax::Ident`] or [`Syntax::IntLit`].
+	#[must_use]
+	pub fn name(&self) -> SyntaxToken {
+		self.0.first_token().unwrap()
+	}
+
+	/// Empty for properties without a `=`, which usually act as flags.
+	pub fn values(&self) -> impl Iterator<Item = Value> {
+		self.0.children().filter_map(Value::cast)
+	}
+}
+
+/// Wraps a node tagged [`Syntax::Value`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Value(SyntaxNode);
+
+simple_astnode!(Syntax, Value, Syntax::Value);
+
+impl Value {
+	/// The literal or identifier, not including any leading [`Syntax::Minus`].
+	#[must_use]
+	pub fn token(&self) -> SyntaxToken {
+		self.0.last_token().unwrap()
+	}
+
+	/// Whether [`Self::token`] is preceded by a [`Syntax::Minus`].
+	#[must_use]
+	pub fn is_negated(&self) -> bool {
+		self.0
+			.first_token()
+			.is_some_and(|token| token.kind() == Syntax::Minus)
+	}
+
+	/// Returns `None` if this is not an integer or floating-point literal,
+	/// or if such a literal is malformed.
+	#[must_use]
+	pub fn number(&self) -> Option<f64> {
+		let token = self.token();
+
+		let num = match token.kind() {
+			Syntax::IntLit => token.text().parse::<i64>().ok()? as f64,
+			Syntax::FloatLit => token.text().trim_end_matches(['f', 'F']).parse().ok()?,
+			_ => return None,
+		};
+
+		Some(if self.is_negated() { -num } else { num })
+	}
+}
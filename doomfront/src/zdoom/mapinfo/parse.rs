@@ -129,12 +129,18 @@ fn block(p: &mut Parser<Syntax>, node: Syntax, kw: Syntax, post_kw: fn(&mut Pars
 	trivia_0plus(p);
 
 	while !p.at(Token::BraceR) && !p.eof() {
+		let token = p.nth(0);
 		let lookahead = p.find(1, |token| !token.is_trivia());
 
-		if lookahead == Token::BraceL {
+		if token == Token::Ident && lookahead == Token::BraceL {
 			sub_block(p);
-		} else {
+		} else if matches!(token, Token::Ident | Token::IntLit) || token.is_keyword() {
 			property(p);
+		} else {
+			p.advance_with_error(
+				Syntax::from(token),
+				&[&["an identifier", "an integer", "`}`"]],
+			);
 		}
 
 		trivia_0plus(p);
@@ -230,7 +236,13 @@ fn property_values(p: &mut Parser<Syntax>) {
 
 fn sub_block(p: &mut Parser<Syntax>) {
 	p.debug_assert_at(Token::Ident);
+
+	if !p.nest(Syntax::Unknown) {
+		return;
+	}
+
 	block(p, Syntax::SubBlock, Syntax::Ident, |_| {});
+	p.unnest();
 }
 
 fn trivia(p: &mut Parser<Syntax>) -> bool {
@@ -251,7 +263,10 @@ fn trivia_0plus(p: &mut Parser<Syntax>) {
 mod test {
 	use crate::{
 		testing::*,
-		zdoom::{self, mapinfo::ParseTree},
+		zdoom::{
+			self,
+			mapinfo::{ast, ParseTree},
+		},
 	};
 
 	use super::*;
@@ -270,6 +285,42 @@ DoomEdNums
 		prettyprint_maybe(ptree.cursor());
 	}
 
+	#[test]
+	fn skill_ast() {
+		const SAMPLE: &str = r#"
+skill lorem
+{
+	DamageFactor = 1.5
+	SpawnFilter = 4
+	FastMonsters
+	Name = "Lorem Ipsum"
+}
+"#;
+
+		let ptree: ParseTree = crate::parse(SAMPLE, file, zdoom::lex::Context::NON_ZSCRIPT);
+		assert_no_errors(&ptree);
+
+		let skilldef = ptree
+			.cursor()
+			.children()
+			.find_map(<ast::SkillDef as rowan::ast::AstNode>::cast)
+			.unwrap();
+
+		assert_eq!(skilldef.name().unwrap().text(), "lorem");
+
+		let props = skilldef.properties().collect::<Vec<_>>();
+		assert_eq!(props.len(), 4);
+		assert_eq!(props[0].name().text(), "DamageFactor");
+		assert_eq!(props[0].values().next().unwrap().number(), Some(1.5));
+		assert_eq!(props[1].values().next().unwrap().number(), Some(4.0));
+		assert_eq!(props[2].name().text(), "FastMonsters");
+		assert_eq!(props[2].values().count(), 0);
+		assert_eq!(
+			props[3].values().next().unwrap().token().text(),
+			"\"Lorem Ipsum\""
+		);
+	}
+
 	#[test]
 	#[ignore]
 	fn with_sample_data() {
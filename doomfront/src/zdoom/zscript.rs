@@ -6,7 +6,10 @@
 
 pub mod ast;
 pub mod autofmt;
+pub mod ceval;
+pub mod mixin;
 pub mod parse;
+pub mod semtok;
 mod syntax;
 
 pub use syntax::Syntax;
@@ -343,7 +343,7 @@ impl VersionDirective {
 	/// [`IntErrorKind::Empty`] is returned if the expected string literal is absent.
 	pub fn version(&self) -> Result<zdoom::Version, IntErrorKind> {
 		let lit = self.string().map_err(|_| IntErrorKind::Empty)?;
-		let text = lit.string().unwrap();
+		let text = lit.string().ok_or(IntErrorKind::Empty)?;
 
 		if text.is_empty() {
 			return Err(IntErrorKind::Empty);
@@ -430,7 +430,7 @@ impl VersionQual {
 	/// [`IntErrorKind::Empty`] is returned if the expected string literal is absent.
 	pub fn version(&self) -> Result<zdoom::Version, IntErrorKind> {
 		let lit = self.string().map_err(|_| IntErrorKind::Empty)?;
-		let text = lit.string().unwrap();
+		let text = lit.string().ok_or(IntErrorKind::Empty)?;
 
 		if text.is_empty() {
 			return Err(IntErrorKind::Empty);
@@ -680,4 +680,177 @@ mod test {
 			assert_eq!(incpath, Path::new(EXPECTED[i]));
 		}
 	}
+
+	#[test]
+	fn return_types() {
+		fn return_types(source: &str) -> ReturnTypes {
+			let ptree = crate::parse(
+				source,
+				zdoom::zscript::parse::file,
+				zdoom::lex::Context::ZSCRIPT_LATEST,
+			);
+
+			crate::testing::assert_no_errors(&ptree);
+
+			ptree
+				.cursor()
+				.descendants()
+				.find_map(FunctionDecl::cast)
+				.unwrap()
+				.return_types()
+		}
+
+		let rettypes = return_types("class Foo { int, bool, double Foo() {} }");
+		assert_eq!(rettypes.count(), 3);
+		assert_eq!(rettypes.excess(), None);
+
+		let names = rettypes
+			.types()
+			.map(|t| t.syntax().text().to_string())
+			.collect::<Vec<_>>();
+
+		assert_eq!(names, ["int", "bool", "double"]);
+
+		let source = "class Foo { int, int, int, int, bool, double Foo() {} }";
+		let rettypes = return_types(source);
+		assert_eq!(rettypes.count(), 6);
+
+		let excess = rettypes.excess().unwrap();
+		assert_eq!(&source[excess], "bool, double");
+	}
+
+	#[test]
+	fn type_ref_casts() {
+		let ptree = crate::parse(
+			"class Foo { void f(int a, Array<Actor> b, class<Actor> c, readonly<Foo> d, Foo.Bar e) {} }",
+			zdoom::zscript::parse::file,
+			zdoom::lex::Context::ZSCRIPT_LATEST,
+		);
+
+		crate::testing::assert_no_errors(&ptree);
+
+		let types = ptree
+			.cursor()
+			.descendants()
+			.filter_map(Parameter::cast)
+			.map(|param| param.type_spec())
+			.collect::<Vec<_>>();
+
+		assert!(types[0].as_primitive_type().is_some());
+		assert!(types[0].as_class_type().is_none());
+		assert!(types[1].as_dyn_array_type().is_some());
+		assert!(types[2].as_class_type().is_some());
+		assert!(types[3].as_readonly_type().is_some());
+		assert!(types[4].as_ident_chain_type().is_some());
+		assert!(types[4].as_map_type().is_none());
+		assert!(types[4].as_native_type().is_none());
+	}
+
+	#[test]
+	fn binary_op_kinds() {
+		fn op_kind(source: &str) -> BinaryOp {
+			let ptree = crate::parse(
+				source,
+				zdoom::zscript::parse::expr,
+				zdoom::lex::Context::ZSCRIPT_LATEST,
+			);
+
+			crate::testing::assert_no_errors(&ptree);
+			BinExpr::cast(ptree.cursor()).unwrap().operator_kind()
+		}
+
+		assert_eq!(op_kind("a + b"), BinaryOp::Add);
+		assert_eq!(op_kind("a >>>= 1"), BinaryOp::UShrAssign);
+		assert_eq!(op_kind("a ~== b"), BinaryOp::ApproxEq);
+		assert_eq!(op_kind("a <>= b"), BinaryOp::ThreeWay);
+		assert_eq!(op_kind("a .. b"), BinaryOp::Concat);
+		assert_eq!(op_kind("a cross b"), BinaryOp::Cross);
+		assert_eq!(op_kind("a ** b"), BinaryOp::Pow);
+		assert_eq!(op_kind("a is 'Actor'"), BinaryOp::Is);
+		// The loosest-binding operator is the root.
+		assert_eq!(op_kind("a = b || c * d"), BinaryOp::Assign);
+
+		assert!(BinaryOp::BitXorAssign.is_assign());
+		assert!(!BinaryOp::NotEq.is_assign());
+	}
+
+	#[test]
+	fn unary_op_kinds() {
+		fn parse(source: &str) -> SyntaxNode {
+			let ptree = crate::parse(
+				source,
+				zdoom::zscript::parse::expr,
+				zdoom::lex::Context::ZSCRIPT_LATEST,
+			);
+
+			crate::testing::assert_no_errors(&ptree);
+			ptree.cursor()
+		}
+
+		let prefix = |source| PrefixExpr::cast(parse(source)).unwrap().operator_kind();
+		let postfix = |source| PostfixExpr::cast(parse(source)).unwrap().operator_kind();
+
+		assert_eq!(prefix("-a"), UnaryOp::Neg);
+		assert_eq!(prefix("!a"), UnaryOp::Not);
+		assert_eq!(prefix("~a"), UnaryOp::BitNot);
+		assert_eq!(prefix("++a"), UnaryOp::PreInc);
+		assert_eq!(prefix("sizeof a"), UnaryOp::SizeOf);
+		assert_eq!(prefix("alignof a"), UnaryOp::AlignOf);
+		assert_eq!(postfix("a++"), PostfixOp::PostInc);
+		assert_eq!(postfix("a--"), PostfixOp::PostDec);
+	}
+
+	#[test]
+	fn primitive_kind_aliases() {
+		fn kind(source: &str) -> PrimitiveKind {
+			let ptree = crate::parse(
+				source,
+				zdoom::zscript::parse::core_type,
+				zdoom::lex::Context::ZSCRIPT_LATEST,
+			);
+
+			let CoreType::Primitive(prim) = CoreType::cast(ptree.cursor()).unwrap() else {
+				panic!("expected a primitive type")
+			};
+
+			prim.kind()
+		}
+
+		assert_eq!(kind("byte"), kind("uint8"));
+		assert_eq!(kind("short"), kind("int16"));
+		assert_eq!(kind("sbyte"), PrimitiveKind::Int8);
+
+		let uint8 = kind("uint8");
+		assert_eq!(uint8.bit_width(), Some(8));
+		assert!(!uint8.is_signed());
+		assert!(!uint8.is_float());
+		assert!(kind("double").is_float());
+		assert_eq!(kind("name").bit_width(), None);
+	}
+
+	#[test]
+	fn property_def() {
+		fn parse(source: &str) -> PropertyDef {
+			let ptree = crate::parse(
+				source,
+				zdoom::zscript::parse::property_def,
+				zdoom::lex::Context::ZSCRIPT_LATEST,
+			);
+
+			crate::testing::assert_no_errors(&ptree);
+			PropertyDef::cast(ptree.cursor()).unwrap()
+		}
+
+		let propdef = parse("property MyProp: field1, field2;");
+		assert_eq!(propdef.name().unwrap().text(), "MyProp");
+
+		let fields = propdef.backing_fields().collect::<Vec<_>>();
+		assert_eq!(fields.len(), 2);
+		assert_eq!(fields[0].text(), "field1");
+		assert_eq!(fields[1].text(), "field2");
+
+		let propdef = parse("/// Unused.\nproperty Dummy: none;");
+		assert_eq!(propdef.name().unwrap().text(), "Dummy");
+		assert_eq!(propdef.backing_fields().count(), 0);
+	}
 }
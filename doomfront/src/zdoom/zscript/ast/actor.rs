@@ -77,6 +77,10 @@ impl PropertyDef {
 			.ok_or(AstError::Missing)
 	}
 
+	/// The fields which a default block setting of this property assigns to,
+	/// in the order its arguments get assigned to them.
+	/// Empty if this property was declared with `none`.
+	///
 	/// Yielded tokens are always tagged [`Syntax::Ident`].
 	pub fn backing_fields(&self) -> impl Iterator<Item = SyntaxToken> {
 		self.0
@@ -110,6 +114,14 @@ impl DefaultBlock {
 			_ => None,
 		})
 	}
+
+	pub fn property_settings(&self) -> impl Iterator<Item = PropertySetting> {
+		self.0.children().filter_map(PropertySetting::cast)
+	}
+
+	pub fn flag_settings(&self) -> impl Iterator<Item = FlagSetting> {
+		self.0.children().filter_map(FlagSetting::cast)
+	}
 }
 
 // DefaultInnard ///////////////////////////////////////////////////////////////
@@ -160,17 +172,13 @@ pub struct FlagSetting(pub(super) SyntaxNode);
 simple_astnode!(Syntax, FlagSetting, Syntax::FlagSetting);
 
 impl FlagSetting {
+	/// `true` for `+`; `false` for `-`.
 	#[must_use]
-	pub fn is_adding(&self) -> bool {
+	pub fn is_set(&self) -> bool {
 		self.0.first_token().unwrap().kind() == Syntax::Plus
 	}
 
-	#[must_use]
-	pub fn is_removing(&self) -> bool {
-		self.0.first_token().unwrap().kind() == Syntax::Minus
-	}
-
-	pub fn name(&self) -> AstResult<IdentChain> {
+	pub fn flag_name(&self) -> AstResult<IdentChain> {
 		match self.syntax().last_child() {
 			Some(node) => IdentChain::cast(node).ok_or(AstError::Incorrect),
 			None => Err(AstError::Missing),
@@ -188,13 +196,15 @@ pub struct PropertySetting(pub(super) SyntaxNode);
 simple_astnode!(Syntax, PropertySetting, Syntax::PropertySetting);
 
 impl PropertySetting {
-	#[must_use]
-	pub fn name(&self) -> IdentChain {
-		IdentChain::cast(self.0.first_child().unwrap()).unwrap()
+	pub fn name(&self) -> AstResult<IdentChain> {
+		match self.0.first_child() {
+			Some(node) => IdentChain::cast(node).ok_or(AstError::Incorrect),
+			None => Err(AstError::Missing),
+		}
 	}
 
 	/// Mind that the returned iterator may yield no items.
-	pub fn exprs(&self) -> impl Iterator<Item = Expr> {
+	pub fn values(&self) -> impl Iterator<Item = Expr> {
 		self.0.children().skip(1).filter_map(Expr::cast)
 	}
 }
@@ -348,33 +358,20 @@ impl StateFlow {
 			_ => unreachable!(),
 		}
 
-		let name = self.0.children().find_map(IdentChain::cast).unwrap();
-
-		let mut prev = name
-			.syntax()
-			.siblings_with_tokens(rowan::Direction::Prev)
-			.skip_while(|elem| elem.kind() != Syntax::Colon2);
-		let mut next = name
-			.syntax()
-			.siblings_with_tokens(rowan::Direction::Next)
-			.skip_while(|elem| elem.kind() != Syntax::Plus);
-
-		let scope = prev.find_map(|elem| {
-			elem.into_token()
-				.filter(|token| matches!(token.kind(), Syntax::Ident | Syntax::KwSuper))
-		});
-
-		let offset = next.find_map(|elem| {
-			elem.into_token()
-				.filter(|token| token.kind() == Syntax::IntLit)
-		});
+		let goto = self.goto_offset().unwrap();
 
 		StateFlowKind::Goto {
-			scope,
-			name,
-			offset: offset.map(LitToken::new),
+			scope: goto.scope(),
+			name: goto.target().unwrap(),
+			offset: goto.offset(),
 		}
 	}
+
+	/// Only `Some` if this is a `goto`.
+	#[must_use]
+	pub fn goto_offset(&self) -> Option<GotoOffset> {
+		self.0.children().find_map(GotoOffset::cast)
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -393,6 +390,60 @@ pub enum StateFlowKind {
 	Wait(SyntaxToken),
 }
 
+// GotoOffset //////////////////////////////////////////////////////////////////
+
+/// Wraps a node tagged [`Syntax::GotoOffset`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GotoOffset(SyntaxNode);
+
+simple_astnode!(Syntax, GotoOffset, Syntax::GotoOffset);
+
+impl GotoOffset {
+	/// `true` for `goto Super::Label`, which must be resolved by searching
+	/// the inheritance chain starting from the parent class.
+	#[must_use]
+	pub fn uses_super(&self) -> bool {
+		self.scope()
+			.is_some_and(|token| token.kind() == Syntax::KwSuper)
+	}
+
+	/// The part before `::`, if any.
+	/// The returned token is always tagged either [`Syntax::Ident`] or [`Syntax::KwSuper`].
+	#[must_use]
+	pub fn scope(&self) -> Option<SyntaxToken> {
+		self.0
+			.children_with_tokens()
+			.take_while(|elem| elem.kind() != Syntax::IdentChain)
+			.find_map(|elem| {
+				elem.into_token()
+					.filter(|token| matches!(token.kind(), Syntax::Ident | Syntax::KwSuper))
+			})
+	}
+
+	/// The name of the state label being jumped to, after any scope qualifier.
+	pub fn target(&self) -> AstResult<IdentChain> {
+		self.0
+			.children()
+			.find_map(IdentChain::cast)
+			.ok_or(AstError::Missing)
+	}
+
+	/// The integer after `+`, if any.
+	/// The returned token is always tagged [`Syntax::IntLit`].
+	#[must_use]
+	pub fn offset(&self) -> Option<LitToken<Syntax>> {
+		self.0
+			.children_with_tokens()
+			.skip_while(|elem| elem.kind() != Syntax::Plus)
+			.find_map(|elem| {
+				elem.into_token()
+					.filter(|token| token.kind() == Syntax::IntLit)
+			})
+			.map(LitToken::new)
+	}
+}
+
 // StateDef ////////////////////////////////////////////////////////////////////
 
 /// Wraps a node tagged [`Syntax::StateDef`].
@@ -445,6 +496,24 @@ impl StateDef {
 			})
 	}
 
+	/// Shorthand for finding a [`StateQual::Light`] in [`Self::quals`].
+	#[must_use]
+	pub fn light(&self) -> Option<StateLight> {
+		self.quals().find_map(|qual| match qual {
+			StateQual::Light(light) => Some(light),
+			_ => None,
+		})
+	}
+
+	/// Shorthand for finding a [`StateQual::Offset`] in [`Self::quals`].
+	#[must_use]
+	pub fn offset(&self) -> Option<StateOffset> {
+		self.quals().find_map(|qual| match qual {
+			StateQual::Offset(offset) => Some(offset),
+			_ => None,
+		})
+	}
+
 	pub fn duration(&self) -> AstResult<Expr> {
 		self.0
 			.children()
@@ -491,6 +560,14 @@ impl StateLight {
 				.map(LitToken::new)
 		})
 	}
+
+	/// Like [`Self::lights`], but yields the tokens as-is.
+	pub fn light_names(&self) -> impl Iterator<Item = SyntaxToken> {
+		self.0.children_with_tokens().filter_map(|elem| {
+			elem.into_token()
+				.filter(|token| matches!(token.kind(), Syntax::NameLit | Syntax::StringLit))
+		})
+	}
 }
 
 /// Wraps a node tagged [`Syntax::StateOffset`].
@@ -2,7 +2,11 @@
 
 use rowan::ast::AstNode;
 
-use crate::{simple_astnode, zdoom::ast::LitToken, AstError, AstResult};
+use crate::{
+	simple_astnode,
+	zdoom::ast::{LitToken, LiteralParseError, LiteralValue},
+	AstError, AstResult,
+};
 
 use super::super::{Syntax, SyntaxNode, SyntaxToken};
 
@@ -254,6 +258,53 @@ impl BinExpr {
 		(ret0, ret1)
 	}
 
+	/// Like [`Self::operator`], but names the operation performed
+	/// rather than the token used to spell it.
+	#[must_use]
+	pub fn operator_kind(&self) -> BinaryOp {
+		match self.operator().0.kind() {
+			Syntax::Eq => BinaryOp::Assign,
+			Syntax::PlusEq => BinaryOp::AddAssign,
+			Syntax::MinusEq => BinaryOp::SubAssign,
+			Syntax::AsteriskEq => BinaryOp::MulAssign,
+			Syntax::SlashEq => BinaryOp::DivAssign,
+			Syntax::PercentEq => BinaryOp::ModAssign,
+			Syntax::AngleL2Eq => BinaryOp::ShlAssign,
+			Syntax::AngleR2Eq => BinaryOp::ShrAssign,
+			Syntax::AngleR3Eq => BinaryOp::UShrAssign,
+			Syntax::AmpersandEq => BinaryOp::BitAndAssign,
+			Syntax::PipeEq => BinaryOp::BitOrAssign,
+			Syntax::CaretEq => BinaryOp::BitXorAssign,
+			Syntax::Pipe2 => BinaryOp::LogicOr,
+			Syntax::Ampersand2 => BinaryOp::LogicAnd,
+			Syntax::Eq2 => BinaryOp::Eq,
+			Syntax::BangEq => BinaryOp::NotEq,
+			Syntax::TildeEq2 => BinaryOp::ApproxEq,
+			Syntax::AngleL => BinaryOp::Lt,
+			Syntax::AngleR => BinaryOp::Gt,
+			Syntax::AngleLEq => BinaryOp::Le,
+			Syntax::AngleREq => BinaryOp::Ge,
+			Syntax::AngleLAngleREq => BinaryOp::ThreeWay,
+			Syntax::KwIs => BinaryOp::Is,
+			Syntax::Dot2 => BinaryOp::Concat,
+			Syntax::Pipe => BinaryOp::BitOr,
+			Syntax::Caret => BinaryOp::BitXor,
+			Syntax::Ampersand => BinaryOp::BitAnd,
+			Syntax::AngleL2 => BinaryOp::Shl,
+			Syntax::AngleR2 => BinaryOp::Shr,
+			Syntax::AngleR3 => BinaryOp::UShr,
+			Syntax::Plus => BinaryOp::Add,
+			Syntax::Minus => BinaryOp::Sub,
+			Syntax::Asterisk => BinaryOp::Mul,
+			Syntax::Slash => BinaryOp::Div,
+			Syntax::Percent => BinaryOp::Mod,
+			Syntax::KwCross => BinaryOp::Cross,
+			Syntax::KwDot => BinaryOp::Dot,
+			Syntax::Asterisk2 => BinaryOp::Pow,
+			other => unreachable!("`{other:?}` is not a binary operator"),
+		}
+	}
+
 	pub fn right(&self) -> AstResult<Expr> {
 		Expr::cast(self.0.children().nth(1).ok_or(AstError::Missing)?).ok_or(AstError::Incorrect)
 	}
@@ -309,6 +360,111 @@ pub enum BinOp {
 	TildeEq2,
 }
 
+/// See [`BinExpr::operator_kind`].
+/// Ordered from loosest-binding to tightest-binding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BinaryOp {
+	/// `=`
+	Assign,
+	/// `+=`
+	AddAssign,
+	/// `-=`
+	SubAssign,
+	/// `*=`
+	MulAssign,
+	/// `/=`
+	DivAssign,
+	/// `%=`
+	ModAssign,
+	/// `<<=`
+	ShlAssign,
+	/// `>>=`
+	ShrAssign,
+	/// `>>>=`
+	UShrAssign,
+	/// `&=`
+	BitAndAssign,
+	/// `|=`
+	BitOrAssign,
+	/// `^=`
+	BitXorAssign,
+	/// `||`
+	LogicOr,
+	/// `&&`
+	LogicAnd,
+	/// `==`
+	Eq,
+	/// `!=`
+	NotEq,
+	/// `~==`
+	ApproxEq,
+	/// `<`
+	Lt,
+	/// `>`
+	Gt,
+	/// `<=`
+	Le,
+	/// `>=`
+	Ge,
+	/// `<>=`
+	ThreeWay,
+	/// `is`
+	Is,
+	/// `..`
+	Concat,
+	/// `|`
+	BitOr,
+	/// `^`
+	BitXor,
+	/// `&`
+	BitAnd,
+	/// `<<`
+	Shl,
+	/// `>>`
+	Shr,
+	/// `>>>`
+	UShr,
+	/// `+`
+	Add,
+	/// `-`
+	Sub,
+	/// `*`
+	Mul,
+	/// `/`
+	Div,
+	/// `%`
+	Mod,
+	/// `cross`
+	Cross,
+	/// `dot`
+	Dot,
+	/// `**`
+	Pow,
+}
+
+impl BinaryOp {
+	/// `true` for [`Self::Assign`] and all compound assignment operators.
+	#[must_use]
+	pub fn is_assign(self) -> bool {
+		matches!(
+			self,
+			Self::Assign
+				| Self::AddAssign
+				| Self::SubAssign
+				| Self::MulAssign
+				| Self::DivAssign
+				| Self::ModAssign
+				| Self::ShlAssign
+				| Self::ShrAssign
+				| Self::UShrAssign
+				| Self::BitAndAssign
+				| Self::BitOrAssign
+				| Self::BitXorAssign
+		)
+	}
+}
+
 // CallExpr ////////////////////////////////////////////////////////////////////
 
 /// Wraps a node tagged [`Syntax::CallExpr`].
@@ -499,6 +655,26 @@ impl Literal {
 			None
 		}
 	}
+
+	/// Adjacent string literals get [unescaped](crate::zdoom::ast::unescape_string)
+	/// and then concatenated.
+	pub fn value(&self) -> Result<LiteralValue, LiteralParseError> {
+		let Some(strings) = self.strings() else {
+			return self.token().value();
+		};
+
+		let mut ret = String::new();
+
+		for token in strings {
+			let LiteralValue::String(string) = token.value()? else {
+				unreachable!()
+			};
+
+			ret.push_str(&string);
+		}
+
+		Ok(LiteralValue::String(ret))
+	}
 }
 
 // MemberExpr //////////////////////////////////////////////////////////////////
@@ -545,21 +721,30 @@ impl PostfixExpr {
 		let ret0 = self.0.last_token().unwrap();
 
 		let ret1 = match ret0.kind() {
-			Syntax::Minus2 => PostfixOp::Minus2,
-			Syntax::Plus2 => PostfixOp::Plus2,
+			Syntax::Minus2 => PostfixOp::PostDec,
+			Syntax::Plus2 => PostfixOp::PostInc,
 			_ => unreachable!(),
 		};
 
 		(ret0, ret1)
 	}
+
+	/// Shorthand for the second element returned by [`Self::operator`].
+	#[must_use]
+	pub fn operator_kind(&self) -> PostfixOp {
+		self.operator().1
+	}
 }
 
-/// See [`PostfixExpr`].
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// See [`PostfixExpr`]. Member access is a [`MemberExpr`] rather than
+/// a postfix expression, and ZScript has no postfix scope resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum PostfixOp {
-	Minus2,
-	Plus2,
+	/// `++`
+	PostInc,
+	/// `--`
+	PostDec,
 }
 
 // PrefixExpr //////////////////////////////////////////////////////////////////
@@ -588,11 +773,29 @@ impl PrefixExpr {
 			Syntax::Plus => PrefixOp::Plus,
 			Syntax::Plus2 => PrefixOp::Plus2,
 			Syntax::Tilde => PrefixOp::Tilde,
+			Syntax::KwAlignOf => PrefixOp::KwAlignOf,
+			Syntax::KwSizeOf => PrefixOp::KwSizeOf,
 			_ => unreachable!(),
 		};
 
 		(ret0, ret1)
 	}
+
+	/// Like [`Self::operator`], but names the operation performed
+	/// rather than the token used to spell it.
+	#[must_use]
+	pub fn operator_kind(&self) -> UnaryOp {
+		match self.operator().1 {
+			PrefixOp::Bang => UnaryOp::Not,
+			PrefixOp::Minus => UnaryOp::Neg,
+			PrefixOp::Minus2 => UnaryOp::PreDec,
+			PrefixOp::Plus => UnaryOp::Pos,
+			PrefixOp::Plus2 => UnaryOp::PreInc,
+			PrefixOp::Tilde => UnaryOp::BitNot,
+			PrefixOp::KwAlignOf => UnaryOp::AlignOf,
+			PrefixOp::KwSizeOf => UnaryOp::SizeOf,
+		}
+	}
 }
 
 /// See [`PrefixExpr`].
@@ -605,6 +808,31 @@ pub enum PrefixOp {
 	Plus,
 	Plus2,
 	Tilde,
+	KwAlignOf,
+	KwSizeOf,
+}
+
+/// See [`PrefixExpr::operator_kind`].
+/// ZScript has no pointer arithmetic, and so no address-of or dereference operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum UnaryOp {
+	/// `-`
+	Neg,
+	/// `+`
+	Pos,
+	/// `!`
+	Not,
+	/// `~`
+	BitNot,
+	/// `++`
+	PreInc,
+	/// `--`
+	PreDec,
+	/// `sizeof`
+	SizeOf,
+	/// `alignof`
+	AlignOf,
 }
 
 // SuperExpr ///////////////////////////////////////////////////////////////////
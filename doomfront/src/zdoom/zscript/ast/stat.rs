@@ -551,10 +551,27 @@ impl StaticConstStat {
 			.ok_or(AstError::Missing)
 	}
 
+	/// The type of each element in the array.
 	pub fn type_spec(&self) -> AstResult<CoreType> {
 		CoreType::cast(self.0.first_child().ok_or(AstError::Missing)?).ok_or(AstError::Incorrect)
 	}
 
+	/// `true` for `static const int Foo[] = ...;`;
+	/// `false` for `static const int[] Foo = ...;`.
+	/// The grammar treats both orderings identically.
+	#[must_use]
+	pub fn brackets_after_name(&self) -> bool {
+		let mut tokens = self.0.children_with_tokens().filter_map(|elem| {
+			elem.into_token()
+				.filter(|token| token.kind() == Syntax::Ident || token.kind() == Syntax::BracketL)
+		});
+
+		tokens
+			.next()
+			.is_some_and(|token| token.kind() == Syntax::Ident)
+	}
+
+	/// The expressions between the braces, i.e. the array's elements.
 	pub fn values(&self) -> impl Iterator<Item = Expr> {
 		self.0.children().filter_map(Expr::cast)
 	}
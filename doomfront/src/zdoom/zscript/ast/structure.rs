@@ -536,9 +536,28 @@ pub struct ReturnTypes(SyntaxNode);
 simple_astnode!(Syntax, ReturnTypes, Syntax::ReturnTypes);
 
 impl ReturnTypes {
-	pub fn iter(&self) -> impl Iterator<Item = TypeRef> {
+	/// GZDoom refuses to compile a function declaring more return types than this.
+	pub const MAX: usize = 4;
+
+	/// Yields the declared return types in order.
+	pub fn types(&self) -> impl Iterator<Item = TypeRef> {
 		self.0.children().filter_map(TypeRef::cast)
 	}
+
+	#[must_use]
+	pub fn count(&self) -> usize {
+		self.types().count()
+	}
+
+	/// If more than [`Self::MAX`] return types are declared, returns a span
+	/// covering every one past the limit, to which a diagnostic can be attached.
+	#[must_use]
+	pub fn excess(&self) -> Option<TextRange> {
+		let mut excess = self.types().skip(Self::MAX);
+		let first = excess.next()?.syntax().text_range();
+		let last = excess.last().map_or(first, |t| t.syntax().text_range());
+		Some(first.cover(last))
+	}
 }
 
 /// Wraps a node tagged [`Syntax::ParamList`].
@@ -22,6 +22,64 @@ impl TypeRef {
 	pub fn array_lengths(&self) -> impl Iterator<Item = ArrayLen> {
 		self.0.children().filter_map(ArrayLen::cast)
 	}
+	// (RAT) These return owned nodes, since `Self::core` does too;
+	// cloning one only bumps a reference count.
+
+	#[must_use]
+	pub fn as_class_type(&self) -> Option<ClassType> {
+		match self.core() {
+			CoreType::Class(inner) => Some(inner),
+			_ => None,
+		}
+	}
+
+	#[must_use]
+	pub fn as_dyn_array_type(&self) -> Option<DynArrayType> {
+		match self.core() {
+			CoreType::DynArray(inner) => Some(inner),
+			_ => None,
+		}
+	}
+
+	#[must_use]
+	pub fn as_ident_chain_type(&self) -> Option<IdentChainType> {
+		match self.core() {
+			CoreType::IdentChain(inner) => Some(inner),
+			_ => None,
+		}
+	}
+
+	#[must_use]
+	pub fn as_map_type(&self) -> Option<MapType> {
+		match self.core() {
+			CoreType::Map(inner) => Some(inner),
+			_ => None,
+		}
+	}
+
+	#[must_use]
+	pub fn as_native_type(&self) -> Option<NativeType> {
+		match self.core() {
+			CoreType::Native(inner) => Some(inner),
+			_ => None,
+		}
+	}
+
+	#[must_use]
+	pub fn as_primitive_type(&self) -> Option<PrimitiveType> {
+		match self.core() {
+			CoreType::Primitive(inner) => Some(inner),
+			_ => None,
+		}
+	}
+
+	#[must_use]
+	pub fn as_readonly_type(&self) -> Option<ReadOnlyType> {
+		match self.core() {
+			CoreType::Readonly(inner) => Some(inner),
+			_ => None,
+		}
+	}
 }
 
 // CoreType ////////////////////////////////////////////////////////////////////
@@ -251,6 +309,133 @@ impl PrimitiveType {
 	pub fn token(&self) -> SyntaxToken {
 		self.0.first_token().unwrap()
 	}
+
+	/// Aliases (e.g. `byte` and `uint8`) get folded into the same kind.
+	#[must_use]
+	pub fn kind(&self) -> PrimitiveKind {
+		let token = self.token();
+
+		match token.kind() {
+			Syntax::KwSByte | Syntax::KwInt8 => PrimitiveKind::Int8,
+			Syntax::KwByte | Syntax::KwUInt8 => PrimitiveKind::UInt8,
+			Syntax::KwShort | Syntax::KwInt16 => PrimitiveKind::Int16,
+			Syntax::KwUShort | Syntax::KwUInt16 => PrimitiveKind::UInt16,
+			Syntax::KwInt => PrimitiveKind::Int32,
+			Syntax::KwUInt => PrimitiveKind::UInt32,
+			Syntax::KwBool => PrimitiveKind::Bool,
+			Syntax::KwFloat => PrimitiveKind::Float,
+			Syntax::KwDouble => PrimitiveKind::Double,
+			Syntax::KwVector2 => PrimitiveKind::Vector2,
+			Syntax::KwVector3 => PrimitiveKind::Vector3,
+			Syntax::KwName => PrimitiveKind::Name,
+			Syntax::KwSound => PrimitiveKind::Sound,
+			Syntax::KwState => PrimitiveKind::State,
+			Syntax::KwString => PrimitiveKind::String,
+			Syntax::KwColor => PrimitiveKind::Color,
+			Syntax::KwVoid => PrimitiveKind::Void,
+			other => unreachable!("unexpected primitive type token: {other:#?}"),
+		}
+	}
+}
+
+/// See [`PrimitiveType::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PrimitiveKind {
+	/// `sbyte` or `int8`.
+	Int8,
+	/// `byte` or `uint8`.
+	UInt8,
+	/// `short` or `int16`.
+	Int16,
+	/// `ushort` or `uint16`.
+	UInt16,
+	/// `int`.
+	Int32,
+	/// `uint`.
+	UInt32,
+	Bool,
+	/// Note that GZDoom stores a `float` class or struct field in 32 bits,
+	/// but uses 64 bits everywhere else.
+	Float,
+	Double,
+	Vector2,
+	Vector3,
+	Name,
+	Sound,
+	State,
+	String,
+	Color,
+	Void,
+}
+
+impl PrimitiveKind {
+	/// Returns `None` if this is not an integral or floating-point kind.
+	#[must_use]
+	pub fn bit_width(self) -> Option<u32> {
+		match self {
+			Self::Int8 | Self::UInt8 => Some(8),
+			Self::Int16 | Self::UInt16 => Some(16),
+			Self::Int32 | Self::UInt32 => Some(32),
+			Self::Float | Self::Double => Some(64),
+			Self::Bool
+			| Self::Vector2
+			| Self::Vector3
+			| Self::Name
+			| Self::Sound
+			| Self::State
+			| Self::String
+			| Self::Color
+			| Self::Void => None,
+		}
+	}
+
+	#[must_use]
+	pub fn is_integer(self) -> bool {
+		matches!(
+			self,
+			Self::Int8 | Self::UInt8 | Self::Int16 | Self::UInt16 | Self::Int32 | Self::UInt32
+		)
+	}
+
+	/// Floating-point kinds are always considered signed.
+	#[must_use]
+	pub fn is_signed(self) -> bool {
+		matches!(
+			self,
+			Self::Int8 | Self::Int16 | Self::Int32 | Self::Float | Self::Double
+		)
+	}
+
+	#[must_use]
+	pub fn is_float(self) -> bool {
+		matches!(self, Self::Float | Self::Double)
+	}
+}
+
+impl std::fmt::Display for PrimitiveKind {
+	/// Writes the canonical (i.e. non-aliased) keyword.
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Int8 => write!(f, "int8"),
+			Self::UInt8 => write!(f, "uint8"),
+			Self::Int16 => write!(f, "int16"),
+			Self::UInt16 => write!(f, "uint16"),
+			Self::Int32 => write!(f, "int"),
+			Self::UInt32 => write!(f, "uint"),
+			Self::Bool => write!(f, "bool"),
+			Self::Float => write!(f, "float"),
+			Self::Double => write!(f, "double"),
+			Self::Vector2 => write!(f, "vector2"),
+			Self::Vector3 => write!(f, "vector3"),
+			Self::Name => write!(f, "name"),
+			Self::Sound => write!(f, "sound"),
+			Self::State => write!(f, "state"),
+			Self::String => write!(f, "string"),
+			Self::Color => write!(f, "color"),
+			Self::Void => write!(f, "void"),
+		}
+	}
 }
 
 // ReadonlyType ////////////////////////////////////////////////////////////////
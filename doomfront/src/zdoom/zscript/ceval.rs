@@ -0,0 +1,290 @@
+//! **C**ompile-time **eval**uation of constant expressions.
+//!
+//! Only covers what can be folded without any knowledge of symbols;
+//! i.e. literals, vector and color literals, groupings, and unary signs.
+
+use rowan::{ast::AstNode, TextRange};
+
+use super::ast;
+
+/// The value of a successfully-folded constant expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CEval {
+	Bool(bool),
+	Int(i64),
+	Float(f64),
+	Vector2([f64; 2]),
+	Vector3([f64; 3]),
+	/// Components are in ARGB order.
+	Color([u8; 4]),
+}
+
+impl CEval {
+	#[must_use]
+	fn as_f64(self) -> Option<f64> {
+		match self {
+			Self::Int(i) => Some(i as f64),
+			Self::Float(f) => Some(f),
+			_ => None,
+		}
+	}
+}
+
+/// Why an expression could not be folded. Each variant carries the span of
+/// the offending (sub-)expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+	/// A vector or color literal had the wrong number of elements for its
+	/// target type, or a number of elements with no corresponding type.
+	ComponentCount {
+		span: TextRange,
+		expected: &'static str,
+		found: usize,
+	},
+	/// A color component was not an integer in the range `0..=255`.
+	ColorComponent(TextRange),
+	/// The expression depends on something only known at runtime,
+	/// or on symbols (which are not resolved here).
+	NotConst(TextRange),
+	/// A literal's token could not be parsed into a value.
+	Literal(TextRange),
+	/// A vector literal component was not numeric, or a vector or color literal
+	/// was folded for a type other than `vector2`, `vector3`, or `color`.
+	TypeMismatch(TextRange),
+}
+
+impl std::error::Error for Error {}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::ComponentCount {
+				span,
+				expected,
+				found,
+			} => {
+				write!(
+					f,
+					"expected {expected} components, found {found} (at {span:?})"
+				)
+			}
+			Self::ColorComponent(span) => {
+				write!(
+					f,
+					"color components must be integers between 0 and 255 (at {span:?})"
+				)
+			}
+			Self::NotConst(span) => {
+				write!(f, "expression is not a compile-time constant (at {span:?})")
+			}
+			Self::Literal(span) => write!(f, "malformed literal (at {span:?})"),
+			Self::TypeMismatch(span) => write!(f, "type mismatch (at {span:?})"),
+		}
+	}
+}
+
+/// Folds `ast` into a value, if it is a constant expression.
+/// Vector literals are folded as by [`vector`] without a target type.
+pub fn expr(ast: &ast::Expr) -> Result<CEval, Error> {
+	let span = ast.syntax().text_range();
+
+	match ast {
+		ast::Expr::Group(e_grp) => expr(&e_grp.inner()),
+		ast::Expr::Literal(e_lit) => literal(e_lit),
+		ast::Expr::Prefix(e_pre) => {
+			let (_, op) = e_pre.operator();
+			let operand = expr(&e_pre.operand())?;
+
+			match (op, operand) {
+				(ast::PrefixOp::Plus, CEval::Int(_) | CEval::Float(_)) => Ok(operand),
+				(ast::PrefixOp::Minus, CEval::Int(i)) => Ok(CEval::Int(-i)),
+				(ast::PrefixOp::Minus, CEval::Float(f)) => Ok(CEval::Float(-f)),
+				(ast::PrefixOp::Minus, CEval::Vector2(v)) => Ok(CEval::Vector2(v.map(|c| -c))),
+				(ast::PrefixOp::Minus, CEval::Vector3(v)) => Ok(CEval::Vector3(v.map(|c| -c))),
+				(ast::PrefixOp::Bang, CEval::Bool(b)) => Ok(CEval::Bool(!b)),
+				(ast::PrefixOp::Tilde, CEval::Int(i)) => Ok(CEval::Int(!i)),
+				_ => Err(Error::NotConst(span)),
+			}
+		}
+		ast::Expr::Vector(e_vec) => vector(e_vec, None),
+		ast::Expr::Binary(_)
+		| ast::Expr::Call(_)
+		| ast::Expr::ClassCast(_)
+		| ast::Expr::Ident(_)
+		| ast::Expr::Index(_)
+		| ast::Expr::Member(_)
+		| ast::Expr::Postfix(_)
+		| ast::Expr::Super(_)
+		| ast::Expr::Ternary(_) => Err(Error::NotConst(span)),
+	}
+}
+
+/// Folds a vector literal into a [`CEval::Vector2`], [`CEval::Vector3`],
+/// or [`CEval::Color`].
+///
+/// If `target` is `None`, the result's type is chosen by the number of elements.
+/// A color is written as either `(r, g, b)` or `(a, r, g, b)`; when there are
+/// only three components, alpha is 0 (as in GZDoom).
+pub fn vector(ast: &ast::VectorExpr, target: Option<ast::PrimitiveKind>) -> Result<CEval, Error> {
+	let span = ast.syntax().text_range();
+	let elems = ast.elements().collect::<Vec<_>>();
+
+	let count_err = |expected| Error::ComponentCount {
+		span,
+		expected,
+		found: elems.len(),
+	};
+
+	match target {
+		None => match elems.len() {
+			2 => vector_components(&elems).map(|c| CEval::Vector2([c[0], c[1]])),
+			3 => vector_components(&elems).map(|c| CEval::Vector3([c[0], c[1], c[2]])),
+			_ => Err(count_err("2 or 3")),
+		},
+		Some(ast::PrimitiveKind::Vector2) => {
+			if elems.len() != 2 {
+				return Err(count_err("2"));
+			}
+
+			vector_components(&elems).map(|c| CEval::Vector2([c[0], c[1]]))
+		}
+		Some(ast::PrimitiveKind::Vector3) => {
+			if elems.len() != 3 {
+				return Err(count_err("3"));
+			}
+
+			vector_components(&elems).map(|c| CEval::Vector3([c[0], c[1], c[2]]))
+		}
+		Some(ast::PrimitiveKind::Color) => {
+			let mut argb = [0; 4];
+
+			let dest = match elems.len() {
+				3 => &mut argb[1..],
+				4 => &mut argb[..],
+				_ => return Err(count_err("3 or 4")),
+			};
+
+			for (e, d) in elems.iter().zip(dest.iter_mut()) {
+				let e_span = e.syntax().text_range();
+
+				let CEval::Int(i) = expr(e)? else {
+					return Err(Error::ColorComponent(e_span));
+				};
+
+				*d = u8::try_from(i).map_err(|_| Error::ColorComponent(e_span))?;
+			}
+
+			Ok(CEval::Color(argb))
+		}
+		Some(_) => Err(Error::TypeMismatch(span)),
+	}
+}
+
+fn vector_components(elems: &[ast::Expr]) -> Result<Vec<f64>, Error> {
+	elems
+		.iter()
+		.map(|e| {
+			expr(e)?
+				.as_f64()
+				.ok_or(Error::TypeMismatch(e.syntax().text_range()))
+		})
+		.collect()
+}
+
+fn literal(ast: &ast::Literal) -> Result<CEval, Error> {
+	let span = ast.syntax().text_range();
+	let token = ast.token();
+
+	if let Some(b) = token.bool() {
+		return Ok(CEval::Bool(b));
+	}
+
+	if let Some(result) = token.int() {
+		return result
+			.ok()
+			.and_then(|(i, _)| i64::try_from(i).ok())
+			.map(CEval::Int)
+			.ok_or(Error::Literal(span));
+	}
+
+	if let Some(result) = token.float() {
+		return result.map(CEval::Float).map_err(|_| Error::Literal(span));
+	}
+
+	Err(Error::NotConst(span))
+}
+
+#[cfg(test)]
+mod test {
+	use crate::zdoom;
+
+	use super::*;
+
+	#[must_use]
+	fn parse_vector(source: &str) -> ast::VectorExpr {
+		let ptree = crate::parse(
+			source,
+			zdoom::zscript::parse::expr,
+			zdoom::lex::Context::ZSCRIPT_LATEST,
+		);
+
+		crate::testing::assert_no_errors(&ptree);
+
+		let ast::Expr::Vector(e_vec) = ast::Expr::cast(ptree.cursor()).unwrap() else {
+			panic!("expected a vector expression")
+		};
+
+		e_vec
+	}
+
+	#[test]
+	fn vector3() {
+		let e_vec = parse_vector("(1.0, 2.0, 3.0)");
+
+		assert_eq!(
+			vector(&e_vec, Some(ast::PrimitiveKind::Vector3)).unwrap(),
+			CEval::Vector3([1.0, 2.0, 3.0])
+		);
+
+		assert_eq!(
+			expr(&ast::Expr::Vector(e_vec.clone())).unwrap(),
+			CEval::Vector3([1.0, 2.0, 3.0])
+		);
+
+		assert!(matches!(
+			vector(&e_vec, Some(ast::PrimitiveKind::Vector2)),
+			Err(Error::ComponentCount { found: 3, .. })
+		));
+	}
+
+	#[test]
+	fn color() {
+		let e_vec = parse_vector("(255, 0, 0)");
+
+		assert_eq!(
+			vector(&e_vec, Some(ast::PrimitiveKind::Color)).unwrap(),
+			CEval::Color([0, 255, 0, 0])
+		);
+
+		let e_vec = parse_vector("(128, 255, 0, 0)");
+
+		assert_eq!(
+			vector(&e_vec, Some(ast::PrimitiveKind::Color)).unwrap(),
+			CEval::Color([128, 255, 0, 0])
+		);
+
+		let e_vec = parse_vector("(256, 0, 0)");
+
+		assert!(matches!(
+			vector(&e_vec, Some(ast::PrimitiveKind::Color)),
+			Err(Error::ColorComponent(_))
+		));
+
+		let e_vec = parse_vector("(1.0, 2.0)");
+
+		assert!(matches!(
+			vector(&e_vec, Some(ast::PrimitiveKind::Color)),
+			Err(Error::ComponentCount { found: 2, .. })
+		));
+	}
+}
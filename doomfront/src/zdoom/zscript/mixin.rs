@@ -0,0 +1,402 @@
+//! Splicing the bodies of [mixin classes](ast::MixinClassDef) into the classes
+//! which include them via [`mixin` statements](ast::MixinStat).
+//!
+//! Resolution of mixin names across files is left to the caller.
+
+use rowan::{ast::AstNode, TextRange};
+
+use super::{ast, SyntaxToken};
+
+/// See [`expand_mixins`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandedClass {
+	/// In declaration order, with each mixin's members taking the place of the
+	/// statement which included them. Never contains [`ast::ClassInnard::Mixin`].
+	pub members: Vec<Member>,
+	pub issues: Vec<Issue>,
+}
+
+impl ExpandedClass {
+	/// Finds the first member declaring `name`, compared ASCII case-insensitively.
+	#[must_use]
+	pub fn find(&self, name: &str) -> Option<&Member> {
+		self.members.iter().find(|member| {
+			member
+				.names()
+				.iter()
+				.any(|n| n.text().eq_ignore_ascii_case(name))
+		})
+	}
+
+	pub fn functions(&self) -> impl Iterator<Item = (&ast::FunctionDecl, &Origin)> {
+		self.members
+			.iter()
+			.filter_map(|member| match &member.innard {
+				ast::ClassInnard::Function(fndecl) => Some((fndecl, &member.origin)),
+				_ => None,
+			})
+	}
+
+	pub fn fields(&self) -> impl Iterator<Item = (&ast::FieldDecl, &Origin)> {
+		self.members
+			.iter()
+			.filter_map(|member| match &member.innard {
+				ast::ClassInnard::Field(field) => Some((field, &member.origin)),
+				_ => None,
+			})
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+	pub innard: ast::ClassInnard,
+	pub origin: Origin,
+}
+
+impl Member {
+	/// The identifiers declared by this member. Empty for `default` and `states`
+	/// blocks; may be more than one for a field declaration.
+	#[must_use]
+	pub fn names(&self) -> Vec<SyntaxToken> {
+		match &self.innard {
+			ast::ClassInnard::Const(inner) => inner.name().into_iter().collect(),
+			ast::ClassInnard::Enum(inner) => inner.name().into_iter().collect(),
+			ast::ClassInnard::Struct(inner) => inner.name().into_iter().collect(),
+			ast::ClassInnard::StaticConst(inner) => inner.name().into_iter().collect(),
+			ast::ClassInnard::Function(inner) => vec![inner.name()],
+			ast::ClassInnard::Field(inner) => inner.names().map(|n| n.ident()).collect(),
+			ast::ClassInnard::Property(inner) => inner.name().into_iter().collect(),
+			ast::ClassInnard::Flag(inner) => inner.name().into_iter().collect(),
+			ast::ClassInnard::Mixin(_)
+			| ast::ClassInnard::Default(_)
+			| ast::ClassInnard::States(_) => vec![],
+		}
+	}
+}
+
+/// Where a [`Member`] of an [`ExpandedClass`] was declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+	/// In the body of the class itself.
+	Own,
+	Mixin {
+		/// As written in the `mixin` statement.
+		name: String,
+		def: ast::MixinClassDef,
+		stat: ast::MixinStat,
+	},
+}
+
+impl Origin {
+	/// The name of the mixin, if this member came from one.
+	#[must_use]
+	pub fn mixin_name(&self) -> Option<&str> {
+		match self {
+			Self::Own => None,
+			Self::Mixin { name, .. } => Some(name),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+	/// Two members declare the same name, and at least one came from a mixin.
+	/// `first` precedes `second` in declaration order.
+	Conflict {
+		name: String,
+		first: (Origin, TextRange),
+		second: (Origin, TextRange),
+	},
+	/// `lookup` found no mixin class by this name.
+	UnknownMixin { name: String, span: TextRange },
+	/// A mixin class itself contains a `mixin` statement, which GZDoom forbids.
+	/// `span` covers that statement; its mixin is not expanded.
+	NestedMixin { mixin: String, span: TextRange },
+}
+
+impl std::fmt::Display for Issue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Conflict {
+				name,
+				first,
+				second,
+			} => {
+				let declared = |origin: &Origin| match origin.mixin_name() {
+					Some(mixin) => format!("declared in mixin `{mixin}`"),
+					None => "declared in the class itself".to_string(),
+				};
+
+				write!(
+					f,
+					"`{name}` is {} and also {}",
+					declared(&first.0),
+					declared(&second.0)
+				)
+			}
+			Self::UnknownMixin { name, .. } => write!(f, "mixin class `{name}` not found"),
+			Self::NestedMixin { mixin, .. } => {
+				write!(f, "mixin class `{mixin}` can not itself use a mixin")
+			}
+		}
+	}
+}
+
+/// Lists the effective members of `class`, with the members of every mixin it
+/// uses spliced in. `lookup` is given a mixin's name as written in a `mixin`
+/// statement; name comparison is up to the caller.
+#[must_use]
+pub fn expand_mixins(
+	class: &ast::ClassDef,
+	mut lookup: impl FnMut(&str) -> Option<ast::MixinClassDef>,
+) -> ExpandedClass {
+	let mut ret = ExpandedClass {
+		members: vec![],
+		issues: vec![],
+	};
+
+	for innard in class.innards() {
+		let ast::ClassInnard::Mixin(stat) = innard else {
+			ret.members.push(Member {
+				innard,
+				origin: Origin::Own,
+			});
+
+			continue;
+		};
+
+		let Ok(name_tok) = stat.name() else {
+			continue;
+		};
+
+		let name = name_tok.text().to_string();
+
+		let Some(def) = lookup(&name) else {
+			ret.issues.push(Issue::UnknownMixin {
+				name,
+				span: stat.syntax().text_range(),
+			});
+
+			continue;
+		};
+
+		for m_innard in def.innards() {
+			if let ast::ClassInnard::Mixin(nested) = m_innard {
+				ret.issues.push(Issue::NestedMixin {
+					mixin: name.clone(),
+					span: nested.syntax().text_range(),
+				});
+
+				continue;
+			}
+
+			ret.members.push(Member {
+				innard: m_innard,
+				origin: Origin::Mixin {
+					name: name.clone(),
+					def: def.clone(),
+					stat: stat.clone(),
+				},
+			});
+		}
+	}
+
+	find_conflicts(&mut ret);
+	ret
+}
+
+fn find_conflicts(expanded: &mut ExpandedClass) {
+	let mut seen: Vec<(SyntaxToken, &Origin)> = vec![];
+
+	for member in &expanded.members {
+		for name in member.names() {
+			let prev = seen
+				.iter()
+				.find(|(n, _)| n.text().eq_ignore_ascii_case(name.text()));
+
+			let Some((prev_name, prev_origin)) = prev else {
+				seen.push((name, &member.origin));
+				continue;
+			};
+
+			if matches!((prev_origin, &member.origin), (Origin::Own, Origin::Own)) {
+				continue;
+			}
+
+			expanded.issues.push(Issue::Conflict {
+				name: name.text().to_string(),
+				first: ((*prev_origin).clone(), prev_name.text_range()),
+				second: (member.origin.clone(), name.text_range()),
+			});
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::zdoom::{self, zscript::ParseTree};
+
+	use super::*;
+
+	#[must_use]
+	fn parse(source: &str) -> ParseTree {
+		let ptree = crate::parse(
+			source,
+			zdoom::zscript::parse::file,
+			zdoom::lex::Context::ZSCRIPT_LATEST,
+		);
+
+		crate::testing::assert_no_errors(&ptree);
+		ptree
+	}
+
+	#[must_use]
+	fn expand(ptree: &ParseTree) -> ExpandedClass {
+		let mut class = None;
+		let mut mixins = vec![];
+
+		for node in ptree.cursor().children() {
+			match ast::TopLevel::cast(node) {
+				Some(ast::TopLevel::ClassDef(classdef)) => class = Some(classdef),
+				Some(ast::TopLevel::MixinClassDef(mixindef)) => mixins.push(mixindef),
+				_ => {}
+			}
+		}
+
+		expand_mixins(&class.unwrap(), |name| {
+			mixins
+				.iter()
+				.find(|m| m.name().unwrap().text().eq_ignore_ascii_case(name))
+				.cloned()
+		})
+	}
+
+	#[test]
+	fn two_mixins() {
+		const SOURCE: &str = r#"
+mixin class Lorem {
+	int ipsum;
+	void Dolor() {}
+}
+
+mixin class Sit {
+	const AMET = 1;
+}
+
+class Consectetur {
+	mixin Lorem;
+	double adipiscing;
+	mixin Sit;
+}
+"#;
+
+		let ptree = parse(SOURCE);
+		let expanded = expand(&ptree);
+		assert!(expanded.issues.is_empty(), "{:#?}", expanded.issues);
+
+		let names = expanded
+			.members
+			.iter()
+			.flat_map(|m| m.names())
+			.map(|n| n.text().to_string())
+			.collect::<Vec<_>>();
+
+		assert_eq!(names, ["ipsum", "Dolor", "adipiscing", "AMET"]);
+
+		let dolor = expanded.find("dolor").unwrap();
+		assert_eq!(dolor.origin.mixin_name(), Some("Lorem"));
+
+		let Origin::Mixin { def, .. } = &dolor.origin else {
+			unreachable!()
+		};
+
+		assert_eq!(def.name().unwrap().text(), "Lorem");
+
+		assert_eq!(expanded.find("adipiscing").unwrap().origin, Origin::Own);
+		assert_eq!(
+			expanded.find("AMET").unwrap().origin.mixin_name(),
+			Some("Sit")
+		);
+
+		let (fndecl, origin) = expanded.functions().next().unwrap();
+		assert_eq!(fndecl.name().text(), "Dolor");
+		assert_eq!(origin.mixin_name(), Some("Lorem"));
+		assert_eq!(expanded.fields().count(), 2);
+	}
+
+	#[test]
+	fn conflict() {
+		const SOURCE: &str = r#"
+mixin class Lorem {
+	int ipsum;
+}
+
+mixin class Dolor {
+	void Ipsum() {}
+}
+
+class Sit {
+	int ipsum;
+	mixin Lorem;
+	mixin Dolor;
+}
+"#;
+
+		let ptree = parse(SOURCE);
+		let expanded = expand(&ptree);
+		assert_eq!(expanded.issues.len(), 2);
+
+		let Issue::Conflict { first, second, .. } = &expanded.issues[0] else {
+			panic!("expected a conflict, found: {:#?}", expanded.issues[0]);
+		};
+
+		assert_eq!(first.0, Origin::Own);
+		assert_eq!(second.0.mixin_name(), Some("Lorem"));
+
+		let Issue::Conflict { second, .. } = &expanded.issues[1] else {
+			panic!("expected a conflict, found: {:#?}", expanded.issues[1]);
+		};
+
+		assert_eq!(second.0.mixin_name(), Some("Dolor"));
+	}
+
+	#[test]
+	fn unknown_and_nested() {
+		const SOURCE: &str = r#"
+mixin class Lorem {
+	mixin Ipsum;
+	int dolor;
+}
+
+class Sit {
+	mixin Amet;
+	mixin Lorem;
+}
+"#;
+
+		let ptree = parse(SOURCE);
+		let expanded = expand(&ptree);
+		assert_eq!(expanded.issues.len(), 2);
+
+		let Issue::UnknownMixin { name, span } = &expanded.issues[0] else {
+			panic!(
+				"expected an unknown mixin, found: {:#?}",
+				expanded.issues[0]
+			);
+		};
+
+		assert_eq!(name, "Amet");
+		assert_eq!(&SOURCE[*span], "mixin Amet;");
+
+		let Issue::NestedMixin { mixin, span } = &expanded.issues[1] else {
+			panic!("expected a nested mixin, found: {:#?}", expanded.issues[1]);
+		};
+
+		assert_eq!(mixin, "Lorem");
+		assert_eq!(&SOURCE[*span], "mixin Ipsum;");
+		assert_eq!(
+			expanded.find("dolor").unwrap().origin.mixin_name(),
+			Some("Lorem")
+		);
+	}
+}
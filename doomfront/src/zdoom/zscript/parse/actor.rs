@@ -206,6 +206,7 @@ fn stateflow_goto(p: &mut Parser<Syntax>) {
 	let flow = p.open();
 	p.advance(Syntax::KwGoto);
 	trivia_0plus(p);
+	let offs = p.open();
 
 	if p.eat(Token::KwSuper, Syntax::KwSuper) {
 		trivia_0plus(p);
@@ -243,6 +244,7 @@ fn stateflow_goto(p: &mut Parser<Syntax>) {
 		p.expect(Token::IntLit, Syntax::IntLit, &[&["an integer"]]);
 	}
 
+	p.close(offs, Syntax::GotoOffset);
 	trivia_0plus(p);
 	p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
 	p.close(flow, Syntax::StateFlow);
@@ -283,6 +285,9 @@ pub(super) fn states_usage(p: &mut Parser<Syntax>) {
 			trivia_0plus(p);
 			kw(p);
 			trivia_0plus(p);
+		} else {
+			p.advance_with_error(Syntax::from(p.nth(0)), &[&["`,`", "`)`"]]);
+			trivia_0plus(p);
 		}
 	}
 
@@ -292,10 +297,9 @@ pub(super) fn states_usage(p: &mut Parser<Syntax>) {
 }
 
 /// Builds a [`Syntax::StateDef`] node.
-/// Note that this starts at the trivia after the sprite.
+/// Note that this starts at the trivia after the sprite (or, given malformed
+/// input, wherever the sprite's merged token ended).
 fn state_def(p: &mut Parser<Syntax>, state: OpenMark) {
-	p.assert_at_if(|token| token.is_trivia());
-
 	trivia_0plus(p);
 	state_frames(p);
 	trivia_0plus(p);
@@ -85,7 +85,7 @@ pub(super) fn ident<const CFG: u8>(p: &mut Parser<Syntax>) {
 	let token = p.nth(0);
 
 	if is_ident::<CFG>(token) {
-		p.advance(Syntax::Ident);
+		p.advance_as_ident(Syntax::Ident);
 	} else {
 		p.advance_with_error(Syntax::from(token), &[&["an identifier"]])
 	}
@@ -131,15 +131,13 @@ pub(super) fn is_ident_lax(token: Token) -> bool {
 /// Builds a [`Syntax::IdentChain`] node.
 /// Backed by [`is_ident`]; see that function's documentation for details on `CFG`.
 pub(super) fn ident_chain<const CFG: u8>(p: &mut Parser<Syntax>) {
-	p.debug_assert_at_if(|token| is_ident::<CFG>(token) || token == Token::Dot);
-
 	let chain = p.open();
 
 	if p.eat(Token::Dot, Syntax::Dot) {
 		trivia_0plus(p);
 	}
 
-	p.advance(Syntax::Ident);
+	ident::<CFG>(p);
 
 	while p.find(0, |token| !token.is_trivia()) == Token::Dot {
 		trivia_0plus(p);
@@ -273,9 +271,8 @@ pub(super) fn trivia_no_doc_0plus(p: &mut Parser<Syntax>) {
 
 /// Builds a [`Syntax::VarName`] node.
 pub(super) fn var_name(p: &mut Parser<Syntax>) {
-	p.debug_assert_at_if(is_ident::<{ ID_SFKW | ID_SQKW | ID_TYPES }>);
 	let name = p.open();
-	p.advance(Syntax::Ident);
+	ident_lax(p);
 
 	loop {
 		if p.find(0, |token| !token.is_trivia()) == Token::BracketL {
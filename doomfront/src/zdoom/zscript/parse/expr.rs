@@ -22,6 +22,10 @@ pub fn expr(p: &mut Parser<Syntax>) {
 }
 
 fn recur(p: &mut Parser<Syntax>, left: Token) {
+	if !p.nest(Syntax::Unknown) {
+		return;
+	}
+
 	let mut lhs = primary_expr(p);
 
 	loop {
@@ -89,6 +93,8 @@ fn recur(p: &mut Parser<Syntax>, left: Token) {
 			break;
 		}
 	}
+
+	p.unnest();
 }
 
 fn primary_expr(p: &mut Parser<Syntax>) -> CloseMark {
@@ -159,7 +165,13 @@ fn primary_expr(p: &mut Parser<Syntax>) -> CloseMark {
 				trivia_0plus(p);
 				p.expect(Token::ParenR, Syntax::ParenR, &[&["`)`"]]);
 				trivia_0plus(p);
-				arg_list(p);
+
+				if p.at(Token::ParenL) {
+					arg_list(p);
+				} else {
+					p.expect(Token::ParenL, Syntax::ParenL, &[&["`(`"]]);
+				}
+
 				return p.close(ex, Syntax::ClassCastExpr);
 			}
 
@@ -28,6 +28,15 @@ use super::{common::*, expr, types::*};
 /// - [`Syntax::UntilStat`]
 /// - [`Syntax::WhileStat`]
 pub fn statement(p: &mut Parser<Syntax>) {
+	if !p.nest(Syntax::Unknown) {
+		return;
+	}
+
+	statement_nested(p);
+	p.unnest();
+}
+
+fn statement_nested(p: &mut Parser<Syntax>) {
 	let token = p.nth(0);
 
 	if expr::in_first_set(token) {
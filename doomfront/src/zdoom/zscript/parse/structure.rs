@@ -21,7 +21,7 @@ pub fn class_def(p: &mut Parser<Syntax>) -> bool {
 		trivia_0plus(p);
 
 		while !p.eof() {
-			class_innard::<false>(p);
+			class_innard(p);
 			trivia_0plus(p);
 		}
 
@@ -33,7 +33,7 @@ pub fn class_def(p: &mut Parser<Syntax>) -> bool {
 	trivia_0plus(p);
 
 	while !p.at(Token::BraceR) && !p.eof() {
-		class_innard::<false>(p);
+		class_innard(p);
 		trivia_0plus(p);
 	}
 
@@ -110,7 +110,7 @@ pub fn mixin_class_def(p: &mut Parser<Syntax>) {
 	trivia_0plus(p);
 
 	while !p.at(Token::BraceR) && !p.eof() {
-		class_innard::<true>(p);
+		class_innard(p);
 		trivia_0plus(p);
 	}
 
@@ -202,7 +202,7 @@ pub fn class_or_struct_extend(p: &mut Parser<Syntax>) {
 
 	if node_syn == Syntax::ClassExtend {
 		while !p.at(Token::BraceR) && !p.eof() {
-			class_innard::<false>(p);
+			class_innard(p);
 			trivia_0plus(p);
 		}
 	} else if node_syn == Syntax::StructExtend {
@@ -219,7 +219,7 @@ pub fn class_or_struct_extend(p: &mut Parser<Syntax>) {
 
 // Innards /////////////////////////////////////////////////////////////////////
 
-fn class_innard<const MIXIN: bool>(p: &mut Parser<Syntax>) {
+fn class_innard(p: &mut Parser<Syntax>) {
 	let token = p.find(0, |token| !token.is_trivia());
 
 	if token == Token::KwStatic && p.find(1, |token| !token.is_trivia()) == Token::KwConst {
@@ -262,7 +262,9 @@ fn class_innard<const MIXIN: bool>(p: &mut Parser<Syntax>) {
 		return;
 	}
 
-	if p.at(Token::KwMixin) && !MIXIN {
+	// (RAT) Mixin classes can not use other mixins, but this gets accepted here
+	// so that `zscript::mixin::expand_mixins` can report it with more context.
+	if p.at(Token::KwMixin) {
 		let mixin = p.open();
 		p.advance(Syntax::KwMixin);
 		trivia_0plus(p);
@@ -299,14 +301,7 @@ fn class_innard<const MIXIN: bool>(p: &mut Parser<Syntax>) {
 		"`vararg`",
 	];
 
-	p.advance_with_error(
-		Syntax::from(p.nth(0)),
-		if !MIXIN {
-			&[EXPECTED, &["`mixin`"]]
-		} else {
-			&[EXPECTED]
-		},
-	)
+	p.advance_with_error(Syntax::from(p.nth(0)), &[EXPECTED, &["`mixin`"]])
 }
 
 fn struct_innard(p: &mut Parser<Syntax>) {
@@ -430,6 +425,12 @@ pub(super) fn member_decl(p: &mut Parser<Syntax>) {
 			trivia_0plus(p);
 			ident_lax(p);
 			trivia_0plus(p);
+
+			if !p.at(Token::ParenL) {
+				p.advance_err_and_close(member, Syntax::from(p.nth(0)), Syntax::Error, &[&["`(`"]]);
+				return;
+			}
+
 			param_list(p);
 			trivia_0plus(p);
 
@@ -450,7 +451,7 @@ pub(super) fn member_decl(p: &mut Parser<Syntax>) {
 			trivia_0plus(p);
 			var_name(p);
 			trivia_0plus(p);
-			p.advance(Syntax::Semicolon);
+			p.expect(Token::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
 			p.close(member, Syntax::FieldDecl);
 		}
 		other => {
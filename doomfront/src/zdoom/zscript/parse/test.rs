@@ -362,6 +362,34 @@ fn stat_static_const_smoke() {
 	let ast = ast::StaticConstStat::cast(ptree.cursor()).unwrap();
 	let name_tok = ast.name().unwrap();
 	assert_eq!(name_tok.text(), "SOME_FLOATS");
+	assert_eq!(ast.values().count(), 8);
+	assert!(!ast.brackets_after_name());
+}
+
+#[test]
+fn stat_static_const_orderings() {
+	for (sample, after_name) in [
+		("static const int[] Foo = { 1, 2, 3 };", false),
+		("static const int Foo[] = { 1, 2, 3 };", true),
+	] {
+		let ptree: ParseTree = crate::parse(
+			sample,
+			static_const_stat,
+			zdoom::lex::Context::ZSCRIPT_LATEST,
+		);
+		assert_no_errors(&ptree);
+
+		let ast = ast::StaticConstStat::cast(ptree.cursor()).unwrap();
+		assert_eq!(ast.name().unwrap().text(), "Foo");
+		assert_eq!(ast.brackets_after_name(), after_name);
+
+		let ast::CoreType::Primitive(prim) = ast.type_spec().unwrap() else {
+			panic!("expected a primitive type");
+		};
+
+		assert_eq!(prim.token().text(), "int");
+		assert_eq!(ast.values().count(), 3);
+	}
 }
 
 // Non-structural top-level ////////////////////////////////////////////////////
@@ -625,6 +653,41 @@ meta Actor a
 	prettyprint_maybe(ptree.cursor());
 }
 
+#[test]
+fn default_block_smoke() {
+	const SAMPLE: &str = r#"default {
+	Health 100;
+	+SHOOTABLE
+	-COUNTKILL;
+	Obituary "%o was fixed.", 2;
+	Monster;
+}"#;
+
+	let ptree: ParseTree = crate::parse(SAMPLE, default_block, zdoom::lex::Context::ZSCRIPT_LATEST);
+	assert_no_errors(&ptree);
+	prettyprint_maybe(ptree.cursor());
+
+	let defblock = ast::DefaultBlock::cast(ptree.cursor()).unwrap();
+
+	let props: Vec<_> = defblock.property_settings().collect();
+	assert_eq!(props.len(), 3);
+	assert_eq!(props[0].name().unwrap().syntax().text(), "Health");
+	assert_eq!(props[0].values().count(), 1);
+	assert_eq!(props[1].name().unwrap().syntax().text(), "Obituary");
+	assert_eq!(props[1].values().count(), 2);
+	assert_eq!(props[2].name().unwrap().syntax().text(), "Monster");
+	assert_eq!(props[2].values().count(), 0);
+
+	let mut flags = defblock.flag_settings();
+	let flag0 = flags.next().unwrap();
+	assert!(flag0.is_set());
+	assert_eq!(flag0.flag_name().unwrap().syntax().text(), "SHOOTABLE");
+	let flag1 = flags.next().unwrap();
+	assert!(!flag1.is_set());
+	assert_eq!(flag1.flag_name().unwrap().syntax().text(), "COUNTKILL");
+	assert!(flags.next().is_none());
+}
+
 #[test]
 fn doc_comment_smoke() {
 	const SAMPLE: &str = r#"
@@ -692,6 +755,20 @@ fn field_smoke() {
 	}
 }
 
+#[test]
+fn field_keyword_name() {
+	const SAMPLE: &str = r#"color color;"#;
+
+	let ptree: ParseTree = crate::parse(SAMPLE, member_decl, zdoom::lex::Context::ZSCRIPT_LATEST);
+	assert_no_errors(&ptree);
+	prettyprint_maybe(ptree.cursor());
+
+	let field = ast::FieldDecl::cast(ptree.cursor()).unwrap();
+	let name = field.names().next().unwrap();
+	assert_eq!(name.ident().kind(), Syntax::Ident);
+	assert_eq!(name.ident().text(), "color");
+}
+
 #[test]
 fn method_smoke() {
 	const SAMPLE: &str = r#"int, int uac_genesis() const;"#;
@@ -728,6 +805,34 @@ fn states_block_smoke() {
 	prettyprint_maybe(ptree.cursor());
 }
 
+#[test]
+fn state_quals() {
+	const SAMPLE: &str = r#"States { Spawn: XZW1 A 4 Bright Light("ZLIGHT", 'ZGLOW') Offset(3, -4); XZW1 B 2; Stop; }"#;
+
+	let ptree: ParseTree = crate::parse(SAMPLE, states_block, zdoom::lex::Context::ZSCRIPT_LATEST);
+	assert_no_errors(&ptree);
+	prettyprint_maybe(ptree.cursor());
+
+	let ast = ast::StatesBlock::cast(ptree.cursor()).unwrap();
+
+	let mut states = ast.innards().filter_map(|innard| match innard {
+		ast::StatesInnard::State(state) => Some(state),
+		_ => None,
+	});
+
+	let state0 = states.next().unwrap();
+	let light = state0.light().unwrap();
+	let names: Vec<_> = light.light_names().map(|t| t.text().to_string()).collect();
+	assert_eq!(names, ["\"ZLIGHT\"", "'ZGLOW'"]);
+	let offset = state0.offset().unwrap();
+	assert_eq!(offset.x().unwrap().syntax().text(), "3");
+	assert_eq!(offset.y().unwrap().syntax().text(), "-4");
+
+	let state1 = states.next().unwrap();
+	assert!(state1.light().is_none());
+	assert!(state1.offset().is_none());
+}
+
 #[test]
 fn goto_smoke() {
 	const SAMPLE: &str = r#####"States {
@@ -794,3 +899,47 @@ goto Consectetur;
 		assert_eq!(format!("{}", name.syntax().text()), "Consectetur");
 	}
 }
+
+#[test]
+fn goto_offset() {
+	const SAMPLE: &str = r#####"States {
+goto Super::Spawn + 2;
+goto Dolor::SitAmet;
+goto Consectetur.Adipiscing;
+stop;
+}"#####;
+
+	let ptree: ParseTree = crate::parse(SAMPLE, states_block, zdoom::lex::Context::ZSCRIPT_LATEST);
+	assert_no_errors(&ptree);
+	prettyprint_maybe(ptree.cursor());
+
+	let ast = ast::StatesBlock::cast(ptree.cursor()).unwrap();
+
+	let mut gotos = ast.innards().map(|innard| {
+		let ast::StatesInnard::Flow(flow) = innard else {
+			panic!()
+		};
+
+		flow.goto_offset()
+	});
+
+	let goto0 = gotos.next().unwrap().unwrap();
+	assert!(goto0.uses_super());
+	assert_eq!(goto0.target().unwrap().syntax().text(), "Spawn");
+	assert_eq!(goto0.offset().unwrap().int().unwrap().unwrap().0, 2);
+
+	let goto1 = gotos.next().unwrap().unwrap();
+	assert!(!goto1.uses_super());
+	assert_eq!(goto1.scope().unwrap().text(), "Dolor");
+	assert_eq!(goto1.target().unwrap().syntax().text(), "SitAmet");
+	assert!(goto1.offset().is_none());
+
+	let goto2 = gotos.next().unwrap().unwrap();
+	assert!(goto2.scope().is_none());
+	assert_eq!(
+		goto2.target().unwrap().syntax().text(),
+		"Consectetur.Adipiscing"
+	);
+
+	assert!(gotos.next().unwrap().is_none());
+}
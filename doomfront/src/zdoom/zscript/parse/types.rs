@@ -7,6 +7,10 @@ use super::common::*;
 
 /// Builds a [`Syntax::TypeRef`] node.
 pub fn type_ref(p: &mut Parser<Syntax>) {
+	if !p.nest(Syntax::Unknown) {
+		return;
+	}
+
 	let tref = p.open();
 	core_type(p);
 
@@ -16,6 +20,7 @@ pub fn type_ref(p: &mut Parser<Syntax>) {
 	}
 
 	p.close(tref, Syntax::TypeRef);
+	p.unnest();
 }
 
 /// Builds a node tagged with one of the following:
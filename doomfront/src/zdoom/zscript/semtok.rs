@@ -0,0 +1,542 @@
+//! Semantic classification of tokens, for syntax highlighting in editors.
+//!
+//! Classification is derived purely from the structure of one file's syntax
+//! tree; no names are resolved across files. Where structure alone cannot tell
+//! what an identifier refers to, the following is tried, in order:
+//! - a parameter or local variable of the enclosing function with the same
+//!   name, declared before the identifier (block scoping is not considered);
+//! - a class, struct, enum, enum variant, or constant declared anywhere in the file;
+//! - [`TokenKind::Field`] for expression positions, or [`TokenKind::Class`]
+//!   for type positions.
+//!
+//! Modifiers are only derived from declarations; e.g. a call to a deprecated
+//! function is not marked [`Modifiers::DEPRECATED`].
+
+use rowan::{TextRange, WalkEvent};
+use rustc_hash::FxHashMap;
+
+use super::{Syntax, SyntaxNode, SyntaxToken};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SemanticToken {
+	pub range: TextRange,
+	pub kind: TokenKind,
+	pub mods: Modifiers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+	Class,
+	Struct,
+	Enum,
+	EnumVariant,
+	/// Static function declarations, and callees which are not member accesses.
+	Function,
+	/// Non-static function declarations, and callees which are member accesses.
+	Method,
+	/// Also used for the name in a named argument.
+	Parameter,
+	LocalVar,
+	Field,
+	/// Also used for flags, in both definitions and default blocks.
+	Property,
+	/// Includes the `true`, `false`, and `null` literals, as well as the
+	/// identifiers in a [`Syntax::StatesUsage`].
+	Keyword,
+	StateLabel,
+	/// Both the sprite and the frames of a [`Syntax::StateDef`].
+	SpriteName,
+	Constant,
+	/// Includes name literals.
+	String,
+	Number,
+	/// Includes region markers.
+	Comment,
+	DocComment,
+	/// Every glyph, as well as any token the lexer did not recognize.
+	Operator,
+}
+
+/// A set of flags. Combine with `|`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+	pub const NONE: Self = Self(0);
+	pub const DECLARATION: Self = Self(1 << 0);
+	/// From a [`Syntax::DeprecationQual`].
+	pub const DEPRECATED: Self = Self(1 << 1);
+	pub const STATIC: Self = Self(1 << 2);
+	pub const NATIVE: Self = Self(1 << 3);
+
+	#[must_use]
+	pub fn contains(self, other: Self) -> bool {
+		(self.0 & other.0) == other.0
+	}
+
+	#[must_use]
+	pub fn is_empty(self) -> bool {
+		self.0 == 0
+	}
+
+	#[must_use]
+	pub fn bits(self) -> u8 {
+		self.0
+	}
+}
+
+impl std::ops::BitOr for Modifiers {
+	type Output = Self;
+
+	fn bitor(self, rhs: Self) -> Self::Output {
+		Self(self.0 | rhs.0)
+	}
+}
+
+impl std::ops::BitOrAssign for Modifiers {
+	fn bitor_assign(&mut self, rhs: Self) {
+		self.0 |= rhs.0;
+	}
+}
+
+/// The returned tokens are sorted, do not overlap, and cover every token
+/// under `root` except for whitespace.
+#[must_use]
+pub fn classify(root: &SyntaxNode) -> Vec<SemanticToken> {
+	let mut classifier = Classifier {
+		symbols: file_symbols(root),
+		scope: None,
+	};
+
+	let mut ret = vec![];
+
+	for token in root
+		.descendants_with_tokens()
+		.filter_map(|elem| elem.into_token())
+	{
+		if token.kind() == Syntax::Whitespace {
+			continue;
+		}
+
+		let (kind, mods) = classifier.classify(&token);
+
+		ret.push(SemanticToken {
+			range: token.text_range(),
+			kind,
+			mods,
+		});
+	}
+
+	ret
+}
+
+struct Classifier {
+	/// Keys are ASCII lowercase.
+	symbols: FxHashMap<String, TokenKind>,
+	/// The function most recently entered, and the parameters and local
+	/// variables it declares, in source order.
+	scope: Option<(SyntaxNode, Vec<(SyntaxToken, TokenKind)>)>,
+}
+
+impl Classifier {
+	#[must_use]
+	fn classify(&mut self, token: &SyntaxToken) -> (TokenKind, Modifiers) {
+		let kind = match token.kind() {
+			Syntax::Ident => return self.ident(token),
+			Syntax::NonWhitespace => {
+				return match token.parent().map(|p| p.kind()) {
+					Some(Syntax::StateLabel) => (TokenKind::StateLabel, Modifiers::DECLARATION),
+					_ => (TokenKind::SpriteName, Modifiers::NONE),
+				};
+			}
+			Syntax::Comment | Syntax::RegionStart | Syntax::RegionEnd => TokenKind::Comment,
+			Syntax::DocComment => TokenKind::DocComment,
+			Syntax::StringLit | Syntax::NameLit => TokenKind::String,
+			Syntax::IntLit | Syntax::FloatLit => TokenKind::Number,
+			Syntax::NullLit => TokenKind::Keyword,
+			other if other.is_keyword() => TokenKind::Keyword,
+			_ => TokenKind::Operator,
+		};
+
+		(kind, Modifiers::NONE)
+	}
+
+	#[must_use]
+	fn ident(&mut self, token: &SyntaxToken) -> (TokenKind, Modifiers) {
+		const DECL: Modifiers = Modifiers::DECLARATION;
+
+		let Some(parent) = token.parent() else {
+			return (TokenKind::Field, Modifiers::NONE);
+		};
+
+		match parent.kind() {
+			Syntax::ClassHead => (TokenKind::Class, DECL | native_if(&parent)),
+			Syntax::MixinClassDef => (TokenKind::Class, DECL),
+			Syntax::StructDef => {
+				let quals = parent
+					.children()
+					.find(|node| node.kind() == Syntax::StructQuals);
+
+				(
+					TokenKind::Struct,
+					DECL | quals.map_or(Modifiers::NONE, |q| native_if(&q)),
+				)
+			}
+			Syntax::EnumDef => (TokenKind::Enum, DECL),
+			Syntax::EnumVariant => (TokenKind::EnumVariant, DECL),
+			Syntax::ConstDef => (TokenKind::Constant, DECL),
+			Syntax::StaticConstStat => (TokenKind::Constant, DECL | Modifiers::STATIC),
+			Syntax::InheritSpec
+			| Syntax::ReplacesClause
+			| Syntax::ClassExtend
+			| Syntax::ClassCastExpr
+			| Syntax::MixinStat
+			| Syntax::GotoOffset => (TokenKind::Class, Modifiers::NONE),
+			Syntax::StructExtend | Syntax::NativeType => (TokenKind::Struct, Modifiers::NONE),
+			Syntax::ReadOnlyType => (self.type_name(token), Modifiers::NONE),
+			Syntax::FunctionDecl => {
+				let mods = member_quals(&parent);
+
+				if mods.contains(Modifiers::STATIC) {
+					(TokenKind::Function, DECL | mods)
+				} else {
+					(TokenKind::Method, DECL | mods)
+				}
+			}
+			Syntax::VarName => match parent.parent().map(|gp| gp.kind()) {
+				Some(Syntax::FieldDecl) => (
+					TokenKind::Field,
+					DECL | member_quals(&parent.parent().unwrap()),
+				),
+				_ => (TokenKind::LocalVar, DECL),
+			},
+			Syntax::LocalVarInit | Syntax::DeclAssignStat => (TokenKind::LocalVar, DECL),
+			Syntax::Parameter => (TokenKind::Parameter, DECL),
+			Syntax::Argument => (TokenKind::Parameter, Modifiers::NONE),
+			Syntax::FlagDef | Syntax::PropertyDef => {
+				let is_name = token
+					.siblings_with_tokens(rowan::Direction::Prev)
+					.all(|elem| elem.kind() != Syntax::Colon);
+
+				if is_name {
+					(TokenKind::Property, DECL)
+				} else {
+					(TokenKind::Field, Modifiers::NONE)
+				}
+			}
+			Syntax::StatesUsage => (TokenKind::Keyword, Modifiers::NONE),
+			Syntax::ActionFunction => (TokenKind::Function, Modifiers::NONE),
+			Syntax::MemberExpr => {
+				if is_callee(&parent) {
+					(TokenKind::Method, Modifiers::NONE)
+				} else {
+					(TokenKind::Field, Modifiers::NONE)
+				}
+			}
+			Syntax::IdentExpr => {
+				if is_callee(&parent) {
+					(TokenKind::Function, Modifiers::NONE)
+				} else {
+					(self.resolve(token), Modifiers::NONE)
+				}
+			}
+			Syntax::IdentChain => match parent.parent().map(|gp| gp.kind()) {
+				Some(Syntax::IdentChainType | Syntax::ClassType) => {
+					(self.type_name(token), Modifiers::NONE)
+				}
+				Some(Syntax::PropertySetting | Syntax::FlagSetting) => {
+					(TokenKind::Property, Modifiers::NONE)
+				}
+				Some(Syntax::GotoOffset) => (TokenKind::StateLabel, Modifiers::NONE),
+				_ => (TokenKind::Field, Modifiers::NONE),
+			},
+			_ => (TokenKind::Field, Modifiers::NONE),
+		}
+	}
+
+	/// For identifiers in expression position.
+	#[must_use]
+	fn resolve(&mut self, token: &SyntaxToken) -> TokenKind {
+		if let Some(kind) = self.local(token) {
+			return kind;
+		}
+
+		self.symbols
+			.get(&token.text().to_ascii_lowercase())
+			.copied()
+			.unwrap_or(TokenKind::Field)
+	}
+
+	/// For identifiers in type position.
+	#[must_use]
+	fn type_name(&self, token: &SyntaxToken) -> TokenKind {
+		match self.symbols.get(&token.text().to_ascii_lowercase()) {
+			Some(kind @ (TokenKind::Struct | TokenKind::Enum)) => *kind,
+			_ => TokenKind::Class,
+		}
+	}
+
+	#[must_use]
+	fn local(&mut self, token: &SyntaxToken) -> Option<TokenKind> {
+		let fndecl = token
+			.parent_ancestors()
+			.find(|node| node.kind() == Syntax::FunctionDecl)?;
+
+		if !self.scope.as_ref().is_some_and(|(node, _)| *node == fndecl) {
+			let decls = fn_decls(&fndecl);
+			self.scope = Some((fndecl, decls));
+		}
+
+		let (_, decls) = self.scope.as_ref().unwrap();
+
+		decls
+			.iter()
+			.rev()
+			.filter(|(decl, _)| decl.text_range().start() < token.text_range().start())
+			.find(|(decl, _)| decl.text().eq_ignore_ascii_case(token.text()))
+			.map(|(_, kind)| *kind)
+	}
+}
+
+/// Maps every type, enum variant, and constant declared in the file to its kind.
+#[must_use]
+fn file_symbols(root: &SyntaxNode) -> FxHashMap<String, TokenKind> {
+	let mut ret = FxHashMap::default();
+
+	for node in root.descendants() {
+		let kind = match node.kind() {
+			Syntax::ClassDef => {
+				let Some(head) = node.children().find(|n| n.kind() == Syntax::ClassHead) else {
+					continue;
+				};
+
+				if let Some(name) = first_ident(&head) {
+					ret.insert(name.text().to_ascii_lowercase(), TokenKind::Class);
+				}
+
+				continue;
+			}
+			Syntax::MixinClassDef => TokenKind::Class,
+			Syntax::StructDef => TokenKind::Struct,
+			Syntax::EnumDef => TokenKind::Enum,
+			Syntax::EnumVariant => TokenKind::EnumVariant,
+			Syntax::ConstDef | Syntax::StaticConstStat => TokenKind::Constant,
+			_ => continue,
+		};
+
+		if let Some(name) = first_ident(&node) {
+			ret.insert(name.text().to_ascii_lowercase(), kind);
+		}
+	}
+
+	ret
+}
+
+/// The parameters and local variables declared by `fndecl`, in source order.
+#[must_use]
+fn fn_decls(fndecl: &SyntaxNode) -> Vec<(SyntaxToken, TokenKind)> {
+	let mut ret = vec![];
+
+	for event in fndecl.preorder_with_tokens() {
+		let WalkEvent::Enter(elem) = event else {
+			continue;
+		};
+
+		let Some(token) = elem.into_token() else {
+			continue;
+		};
+
+		if token.kind() != Syntax::Ident {
+			continue;
+		}
+
+		let Some(parent) = token.parent() else {
+			continue;
+		};
+
+		let kind = match parent.kind() {
+			Syntax::Parameter => TokenKind::Parameter,
+			Syntax::LocalVarInit | Syntax::DeclAssignStat => TokenKind::LocalVar,
+			Syntax::VarName
+				if parent
+					.parent()
+					.is_some_and(|gp| gp.kind() == Syntax::ForEachStat) =>
+			{
+				TokenKind::LocalVar
+			}
+			_ => continue,
+		};
+
+		ret.push((token, kind));
+	}
+
+	ret
+}
+
+#[must_use]
+fn first_ident(node: &SyntaxNode) -> Option<SyntaxToken> {
+	node.children_with_tokens()
+		.find_map(|elem| elem.into_token().filter(|t| t.kind() == Syntax::Ident))
+}
+
+/// `true` if `node` is the first child of a [`Syntax::CallExpr`].
+#[must_use]
+fn is_callee(node: &SyntaxNode) -> bool {
+	node.parent().is_some_and(|parent| {
+		parent.kind() == Syntax::CallExpr && parent.first_child().as_ref() == Some(node)
+	})
+}
+
+#[must_use]
+fn native_if(node: &SyntaxNode) -> Modifiers {
+	if node
+		.children_with_tokens()
+		.any(|elem| elem.kind() == Syntax::KwNative)
+	{
+		Modifiers::NATIVE
+	} else {
+		Modifiers::NONE
+	}
+}
+
+/// `member` is a [`Syntax::FieldDecl`] or [`Syntax::FunctionDecl`].
+#[must_use]
+fn member_quals(member: &SyntaxNode) -> Modifiers {
+	let mut ret = Modifiers::NONE;
+
+	let Some(quals) = member
+		.children()
+		.find(|node| node.kind() == Syntax::MemberQuals)
+	else {
+		return ret;
+	};
+
+	for elem in quals.children_with_tokens() {
+		match elem.kind() {
+			Syntax::KwStatic => ret |= Modifiers::STATIC,
+			Syntax::KwNative => ret |= Modifiers::NATIVE,
+			Syntax::DeprecationQual => ret |= Modifiers::DEPRECATED,
+			_ => {}
+		}
+	}
+
+	ret
+}
+
+#[cfg(test)]
+mod test {
+	use rowan::TextSize;
+
+	use crate::zdoom;
+
+	use super::*;
+
+	const SAMPLE: &str = r#####"
+// Lorem ipsum dolor sit amet.
+enum Hue { CLR_RED = 1 }
+const MAX_HP = 100;
+
+class Lorem : Actor replaces Ipsum {
+	int health;
+
+	deprecated("4.0") static void Dolor(int amount) {
+		let dmg = Sit(amount + MAX_HP) * CLR_RED;
+		self.Amet(dmg, health);
+	}
+
+	default {
+		Health 50;
+		+SHOOTABLE
+	}
+
+	states {
+	Spawn:
+		POSS A 10 A_Look;
+		goto Super::Spawn;
+	}
+}
+"#####;
+
+	#[must_use]
+	fn at<'t>(tokens: &'t [SemanticToken], needle: &str, nth: usize) -> &'t SemanticToken {
+		let offs = SAMPLE
+			.match_indices(needle)
+			.nth(nth)
+			.unwrap_or_else(|| panic!("`{needle}` #{nth} not in the sample"))
+			.0;
+
+		let offs = TextSize::from(offs as u32);
+
+		tokens
+			.iter()
+			.find(|t| t.range.start() == offs)
+			.unwrap_or_else(|| panic!("no token at `{needle}` #{nth}"))
+	}
+
+	#[test]
+	fn smoke() {
+		let ptree = crate::parse(
+			SAMPLE,
+			zdoom::zscript::parse::file,
+			zdoom::lex::Context::ZSCRIPT_LATEST,
+		);
+
+		crate::testing::assert_no_errors(&ptree);
+		let tokens = classify(&ptree.cursor());
+
+		// Sorted, non-overlapping, and gap-free save for whitespace.
+		for pair in tokens.windows(2) {
+			assert!(pair[0].range.end() <= pair[1].range.start());
+
+			let between =
+				&SAMPLE[usize::from(pair[0].range.end())..usize::from(pair[1].range.start())];
+			assert!(between.chars().all(char::is_whitespace));
+		}
+
+		let cases = [
+			("// Lorem", 0, TokenKind::Comment, Modifiers::NONE),
+			("Hue", 0, TokenKind::Enum, Modifiers::DECLARATION),
+			("CLR_RED", 0, TokenKind::EnumVariant, Modifiers::DECLARATION),
+			("MAX_HP", 0, TokenKind::Constant, Modifiers::DECLARATION),
+			("Lorem", 1, TokenKind::Class, Modifiers::DECLARATION),
+			("Actor", 0, TokenKind::Class, Modifiers::NONE),
+			("Ipsum", 0, TokenKind::Class, Modifiers::NONE),
+			("int", 0, TokenKind::Keyword, Modifiers::NONE),
+			("health", 0, TokenKind::Field, Modifiers::DECLARATION),
+			("\"4.0\"", 0, TokenKind::String, Modifiers::NONE),
+			(
+				"Dolor",
+				0,
+				TokenKind::Function,
+				Modifiers::DECLARATION | Modifiers::DEPRECATED | Modifiers::STATIC,
+			),
+			("amount", 0, TokenKind::Parameter, Modifiers::DECLARATION),
+			("dmg ", 0, TokenKind::LocalVar, Modifiers::DECLARATION),
+			("Sit", 0, TokenKind::Function, Modifiers::NONE),
+			("amount", 1, TokenKind::Parameter, Modifiers::NONE),
+			("+", 0, TokenKind::Operator, Modifiers::NONE),
+			("MAX_HP", 1, TokenKind::Constant, Modifiers::NONE),
+			("CLR_RED", 1, TokenKind::EnumVariant, Modifiers::NONE),
+			("self", 0, TokenKind::Field, Modifiers::NONE),
+			("Amet", 0, TokenKind::Method, Modifiers::NONE),
+			("dmg,", 0, TokenKind::LocalVar, Modifiers::NONE),
+			("health)", 0, TokenKind::Field, Modifiers::NONE),
+			("Health", 0, TokenKind::Property, Modifiers::NONE),
+			("50", 0, TokenKind::Number, Modifiers::NONE),
+			("SHOOTABLE", 0, TokenKind::Property, Modifiers::NONE),
+			("Spawn", 0, TokenKind::StateLabel, Modifiers::DECLARATION),
+			("POSS", 0, TokenKind::SpriteName, Modifiers::NONE),
+			("A 10", 0, TokenKind::SpriteName, Modifiers::NONE),
+			("A_Look", 0, TokenKind::Function, Modifiers::NONE),
+			("goto", 0, TokenKind::Keyword, Modifiers::NONE),
+			("Super", 0, TokenKind::Keyword, Modifiers::NONE),
+			("Spawn", 1, TokenKind::StateLabel, Modifiers::NONE),
+		];
+
+		for (needle, nth, kind, mods) in cases {
+			let token = at(&tokens, needle, nth);
+			assert_eq!(token.kind, kind, "`{needle}` #{nth}");
+			assert_eq!(token.mods, mods, "`{needle}` #{nth}");
+		}
+	}
+}
@@ -52,7 +52,9 @@ pub enum Syntax {
 	/// The third part of a `for` loop's opening "header".
 	ForLoopIter,
 	FunctionDecl,
-	/// `'goto' 'super::'? identchain ('+' integer)?`
+	/// `(('super' | ident) '::')? identchain ('+' integer)?`
+	///
+	/// Always a child of a [`Syntax::StateFlow`] node, after [`Syntax::KwGoto`].
 	GotoOffset,
 	/// `(ident) | ('.'? ident ('.' ident)*)`
 	///
@@ -503,6 +505,15 @@ impl Syntax {
 		)
 	}
 
+	/// Note that `true` and `false` are [keywords](Self::is_keyword).
+	#[must_use]
+	pub fn is_literal(self) -> bool {
+		matches!(
+			self,
+			Self::FloatLit | Self::IntLit | Self::NameLit | Self::NullLit | Self::StringLit
+		)
+	}
+
 	#[must_use]
 	pub fn is_keyword(self) -> bool {
 		let u = self as u16;
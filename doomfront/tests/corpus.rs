@@ -0,0 +1,65 @@
+//! Replays every input under `tests/corpus/<target>/` through the function run
+//! by the fuzz target of the same name (see the "Fuzzing" section of `README.md`), so that inputs
+//! which once crashed a parser keep being checked by `cargo test`.
+
+#![cfg(feature = "zdoom")]
+
+use std::path::{Path, PathBuf};
+
+#[test]
+fn corpus() {
+	let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+	let mut count = 0;
+	let mut failures: Vec<PathBuf> = vec![];
+
+	for result in walkdir::WalkDir::new(&root).min_depth(2).max_depth(2) {
+		let entry = result.unwrap();
+
+		if !entry.file_type().is_file() {
+			continue;
+		}
+
+		let path = entry.into_path();
+
+		let target = path
+			.parent()
+			.and_then(|dir| dir.file_name())
+			.and_then(|name| name.to_str())
+			.unwrap();
+
+		let function = match target {
+			"udmf_textmap" => Some(subterra::level::udmf::fuzz as fn(&str)),
+			other => doomfront::testing::fuzz_target(other),
+		};
+
+		let Some(function) = function else {
+			panic!("`{}` is not in a fuzz target's directory", path.display());
+		};
+
+		let bytes = std::fs::read(&path).unwrap();
+
+		// (RAT) The fuzz targets discard non-UTF-8 input in the same way.
+		let Ok(source) = std::str::from_utf8(&bytes) else {
+			continue;
+		};
+
+		count += 1;
+
+		if std::panic::catch_unwind(|| function(source)).is_err() {
+			failures.push(path);
+		}
+	}
+
+	assert!(
+		count > 0,
+		"no corpus inputs found under `{}`",
+		root.display()
+	);
+
+	assert!(
+		failures.is_empty(),
+		"{} corpus input(s) failed: {:#?}",
+		failures.len(),
+		failures
+	);
+}
@@ -4,6 +4,7 @@
 //! and projectiles are all actors.
 
 pub mod blueprint;
+pub mod state;
 
 use std::num::NonZeroI32;
 
@@ -12,11 +13,65 @@ use bitflags::bitflags;
 use serde::{Deserialize, Serialize};
 
 pub use blueprint::Blueprint;
+pub use state::{StateMachine, StateTable};
 
 /// Strongly-typed [`Entity`] wrapper.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Actor(Entity);
 
+impl Actor {
+	#[must_use]
+	pub fn new(entity: Entity) -> Self {
+		Self(entity)
+	}
+
+	#[must_use]
+	pub fn entity(self) -> Entity {
+		self.0
+	}
+}
+
+// Health //////////////////////////////////////////////////////////////////////
+
+#[derive(Debug, Component, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+	/// Can go below zero; how far below decides between a normal and
+	/// "extreme" death. See [`Blueprint::gib_health`].
+	pub current: i32,
+}
+
+impl Health {
+	#[must_use]
+	pub fn new(blueprint: &Blueprint) -> Self {
+		Self {
+			current: blueprint.health_starting,
+		}
+	}
+
+	#[must_use]
+	pub fn is_dead(self) -> bool {
+		self.current <= 0
+	}
+}
+
+/// A "flag" component for actors which have been killed.
+/// AI systems should exclude actors with this.
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Corpse;
+
+/// A "flag" component for actors which block the movement of others.
+/// Removed when an actor dies.
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct Solid;
+
+/// A "flag" component for actors controlled by a [player](crate::player::Player).
+/// These are what monsters look for and chase.
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct PlayerPawn;
+
 // Monster /////////////////////////////////////////////////////////////////////
 
 #[derive(Debug, Component)]
@@ -1,9 +1,101 @@
+use std::sync::Arc;
+
 use bevy::prelude::*;
 use data::{EditorNum, SpawnNum};
 
+use crate::{asset::AssetStore, gfx::style::RenderStyle};
+
+use super::state::StateTable;
+
 /// The prototype used to instantiate new entities.
 #[derive(Asset, TypePath, Debug)]
 pub struct Blueprint {
 	pub editor_num: EditorNum,
 	pub spawn_num: SpawnNum,
+	/// What the actor's health gets set to upon spawning. Default is 1000.
+	pub health_starting: i32,
+	/// Health value below which the actor enters its `XDeath` state sequence
+	/// upon dying. Conventionally the negation of `health_starting`.
+	pub gib_health: i32,
+	/// Out of 256; the chance that damage which does not kill the actor sends
+	/// it to its `Pain` state sequence. 256 means "always".
+	pub pain_chance: u16,
+	/// "Actor finite state machine".
+	pub states: StateTable,
+	/// Copied into a component of each actor spawned from this blueprint.
+	pub render_style: RenderStyle,
+	/// In map units. Default is 20.
+	pub radius: f32,
+	/// In map units. Default is 16.
+	pub height: f32,
+	/// How far a monster moves per step of its chase logic. Default is 0.
+	pub speed: f32,
+	/// How close (beyond the target's radius) a monster must be to its target
+	/// to make a melee attack. Default is 44.
+	pub melee_range: f32,
+	/// Multiplied by a random number from 1 to 8 per melee attack. Default is 0.
+	pub melee_damage: i32,
+	/// Multiplied by a random number from 1 to 3 per hitscan attack
+	/// (e.g. the pistol's 5). Default is 0.
+	pub hitscan_damage: i32,
+}
+
+impl AssetStore {
+	/// Reads `bp`'s editor number and spawn number into separate indices,
+	/// so it is found by [`Self::bp_by_ednum`] only if it has a `DoomEdNum`
+	/// and by [`Self::bp_by_spawnnum`] only if it has a `SpawnID`.
+	pub fn register_blueprint(&mut self, id: impl Into<String>, bp: Blueprint) -> Arc<Blueprint> {
+		let (editor_num, spawn_num) = (bp.editor_num, bp.spawn_num);
+		self.register_actor(id, editor_num, spawn_num, bp)
+	}
+
+	/// The last blueprint assigned editor number `num`.
+	#[must_use]
+	pub fn bp_by_ednum(&self, num: EditorNum) -> Option<Arc<Blueprint>> {
+		self.by_ednum(num)
+	}
+
+	/// The last blueprint assigned spawn number `num`. Used by ACS.
+	#[must_use]
+	pub fn bp_by_spawnnum(&self, num: SpawnNum) -> Option<Arc<Blueprint>> {
+		self.by_spawnnum(num)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn blueprint(editor_num: EditorNum, spawn_num: SpawnNum) -> Blueprint {
+		Blueprint {
+			editor_num,
+			spawn_num,
+			health_starting: 1000,
+			gib_health: -1000,
+			pain_chance: 0,
+			states: StateTable::default(),
+			render_style: RenderStyle::Normal,
+			radius: 20.0,
+			height: 16.0,
+			speed: 0.0,
+			melee_range: 44.0,
+			melee_damage: 0,
+			hitscan_damage: 0,
+		}
+	}
+
+	#[test]
+	fn spawn_num_without_ednum() {
+		let mut store = AssetStore::default();
+		let bp = store.register_blueprint("spawnid_only", blueprint(0, 12));
+		store.register_blueprint("zombieman", blueprint(3004, 4));
+
+		assert!(Arc::ptr_eq(&store.bp_by_spawnnum(12).unwrap(), &bp));
+
+		for num in [0, 12, 3004] {
+			assert!(store
+				.bp_by_ednum(num)
+				.map_or(true, |found| !Arc::ptr_eq(&found, &bp)));
+		}
+	}
 }
@@ -0,0 +1,200 @@
+//! Actor "finite state machines": the [definitions](StateTable) carried by
+//! [blueprints](super::Blueprint) and the [per-actor playback](StateMachine).
+
+use bevy::prelude::*;
+
+/// Ordered as defined by the script-based blueprint.
+#[derive(Debug, Default)]
+pub struct StateTable {
+	/// Each element's field `::1` indexes into `states`.
+	labels: Vec<(String, usize)>,
+	states: Vec<StateDef>,
+	/// Parallel to `states`.
+	frames: Vec<Option<Frame>>,
+}
+
+/// One frame of an actor's appearance and behavior.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateDef {
+	/// In ticks. `-1` means "forever".
+	pub duration: i16,
+	/// Index of the state to enter after this one's duration elapses.
+	/// `None` means the actor stays in this state.
+	pub next: Option<usize>,
+	/// Run whenever this state is entered.
+	pub action: Option<Action>,
+}
+
+/// What an actor (or a weapon's view sprite) looks like while in a state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+	pub image: Handle<Image>,
+	/// The picture's left and top offsets, in that order, as in Doom's picture format.
+	pub offset: Vec2,
+}
+
+/// Native behavior which a [`StateDef`] can invoke.
+/// Script-defined actions are not yet supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+	/// `A_Look`; see [`crate::sim::ai::look`].
+	Look,
+	/// `A_Chase`; see [`crate::sim::ai::chase`].
+	Chase,
+	/// `A_FaceTarget`.
+	FaceTarget,
+	/// Faces the target and hits it if it is within melee range.
+	MeleeAttack,
+	/// Currently only faces the target; projectiles are not yet spawned.
+	MissileAttack,
+	/// `A_WeaponReady`; see [`crate::sim::weapon::tick_weapon`].
+	WeaponReady,
+	/// `A_GunFlash`: starts the weapon's `Flash` sequence alongside its own.
+	GunFlash,
+	/// Fires one hitscan attack; see [`crate::sim::weapon::hitscan`].
+	FireHitscan,
+}
+
+impl StateDef {
+	#[must_use]
+	pub fn infinite(&self) -> bool {
+		self.duration == -1
+	}
+}
+
+impl StateTable {
+	/// Returns the index of the newly-added state.
+	pub fn push(&mut self, state: StateDef) -> usize {
+		self.states.push(state);
+		self.frames.push(None);
+		self.states.len() - 1
+	}
+
+	/// Panics if `index` is out of bounds.
+	pub fn set_frame(&mut self, index: usize, frame: Frame) {
+		self.frames[index] = Some(frame);
+	}
+
+	/// Panics if `index` is out of bounds.
+	pub fn add_label(&mut self, label: impl Into<String>, index: usize) {
+		assert!(index < self.states.len(), "state label index out of bounds");
+		self.labels.push((label.into(), index));
+	}
+
+	/// Labels are compared ASCII case-insensitively, as in ZDoom.
+	#[must_use]
+	pub fn find(&self, label: &str) -> Option<usize> {
+		self.labels
+			.iter()
+			.find(|(l, _)| l.eq_ignore_ascii_case(label))
+			.map(|(_, i)| *i)
+	}
+
+	#[must_use]
+	pub fn get(&self, index: usize) -> Option<&StateDef> {
+		self.states.get(index)
+	}
+
+	/// Returns `None` if the state is out of bounds or is invisible.
+	#[must_use]
+	pub fn frame(&self, index: usize) -> Option<&Frame> {
+		self.frames.get(index).and_then(Option::as_ref)
+	}
+
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.states.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.states.is_empty()
+	}
+}
+
+/// An actor's position in its blueprint's [`StateTable`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateMachine {
+	pub state: usize,
+	/// Ticks left until the next state is entered. `-1` means "never".
+	pub tics: i16,
+	/// Set whenever a state is entered; see [`Self::take_entered`].
+	entered: bool,
+}
+
+impl StateMachine {
+	/// Starts at the state under the label `Spawn`, or the first state if
+	/// there is no such label. Returns `None` if `table` is empty.
+	#[must_use]
+	pub fn new(table: &StateTable) -> Option<Self> {
+		let state = table.find("Spawn").unwrap_or(0);
+		let def = table.get(state)?;
+
+		Some(Self {
+			state,
+			tics: def.duration,
+			entered: false,
+		})
+	}
+
+	/// Like [`Self::new`], but starting at the state under `label`.
+	/// Returns `None` if `table` has no such label.
+	#[must_use]
+	pub fn at_label(table: &StateTable, label: &str) -> Option<Self> {
+		let state = table.find(label)?;
+
+		Some(Self {
+			state,
+			tics: table.states[state].duration,
+			entered: false,
+		})
+	}
+
+	/// Returns `false` (and changes nothing) if `table` has no such label.
+	pub fn enter_label(&mut self, table: &StateTable, label: &str) -> bool {
+		let Some(index) = table.find(label) else {
+			return false;
+		};
+
+		self.enter(table, index);
+		true
+	}
+
+	/// Panics if `index` is out of bounds.
+	pub fn enter(&mut self, table: &StateTable, index: usize) {
+		self.state = index;
+		self.tics = table.states[index].duration;
+		self.entered = true;
+	}
+
+	/// Returns `true` if a state has been entered since the last call,
+	/// meaning the current state's [action](StateDef::action) is due to run.
+	/// The state which an actor spawns in does not count as entered.
+	pub fn take_entered(&mut self) -> bool {
+		std::mem::take(&mut self.entered)
+	}
+
+	/// Advance by one tick, moving to the next state if this one's time is up.
+	/// Zero-duration states are passed through within the same tick.
+	pub fn tick(&mut self, table: &StateTable) {
+		if self.tics == -1 {
+			return;
+		}
+
+		self.tics = (self.tics - 1).max(0);
+
+		// (RAT) Bounded so a cycle of zero-duration states can not hang the sim.
+		for _ in 0..table.len() {
+			if self.tics > 0 {
+				return;
+			}
+
+			let Some(next) = table.states[self.state].next else {
+				self.tics = -1;
+				return;
+			};
+
+			self.enter(table, next);
+		}
+	}
+}
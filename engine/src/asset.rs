@@ -1,5 +1,7 @@
 //! Code bridging [`data`] and [`bevy::asset`].
 
+mod store;
+
 use bevy::{
 	prelude::*,
 	render::{
@@ -7,7 +9,14 @@ use bevy::{
 		texture::{ImageAddressMode, ImageFilterMode, ImageSampler, ImageSamplerDescriptor},
 	},
 };
-use data::gfx::{ColorMap, Palette, PaletteSet, PictureReader};
+use data::gfx::{
+	font::{self, Font},
+	ColorMap, ColorMapSet, Palette, PaletteSet, PatchTable, PatchedTex, PictureReader, TextureX,
+};
+use util::Id8;
+use vfs::{FileRef, FolderRef, MountFormat, Slot, VPath, VirtualFs};
+
+pub use self::store::{AssetKey, AssetLookupError, AssetStore};
 
 #[must_use]
 pub fn flat_to_image(
@@ -121,6 +130,303 @@ pub fn palset_to_image(palset: &PaletteSet, label: Option<String>) -> Image {
 	img
 }
 
+/// Boom's colormap namespace: each lump between the `C_START` and `C_END`
+/// markers of `wad` is a set in the same format as `COLORMAP`, which sectors
+/// refer to by lump name (see [`Sector::colormap`]).
+/// Lumps are yielded in WAD order, with an error for any which is too short;
+/// any which cannot be read are skipped.
+///
+/// [`Sector::colormap`]: crate::world::level::Sector::colormap
+#[must_use]
+pub fn boom_colormaps(wad: FolderRef) -> Vec<(Id8, Result<Box<[ColorMap; 34]>, data::Error>)> {
+	wad.namespace("C_START", "C_END")
+		.filter_map(|fref| {
+			let name = fref.name().lump_name()?;
+			let mut guard = fref.lock();
+			let bytes = guard.read().ok()?;
+			// (RAT) Like Boom, ignore anything past the 34th map.
+			let len = bytes.len().min(256 * 34);
+			let result = ColorMapSet::new(&bytes[..len]).map(|set| Box::new(set.into_owned()));
+			Some((name, result))
+		})
+		.collect()
+}
+
+/// Each FON1 and FON2 lump in `wad` under its own name, in WAD order, and then
+/// Doom's HUD font under `STCFN`: one [picture](PictureReader) per character
+/// (see [`font::stcfn_char`]), composed into one font with `palette`.
+/// Lumps which cannot be read are skipped.
+#[must_use]
+pub fn wad_fonts(wad: FolderRef, palette: &Palette) -> Vec<(Id8, Result<Font, data::Error>)> {
+	let mut ret = vec![];
+	let mut stcfn = vec![];
+
+	for fref in wad.files() {
+		let Some(name) = fref.name().lump_name() else {
+			continue;
+		};
+
+		let mut guard = fref.lock();
+
+		let Ok(bytes) = guard.read() else {
+			continue;
+		};
+
+		if Font::is_fon1(&bytes) {
+			ret.push((name, Font::from_fon1(&bytes)));
+		} else if Font::is_fon2(&bytes) {
+			ret.push((name, Font::from_fon2(&bytes)));
+		} else if let Some(ch) = font::stcfn_char(&name) {
+			stcfn.push((ch, bytes.into_owned()));
+		}
+	}
+
+	if !stcfn.is_empty() {
+		let patches = stcfn.iter().map(|(ch, bytes)| (*ch, bytes.as_slice()));
+		let name = Id8::try_from("STCFN").unwrap();
+		ret.push((name, Font::from_patches(patches, palette)));
+	}
+
+	ret
+}
+
+/// See [`resolve_texture`].
+#[derive(Debug)]
+pub enum TextureSource<'vfs> {
+	/// A graphic between the `TX_START` and `TX_END` markers of a WAD,
+	/// or under the `textures` folder of any other kind of mount.
+	Single(FileRef<'vfs>),
+	/// To be assembled from patches per a TEXTURE1 or TEXTURE2 definition.
+	/// Each of `patches` corresponds to one of `def`'s patches, and is `None`
+	/// if the patch index is out of range or its lump can not be found.
+	Composite {
+		def: PatchedTex,
+		patches: Vec<Option<FileRef<'vfs>>>,
+	},
+}
+
+/// Finds what a level referring to texture `name` should use. Mounts are
+/// searched in reverse load order, so that the latest one defining `name` wins,
+/// whether its definition is a composite or a [single](TextureSource::Single)
+/// graphic. Within one mount, the latter wins.
+///
+/// As in vanilla, composites only come from the latest TEXTURE1 and TEXTURE2
+/// lumps, together with the latest PNAMES lump. They count as defined by the
+/// latest mount with any of those three lumps; a mount which only redefines
+/// PNAMES re-assembles the previous TEXTUREx against its own patch table.
+/// Patch lumps are looked up across all mounts, latest first.
+///
+/// `name` is compared ASCII case-insensitively.
+/// TEXTUREx and PNAMES lumps which can not be read are ignored.
+pub fn resolve_texture<'vfs>(
+	vfs: &'vfs VirtualFs,
+	name: &Id8,
+) -> Result<Option<TextureSource<'vfs>>, data::Error> {
+	let mut name = *name;
+	name.make_ascii_uppercase();
+
+	let roots: Vec<_> = vfs
+		.mounts()
+		.iter()
+		.filter_map(|mntinfo| match mntinfo.root {
+			Slot::Folder(oslot) => Some((mntinfo.format, vfs.get_folder(oslot).unwrap())),
+			Slot::File(_) => None,
+		})
+		.collect();
+
+	let latest = |lump: &str| {
+		roots
+			.iter()
+			.enumerate()
+			.rev()
+			.find_map(|(i, (_, root))| find_lump(*root, lump).map(|fref| (i, fref)))
+	};
+
+	let texture1 = latest("TEXTURE1");
+	let texture2 = latest("TEXTURE2");
+	let pnames = latest("PNAMES");
+
+	let composite_mount = [texture1, texture2, pnames]
+		.into_iter()
+		.flatten()
+		.map(|(i, _)| i)
+		.max();
+
+	for (i, (format, root)) in roots.iter().enumerate().rev() {
+		let single = if *format == MountFormat::Wad {
+			root.namespace("TX_START", "TX_END")
+				.filter(|fref| fref.name().lump_name() == Some(name))
+				.last()
+		} else {
+			root.lookup(VPath::new("textures"))
+				.and_then(|r| r.into_folder())
+				.and_then(|folder| find_by_prefix(folder, &name))
+		};
+
+		if let Some(fref) = single {
+			return Ok(Some(TextureSource::Single(fref)));
+		}
+
+		if composite_mount == Some(i) {
+			if let Some(composite) = composite(&roots, &name, [texture1, texture2], pnames)? {
+				return Ok(Some(composite));
+			}
+		}
+	}
+
+	Ok(None)
+}
+
+/// See [`resolve_texture`]. `roots` are in load order.
+fn composite<'vfs>(
+	roots: &[(MountFormat, FolderRef<'vfs>)],
+	name: &Id8,
+	texturex: [Option<(usize, FileRef)>; 2],
+	pnames: Option<(usize, FileRef)>,
+) -> Result<Option<TextureSource<'vfs>>, data::Error> {
+	let Some(bytes) = pnames.and_then(|(_, fref)| read_lump(fref)) else {
+		return Ok(None);
+	};
+
+	let patch_table = PatchTable::new(&bytes)?.unwrap_or_default();
+
+	// (RAT) TEXTURE1 comes first, since vanilla takes the first match.
+	for (_, texx) in texturex.into_iter().flatten() {
+		let Some(bytes) = read_lump(texx) else {
+			continue;
+		};
+
+		let Some(mut texx) = TextureX::new(&bytes)? else {
+			continue;
+		};
+
+		let Some(pos) = texx
+			.iter()
+			.position(|def| def.name.eq_ignore_ascii_case(name))
+		else {
+			continue;
+		};
+
+		let def = texx.swap_remove(pos);
+
+		let patches = def
+			.patches
+			.iter()
+			.map(|patch| {
+				let pname = patch_table.get(patch.index)?;
+
+				roots.iter().rev().find_map(|(format, root)| {
+					if *format == MountFormat::Wad {
+						find_lump(*root, pname)
+					} else {
+						root.lookup(VPath::new("patches"))
+							.and_then(|r| r.into_folder())
+							.and_then(|folder| find_by_prefix(folder, pname))
+					}
+				})
+			})
+			.collect();
+
+		return Ok(Some(TextureSource::Composite { def, patches }));
+	}
+
+	Ok(None)
+}
+
+/// The last file directly in `folder` with lump name `name`,
+/// as per vanilla's `W_CheckNumForName`.
+#[must_use]
+fn find_lump<'vfs>(folder: FolderRef<'vfs>, name: &str) -> Option<FileRef<'vfs>> {
+	folder
+		.files()
+		.filter(|fref| {
+			fref.name()
+				.lump_name()
+				.is_some_and(|n| n.eq_ignore_ascii_case(name))
+		})
+		.last()
+}
+
+/// The first file under `folder` with a file prefix of `name`,
+/// searching subfolders only if `folder` itself has no such file.
+#[must_use]
+fn find_by_prefix<'vfs>(folder: FolderRef<'vfs>, name: &str) -> Option<FileRef<'vfs>> {
+	folder
+		.files()
+		.find(|fref| {
+			fref.name()
+				.file_prefix()
+				.is_some_and(|pfx| pfx.as_str().eq_ignore_ascii_case(name))
+		})
+		.or_else(|| {
+			folder
+				.subfolders()
+				.find_map(|sub| find_by_prefix(sub, name))
+		})
+}
+
+#[must_use]
+fn read_lump(fref: FileRef) -> Option<Vec<u8>> {
+	let mut guard = fref.lock();
+	guard.read().ok().map(|bytes| bytes.into_owned())
+}
+
+/// A [picture](PictureReader) (e.g. a TEXTUREx patch) decoded to 8-bit RGBA,
+/// for compositing on the CPU before anything gets uploaded as an [`Image`].
+/// Pixels which no column post covers are fully transparent.
+#[derive(Debug, Clone)]
+pub struct PatchImage {
+	inner: image::RgbaImage,
+	offset: IVec2,
+}
+
+impl PatchImage {
+	pub fn from_picture(
+		bytes: &[u8],
+		palette: &Palette,
+		colormap: &ColorMap,
+	) -> Result<Self, data::Error> {
+		let pic_reader = PictureReader::new(bytes)?;
+		let (left, top) = pic_reader.offset();
+
+		let mut inner =
+			image::RgbaImage::new(pic_reader.width() as u32, pic_reader.height() as u32);
+
+		pic_reader.read(palette, colormap, |row, col, pixel| {
+			inner.put_pixel(row, col, image::Rgba([pixel.r, pixel.g, pixel.b, 255]));
+		});
+
+		Ok(Self {
+			inner,
+			offset: IVec2::new(left as i32, top as i32),
+		})
+	}
+
+	/// Width, then height.
+	#[must_use]
+	pub fn dimensions(&self) -> (u32, u32) {
+		self.inner.dimensions()
+	}
+
+	/// Panics if `x` or `y` is out of bounds.
+	#[must_use]
+	pub fn pixel(&self, x: u32, y: u32) -> image::Rgba<u8> {
+		*self.inner.get_pixel(x, y)
+	}
+
+	/// Row-major, four bytes per pixel.
+	#[must_use]
+	pub fn as_bytes(&self) -> &[u8] {
+		self.inner.as_raw()
+	}
+
+	/// See [`PictureReader::offset`].
+	#[must_use]
+	pub fn offset(&self) -> IVec2 {
+		self.offset
+	}
+}
+
 pub fn picture_to_image(
 	bytes: &[u8],
 	palette: &Palette,
@@ -167,3 +473,285 @@ pub fn picture_to_image(
 
 	Ok(img)
 }
+
+#[cfg(test)]
+mod test {
+	use data::gfx::Rgb8;
+
+	use super::*;
+
+	#[test]
+	fn boom_colormaps() {
+		let dir = std::env::temp_dir().join(format!("viletech_boom_cmaps_{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		let wad_path = dir.join("cmaps.wad");
+
+		// Every map is the identity, save for one index.
+		let mut watermap = vec![];
+
+		for _ in 0..34 {
+			watermap.extend(0..=255_u8);
+		}
+
+		watermap[0] = 0xC0;
+
+		std::fs::write(
+			&wad_path,
+			vfs::build_pwad(&[
+				("C_START", vec![]),
+				("WATERMAP", watermap),
+				("SHORTMAP", vec![0; 256]),
+				("C_END", vec![]),
+				("NOTAMAP", vec![0; 256 * 34]),
+			]),
+		)
+		.unwrap();
+
+		let mut vfs = vfs::VirtualFs::default();
+		vfs.mount(&wad_path, vfs::VPath::new("cmaps")).unwrap();
+		let wad = vfs
+			.lookup(vfs::VPath::new("/cmaps"))
+			.unwrap()
+			.into_folder()
+			.unwrap();
+
+		let cmaps = super::boom_colormaps(wad);
+		assert_eq!(cmaps.len(), 2);
+
+		assert_eq!(cmaps[0].0, "WATERMAP");
+		let watermap = cmaps[0].1.as_ref().unwrap();
+		assert_eq!(watermap[0][0], 0xC0);
+		assert_eq!(watermap[33][255], 255);
+
+		assert_eq!(cmaps[1].0, "SHORTMAP");
+		assert!(matches!(cmaps[1].1, Err(data::Error::SizeMismatch { .. })));
+
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+
+	/// 2x2, offset by (3, -4). The first column's post starts at row 0;
+	/// the second column's at row 1.
+	#[must_use]
+	fn picture() -> Vec<u8> {
+		let mut bytes = vec![];
+		bytes.extend_from_slice(&2_u16.to_le_bytes());
+		bytes.extend_from_slice(&2_u16.to_le_bytes());
+		bytes.extend_from_slice(&3_i16.to_le_bytes());
+		bytes.extend_from_slice(&(-4_i16).to_le_bytes());
+		bytes.extend_from_slice(&16_u32.to_le_bytes());
+		bytes.extend_from_slice(&22_u32.to_le_bytes());
+		bytes.extend_from_slice(&[0, 1, 0, 7, 0, 255]);
+		bytes.extend_from_slice(&[1, 1, 0, 9, 0, 255]);
+		bytes
+	}
+
+	#[must_use]
+	fn palette() -> Palette {
+		Palette(std::array::from_fn(|i| Rgb8 {
+			r: i as u8,
+			g: 0,
+			b: 255 - (i as u8),
+		}))
+	}
+
+	#[test]
+	fn patch_image() {
+		let colormap = ColorMap(std::array::from_fn(|i| i as u8));
+		let patch = PatchImage::from_picture(&picture(), &palette(), &colormap).unwrap();
+
+		assert_eq!(patch.dimensions(), (2, 2));
+		assert_eq!(patch.offset(), IVec2::new(3, -4));
+		assert_eq!(patch.pixel(0, 0), image::Rgba([7, 0, 248, 255]));
+		assert_eq!(patch.pixel(1, 1), image::Rgba([9, 0, 246, 255]));
+		assert_eq!(patch.pixel(1, 0), image::Rgba([0, 0, 0, 0]));
+		assert_eq!(patch.pixel(0, 1), image::Rgba([0, 0, 0, 0]));
+		assert_eq!(patch.as_bytes().len(), 2 * 2 * 4);
+		assert_eq!(&patch.as_bytes()[..4], &[7, 0, 248, 255]);
+	}
+
+	#[test]
+	fn wad_fonts() {
+		let dir = std::env::temp_dir().join(format!("viletech_fonts_{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		let wad_path = dir.join("fonts.wad");
+
+		// 1x1 glyphs, each of shade 1.
+		let mut fon1 = b"FON1".to_vec();
+		fon1.extend_from_slice(&1_u16.to_le_bytes());
+		fon1.extend_from_slice(&1_u16.to_le_bytes());
+		fon1.extend_from_slice(&[(-127_i8) as u8, 1, (-127_i8) as u8, 1]);
+
+		std::fs::write(
+			&wad_path,
+			vfs::build_pwad(&[
+				("STCFN065", picture()),
+				("CONFONT", fon1),
+				("BADFONT", b"FON2".to_vec()),
+				("STCFN066", picture()),
+				("STCFNXYZ", vec![0; 4]),
+			]),
+		)
+		.unwrap();
+
+		let mut vfs = vfs::VirtualFs::default();
+		vfs.mount(&wad_path, vfs::VPath::new("fonts")).unwrap();
+		let wad = vfs
+			.lookup(vfs::VPath::new("/fonts"))
+			.unwrap()
+			.into_folder()
+			.unwrap();
+
+		let fonts = super::wad_fonts(wad, &palette());
+		assert_eq!(fonts.len(), 3);
+
+		assert_eq!(fonts[0].0, "CONFONT");
+		let confont = fonts[0].1.as_ref().unwrap();
+		assert_eq!(confont.kind(), font::FontKind::Fon1);
+		assert_eq!(confont.measure("AB", 1), (2, 1));
+
+		assert_eq!(fonts[1].0, "BADFONT");
+		assert!(fonts[1].1.is_err());
+
+		assert_eq!(fonts[2].0, "STCFN");
+		let stcfn = fonts[2].1.as_ref().unwrap();
+		assert_eq!(stcfn.kind(), font::FontKind::Patches);
+		assert_eq!(stcfn.height(), 2);
+		assert!(stcfn.glyph('A').is_some());
+		assert!(stcfn.glyph('B').is_some());
+		assert!(stcfn.glyph('C').is_none());
+
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn resolve_texture() {
+		let dir = std::env::temp_dir().join(format!("viletech_textures_{}", std::process::id()));
+		std::fs::create_dir_all(dir.join("loose/textures")).unwrap();
+
+		std::fs::write(
+			dir.join("base.wad"),
+			vfs::build_pwad(&[
+				("PNAMES", pnames(&["BASEPAT"])),
+				("TEXTURE1", texturex(&[("WALL01", 64), ("WALL02", 32)])),
+				("BASEPAT", picture()),
+				("TX_START", vec![]),
+				("WALL01", picture()),
+				("WALL03", picture()),
+				("TX_END", vec![]),
+			]),
+		)
+		.unwrap();
+
+		// Redefines one texture, and so overrides `base.wad`'s namespaced one too.
+		std::fs::write(
+			dir.join("redef.wad"),
+			vfs::build_pwad(&[("TEXTURE1", texturex(&[("WALL01", 128)]))]),
+		)
+		.unwrap();
+
+		// Only redefines PNAMES, so gets `redef.wad`'s TEXTURE1 with its own patches.
+		std::fs::write(
+			dir.join("pnames.wad"),
+			vfs::build_pwad(&[("PNAMES", pnames(&["NEWPAT"])), ("NEWPAT", picture())]),
+		)
+		.unwrap();
+
+		std::fs::write(dir.join("loose/textures/wall03.png"), b"").unwrap();
+
+		let mut vfs = vfs::VirtualFs::default();
+		vfs.mount(&dir.join("base.wad"), vfs::VPath::new("base"))
+			.unwrap();
+		vfs.mount(&dir.join("redef.wad"), vfs::VPath::new("redef"))
+			.unwrap();
+
+		fn wall01(vfs: &vfs::VirtualFs) -> (PatchedTex, Vec<Option<FileRef<'_>>>) {
+			match super::resolve_texture(vfs, &id8("wall01")).unwrap() {
+				Some(TextureSource::Composite { def, patches }) => (def, patches),
+				other => panic!("expected a composite, found: {other:#?}"),
+			}
+		}
+
+		let (def, patches) = wall01(&vfs);
+		assert_eq!(def.size_x, 128);
+		assert_eq!(patches[0].unwrap().path().as_str(), "/base/BASEPAT");
+
+		// Only in the overridden TEXTURE1.
+		assert!(super::resolve_texture(&vfs, &id8("WALL02"))
+			.unwrap()
+			.is_none());
+
+		vfs.mount(&dir.join("pnames.wad"), vfs::VPath::new("pnames"))
+			.unwrap();
+		let (def, patches) = wall01(&vfs);
+		assert_eq!(def.size_x, 128);
+		assert_eq!(patches[0].unwrap().path().as_str(), "/pnames/NEWPAT");
+
+		let Some(TextureSource::Single(wall03)) =
+			super::resolve_texture(&vfs, &id8("WALL03")).unwrap()
+		else {
+			panic!("expected a namespaced texture");
+		};
+
+		assert_eq!(wall03.path().as_str(), "/base/WALL03");
+
+		vfs.mount(&dir.join("loose"), vfs::VPath::new("loose"))
+			.unwrap();
+
+		let Some(TextureSource::Single(wall03)) =
+			super::resolve_texture(&vfs, &id8("WALL03")).unwrap()
+		else {
+			panic!("expected a namespaced texture");
+		};
+
+		assert_eq!(wall03.path().as_str(), "/loose/textures/wall03.png");
+		assert!(super::resolve_texture(&vfs, &id8("WALL04"))
+			.unwrap()
+			.is_none());
+
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+
+	#[must_use]
+	fn id8(name: &str) -> Id8 {
+		Id8::try_from(name).unwrap()
+	}
+
+	#[must_use]
+	fn pnames(names: &[&str]) -> Vec<u8> {
+		let mut ret = (names.len() as u32).to_le_bytes().to_vec();
+
+		for name in names {
+			let mut raw = [0_u8; 8];
+			raw[..name.len()].copy_from_slice(name.as_bytes());
+			ret.extend_from_slice(&raw);
+		}
+
+		ret
+	}
+
+	/// Each texture is `(name, width)`, 64 units tall,
+	/// and made of patch 0 in the PNAMES table at the origin.
+	#[must_use]
+	fn texturex(textures: &[(&str, u16)]) -> Vec<u8> {
+		let mut ret = (textures.len() as u32).to_le_bytes().to_vec();
+		let defs_start = 4 + (textures.len() * 4);
+
+		for i in 0..textures.len() {
+			ret.extend_from_slice(&((defs_start + (i * 32)) as u32).to_le_bytes());
+		}
+
+		for (name, width) in textures {
+			let mut raw = [0_u8; 8];
+			raw[..name.len()].copy_from_slice(name.as_bytes());
+			ret.extend_from_slice(&raw);
+			ret.extend_from_slice(&0_u32.to_le_bytes()); // Masked
+			ret.extend_from_slice(&width.to_le_bytes());
+			ret.extend_from_slice(&64_u16.to_le_bytes()); // Height
+			ret.extend_from_slice(&0_u32.to_le_bytes()); // Column directory
+			ret.extend_from_slice(&1_u16.to_le_bytes()); // Patch count
+			ret.extend_from_slice(&[0; 10]); // Origin, patch index, and unused fields
+		}
+
+		ret
+	}
+}
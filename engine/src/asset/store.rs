@@ -0,0 +1,617 @@
+//! A type-checked registry of assets of any type, for lookups by ID, nickname,
+//! editor number, or spawn number.
+//!
+//! Assets are kept type-erased alongside the [`TypeId`] they were registered
+//! with, and every lookup checks that ID against the type asked for. A
+//! bookkeeping bug (e.g. a nickname leading to an asset of another type) then
+//! surfaces as an [`AssetLookupError::TypeMismatch`] at the lookup which found
+//! it, rather than as a panic somewhere downstream.
+
+use std::{
+	any::{Any, TypeId},
+	hash::{Hash, Hasher},
+	sync::{
+		atomic::{self, AtomicBool},
+		Arc,
+	},
+};
+
+use data::{EditorNum, SpawnNum};
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
+use smallvec::SmallVec;
+use tracing::error;
+use util::SmallString;
+
+/// Built from an asset ID or nickname, and the type of asset it is for.
+/// Keys for the same string but different types differ.
+///
+/// Displays as `<type name>/<ID>`.
+#[derive(Debug, Clone)]
+pub struct AssetKey {
+	type_id: TypeId,
+	/// Of the ID or nickname.
+	hash: u64,
+	/// (RAT) This and `id` are only kept for display; comparing and hashing keys
+	/// only involves `type_id` and `hash`, so that lookups stay cheap.
+	type_name: SmallString,
+	id: SmallString,
+}
+
+impl AssetKey {
+	#[must_use]
+	pub fn new<A: 'static>(id: &str) -> Self {
+		let mut hasher = FxHasher::default();
+		id.hash(&mut hasher);
+
+		Self {
+			type_id: TypeId::of::<A>(),
+			hash: hasher.finish(),
+			type_name: std::any::type_name::<A>().into(),
+			id: id.into(),
+		}
+	}
+
+	/// Which type of asset this key was built for.
+	#[must_use]
+	pub fn asset_type(&self) -> TypeId {
+		self.type_id
+	}
+}
+
+impl PartialEq for AssetKey {
+	fn eq(&self, other: &Self) -> bool {
+		self.type_id == other.type_id && self.hash == other.hash
+	}
+}
+
+impl Eq for AssetKey {}
+
+impl Hash for AssetKey {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.type_id.hash(state);
+		self.hash.hash(state);
+	}
+}
+
+impl std::fmt::Display for AssetKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}/{}", self.type_name, self.id)
+	}
+}
+
+#[derive(Debug)]
+pub enum AssetLookupError {
+	/// Holds the ID, nickname, or number that was looked up.
+	NotFound(String),
+	/// A key for one type led to an asset of another.
+	/// Always indicates a bug in the store's bookkeeping.
+	TypeMismatch {
+		id: String,
+		expected: &'static str,
+		found: &'static str,
+	},
+}
+
+impl std::error::Error for AssetLookupError {}
+
+impl std::fmt::Display for AssetLookupError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NotFound(id) => write!(f, "no asset found for: {id}"),
+			Self::TypeMismatch {
+				id,
+				expected,
+				found,
+			} => write!(
+				f,
+				"asset lookup for `{id}` expected type `{expected}` but found `{found}`"
+			),
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Entry {
+	id: String,
+	type_id: TypeId,
+	type_name: &'static str,
+	asset: Arc<dyn Any + Send + Sync>,
+}
+
+impl Entry {
+	fn downcast<A: Any + Send + Sync>(&self) -> Result<Arc<A>, AssetLookupError> {
+		self.asset
+			.clone()
+			.downcast::<A>()
+			.map_err(|_| AssetLookupError::TypeMismatch {
+				id: self.id.clone(),
+				expected: std::any::type_name::<A>(),
+				found: self.type_name,
+			})
+	}
+}
+
+/// See the [module-level documentation](self).
+#[derive(Debug, Default)]
+pub struct AssetStore {
+	assets: FxHashMap<AssetKey, Arc<Entry>>,
+	/// The last element in each stack was registered last, and takes precedence.
+	nicknames: FxHashMap<AssetKey, SmallVec<[Arc<Entry>; 2]>>,
+	/// The last element in each stack was registered last, and takes precedence.
+	editor_nums: FxHashMap<EditorNum, SmallVec<[Arc<Entry>; 2]>>,
+	/// Kept apart from [`Self::editor_nums`], since an actor can have
+	/// an editor number, a spawn number, both, or neither.
+	/// The last element in each stack was registered last, and takes precedence.
+	spawn_nums: FxHashMap<SpawnNum, SmallVec<[Arc<Entry>; 2]>>,
+	/// Every asset in [`Self::assets`], grouped by type and in registration order,
+	/// so that [`Self::assets_of_type`] never has to look at assets of any other type.
+	by_type: FxHashMap<TypeId, Vec<Arc<Entry>>>,
+	/// Set upon the first lookup which fails due to a [type mismatch],
+	/// so that a bookkeeping bug doesn't flood the log.
+	///
+	/// [type mismatch]: AssetLookupError::TypeMismatch
+	lookup_err_logged: AtomicBool,
+}
+
+impl AssetStore {
+	/// If an asset of the same type was already registered under `id`, it gets
+	/// replaced, although nicknames and editor numbers pointing to it remain.
+	pub fn register<A: Any + Send + Sync>(&mut self, id: impl Into<String>, asset: A) -> Arc<A> {
+		let id = id.into();
+		let key = AssetKey::new::<A>(&id);
+		let asset = Arc::new(asset);
+
+		let entry = Arc::new(Entry {
+			id,
+			type_id: TypeId::of::<A>(),
+			type_name: std::any::type_name::<A>(),
+			asset: asset.clone(),
+		});
+
+		debug_assert_eq!(key.asset_type(), entry.type_id);
+
+		let of_type = self.by_type.entry(entry.type_id).or_default();
+
+		if let Some(old) = self.assets.insert(key, entry.clone()) {
+			of_type.retain(|e| !Arc::ptr_eq(e, &old));
+		}
+
+		of_type.push(entry);
+		asset
+	}
+
+	/// Makes the asset of type `A` registered under `id` the first result of
+	/// [`Self::last_asset_by_nick`] for `nick`. Returns `false` if there is no such asset.
+	pub fn add_nickname<A: Any + Send + Sync>(&mut self, nick: &str, id: &str) -> bool {
+		let Some(entry) = self.assets.get(&AssetKey::new::<A>(id)) else {
+			return false;
+		};
+
+		let key = AssetKey::new::<A>(nick);
+		debug_assert_eq!(key.asset_type(), entry.type_id);
+		self.nicknames.entry(key).or_default().push(entry.clone());
+		true
+	}
+
+	/// Makes the asset of type `A` registered under `id` the first result of
+	/// [`Self::by_ednum`] for `num`. Returns `false` if there is no such asset.
+	pub fn add_editor_num<A: Any + Send + Sync>(&mut self, num: EditorNum, id: &str) -> bool {
+		let Some(entry) = self.assets.get(&AssetKey::new::<A>(id)) else {
+			return false;
+		};
+
+		self.editor_nums.entry(num).or_default().push(entry.clone());
+		true
+	}
+
+	/// Makes the asset of type `A` registered under `id` the first result of
+	/// [`Self::by_spawnnum`] for `num`. Returns `false` if there is no such asset.
+	pub fn add_spawn_num<A: Any + Send + Sync>(&mut self, num: SpawnNum, id: &str) -> bool {
+		let Some(entry) = self.assets.get(&AssetKey::new::<A>(id)) else {
+			return false;
+		};
+
+		self.spawn_nums.entry(num).or_default().push(entry.clone());
+		true
+	}
+
+	/// [Registers](Self::register) `asset`, then indexes it under `editor_num`
+	/// (i.e. `DoomEdNum`) and `spawn_num` (i.e. `SpawnID`) independently of each
+	/// other. A number of 0 means "none" and does not get indexed.
+	pub fn register_actor<A: Any + Send + Sync>(
+		&mut self,
+		id: impl Into<String>,
+		editor_num: EditorNum,
+		spawn_num: SpawnNum,
+		asset: A,
+	) -> Arc<A> {
+		let id = id.into();
+		let ret = self.register(id.as_str(), asset);
+
+		if editor_num != 0 {
+			self.add_editor_num::<A>(editor_num, &id);
+		}
+
+		if spawn_num != 0 {
+			self.add_spawn_num::<A>(spawn_num, &id);
+		}
+
+		ret
+	}
+
+	/// Note that `A` here is a filter on the type that comes out of the lookup,
+	/// rather than an assertion that the asset under `id` is that type, so this
+	/// returns an `Option` rather than a [`Result`]. See [`Self::try_get_asset`].
+	#[must_use]
+	pub fn get_asset<A: Any + Send + Sync>(&self, id: &str) -> Option<Arc<A>> {
+		self.logged(self.try_get_asset(id))
+	}
+
+	/// Like [`Self::get_asset`], but distinguishes between nothing being found and an
+	/// asset of the wrong type being found due to a bookkeeping bug.
+	pub fn try_get_asset<A: Any + Send + Sync>(
+		&self,
+		id: &str,
+	) -> Result<Arc<A>, AssetLookupError> {
+		let Some(entry) = self.assets.get(&AssetKey::new::<A>(id)) else {
+			return Err(AssetLookupError::NotFound(id.to_string()));
+		};
+
+		entry.downcast()
+	}
+
+	/// The asset most recently given `nick`. See [`Self::try_last_asset_by_nick`].
+	#[must_use]
+	pub fn last_asset_by_nick<A: Any + Send + Sync>(&self, nick: &str) -> Option<Arc<A>> {
+		self.logged(self.try_last_asset_by_nick(nick))
+	}
+
+	/// Like [`Self::last_asset_by_nick`], but distinguishes between nothing being found
+	/// and an asset of the wrong type being found due to a bookkeeping bug.
+	pub fn try_last_asset_by_nick<A: Any + Send + Sync>(
+		&self,
+		nick: &str,
+	) -> Result<Arc<A>, AssetLookupError> {
+		let Some(stack) = self.nicknames.get(&AssetKey::new::<A>(nick)) else {
+			return Err(AssetLookupError::NotFound(nick.to_string()));
+		};
+
+		stack
+			.last()
+			.expect("asset store missed an empty nickname stack")
+			.downcast()
+	}
+
+	/// The asset most recently given editor number `num`.
+	/// See [`Self::try_by_ednum`].
+	#[must_use]
+	pub fn by_ednum<A: Any + Send + Sync>(&self, num: EditorNum) -> Option<Arc<A>> {
+		self.logged(self.try_by_ednum(num))
+	}
+
+	/// Like [`Self::by_ednum`], but distinguishes between nothing being found
+	/// and an asset of the wrong type being found.
+	pub fn try_by_ednum<A: Any + Send + Sync>(
+		&self,
+		num: EditorNum,
+	) -> Result<Arc<A>, AssetLookupError> {
+		let Some(stack) = self.editor_nums.get(&num) else {
+			return Err(AssetLookupError::NotFound(format!("editor number {num}")));
+		};
+
+		stack
+			.last()
+			.expect("asset store missed an empty ed-num stack")
+			.downcast()
+	}
+
+	/// The asset most recently given spawn number `num`.
+	/// See [`Self::try_by_spawnnum`].
+	#[must_use]
+	pub fn by_spawnnum<A: Any + Send + Sync>(&self, num: SpawnNum) -> Option<Arc<A>> {
+		self.logged(self.try_by_spawnnum(num))
+	}
+
+	/// Like [`Self::by_spawnnum`], but distinguishes between nothing being found
+	/// and an asset of the wrong type being found.
+	pub fn try_by_spawnnum<A: Any + Send + Sync>(
+		&self,
+		num: SpawnNum,
+	) -> Result<Arc<A>, AssetLookupError> {
+		let Some(stack) = self.spawn_nums.get(&num) else {
+			return Err(AssetLookupError::NotFound(format!("spawn number {num}")));
+		};
+
+		stack
+			.last()
+			.expect("asset store missed an empty spawn-num stack")
+			.downcast()
+	}
+
+	/// Every asset of type `A`, in registration order.
+	/// Assets of other types are never visited.
+	pub fn assets_of_type<A: Any + Send + Sync>(&self) -> impl Iterator<Item = Arc<A>> + '_ {
+		self.by_type
+			.get(&TypeId::of::<A>())
+			.into_iter()
+			.flatten()
+			.filter_map(|entry| self.logged(entry.downcast()))
+	}
+
+	/// The number of assets registered, of all types.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.assets.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.assets.is_empty()
+	}
+
+	/// Builds a new store with every asset of `self` and `other`, as though all of
+	/// `other`'s had been registered after all of `self`'s. So where both have an
+	/// asset of the same type under the same ID, `other`'s wins, and the nickname
+	/// and number stacks of `other` go on top of those of `self`.
+	///
+	/// Beware that this is expensive, since every index gets copied. Assets are
+	/// shared by reference count with the source, so neither `self` nor `other`
+	/// needs to outlive the result.
+	#[must_use]
+	pub fn merged(&self, other: &Self) -> Self {
+		let mut ret = Self::default();
+
+		for src in [self, other] {
+			for (key, entry) in &src.assets {
+				ret.assets.insert(key.clone(), entry.clone());
+			}
+
+			for (key, stack) in &src.nicknames {
+				let dst = ret.nicknames.entry(key.clone()).or_default();
+				dst.extend(stack.iter().cloned());
+			}
+
+			for (num, stack) in &src.editor_nums {
+				let dst = ret.editor_nums.entry(*num).or_default();
+				dst.extend(stack.iter().cloned());
+			}
+
+			for (num, stack) in &src.spawn_nums {
+				let dst = ret.spawn_nums.entry(*num).or_default();
+				dst.extend(stack.iter().cloned());
+			}
+		}
+
+		// Entries which lost to one of `other`'s are left out, and so are
+		// second occurrences of entries which the two sources share.
+		let mut live: FxHashSet<_> = ret.assets.values().map(Arc::as_ptr).collect();
+
+		for src in [self, other] {
+			for (type_id, entries) in &src.by_type {
+				let dst = ret.by_type.entry(*type_id).or_default();
+
+				dst.extend(
+					entries
+						.iter()
+						.filter(|entry| live.remove(&Arc::as_ptr(entry)))
+						.cloned(),
+				);
+			}
+		}
+
+		ret
+	}
+
+	pub fn clear(&mut self) {
+		self.assets.clear();
+		self.nicknames.clear();
+		self.editor_nums.clear();
+		self.spawn_nums.clear();
+		self.by_type.clear();
+	}
+
+	/// Lookups which don't return a [`Result`] go through this, so that a
+	/// bookkeeping bug gets reported (once) instead of causing a panic.
+	#[must_use]
+	fn logged<T>(&self, result: Result<T, AssetLookupError>) -> Option<T> {
+		match result {
+			Ok(t) => Some(t),
+			Err(AssetLookupError::NotFound(_)) => None,
+			Err(err) => {
+				if !self.lookup_err_logged.swap(true, atomic::Ordering::Relaxed) {
+					error!("{err} (further errors of this kind will not be logged)");
+				}
+
+				None
+			}
+		}
+	}
+
+	/// Points the top of the nickname stack for `nick` (under type `A`)
+	/// at the asset of type `B` registered under `id`, bypassing all checks.
+	#[cfg(test)]
+	fn corrupt_nickname<A: 'static, B: 'static>(&mut self, nick: &str, id: &str) {
+		let entry = self.assets[&AssetKey::new::<B>(id)].clone();
+		let stack = self.nicknames.get_mut(&AssetKey::new::<A>(nick)).unwrap();
+		*stack.last_mut().unwrap() = entry;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[derive(Debug, PartialEq)]
+	struct Sound(u32);
+
+	#[derive(Debug, PartialEq)]
+	struct Picture(&'static str);
+
+	#[test]
+	fn asset_key() {
+		let key = AssetKey::new::<Sound>("DSPISTOL");
+		assert_eq!(
+			key.to_string(),
+			format!("{}/DSPISTOL", std::any::type_name::<Sound>())
+		);
+		assert_eq!(AssetKey::new::<u32>("MAP01").to_string(), "u32/MAP01");
+
+		assert_eq!(key, AssetKey::new::<Sound>("DSPISTOL"));
+		assert_ne!(key, AssetKey::new::<Picture>("DSPISTOL"));
+		assert_ne!(key, AssetKey::new::<Sound>("DSSHOTGN"));
+	}
+
+	#[test]
+	fn lookup_type_mismatch() {
+		let mut store = AssetStore::default();
+		store.register("DSPISTOL", Sound(1));
+		store.register("TROOA1", Picture("troo"));
+		assert!(store.add_nickname::<Sound>("pistol", "DSPISTOL"));
+		assert!(!store.add_nickname::<Sound>("troo", "TROOA1"));
+
+		assert_eq!(
+			*store.last_asset_by_nick::<Sound>("pistol").unwrap(),
+			Sound(1)
+		);
+
+		store.corrupt_nickname::<Sound, Picture>("pistol", "TROOA1");
+
+		match store.try_last_asset_by_nick::<Sound>("pistol") {
+			Err(AssetLookupError::TypeMismatch {
+				id,
+				expected,
+				found,
+			}) => {
+				assert_eq!(id, "TROOA1");
+				assert_eq!(expected, std::any::type_name::<Sound>());
+				assert_eq!(found, std::any::type_name::<Picture>());
+			}
+			other => panic!("expected a type mismatch, got: {other:?}"),
+		}
+
+		// The infallible path reports the same bug without panicking.
+		assert!(store.last_asset_by_nick::<Sound>("pistol").is_none());
+		assert!(store.last_asset_by_nick::<Sound>("pistol").is_none());
+		assert!(store.lookup_err_logged.load(atomic::Ordering::Relaxed));
+
+		assert!(matches!(
+			store.try_get_asset::<Sound>("TROOA1"),
+			Err(AssetLookupError::NotFound(_))
+		));
+		assert!(matches!(
+			store.try_by_ednum::<Sound>(3001),
+			Err(AssetLookupError::NotFound(_))
+		));
+	}
+
+	#[test]
+	fn lookup_by_ednum() {
+		let mut store = AssetStore::default();
+		store.register("imp", Picture("imp"));
+		store.register("darkimp", Picture("darkimp"));
+		assert!(store.add_editor_num::<Picture>(3001, "imp"));
+		assert!(store.add_editor_num::<Picture>(3001, "darkimp"));
+
+		assert_eq!(
+			*store.by_ednum::<Picture>(3001).unwrap(),
+			Picture("darkimp")
+		);
+		assert!(matches!(
+			store.try_by_ednum::<Sound>(3001),
+			Err(AssetLookupError::TypeMismatch { .. })
+		));
+	}
+
+	#[test]
+	fn spawn_nums_independent() {
+		let mut store = AssetStore::default();
+
+		for (id, editor_num, spawn_num) in [
+			("spawnid_only", 0, 12),
+			("ednum_only", 3004, 0),
+			("both", 3001, 1),
+			("neither", 0, 0),
+		] {
+			store.register_actor(id, editor_num, spawn_num, Picture(id));
+		}
+
+		assert_eq!(
+			*store.by_spawnnum::<Picture>(12).unwrap(),
+			Picture("spawnid_only")
+		);
+		assert!(store.by_ednum::<Picture>(12).is_none());
+		assert!(store.by_spawnnum::<Picture>(3004).is_none());
+		assert_eq!(*store.by_ednum::<Picture>(3001).unwrap(), Picture("both"));
+		assert_eq!(*store.by_spawnnum::<Picture>(1).unwrap(), Picture("both"));
+		assert!(store.by_ednum::<Picture>(0).is_none());
+		assert!(store.by_spawnnum::<Picture>(0).is_none());
+
+		for stack in store.editor_nums.values() {
+			assert!(stack.iter().all(|entry| entry.id != "spawnid_only"));
+		}
+	}
+
+	#[test]
+	fn merged() {
+		let mut ours = AssetStore::default();
+		ours.register("DSPISTOL", Sound(1));
+		ours.register("DSSHOTGN", Sound(2));
+		assert!(ours.add_nickname::<Sound>("pistol", "DSPISTOL"));
+		ours.register_actor("imp", 3001, 5, Picture("ours"));
+
+		let mut theirs = AssetStore::default();
+		theirs.register("DSPISTOL", Sound(10));
+		assert!(theirs.add_nickname::<Sound>("pistol", "DSPISTOL"));
+		theirs.register_actor("imp2", 3001, 0, Picture("theirs"));
+
+		let store = ours.merged(&theirs);
+		assert_eq!(*ours.get_asset::<Sound>("DSPISTOL").unwrap(), Sound(1));
+		drop(ours);
+		drop(theirs);
+
+		assert_eq!(store.len(), 4);
+		assert_eq!(*store.get_asset::<Sound>("DSPISTOL").unwrap(), Sound(10));
+		assert_eq!(
+			*store.last_asset_by_nick::<Sound>("pistol").unwrap(),
+			Sound(10)
+		);
+		assert_eq!(*store.by_ednum::<Picture>(3001).unwrap(), Picture("theirs"));
+		assert_eq!(*store.by_spawnnum::<Picture>(5).unwrap(), Picture("ours"));
+
+		let sounds: Vec<_> = store.assets_of_type::<Sound>().map(|s| s.0).collect();
+		assert_eq!(sounds, [2, 10]);
+		assert_eq!(store.assets_of_type::<Picture>().count(), 2);
+
+		let twice = store.merged(&store);
+		assert_eq!(twice.len(), 4);
+		assert_eq!(twice.assets_of_type::<Sound>().count(), 2);
+		assert_eq!(
+			*twice.last_asset_by_nick::<Sound>("pistol").unwrap(),
+			Sound(10)
+		);
+	}
+
+	#[test]
+	fn assets_of_type() {
+		let mut store = AssetStore::default();
+
+		for i in 0..4 {
+			store.register(format!("sound{i}"), Sound(i));
+			store.register(format!("picture{i}"), Picture("pic"));
+		}
+
+		// Replacing an asset must not leave the old one behind in the index.
+		store.register("sound2", Sound(20));
+
+		let sounds: Vec<_> = store.assets_of_type::<Sound>().collect();
+		let sounds: Vec<_> = sounds.iter().map(|s| s.0).collect();
+		assert_eq!(sounds, [0, 1, 3, 20]);
+		assert_eq!(store.assets_of_type::<Picture>().count(), 4);
+		assert_eq!(store.assets_of_type::<u8>().count(), 0);
+		assert_eq!(store.len(), 8);
+
+		store.clear();
+		assert!(store.is_empty());
+		assert_eq!(store.assets_of_type::<Sound>().count(), 0);
+	}
+}
@@ -3,13 +3,16 @@
 mod gui;
 mod midi;
 
-use std::{io::Cursor, path::PathBuf};
+use std::{collections::HashMap, io::Cursor, path::PathBuf, sync::Arc, time::Duration};
 
 use arrayvec::ArrayVec;
-use bevy::prelude::{Entity, Resource};
+use bevy::prelude::{App, Entity, Plugin, PostUpdate, ResMut, Resource};
 use bevy_egui::egui;
+use byteorder::{ByteOrder, LittleEndian};
+use cpal::traits::{DeviceTrait, HostTrait};
 use crossbeam::channel::SendError;
 use kira::{
+	dsp::Frame,
 	manager::{
 		backend::{cpal::CpalBackend, Backend},
 		error::{AddSpatialSceneError, AddSubTrackError, PlaySoundError},
@@ -20,7 +23,7 @@ use kira::{
 		PlaybackState, SoundData,
 	},
 	spatial::{
-		emitter::{EmitterHandle, EmitterSettings},
+		emitter::{EmitterDistances, EmitterHandle, EmitterSettings},
 		listener::{ListenerHandle, ListenerSettings},
 		scene::{AddListenerError, SpatialSceneHandle, SpatialSceneSettings},
 	},
@@ -32,22 +35,60 @@ use kira::{
 		TrackBuilder, TrackHandle, TrackRoutes,
 	},
 	tween::Tween,
-	CommandError, OutputDestination,
+	CommandError, OutputDestination, Volume,
 };
 use nodi::midly;
 use rayon::prelude::*;
 use tracing::{debug, error, info, warn};
-use vfs::VirtualFs;
+use util::Id8;
+use vfs::{FolderRef, VirtualFs};
 
 use self::gui::DevGui;
 
-pub use self::midi::{
-	render as render_midi, Data as MidiData, FileFormat as MidiFormat, Handle as MidiHandle,
-	Settings as MidiSettings, SoundFont, SoundFontKind,
+pub use self::{
+	gui::AudioPanel,
+	midi::{
+		render as render_midi, Data as MidiData, FileFormat as MidiFormat, Handle as MidiHandle,
+		Settings as MidiSettings, SoundFont, SoundFontKind,
+	},
 };
 
 pub const MUSIC_LAYERS: usize = 8;
 
+/// Vanilla's `S_CLOSE_DIST`, in map units: spatial sounds are at full volume
+/// within this distance of the listener.
+pub const CLOSE_DIST: f32 = 200.0;
+/// Vanilla's `S_CLIPPING_DIST`, in map units: spatial sounds are inaudible
+/// beyond this distance from the listener.
+pub const CLIPPING_DIST: f32 = 1200.0;
+
+/// Makes sure an [`AudioCore`] exists (with default settings, if the app has not
+/// inserted one of its own) and clears finished sound handles every frame.
+#[derive(Debug, Default)]
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+	fn build(&self, app: &mut App) {
+		if !app.world.contains_resource::<AudioCore>() {
+			match AudioCore::new(None) {
+				Ok(audio) => {
+					app.insert_resource(audio);
+				}
+				Err(err) => error!("Failed to initialize audio output: {err}"),
+			}
+		}
+
+		app.init_resource::<SoundTable>()
+			.add_systems(PostUpdate, update_audio);
+	}
+}
+
+fn update_audio(audio: Option<ResMut<AudioCore>>) {
+	if let Some(mut audio) = audio {
+		audio.update();
+	}
+}
+
 /// The centre for music and sound effect playback for any VileTech application.
 #[derive(Resource)]
 pub struct AudioCore {
@@ -56,6 +97,8 @@ pub struct AudioCore {
 	scene: SpatialSceneHandle,
 	listener: ListenerHandle,
 	tracks: SubTracks,
+	/// The native sample rate of the default output device.
+	output_rate: u32,
 
 	/// Unlike `sounds`, this behaves like a stack.
 	/// Only the last element gets played at any given time.
@@ -118,6 +161,11 @@ impl AudioCore {
 
 		let soundfonts = Self::collect_soundfonts()?;
 
+		let output_rate = cpal::default_host()
+			.default_output_device()
+			.and_then(|device| device.default_output_config().ok())
+			.map_or(44100, |config| config.sample_rate().0);
+
 		let mut ret = Self {
 			manager,
 			soundfonts,
@@ -128,6 +176,7 @@ impl AudioCore {
 				music: t_music,
 				menu: t_menu,
 			},
+			output_rate,
 
 			music: ArrayVec::new(),
 			sounds: {
@@ -147,6 +196,8 @@ impl AudioCore {
 		Ok(ret)
 	}
 
+	/// If `data` is not at the output device's sample rate (e.g. a DMX sound
+	/// effect from [`sound_from_dmx`]), it gets [resampled](resample_linear) first.
 	pub fn start_sfx_wave(
 		&mut self,
 		mut data: StaticSoundData,
@@ -155,6 +206,11 @@ impl AudioCore {
 	) -> Result<(), Error> {
 		let mut entity = None;
 
+		if data.sample_rate != self.output_rate {
+			data.frames = resample_linear(&data.frames, data.sample_rate, self.output_rate).into();
+			data.sample_rate = self.output_rate;
+		}
+
 		let emitter = match space {
 			SoundSpace::World {
 				pos,
@@ -228,6 +284,139 @@ impl AudioCore {
 		self.start_sfx_on_channel(sfx, channel)
 	}
 
+	/// Looks up `name` in `table` and starts it as a sound effect on any free
+	/// channel. `volume` is a linear amplitude multiplier.
+	///
+	/// `attenuation` works like in [SNDINFO] and ZDoom's `A_StartSound`:
+	/// `1.0` gives vanilla's falloff between [`CLOSE_DIST`] and [`CLIPPING_DIST`],
+	/// higher values make the sound die out faster, and `0.0` (or less) makes it
+	/// audible everywhere. Without a `position`, the sound is never spatial.
+	///
+	/// [SNDINFO]: https://zdoom.org/wiki/SNDINFO
+	pub fn play_sound(
+		&mut self,
+		table: &SoundTable,
+		name: &str,
+		volume: f64,
+		attenuation: f32,
+		position: Option<glam::Vec3>,
+	) -> Result<(), Error> {
+		let data = table
+			.resolve(name)
+			.ok_or_else(|| Error::UnknownSound(name.to_string()))?
+			.with_modified_settings(|s| s.volume(Volume::Amplitude(volume)));
+
+		let space = match position {
+			Some(pos) if attenuation > 0.0 => SoundSpace::World {
+				pos,
+				entity: None,
+				settings: EmitterSettings::new()
+					.distances(EmitterDistances {
+						min_distance: CLOSE_DIST / attenuation,
+						max_distance: CLIPPING_DIST / attenuation,
+					})
+					.persist_until_sounds_finish(true),
+			},
+			_ => SoundSpace::Unsourced,
+		};
+
+		self.start_sfx_wave(data, None, space)
+	}
+
+	/// Looks up `name` in `table` and starts it looping as the only layer of a
+	/// new song, which replaces the top of the music stack. The outgoing song
+	/// fades out over `fade` while the new one fades in over the same duration;
+	/// a zero `fade` is an instant switch.
+	///
+	/// Music inserted with [`SoundTable::insert_music`] is routed by its
+	/// [format](AudioFormat): MIDI-like formats go through [`Self::play_midi`],
+	/// and waveforms only get decoded now. Note that MIDI music does not loop yet.
+	pub fn play_music(
+		&mut self,
+		table: &SoundTable,
+		name: &str,
+		fade: Duration,
+	) -> Result<(), Error> {
+		let data = match table.resolve_music(name) {
+			Some(stream) => match stream.decode_waveform(StaticSoundSettings::default()) {
+				Some(result) => result.map_err(Error::WaveDecode)?,
+				None => return self.play_midi(stream.source_bytes(), fade),
+			},
+			None => table
+				.resolve(name)
+				.ok_or_else(|| Error::UnknownSound(name.to_string()))?
+				.clone(),
+		};
+
+		let tween = Tween {
+			duration: fade,
+			..Default::default()
+		};
+
+		let dest = OutputDestination::Track(self.tracks.music.handle.id());
+
+		let data = data.with_modified_settings(|s| {
+			s.loop_region(..)
+				.fade_in_tween(tween)
+				.output_destination(dest)
+		});
+
+		let sound = self.manager.play(data).map_err(Error::PlayWave)?;
+		self.replace_music(Handle::Wave(sound), tween);
+		Ok(())
+	}
+
+	/// Parses `bytes` as a Standard MIDI File or DMX MUS (which gets converted
+	/// to MIDI) and starts it as a new song, synthesized with the first SF2
+	/// SoundFont found at startup. Otherwise behaves like [`Self::play_music`].
+	pub fn play_midi(&mut self, bytes: &[u8], fade: Duration) -> Result<(), Error> {
+		let smf = match MidiFormat::deduce(bytes) {
+			Some(MidiFormat::DmxMus) => mus::to_midi(bytes).map_err(Error::ParseMus)?,
+			Some(MidiFormat::Midi) | None => midly::Smf::parse(bytes).map_err(Error::ParseMidi)?,
+			Some(other) => return Err(Error::UnsupportedMusic(other)),
+		};
+
+		let soundfont = self
+			.soundfonts
+			.iter()
+			.find(|sf| sf.kind() == SoundFontKind::Sf2)
+			.ok_or(Error::NoSoundFont)?;
+
+		let tween = Tween {
+			duration: fade,
+			..Default::default()
+		};
+
+		let data = MidiData::new(
+			smf,
+			soundfont.full_path().to_path_buf(),
+			MidiSettings {
+				destination: OutputDestination::Track(self.tracks.music.handle.id()),
+				fade_in: Some(tween),
+				..Default::default()
+			},
+		);
+
+		let sound = self.manager.play(data).map_err(Error::PlayMidi)?;
+		self.replace_music(Handle::Midi(sound), tween);
+		Ok(())
+	}
+
+	/// Fades out the top of the music stack (if any) and puts a new song,
+	/// with `handle` as its only layer, in its place.
+	fn replace_music(&mut self, handle: Handle, tween: Tween) {
+		if let Some(mut prev) = self.music.pop() {
+			prev.stop_all_layers(Some(tween));
+		}
+
+		let mut song = MusicController {
+			layers: Default::default(),
+		};
+
+		song.layers[0] = Some(handle);
+		self.music.push(song);
+	}
+
 	/// `dest` is only altered if returning `Ok`.
 	fn spatial_setup(
 		&mut self,
@@ -583,6 +772,204 @@ impl std::fmt::Debug for MusicController {
 	}
 }
 
+// SoundTable //////////////////////////////////////////////////////////////////
+
+/// Decoded sounds and undecoded music by lump name, and the logical names which
+/// [SNDINFO] assigns to them. All names are case-insensitive.
+///
+/// [SNDINFO]: https://zdoom.org/wiki/SNDINFO
+#[derive(Resource, Default)]
+pub struct SoundTable {
+	sounds: HashMap<String, StaticSoundData>,
+	/// Kept encoded; see [`AudioStream`].
+	music: HashMap<String, AudioStream>,
+	/// Logical name to lump name.
+	aliases: HashMap<String, String>,
+}
+
+impl SoundTable {
+	/// Replaces any sound already under `lump`.
+	pub fn insert(&mut self, lump: &str, data: StaticSoundData) {
+		self.sounds.insert(lump.to_ascii_lowercase(), data);
+	}
+
+	/// Replaces any music already under `lump`. Returns `false` (leaving this
+	/// table untouched) if `bytes` are not in any recognized [`AudioFormat`].
+	pub fn insert_music(&mut self, lump: &str, bytes: impl Into<Arc<[u8]>>) -> bool {
+		let Some(stream) = AudioStream::new(bytes) else {
+			return false;
+		};
+
+		self.music.insert(lump.to_ascii_lowercase(), stream);
+		true
+	}
+
+	/// Decodes every [DMX sound](sound_from_dmx) in `wad`: those between the
+	/// `DS_START` and `DS_END` markers (ZDoom's sound namespace), and those
+	/// anywhere else with a `DS` prefix, like vanilla's. Lumps which are not
+	/// DMX format 3 (e.g. PC speaker sounds) are left alone, and lumps which
+	/// cannot be read are skipped. Malformed sounds are returned by name.
+	pub fn insert_dmx(&mut self, wad: FolderRef) -> Vec<(Id8, DmxSfxError)> {
+		let mut errors = vec![];
+		let mut in_namespace = false;
+
+		for fref in wad.files() {
+			let Some(name) = fref.name().lump_name() else {
+				continue;
+			};
+
+			if name == "DS_START" {
+				in_namespace = true;
+				continue;
+			} else if name == "DS_END" {
+				in_namespace = false;
+				continue;
+			}
+
+			if !in_namespace && !name.get(..2).is_some_and(|p| p.eq_ignore_ascii_case("DS")) {
+				continue;
+			}
+
+			let mut guard = fref.lock();
+
+			let Ok(bytes) = guard.read() else {
+				continue;
+			};
+
+			match sound_from_dmx(&bytes) {
+				Some(Ok(data)) => self.insert(name.as_str(), data),
+				Some(Err(err)) => errors.push((name, err)),
+				None => {}
+			}
+		}
+
+		errors
+	}
+
+	/// Like a SNDINFO `logicalname lumpname` line; later assignments win.
+	pub fn assign(&mut self, logical: &str, lump: &str) {
+		self.aliases
+			.insert(logical.to_ascii_lowercase(), lump.to_ascii_lowercase());
+	}
+
+	/// Logical names take priority over lump names, so a mod can assign a new
+	/// lump to e.g. `weapons/pistol` without touching `DSPISTOL`.
+	#[must_use]
+	pub fn resolve(&self, name: &str) -> Option<&StaticSoundData> {
+		let name = name.to_ascii_lowercase();
+
+		match self.aliases.get(&name) {
+			Some(lump) => self.sounds.get(lump),
+			None => self.sounds.get(&name),
+		}
+	}
+
+	/// Like [`Self::resolve`], but for music inserted with [`Self::insert_music`].
+	#[must_use]
+	pub fn resolve_music(&self, name: &str) -> Option<&AudioStream> {
+		let name = name.to_ascii_lowercase();
+		let lump = self.aliases.get(&name).unwrap_or(&name);
+		self.music.get(lump)
+	}
+
+	pub fn clear(&mut self) {
+		self.sounds.clear();
+		self.music.clear();
+		self.aliases.clear();
+	}
+}
+
+impl std::fmt::Debug for SoundTable {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("SoundTable")
+			.field("sounds", &self.sounds.len())
+			.field("music", &self.music.len())
+			.field("aliases", &self.aliases)
+			.finish()
+	}
+}
+
+// AudioStream /////////////////////////////////////////////////////////////////
+
+/// The encodings which an [`AudioStream`] may hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioFormat {
+	Flac,
+	Mp3,
+	Ogg,
+	Wav,
+	/// Needs a soundfont to be rendered; see [`AudioCore::play_midi`].
+	MidiRaw(MidiFormat),
+}
+
+impl AudioFormat {
+	/// Returns `None` if `bytes` are not in any recognized format.
+	#[must_use]
+	pub fn deduce(bytes: &[u8]) -> Option<Self> {
+		if let Some(midi) = MidiFormat::deduce(bytes) {
+			return Some(Self::MidiRaw(midi));
+		}
+
+		match bytes {
+			[b'f', b'L', b'a', b'C', ..] => Some(Self::Flac),
+			[b'O', b'g', b'g', b'S', ..] => Some(Self::Ogg),
+			[b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(Self::Wav),
+			// (RAT) Either an ID3v2 tag, or the sync word of an MPEG-1 Layer III
+			// frame header (with or without a CRC).
+			[b'I', b'D', b'3', ..] | [0xFF, 0xFA | 0xFB, ..] => Some(Self::Mp3),
+			_ => None,
+		}
+	}
+}
+
+/// A music track kept in its encoded form until it starts playing.
+///
+/// (RAT) Decoding a whole track up-front (as is done for sounds) can cost tens of
+/// megabytes per track and a noticeable amount of load time, most of which is
+/// wasted on music that never gets played.
+#[derive(Debug, Clone)]
+pub struct AudioStream {
+	source_bytes: Arc<[u8]>,
+	format: AudioFormat,
+}
+
+impl AudioStream {
+	/// Returns `None` if `bytes` are not in any recognized [`AudioFormat`].
+	#[must_use]
+	pub fn new(bytes: impl Into<Arc<[u8]>>) -> Option<Self> {
+		let source_bytes = bytes.into();
+
+		AudioFormat::deduce(&source_bytes).map(|format| Self {
+			source_bytes,
+			format,
+		})
+	}
+
+	#[must_use]
+	pub fn source_bytes(&self) -> &[u8] {
+		&self.source_bytes
+	}
+
+	#[must_use]
+	pub fn format(&self) -> AudioFormat {
+		self.format
+	}
+
+	/// Returns `None` if this is a [`AudioFormat::MidiRaw`] stream, since rendering
+	/// MIDI additionally requires a soundfont.
+	#[must_use]
+	pub fn decode_waveform(
+		&self,
+		settings: StaticSoundSettings,
+	) -> Option<Result<StaticSoundData, kira::sound::FromFileError>> {
+		if matches!(self.format, AudioFormat::MidiRaw(_)) {
+			return None;
+		}
+
+		Some(sound_from_bytes(self.source_bytes.to_vec(), settings))
+	}
+}
+
 // Helpers /////////////////////////////////////////////////////////////////////
 
 pub fn sound_from_bytes(
@@ -593,8 +980,117 @@ pub fn sound_from_bytes(
 	StaticSoundData::from_cursor(cursor, settings)
 }
 
+/// Decodes a [DMX digitized sound] (format 3), as used for all of vanilla's
+/// sound effects: an 8-byte header (format number, sample rate, sample count),
+/// then unsigned 8-bit mono PCM. The first and last 16 samples are padding,
+/// which DMX never plays, and so get skipped.
+///
+/// Returns `None` if `bytes` do not start with format number 3.
+///
+/// [DMX digitized sound]: https://doomwiki.org/wiki/Sound
+pub fn sound_from_dmx(bytes: &[u8]) -> Option<Result<StaticSoundData, DmxSfxError>> {
+	const HEADER_LEN: usize = 8;
+	const PAD: usize = 16;
+
+	if bytes.len() < 2 || bytes[0..2] != [0x03, 0x00] {
+		return None;
+	}
+
+	if bytes.len() < HEADER_LEN {
+		return Some(Err(DmxSfxError::Truncated {
+			declared: HEADER_LEN,
+			available: bytes.len(),
+		}));
+	}
+
+	let sample_rate = LittleEndian::read_u16(&bytes[2..4]) as u32;
+	let declared = LittleEndian::read_u32(&bytes[4..8]) as usize;
+	let samples = &bytes[HEADER_LEN..];
+
+	if sample_rate == 0 {
+		return Some(Err(DmxSfxError::ZeroRate));
+	}
+
+	if declared > samples.len() {
+		return Some(Err(DmxSfxError::Truncated {
+			declared,
+			available: samples.len(),
+		}));
+	}
+
+	let samples = &samples[..declared];
+
+	let unpadded = if samples.len() > PAD * 2 {
+		&samples[PAD..(samples.len() - PAD)]
+	} else {
+		&[]
+	};
+
+	let frames = unpadded
+		.iter()
+		.map(|s| Frame::from_mono((*s as f32 - 128.0) / 128.0))
+		.collect::<Vec<_>>();
+
+	Some(Ok(StaticSoundData {
+		sample_rate,
+		frames: frames.into(),
+		settings: StaticSoundSettings::default(),
+	}))
+}
+
+/// Converts `frames` sampled at `from` Hz to `to` Hz by linear interpolation
+/// between each pair of neighbouring frames. This is cheap and adequate for
+/// 8-bit 11 kHz sound effects, but dulls high frequencies a little when
+/// upsampling and does nothing to prevent aliasing when downsampling.
+#[must_use]
+pub fn resample_linear(frames: &[Frame], from: u32, to: u32) -> Vec<Frame> {
+	if from == to || frames.is_empty() {
+		return frames.to_vec();
+	}
+
+	let len = (frames.len() as u64 * to as u64 / from as u64) as usize;
+	let step = from as f64 / to as f64;
+
+	(0..len)
+		.map(|i| {
+			let pos = i as f64 * step;
+			let index = pos as usize;
+			let t = (pos - index as f64) as f32;
+			let a = frames[index.min(frames.len() - 1)];
+			let b = frames[(index + 1).min(frames.len() - 1)];
+			a + (b - a) * t
+		})
+		.collect()
+}
+
 // Error ///////////////////////////////////////////////////////////////////////
 
+/// See [`sound_from_dmx`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmxSfxError {
+	/// The header declares more samples than the lump holds.
+	Truncated { declared: usize, available: usize },
+	/// The header declares a sample rate of 0 Hz.
+	ZeroRate,
+}
+
+impl std::error::Error for DmxSfxError {}
+
+impl std::fmt::Display for DmxSfxError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Truncated {
+				declared,
+				available,
+			} => write!(
+				f,
+				"DMX sound declares {declared} bytes of samples, but only {available} are present"
+			),
+			Self::ZeroRate => write!(f, "DMX sound has a sample rate of 0"),
+		}
+	}
+}
+
 #[derive(Debug)]
 pub enum Error {
 	// TODO: `kira::AddEmitterError` isn't exposed, likely mistakenly.
@@ -613,13 +1109,21 @@ pub enum Error {
 	MidiSynth(fluidlite::Error),
 	/// Tried to push a new music layer group onto the stack, but it was full.
 	MusicFull,
+	/// MIDI playback was requested, but no SF2 SoundFont was found at startup.
+	NoSoundFont,
 	ParseMidi(midly::Error),
+	ParseMus(mus::Error),
 	PlayMidi(PlayMidiError),
 	PlayWave(PlayWaveError),
 	SoundFontRead(PathBuf, fluidlite::Error),
 	SpatialScene(AddSpatialSceneError),
 	SubTrack(AddSubTrackError),
 	ThreadPanic,
+	/// A [`SoundTable`] has no sound by this logical or lump name.
+	UnknownSound(String),
+	/// This MIDI-like format can be recognized, but not yet played.
+	UnsupportedMusic(MidiFormat),
+	WaveDecode(kira::sound::FromFileError),
 }
 
 impl From<CommandError> for Error {
@@ -649,16 +1153,21 @@ impl std::error::Error for Error {
 			Self::Listener(err) => Some(err),
 			Self::MidiSynth(err) => Some(err),
 			Self::ParseMidi(err) => Some(err),
+			Self::ParseMus(err) => Some(err),
 			Self::PlayMidi(err) => Some(err),
 			Self::PlayWave(err) => Some(err),
 			Self::SoundFontRead(_, err) => Some(err),
 			Self::SpatialScene(err) => Some(err),
 			Self::SubTrack(err) => Some(err),
+			Self::WaveDecode(err) => Some(err),
 			Self::CommandOverflow
 			| Self::InvalidChannel { .. }
 			| Self::InvalidMusicLayer { .. }
 			| Self::MusicFull
-			| Self::ThreadPanic => None,
+			| Self::NoSoundFont
+			| Self::ThreadPanic
+			| Self::UnknownSound(_)
+			| Self::UnsupportedMusic(_) => None,
 		}
 	}
 }
@@ -691,7 +1200,9 @@ impl std::fmt::Display for Error {
 				f,
 				"tried to push a new music layer group, but the music stack is full"
 			),
+			Self::NoSoundFont => write!(f, "no SF2 SoundFont is available for MIDI playback"),
 			Self::ParseMidi(err) => write!(f, "failed to parse MIDI file: {err}"),
+			Self::ParseMus(err) => write!(f, "failed to convert DMX MUS to MIDI: {err}"),
 			Self::PlayMidi(err) => write!(f, "failed to play MIDI audio: {err}"),
 			Self::PlayWave(err) => write!(f, "failed to play non-MIDI audio: {err}"),
 			Self::SoundFontRead(path, err) => write!(
@@ -702,6 +1213,11 @@ impl std::fmt::Display for Error {
 			Self::SpatialScene(err) => write!(f, "failed to create spatial scene: {err}"),
 			Self::SubTrack(err) => write!(f, "failed to create an audio sub-track: {err}"),
 			Self::ThreadPanic => write!(f, "audio thread has panicked"),
+			Self::UnknownSound(name) => write!(f, "no sound named: `{name}`"),
+			Self::UnsupportedMusic(format) => {
+				write!(f, "music format is not yet supported: {format:?}")
+			}
+			Self::WaveDecode(err) => write!(f, "failed to decode waveform audio: {err}"),
 		}
 	}
 }
@@ -715,6 +1231,189 @@ mod test {
 
 	use super::*;
 
+	#[test]
+	fn sound_table() {
+		let sound = |sample_rate| StaticSoundData {
+			sample_rate,
+			frames: std::sync::Arc::new([]),
+			settings: StaticSoundSettings::default(),
+		};
+
+		let mut table = SoundTable::default();
+		table.insert("DSPISTOL", sound(11025));
+		table.insert("DSPISTL2", sound(22050));
+		assert_eq!(table.resolve("dspistol").unwrap().sample_rate, 11025);
+		assert!(table.resolve("weapons/pistol").is_none());
+
+		table.assign("weapons/pistol", "DSPISTOL");
+		assert_eq!(table.resolve("Weapons/Pistol").unwrap().sample_rate, 11025);
+
+		// A reassignment shadows the first.
+		table.assign("weapons/pistol", "dspistl2");
+		assert_eq!(table.resolve("weapons/pistol").unwrap().sample_rate, 22050);
+
+		// Assigned to a lump which doesn't exist.
+		table.assign("misc/secret", "DSSECRET");
+		assert!(table.resolve("misc/secret").is_none());
+
+		// Music shares logical names with sounds, but not storage.
+		assert!(table.insert_music("D_E1M1", MUS_HEADER.to_vec()));
+		table.assign("music/e1m1", "d_e1m1");
+		assert_eq!(
+			table.resolve_music("MUSIC/E1M1").unwrap().source_bytes(),
+			MUS_HEADER
+		);
+		assert!(table.resolve("music/e1m1").is_none());
+		assert!(table.resolve_music("dspistol").is_none());
+	}
+
+	/// Long enough for [`MidiFormat::deduce`], which needs 12 bytes.
+	const MUS_HEADER: &[u8] = b"MUS\x1a\0\0\0\0\0\0\0\0";
+
+	#[test]
+	fn audio_format() {
+		assert_eq!(
+			AudioFormat::deduce(MUS_HEADER),
+			Some(AudioFormat::MidiRaw(MidiFormat::DmxMus))
+		);
+		assert_eq!(
+			AudioFormat::deduce(b"MThd\0\0\0\x06\0\0\0\x01"),
+			Some(AudioFormat::MidiRaw(MidiFormat::Midi))
+		);
+		assert_eq!(AudioFormat::deduce(b"fLaC\0"), Some(AudioFormat::Flac));
+		assert_eq!(AudioFormat::deduce(b"OggS\0"), Some(AudioFormat::Ogg));
+		assert_eq!(
+			AudioFormat::deduce(b"RIFF\0\0\0\0WAVEfmt "),
+			Some(AudioFormat::Wav)
+		);
+		assert_eq!(AudioFormat::deduce(b"ID3\x04"), Some(AudioFormat::Mp3));
+		assert_eq!(AudioFormat::deduce(b"RIFF\0\0\0\0AVI "), None);
+		assert_eq!(AudioFormat::deduce(b"OggS"), Some(AudioFormat::Ogg));
+		assert_eq!(AudioFormat::deduce(b""), None);
+
+		let mut table = SoundTable::default();
+		assert!(!table.insert_music("D_RUNNIN", b"not music".to_vec()));
+		assert!(table.resolve_music("d_runnin").is_none());
+
+		let stream = AudioStream::new(MUS_HEADER.to_vec()).unwrap();
+		assert!(stream
+			.decode_waveform(StaticSoundSettings::default())
+			.is_none());
+
+		// Only gets decoded on demand, and so only fails on demand.
+		let stream = AudioStream::new(b"OggS\0\0\0\0".to_vec()).unwrap();
+		assert_eq!(stream.format(), AudioFormat::Ogg);
+		assert!(matches!(
+			stream.decode_waveform(StaticSoundSettings::default()),
+			Some(Err(_))
+		));
+	}
+
+	/// Format 3, with 16 bytes of padding either side of `samples`.
+	#[must_use]
+	fn dmx_lump(sample_rate: u16, samples: &[u8]) -> Vec<u8> {
+		let mut bytes = vec![0x03, 0x00];
+		bytes.extend_from_slice(&sample_rate.to_le_bytes());
+		bytes.extend_from_slice(&(samples.len() as u32 + 32).to_le_bytes());
+		bytes.extend_from_slice(&[0x80; 16]);
+		bytes.extend_from_slice(samples);
+		bytes.extend_from_slice(&[0x80; 16]);
+		bytes
+	}
+
+	#[test]
+	fn dmx_sound() {
+		let sound = sound_from_dmx(&dmx_lump(11025, &[0x80, 0xC0, 0x00]))
+			.unwrap()
+			.unwrap();
+
+		assert_eq!(sound.sample_rate, 11025);
+		assert_eq!(sound.frames.len(), 3);
+		assert_eq!(sound.frames[0], Frame::from_mono(0.0));
+		assert_eq!(sound.frames[1], Frame::from_mono(0.5));
+		assert_eq!(sound.frames[2], Frame::from_mono(-1.0));
+
+		// Not format 3, e.g. a PC speaker sound.
+		assert!(sound_from_dmx(&[0x00, 0x00, 0x03, 0x00]).is_none());
+
+		let mut zero_rate = dmx_lump(11025, &[0x80]);
+		zero_rate[2..4].copy_from_slice(&[0, 0]);
+		assert_eq!(
+			sound_from_dmx(&zero_rate).unwrap().unwrap_err(),
+			DmxSfxError::ZeroRate
+		);
+
+		let truncated = dmx_lump(11025, &[0x80; 8]);
+
+		assert_eq!(
+			sound_from_dmx(&truncated[..(truncated.len() - 4)])
+				.unwrap()
+				.unwrap_err(),
+			DmxSfxError::Truncated {
+				declared: 40,
+				available: 36,
+			}
+		);
+	}
+
+	#[test]
+	fn resampling() {
+		let frames = [Frame::from_mono(0.0), Frame::from_mono(1.0)];
+
+		let up = resample_linear(&frames, 11025, 44100);
+		assert_eq!(up.len(), 8);
+		assert_eq!(up[2], Frame::from_mono(0.5));
+
+		let down = resample_linear(&up, 44100, 22050);
+		assert_eq!(down.len(), 4);
+
+		assert_eq!(resample_linear(&frames, 11025, 11025), frames);
+	}
+
+	#[test]
+	fn dmx_sounds_from_wad() {
+		let dir = std::env::temp_dir().join(format!("viletech_dmx_{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		let wad_path = dir.join("sounds.wad");
+
+		std::fs::write(
+			&wad_path,
+			vfs::build_pwad(&[
+				("DSPISTOL", dmx_lump(11025, &[0x80; 4])),
+				// Format 0, like a PC speaker sound.
+				("DSNOTDMX", vec![0; 8]),
+				("DSBROKEN", dmx_lump(11025, &[0x80; 4])[..20].to_vec()),
+				("DS_START", vec![]),
+				("BLASTER", dmx_lump(22050, &[0x80; 4])),
+				("DS_END", vec![]),
+				("NOTSOUND", dmx_lump(22050, &[0x80; 4])),
+			]),
+		)
+		.unwrap();
+
+		let mut vfs = VirtualFs::default();
+		vfs.mount(&wad_path, vfs::VPath::new("sounds")).unwrap();
+		let wad = vfs
+			.lookup(vfs::VPath::new("/sounds"))
+			.unwrap()
+			.into_folder()
+			.unwrap();
+
+		let mut table = SoundTable::default();
+		let errors = table.insert_dmx(wad);
+
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].0, "DSBROKEN");
+		assert!(matches!(errors[0].1, DmxSfxError::Truncated { .. }));
+
+		assert_eq!(table.resolve("dspistol").unwrap().sample_rate, 11025);
+		assert_eq!(table.resolve("blaster").unwrap().sample_rate, 22050);
+		assert!(table.resolve("dsnotdmx").is_none());
+		assert!(table.resolve("notsound").is_none());
+
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+
 	fn read_sample_data(env_var_name: &'static str) -> Result<(PathBuf, Vec<u8>), String> {
 		let path = match std::env::var(env_var_name) {
 			Ok(p) => PathBuf::from(p),
@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use bevy::prelude::{Mut, World};
 use bevy_egui::egui;
 use indoc::formatdoc;
 use kira::{
@@ -11,8 +12,26 @@ use nodi::midly::Smf;
 use tracing::{error, info};
 use vfs::{VPath, VirtualFs};
 
+use crate::devgui::DevPanel;
+
 use super::{AudioCore, MidiData, MidiSettings, SoundSpace};
 
+/// Puts [`AudioCore`]'s developer GUI into a [`crate::devgui::DevGui`].
+#[derive(Debug, Default)]
+pub struct AudioPanel;
+
+impl DevPanel for AudioPanel {
+	fn name(&self) -> &'static str {
+		"Audio"
+	}
+
+	fn ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, world: &mut World) {
+		world.resource_scope(|world, mut audio: Mut<AudioCore>| {
+			audio.ui(ctx, ui, world.resource::<crate::VirtualFs>());
+		});
+	}
+}
+
 impl AudioCore {
 	pub(super) fn ui_impl(&mut self, _ctx: &egui::Context, ui: &mut egui::Ui, vfs: &VirtualFs) {
 		egui::ScrollArea::vertical().show(ui, |ui| {
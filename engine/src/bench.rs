@@ -0,0 +1,554 @@
+//! A harness for measuring load and simulation performance in a headless
+//! context, which doubles as a determinism check.
+//!
+//! Driven by the `bench` console command and the `--bench` launch argument of
+//! the client and server, both of which take a [`BenchSpec`].
+
+use std::{
+	path::{Path, PathBuf},
+	str::FromStr,
+	time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use nanorand::{Rng, WyRand};
+
+use crate::{
+	sim::{skill::SkillDef, Sim},
+	vfs::VPath,
+	VirtualFs,
+};
+
+/// Incremented whenever the layout of [`Report`]'s JSON form changes, so that
+/// CI scripts can refuse to compare reports from different schemas.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// How many tick times are kept for computing percentiles; see [`Reservoir`].
+pub const RESERVOIR_CAPACITY: usize = 4096;
+
+/// Parsed from a string of `;`-separated `key=value` pairs:
+/// - `load=path[,path...]`: files to mount, in order. Optional.
+/// - `ticks=N`: how many ticks to run the sim for.
+/// - `demo=path`: a demo to play back instead of a tick count.
+/// - `seed=N`: for the sim's PRNG. Defaults to 0.
+///
+/// e.g. `load=doom2.wad,mymod.pk3;ticks=350;seed=1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchSpec {
+	pub load_order: Vec<PathBuf>,
+	pub run: BenchRun,
+	pub seed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BenchRun {
+	Ticks(u32),
+	Demo(PathBuf),
+}
+
+impl FromStr for BenchSpec {
+	type Err = SpecError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut load_order = vec![];
+		let mut run = None;
+		let mut seed = 0;
+
+		for pair in s.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+			let Some((key, val)) = pair.split_once('=') else {
+				return Err(SpecError::MalformedPair(pair.to_string()));
+			};
+
+			let (key, val) = (key.trim(), val.trim());
+
+			if key.eq_ignore_ascii_case("load") {
+				load_order.extend(
+					val.split(',')
+						.map(str::trim)
+						.filter(|p| !p.is_empty())
+						.map(PathBuf::from),
+				);
+			} else if key.eq_ignore_ascii_case("ticks") {
+				if run.is_some() {
+					return Err(SpecError::AmbiguousRun);
+				}
+
+				let ticks = val
+					.parse::<u32>()
+					.map_err(|_| SpecError::InvalidNumber(val.to_string()))?;
+
+				run = Some(BenchRun::Ticks(ticks));
+			} else if key.eq_ignore_ascii_case("demo") {
+				if run.is_some() {
+					return Err(SpecError::AmbiguousRun);
+				}
+
+				run = Some(BenchRun::Demo(PathBuf::from(val)));
+			} else if key.eq_ignore_ascii_case("seed") {
+				seed = val
+					.parse::<u64>()
+					.map_err(|_| SpecError::InvalidNumber(val.to_string()))?;
+			} else {
+				return Err(SpecError::UnknownKey(key.to_string()));
+			}
+		}
+
+		let Some(run) = run else {
+			return Err(SpecError::NoRun);
+		};
+
+		Ok(Self {
+			load_order,
+			run,
+			seed,
+		})
+	}
+}
+
+/// See [`BenchSpec`]'s [`FromStr`] implementation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecError {
+	/// Something other than a `key=value` pair.
+	MalformedPair(String),
+	UnknownKey(String),
+	InvalidNumber(String),
+	/// Neither `ticks` nor `demo` were given.
+	NoRun,
+	/// Both `ticks` and `demo` were given, or one of them was given twice.
+	AmbiguousRun,
+}
+
+impl std::error::Error for SpecError {}
+
+impl std::fmt::Display for SpecError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::MalformedPair(pair) => write!(f, "expected `key=value`, found: `{pair}`"),
+			Self::UnknownKey(key) => write!(f, "unknown benchmark spec key: `{key}`"),
+			Self::InvalidNumber(num) => write!(f, "invalid number in benchmark spec: `{num}`"),
+			Self::NoRun => write!(f, "benchmark spec needs either `ticks` or `demo`"),
+			Self::AmbiguousRun => write!(f, "benchmark spec can have only one `ticks` or `demo`"),
+		}
+	}
+}
+
+// Sampling ////////////////////////////////////////////////////////////////////
+
+/// A fixed-size uniform sample of an unbounded stream of durations
+/// (i.e. Vitter's "Algorithm R"), so that arbitrarily long runs need only a
+/// constant amount of memory. The maximum and count are always exact.
+///
+/// Uses its own PRNG with a fixed seed so as to not perturb the sim's.
+#[derive(Debug)]
+pub struct Reservoir {
+	samples: Vec<Duration>,
+	capacity: usize,
+	seen: u64,
+	max: Duration,
+	prng: WyRand,
+}
+
+impl Reservoir {
+	/// Panics if `capacity` is 0.
+	#[must_use]
+	pub fn new(capacity: usize) -> Self {
+		assert!(capacity > 0, "a `Reservoir` needs a non-zero capacity");
+
+		Self {
+			samples: Vec::with_capacity(capacity),
+			capacity,
+			seen: 0,
+			max: Duration::ZERO,
+			prng: WyRand::new_seed(0),
+		}
+	}
+
+	pub fn push(&mut self, sample: Duration) {
+		self.seen += 1;
+		self.max = self.max.max(sample);
+
+		if self.samples.len() < self.capacity {
+			self.samples.push(sample);
+			return;
+		}
+
+		let i = self.prng.generate_range(0..self.seen);
+
+		if let Some(slot) = self.samples.get_mut(i as usize) {
+			*slot = sample;
+		}
+	}
+
+	/// How many samples have been pushed in total.
+	#[must_use]
+	pub fn seen(&self) -> u64 {
+		self.seen
+	}
+
+	#[must_use]
+	pub fn percentiles(&self) -> Percentiles {
+		let mut sorted = self.samples.clone();
+		sorted.sort_unstable();
+
+		Percentiles {
+			p50_ns: nearest_rank(&sorted, 50),
+			p95_ns: nearest_rank(&sorted, 95),
+			p99_ns: nearest_rank(&sorted, 99),
+			max_ns: self.max.as_nanos() as u64,
+		}
+	}
+}
+
+/// All values are in nanoseconds, and are 0 if nothing was sampled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Percentiles {
+	pub p50_ns: u64,
+	pub p95_ns: u64,
+	pub p99_ns: u64,
+	pub max_ns: u64,
+}
+
+/// `sorted` must be in ascending order.
+#[must_use]
+fn nearest_rank(sorted: &[Duration], percentile: usize) -> u64 {
+	if sorted.is_empty() {
+		return 0;
+	}
+
+	let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+	sorted[rank - 1].as_nanos() as u64
+}
+
+// Report //////////////////////////////////////////////////////////////////////
+
+/// The outcome of one benchmark. Serialized to JSON for CI comparison;
+/// see [`SCHEMA_VERSION`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Report {
+	pub schema: u32,
+	pub engine_version: String,
+	pub seed: u64,
+	/// One entry per timed step of the load, in the order they happened.
+	pub load: Vec<PhaseTime>,
+	pub ticks: u64,
+	pub tick_times: Percentiles,
+	/// Always `None` until the engine has a profiler which counts allocations.
+	pub allocations: Option<u64>,
+	/// See [`crate::rng::RngCore::state_hash`]. Identical across runs with the
+	/// same spec if the sim is deterministic.
+	pub rng_hash: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PhaseTime {
+	/// e.g. `mount: /home/user/doom2.wad`.
+	pub name: String,
+	pub nanos: u64,
+}
+
+impl Report {
+	#[must_use]
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("benchmark report serialization failed")
+	}
+
+	/// Writes [`Self::to_json`] to a file in `dir`, named after the current
+	/// UNIX time, and returns that file's path.
+	pub fn write_json(&self, dir: &Path) -> std::io::Result<PathBuf> {
+		let secs = std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.unwrap_or_default()
+			.as_secs();
+
+		std::fs::create_dir_all(dir)?;
+		let path = dir.join(format!("bench_{secs}.json"));
+		std::fs::write(&path, self.to_json())?;
+		Ok(path)
+	}
+}
+
+impl std::fmt::Display for Report {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let ms = |nanos: u64| Duration::from_nanos(nanos).as_secs_f64() * 1000.0;
+
+		writeln!(f, "Benchmark results (seed {}):", self.seed)?;
+
+		for phase in &self.load {
+			writeln!(f, "\t{}: {:.3} ms", phase.name, ms(phase.nanos))?;
+		}
+
+		writeln!(
+			f,
+			"\t{} ticks; p50 {:.3} ms, p95 {:.3} ms, p99 {:.3} ms, max {:.3} ms",
+			self.ticks,
+			ms(self.tick_times.p50_ns),
+			ms(self.tick_times.p95_ns),
+			ms(self.tick_times.p99_ns),
+			ms(self.tick_times.max_ns),
+		)?;
+
+		if let Some(allocs) = self.allocations {
+			writeln!(f, "\t{allocs} allocations")?;
+		}
+
+		write!(f, "\tRNG state hash: {:#018x}", self.rng_hash)
+	}
+}
+
+// Running /////////////////////////////////////////////////////////////////////
+
+/// Prefix of the mount points used for [`BenchSpec::load_order`]. Anything
+/// mounted under such a name gets unmounted at the start of every [`run`].
+pub const MOUNT_PREFIX: &str = "bench_";
+
+/// Mounts the spec's load order into `world`'s [`VirtualFs`], then runs the
+/// sim twice from the same seed: once timed, and once more to verify that the
+/// RNG ends up in the same state.
+///
+/// Each tick runs the [`FixedUpdate`] schedule, so whatever systems the caller
+/// has added there get measured. Any existing [`Sim`] resource is replaced.
+pub fn run(world: &mut World, spec: &BenchSpec) -> Result<Report, Error> {
+	let ticks = match &spec.run {
+		BenchRun::Ticks(t) => *t,
+		BenchRun::Demo(path) => return Err(Error::DemoUnsupported(path.clone())),
+	};
+
+	let mut load = vec![];
+
+	{
+		let mut vfs = world.get_resource_mut::<VirtualFs>().ok_or(Error::NoVfs)?;
+
+		let _ = vfs.retain(|mntinfo| !mntinfo.mount_point.as_str().starts_with(MOUNT_PREFIX));
+
+		for (i, path) in spec.load_order.iter().enumerate() {
+			let mount_point = format!("{MOUNT_PREFIX}{i}");
+			let start = Instant::now();
+
+			vfs.mount(path, VPath::new(&mount_point))
+				.map_err(|err| Error::Mount(path.clone(), err))?;
+
+			load.push(PhaseTime {
+				name: format!("mount: {}", path.display()),
+				nanos: start.elapsed().as_nanos() as u64,
+			});
+		}
+	}
+
+	let mut reservoir = Reservoir::new(RESERVOIR_CAPACITY);
+	let rng_hash = run_ticks(world, spec.seed, ticks, Some(&mut reservoir))?;
+	let rerun_hash = run_ticks(world, spec.seed, ticks, None)?;
+
+	if rng_hash != rerun_hash {
+		return Err(Error::Nondeterministic {
+			first: rng_hash,
+			second: rerun_hash,
+		});
+	}
+
+	Ok(Report {
+		schema: SCHEMA_VERSION,
+		engine_version: crate::VERSION.to_string(),
+		seed: spec.seed,
+		load,
+		ticks: reservoir.seen(),
+		tick_times: reservoir.percentiles(),
+		allocations: None,
+		rng_hash,
+	})
+}
+
+/// Calls [`run`] on a world built solely for it, which contains an empty
+/// [`VirtualFs`] and runs [`crate::sim::tick`] every tick.
+/// For when the engine is launched with nothing to do but benchmark.
+pub fn run_headless(spec: &BenchSpec) -> Result<Report, Error> {
+	let mut world = World::new();
+	world.insert_resource(VirtualFs(vfs::VirtualFs::default()));
+	world.insert_resource(Time::<Fixed>::default());
+
+	let mut schedule = Schedule::new(FixedUpdate);
+	schedule.add_systems(crate::sim::tick);
+	world.add_schedule(schedule);
+
+	run(&mut world, spec)
+}
+
+/// Returns the sim's RNG state hash after the last tick.
+fn run_ticks(
+	world: &mut World,
+	seed: u64,
+	ticks: u32,
+	mut reservoir: Option<&mut Reservoir>,
+) -> Result<u64, Error> {
+	world.insert_resource(Sim::new(SkillDef::default(), seed));
+
+	for _ in 0..ticks {
+		let start = Instant::now();
+
+		world
+			.try_run_schedule(FixedUpdate)
+			.map_err(|_| Error::NoSchedule)?;
+
+		if let Some(r) = reservoir.as_mut() {
+			r.push(start.elapsed());
+		}
+	}
+
+	Ok(world.resource::<Sim>().rng_hash())
+}
+
+/// Things that can go wrong during [`run`].
+#[derive(Debug)]
+pub enum Error {
+	/// Demo playback does not exist yet.
+	DemoUnsupported(PathBuf),
+	Mount(PathBuf, vfs::Error),
+	/// The world has no [`VirtualFs`] resource.
+	NoVfs,
+	/// The world has no [`FixedUpdate`] schedule.
+	NoSchedule,
+	/// The timed run and the verification run left the sim's RNG in different states.
+	Nondeterministic {
+		first: u64,
+		second: u64,
+	},
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Mount(_, err) => Some(err),
+			_ => None,
+		}
+	}
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::DemoUnsupported(path) => {
+				write!(f, "demo playback is not yet supported: {}", path.display())
+			}
+			Self::Mount(path, err) => write!(f, "failed to mount {}: {err}", path.display()),
+			Self::NoVfs => write!(f, "no virtual file system to mount the load order into"),
+			Self::NoSchedule => write!(f, "no fixed-update schedule to run the sim with"),
+			Self::Nondeterministic { first, second } => write!(
+				f,
+				"sim is not deterministic; RNG state hashes differ: {first:#018x} vs. {second:#018x}"
+			),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn spec_parsing() {
+		let spec = "load=doom2.wad, lorem.pk3; ticks=50; seed=7"
+			.parse::<BenchSpec>()
+			.unwrap();
+
+		assert_eq!(
+			spec.load_order,
+			[PathBuf::from("doom2.wad"), PathBuf::from("lorem.pk3")]
+		);
+		assert_eq!(spec.run, BenchRun::Ticks(50));
+		assert_eq!(spec.seed, 7);
+
+		assert_eq!("seed=1".parse::<BenchSpec>(), Err(SpecError::NoRun));
+		assert_eq!(
+			"ticks=1;demo=a.lmp".parse::<BenchSpec>(),
+			Err(SpecError::AmbiguousRun)
+		);
+		assert!(matches!(
+			"ticks=x".parse::<BenchSpec>(),
+			Err(SpecError::InvalidNumber(_))
+		));
+	}
+
+	#[test]
+	fn percentiles() {
+		let mut reservoir = Reservoir::new(1000);
+
+		for i in 1..=100 {
+			reservoir.push(Duration::from_nanos(i));
+		}
+
+		let pct = reservoir.percentiles();
+		assert_eq!(pct.p50_ns, 50);
+		assert_eq!(pct.p95_ns, 95);
+		assert_eq!(pct.p99_ns, 99);
+		assert_eq!(pct.max_ns, 100);
+
+		assert_eq!(Reservoir::new(1).percentiles(), Percentiles::default());
+	}
+
+	#[test]
+	fn reservoir_bounded() {
+		let mut reservoir = Reservoir::new(16);
+
+		for i in 0..10_000 {
+			reservoir.push(Duration::from_micros(i));
+		}
+
+		assert_eq!(reservoir.samples.len(), 16);
+		assert_eq!(reservoir.seen(), 10_000);
+		assert_eq!(reservoir.percentiles().max_ns, 9_999_000);
+	}
+
+	#[test]
+	fn json_schema() {
+		let report = Report {
+			schema: SCHEMA_VERSION,
+			engine_version: "0.0.0".to_string(),
+			seed: 0,
+			load: vec![PhaseTime {
+				name: "mount: doom2.wad".to_string(),
+				nanos: 1000,
+			}],
+			ticks: 50,
+			tick_times: Percentiles::default(),
+			allocations: None,
+			rng_hash: 0xDEAD,
+		};
+
+		let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+
+		for key in [
+			"schema",
+			"engine_version",
+			"seed",
+			"load",
+			"ticks",
+			"tick_times",
+			"allocations",
+			"rng_hash",
+		] {
+			assert!(json.get(key).is_some(), "missing key: {key}");
+		}
+
+		assert_eq!(json["tick_times"].as_object().unwrap().len(), 4);
+		assert_eq!(json["load"][0]["nanos"], 1000);
+
+		let back: Report = serde_json::from_str(&report.to_json()).unwrap();
+		assert_eq!(back, report);
+	}
+
+	#[test]
+	fn headless_run() {
+		let mut world = World::new();
+		world.insert_resource(VirtualFs(vfs::VirtualFs::default()));
+		world.add_schedule(Schedule::new(FixedUpdate));
+
+		let spec = "ticks=50;seed=3".parse::<BenchSpec>().unwrap();
+		let a = run(&mut world, &spec).unwrap();
+		let b = run_headless(&spec).unwrap();
+
+		assert_eq!(a.ticks, 50);
+		assert_eq!(a.rng_hash, b.rng_hash);
+
+		let spec = "ticks=50;seed=4".parse::<BenchSpec>().unwrap();
+		assert_ne!(run(&mut world, &spec).unwrap().rng_hash, a.rng_hash);
+	}
+}
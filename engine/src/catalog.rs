@@ -26,7 +26,9 @@ use smallvec::SmallVec;
 use util::{EditorNum, Outcome, SendTracker, SpawnNum};
 use vfs::VPathBuf;
 
-use crate::vfs::{FileRef, MountError, MountInfo, MountOutcome, MountRequest, VirtualFs};
+use crate::vfs::{
+	FileRef, MountError, MountFormat, MountInfo, MountOutcome, MountRequest, VirtualFs,
+};
 
 use self::{
 	dobj::{Blueprint, DataRef, Datum, DatumStore},
@@ -1,9 +1,8 @@
 //! Sound and music assets.
 
-use byteorder::{ByteOrder, LittleEndian};
 use kira::sound::static_sound::StaticSoundData;
 
-use crate::audio::MidiData;
+use crate::audio::{self, MidiData};
 
 #[derive(Debug)]
 #[allow(clippy::large_enum_variant)] // TODO: `MidiData` will eventually reach size parity.
@@ -35,30 +34,6 @@ impl Audio {
 		bytes.len() > 3 && matches!(bytes, &[0x4f, 0x67, 0x67, 0x53])
 	}
 
-	/// Adapted from SLADE's `DoomPCSpeakerDataFormat::isThisFormat`.
-	#[must_use]
-	pub fn is_pc_speaker_sound(bytes: &[u8]) -> bool {
-		if bytes.len() < 4 {
-			return false;
-		}
-
-		// (SLADE) The first two bytes must always be NUL.
-		if bytes[0] > 0 || bytes[1] > 0 {
-			return false;
-		}
-
-		// (SLADE) Next is the number of samples (LE uint16_t), and each sample
-		// is a single byte, so the size can be checked easily.
-
-		let sample_count = 4 + LittleEndian::read_u16(&bytes[2..4]);
-
-		if bytes.len() == sample_count as usize {
-			return true;
-		}
-
-		false
-	}
-
 	/// Source: <https://docs.rs/infer/0.13.0/src/infer/matchers/audio.rs.html#55-65>
 	#[must_use]
 	pub fn is_wav(bytes: &[u8]) -> bool {
@@ -20,7 +20,10 @@ use tracing::{info, warn};
 use util::{EditorNum, Outcome, SendTracker, SpawnNum};
 use vfs::VPathBuf;
 
-use crate::{catalog::dobj::datum_type_name, vfs::MountFormat};
+use crate::{
+	catalog::dobj::datum_type_name,
+	vfs::{FileRef, MountFormat},
+};
 
 use super::{
 	dobj::{DatumStore, Store},
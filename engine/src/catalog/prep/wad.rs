@@ -4,7 +4,7 @@ use std::{io::Cursor, ops::Range};
 
 use kira::sound::static_sound::{StaticSoundData, StaticSoundSettings};
 use rayon::prelude::*;
-use util::Outcome;
+use util::{io, Outcome};
 
 use crate::{
 	catalog::{
@@ -183,8 +183,8 @@ impl Catalog {
 		let proceed = wad
 			.children()
 			.unwrap()
-			.filter(|c| !c.is_empty())
 			.enumerate()
+			.filter(|(_, c)| !c.is_empty())
 			.par_bridge()
 			.try_for_each(|(cndx, child)| {
 				if ctx.is_cancelled() {
@@ -262,8 +262,9 @@ impl Catalog {
 		];
 
 		if UNHANDLED.iter().any(|&name| fpfx == name)
-			|| Audio::is_pc_speaker_sound(bytes)
-			|| mus::is_dmxmus(bytes)
+			|| io::is_doom_sfx(bytes)
+			|| io::is_pc_speaker_sound(bytes)
+			|| io::is_dmx_mus(bytes)
 		{
 			return;
 		}
@@ -12,7 +12,7 @@ use bevy_egui::egui::{
 use crossbeam::channel::Receiver;
 use util::lazy_regex;
 
-use crate::terminal::{self, Alias, Terminal};
+use crate::terminal::{self, Alias, CommandOutput, ExecError, Terminal};
 
 pub type Sender = crossbeam::channel::Sender<Message>;
 
@@ -45,9 +45,22 @@ pub struct Console<C: terminal::Command> {
 	/// If `false`, messages tagged [`MessageKind::Toast`] aren't drawn.
 	draw_toast: bool,
 
+	/// How much of the drop-down overlay is currently showing.
+	overlay: Overlay,
+	/// If `true`, closing the overlay leaves it in [`Overlay::Notify`].
+	notify: bool,
+	/// How many of the most recent lines get drawn by [`Overlay::Notify`].
+	notify_lines: usize,
+	/// Set when the overlay opens, since the key which opened it also types a
+	/// character that the next draw of the input line has to take back out.
+	swallow_toggle: bool,
+
 	/// Console commands can emit a "request" in order to act upon the client.
 	/// Between frames, this container gets drained and all requests are fulfilled.
 	pub requests: VecDeque<C::Output>,
+	/// Every non-empty line of input submitted, in order. Between frames, this
+	/// container gets drained so that scripts can be notified.
+	pub submissions: VecDeque<Box<str>>,
 }
 
 /// All messages that get sent to the console are tagged so they can be filtered.
@@ -69,6 +82,44 @@ pub struct Message {
 	kind: MessageKind,
 }
 
+/// The state of the "Quake-style" drop-down console overlay.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Overlay {
+	/// Nothing is drawn and no input is captured.
+	#[default]
+	Closed,
+	/// The most recent lines are drawn, but there is no input line,
+	/// and no input is captured.
+	Notify,
+	/// The scrollback and input line are drawn, and all keyboard input is
+	/// captured away from game actions.
+	Open,
+}
+
+impl Overlay {
+	/// Opens the overlay if it is closed or half-open; otherwise closes it to
+	/// [`Overlay::Notify`] if `notify` is `true` or [`Overlay::Closed`] if not.
+	#[must_use]
+	pub fn toggled(self, notify: bool) -> Self {
+		match self {
+			Self::Closed | Self::Notify => Self::Open,
+			Self::Open => {
+				if notify {
+					Self::Notify
+				} else {
+					Self::Closed
+				}
+			}
+		}
+	}
+
+	/// If `true`, keyboard input should not reach game actions.
+	#[must_use]
+	pub fn captures_input(self) -> bool {
+		self == Self::Open
+	}
+}
+
 // Public interface.
 impl<C: terminal::Command> Console<C> {
 	#[must_use]
@@ -88,101 +139,74 @@ impl<C: terminal::Command> Console<C> {
 			cursor_to_end: false,
 			draw_log: true,
 			draw_toast: true,
+			overlay: Overlay::Closed,
+			notify: true,
+			notify_lines: 4,
+			swallow_toggle: false,
 			requests: VecDeque::new(),
+			submissions: VecDeque::new(),
 		}
 	}
 
 	/// Receive incoming messages from the log backend - and ongoing playsim, if
 	/// any - and draw the window and all of its contents.
 	pub fn ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
-		while let Ok(msg) = self.log_receiver.try_recv() {
-			self.scroll_to_bottom = true;
-			self.messages.push(msg);
-		}
+		self.receive();
 
 		egui::menu::bar(ui, |ui| {
 			ui.toggle_value(&mut self.draw_log, "Show Engine Log");
 			ui.toggle_value(&mut self.draw_toast, "Show Game Log");
+			ui.toggle_value(&mut self.notify, "Notify on Close");
 			ui.separator();
 			ui.label("Input History Capacity");
 			ui.add(DragValue::new(&mut self.input_history_cap).clamp_range(0..=1024));
 		});
 
-		let mut layout = *ui.layout();
-		layout.main_dir = egui::Direction::BottomUp;
-
 		ui.set_min_height(ui.spacing().interact_size.y * 4.0);
-		let txt_style_height = ui.text_style_height(&egui::TextStyle::Body);
 
 		ui.separator();
-
-		let scroll_area = ScrollArea::horizontal()
-			.id_source("viletech_dgui_console_log")
-			.auto_shrink([false, false])
-			.max_height(ui.available_height() - (txt_style_height * 2.5))
-			.stick_to_bottom(true);
-
-		scroll_area.show(ui, |ui| {
-			ui.vertical(|ui| {
-				for item in &self.messages {
-					match item.kind {
-						MessageKind::Toast => {
-							if !self.draw_toast {
-								continue;
-							}
-
-							for line in item.string.lines() {
-								Self::draw_line(ui, line);
-							}
-						}
-						MessageKind::Log => {
-							if !self.draw_log {
-								continue;
-							}
-
-							for line in item.string.lines() {
-								Self::draw_line_log(ui, line);
-							}
-						}
-						MessageKind::Help => {
-							for line in item.string.lines() {
-								Self::draw_line(ui, line);
-							}
-						}
-					}
-				}
-			});
-
-			if self.scroll_to_bottom {
-				self.scroll_to_bottom = false;
-				ui.scroll_to_cursor(Some(egui::Align::BOTTOM));
-			}
-		});
-
+		self.ui_scrollback(ui, "viletech_dgui_console_log");
 		ui.separator();
+		self.ui_input(ctx, ui, egui::Id::new("viletech_console_txedit"));
+	}
 
-		ui.horizontal(|ui| {
-			let input_len = self.input.len();
-			let edit_id = egui::Id::new("viletech_console_txedit");
-			let resp_edit = ui.add(egui::TextEdit::singleline(&mut self.input).id(edit_id));
-			let mut tes = egui::TextEdit::load_state(ctx, edit_id).unwrap_or_default();
-
-			if self.cursor_to_end {
-				self.cursor_to_end = false;
-				let range = CCursorRange::one(CCursor::new(input_len));
-				tes.set_ccursor_range(Some(range));
-				TextEditState::store(tes, ctx, edit_id);
-			}
-
-			if self.defocus_textedit {
-				self.defocus_textedit = false;
-				resp_edit.surrender_focus();
+	/// Draws the "Quake-style" drop-down overlay at the top of the screen,
+	/// in whatever form is dictated by [`Self::overlay`].
+	pub fn ui_overlay(&mut self, ctx: &egui::Context) {
+		self.receive();
+
+		match self.overlay {
+			Overlay::Closed => {}
+			Overlay::Notify => {
+				egui::Area::new("viletech_console_notify")
+					.anchor(egui::Align2::LEFT_TOP, [4.0, 4.0])
+					.interactable(false)
+					.show(ctx, |ui| {
+						for line in self.recent_lines(self.notify_lines) {
+							Self::draw_line(ui, line);
+						}
+					});
 			}
-
-			if ui.add(egui::widgets::Button::new("Submit")).clicked() {
-				self.try_submit();
+			Overlay::Open => {
+				let before = self.swallow_toggle.then(|| self.input.clone());
+				self.swallow_toggle = false;
+
+				let screen_rect = ctx.input(|inps| inps.screen_rect);
+
+				egui::TopBottomPanel::top("viletech_console_overlay")
+					.exact_height(screen_rect.height() * 0.5)
+					.frame(egui::Frame::side_top_panel(&ctx.style()).multiply_with_opacity(0.9))
+					.show(ctx, |ui| {
+						self.ui_scrollback(ui, "viletech_console_overlay_log");
+						ui.separator();
+						self.ui_input(ctx, ui, egui::Id::new("viletech_console_overlay_txedit"));
+					});
+
+				if let Some(before) = before {
+					self.drop_toggle_char(&before);
+				}
 			}
-		});
+		}
 	}
 
 	/// Appends a custom message.
@@ -201,6 +225,7 @@ impl<C: terminal::Command> Console<C> {
 		down_pressed: bool,
 		esc_pressed: bool,
 		enter_pressed: bool,
+		tab_pressed: bool,
 	) {
 		if up_pressed {
 			if self.input_history_pos < 1 {
@@ -226,12 +251,81 @@ impl<C: terminal::Command> Console<C> {
 					.push_str(&self.input_history[self.input_history_pos]);
 			}
 		} else if esc_pressed {
-			self.defocus_textedit = true;
+			if self.overlay.captures_input() {
+				self.close_overlay();
+			} else {
+				self.defocus_textedit = true;
+			}
 		} else if enter_pressed {
 			self.try_submit();
+		} else if tab_pressed {
+			self.try_complete();
+		}
+	}
+
+	#[must_use]
+	pub fn overlay(&self) -> Overlay {
+		self.overlay
+	}
+
+	/// Opens the drop-down overlay if it is closed or half-open, and closes it
+	/// if it is open. See [`Overlay::toggled`].
+	pub fn toggle_overlay(&mut self) {
+		self.overlay = self.overlay.toggled(self.notify);
+		self.swallow_toggle = self.overlay == Overlay::Open;
+	}
+
+	/// Closes the drop-down overlay, leaving it half-open if so configured.
+	pub fn close_overlay(&mut self) {
+		if self.overlay == Overlay::Open {
+			self.toggle_overlay();
 		}
 	}
 
+	/// If `true`, closing the overlay leaves it in [`Overlay::Notify`] rather
+	/// than [`Overlay::Closed`].
+	pub fn set_notify(&mut self, notify: bool) {
+		self.notify = notify;
+
+		if !notify && self.overlay == Overlay::Notify {
+			self.overlay = Overlay::Closed;
+		}
+	}
+
+	/// If `true`, keyboard input should go to the console and not to game actions.
+	#[must_use]
+	pub fn captures_input(&self) -> bool {
+		self.overlay.captures_input()
+	}
+
+	#[must_use]
+	pub fn input(&self) -> &str {
+		&self.input
+	}
+
+	/// Replaces the currently-buffered input, as though typed by the user.
+	pub fn set_input(&mut self, input: impl Into<String>) {
+		self.input = input.into();
+		self.cursor_to_end = true;
+	}
+
+	/// Runs `source` via [`Terminal::exec_lines`]. Command outputs get queued
+	/// as [requests](Self::requests), and errors get logged. `read` resolves the
+	/// files named by nested `exec` lines; see [`terminal::read_exec_file`].
+	pub fn exec_lines(
+		&mut self,
+		source: &str,
+		origin: &str,
+		read: impl FnMut(&str) -> Result<String, String>,
+	) {
+		let mut sink = ExecSink {
+			requests: &mut self.requests,
+			read,
+		};
+
+		self.terminal.exec_lines(source, origin, &mut sink);
+	}
+
 	pub fn register_command(&mut self, id: &'static str, cmd: C, enabled: bool) {
 		self.terminal.register_command(id, cmd, enabled);
 	}
@@ -305,6 +399,167 @@ impl<C: terminal::Command> Console<C> {
 
 // Internal implementation details.
 impl<C: terminal::Command> Console<C> {
+	fn receive(&mut self) {
+		while let Ok(msg) = self.log_receiver.try_recv() {
+			self.scroll_to_bottom = true;
+			crate::crash::push_console_line(&msg.string);
+			self.messages.push(msg);
+		}
+	}
+
+	/// If the only change to the input since `before` is one inserted backquote,
+	/// take it back out. Any other backquote was typed deliberately.
+	fn drop_toggle_char(&mut self, before: &str) {
+		if self.input.len() != before.len() + 1 {
+			return;
+		}
+
+		let typed = self.input.match_indices('`').any(|(i, _)| {
+			before.get(..i) == Some(&self.input[..i])
+				&& before.get(i..) == Some(&self.input[(i + 1)..])
+		});
+
+		if typed {
+			self.input = before.to_string();
+		}
+	}
+
+	/// Yields, in order, up to `count` of the most recent lines which pass the
+	/// message kind filters.
+	fn recent_lines(&self, count: usize) -> impl Iterator<Item = &str> {
+		let mut lines: Vec<&str> = self
+			.messages
+			.iter()
+			.rev()
+			.filter(|msg| match msg.kind {
+				MessageKind::Toast => self.draw_toast,
+				MessageKind::Log => self.draw_log,
+				MessageKind::Help => true,
+			})
+			.flat_map(|msg| msg.string.lines().rev())
+			.take(count)
+			.collect();
+
+		lines.reverse();
+		lines.into_iter()
+	}
+
+	fn ui_scrollback(&mut self, ui: &mut egui::Ui, id_source: &'static str) {
+		let txt_style_height = ui.text_style_height(&egui::TextStyle::Body);
+
+		let scroll_area = ScrollArea::horizontal()
+			.id_source(id_source)
+			.auto_shrink([false, false])
+			.max_height(ui.available_height() - (txt_style_height * 2.5))
+			.stick_to_bottom(true);
+
+		scroll_area.show(ui, |ui| {
+			ui.vertical(|ui| {
+				for item in &self.messages {
+					match item.kind {
+						MessageKind::Toast => {
+							if !self.draw_toast {
+								continue;
+							}
+
+							for line in item.string.lines() {
+								Self::draw_line(ui, line);
+							}
+						}
+						MessageKind::Log => {
+							if !self.draw_log {
+								continue;
+							}
+
+							for line in item.string.lines() {
+								Self::draw_line_log(ui, line);
+							}
+						}
+						MessageKind::Help => {
+							for line in item.string.lines() {
+								Self::draw_line(ui, line);
+							}
+						}
+					}
+				}
+			});
+
+			if self.scroll_to_bottom {
+				self.scroll_to_bottom = false;
+				ui.scroll_to_cursor(Some(egui::Align::BOTTOM));
+			}
+		});
+	}
+
+	fn ui_input(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, edit_id: egui::Id) {
+		ui.horizontal(|ui| {
+			let input_len = self.input.len();
+
+			let resp_edit = ui.add(
+				egui::TextEdit::singleline(&mut self.input)
+					.id(edit_id)
+					.lock_focus(true),
+			);
+
+			let mut tes = egui::TextEdit::load_state(ctx, edit_id).unwrap_or_default();
+
+			if self.cursor_to_end {
+				self.cursor_to_end = false;
+				let range = CCursorRange::one(CCursor::new(input_len));
+				tes.set_ccursor_range(Some(range));
+				TextEditState::store(tes, ctx, edit_id);
+			}
+
+			if self.defocus_textedit {
+				self.defocus_textedit = false;
+				resp_edit.surrender_focus();
+			} else if self.overlay.captures_input() {
+				resp_edit.request_focus();
+			}
+
+			if ui.add(egui::widgets::Button::new("Submit")).clicked() {
+				self.try_submit();
+			}
+		});
+	}
+
+	/// Completes the first token of the input buffer against the terminal's
+	/// command IDs and aliases. If there is more than one candidate, the input
+	/// is extended to their longest common prefix and all of them are listed.
+	fn try_complete(&mut self) {
+		if self.input.is_empty() || self.input.contains(char::is_whitespace) {
+			return;
+		}
+
+		let (completed, listing) = {
+			let candidates = self.terminal.complete(&self.input);
+
+			match candidates.as_slice() {
+				[] => return,
+				[only] => (format!("{only} "), None),
+				[first, rest @ ..] => {
+					let prefix_len = rest.iter().fold(first.len(), |len, cand| {
+						first
+							.bytes()
+							.zip(cand.bytes())
+							.take(len)
+							.take_while(|(a, b)| a == b)
+							.count()
+					});
+
+					(first[..prefix_len].to_string(), Some(candidates.join("  ")))
+				}
+			}
+		};
+
+		if let Some(listing) = listing {
+			self.write(listing, MessageKind::Help);
+		}
+
+		self.input = completed;
+		self.cursor_to_end = true;
+	}
+
 	fn try_submit(&mut self) {
 		if self.input.is_empty() {
 			info!("$");
@@ -332,6 +587,8 @@ impl<C: terminal::Command> Console<C> {
 		}
 
 		info!("$ {}", &self.input);
+		self.submissions
+			.push_back(self.input.clone().into_boxed_str());
 		let mut ret = self.terminal.submit(&self.input);
 
 		for output in ret.drain(..) {
@@ -385,6 +642,29 @@ impl<C: terminal::Command> Console<C> {
 	}
 }
 
+/// See [`Console::exec_lines`].
+struct ExecSink<'c, O, R> {
+	requests: &'c mut VecDeque<O>,
+	read: R,
+}
+
+impl<O, R> CommandOutput<O> for ExecSink<'_, O, R>
+where
+	R: FnMut(&str) -> Result<String, String>,
+{
+	fn output(&mut self, output: O) {
+		self.requests.push_back(output);
+	}
+
+	fn error(&mut self, error: ExecError) {
+		error!("{error}");
+	}
+
+	fn read(&mut self, path: &str) -> Result<String, String> {
+		(self.read)(path)
+	}
+}
+
 /// Provides a bridge between the logging backend, which needs a channel sender
 /// as well as a [`std::io::Write`] implementation, and the console.
 #[derive(Debug)]
@@ -451,3 +731,128 @@ impl io::Write for Writer {
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[derive(Debug)]
+	struct Echo;
+
+	impl terminal::Command for Echo {
+		type Output = String;
+
+		fn call(&self, args: terminal::CommandArgs) -> Self::Output {
+			args.concat(0)
+		}
+	}
+
+	#[must_use]
+	fn console() -> (Console<Echo>, Sender) {
+		let (sender, receiver) = crossbeam::channel::unbounded();
+		let mut console = Console::new(receiver);
+		console.register_command("echo", Echo, true);
+		console.register_command("exec", Echo, true);
+		console.register_command("version", Echo, true);
+		(console, sender)
+	}
+
+	#[test]
+	fn overlay_transitions() {
+		let (mut console, _) = console();
+		assert_eq!(console.overlay(), Overlay::Closed);
+		assert!(!console.captures_input());
+
+		console.toggle_overlay();
+		assert_eq!(console.overlay(), Overlay::Open);
+		assert!(console.captures_input());
+
+		console.toggle_overlay();
+		assert_eq!(console.overlay(), Overlay::Notify);
+		assert!(!console.captures_input());
+
+		console.toggle_overlay();
+		assert_eq!(console.overlay(), Overlay::Open);
+
+		// Escape releases input capture.
+		console.key_input(false, false, true, false, false);
+		assert_eq!(console.overlay(), Overlay::Notify);
+
+		console.set_notify(false);
+		assert_eq!(console.overlay(), Overlay::Closed);
+		console.toggle_overlay();
+		console.toggle_overlay();
+		assert_eq!(console.overlay(), Overlay::Closed);
+	}
+
+	#[test]
+	fn submit_and_capture() {
+		let (mut console, sender) = console();
+
+		console.set_input("echo hello world");
+		console.key_input(false, false, false, true, false);
+		assert!(console.input().is_empty());
+		assert_eq!(console.requests.pop_front().unwrap(), "echo hello world");
+		assert_eq!(
+			console.submissions.pop_front().as_deref(),
+			Some("echo hello world")
+		);
+
+		// History navigation restores the submitted line.
+		console.key_input(true, false, false, false, false);
+		assert_eq!(console.input(), "echo hello world");
+		console.key_input(false, true, false, false, false);
+		assert!(console.input().is_empty());
+
+		sender
+			.send(Message {
+				string: "first\nsecond".into(),
+				kind: MessageKind::Log,
+			})
+			.unwrap();
+
+		console.receive();
+		console.write("third".to_string(), MessageKind::Help);
+
+		let recent: Vec<_> = console.recent_lines(2).collect();
+		assert_eq!(recent, ["second", "third"]);
+	}
+
+	#[test]
+	fn tab_completion() {
+		let (mut console, _) = console();
+
+		console.set_input("v");
+		console.key_input(false, false, false, false, true);
+		assert_eq!(console.input(), "version ");
+
+		console.set_input("e");
+		console.key_input(false, false, false, false, true);
+		assert_eq!(console.input(), "e");
+		assert_eq!(console.recent_lines(1).next().unwrap(), "echo  exec");
+
+		console.set_input("ex");
+		console.key_input(false, false, false, false, true);
+		assert_eq!(console.input(), "exec ");
+	}
+
+	#[test]
+	fn toggle_char() {
+		let (mut console, _) = console();
+
+		// Only the backquote typed by the toggle key gets removed...
+		console.set_input("echo `a`");
+		console.input.insert(5, '`');
+		console.drop_toggle_char("echo `a`");
+		assert_eq!(console.input(), "echo `a`");
+
+		// ...and only if nothing else changed.
+		console.set_input("echo `a`x`");
+		console.drop_toggle_char("echo `a`");
+		assert_eq!(console.input(), "echo `a`x`");
+
+		console.set_input("echo `a`");
+		console.drop_toggle_char("echo `a`");
+		assert_eq!(console.input(), "echo `a`");
+	}
+}
@@ -0,0 +1,483 @@
+//! Crash reporting.
+//!
+//! [`install`] replaces the panic hook with one which, after the previous hook
+//! has run, writes a plain-text report to a timestamped file. Besides the panic
+//! message and a backtrace, the report holds a section from each state provider
+//! [registered](register) by the engine or the application hosting it.
+
+use std::{
+	any::Any,
+	backtrace::Backtrace,
+	collections::VecDeque,
+	panic::AssertUnwindSafe,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+	time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+
+/// Returns `None` if there is nothing worth reporting.
+pub type Provider = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+/// How long the panic hook waits on all providers combined before giving up
+/// on those which have not yet finished.
+pub const PROVIDER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many lines [`push_console_line`] retains.
+pub const CONSOLE_TAIL_LEN: usize = 64;
+
+/// Installs the crash-reporting panic hook, and registers the "Version" and
+/// "Console" providers.
+///
+/// Reports get written to `dir`, which is created if it does not exist yet.
+/// The path to each report is printed to stderr, and then passed to `on_report`
+/// (e.g. so the application can show a dialog).
+///
+/// Only the first panic in the process gets reported; any panics which follow
+/// it (in other threads, or from unwinding) are left to the previous hook.
+pub fn install(dir: PathBuf, on_report: fn(&Path)) {
+	register("Version", || Some(crate::version_info().join("\n")));
+
+	register("Console", || {
+		let tail = CONSOLE_TAIL.try_lock_for(PROVIDER_TIMEOUT)?;
+
+		if tail.is_empty() {
+			return None;
+		}
+
+		Some(
+			tail.iter()
+				.map(|line| line.as_ref())
+				.collect::<Vec<_>>()
+				.join("\n"),
+		)
+	});
+
+	let prev_hook = std::panic::take_hook();
+
+	std::panic::set_hook(Box::new(move |info| {
+		// (RAT) A provider which panics is skipped, so its panic would only be
+		// noise to someone reading stderr.
+		if std::thread::current().name() == Some(GATHER_THREAD_NAME) {
+			return;
+		}
+
+		prev_hook(info);
+
+		if REPORTING.swap(true, Ordering::AcqRel) {
+			return;
+		}
+
+		let report = Report::new(
+			panic_message(info.payload()),
+			info.location().map(|loc| loc.to_string()),
+			std::thread::current().name().map(String::from),
+			Backtrace::force_capture().to_string(),
+			gather(PROVIDER_TIMEOUT),
+		);
+
+		match report.write(&dir) {
+			Ok(path) => {
+				eprintln!("Crash report written to: {}", path.display());
+				on_report(&path);
+			}
+			Err(err) => {
+				eprintln!("Failed to write crash report to `{}`: {err}", dir.display());
+			}
+		}
+	}));
+}
+
+/// Where crash reports should go; a `crashes` directory under the user
+/// directory if one has been selected, or the temporary directory otherwise.
+#[must_use]
+pub fn default_dir() -> PathBuf {
+	let portable = crate::user::user_dir_portable();
+	let home = crate::user::user_dir_home();
+
+	match crate::user::select_user_dir(&portable, &home) {
+		Some(udir) => udir.join("crashes"),
+		None => std::env::temp_dir().join("viletech"),
+	}
+}
+
+/// Adds a section to all future crash reports. If a provider has already been
+/// registered under `name`, it gets replaced.
+///
+/// `provider` runs on a separate thread in the middle of a panic, so it must not
+/// block indefinitely; prefer `try_read_for`/`try_lock_for` with
+/// [`PROVIDER_TIMEOUT`] when taking locks. If it panics, it gets skipped.
+pub fn register(name: &'static str, provider: impl Fn() -> Option<String> + Send + Sync + 'static) {
+	let provider: Provider = Arc::new(provider);
+	let mut providers = PROVIDERS.lock();
+
+	match providers.iter_mut().find(|(n, _)| *n == name) {
+		Some((_, p)) => *p = provider,
+		None => providers.push((name, provider)),
+	}
+}
+
+/// Removes the provider registered under `name`, if there is one.
+pub fn unregister(name: &'static str) {
+	PROVIDERS.lock().retain(|(n, _)| *n != name);
+}
+
+/// Shorthand for registering a provider which always reports `value`.
+/// Suitable for state which changes rarely, like the current load order.
+pub fn set_note(name: &'static str, value: String) {
+	register(name, move || Some(value.clone()));
+}
+
+/// Retains `line` for the "Console" section of crash reports, evicting the
+/// oldest line if there are already [`CONSOLE_TAIL_LEN`].
+pub fn push_console_line(line: &str) {
+	let mut tail = CONSOLE_TAIL.lock();
+
+	if tail.len() >= CONSOLE_TAIL_LEN {
+		tail.pop_front();
+	}
+
+	tail.push_back(line.into());
+}
+
+/// Runs every registered provider on a separate thread, giving up on those
+/// which have not finished once `timeout` has elapsed.
+#[must_use]
+pub fn gather(timeout: Duration) -> Gathered {
+	let deadline = Instant::now() + timeout;
+
+	let Some(providers) = PROVIDERS.try_lock_until(deadline).map(|p| p.clone()) else {
+		return Gathered::default();
+	};
+
+	let (sender, receiver) = crossbeam::channel::unbounded();
+	let names: Vec<_> = providers.iter().map(|(name, _)| *name).collect();
+
+	let spawned = std::thread::Builder::new()
+		.name(GATHER_THREAD_NAME.to_string())
+		.spawn(move || {
+			for (i, (_, provider)) in providers.into_iter().enumerate() {
+				let result = std::panic::catch_unwind(AssertUnwindSafe(|| provider()));
+
+				if sender.send((i, result.ok())).is_err() {
+					return;
+				}
+			}
+		});
+
+	if spawned.is_err() {
+		return Gathered {
+			sections: vec![],
+			unavailable: names,
+		};
+	}
+
+	let mut results = vec![None; names.len()];
+
+	for _ in 0..names.len() {
+		let Ok((i, result)) = receiver.recv_deadline(deadline) else {
+			break;
+		};
+
+		results[i] = Some(result);
+	}
+
+	Gathered::aggregate(&names, results)
+}
+
+/// What [`gather`] got from the registered providers.
+#[derive(Debug, Default)]
+pub struct Gathered {
+	pub sections: Vec<Section>,
+	/// Providers which panicked or failed to finish in time.
+	pub unavailable: Vec<&'static str>,
+}
+
+impl Gathered {
+	/// `results` is parallel to `names`. An outer `None` means the provider
+	/// did not finish in time; `Some(None)` means it panicked, and `Some(Some(None))`
+	/// means it finished but had nothing to report.
+	#[must_use]
+	fn aggregate(names: &[&'static str], results: Vec<Option<Option<Option<String>>>>) -> Self {
+		let mut ret = Self::default();
+
+		for (name, result) in names.iter().copied().zip(results) {
+			match result {
+				Some(Some(Some(body))) => ret.sections.push(Section { name, body }),
+				Some(Some(None)) => {}
+				Some(None) | None => ret.unavailable.push(name),
+			}
+		}
+
+		ret
+	}
+}
+
+#[derive(Debug)]
+pub struct Section {
+	pub name: &'static str,
+	pub body: String,
+}
+
+#[derive(Debug)]
+pub struct Report {
+	pub message: String,
+	/// In the format `file:line:column`.
+	pub location: Option<String>,
+	/// The name of the thread that panicked, if it had one.
+	pub thread: Option<String>,
+	pub backtrace: String,
+	pub gathered: Gathered,
+}
+
+impl Report {
+	#[must_use]
+	pub fn new(
+		message: String,
+		location: Option<String>,
+		thread: Option<String>,
+		backtrace: String,
+		gathered: Gathered,
+	) -> Self {
+		Self {
+			message,
+			location,
+			thread,
+			backtrace,
+			gathered,
+		}
+	}
+
+	/// Writes this report to a file named after the current UTC time in `dir`,
+	/// creating `dir` if necessary. Returns the path to the written file.
+	pub fn write(&self, dir: &Path) -> std::io::Result<PathBuf> {
+		let now = time::OffsetDateTime::now_utc();
+
+		let fname = format!(
+			"crash_{:04}{:02}{:02}-{:02}{:02}{:02}.txt",
+			now.year(),
+			now.month() as u8,
+			now.day(),
+			now.hour(),
+			now.minute(),
+			now.second()
+		);
+
+		std::fs::create_dir_all(dir)?;
+		let path = dir.join(fname);
+		std::fs::write(&path, self.to_string())?;
+		Ok(path)
+	}
+}
+
+impl std::fmt::Display for Report {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "VileTech crash report")?;
+		writeln!(f)?;
+		writeln!(f, "== Panic ==")?;
+		writeln!(
+			f,
+			"Thread: {}",
+			self.thread.as_deref().unwrap_or("<unnamed>")
+		)?;
+		writeln!(
+			f,
+			"Location: {}",
+			self.location.as_deref().unwrap_or("<unknown>")
+		)?;
+		writeln!(f, "Message: {}", self.message)?;
+
+		for section in &self.gathered.sections {
+			writeln!(f)?;
+			writeln!(f, "== {} ==", section.name)?;
+			writeln!(f, "{}", section.body.trim_end())?;
+		}
+
+		if !self.gathered.unavailable.is_empty() {
+			writeln!(f)?;
+			writeln!(f, "== Unavailable ==")?;
+
+			for name in &self.gathered.unavailable {
+				writeln!(f, "{name} (panicked or timed out)")?;
+			}
+		}
+
+		writeln!(f)?;
+		writeln!(f, "== Backtrace ==")?;
+		writeln!(f, "{}", self.backtrace.trim_end())
+	}
+}
+
+// Details /////////////////////////////////////////////////////////////////////
+
+const GATHER_THREAD_NAME: &str = "viletech-crash-report";
+
+static PROVIDERS: Mutex<Vec<(&'static str, Provider)>> = Mutex::new(vec![]);
+static CONSOLE_TAIL: Mutex<VecDeque<Box<str>>> = Mutex::new(VecDeque::new());
+static REPORTING: AtomicBool = AtomicBool::new(false);
+
+#[must_use]
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+	if let Some(s) = payload.downcast_ref::<&str>() {
+		(*s).to_string()
+	} else if let Some(s) = payload.downcast_ref::<String>() {
+		s.clone()
+	} else {
+		"<non-string panic payload>".to_string()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Environment variable which tells [`subprocess`] to panic instead of
+	/// spawning itself.
+	const SUBPROCESS_DIR_VAR: &str = "VILETECH_CRASH_TEST_DIR";
+
+	#[test]
+	fn aggregation() {
+		let names = ["Finished", "Empty", "Panicked", "Timed Out"];
+
+		let results = vec![
+			Some(Some(Some("body".to_string()))),
+			Some(Some(None)),
+			Some(None),
+			None,
+		];
+
+		let gathered = Gathered::aggregate(&names, results);
+		assert_eq!(gathered.sections.len(), 1);
+		assert_eq!(gathered.sections[0].name, "Finished");
+		assert_eq!(gathered.sections[0].body, "body");
+		assert_eq!(gathered.unavailable, ["Panicked", "Timed Out"]);
+	}
+
+	#[test]
+	fn formatting() {
+		let report = Report::new(
+			"oh no".to_string(),
+			Some("src/main.rs:1:2".to_string()),
+			None,
+			"0: main\n".to_string(),
+			Gathered {
+				sections: vec![Section {
+					name: "Load Order",
+					body: "doom2.wad\n".to_string(),
+				}],
+				unavailable: vec!["GPU Adapter"],
+			},
+		);
+
+		let expected = "\
+VileTech crash report
+
+== Panic ==
+Thread: <unnamed>
+Location: src/main.rs:1:2
+Message: oh no
+
+== Load Order ==
+doom2.wad
+
+== Unavailable ==
+GPU Adapter (panicked or timed out)
+
+== Backtrace ==
+0: main
+";
+
+		assert_eq!(report.to_string(), expected);
+	}
+
+	#[test]
+	fn providers() {
+		register("test_ok", || Some("fine".to_string()));
+		register("test_panic", || panic!("provider failure"));
+		register("test_slow", || {
+			std::thread::sleep(Duration::from_secs(5));
+			Some("too late".to_string())
+		});
+		register("test_after", || Some("unreached".to_string()));
+
+		let gathered = gather(Duration::from_millis(250));
+
+		unregister("test_ok");
+		unregister("test_panic");
+		unregister("test_slow");
+		unregister("test_after");
+
+		assert!(gathered
+			.sections
+			.iter()
+			.any(|s| s.name == "test_ok" && s.body == "fine"));
+
+		for name in ["test_panic", "test_slow", "test_after"] {
+			assert!(gathered.unavailable.contains(&name));
+		}
+	}
+
+	#[test]
+	fn console_tail() {
+		for i in 0..(CONSOLE_TAIL_LEN + 8) {
+			push_console_line(&i.to_string());
+		}
+
+		let tail = CONSOLE_TAIL.lock();
+		assert_eq!(tail.len(), CONSOLE_TAIL_LEN);
+		assert_eq!(
+			tail.back().unwrap().as_ref(),
+			(CONSOLE_TAIL_LEN + 7).to_string()
+		);
+	}
+
+	/// Re-runs this test in a child process which installs the hook and panics,
+	/// then checks the report it leaves behind.
+	#[test]
+	fn subprocess() {
+		if let Ok(dir) = std::env::var(SUBPROCESS_DIR_VAR) {
+			install(PathBuf::from(dir), |_| {});
+			set_note("Load Order", "freedoom2.wad".to_string());
+			panic!("deliberate crash");
+		}
+
+		let dir = std::env::temp_dir().join(format!("viletech_crash_{}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&dir);
+
+		let output = std::process::Command::new(std::env::current_exe().unwrap())
+			.args(["--exact", "crash::test::subprocess", "--nocapture"])
+			.env(SUBPROCESS_DIR_VAR, &dir)
+			.output()
+			.unwrap();
+
+		assert!(!output.status.success());
+
+		let report = std::fs::read_dir(&dir)
+			.unwrap()
+			.map(|entry| entry.unwrap().path())
+			.find(|path| path.extension().is_some_and(|ext| ext == "txt"))
+			.expect("no crash report was written");
+
+		let text = std::fs::read_to_string(report).unwrap();
+		let _ = std::fs::remove_dir_all(&dir);
+
+		for header in [
+			"== Panic ==",
+			"== Version ==",
+			"== Load Order ==",
+			"== Backtrace ==",
+		] {
+			assert!(
+				text.contains(header),
+				"report is missing `{header}`:\n{text}"
+			);
+		}
+
+		assert!(text.contains("Message: deliberate crash"));
+		assert!(text.contains("freedoom2.wad"));
+	}
+}
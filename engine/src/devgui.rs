@@ -0,0 +1,554 @@
+//! The framework of the developer/debug GUI: a registry of [panels](DevPanel),
+//! which both the engine and applications can add to, and where they are docked.
+
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::prelude::{Resource, World};
+use bevy_egui::egui;
+use serde::{Deserialize, Serialize};
+
+/// Whatever a [`DevPanel`] chooses to remember between runs, as key-value pairs.
+pub type PanelState = BTreeMap<String, String>;
+
+pub trait DevPanel: 'static + Send + Sync {
+	/// Shown on this panel's tab, and used as its key when persisting the layout.
+	/// Must be unique among the panels [registered](DevGui::register) to a [`DevGui`].
+	#[must_use]
+	fn name(&self) -> &'static str;
+
+	/// The [`DevGui`] itself is not in `world` while this is being called.
+	fn ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, world: &mut World);
+
+	/// Called upon [`DevGui::save`].
+	fn save(&self, _: &mut PanelState) {}
+
+	/// Called upon [`DevGui::restore`], if anything was saved for this panel.
+	fn load(&mut self, _: &PanelState) {}
+}
+
+/// One of the regions of the developer GUI into which panels can be docked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DockSlot {
+	Left,
+	Bottom,
+	Central,
+}
+
+impl DockSlot {
+	pub const ALL: [Self; 3] = [Self::Left, Self::Bottom, Self::Central];
+}
+
+impl std::fmt::Display for DockSlot {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Left => write!(f, "Left"),
+			Self::Bottom => write!(f, "Bottom"),
+			Self::Central => write!(f, "Centre"),
+		}
+	}
+}
+
+/// The tabs docked into one [`DockSlot`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockNode {
+	/// Panel names, in tab order.
+	pub tabs: Vec<String>,
+	/// Index into [`Self::tabs`] of the panel currently being shown.
+	pub active: usize,
+}
+
+impl DockNode {
+	/// Returns `false` if there was no tab named `name`.
+	fn remove(&mut self, name: &str) -> bool {
+		let Some(pos) = self.tabs.iter().position(|tab| tab == name) else {
+			return false;
+		};
+
+		self.tabs.remove(pos);
+
+		if self.active > pos {
+			self.active -= 1;
+		}
+
+		self.active = self.active.min(self.tabs.len().saturating_sub(1));
+		true
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DockLayout {
+	pub left: DockNode,
+	pub bottom: DockNode,
+	pub central: DockNode,
+	/// In logical pixels.
+	pub left_width: f32,
+	/// In logical pixels.
+	pub bottom_height: f32,
+}
+
+impl Default for DockLayout {
+	fn default() -> Self {
+		Self {
+			left: DockNode::default(),
+			bottom: DockNode::default(),
+			central: DockNode::default(),
+			left_width: 400.0,
+			bottom_height: 250.0,
+		}
+	}
+}
+
+impl DockLayout {
+	#[must_use]
+	pub fn node(&self, slot: DockSlot) -> &DockNode {
+		match slot {
+			DockSlot::Left => &self.left,
+			DockSlot::Bottom => &self.bottom,
+			DockSlot::Central => &self.central,
+		}
+	}
+
+	#[must_use]
+	pub fn node_mut(&mut self, slot: DockSlot) -> &mut DockNode {
+		match slot {
+			DockSlot::Left => &mut self.left,
+			DockSlot::Bottom => &mut self.bottom,
+			DockSlot::Central => &mut self.central,
+		}
+	}
+
+	/// Returns `None` if no tab is named `name`, i.e. that panel is closed.
+	#[must_use]
+	pub fn find(&self, name: &str) -> Option<DockSlot> {
+		DockSlot::ALL
+			.into_iter()
+			.find(|slot| self.node(*slot).tabs.iter().any(|tab| tab == name))
+	}
+
+	/// Returns `false` if no tab was named `name`.
+	fn remove(&mut self, name: &str) -> bool {
+		DockSlot::ALL
+			.into_iter()
+			.any(|slot| self.node_mut(slot).remove(name))
+	}
+}
+
+/// What gets written to the [user's global config](crate::user::GlobalConfig)
+/// so that the developer GUI looks the same upon the next run.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DevGuiConfig {
+	pub layout: DockLayout,
+	/// Registered panels which were not docked anywhere. These stay closed upon
+	/// restoration, rather than getting docked into their default slot.
+	pub closed: Vec<String>,
+	pub panels: BTreeMap<String, PanelState>,
+}
+
+/// Owns every [`DevPanel`] and the [`DockLayout`] they are arranged by.
+#[derive(Resource, Default)]
+pub struct DevGui {
+	panels: Vec<Registered>,
+	layout: DockLayout,
+}
+
+struct Registered {
+	panel: Box<dyn DevPanel>,
+	/// Where this panel gets docked upon registration or re-opening.
+	home: DockSlot,
+}
+
+impl DevGui {
+	/// The panel starts open, as the last tab docked into `home`.
+	pub fn register(&mut self, panel: impl DevPanel, home: DockSlot) -> Result<(), DuplicatePanel> {
+		let name = panel.name();
+
+		if self.panels.iter().any(|reg| reg.panel.name() == name) {
+			return Err(DuplicatePanel(name));
+		}
+
+		self.panels.push(Registered {
+			panel: Box::new(panel),
+			home,
+		});
+
+		self.layout.node_mut(home).tabs.push(name.to_string());
+		Ok(())
+	}
+
+	/// Call after every panel has been [registered](Self::register).
+	/// Persisted tabs for panels which are no longer registered get dropped;
+	/// registered panels which `config` knows nothing about get docked into
+	/// their default slot.
+	pub fn restore(&mut self, config: &DevGuiConfig) {
+		let mut layout = config.layout.clone();
+		let mut seen = HashSet::new();
+
+		for slot in DockSlot::ALL {
+			let node = layout.node_mut(slot);
+
+			node.tabs.retain(|tab| {
+				self.panels.iter().any(|reg| reg.panel.name() == tab) && seen.insert(tab.clone())
+			});
+
+			node.active = node.active.min(node.tabs.len().saturating_sub(1));
+		}
+
+		for reg in &mut self.panels {
+			let name = reg.panel.name();
+
+			if layout.find(name).is_none() && !config.closed.iter().any(|c| c == name) {
+				layout.node_mut(reg.home).tabs.push(name.to_string());
+			}
+
+			if let Some(state) = config.panels.get(name) {
+				reg.panel.load(state);
+			}
+		}
+
+		self.layout = layout;
+	}
+
+	#[must_use]
+	pub fn save(&self) -> DevGuiConfig {
+		let mut panels = BTreeMap::new();
+
+		for reg in &self.panels {
+			let mut state = PanelState::new();
+			reg.panel.save(&mut state);
+
+			if !state.is_empty() {
+				panels.insert(reg.panel.name().to_string(), state);
+			}
+		}
+
+		DevGuiConfig {
+			layout: self.layout.clone(),
+			closed: self.closed().map(str::to_string).collect(),
+			panels,
+		}
+	}
+
+	#[must_use]
+	pub fn layout(&self) -> &DockLayout {
+		&self.layout
+	}
+
+	/// Docks the panel named `name` back into its default slot and shows it.
+	/// Does nothing if no such panel is registered, or if it is already open.
+	pub fn open(&mut self, name: &str) {
+		let Some(reg) = self.panels.iter().find(|reg| reg.panel.name() == name) else {
+			return;
+		};
+
+		if self.layout.find(name).is_some() {
+			return;
+		}
+
+		let node = self.layout.node_mut(reg.home);
+		node.tabs.push(name.to_string());
+		node.active = node.tabs.len() - 1;
+	}
+
+	pub fn close(&mut self, name: &str) {
+		let _ = self.layout.remove(name);
+	}
+
+	/// The names of every registered panel which is not docked anywhere.
+	pub fn closed(&self) -> impl Iterator<Item = &'static str> + '_ {
+		self.panels
+			.iter()
+			.map(|reg| reg.panel.name())
+			.filter(|name| self.layout.find(name).is_none())
+	}
+
+	/// A menu button for re-opening closed panels; put it in a menu bar.
+	pub fn ui_windows_menu(&mut self, ui: &mut egui::Ui) {
+		let mut to_open = None;
+
+		ui.menu_button("Windows", |ui| {
+			let mut any = false;
+
+			for name in self.closed() {
+				any = true;
+
+				if ui.button(name).clicked() {
+					to_open = Some(name);
+					ui.close_menu();
+				}
+			}
+
+			if !any {
+				ui.weak("All panels are open.");
+			}
+		});
+
+		if let Some(name) = to_open {
+			self.open(name);
+		}
+	}
+
+	/// Draws every docked panel. Tabs can be re-docked or closed by right-clicking them.
+	pub fn ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, world: &mut World) {
+		let mut action = None;
+
+		if !self.layout.bottom.tabs.is_empty() {
+			let resp = egui::TopBottomPanel::bottom("viletech_devgui_bottom")
+				.resizable(true)
+				.default_height(self.layout.bottom_height)
+				.show_inside(ui, |ui| {
+					self.ui_node(DockSlot::Bottom, ctx, ui, world, &mut action);
+				});
+
+			self.layout.bottom_height = resp.response.rect.height();
+		}
+
+		if !self.layout.left.tabs.is_empty() {
+			let resp = egui::SidePanel::left("viletech_devgui_left")
+				.resizable(true)
+				.default_width(self.layout.left_width)
+				.show_inside(ui, |ui| {
+					self.ui_node(DockSlot::Left, ctx, ui, world, &mut action);
+				});
+
+			self.layout.left_width = resp.response.rect.width();
+		}
+
+		egui::CentralPanel::default().show_inside(ui, |ui| {
+			self.ui_node(DockSlot::Central, ctx, ui, world, &mut action);
+		});
+
+		match action {
+			Some(TabAction::Select(slot, index)) => {
+				self.layout.node_mut(slot).active = index;
+			}
+			Some(TabAction::Move(name, to)) => {
+				self.close(&name);
+				let node = self.layout.node_mut(to);
+				node.tabs.push(name);
+				node.active = node.tabs.len() - 1;
+			}
+			Some(TabAction::Close(name)) => self.close(&name),
+			None => {}
+		}
+	}
+
+	fn ui_node(
+		&mut self,
+		slot: DockSlot,
+		ctx: &egui::Context,
+		ui: &mut egui::Ui,
+		world: &mut World,
+		action: &mut Option<TabAction>,
+	) {
+		let node = self.layout.node(slot);
+
+		ui.horizontal(|ui| {
+			for (i, name) in node.tabs.iter().enumerate() {
+				let resp = ui.selectable_label(i == node.active, name.as_str());
+
+				if resp.clicked() {
+					*action = Some(TabAction::Select(slot, i));
+				}
+
+				resp.context_menu(|ui| {
+					for to in DockSlot::ALL {
+						if to != slot && ui.button(format!("Dock {to}")).clicked() {
+							*action = Some(TabAction::Move(name.clone(), to));
+							ui.close_menu();
+						}
+					}
+
+					if ui.button("Close").clicked() {
+						*action = Some(TabAction::Close(name.clone()));
+						ui.close_menu();
+					}
+				});
+			}
+		});
+
+		ui.separator();
+
+		let Some(name) = node.tabs.get(node.active) else {
+			ui.weak("Nothing is docked here. Closed panels can be re-opened via \"Windows\".");
+			return;
+		};
+
+		let Some(reg) = self.panels.iter_mut().find(|reg| reg.panel.name() == name) else {
+			return;
+		};
+
+		ui.push_id(reg.panel.name(), |ui| {
+			reg.panel.ui(ctx, ui, world);
+		});
+	}
+}
+
+impl std::fmt::Debug for DevGui {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("DevGui")
+			.field(
+				"panels",
+				&self
+					.panels
+					.iter()
+					.map(|reg| reg.panel.name())
+					.collect::<Vec<_>>(),
+			)
+			.field("layout", &self.layout)
+			.finish()
+	}
+}
+
+#[derive(Debug)]
+enum TabAction {
+	Select(DockSlot, usize),
+	Move(String, DockSlot),
+	Close(String),
+}
+
+/// See [`DevGui::register`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuplicatePanel(pub &'static str);
+
+impl std::error::Error for DuplicatePanel {}
+
+impl std::fmt::Display for DuplicatePanel {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "a developer GUI panel named `{}` already exists", self.0)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	struct Panel {
+		name: &'static str,
+		filter: String,
+	}
+
+	impl Panel {
+		#[must_use]
+		fn new(name: &'static str) -> Self {
+			Self {
+				name,
+				filter: String::new(),
+			}
+		}
+	}
+
+	impl DevPanel for Panel {
+		fn name(&self) -> &'static str {
+			self.name
+		}
+
+		fn ui(&mut self, _: &egui::Context, _: &mut egui::Ui, _: &mut World) {}
+
+		fn save(&self, state: &mut PanelState) {
+			if !self.filter.is_empty() {
+				state.insert("filter".to_string(), self.filter.clone());
+			}
+		}
+
+		fn load(&mut self, state: &PanelState) {
+			if let Some(filter) = state.get("filter") {
+				self.filter = filter.clone();
+			}
+		}
+	}
+
+	#[test]
+	fn registry() {
+		let mut devgui = DevGui::default();
+		devgui.register(Panel::new("VFS"), DockSlot::Left).unwrap();
+		devgui
+			.register(Panel::new("Console"), DockSlot::Bottom)
+			.unwrap();
+		devgui
+			.register(Panel::new("Audio"), DockSlot::Central)
+			.unwrap();
+
+		assert_eq!(
+			devgui.register(Panel::new("VFS"), DockSlot::Central),
+			Err(DuplicatePanel("VFS"))
+		);
+
+		assert_eq!(devgui.layout().left.tabs, ["VFS"]);
+		assert_eq!(devgui.layout().find("Console"), Some(DockSlot::Bottom));
+
+		devgui.close("Audio");
+		assert_eq!(devgui.closed().collect::<Vec<_>>(), ["Audio"]);
+		devgui.open("Audio");
+		assert_eq!(devgui.layout().find("Audio"), Some(DockSlot::Central));
+
+		let mut config = devgui.save();
+		config.layout.central.tabs.push("Debugger".to_string());
+		config.layout.left.tabs.push("Console".to_string());
+		config.layout.left.active = 1;
+		config.layout.bottom.tabs.clear();
+		config.closed.push("Audio".to_string());
+		config.layout.central.tabs.retain(|tab| tab != "Audio");
+		config.panels.insert(
+			"Console".to_string(),
+			PanelState::from([("filter".to_string(), "warn".to_string())]),
+		);
+
+		let mut devgui = DevGui::default();
+		devgui.register(Panel::new("VFS"), DockSlot::Left).unwrap();
+		devgui
+			.register(Panel::new("Console"), DockSlot::Bottom)
+			.unwrap();
+		devgui
+			.register(Panel::new("Audio"), DockSlot::Central)
+			.unwrap();
+		devgui
+			.register(Panel::new("Assets"), DockSlot::Left)
+			.unwrap();
+		devgui.restore(&config);
+
+		let layout = devgui.layout();
+		// Unknown persisted tabs get dropped.
+		assert!(layout.central.tabs.is_empty());
+		// Persisted re-docking is respected.
+		assert_eq!(layout.left.tabs, ["VFS", "Console", "Assets"]);
+		assert_eq!(layout.left.active, 1);
+		assert!(layout.bottom.tabs.is_empty());
+		// Persisted closure is respected.
+		assert_eq!(layout.find("Audio"), None);
+
+		let saved = devgui.save();
+		assert_eq!(saved.panels["Console"]["filter"], "warn");
+		assert_eq!(saved.closed, ["Audio"]);
+	}
+
+	#[test]
+	fn config_roundtrip() {
+		let mut devgui = DevGui::default();
+		devgui.register(Panel::new("VFS"), DockSlot::Left).unwrap();
+		devgui
+			.register(Panel::new("Console"), DockSlot::Bottom)
+			.unwrap();
+		devgui
+			.register(Panel::new("Audio"), DockSlot::Central)
+			.unwrap();
+		devgui.close("Audio");
+
+		let mut config = devgui.save();
+		config.layout.left_width = 320.0;
+		config.panels.insert(
+			"VFS".to_string(),
+			PanelState::from([("selection".to_string(), "/viletech/textures".to_string())]),
+		);
+
+		let text = toml::to_string_pretty(&config).unwrap();
+		let parsed: DevGuiConfig = toml::from_str(&text).unwrap();
+		assert_eq!(parsed, config);
+
+		// Older configs without a developer GUI section get the defaults.
+		let parsed: DevGuiConfig = toml::from_str("").unwrap();
+		assert_eq!(parsed, DevGuiConfig::default());
+	}
+}
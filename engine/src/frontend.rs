@@ -1,5 +1,7 @@
 //! A menu for changing settings and choosing files to load.
 
+mod iwad;
+
 use std::{
 	borrow::Cow,
 	collections::VecDeque,
@@ -10,6 +12,8 @@ use bevy::prelude::Resource;
 use bevy_egui::egui;
 use serde::{Deserialize, Serialize};
 
+pub use self::iwad::*;
+
 /// A menu for changing settings and choosing files to load.
 ///
 /// This is the first thing a client end user sees assuming they have not passed
@@ -19,21 +23,29 @@ pub struct FrontendMenu {
 	/// *Always* contains at least one element.
 	presets: VecDeque<LoadOrderPreset>,
 	cur_preset: usize,
+	iwads: IwadConfig,
 	full_paths: bool,
 	dev_mode: bool,
+	show_settings: bool,
 }
 
 impl FrontendMenu {
 	#[must_use]
-	pub fn new(presets: Option<(VecDeque<LoadOrderPreset>, usize)>, dev_mode: bool) -> Self {
+	pub fn new(
+		presets: Option<(VecDeque<LoadOrderPreset>, usize)>,
+		iwads: IwadConfig,
+		dev_mode: bool,
+	) -> Self {
 		let (presets, cur_preset) =
 			presets.unwrap_or_else(|| (VecDeque::from([LoadOrderPreset::new()]), 0));
 
 		let ret = Self {
 			presets,
 			cur_preset,
+			iwads,
 			full_paths: false,
 			dev_mode,
+			show_settings: false,
 		};
 
 		assert!(ret.cur_preset < ret.presets.len());
@@ -77,6 +89,8 @@ impl FrontendMenu {
 				egui::CentralPanel::default().show_inside(ui, |ui| {
 					ui.heading("Load Order");
 
+					self.ui_base_iwad(ui);
+
 					egui::menu::bar(ui, |ui| {
 						if ui.button("\u{2B}\u{1F4C4}").clicked() {
 							if let Some(files) = rfd::FileDialog::new()
@@ -153,13 +167,31 @@ impl FrontendMenu {
 				});
 			});
 
+		if self.show_settings {
+			let mut open = true;
+
+			egui::Window::new("Settings")
+				.id("viletech_frontend_settings".into())
+				.open(&mut open)
+				.show(ctx, |ui| {
+					self.ui_settings(ui);
+				});
+
+			self.show_settings = open;
+		}
+
 		ret
 	}
 
+	/// The base IWAD, if one is chosen, always comes first.
 	#[must_use]
 	pub fn to_mount(&mut self) -> Vec<&Path> {
 		let mut ret = Vec::<&Path>::default();
 
+		if let Some(iwad) = &self.load_order().base_iwad {
+			ret.push(iwad);
+		}
+
 		for entry in self.load_order().iter() {
 			entry.get_paths(&mut ret);
 		}
@@ -177,6 +209,22 @@ impl FrontendMenu {
 		self.dev_mode
 	}
 
+	#[must_use]
+	pub fn iwads(&self) -> &IwadConfig {
+		&self.iwads
+	}
+
+	#[must_use]
+	pub fn iwads_mut(&mut self) -> &mut IwadConfig {
+		&mut self.iwads
+	}
+
+	/// Sets the base IWAD of the current load order preset.
+	/// `path` is expected, but not required, to be [known](IwadConfig::known).
+	pub fn set_base_iwad(&mut self, path: Option<PathBuf>) {
+		self.load_order_mut().base_iwad = path;
+	}
+
 	/// Returns the current array of load order presets as well as the
 	/// index of the currently-selected one, to be serialized.
 	#[must_use]
@@ -191,7 +239,8 @@ impl FrontendMenu {
 	fn ui_menu_bar(&mut self, ui: &mut egui::Ui) -> Outcome {
 		let mut ret = Outcome::None;
 
-		let load_order_empty = self.load_order().is_empty();
+		let load_order_empty =
+			self.load_order().is_empty() && self.load_order().base_iwad.is_none();
 
 		ui.label("Start");
 
@@ -226,6 +275,7 @@ impl FrontendMenu {
 		ui.separator();
 
 		// TODO: user information management (e.g. preferences) goes here.
+		ui.toggle_value(&mut self.show_settings, "Settings");
 
 		// TODO: tooltip.
 		ui.checkbox(&mut self.dev_mode, "Developer Mode");
@@ -310,6 +360,99 @@ impl FrontendMenu {
 		}
 	}
 
+	fn ui_base_iwad(&mut self, ui: &mut egui::Ui) {
+		let show_full_paths = self.full_paths;
+		let iwads = &self.iwads;
+		let base = &mut self.presets[self.cur_preset].base_iwad;
+
+		let label = |path: &Path| {
+			let known = iwads.known().iter().find(|k| k.path == path);
+
+			let name = if show_full_paths {
+				path.to_string_lossy()
+			} else {
+				path.file_name()
+					.map_or(Cow::Borrowed("<unnamed file>"), |n| n.to_string_lossy())
+			};
+
+			match known {
+				Some(k) => format!("{} ({name})", k.game.pretty_name()),
+				None => name.into_owned(),
+			}
+		};
+
+		let selected = base.as_deref().map_or("None".to_string(), label);
+
+		ui.horizontal(|ui| {
+			ui.label("Base IWAD:");
+
+			egui::ComboBox::from_id_source("viletech_frontend_base_iwad")
+				.selected_text(selected)
+				.show_ui(ui, |ui| {
+					ui.selectable_value(base, None, "None");
+
+					for known in iwads.known() {
+						ui.selectable_value(base, Some(known.path.clone()), label(&known.path));
+					}
+				});
+
+			if iwads.known().is_empty() {
+				ui.label("No IWADs found. Add a search directory under \"Settings\".");
+			}
+		});
+	}
+
+	fn ui_settings(&mut self, ui: &mut egui::Ui) {
+		ui.heading("IWAD Search Directories");
+
+		let mut to_remove = None;
+
+		egui::Grid::new("viletech_frontend_search_dirs")
+			.num_columns(2)
+			.striped(true)
+			.show(ui, |ui| {
+				for (i, dir) in self.iwads.search_dirs.iter().enumerate() {
+					let mut text = egui::RichText::new(dir.to_string_lossy());
+
+					if !dir.exists() {
+						text = text.weak();
+					}
+
+					ui.label(text);
+
+					if ui.button("\u{2796}").clicked() {
+						to_remove = Some(i);
+					}
+
+					ui.end_row();
+				}
+			});
+
+		if let Some(i) = to_remove {
+			self.iwads.search_dirs.remove(i);
+		}
+
+		ui.horizontal(|ui| {
+			if ui.button("\u{2B}\u{1F4C1}").clicked() {
+				if let Some(dirs) = rfd::FileDialog::new().pick_folders() {
+					self.iwads.search_dirs.extend(dirs);
+					self.iwads.rescan();
+				}
+			}
+
+			if ui.button("Rescan").clicked() {
+				self.iwads.rescan();
+			}
+
+			if ui.button("Restore Defaults").clicked() {
+				self.iwads.search_dirs = default_search_dirs();
+				self.iwads.rescan();
+			}
+		});
+
+		ui.label(format!("{} IWAD(s) known.", self.iwads.known().len()));
+	}
+
 	fn ui_presets(&mut self, ui: &mut egui::Ui) {
 		for (_, preset) in self.presets.iter_mut().enumerate() {
 			ui.label(&preset.name);
@@ -359,6 +502,9 @@ pub enum Outcome {
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct LoadOrderPreset {
 	name: String,
+	/// Gets mounted before all of [`Self::entries`].
+	#[serde(default)]
+	pub base_iwad: Option<PathBuf>,
 	entries: VecDeque<LoadOrderEntry>,
 }
 
@@ -367,6 +513,7 @@ impl LoadOrderPreset {
 	pub fn new() -> Self {
 		LoadOrderPreset {
 			name: "Default".to_string(), // TODO: Localize this.
+			base_iwad: None,
 			entries: VecDeque::default(),
 		}
 	}
@@ -0,0 +1,492 @@
+//! Finding and identifying IWADs on the user's machine.
+//!
+//! The user configures a list of directories to search (defaulting to the
+//! places where package managers and storefronts conventionally put IWADs),
+//! which get walked to a bounded depth. Every WAD found is read just far enough
+//! to identify what game it's for, and the result is cached alongside the file's
+//! modification time so that subsequent scans only need to stat each file.
+
+use std::{
+	io::Cursor,
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use sha3::Digest;
+use tracing::{debug, warn};
+use wadload::{DirReader, WadKind};
+
+/// Which game an IWAD is the basis for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum GameId {
+	DoomShareware,
+	Doom,
+	DoomUltimate,
+	Doom2,
+	Plutonia,
+	Tnt,
+	FreeDoom1,
+	FreeDoom2,
+	FreeDm,
+	Heretic,
+	Hexen,
+	Strife,
+	Chex,
+	HacX,
+}
+
+impl GameId {
+	/// Lump names which must all be present in an IWAD to identify its game, in
+	/// the order they get checked. Everything else is checked before the Doom
+	/// family, since most other games also have a lump like `MAP01` or `E1M1`.
+	/// Much like GZDoom's
+	/// [`IWADINFO`](https://zdoom.org/wiki/IWADINFO) `MustContain` property.
+	const FINGERPRINTS: &'static [(Self, &'static [&'static str])] = &[
+		(Self::FreeDm, &["FREEDM"]),
+		(Self::FreeDoom1, &["FREEDOOM", "E1M1"]),
+		(Self::FreeDoom2, &["FREEDOOM", "MAP01"]),
+		(Self::HacX, &["MAP01", "HACX-R"]),
+		(Self::Chex, &["E1M1", "W94_1", "POSSH0M0"]),
+		(Self::Hexen, &["TITLE", "MAP01", "WINNOWR"]),
+		(Self::Strife, &["MAP01", "ENDSTRF"]),
+		(Self::Heretic, &["E1M1", "TITLE", "MUS_E1M1"]),
+		(Self::Plutonia, &["MAP01", "CAMO1"]),
+		(Self::Tnt, &["MAP01", "REDTNT2"]),
+		(Self::Doom2, &["MAP01"]),
+		(Self::DoomUltimate, &["E4M1"]),
+		(Self::Doom, &["E2M1"]),
+		(Self::DoomShareware, &["E1M1"]),
+	];
+
+	/// Returns `None` if `lumps` fits no known game.
+	#[must_use]
+	pub fn identify<'n>(lumps: impl IntoIterator<Item = &'n str>) -> Option<Self> {
+		let lumps = lumps
+			.into_iter()
+			.map(|name| name.to_ascii_uppercase())
+			.collect::<std::collections::HashSet<_>>();
+
+		Self::FINGERPRINTS
+			.iter()
+			.find(|(_, must)| must.iter().all(|name| lumps.contains(*name)))
+			.map(|(game, _)| *game)
+	}
+
+	#[must_use]
+	pub fn pretty_name(self) -> &'static str {
+		match self {
+			Self::DoomShareware => "DOOM Shareware",
+			Self::Doom => "DOOM",
+			Self::DoomUltimate => "The Ultimate DOOM",
+			Self::Doom2 => "DOOM II: Hell on Earth",
+			Self::Plutonia => "Final DOOM: The Plutonia Experiment",
+			Self::Tnt => "Final DOOM: TNT - Evilution",
+			Self::FreeDoom1 => "Freedoom: Phase 1",
+			Self::FreeDoom2 => "Freedoom: Phase 2",
+			Self::FreeDm => "FreeDM",
+			Self::Heretic => "Heretic",
+			Self::Hexen => "Hexen",
+			Self::Strife => "Strife",
+			Self::Chex => "Chex Quest",
+			Self::HacX => "HacX",
+		}
+	}
+}
+
+/// An IWAD found by a scan or added by the user, with its identification cached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownIwad {
+	pub path: PathBuf,
+	pub game: GameId,
+	/// Hexadecimal SHA3-256 digest of the entire file.
+	pub checksum: String,
+	/// If the file's modification time no longer matches this,
+	/// it gets identified again on the next scan.
+	pub mtime: SystemTime,
+}
+
+/// The persistent half of IWAD discovery; lives in the global config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IwadConfig {
+	#[serde(default = "default_search_dirs")]
+	pub search_dirs: Vec<PathBuf>,
+	#[serde(default)]
+	known: Vec<KnownIwad>,
+}
+
+impl Default for IwadConfig {
+	fn default() -> Self {
+		Self {
+			search_dirs: default_search_dirs(),
+			known: vec![],
+		}
+	}
+}
+
+impl IwadConfig {
+	/// Directories are not walked any deeper than this below each search directory.
+	pub const MAX_DEPTH: usize = 3;
+	/// Anything smaller can not even hold a WAD header.
+	pub const MIN_SIZE: u64 = 12;
+	/// The largest commercial IWADs are a few dozen megabytes.
+	pub const MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+	/// Sorted by game, then path.
+	#[must_use]
+	pub fn known(&self) -> &[KnownIwad] {
+		&self.known
+	}
+
+	/// Re-identifies every cached IWAD whose file has changed since it was last
+	/// identified, forgets those which have been removed or no longer identify,
+	/// and then walks every search directory for IWADs not yet known.
+	///
+	/// Files added via [`Self::add_file`] are retained even if they are not
+	/// under any search directory.
+	pub fn rescan(&mut self) {
+		let mut known = Vec::with_capacity(self.known.len());
+
+		for iwad in self.known.drain(..) {
+			let Ok(mtime) = std::fs::metadata(&iwad.path).and_then(|m| m.modified()) else {
+				debug!("Forgetting IWAD `{}`; it is gone.", iwad.path.display());
+				continue;
+			};
+
+			if mtime == iwad.mtime {
+				known.push(iwad);
+				continue;
+			}
+
+			match fingerprint(&iwad.path) {
+				Ok(k) => known.push(k),
+				Err(err) => debug!("Forgetting IWAD `{}`: {err}", iwad.path.display()),
+			}
+		}
+
+		for dir in &self.search_dirs {
+			let walker = walkdir::WalkDir::new(dir)
+				.max_depth(Self::MAX_DEPTH)
+				.follow_links(false)
+				.into_iter();
+
+			for entry in walker {
+				let entry = match entry {
+					Ok(e) => e,
+					Err(err) => {
+						// Most search directories are not expected to exist.
+						if err.depth() > 0 {
+							warn!("IWAD scan error under `{}`: {err}", dir.display());
+						}
+
+						continue;
+					}
+				};
+
+				if !is_candidate(&entry) || known.iter().any(|k| k.path == entry.path()) {
+					continue;
+				}
+
+				if let Ok(k) = fingerprint(entry.path()) {
+					known.push(k);
+				}
+			}
+		}
+
+		self.known = known;
+		self.sort();
+	}
+
+	/// For when the user browses to an IWAD directly rather than adding
+	/// a directory containing it. Re-identifies the file if it was already known.
+	pub fn add_file(&mut self, path: impl AsRef<Path>) -> Result<&KnownIwad, IwadError> {
+		let new = fingerprint(path.as_ref())?;
+		let path = new.path.clone();
+
+		self.known.retain(|k| k.path != path);
+		self.known.push(new);
+		self.sort();
+
+		Ok(self.known.iter().find(|k| k.path == path).unwrap())
+	}
+
+	fn sort(&mut self) {
+		self.known
+			.sort_by(|a, b| a.game.cmp(&b.game).then_with(|| a.path.cmp(&b.path)));
+	}
+}
+
+/// Things that can go wrong when trying to identify a single IWAD.
+#[derive(Debug)]
+pub enum IwadError {
+	Io {
+		source: std::io::Error,
+		path: PathBuf,
+	},
+	Wad {
+		source: wadload::Error,
+		path: PathBuf,
+	},
+	/// The file is a valid WAD, but a PWAD.
+	NotIwad(PathBuf),
+	/// The file is a valid IWAD, but it does not match any known game.
+	Unrecognized(PathBuf),
+}
+
+impl std::error::Error for IwadError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Io { source, .. } => Some(source),
+			Self::Wad { source, .. } => Some(source),
+			Self::NotIwad(_) | Self::Unrecognized(_) => None,
+		}
+	}
+}
+
+impl std::fmt::Display for IwadError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io { source, path } => {
+				write!(f, "failed to read `{}`: {source}", path.display())
+			}
+			Self::Wad { source, path } => {
+				write!(f, "`{}` is not a valid WAD: {source}", path.display())
+			}
+			Self::NotIwad(path) => write!(f, "`{}` is a PWAD, not an IWAD", path.display()),
+			Self::Unrecognized(path) => {
+				write!(f, "`{}` is not an IWAD for any known game", path.display())
+			}
+		}
+	}
+}
+
+/// The platform-conventional places for IWADs to be installed.
+/// Whether any of them exist is not considered.
+#[must_use]
+pub fn default_search_dirs() -> Vec<PathBuf> {
+	match std::env::consts::OS {
+		"linux" => {
+			let mut ret = vec![];
+
+			let data_home = std::env::var_os("XDG_DATA_HOME")
+				.map(PathBuf::from)
+				.or_else(|| home::home_dir().map(|h| h.join(".local/share")));
+
+			if let Some(data_home) = data_home {
+				ret.push(data_home.join("games/doom"));
+			}
+
+			let data_dirs = std::env::var("XDG_DATA_DIRS")
+				.unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+
+			for dir in data_dirs.split(':').filter(|d| !d.is_empty()) {
+				ret.push(Path::new(dir).join("games/doom"));
+			}
+
+			if !ret.iter().any(|p| p == Path::new("/usr/share/games/doom")) {
+				ret.push(PathBuf::from("/usr/share/games/doom"));
+			}
+
+			ret
+		}
+		"windows" => {
+			const STEAM: &str = r"C:\Program Files (x86)\Steam\steamapps\common";
+
+			let mut ret = [
+				"Ultimate Doom",
+				"Doom 2",
+				"Final Doom",
+				"Heretic Shadow of the Serpent Riders",
+				"Hexen",
+				"Strife",
+			]
+			.iter()
+			.map(|game| Path::new(STEAM).join(game))
+			.collect::<Vec<_>>();
+
+			ret.push(PathBuf::from(r"C:\GOG Games"));
+			ret.push(PathBuf::from(r"C:\Program Files (x86)\GOG Galaxy\Games"));
+			ret
+		}
+		_ => vec![],
+	}
+}
+
+// Details /////////////////////////////////////////////////////////////////////
+
+#[must_use]
+fn is_candidate(entry: &walkdir::DirEntry) -> bool {
+	if !entry.file_type().is_file() {
+		return false;
+	}
+
+	let ext_ok = entry
+		.path()
+		.extension()
+		.is_some_and(|ext| ext.eq_ignore_ascii_case("wad") || ext.eq_ignore_ascii_case("iwad"));
+
+	ext_ok
+		&& entry
+			.metadata()
+			.is_ok_and(|m| (IwadConfig::MIN_SIZE..=IwadConfig::MAX_SIZE).contains(&m.len()))
+}
+
+fn fingerprint(path: &Path) -> Result<KnownIwad, IwadError> {
+	let io_err = |source| IwadError::Io {
+		source,
+		path: path.to_path_buf(),
+	};
+
+	let wad_err = |source| IwadError::Wad {
+		source,
+		path: path.to_path_buf(),
+	};
+
+	let mtime = std::fs::metadata(path)
+		.and_then(|m| m.modified())
+		.map_err(io_err)?;
+	let bytes = std::fs::read(path).map_err(io_err)?;
+	let dir = DirReader::new(Cursor::new(&bytes)).map_err(wad_err)?;
+
+	if dir.wad_kind() != WadKind::IWad {
+		return Err(IwadError::NotIwad(path.to_path_buf()));
+	}
+
+	let names = dir
+		.map(|entry| entry.map(|e| e.name))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(wad_err)?;
+
+	let game = GameId::identify(names.iter().map(|n| n.as_str()))
+		.ok_or_else(|| IwadError::Unrecognized(path.to_path_buf()))?;
+
+	let checksum = sha3::Sha3_256::digest(&bytes)
+		.iter()
+		.map(|byte| format!("{byte:02x}"))
+		.collect();
+
+	Ok(KnownIwad {
+		path: path.to_path_buf(),
+		game,
+		checksum,
+		mtime,
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use std::io::Write;
+
+	use super::*;
+
+	/// Makes a WAD with zero-length lumps named `lumps`.
+	fn fake_wad(path: &Path, magic: &[u8; 4], lumps: &[&str]) {
+		let mut bytes = vec![];
+		bytes.extend_from_slice(magic);
+		bytes.extend_from_slice(&(lumps.len() as i32).to_le_bytes());
+		bytes.extend_from_slice(&12_i32.to_le_bytes());
+
+		for lump in lumps {
+			let mut name = [0; 8];
+			name[..lump.len()].copy_from_slice(lump.as_bytes());
+			bytes.extend_from_slice(&12_i32.to_le_bytes());
+			bytes.extend_from_slice(&0_i32.to_le_bytes());
+			bytes.extend_from_slice(&name);
+		}
+
+		let mut file = std::fs::File::create(path).unwrap();
+		file.write_all(&bytes).unwrap();
+	}
+
+	fn tempdir(name: &str) -> PathBuf {
+		let ret = std::env::temp_dir().join(format!(
+			"viletech_iwad_{name}_{pid}",
+			pid = std::process::id()
+		));
+
+		let _ = std::fs::remove_dir_all(&ret);
+		std::fs::create_dir_all(&ret).unwrap();
+		ret
+	}
+
+	#[test]
+	fn identify() {
+		assert_eq!(GameId::identify(["MAP01", "CAMO1"]), Some(GameId::Plutonia));
+		assert_eq!(
+			GameId::identify(["e1m1", "e4m1"]),
+			Some(GameId::DoomUltimate)
+		);
+		assert_eq!(
+			GameId::identify(["FREEDOOM", "MAP01"]),
+			Some(GameId::FreeDoom2)
+		);
+		assert_eq!(
+			GameId::identify(["TITLE", "MAP01", "WINNOWR"]),
+			Some(GameId::Hexen)
+		);
+		assert_eq!(GameId::identify(["PLAYPAL", "COLORMAP"]), None);
+	}
+
+	#[test]
+	fn scan() {
+		let root = tempdir("scan");
+		let nested = root.join("a/b");
+		let too_deep = root.join("a/b/c/d");
+		std::fs::create_dir_all(&too_deep).unwrap();
+
+		fake_wad(&root.join("DOOM2.WAD"), b"IWAD", &["PLAYPAL", "MAP01"]);
+		fake_wad(
+			&nested.join("heretic.wad"),
+			b"IWAD",
+			&["E1M1", "TITLE", "MUS_E1M1"],
+		);
+		fake_wad(&root.join("mod.wad"), b"PWAD", &["MAP01"]);
+		fake_wad(&root.join("unknown.wad"), b"IWAD", &["PLAYPAL"]);
+		fake_wad(&root.join("doom2.txt"), b"IWAD", &["MAP01"]);
+		fake_wad(&too_deep.join("tnt.wad"), b"IWAD", &["MAP01", "REDTNT2"]);
+
+		let mut config = IwadConfig {
+			search_dirs: vec![root.clone(), root.join("nonexistent")],
+			known: vec![],
+		};
+
+		config.rescan();
+
+		let games = config.known().iter().map(|k| k.game).collect::<Vec<_>>();
+		assert_eq!(games, [GameId::Doom2, GameId::Heretic]);
+		assert_eq!(config.known()[0].checksum.len(), 64);
+
+		// A file changing on disk invalidates its cache entry...
+		let doom2 = config.known()[0].clone();
+		fake_wad(&doom2.path, b"IWAD", &["MAP01", "CAMO1"]);
+		let later = doom2.mtime + std::time::Duration::from_secs(10);
+		std::fs::File::options()
+			.write(true)
+			.open(&doom2.path)
+			.unwrap()
+			.set_modified(later)
+			.unwrap();
+
+		// ...and so does removing it.
+		std::fs::remove_file(nested.join("heretic.wad")).unwrap();
+
+		config.rescan();
+		assert_eq!(config.known().len(), 1);
+		assert_eq!(config.known()[0].game, GameId::Plutonia);
+		assert_ne!(config.known()[0].checksum, doom2.checksum);
+
+		// Files outside of the search directories can be added by hand.
+		config.search_dirs.clear();
+		let added = config.add_file(too_deep.join("tnt.wad")).unwrap();
+		assert_eq!(added.game, GameId::Tnt);
+		assert!(matches!(
+			config.add_file(root.join("mod.wad")),
+			Err(IwadError::NotIwad(_))
+		));
+
+		config.rescan();
+		assert_eq!(config.known().len(), 2);
+
+		std::fs::remove_dir_all(&root).unwrap();
+	}
+}
@@ -1,5 +1,8 @@
 //! Graphics-related symbols.
 
+pub mod hud;
+pub mod style;
+
 use std::num::NonZeroU32;
 
 use bevy::{
@@ -12,7 +15,7 @@ use bevy::{
 		render_resource::{
 			AsBindGroup, AsBindGroupError, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntry,
 			BindingType, PreparedBindGroup, RenderPipelineDescriptor, SamplerBindingType,
-			ShaderRef, ShaderStages, SpecializedMeshPipelineError, TextureSampleType,
+			ShaderDefVal, ShaderRef, ShaderStages, SpecializedMeshPipelineError, TextureSampleType,
 			TextureViewDimension, UnpreparedBindGroup, VertexFormat,
 		},
 		renderer::RenderDevice,
@@ -23,6 +26,8 @@ use vfs::FileSlot;
 
 use crate::types::FxIndexSet;
 
+use self::style::{RenderPass, StyleKey};
+
 pub const ATTRIBUTE_TEXINDEX: MeshVertexAttribute =
 	MeshVertexAttribute::new("TexIndex", 666_451, VertexFormat::Uint32);
 
@@ -52,6 +57,8 @@ pub struct ImageSlot {
 pub struct TerrainMaterial {
 	pub set: FxIndexSet<ImageSlot>,
 	pub textures: Vec<Handle<Image>>,
+	/// Each distinct style needs its own material, since it selects a shader variant.
+	pub style: StyleKey,
 }
 
 impl TerrainMaterial {
@@ -67,11 +74,15 @@ impl Material for TerrainMaterial {
 		"/home/jerome/Data/viletech/assets/viletech/shaders/terrain.wgsl".into()
 	}
 
+	fn alpha_mode(&self) -> AlphaMode {
+		self.style.alpha_mode()
+	}
+
 	fn specialize(
 		_pipeline: &MaterialPipeline<Self>,
 		descriptor: &mut RenderPipelineDescriptor,
 		layout: &MeshVertexBufferLayout,
-		_key: MaterialPipelineKey<Self>,
+		key: MaterialPipelineKey<Self>,
 	) -> Result<(), SpecializedMeshPipelineError> {
 		let vertex_layout = layout.get_layout(&[
 			Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
@@ -81,12 +92,19 @@ impl Material for TerrainMaterial {
 		])?;
 
 		descriptor.vertex.buffers = vec![vertex_layout];
+
+		if let Some(fragment) = descriptor.fragment.as_mut() {
+			fragment
+				.shader_defs
+				.extend(style_shader_defs(key.bind_group_data));
+		}
+
 		Ok(())
 	}
 }
 
 impl AsBindGroup for TerrainMaterial {
-	type Data = ();
+	type Data = StyleKey;
 
 	fn as_bind_group(
 		&self,
@@ -121,7 +139,7 @@ impl AsBindGroup for TerrainMaterial {
 		Ok(PreparedBindGroup {
 			bindings: vec![],
 			bind_group,
-			data: (),
+			data: self.style,
 		})
 	}
 
@@ -169,6 +187,36 @@ impl AsBindGroup for TerrainMaterial {
 	}
 }
 
+/// Shader definitions consumed by `terrain.wgsl`:
+/// - `STYLE_ALPHA`: opacity out of 255. Absent if fully opaque.
+/// - `STYLE_ADDITIVE`: output is premultiplied with zero alpha, so that the
+/// premultiplied blend state used by [`AlphaMode::Add`] becomes additive.
+/// - `STYLE_FUZZ`: see [`style::RenderStyle::Shadow`].
+/// - `STYLE_STENCIL`: a color packed as `0xRRGGBB`.
+#[must_use]
+fn style_shader_defs(key: StyleKey) -> Vec<ShaderDefVal> {
+	let mut ret = vec![];
+
+	if key.alpha != u8::MAX {
+		ret.push(ShaderDefVal::UInt("STYLE_ALPHA".into(), key.alpha as u32));
+	}
+
+	if key.pass == RenderPass::Additive {
+		ret.push("STYLE_ADDITIVE".into());
+	}
+
+	if key.fuzz {
+		ret.push("STYLE_FUZZ".into());
+	}
+
+	if let Some([r, g, b]) = key.stencil {
+		let packed = ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
+		ret.push(ShaderDefVal::UInt("STYLE_STENCIL".into(), packed));
+	}
+
+	ret
+}
+
 #[derive(AsBindGroup, Reflect, Asset, Debug, Clone, TypeUuid)]
 #[uuid = "8754faf6-ee9a-11ed-a05b-0242ac120003"]
 pub struct Sky2dMaterial {
@@ -0,0 +1,223 @@
+//! Drawing over the 3D view, in terms of Doom's 320x200 virtual screen.
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{actor::Blueprint, sim::weapon::WeaponSlot};
+
+/// The size of the screen which vanilla's HUD and view sprites were laid out on.
+pub const VIRTUAL_SIZE: Vec2 = Vec2::new(320.0, 200.0);
+
+/// Vanilla's `WEAPONTOP`: the Y coordinate of a raised view sprite,
+/// before picture offsets and bob.
+pub const WEAPON_TOP: f32 = 32.0;
+
+/// How long a [`HudMessage`] stays on screen.
+pub const MESSAGE_DURATION: Duration = Duration::from_secs(3);
+/// The last part of [`MESSAGE_DURATION`], over which a message fades out.
+pub const MESSAGE_FADE: Duration = Duration::from_secs(1);
+
+/// A "flag" component for the actor whose HUD gets drawn,
+/// i.e. the one the local player is seeing through.
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct HudOwner;
+
+/// Maps the virtual screen onto a real one, scaled uniformly to fit and centered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HudScale {
+	pub scale: f32,
+	/// The real position of the virtual screen's top-left corner.
+	pub origin: Vec2,
+}
+
+impl HudScale {
+	#[must_use]
+	pub fn new(screen: Vec2) -> Self {
+		let scale = (screen.x / VIRTUAL_SIZE.x).min(screen.y / VIRTUAL_SIZE.y);
+
+		Self {
+			scale,
+			origin: (screen - VIRTUAL_SIZE * scale) / 2.0,
+		}
+	}
+
+	#[must_use]
+	pub fn to_screen(self, rect: Rect) -> Rect {
+		Rect::from_corners(
+			self.origin + rect.min * self.scale,
+			self.origin + rect.max * self.scale,
+		)
+	}
+}
+
+/// Where on the virtual screen a view sprite frame of `size` gets drawn.
+/// `offset` is the frame's (left, top) [offset](crate::actor::state::Frame::offset);
+/// `bob` comes from [`WeaponSlot::bob`].
+#[must_use]
+pub fn view_sprite_rect(size: Vec2, offset: Vec2, bob: Vec2) -> Rect {
+	let min = Vec2::new(1.0, WEAPON_TOP) + bob - offset;
+	Rect::from_corners(min, min + size)
+}
+
+/// Draws the [`HudOwner`]'s weapon and its flash, if any.
+pub fn draw_view_sprites(
+	mut egui: EguiContexts,
+	blueprints: Res<Assets<Blueprint>>,
+	images: Res<Assets<Image>>,
+	owners: Query<&WeaponSlot, With<HudOwner>>,
+) {
+	let Ok(slot) = owners.get_single() else {
+		return;
+	};
+
+	let Some(weapon) = blueprints.get(&slot.weapon) else {
+		return;
+	};
+
+	let layers = [Some(slot.psprite), slot.flash];
+	let mut sprites = vec![];
+
+	for fsm in layers.into_iter().flatten() {
+		let Some(frame) = weapon.states.frame(fsm.state) else {
+			continue;
+		};
+
+		let Some(image) = images.get(&frame.image) else {
+			continue;
+		};
+
+		let size = image.texture_descriptor.size;
+		let size = Vec2::new(size.width as f32, size.height as f32);
+		let tex_id = egui.add_image(frame.image.clone_weak());
+		sprites.push((tex_id, view_sprite_rect(size, frame.offset, slot.bob)));
+	}
+
+	let ctx = egui.ctx_mut();
+	let screen = ctx.screen_rect();
+	let hud = HudScale::new(Vec2::new(screen.width(), screen.height()));
+
+	let painter = ctx.layer_painter(egui::LayerId::new(
+		egui::Order::Background,
+		egui::Id::new("viletech_hud"),
+	));
+
+	for (tex_id, rect) in sprites {
+		let rect = hud.to_screen(rect);
+
+		painter.image(
+			tex_id,
+			egui::Rect::from_min_max(
+				egui::pos2(rect.min.x, rect.min.y),
+				egui::pos2(rect.max.x, rect.max.y),
+			),
+			egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+			egui::Color32::WHITE,
+		);
+	}
+}
+
+/// A short notification (e.g. "Screenshot saved") for the top-left corner
+/// of the screen. See [`draw_messages`].
+#[derive(Event, Debug, Clone)]
+pub struct HudMessage(pub String);
+
+/// The [`HudMessage`]s currently on screen, oldest first,
+/// each paired with the [elapsed time](Time::elapsed) at which it arrived.
+#[derive(Resource, Debug, Default)]
+pub struct HudMessages(VecDeque<(String, Duration)>);
+
+/// Returns `None` once a message of this `age` should no longer be drawn.
+#[must_use]
+pub fn message_alpha(age: Duration) -> Option<f32> {
+	if age >= MESSAGE_DURATION {
+		return None;
+	}
+
+	let fade_start = MESSAGE_DURATION - MESSAGE_FADE;
+
+	if age <= fade_start {
+		return Some(1.0);
+	}
+
+	Some(1.0 - (age - fade_start).as_secs_f32() / MESSAGE_FADE.as_secs_f32())
+}
+
+/// Collects new [`HudMessage`]s, drops expired ones, and draws the rest.
+pub fn draw_messages(
+	mut egui: EguiContexts,
+	time: Res<Time>,
+	mut events: EventReader<HudMessage>,
+	mut messages: ResMut<HudMessages>,
+) {
+	let now = time.elapsed();
+
+	for msg in events.read() {
+		messages.0.push_back((msg.0.clone(), now));
+	}
+
+	messages
+		.0
+		.retain(|(_, since)| message_alpha(now.saturating_sub(*since)).is_some());
+
+	if messages.0.is_empty() {
+		return;
+	}
+
+	let ctx = egui.ctx_mut();
+
+	let painter = ctx.layer_painter(egui::LayerId::new(
+		egui::Order::Foreground,
+		egui::Id::new("viletech_hud_messages"),
+	));
+
+	let font = egui::FontId::proportional(16.0);
+	let mut pos = egui::pos2(8.0, 8.0);
+
+	for (text, since) in &messages.0 {
+		let Some(alpha) = message_alpha(now.saturating_sub(*since)) else {
+			continue;
+		};
+
+		let color = egui::Color32::WHITE.gamma_multiply(alpha);
+		let rect = painter.text(pos, egui::Align2::LEFT_TOP, text, font.clone(), color);
+		pos.y = rect.max.y + 2.0;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn scaling() {
+		let hud = HudScale::new(Vec2::new(1920.0, 1080.0));
+		assert_eq!(hud.scale, 5.4);
+		assert_eq!(hud.origin, Vec2::new(96.0, 0.0));
+
+		let full = Rect::from_corners(Vec2::ZERO, VIRTUAL_SIZE);
+		let real = hud.to_screen(full);
+		assert_eq!(real.min, Vec2::new(96.0, 0.0));
+		assert_eq!(real.max, Vec2::new(1824.0, 1080.0));
+
+		// A 57x62 frame, offset so that it sits on the bottom edge of the screen.
+		let size = Vec2::new(57.0, 62.0);
+		let offset = Vec2::new(-122.0, -106.0);
+		let rect = view_sprite_rect(size, offset, Vec2::ZERO);
+		assert_eq!(rect.min, Vec2::new(123.0, 138.0));
+		assert_eq!(rect.max, Vec2::new(180.0, 200.0));
+
+		let rect = view_sprite_rect(size, offset, Vec2::new(-16.0, 8.0));
+		assert_eq!(rect.min, Vec2::new(107.0, 146.0));
+	}
+
+	#[test]
+	fn message_fade() {
+		assert_eq!(message_alpha(Duration::ZERO), Some(1.0));
+		assert_eq!(message_alpha(Duration::from_secs(2)), Some(1.0));
+		assert_eq!(message_alpha(Duration::from_millis(2500)), Some(0.5));
+		assert_eq!(message_alpha(MESSAGE_DURATION), None);
+	}
+}
@@ -0,0 +1,312 @@
+//! How sprites and wall surfaces blend with what is drawn behind them.
+//!
+//! Opaque geometry (including [`RenderStyle::Shadow`] and [`RenderStyle::Stencil`],
+//! which never blend) stays in the depth-tested opaque pass. Everything else
+//! gets drawn afterwards, sorted [back to front](sort_back_to_front).
+
+use bevy::prelude::*;
+use data::level::read::LineFlags;
+
+/// Attached to actors (from [`Blueprint::render_style`]) and to linedefs,
+/// in which case it applies to their middle textures.
+///
+/// [`Blueprint::render_style`]: crate::actor::Blueprint::render_style
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub enum RenderStyle {
+	#[default]
+	Normal,
+	/// Alpha-blended. The value is an opacity in the range `[0.0, 1.0]`.
+	Translucent(f32),
+	/// Source color, scaled by the given opacity, is added to the destination.
+	Additive(f32),
+	/// The spectre's "fuzz" effect. Implemented as dithered alpha: every other
+	/// texel in a screen-space checkerboard is discarded and the rest are drawn
+	/// near-black. This needs no sorting, since surviving texels write depth.
+	Shadow,
+	/// Every texel which is not fully transparent is drawn in the given color.
+	Stencil(Color),
+}
+
+/// See [`RenderStyle::pass`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RenderPass {
+	Opaque,
+	Blended,
+	Additive,
+}
+
+impl RenderStyle {
+	/// Strife's [`LineFlags::TRANSLUCENT`] draws a middle texture at this opacity.
+	pub const STRIFE_LINE_ALPHA: f32 = 0.75;
+
+	/// Which pass a surface with this style gets drawn in.
+	/// A fully-opaque translucent surface is drawn as though it were [`Self::Normal`].
+	#[must_use]
+	pub fn pass(self) -> RenderPass {
+		match self {
+			Self::Normal | Self::Shadow | Self::Stencil(_) => RenderPass::Opaque,
+			Self::Translucent(alpha) if alpha >= 1.0 => RenderPass::Opaque,
+			Self::Translucent(_) => RenderPass::Blended,
+			Self::Additive(_) => RenderPass::Additive,
+		}
+	}
+
+	/// `alpha` and `style` are a UDMF linedef's `alpha` and `renderstyle` fields,
+	/// if present. `style` is only checked for `"add"`; anything else counts
+	/// as `"translucent"`.
+	#[must_use]
+	pub fn for_line(flags: LineFlags, alpha: Option<f32>, style: Option<&str>) -> Self {
+		let alpha = match alpha {
+			Some(a) => a.clamp(0.0, 1.0),
+			None if flags.contains(LineFlags::TRANSLUCENT) => Self::STRIFE_LINE_ALPHA,
+			None => return Self::Normal,
+		};
+
+		if style.is_some_and(|s| s.eq_ignore_ascii_case("add")) {
+			Self::Additive(alpha)
+		} else if alpha >= 1.0 {
+			Self::Normal
+		} else {
+			Self::Translucent(alpha)
+		}
+	}
+
+	#[must_use]
+	pub fn key(self) -> StyleKey {
+		let alpha = |a: f32| (a.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+		match self {
+			Self::Normal => StyleKey::default(),
+			Self::Translucent(a) => StyleKey {
+				pass: self.pass(),
+				alpha: alpha(a),
+				..Default::default()
+			},
+			Self::Additive(a) => StyleKey {
+				pass: RenderPass::Additive,
+				alpha: alpha(a),
+				..Default::default()
+			},
+			Self::Shadow => StyleKey {
+				fuzz: true,
+				..Default::default()
+			},
+			Self::Stencil(color) => {
+				let [r, g, b, _] = color.as_rgba_u8();
+
+				StyleKey {
+					stencil: Some([r, g, b]),
+					..Default::default()
+				}
+			}
+		}
+	}
+}
+
+/// A hashable reduction of [`RenderStyle`], used to select a shader variant.
+/// Opacity gets quantized to 8 bits so that it can be baked into the variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StyleKey {
+	pub pass: RenderPass,
+	pub alpha: u8,
+	pub fuzz: bool,
+	pub stencil: Option<[u8; 3]>,
+}
+
+impl Default for StyleKey {
+	fn default() -> Self {
+		Self {
+			pass: RenderPass::Opaque,
+			alpha: u8::MAX,
+			fuzz: false,
+			stencil: None,
+		}
+	}
+}
+
+impl StyleKey {
+	#[must_use]
+	pub fn alpha_mode(self) -> AlphaMode {
+		match self.pass {
+			RenderPass::Opaque if self.fuzz || self.stencil.is_some() => AlphaMode::Mask(0.5),
+			RenderPass::Opaque => AlphaMode::Opaque,
+			RenderPass::Blended => AlphaMode::Blend,
+			RenderPass::Additive => AlphaMode::Add,
+		}
+	}
+}
+
+// Sorting /////////////////////////////////////////////////////////////////////
+
+/// One sprite or middle texture to be drawn in a blended or additive pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Translucent<T> {
+	/// The position of this item's subsector in a front-to-back BSP traversal
+	/// from the camera; higher values are farther away.
+	pub subsector_rank: u32,
+	pub shape: TranslucentShape,
+	pub item: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TranslucentShape {
+	Sprite {
+		pos: Vec3,
+	},
+	/// The two ends of a linedef, at any height; only the horizontal plane is considered.
+	Wall {
+		start: Vec3,
+		end: Vec3,
+	},
+}
+
+impl TranslucentShape {
+	/// Distance is measured on the horizontal (XZ) plane. For walls, this is
+	/// the distance to the closest point on the wall's line segment.
+	#[must_use]
+	pub fn distance_sq(&self, camera: Vec3) -> f32 {
+		let camera = camera.xz();
+
+		match self {
+			Self::Sprite { pos } => pos.xz().distance_squared(camera),
+			Self::Wall { start, end } => {
+				let (a, b) = (start.xz(), end.xz());
+				let ab = b - a;
+				let len_sq = ab.length_squared();
+
+				let t = if len_sq > 0.0 {
+					((camera - a).dot(ab) / len_sq).clamp(0.0, 1.0)
+				} else {
+					0.0
+				};
+
+				(a + ab * t).distance_squared(camera)
+			}
+		}
+	}
+}
+
+/// Farther subsectors get drawn first; within one subsector, farther items get
+/// drawn first. Ties keep their original order.
+pub fn sort_back_to_front<T>(items: &mut [Translucent<T>], camera: Vec3) {
+	items.sort_by(|a, b| {
+		b.subsector_rank.cmp(&a.subsector_rank).then_with(|| {
+			b.shape
+				.distance_sq(camera)
+				.total_cmp(&a.shape.distance_sq(camera))
+		})
+	});
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn pass_assignment() {
+		assert_eq!(RenderStyle::Normal.pass(), RenderPass::Opaque);
+		assert_eq!(RenderStyle::Translucent(0.5).pass(), RenderPass::Blended);
+		assert_eq!(RenderStyle::Translucent(1.0).pass(), RenderPass::Opaque);
+		assert_eq!(RenderStyle::Additive(1.0).pass(), RenderPass::Additive);
+		assert_eq!(RenderStyle::Shadow.pass(), RenderPass::Opaque);
+		assert_eq!(RenderStyle::Stencil(Color::RED).pass(), RenderPass::Opaque);
+
+		assert_eq!(RenderStyle::Normal.key().alpha_mode(), AlphaMode::Opaque);
+		assert_eq!(
+			RenderStyle::Translucent(0.5).key().alpha_mode(),
+			AlphaMode::Blend
+		);
+		assert_eq!(
+			RenderStyle::Additive(0.5).key().alpha_mode(),
+			AlphaMode::Add
+		);
+		assert!(matches!(
+			RenderStyle::Shadow.key().alpha_mode(),
+			AlphaMode::Mask(_)
+		));
+		assert_eq!(RenderStyle::Translucent(0.5).key().alpha, 128);
+	}
+
+	#[test]
+	fn line_styles() {
+		let none = LineFlags::empty();
+		let strife = LineFlags::TRANSLUCENT;
+
+		assert_eq!(RenderStyle::for_line(none, None, None), RenderStyle::Normal);
+		assert_eq!(
+			RenderStyle::for_line(strife, None, None),
+			RenderStyle::Translucent(RenderStyle::STRIFE_LINE_ALPHA)
+		);
+		assert_eq!(
+			RenderStyle::for_line(strife, Some(0.25), None),
+			RenderStyle::Translucent(0.25)
+		);
+		assert_eq!(
+			RenderStyle::for_line(none, Some(0.5), Some("Add")),
+			RenderStyle::Additive(0.5)
+		);
+		assert_eq!(
+			RenderStyle::for_line(none, Some(1.0), Some("translucent")),
+			RenderStyle::Normal
+		);
+	}
+
+	/// A translucent wall at 5 units in front of the camera, with one sprite
+	/// behind it and another in front of it, all within one subsector.
+	#[test]
+	fn sprite_and_wall_order() {
+		let camera = Vec3::ZERO;
+
+		let mut items = [
+			Translucent {
+				subsector_rank: 0,
+				shape: TranslucentShape::Sprite {
+					pos: Vec3::new(1.0, 0.0, -3.0),
+				},
+				item: "front",
+			},
+			Translucent {
+				subsector_rank: 0,
+				shape: TranslucentShape::Wall {
+					start: Vec3::new(-10.0, 0.0, -5.0),
+					end: Vec3::new(10.0, 64.0, -5.0),
+				},
+				item: "wall",
+			},
+			Translucent {
+				subsector_rank: 0,
+				shape: TranslucentShape::Sprite {
+					pos: Vec3::new(-1.0, 0.0, -8.0),
+				},
+				item: "behind",
+			},
+		];
+
+		sort_back_to_front(&mut items, camera);
+		let order: Vec<_> = items.iter().map(|t| t.item).collect();
+		assert_eq!(order, ["behind", "wall", "front"]);
+	}
+
+	#[test]
+	fn subsector_rank_first() {
+		let mut items = [
+			Translucent {
+				subsector_rank: 1,
+				shape: TranslucentShape::Sprite {
+					pos: Vec3::new(0.0, 0.0, -2.0),
+				},
+				item: "near_but_farther_subsector",
+			},
+			Translucent {
+				subsector_rank: 0,
+				shape: TranslucentShape::Sprite {
+					pos: Vec3::new(0.0, 0.0, -9.0),
+				},
+				item: "far_but_nearer_subsector",
+			},
+		];
+
+		sort_back_to_front(&mut items, Vec3::ZERO);
+		assert_eq!(items[0].item, "near_but_farther_subsector");
+	}
+}
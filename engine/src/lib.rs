@@ -14,12 +14,15 @@ pub mod actor;
 pub mod asset;
 pub mod audio;
 pub mod basedata;
+pub mod bench;
 pub extern crate bytemuck;
 // pub mod catalog;
 pub mod console;
+pub mod crash;
 pub extern crate crossbeam;
 pub extern crate dashmap;
 pub extern crate data;
+pub mod devgui;
 pub mod frontend;
 pub mod gfx;
 pub extern crate image;
@@ -27,14 +30,18 @@ pub extern crate indexmap;
 pub extern crate kira;
 pub use data::level;
 pub extern crate lith;
+pub mod load;
 pub mod log;
 pub extern crate mus;
 pub extern crate nanorand;
+pub mod net;
 pub mod player;
 pub extern crate rayon;
 pub extern crate regex;
 pub mod rng;
 pub extern crate rustc_hash;
+pub mod save;
+pub mod script;
 pub mod sim;
 pub mod terminal;
 pub extern crate tracing;
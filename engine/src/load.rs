@@ -0,0 +1,955 @@
+//! Presentation of game loading to the user.
+//!
+//! The catalog cannot be queried until a load finishes, so anything shown during
+//! one (i.e. a mount's title screen) gets decoded on the loading thread and is
+//! handed to the client through an [`EarlyAssets`] channel as raw RGBA.
+
+use std::path::PathBuf;
+
+use bevy::prelude::Image;
+use data::{
+	gfx::{ColorMap, Palette, PictureReader},
+	level::udmf,
+};
+use doomfront::zdoom::acs;
+use kira::sound::{static_sound::StaticSoundSettings, FromFileError};
+use parking_lot::Mutex;
+use vfs::{FileRef, FolderRef, VPath, VPathBuf};
+
+use crate::{
+	asset::picture_to_image,
+	audio::{sound_from_bytes, sound_from_dmx, DmxSfxError, SoundTable},
+	world::level::{parse_textmap, Level},
+};
+
+/// Lumps checked for a splash (in descending priority) if a mount
+/// does not declare one of its own.
+pub const SPLASH_LUMPS: &[&str] = &["TITLEPIC", "INTERPIC"];
+
+/// A mount's title screen, for display while loading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Splash {
+	/// The index of the originating mount in the load order.
+	pub mount: usize,
+	pub width: u32,
+	pub height: u32,
+	/// Row-major, non-premultiplied, 8 bits per channel.
+	pub rgba: Vec<u8>,
+}
+
+impl Splash {
+	/// PNG and other common formats get tried first. Doom's picture format is
+	/// tried last, and can only be decoded if a palette and colormap are known.
+	#[must_use]
+	pub fn decode(
+		mount: usize,
+		bytes: &[u8],
+		palette: Option<(&Palette, &ColorMap)>,
+	) -> Option<Self> {
+		if let Ok(img) = image::load_from_memory(bytes) {
+			let img = img.into_rgba8();
+			let (width, height) = img.dimensions();
+
+			if width == 0 || height == 0 {
+				return None;
+			}
+
+			return Some(Self {
+				mount,
+				width,
+				height,
+				rgba: img.into_raw(),
+			});
+		}
+
+		let (palette, colormap) = palette?;
+		let reader = PictureReader::new(bytes).ok()?;
+		let width = reader.width() as u32;
+		let height = reader.height() as u32;
+
+		if width == 0 || height == 0 {
+			return None;
+		}
+
+		let mut rgba = vec![0; (width * height * 4) as usize];
+
+		// (RAT) The picture reader's "row" is what is more commonly called
+		// the X coordinate, and its "column" is the Y coordinate.
+		reader.read(palette, colormap, |x, y, pixel| {
+			let i = ((y * width + x) * 4) as usize;
+			rgba[i..(i + 4)].copy_from_slice(&[pixel.r, pixel.g, pixel.b, u8::MAX]);
+		});
+
+		Some(Self {
+			mount,
+			width,
+			height,
+			rgba,
+		})
+	}
+
+	/// The factor by which to scale this splash to fit within an area of `bounds`.
+	/// If the splash fits at its native size, this is the largest whole number
+	/// at which it still fits; otherwise it gets shrunk just enough to fit.
+	#[must_use]
+	pub fn scale_to_fit(&self, bounds: [f32; 2]) -> f32 {
+		let fit = (bounds[0] / self.width as f32).min(bounds[1] / self.height as f32);
+
+		if fit >= 1.0 {
+			fit.floor()
+		} else {
+			fit
+		}
+	}
+}
+
+/// Returns the file that a mount's splash should be decoded from, if it has one.
+///
+/// `declared` (e.g. a `splash.png` named by a VileTech mount's `meta.toml`)
+/// is resolved relative to `root` and takes priority over [`SPLASH_LUMPS`],
+/// which are matched by file prefix among the files in `root` and its `graphics`
+/// folder, so that a ZDoom mod's `graphics/titlepic.png` also counts.
+#[must_use]
+pub fn find_splash<'vfs>(root: FolderRef<'vfs>, declared: Option<&VPath>) -> Option<FileRef<'vfs>> {
+	if let Some(fref) = declared
+		.and_then(|vpath| root.lookup(vpath))
+		.and_then(|r| r.into_file())
+	{
+		return Some(fref);
+	}
+
+	let mut best: Option<(usize, FileRef)> = None;
+
+	let mut consider = |fref: FileRef<'vfs>| {
+		let Some(rank) = splash_rank(fref.name()) else {
+			return;
+		};
+
+		if best.as_ref().map_or(true, |(b, _)| rank < *b) {
+			best = Some((rank, fref));
+		}
+	};
+
+	for fref in root.files() {
+		consider(fref);
+	}
+
+	for subfolder in root.subfolders() {
+		if subfolder.name().eq_ignore_ascii_case("graphics") {
+			for fref in subfolder.files() {
+				consider(fref);
+			}
+		}
+	}
+
+	best.map(|(_, fref)| fref)
+}
+
+/// An index into [`SPLASH_LUMPS`].
+#[must_use]
+fn splash_rank(name: &VPath) -> Option<usize> {
+	let prefix = name.file_prefix()?;
+	SPLASH_LUMPS
+		.iter()
+		.position(|lump| prefix.eq_ignore_ascii_case(lump))
+}
+
+// ACS ///////////////////////////////////////////////////////////////////////
+
+/// See [`check_acs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcsSyntaxError {
+	pub path: VPathBuf,
+	/// 1-based.
+	pub line: usize,
+	pub found: String,
+	pub expected: Vec<&'static str>,
+}
+
+impl std::error::Error for AcsSyntaxError {}
+
+impl std::fmt::Display for AcsSyntaxError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"syntax error in ACS source: {} (line {}, found `{}`, expected one of: {})",
+			self.path,
+			self.line,
+			self.found,
+			self.expected.join(", ")
+		)
+	}
+}
+
+/// ACS source only gets compiled by external tools for now, so this only parses
+/// each `SCRIPTS` lump and `.acs` file under `folder` (recursively) to find its
+/// syntax errors. These should be reported as warnings and never fail a load,
+/// since the compiled `BEHAVIOR` is what the engine actually runs.
+/// Files which can not be read as UTF-8 text are skipped.
+#[must_use]
+pub fn check_acs(folder: FolderRef) -> Vec<AcsSyntaxError> {
+	let mut ret = vec![];
+	check_acs_recur(folder, &mut ret);
+	ret
+}
+
+fn check_acs_recur(folder: FolderRef, errors: &mut Vec<AcsSyntaxError>) {
+	for fref in folder.files() {
+		let name = fref.name();
+
+		let is_acs = name
+			.file_prefix()
+			.is_some_and(|pfx| pfx.as_str().eq_ignore_ascii_case("scripts"))
+			|| name
+				.extension()
+				.is_some_and(|ext| ext.eq_ignore_ascii_case("acs"));
+
+		if !is_acs {
+			continue;
+		}
+
+		let Ok(source) = fref.copy_string() else {
+			continue;
+		};
+
+		let ptree: acs::ParseTree = doomfront::parse(&source, acs::parse::file, ());
+
+		for err in ptree.errors() {
+			let span = err.found().span();
+
+			errors.push(AcsSyntaxError {
+				path: fref.path(),
+				line: source[..span.start].matches('\n').count() + 1,
+				found: source[span].to_string(),
+				expected: err.expected().collect(),
+			});
+		}
+	}
+
+	for subfolder in folder.subfolders() {
+		check_acs_recur(subfolder, errors);
+	}
+}
+
+// VPK /////////////////////////////////////////////////////////////////////////
+
+/// The top-level directories of a VileTech package (VPK)
+/// which [`pproc_pass3_vpk`] recognizes. Names are case-insensitive.
+///
+/// (RAT) Rather than WAD namespace markers, a VPK's directory hierarchy decides
+/// how each of its files gets processed. Anything nested deeper than a package's
+/// top level (e.g. `gfx/sprites/`) is treated the same as its top-level ancestor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VpkDir {
+	/// `gfx/`. Any common image format, or Doom's picture format.
+	Gfx,
+	/// `sfx/`. DMX digitized sound, or any waveform format.
+	Sfx,
+	/// `music/`. Kept encoded; see [`AudioStream`](crate::audio::AudioStream).
+	Music,
+	/// `maps/`. Only `.udmf` files are recognized.
+	Maps,
+	/// `scripts/`. Only ACS gets [checked](check_acs) for now.
+	Scripts,
+}
+
+impl VpkDir {
+	#[must_use]
+	pub fn from_name(name: &str) -> Option<Self> {
+		const DIRS: &[(&str, VpkDir)] = &[
+			("gfx", VpkDir::Gfx),
+			("sfx", VpkDir::Sfx),
+			("music", VpkDir::Music),
+			("maps", VpkDir::Maps),
+			("scripts", VpkDir::Scripts),
+		];
+
+		DIRS.iter()
+			.find(|(dir, _)| name.eq_ignore_ascii_case(dir))
+			.map(|(_, kind)| *kind)
+	}
+}
+
+/// What [`pproc_pass3_vpk`] made of a package,
+/// besides the sounds and music it put into a [`SoundTable`].
+#[derive(Debug, Default)]
+pub struct VpkOutput {
+	pub images: Vec<(VPathBuf, Image)>,
+	pub levels: Vec<(VPathBuf, Level)>,
+	pub errors: Vec<VpkError>,
+	/// See [`check_acs`].
+	pub acs_errors: Vec<AcsSyntaxError>,
+}
+
+/// See [`pproc_pass3_vpk`].
+#[derive(Debug)]
+pub struct VpkError {
+	pub path: VPathBuf,
+	pub kind: VpkErrorKind,
+}
+
+#[derive(Debug)]
+pub enum VpkErrorKind {
+	Read(vfs::Error),
+	/// A file in `gfx/` was in a common image format, but could not be decoded.
+	Image(image::ImageError),
+	/// A file in `gfx/` was in no common image format, nor Doom's picture format.
+	Picture(data::Error),
+	Dmx(DmxSfxError),
+	Waveform(FromFileError),
+	/// A file in `music/` was in no recognized [format](crate::audio::AudioFormat).
+	UnknownMusic,
+	/// A fatal error, or one which caused part of a level to be skipped.
+	Udmf(udmf::Error),
+}
+
+impl std::error::Error for VpkError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match &self.kind {
+			VpkErrorKind::Read(err) => Some(err),
+			VpkErrorKind::Image(err) => Some(err),
+			VpkErrorKind::Picture(err) => Some(err),
+			VpkErrorKind::Dmx(err) => Some(err),
+			VpkErrorKind::Waveform(err) => Some(err),
+			VpkErrorKind::Udmf(err) => Some(err),
+			VpkErrorKind::UnknownMusic => None,
+		}
+	}
+}
+
+impl std::fmt::Display for VpkError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match &self.kind {
+			VpkErrorKind::Read(err) => write!(f, "failed to read `{}`: {err}", self.path),
+			VpkErrorKind::Image(err) => write!(f, "failed to decode image `{}`: {err}", self.path),
+			VpkErrorKind::Picture(err) => {
+				write!(f, "`{}` is not a recognized image format: {err}", self.path)
+			}
+			VpkErrorKind::Dmx(err) => write!(f, "failed to decode sound `{}`: {err}", self.path),
+			VpkErrorKind::Waveform(err) => {
+				write!(f, "failed to decode sound `{}`: {err}", self.path)
+			}
+			VpkErrorKind::Udmf(err) => write!(f, "error in level `{}`: {err}", self.path),
+			VpkErrorKind::UnknownMusic => {
+				write!(f, "`{}` is not a recognized music format", self.path)
+			}
+		}
+	}
+}
+
+/// The VileTech package counterpart to post-processing a WAD, where each of
+/// `root`'s top-level directories decides what gets done with the files under
+/// it (recursively); see [`VpkDir`]. Sounds and music go into `sounds` under
+/// their file prefix, replacing any already there. Anything outside of those
+/// directories (e.g. `meta.toml`) gets left alone.
+///
+/// Doom pictures in `gfx/` are decoded with `palette` and `colormap`.
+/// No error fails the package as a whole.
+#[must_use]
+pub fn pproc_pass3_vpk(
+	root: FolderRef,
+	palette: &Palette,
+	colormap: &ColorMap,
+	sounds: &mut SoundTable,
+) -> VpkOutput {
+	let mut ret = VpkOutput::default();
+
+	for subfolder in root.subfolders() {
+		let Some(kind) = VpkDir::from_name(subfolder.name().as_str()) else {
+			continue;
+		};
+
+		if kind == VpkDir::Scripts {
+			check_acs_recur(subfolder, &mut ret.acs_errors);
+			continue;
+		}
+
+		pproc_vpk_recur(subfolder, kind, palette, colormap, sounds, &mut ret);
+	}
+
+	ret
+}
+
+fn pproc_vpk_recur(
+	folder: FolderRef,
+	kind: VpkDir,
+	palette: &Palette,
+	colormap: &ColorMap,
+	sounds: &mut SoundTable,
+	out: &mut VpkOutput,
+) {
+	for fref in folder.files() {
+		if fref.is_empty() {
+			continue;
+		}
+
+		if let Err(kind) = pproc_vpk_file(fref, kind, palette, colormap, sounds, out) {
+			out.errors.push(VpkError {
+				path: fref.path(),
+				kind,
+			});
+		}
+	}
+
+	for subfolder in folder.subfolders() {
+		pproc_vpk_recur(subfolder, kind, palette, colormap, sounds, out);
+	}
+}
+
+fn pproc_vpk_file(
+	fref: FileRef,
+	kind: VpkDir,
+	palette: &Palette,
+	colormap: &ColorMap,
+	sounds: &mut SoundTable,
+	out: &mut VpkOutput,
+) -> Result<(), VpkErrorKind> {
+	let prefix = fref.name().file_prefix().unwrap_or(fref.name()).as_str();
+
+	match kind {
+		VpkDir::Gfx => {
+			let bytes = fref.lock().read().map_err(VpkErrorKind::Read)?.into_owned();
+
+			let image = if image::guess_format(&bytes).is_ok() {
+				let img = image::load_from_memory(&bytes).map_err(VpkErrorKind::Image)?;
+				Image::from_dynamic(img, true)
+			} else {
+				picture_to_image(&bytes, palette, colormap, Some(fref.path().to_string()))
+					.map_err(VpkErrorKind::Picture)?
+			};
+
+			out.images.push((fref.path(), image));
+		}
+		VpkDir::Sfx => {
+			let bytes = fref.lock().read().map_err(VpkErrorKind::Read)?.into_owned();
+
+			let data = match sound_from_dmx(&bytes) {
+				Some(result) => result.map_err(VpkErrorKind::Dmx)?,
+				None => sound_from_bytes(bytes, StaticSoundSettings::default())
+					.map_err(VpkErrorKind::Waveform)?,
+			};
+
+			sounds.insert(prefix, data);
+		}
+		VpkDir::Music => {
+			let bytes = fref.lock().read().map_err(VpkErrorKind::Read)?.into_owned();
+
+			if !sounds.insert_music(prefix, bytes) {
+				return Err(VpkErrorKind::UnknownMusic);
+			}
+		}
+		VpkDir::Maps => {
+			let is_udmf = fref
+				.name()
+				.extension()
+				.is_some_and(|ext| ext.eq_ignore_ascii_case("udmf"));
+
+			if !is_udmf {
+				return Ok(());
+			}
+
+			let source = fref.copy_string().map_err(VpkErrorKind::Read)?;
+			let (level, errors) = parse_textmap(&source).map_err(VpkErrorKind::Udmf)?;
+
+			out.errors.extend(errors.into_iter().map(|err| VpkError {
+				path: fref.path(),
+				kind: VpkErrorKind::Udmf(err),
+			}));
+
+			out.levels.push((fref.path(), level));
+		}
+		VpkDir::Scripts => unreachable!("VPK scripts are checked as a whole"),
+	}
+
+	Ok(())
+}
+
+// LoadError ///////////////////////////////////////////////////////////////////
+
+/// Which step of a load an error arose from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadPhase {
+	Mount,
+	PostProc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+	/// The offending file was skipped, but the load carried on.
+	Warning,
+	/// The load can not succeed.
+	Fatal,
+}
+
+/// Implemented by [`MountError`] and [`PostProcError`], so that every error in a
+/// [`LoadOutcome`] can be reported on in one pass; see [`LoadOutcome::all_errors`].
+pub trait LoadError: std::error::Error {
+	#[must_use]
+	fn phase(&self) -> LoadPhase;
+	#[must_use]
+	fn severity(&self) -> Severity;
+}
+
+/// A file in the load order which could not be [mounted](vfs::VirtualFs::mount).
+#[derive(Debug)]
+pub struct MountError {
+	pub path: PathBuf,
+	pub kind: vfs::Error,
+}
+
+impl std::error::Error for MountError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.kind)
+	}
+}
+
+impl std::fmt::Display for MountError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"failed to mount `{}`: {}",
+			self.path.display(),
+			self.kind
+		)
+	}
+}
+
+impl LoadError for MountError {
+	fn phase(&self) -> LoadPhase {
+		LoadPhase::Mount
+	}
+
+	/// (RAT) A file which failed to mount has no content for post-processing
+	/// to work with, and every mount after it may be relying on that content.
+	fn severity(&self) -> Severity {
+		Severity::Fatal
+	}
+}
+
+#[derive(Debug)]
+pub enum PostProcError {
+	Vpk(VpkError),
+	Acs(AcsSyntaxError),
+}
+
+impl From<VpkError> for PostProcError {
+	fn from(value: VpkError) -> Self {
+		Self::Vpk(value)
+	}
+}
+
+impl From<AcsSyntaxError> for PostProcError {
+	fn from(value: AcsSyntaxError) -> Self {
+		Self::Acs(value)
+	}
+}
+
+impl std::error::Error for PostProcError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Vpk(err) => err.source(),
+			Self::Acs(_) => None,
+		}
+	}
+}
+
+impl std::fmt::Display for PostProcError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Vpk(err) => err.fmt(f),
+			Self::Acs(err) => err.fmt(f),
+		}
+	}
+}
+
+impl LoadError for PostProcError {
+	fn phase(&self) -> LoadPhase {
+		LoadPhase::PostProc
+	}
+
+	/// (RAT) Neither [`pproc_pass3_vpk`] nor [`check_acs`] ever fails a package
+	/// as a whole; whatever file was at fault has just been skipped.
+	fn severity(&self) -> Severity {
+		Severity::Warning
+	}
+}
+
+/// Every vector of errors herein has one sub-vector per mount in the load order.
+#[derive(Debug)]
+pub enum LoadOutcome {
+	/// A load was requested with an empty load order.
+	NoOp,
+	Cancelled,
+	/// Post-processing was not attempted.
+	MountFail {
+		errors: Vec<Vec<MountError>>,
+	},
+	PostProcFail {
+		errors: Vec<Vec<PostProcError>>,
+	},
+	Ok {
+		mount: Vec<Vec<MountError>>,
+		pproc: Vec<Vec<PostProcError>>,
+	},
+}
+
+impl LoadOutcome {
+	/// Every error from every mount, mount errors first.
+	/// Yields nothing for [`LoadOutcome::NoOp`] and [`LoadOutcome::Cancelled`].
+	pub fn all_errors(&self) -> impl Iterator<Item = &dyn LoadError> {
+		let (mount, pproc): (&[Vec<MountError>], &[Vec<PostProcError>]) = match self {
+			Self::NoOp | Self::Cancelled => (&[], &[]),
+			Self::MountFail { errors } => (errors, &[]),
+			Self::PostProcFail { errors } => (&[], errors),
+			Self::Ok { mount, pproc } => (mount, pproc),
+		};
+
+		mount
+			.iter()
+			.flatten()
+			.map(|err| err as &dyn LoadError)
+			.chain(pproc.iter().flatten().map(|err| err as &dyn LoadError))
+	}
+}
+
+// EarlyAssets /////////////////////////////////////////////////////////////////
+
+/// Hands assets from the loading thread to the client while a load is ongoing.
+///
+/// Bounded: only the latest accepted [`Splash`] is ever held.
+/// See [`Self::publish`] for which one that is.
+#[derive(Debug, Default)]
+pub struct EarlyAssets(Mutex<EarlyInner>);
+
+#[derive(Debug, Default)]
+struct EarlyInner {
+	/// Published but not yet [taken](EarlyAssets::take).
+	pending: Option<Splash>,
+	/// The load order index of the last accepted splash, even if it was taken.
+	latest: Option<usize>,
+}
+
+impl EarlyAssets {
+	/// Mounts may finish out of order, so a splash only gets accepted if it
+	/// comes from a mount later in the load order than every splash before it.
+	/// In other words, the last mount in the load order which has a splash wins.
+	/// An accepted splash replaces any which has not been taken yet.
+	///
+	/// Returns `true` if `splash` was accepted.
+	pub fn publish(&self, splash: Splash) -> bool {
+		let mut guard = self.0.lock();
+
+		if guard.latest.is_some_and(|l| l > splash.mount) {
+			return false;
+		}
+
+		guard.latest = Some(splash.mount);
+		guard.pending = Some(splash);
+		true
+	}
+
+	/// Looks for, decodes, and [publishes](Self::publish) the splash of the mount
+	/// at index `mount` in the load order. Decoding gets skipped entirely if a
+	/// splash from a later mount has already been accepted.
+	///
+	/// Returns `true` if a splash was found, decoded, and accepted.
+	pub fn offer_mount(
+		&self,
+		mount: usize,
+		root: FolderRef,
+		declared: Option<&VPath>,
+		palette: Option<(&Palette, &ColorMap)>,
+	) -> bool {
+		if self.0.lock().latest.is_some_and(|l| l > mount) {
+			return false;
+		}
+
+		let Some(fref) = find_splash(root, declared) else {
+			return false;
+		};
+
+		let mut guard = fref.lock();
+
+		let Ok(bytes) = guard.read() else {
+			return false;
+		};
+
+		match Splash::decode(mount, &bytes, palette) {
+			Some(splash) => self.publish(splash),
+			None => false,
+		}
+	}
+
+	/// Gets the newest accepted splash, if there is one which has not already
+	/// been taken. The client should keep showing what it last took otherwise.
+	#[must_use]
+	pub fn take(&self) -> Option<Splash> {
+		self.0.lock().pending.take()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use data::gfx::Rgb8;
+
+	use super::*;
+
+	#[must_use]
+	fn splash(mount: usize) -> Splash {
+		Splash {
+			mount,
+			width: 1,
+			height: 1,
+			rgba: vec![0, 0, 0, u8::MAX],
+		}
+	}
+
+	#[test]
+	fn last_mount_wins() {
+		let early = EarlyAssets::default();
+		assert!(early.take().is_none());
+
+		assert!(early.publish(splash(1)));
+		assert!(early.publish(splash(3)));
+		// Mount 2 finished after mount 3, but comes before it in the load order.
+		assert!(!early.publish(splash(2)));
+		assert_eq!(early.take().unwrap().mount, 3);
+		assert!(early.take().is_none());
+
+		// Having been taken does not lower the bar.
+		assert!(!early.publish(splash(0)));
+		assert!(early.take().is_none());
+		assert!(early.publish(splash(4)));
+		assert_eq!(early.take().unwrap().mount, 4);
+	}
+
+	#[test]
+	fn decode() {
+		let mut png = vec![];
+
+		image::RgbaImage::from_raw(2, 1, vec![255, 0, 0, 255, 0, 255, 0, 128])
+			.unwrap()
+			.write_to(
+				&mut std::io::Cursor::new(&mut png),
+				image::ImageOutputFormat::Png,
+			)
+			.unwrap();
+
+		let s = Splash::decode(0, &png, None).unwrap();
+		assert_eq!((s.width, s.height), (2, 1));
+		assert_eq!(s.rgba, [255, 0, 0, 255, 0, 255, 0, 128]);
+
+		// A 1x2 Doom picture with one post covering the whole column.
+		let pic = [
+			1, 0, 2, 0, 0, 0, 0, 0, // Header
+			12, 0, 0, 0, // Column offsets
+			0, 2, 0, 1, 2, 0, 255, // Column 0
+		];
+
+		assert!(Splash::decode(0, &pic, None).is_none());
+
+		let mut palette = Palette([Rgb8 { r: 0, g: 0, b: 0 }; 256]);
+		palette[1] = Rgb8 {
+			r: 10,
+			g: 20,
+			b: 30,
+		};
+		palette[2] = Rgb8 {
+			r: 40,
+			g: 50,
+			b: 60,
+		};
+		let mut colormap = ColorMap([0; 256]);
+
+		for (i, entry) in colormap.iter_mut().enumerate() {
+			*entry = i as u8;
+		}
+
+		let s = Splash::decode(5, &pic, Some((&palette, &colormap))).unwrap();
+		assert_eq!(s.mount, 5);
+		assert_eq!((s.width, s.height), (1, 2));
+		assert_eq!(s.rgba, [10, 20, 30, 255, 40, 50, 60, 255]);
+	}
+
+	#[test]
+	fn scale_to_fit() {
+		let titlepic = Splash {
+			mount: 0,
+			width: 320,
+			height: 200,
+			rgba: vec![],
+		};
+
+		assert_eq!(titlepic.scale_to_fit([1920.0, 1080.0]), 5.0);
+		assert_eq!(titlepic.scale_to_fit([640.0, 480.0]), 2.0);
+		assert_eq!(titlepic.scale_to_fit([320.0, 200.0]), 1.0);
+		assert_eq!(titlepic.scale_to_fit([160.0, 200.0]), 0.5);
+	}
+
+	#[test]
+	fn rank() {
+		assert_eq!(splash_rank(VPath::new("TITLEPIC")), Some(0));
+		assert_eq!(splash_rank(VPath::new("titlepic.png")), Some(0));
+		assert_eq!(splash_rank(VPath::new("INTERPIC")), Some(1));
+		assert_eq!(splash_rank(VPath::new("CREDIT")), None);
+	}
+
+	#[test]
+	fn acs_syntax_errors() {
+		const BROKEN: &str = "script 1 OPEN\n{\n\tint x = ;\n\tprint(s:\"after\");\n}\n";
+		const FINE: &str = "#include \"zcommon.acs\"\n\nscript 2 ENTER { Delay(1); }\n";
+
+		let dir = std::env::temp_dir().join(format!("viletech_acs_{}", std::process::id()));
+		let mod_dir = dir.join("mod");
+		std::fs::create_dir_all(mod_dir.join("acs")).unwrap();
+		std::fs::write(mod_dir.join("acs/broken.acs"), BROKEN).unwrap();
+		std::fs::write(mod_dir.join("acs/fine.acs"), FINE).unwrap();
+		std::fs::write(mod_dir.join("readme.txt"), BROKEN).unwrap();
+
+		std::fs::write(
+			dir.join("maps.wad"),
+			vfs::build_pwad(&[("SCRIPTS", BROKEN.as_bytes().to_vec())]),
+		)
+		.unwrap();
+
+		let mut vfs = vfs::VirtualFs::default();
+		vfs.mount(&mod_dir, VPath::new("mod")).unwrap();
+		vfs.mount(&dir.join("maps.wad"), VPath::new("maps"))
+			.unwrap();
+
+		let mut errors = check_acs(vfs.root());
+		errors.sort_by(|a, b| a.path.cmp(&b.path));
+		assert_eq!(errors.len(), 2);
+
+		assert_eq!(errors[0].path, VPath::new("/maps/SCRIPTS"));
+		assert_eq!(errors[1].path, VPath::new("/mod/acs/broken.acs"));
+		assert_eq!(errors[1].line, 3);
+		assert_eq!(errors[1].found, ";");
+		assert!(!errors[1].expected.is_empty());
+
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn all_errors() {
+		let mut vfs = vfs::VirtualFs::default();
+		let path = std::env::temp_dir().join("viletech_nonexistent.wad");
+
+		let mount = vec![
+			vec![MountError {
+				kind: vfs.mount(&path, VPath::new("missing")).unwrap_err(),
+				path,
+			}],
+			vec![],
+		];
+
+		let pproc = vec![
+			vec![],
+			vec![
+				PostProcError::from(VpkError {
+					path: VPath::new("/vpk/music/notes.txt").to_owned(),
+					kind: VpkErrorKind::UnknownMusic,
+				}),
+				PostProcError::from(AcsSyntaxError {
+					path: VPath::new("/vpk/scripts/broken.acs").to_owned(),
+					line: 3,
+					found: ";".to_string(),
+					expected: vec!["an expression"],
+				}),
+			],
+		];
+
+		let outcome = LoadOutcome::Ok { mount, pproc };
+		let errors: Vec<_> = outcome.all_errors().collect();
+		assert_eq!(errors.len(), 3);
+
+		assert_eq!(errors[0].phase(), LoadPhase::Mount);
+		assert_eq!(errors[0].severity(), Severity::Fatal);
+		assert!(errors[0].to_string().contains("viletech_nonexistent.wad"));
+
+		for err in &errors[1..] {
+			assert_eq!(err.phase(), LoadPhase::PostProc);
+			assert_eq!(err.severity(), Severity::Warning);
+		}
+
+		assert!(errors[1].to_string().contains("notes.txt"));
+		assert!(errors[2].to_string().contains("broken.acs"));
+
+		assert_eq!(LoadOutcome::Cancelled.all_errors().count(), 0);
+	}
+
+	#[test]
+	fn vpk_pass3() {
+		const TEXTMAP: &str = "namespace = \"zdoom\";\nvertex { x = 0.0; y = 0.0; }\nbogus { }\n";
+		// A 1x2 Doom picture with one post covering the whole column.
+		const PIC: &[u8] = &[1, 0, 2, 0, 0, 0, 0, 0, 12, 0, 0, 0, 0, 2, 0, 1, 2, 0, 255];
+
+		const MUS: &[u8] = b"MUS\x1a\0\0\0\0\0\0\0\0";
+
+		let mut dmx = vec![0x03, 0x00];
+		dmx.extend_from_slice(&11025_u16.to_le_bytes());
+		dmx.extend_from_slice(&33_u32.to_le_bytes());
+		dmx.extend_from_slice(&[0x80; 33]);
+
+		let dir = std::env::temp_dir().join(format!("viletech_vpk_{}", std::process::id()));
+
+		for (path, bytes) in [
+			("meta.toml", b"[package]".as_slice()),
+			("gfx/sprites/PICA0.lmp", PIC),
+			("gfx/broken.png", b"\x89PNG\r\n\x1a\n"),
+			("SFX/dspistol.lmp", &dmx),
+			("music/d_runnin.mus", MUS),
+			("music/notes.txt", b"not music"),
+			("maps/map01.udmf", TEXTMAP.as_bytes()),
+			("maps/map01.txt", TEXTMAP.as_bytes()),
+			("scripts/broken.acs", b"script 1 OPEN\n{\n\tint x = ;\n}\n"),
+			("textures/PICB0.lmp", PIC),
+		] {
+			let path = dir.join(path);
+			std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+			std::fs::write(path, bytes).unwrap();
+		}
+
+		let mut vfs = vfs::VirtualFs::default();
+		vfs.mount(&dir, VPath::new("vpk")).unwrap();
+		let root = vfs
+			.lookup(VPath::new("/vpk"))
+			.unwrap()
+			.into_folder()
+			.unwrap();
+
+		let palette = Palette([Rgb8 { r: 0, g: 0, b: 0 }; 256]);
+		let colormap = ColorMap([0; 256]);
+		let mut sounds = SoundTable::default();
+		let out = pproc_pass3_vpk(root, &palette, &colormap, &mut sounds);
+
+		assert_eq!(out.images.len(), 1);
+		assert_eq!(out.images[0].0, VPath::new("/vpk/gfx/sprites/PICA0.lmp"));
+		assert!(sounds.resolve("dspistol").is_some());
+		assert_eq!(
+			sounds.resolve_music("d_runnin").unwrap().source_bytes(),
+			MUS
+		);
+		assert_eq!(out.levels.len(), 1);
+		assert_eq!(out.levels[0].1.vertices.len(), 1);
+		assert_eq!(out.acs_errors.len(), 1);
+
+		// The broken PNG, the text file in `music/`,
+		// and the unrecognized block in the level.
+		assert_eq!(out.errors.len(), 3);
+		assert!(out
+			.errors
+			.iter()
+			.any(|err| matches!(err.kind, VpkErrorKind::UnknownMusic)));
+		assert!(out
+			.errors
+			.iter()
+			.any(|err| matches!(err.kind, VpkErrorKind::Image(_))));
+		assert!(out
+			.errors
+			.iter()
+			.any(|err| matches!(err.kind, VpkErrorKind::Udmf(_))));
+
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+}
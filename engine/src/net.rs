@@ -0,0 +1,165 @@
+//! Networking concerns shared between the client and dedicated server.
+
+pub mod manifest;
+pub mod msg;
+
+use std::num::IntErrorKind;
+
+use sha3::Digest;
+
+/// The scheme expected by [`parse_connection_string`].
+pub const URI_SCHEME: &str = "viletech://";
+
+/// The port used by the dedicated server if none is specified.
+pub const DEFAULT_PORT: u16 = 6666;
+
+/// Everything a client needs to connect to a server.
+/// See [`parse_connection_string`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionParams {
+	/// A domain name or IP address. IPv6 addresses have their brackets removed.
+	pub host: String,
+	pub port: u16,
+	/// A SHA3-256 hash of the server's password, if one was given.
+	pub passhash: Option<[u8; 32]>,
+}
+
+/// Accepts a URI of the form `viletech://host[:port][/password]`.
+///
+/// IPv6 hosts must be surrounded in brackets (e.g. `viletech://[::1]:6666`).
+/// If no port is given, [`DEFAULT_PORT`] is used. The password never leaves
+/// this function in plain text; it gets hashed into [`ConnectionParams::passhash`].
+pub fn parse_connection_string(uri: &str) -> Result<ConnectionParams, ParseError> {
+	let uri = uri.trim();
+
+	let Some(rest) = uri
+		.get(..URI_SCHEME.len())
+		.filter(|scheme| scheme.eq_ignore_ascii_case(URI_SCHEME))
+		.map(|_| &uri[URI_SCHEME.len()..])
+	else {
+		return Err(ParseError::Scheme);
+	};
+
+	let (authority, password) = match rest.split_once('/') {
+		Some((a, p)) => (a, Some(p)),
+		None => (rest, None),
+	};
+
+	let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+		let Some((host, after)) = bracketed.split_once(']') else {
+			return Err(ParseError::UnclosedBracket);
+		};
+
+		match after {
+			"" => (host, None),
+			_ => match after.strip_prefix(':') {
+				Some(port) => (host, Some(port)),
+				None => return Err(ParseError::TrailingChars),
+			},
+		}
+	} else {
+		match authority.split_once(':') {
+			Some((host, port)) => (host, Some(port)),
+			None => (authority, None),
+		}
+	};
+
+	if host.is_empty() {
+		return Err(ParseError::EmptyHost);
+	}
+
+	if host.contains(char::is_whitespace) {
+		return Err(ParseError::InvalidHost);
+	}
+
+	let port = match port {
+		Some(p) => p
+			.parse::<u16>()
+			.map_err(|err| ParseError::Port(err.kind().clone()))?,
+		None => DEFAULT_PORT,
+	};
+
+	let passhash = password.filter(|p| !p.is_empty()).map(|p| {
+		let mut hasher = sha3::Sha3_256::new();
+		hasher.update(p.as_bytes());
+		<[u8; 32]>::from(hasher.finalize())
+	});
+
+	Ok(ConnectionParams {
+		host: host.to_string(),
+		port,
+		passhash,
+	})
+}
+
+/// See [`parse_connection_string`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+	/// The string did not start with [`URI_SCHEME`].
+	Scheme,
+	EmptyHost,
+	/// The host contained whitespace.
+	InvalidHost,
+	/// An IPv6 host's opening `[` had no matching `]`.
+	UnclosedBracket,
+	/// Something other than a port followed a bracketed IPv6 host.
+	TrailingChars,
+	Port(IntErrorKind),
+}
+
+impl std::error::Error for ParseError {}
+
+impl std::fmt::Display for ParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Scheme => write!(f, "connection string must start with `{URI_SCHEME}`"),
+			Self::EmptyHost => write!(f, "connection string has no host"),
+			Self::InvalidHost => write!(f, "connection string host contains whitespace"),
+			Self::UnclosedBracket => write!(f, "connection string IPv6 host is missing a `]`"),
+			Self::TrailingChars => {
+				write!(f, "connection string has unexpected characters after its host")
+			}
+			Self::Port(kind) => write!(f, "connection string has an invalid port: {kind:?}"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn connection_string() {
+		let params = parse_connection_string("viletech://localhost").unwrap();
+		assert_eq!(params.host, "localhost");
+		assert_eq!(params.port, DEFAULT_PORT);
+		assert!(params.passhash.is_none());
+
+		let params = parse_connection_string("viletech://192.168.0.2:10666/hunter2").unwrap();
+		assert_eq!(params.host, "192.168.0.2");
+		assert_eq!(params.port, 10666);
+		assert!(params.passhash.is_some());
+
+		let params = parse_connection_string("VILETECH://[::1]:7777/").unwrap();
+		assert_eq!(params.host, "::1");
+		assert_eq!(params.port, 7777);
+		assert!(params.passhash.is_none());
+
+		assert_eq!(
+			parse_connection_string("http://localhost"),
+			Err(ParseError::Scheme)
+		);
+		assert_eq!(
+			parse_connection_string("viletech://:6666"),
+			Err(ParseError::EmptyHost)
+		);
+		assert_eq!(
+			parse_connection_string("viletech://[::1"),
+			Err(ParseError::UnclosedBracket)
+		);
+		assert!(matches!(
+			parse_connection_string("viletech://localhost:99999"),
+			Err(ParseError::Port(_))
+		));
+	}
+}
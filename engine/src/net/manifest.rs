@@ -0,0 +1,348 @@
+//! Describing and comparing the content a server and client have loaded.
+//!
+//! Upon connecting, a client receives the server's [`ContentManifest`] and
+//! [compares](compare) it against its own. The resulting [`ManifestReport`]
+//! gets sent back, so that both sides can act upon the same [`Verdict`].
+
+use serde::{Deserialize, Serialize};
+use sha3::Digest;
+
+/// Every mount a server or client has loaded, in load order.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentManifest {
+	pub mounts: Vec<MountEntry>,
+}
+
+impl ContentManifest {
+	#[must_use]
+	pub fn find(&self, id: &str) -> Option<&MountEntry> {
+		self.mounts.iter().find(|m| m.id == id)
+	}
+}
+
+/// One element of a [`ContentManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MountEntry {
+	/// Unique within a manifest. Mounts are matched between manifests by this.
+	pub id: String,
+	/// Shown to users in reports, but never compared.
+	pub name: String,
+	/// In bytes.
+	pub size: u64,
+	/// A SHA3-256 hash of the mount's content.
+	pub checksum: [u8; 32],
+}
+
+impl MountEntry {
+	/// Hashes `bytes` to produce [`Self::checksum`].
+	#[must_use]
+	pub fn new(id: String, name: String, bytes: &[u8]) -> Self {
+		Self {
+			id,
+			name,
+			size: bytes.len() as u64,
+			checksum: sha3::Sha3_256::digest(bytes).into(),
+		}
+	}
+}
+
+/// How the server treats mounts which a client has loaded but the server has not.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExtraMountPolicy {
+	/// The client gets rejected.
+	Reject,
+	/// The client is admitted, but both sides log a warning.
+	#[default]
+	Warn,
+}
+
+/// The outcome of [`compare`]. Each field is in the server's load order
+/// (or, for [`Self::extra`], the client's).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestReport {
+	/// IDs of mounts the server has but the client lacks.
+	pub missing: Vec<String>,
+	/// Mounts both sides have, but with different content.
+	pub mismatched: Vec<Mismatch>,
+	/// IDs of mounts the client has but the server lacks.
+	pub extra: Vec<String>,
+	/// `true` if the mounts both sides share were loaded in a different order.
+	pub out_of_order: bool,
+}
+
+/// See [`ManifestReport::mismatched`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mismatch {
+	pub id: String,
+	pub server_size: u64,
+	pub client_size: u64,
+	pub server_checksum: [u8; 32],
+	pub client_checksum: [u8; 32],
+}
+
+/// See [`ManifestReport::verdict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+	/// Both manifests are identical.
+	Match,
+	/// The client may proceed, but the report should be shown and logged.
+	Warn,
+	Reject,
+}
+
+impl ManifestReport {
+	/// Missing mounts, mismatched mounts, and a different load order are always
+	/// fatal; the fatality of extra client-side mounts depends on `policy`.
+	#[must_use]
+	pub fn verdict(&self, policy: ExtraMountPolicy) -> Verdict {
+		if !self.missing.is_empty() || !self.mismatched.is_empty() || self.out_of_order {
+			return Verdict::Reject;
+		}
+
+		if self.extra.is_empty() {
+			return Verdict::Match;
+		}
+
+		match policy {
+			ExtraMountPolicy::Reject => Verdict::Reject,
+			ExtraMountPolicy::Warn => Verdict::Warn,
+		}
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.missing.is_empty()
+			&& self.mismatched.is_empty()
+			&& self.extra.is_empty()
+			&& !self.out_of_order
+	}
+}
+
+impl std::fmt::Display for ManifestReport {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if self.is_empty() {
+			return write!(f, "content matches the server");
+		}
+
+		for id in &self.missing {
+			writeln!(f, "missing: {id}")?;
+		}
+
+		for mm in &self.mismatched {
+			writeln!(
+				f,
+				"mismatched: {} (server: {} bytes, {}; client: {} bytes, {})",
+				mm.id,
+				mm.server_size,
+				hex(&mm.server_checksum),
+				mm.client_size,
+				hex(&mm.client_checksum)
+			)?;
+		}
+
+		for id in &self.extra {
+			writeln!(f, "extra: {id}")?;
+		}
+
+		if self.out_of_order {
+			writeln!(f, "load order differs from the server")?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Pure; all decisions about what is fatal are deferred to [`ManifestReport::verdict`].
+#[must_use]
+pub fn compare(server: &ContentManifest, client: &ContentManifest) -> ManifestReport {
+	let mut report = ManifestReport::default();
+
+	for s_mount in &server.mounts {
+		let Some(c_mount) = client.find(&s_mount.id) else {
+			report.missing.push(s_mount.id.clone());
+			continue;
+		};
+
+		if s_mount.size != c_mount.size || s_mount.checksum != c_mount.checksum {
+			report.mismatched.push(Mismatch {
+				id: s_mount.id.clone(),
+				server_size: s_mount.size,
+				client_size: c_mount.size,
+				server_checksum: s_mount.checksum,
+				client_checksum: c_mount.checksum,
+			});
+		}
+	}
+
+	for c_mount in &client.mounts {
+		if server.find(&c_mount.id).is_none() {
+			report.extra.push(c_mount.id.clone());
+		}
+	}
+
+	let shared_s = server
+		.mounts
+		.iter()
+		.filter(|m| client.find(&m.id).is_some())
+		.map(|m| m.id.as_str());
+
+	let shared_c = client
+		.mounts
+		.iter()
+		.filter(|m| server.find(&m.id).is_some())
+		.map(|m| m.id.as_str());
+
+	report.out_of_order = !shared_s.eq(shared_c);
+
+	report
+}
+
+#[must_use]
+fn hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::net::msg::{ClientMessage, ServerMessage};
+
+	#[must_use]
+	fn mount(id: &str, content: &[u8]) -> MountEntry {
+		MountEntry::new(id.to_string(), id.to_uppercase(), content)
+	}
+
+	#[must_use]
+	fn manifest(mounts: &[(&str, &[u8])]) -> ContentManifest {
+		ContentManifest {
+			mounts: mounts.iter().map(|(id, c)| mount(id, c)).collect(),
+		}
+	}
+
+	#[test]
+	fn exact_match() {
+		let s = manifest(&[("doom2", b"iwad"), ("mod", b"pk3")]);
+		let report = compare(&s, &s.clone());
+		assert!(report.is_empty());
+		assert_eq!(report.verdict(ExtraMountPolicy::Reject), Verdict::Match);
+		assert_eq!(report.verdict(ExtraMountPolicy::Warn), Verdict::Match);
+	}
+
+	#[test]
+	fn missing() {
+		let s = manifest(&[("doom2", b"iwad"), ("mod", b"pk3")]);
+		let c = manifest(&[("doom2", b"iwad")]);
+		let report = compare(&s, &c);
+		assert_eq!(report.missing, ["mod"]);
+		assert!(!report.out_of_order);
+		assert_eq!(report.verdict(ExtraMountPolicy::Warn), Verdict::Reject);
+	}
+
+	#[test]
+	fn mismatched() {
+		let s = manifest(&[("doom2", b"iwad"), ("mod", b"pk3")]);
+		let c = manifest(&[("doom2", b"iwad"), ("mod", b"pk3 v2")]);
+		let report = compare(&s, &c);
+		assert_eq!(report.mismatched.len(), 1);
+		assert_eq!(report.mismatched[0].id, "mod");
+		assert_eq!(report.mismatched[0].server_size, 3);
+		assert_eq!(report.mismatched[0].client_size, 6);
+		assert_eq!(report.verdict(ExtraMountPolicy::Warn), Verdict::Reject);
+
+		// Same size, different content.
+		let c = manifest(&[("doom2", b"iwad"), ("mod", b"zip")]);
+		let report = compare(&s, &c);
+		assert_eq!(report.mismatched.len(), 1);
+		assert_eq!(report.verdict(ExtraMountPolicy::Warn), Verdict::Reject);
+	}
+
+	#[test]
+	fn extra() {
+		let s = manifest(&[("doom2", b"iwad")]);
+		let c = manifest(&[("doom2", b"iwad"), ("hud", b"pk3")]);
+		let report = compare(&s, &c);
+		assert_eq!(report.extra, ["hud"]);
+		assert!(!report.out_of_order);
+		assert_eq!(report.verdict(ExtraMountPolicy::Warn), Verdict::Warn);
+		assert_eq!(report.verdict(ExtraMountPolicy::Reject), Verdict::Reject);
+
+		// Interleaving an extra mount does not disturb the shared order.
+		let s = manifest(&[("doom2", b"iwad"), ("mod", b"pk3")]);
+		let c = manifest(&[("doom2", b"iwad"), ("hud", b"pk3"), ("mod", b"pk3")]);
+		let report = compare(&s, &c);
+		assert!(!report.out_of_order);
+		assert_eq!(report.verdict(ExtraMountPolicy::Warn), Verdict::Warn);
+	}
+
+	#[test]
+	fn out_of_order() {
+		let s = manifest(&[("doom2", b"iwad"), ("a", b"1"), ("b", b"2")]);
+		let c = manifest(&[("doom2", b"iwad"), ("b", b"2"), ("a", b"1")]);
+		let report = compare(&s, &c);
+		assert!(report.missing.is_empty());
+		assert!(report.mismatched.is_empty());
+		assert!(report.extra.is_empty());
+		assert!(report.out_of_order);
+		assert_eq!(report.verdict(ExtraMountPolicy::Warn), Verdict::Reject);
+	}
+
+	#[test]
+	fn display() {
+		let s = manifest(&[("doom2", b"iwad"), ("mod", b"pk3")]);
+		let c = manifest(&[("hud", b"pk3")]);
+		let report = compare(&s, &c).to_string();
+		assert!(report.contains("missing: doom2"));
+		assert!(report.contains("missing: mod"));
+		assert!(report.contains("extra: hud"));
+		assert!(!report.contains("load order"));
+	}
+
+	/// Both sides of the exchange, with each message serialized in between.
+	#[test]
+	fn loopback() {
+		#[must_use]
+		fn exchange(
+			server: &ContentManifest,
+			client: &ContentManifest,
+			policy: ExtraMountPolicy,
+		) -> (Verdict, Verdict) {
+			let msg = ServerMessage::ContentManifest(server.clone());
+			let json = serde_json::to_string(&msg).unwrap();
+
+			let ServerMessage::ContentManifest(received) = serde_json::from_str(&json).unwrap()
+			else {
+				panic!("expected a `ContentManifest` message");
+			};
+
+			let report = compare(&received, client);
+			let c_verdict = report.verdict(policy);
+			let msg = ClientMessage::ManifestReport(report);
+			let json = serde_json::to_string(&msg).unwrap();
+
+			let ClientMessage::ManifestReport(received) = serde_json::from_str(&json).unwrap()
+			else {
+				panic!("expected a `ManifestReport` message");
+			};
+
+			(c_verdict, received.verdict(policy))
+		}
+
+		let server = manifest(&[("doom2", b"iwad"), ("mod", b"pk3")]);
+		let same = server.clone();
+		let extra = manifest(&[("doom2", b"iwad"), ("mod", b"pk3"), ("hud", b"pk3")]);
+		let stale = manifest(&[("doom2", b"iwad"), ("mod", b"old")]);
+
+		let cases = [
+			(&same, ExtraMountPolicy::Reject, Verdict::Match),
+			(&extra, ExtraMountPolicy::Warn, Verdict::Warn),
+			(&extra, ExtraMountPolicy::Reject, Verdict::Reject),
+			(&stale, ExtraMountPolicy::Warn, Verdict::Reject),
+		];
+
+		for (client, policy, expected) in cases {
+			let (c_verdict, s_verdict) = exchange(&server, client, policy);
+			assert_eq!(c_verdict, expected);
+			assert_eq!(s_verdict, expected);
+		}
+	}
+}
@@ -0,0 +1,134 @@
+//! Message types exchanged between the client and server.
+//!
+//! Each message kind is sent over a fixed channel; see the `CHANNEL_*` constants.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{player::predict::Input, sim::movement::PawnState};
+
+use super::manifest::{ContentManifest, ManifestReport};
+
+/// For messages which must arrive, and in the order they were sent
+/// (e.g. [`ClientMessage::Connect`], [`ServerMessage::PlayerJoined`]).
+pub const CHANNEL_RELIABLE_ORDERED: u8 = 0;
+/// For messages which must arrive, but in any order
+/// (e.g. [`ClientMessage::ChatMessage`], [`ServerMessage::ChatBroadcast`]).
+pub const CHANNEL_RELIABLE_UNORDERED: u8 = 1;
+/// For messages which are superseded by the next one of their kind
+/// (e.g. [`ClientMessage::Inputs`], [`ServerMessage::TickUpdate`]).
+pub const CHANNEL_UNRELIABLE: u8 = 2;
+
+/// Sent from a client to the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+	Connect {
+		username: String,
+	},
+	/// Every input the server has yet to acknowledge, oldest first, so that
+	/// losing one message on the unreliable channel loses no input.
+	/// See [`Predictor::unacknowledged`](crate::player::predict::Predictor::unacknowledged).
+	Inputs(Vec<Input>),
+	ChatMessage {
+		text: String,
+	},
+	/// The result of comparing a [`ServerMessage::ContentManifest`] against
+	/// the client's own loaded content.
+	ManifestReport(ManifestReport),
+}
+
+impl ClientMessage {
+	/// Which channel this kind of message should be sent over.
+	#[must_use]
+	pub fn channel(&self) -> u8 {
+		match self {
+			Self::Connect { .. } | Self::ManifestReport(_) => CHANNEL_RELIABLE_ORDERED,
+			Self::Inputs(_) => CHANNEL_UNRELIABLE,
+			Self::ChatMessage { .. } => CHANNEL_RELIABLE_UNORDERED,
+		}
+	}
+}
+
+/// Sent from the server to one or more clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+	TickUpdate {
+		tick: u64,
+		/// The recipient's own pawn, for use in reconciliation.
+		/// `None` until the server has processed any of its input.
+		own: Option<PlayerState>,
+		players: Vec<PlayerUpdate>,
+	},
+	PlayerJoined {
+		id: u64,
+		name: String,
+	},
+	PlayerLeft {
+		id: u64,
+	},
+	ChatBroadcast {
+		/// The ID of the sending player.
+		from: u64,
+		text: String,
+	},
+	/// Sent to each client as it connects, so it can verify that it has loaded
+	/// the same content as the server.
+	ContentManifest(ContentManifest),
+}
+
+impl ServerMessage {
+	/// Which channel this kind of message should be sent over.
+	#[must_use]
+	pub fn channel(&self) -> u8 {
+		match self {
+			Self::TickUpdate { .. } => CHANNEL_UNRELIABLE,
+			Self::PlayerJoined { .. } | Self::PlayerLeft { .. } | Self::ContentManifest(_) => {
+				CHANNEL_RELIABLE_ORDERED
+			}
+			Self::ChatBroadcast { .. } => CHANNEL_RELIABLE_UNORDERED,
+		}
+	}
+}
+
+/// The authoritative state of one player, as of a [`ServerMessage::TickUpdate`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayerUpdate {
+	pub id: u64,
+	pub pos: [f32; 3],
+	/// In radians.
+	pub yaw: f32,
+}
+
+/// The authoritative state of the recipient's own pawn, as of a [`ServerMessage::TickUpdate`].
+/// Pass to [`Predictor::reconcile`](crate::player::predict::Predictor::reconcile).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayerState {
+	/// The sequence number of the last [input](Input) the server processed
+	/// before producing the rest of this state.
+	pub ack: u32,
+	pub pos: [f32; 3],
+	pub vel: [f32; 3],
+	/// In radians.
+	pub yaw: f32,
+}
+
+impl PlayerState {
+	#[must_use]
+	pub fn new(pawn: PawnState, ack: u32) -> Self {
+		Self {
+			ack,
+			pos: pawn.pos.to_array(),
+			vel: pawn.vel.to_array(),
+			yaw: pawn.yaw,
+		}
+	}
+
+	#[must_use]
+	pub fn pawn(&self) -> PawnState {
+		PawnState {
+			pos: Vec3::from_array(self.pos),
+			vel: Vec3::from_array(self.vel),
+			yaw: self.yaw,
+		}
+	}
+}
@@ -1,5 +1,7 @@
 //! Intermediary structures between the human (or bot) player and actors.
 
+pub mod predict;
+
 use bitvec::vec::BitVec;
 
 #[derive(Debug)]
@@ -0,0 +1,470 @@
+//! Client-side prediction and reconciliation of the local player's movement.
+//!
+//! The client applies its own input immediately instead of waiting for the
+//! server to confirm it. Each input is stamped with a sequence number and kept
+//! in a short history; when the server's authoritative state arrives along with
+//! the last sequence number it processed, every input the server has not seen
+//! yet gets replayed on top of it, and the result is compared to what the
+//! client had predicted.
+//!
+//! Both sides advance the pawn via [`movement::step`]; the server's half of
+//! the bookkeeping is [`Authority`].
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::sim::movement::{self, PawnState, TicCmd};
+
+/// How many ticks of local input are kept for replay after reconciliation.
+pub const INPUT_HISTORY_LEN: usize = 32;
+
+/// If a reconciled position is further than this many map units from the
+/// predicted position, the prediction is discarded outright instead of being
+/// smoothly corrected.
+pub const SNAP_THRESHOLD: f32 = 0.5;
+
+/// One tick's worth of local player input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Input {
+	/// Monotonically increasing; the server echoes the last one it has processed.
+	pub seq: u32,
+	pub cmd: TicCmd,
+}
+
+/// See [`Predictor::reconcile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reconciliation {
+	/// The prediction was within [`SNAP_THRESHOLD`] of the server's state
+	/// and will be smoothly corrected.
+	Smoothed,
+	/// The prediction was too far off and was replaced outright.
+	Snapped,
+	/// The server's state acknowledged an older input than one already
+	/// reconciled against (i.e. it arrived out of order), and was ignored.
+	Stale,
+}
+
+/// Tracks the local player's predicted state and unacknowledged input.
+#[derive(Debug, Resource)]
+pub struct Predictor {
+	/// Ring buffer of every input not yet acknowledged by the server,
+	/// no more than [`INPUT_HISTORY_LEN`] long.
+	history: VecDeque<Input>,
+	next_seq: u32,
+	/// `None` until the first call to [`Self::reconcile`].
+	last_ack: Option<u32>,
+	predicted: PawnState,
+	/// The visual offset left over from the last smoothed correction.
+	/// Decays towards zero via [`Predictor::decay`].
+	error: Vec3,
+}
+
+impl Predictor {
+	#[must_use]
+	pub fn new(state: PawnState) -> Self {
+		Self {
+			history: VecDeque::with_capacity(INPUT_HISTORY_LEN),
+			next_seq: 0,
+			last_ack: None,
+			predicted: state,
+			error: Vec3::ZERO,
+		}
+	}
+
+	/// Immediately applies one tick of local input to the predicted state and
+	/// records it for later replay. The returned input should be sent to the
+	/// server, although sending all of [`Self::unacknowledged`] instead means
+	/// that one lost message does not lose any input.
+	pub fn predict(&mut self, cmd: TicCmd) -> Input {
+		let input = Input {
+			seq: self.next_seq,
+			cmd,
+		};
+
+		self.next_seq = self.next_seq.wrapping_add(1);
+		self.predicted = movement::step(self.predicted, &input.cmd);
+
+		if self.history.len() >= INPUT_HISTORY_LEN {
+			let _ = self.history.pop_front();
+		}
+
+		self.history.push_back(input);
+		input
+	}
+
+	/// Call when the server sends its authoritative state for the local player.
+	/// `ack` is the sequence number of the last input the server processed
+	/// before producing `authoritative`.
+	pub fn reconcile(&mut self, authoritative: PawnState, ack: u32) -> Reconciliation {
+		// Sequence numbers wrap, so compare by distance rather than by magnitude.
+		if self
+			.last_ack
+			.is_some_and(|last| (ack.wrapping_sub(last) as i32) < 0)
+		{
+			return Reconciliation::Stale;
+		}
+
+		self.last_ack = Some(ack);
+
+		while let Some(input) = self.history.front() {
+			if (ack.wrapping_sub(input.seq) as i32) < 0 {
+				break;
+			}
+
+			let _ = self.history.pop_front();
+		}
+
+		let mut replayed = authoritative;
+
+		for input in &self.history {
+			replayed = movement::step(replayed, &input.cmd);
+		}
+
+		let delta = self.visual().pos - replayed.pos;
+		self.predicted = replayed;
+
+		if delta.length() > SNAP_THRESHOLD {
+			self.error = Vec3::ZERO;
+			Reconciliation::Snapped
+		} else {
+			self.error = delta;
+			Reconciliation::Smoothed
+		}
+	}
+
+	/// Shrinks the remaining correction offset. `factor` is the proportion
+	/// of the offset to keep, and gets clamped to the range `0.0..=1.0`.
+	pub fn decay(&mut self, factor: f32) {
+		self.error *= factor.clamp(0.0, 1.0);
+
+		if self.error.length_squared() < f32::EPSILON {
+			self.error = Vec3::ZERO;
+		}
+	}
+
+	/// The predicted state, without any smoothing applied.
+	#[must_use]
+	pub fn predicted(&self) -> PawnState {
+		self.predicted
+	}
+
+	/// The predicted state with the remaining correction offset applied.
+	/// This is what should be presented to the user.
+	#[must_use]
+	pub fn visual(&self) -> PawnState {
+		PawnState {
+			pos: self.predicted.pos + self.error,
+			..self.predicted
+		}
+	}
+
+	/// The number of inputs not yet acknowledged by the server.
+	#[must_use]
+	pub fn pending(&self) -> usize {
+		self.history.len()
+	}
+
+	/// Every input not yet acknowledged by the server, oldest first.
+	pub fn unacknowledged(&self) -> impl Iterator<Item = &Input> {
+		self.history.iter()
+	}
+}
+
+/// The server's record of one client's pawn, advanced only by that client's input.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Authority {
+	state: PawnState,
+	/// `None` until the first input gets processed.
+	last_seq: Option<u32>,
+}
+
+impl Authority {
+	#[must_use]
+	pub fn new(state: PawnState) -> Self {
+		Self {
+			state,
+			last_seq: None,
+		}
+	}
+
+	/// Applies every input in `inputs` newer than the last one processed,
+	/// in the order given. Inputs which were already processed (e.g. because
+	/// the client re-sent them) or which arrived too late are ignored.
+	pub fn receive<'i>(&mut self, inputs: impl IntoIterator<Item = &'i Input>) {
+		for input in inputs {
+			if self
+				.last_seq
+				.is_some_and(|last| (input.seq.wrapping_sub(last) as i32) <= 0)
+			{
+				continue;
+			}
+
+			self.state = movement::step(self.state, &input.cmd);
+			self.last_seq = Some(input.seq);
+		}
+	}
+
+	#[must_use]
+	pub fn state(&self) -> PawnState {
+		self.state
+	}
+
+	/// The sequence number of the last input processed, to be sent to the
+	/// client along with [`Self::state`]. `None` if no input has arrived yet.
+	#[must_use]
+	pub fn ack(&self) -> Option<u32> {
+		self.last_seq
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::sim::movement::{FORWARD_MOVE, SIDE_MOVE};
+
+	use super::*;
+
+	fn forward() -> TicCmd {
+		TicCmd {
+			forward: FORWARD_MOVE[0],
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn replay_matches_prediction() {
+		let mut pred = Predictor::new(PawnState::default());
+		let mut auth = Authority::default();
+		let mut inputs = vec![];
+
+		for _ in 0..4 {
+			inputs.push(pred.predict(forward()));
+		}
+
+		// The server has processed the first two inputs and agrees with them.
+		auth.receive(&inputs[..2]);
+
+		assert_eq!(
+			pred.reconcile(auth.state(), auth.ack().unwrap()),
+			Reconciliation::Smoothed
+		);
+		assert_eq!(pred.pending(), 2);
+
+		auth.receive(&inputs);
+		assert_eq!(pred.predicted(), auth.state());
+		assert_eq!(pred.visual(), auth.state());
+	}
+
+	#[test]
+	fn snap_and_smooth() {
+		let mut pred = Predictor::new(PawnState::default());
+		let predicted = pred.predict(TicCmd::default());
+		assert_eq!(pred.predicted().pos, Vec3::ZERO);
+
+		let auth = PawnState {
+			pos: Vec3::new(0.25, 0.0, 0.0),
+			..Default::default()
+		};
+
+		assert_eq!(
+			pred.reconcile(auth, predicted.seq),
+			Reconciliation::Smoothed
+		);
+		assert_eq!(pred.predicted().pos, auth.pos);
+		assert_eq!(pred.visual().pos, Vec3::ZERO);
+		pred.decay(0.0);
+		assert_eq!(pred.visual().pos, auth.pos);
+
+		let auth = PawnState {
+			pos: Vec3::new(10.0, 0.0, 0.0),
+			..Default::default()
+		};
+
+		assert_eq!(pred.reconcile(auth, predicted.seq), Reconciliation::Snapped);
+		assert_eq!(pred.visual().pos, auth.pos);
+	}
+
+	#[test]
+	fn stale_ack() {
+		let mut pred = Predictor::new(PawnState::default());
+
+		for _ in 0..4 {
+			pred.predict(forward());
+		}
+
+		let predicted = pred.predicted();
+		let mut auth = Authority::default();
+		auth.receive(pred.unacknowledged());
+
+		assert_eq!(pred.reconcile(auth.state(), 3), Reconciliation::Smoothed);
+		assert_eq!(
+			pred.reconcile(PawnState::default(), 1),
+			Reconciliation::Stale
+		);
+		assert_eq!(pred.predicted(), predicted);
+	}
+
+	#[test]
+	fn history_is_bounded() {
+		let mut pred = Predictor::new(PawnState::default());
+
+		for _ in 0..(INPUT_HISTORY_LEN * 2) {
+			pred.predict(forward());
+		}
+
+		assert_eq!(pred.pending(), INPUT_HISTORY_LEN);
+	}
+
+	#[test]
+	fn authority_ignores_duplicates() {
+		let mut pred = Predictor::new(PawnState::default());
+		let mut auth = Authority::default();
+
+		for _ in 0..3 {
+			pred.predict(forward());
+			// The client re-sends everything unacknowledged every tick.
+			auth.receive(pred.unacknowledged());
+		}
+
+		assert_eq!(auth.ack(), Some(2));
+		assert_eq!(auth.state(), pred.predicted());
+	}
+
+	/// A one-way connection which delays every message by a pseudo-random
+	/// number of ticks (so they can arrive out of order) and drops some.
+	struct Channel<T> {
+		in_flight: Vec<(u32, T)>,
+		rng: u64,
+		loss_percent: u64,
+		max_delay: u64,
+	}
+
+	impl<T> Channel<T> {
+		#[must_use]
+		fn new(seed: u64, loss_percent: u64, max_delay: u64) -> Self {
+			Self {
+				in_flight: vec![],
+				rng: seed,
+				loss_percent,
+				max_delay,
+			}
+		}
+
+		#[must_use]
+		fn next_random(&mut self) -> u64 {
+			// xorshift64
+			self.rng ^= self.rng << 13;
+			self.rng ^= self.rng >> 7;
+			self.rng ^= self.rng << 17;
+			self.rng
+		}
+
+		fn send(&mut self, now: u32, msg: T) {
+			if self.next_random() % 100 < self.loss_percent {
+				return;
+			}
+
+			let delay = 1 + (self.next_random() % self.max_delay) as u32;
+			self.in_flight.push((now + delay, msg));
+		}
+
+		#[must_use]
+		fn receive(&mut self, now: u32) -> Vec<T> {
+			let mut ret = vec![];
+			let mut i = 0;
+
+			while i < self.in_flight.len() {
+				if self.in_flight[i].0 <= now {
+					ret.push(self.in_flight.remove(i).1);
+				} else {
+					i += 1;
+				}
+			}
+
+			ret
+		}
+	}
+
+	/// Runs a client and a server against each other for `ticks` ticks of
+	/// scripted input followed by enough idle ticks for every message to land,
+	/// then checks that both ended up in the same place.
+	/// Returns how many reconciliations of each kind the client went through.
+	#[must_use]
+	fn run_session(loss_percent: u64, max_delay: u64, ticks: u32) -> [usize; 3] {
+		let mut pred = Predictor::new(PawnState::default());
+		let mut auth = Authority::default();
+		let mut to_server = Channel::<Vec<Input>>::new(0x5EED, loss_percent, max_delay);
+		let mut to_client = Channel::<(PawnState, u32)>::new(0xD00D, loss_percent, max_delay);
+		let mut counts = [0; 3];
+
+		// Running in a wobbly circle, with a pause in the middle.
+		let script = |tick: u32| -> TicCmd {
+			if (ticks / 2..ticks / 2 + 10).contains(&tick) || tick >= ticks {
+				return TicCmd::default();
+			}
+
+			TicCmd {
+				forward: FORWARD_MOVE[1],
+				side: if (tick / 8) % 2 == 0 {
+					SIDE_MOVE[0]
+				} else {
+					-SIDE_MOVE[1]
+				},
+				turn: 256 + (tick % 5) as i16 * 64,
+				buttons: 0,
+			}
+		};
+
+		// Three seconds is plenty for friction to stop the pawn.
+		let settle = ticks + max_delay as u32 * 4 + 105;
+
+		for now in 0..settle {
+			let _ = pred.predict(script(now));
+			to_server.send(now, pred.unacknowledged().copied().collect());
+
+			for inputs in to_server.receive(now) {
+				auth.receive(&inputs);
+			}
+
+			if let Some(ack) = auth.ack() {
+				to_client.send(now, (auth.state(), ack));
+			}
+
+			for (state, ack) in to_client.receive(now) {
+				let i = match pred.reconcile(state, ack) {
+					Reconciliation::Smoothed => 0,
+					Reconciliation::Snapped => 1,
+					Reconciliation::Stale => 2,
+				};
+
+				counts[i] += 1;
+			}
+
+			pred.decay(0.5);
+		}
+
+		// Only idle input remains unacknowledged, and the pawn has stopped moving.
+		assert_eq!(pred.predicted(), auth.state());
+		assert_eq!(pred.visual(), auth.state());
+		assert_ne!(auth.state().pos, Vec3::ZERO);
+
+		counts
+	}
+
+	#[test]
+	fn delayed_channel() {
+		let [smoothed, snapped, _] = run_session(0, 6, 350);
+		assert!(smoothed > 0);
+		// Without loss, the server sees exactly what the client predicted with,
+		// so no correction is ever large enough to snap.
+		assert_eq!(snapped, 0);
+	}
+
+	#[test]
+	fn lossy_channel() {
+		let [smoothed, _, stale] = run_session(25, 8, 350);
+		assert!(smoothed > 0);
+		assert!(stale > 0);
+	}
+}
@@ -3,6 +3,7 @@
 use bevy::prelude::Resource;
 use indexmap::IndexMap;
 use nanorand::{Rng, SeedableRng, WyRand};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use util::string::ZString;
 
 /// Contains a map of named random number generators.
@@ -68,6 +69,46 @@ impl<B: Prng> RngCore<B> {
 	}
 }
 
+impl<B: Prng + Clone> RngCore<B> {
+	/// Summarizes the state of every contained PRNG (and its key) without
+	/// advancing any of them, such that two cores which will produce the same
+	/// sequences of numbers give the same hash. Useful for checking determinism.
+	#[must_use]
+	pub fn state_hash(&self) -> u64 {
+		use std::hash::{Hash, Hasher};
+
+		let mut hasher = rustc_hash::FxHasher::default();
+
+		for (key, prng) in &self.prngs {
+			key.hash(&mut hasher);
+			prng.clone().range_i64(i64::MIN, i64::MAX).hash(&mut hasher);
+		}
+
+		hasher.finish()
+	}
+}
+
+/// Serializes as a sequence of `(key, PRNG)` pairs, in insertion order,
+/// so that a save game restores every stream exactly where it left off.
+impl<B: Prng + Serialize> Serialize for RngCore<B> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_seq(self.prngs.iter().map(|(key, prng)| (key.0.as_str(), prng)))
+	}
+}
+
+impl<'de, B: Prng + Deserialize<'de>> Deserialize<'de> for RngCore<B> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let pairs = Vec::<(String, B)>::deserialize(deserializer)?;
+
+		Ok(Self {
+			prngs: pairs
+				.into_iter()
+				.map(|(key, prng)| (ZString(key), prng))
+				.collect(),
+		})
+	}
+}
+
 impl<B: Prng> std::ops::Index<usize> for RngCore<B> {
 	type Output = B;
 
@@ -164,3 +205,157 @@ impl Prng for WyRand {
 		self.generate()
 	}
 }
+
+// Doom ////////////////////////////////////////////////////////////////////////
+
+/// Vanilla Doom's `rndtable`, from `m_random.c`.
+#[rustfmt::skip]
+const RNDTABLE: [u8; 256] = [
+	  0,   8, 109, 220, 222, 241, 149, 107,  75, 248, 254, 140,  16,  66,  74,  21,
+	211,  47,  80, 242, 154,  27, 205, 128, 161,  89,  77,  36,  95, 110,  85,  48,
+	212, 140, 211, 249,  22,  79, 200,  50,  28, 188,  52, 140, 202, 120,  68, 145,
+	 62,  70, 184, 190,  91, 197, 152, 224, 149, 104,  25, 178, 252, 182, 202, 182,
+	141, 197,   4,  81, 181, 242, 145,  42,  39, 227, 156, 198, 225, 193, 219,  93,
+	122, 175, 249,   0, 175, 143,  70, 239,  46, 246, 163,  53, 163, 109, 168, 135,
+	  2, 235,  25,  92,  20, 145, 138,  77,  69, 166,  78, 176, 173, 212, 166, 113,
+	 94, 161,  41,  50, 239,  49, 111, 164,  70,  60,   2,  37, 171,  75, 136, 156,
+	 11,  56,  42, 146, 138, 229,  73, 146,  77,  61,  98, 196, 135, 106,  63, 197,
+	195,  86,  96, 203, 113, 101, 170, 247, 181, 113,  80, 250, 108,   7, 255, 237,
+	129, 226,  79, 107, 112, 166, 103, 241,  24, 223, 239, 120, 198,  58,  60,  82,
+	128,   3, 184,  66, 143, 224, 145, 224,  81, 206, 163,  45,  63,  90, 168, 114,
+	 59,  33, 159,  95,  28, 139, 123,  98, 125, 196,  15,  70, 194, 253,  54,  14,
+	109, 226,  71,  17, 161,  93, 186,  87, 244, 138,  20,  52, 123, 251,  26,  36,
+	 17,  46,  52, 231, 232,  76,  31, 221,  84,  37, 216, 165, 212, 106, 197, 242,
+	 98,  43,  39, 175, 254, 145, 190,  84, 118, 222, 187, 136, 120, 163, 236, 249,
+];
+
+/// A reproduction of vanilla Doom's `P_Random`, which walks [a fixed table](RNDTABLE)
+/// of 256 bytes. Its entire state is one index into that table, which makes it
+/// trivial to serialize and compare, and gives demo-compatible results for
+/// `range_i64(0, 255)`.
+///
+/// Ranges wider than a byte are built from several consecutive table entries.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DoomRng {
+	index: u8,
+}
+
+impl DoomRng {
+	/// Equivalent to vanilla's `P_Random`.
+	#[must_use]
+	pub fn next_byte(&mut self) -> u8 {
+		self.index = self.index.wrapping_add(1);
+		RNDTABLE[self.index as usize]
+	}
+
+	/// Returns a number in the range `0..=span`.
+	#[must_use]
+	fn below_incl(&mut self, span: u64) -> u64 {
+		if span <= u8::MAX as u64 {
+			return (self.next_byte() as u64) % (span + 1);
+		}
+
+		let mut ret = 0;
+
+		for _ in 0..8 {
+			ret = (ret << 8) | (self.next_byte() as u64);
+		}
+
+		match span.checked_add(1) {
+			Some(modulus) => ret % modulus,
+			None => ret,
+		}
+	}
+}
+
+impl Prng for DoomRng {
+	/// Vanilla only ever "seeds" by resetting the index to 0 (see `M_ClearRandom`),
+	/// so all eight bytes of `seed` get folded into a starting index.
+	fn seed(&mut self, seed: u64) {
+		self.index = seed.to_le_bytes().into_iter().fold(0, |acc, b| acc ^ b);
+	}
+
+	fn range_i64(&mut self, min_incl: i64, max_incl: i64) -> i64 {
+		let span = max_incl.wrapping_sub(min_incl) as u64;
+		min_incl.wrapping_add(self.below_incl(span) as i64)
+	}
+
+	fn range_f64(&mut self, min_incl: f64, max_incl: f64) -> f64 {
+		min_incl + ((self.next_byte() as f64) / (u8::MAX as f64)) * (max_incl - min_incl)
+	}
+
+	fn range_usize(&mut self, min_incl: usize, max_incl: usize) -> usize {
+		min_incl + (self.below_incl((max_incl - min_incl) as u64) as usize)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn doom_table() {
+		let mut prng = DoomRng::default();
+		let first: Vec<_> = (0..4).map(|_| prng.range_i64(0, 255)).collect();
+		assert_eq!(first, [8, 109, 220, 222]);
+
+		// The table wraps around after 256 calls.
+		for _ in 4..256 {
+			let _ = prng.next_byte();
+		}
+
+		assert_eq!(prng, DoomRng::default());
+	}
+
+	#[test]
+	fn same_seed_same_sequence() {
+		#[must_use]
+		fn core(seed: u64) -> RngCore<DoomRng> {
+			let mut core = RngCore::<DoomRng>::default();
+			core.get_default().seed(seed);
+			core.add_default("ai".to_string());
+			core.get("ai").seed(seed ^ 0xABCD);
+			core
+		}
+
+		let mut a = core(1234);
+		let mut b = core(1234);
+
+		for _ in 0..1000 {
+			assert_eq!(
+				a.get_default().range_i64(-50, 50),
+				b.get_default().range_i64(-50, 50)
+			);
+			assert_eq!(
+				a.get("ai").range_i64(i64::MIN, i64::MAX),
+				b.get("ai").range_i64(i64::MIN, i64::MAX)
+			);
+		}
+
+		assert_eq!(a.state_hash(), b.state_hash());
+
+		// Streams are independent; drawing from one leaves the other untouched.
+		let before = *a.get("ai");
+		let _ = a.get_default().range_usize(0, 9);
+		assert_eq!(*a.get("ai"), before);
+	}
+
+	#[test]
+	fn serde_roundtrip() {
+		let mut core = RngCore::<DoomRng>::default();
+		core.add_default("weapon".to_string());
+		core.get("weapon").seed(77);
+		let _ = core.get_default().next_byte();
+
+		let json = serde_json::to_string(&core).unwrap();
+		let mut restored: RngCore<DoomRng> = serde_json::from_str(&json).unwrap();
+		assert_eq!(restored.state_hash(), core.state_hash());
+
+		for _ in 0..64 {
+			assert_eq!(
+				restored.get("weapon").next_byte(),
+				core.get("weapon").next_byte()
+			);
+		}
+	}
+}
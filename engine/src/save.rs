@@ -0,0 +1,264 @@
+//! Save game serialization.
+//!
+//! Level state gets saved as its differences from the level as it was loaded,
+//! so that saves stay small and the parts of a level which never change during
+//! play (e.g. its lines) always come from the user's content.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::world::level::{Level, Sector, SideDef};
+
+/// The first bytes of every blob written by [`serialize_level`].
+pub const LEVEL_MAGIC: [u8; 4] = *b"VTLV";
+/// Increment this whenever [`LevelDelta`] (or anything it contains) changes
+/// in a way that makes older saves unreadable.
+pub const LEVEL_FORMAT_VERSION: u16 = 1;
+
+/// Everything about a [`Level`] which can change during play, as elements
+/// which differ from those of the level as it was loaded. Each element is
+/// paired with its index in the corresponding array of the [`Level`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelDelta {
+	pub vertices: Vec<(usize, Vec4)>,
+	pub sidedefs: Vec<(usize, SideDef)>,
+	pub sectors: Vec<(usize, Sector)>,
+}
+
+impl LevelDelta {
+	/// `base` and `current` are expected to be the same level,
+	/// and so to have the same number of vertices, sidedefs, and sectors.
+	#[must_use]
+	pub fn diff(base: &Level, current: &Level) -> Self {
+		#[must_use]
+		fn changed<T: PartialEq + Clone>(base: &[T], current: &[T]) -> Vec<(usize, T)> {
+			base.iter()
+				.zip(current)
+				.enumerate()
+				.filter(|(_, (b, c))| b != c)
+				.map(|(i, (_, c))| (i, c.clone()))
+				.collect()
+		}
+
+		Self {
+			vertices: base
+				.vertices
+				.iter()
+				.zip(&current.vertices)
+				.enumerate()
+				.filter(|(_, (b, c))| b != c)
+				.map(|(i, (_, c))| (i, c.0))
+				.collect(),
+			sidedefs: changed(&base.sidedefs, &current.sidedefs),
+			sectors: changed(&base.sectors, &current.sectors),
+		}
+	}
+
+	/// If any index is out of bounds for `level`, the delta must have come from
+	/// another level; `level` is left untouched and an error is returned.
+	pub fn apply(&self, level: &mut Level) -> Result<(), SaveError> {
+		let in_bounds = self.vertices.iter().all(|(i, _)| *i < level.vertices.len())
+			&& self.sidedefs.iter().all(|(i, _)| *i < level.sidedefs.len())
+			&& self.sectors.iter().all(|(i, _)| *i < level.sectors.len());
+
+		if !in_bounds {
+			return Err(SaveError::LevelMismatch);
+		}
+
+		for (i, pos) in &self.vertices {
+			level.vertices[*i].0 = *pos;
+		}
+
+		for (i, sidedef) in &self.sidedefs {
+			level.sidedefs[*i] = *sidedef;
+		}
+
+		for (i, sector) in &self.sectors {
+			level.sectors[*i] = sector.clone();
+		}
+
+		Ok(())
+	}
+}
+
+/// Writes [`LEVEL_MAGIC`], then [`LEVEL_FORMAT_VERSION`] (little-endian),
+/// then the [`LevelDelta`] between `base` and `current`.
+#[must_use]
+pub fn serialize_level(base: &Level, current: &Level) -> Vec<u8> {
+	let delta = LevelDelta::diff(base, current);
+	let mut ret = Vec::with_capacity(LEVEL_MAGIC.len() + 2);
+	ret.extend_from_slice(&LEVEL_MAGIC);
+	ret.extend_from_slice(&LEVEL_FORMAT_VERSION.to_le_bytes());
+	serde_json::to_writer(&mut ret, &delta).expect("level delta serialization failed");
+	ret
+}
+
+/// Rebuilds the state saved by [`serialize_level`] on top of a copy of `base`,
+/// which should be the same level as was given to [`serialize_level`].
+pub fn deserialize_level(base: &Level, bytes: &[u8]) -> Result<Level, SaveError> {
+	let Some(rest) = bytes.strip_prefix(&LEVEL_MAGIC) else {
+		return Err(SaveError::NotASave);
+	};
+
+	if rest.len() < 2 {
+		return Err(SaveError::NotASave);
+	}
+
+	let (version, payload) = rest.split_at(2);
+	let version = u16::from_le_bytes([version[0], version[1]]);
+
+	if version != LEVEL_FORMAT_VERSION {
+		return Err(SaveError::Version(version));
+	}
+
+	let delta: LevelDelta = serde_json::from_slice(payload).map_err(SaveError::Malformed)?;
+	let mut ret = base.clone();
+	delta.apply(&mut ret)?;
+	Ok(ret)
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+	/// The save indexes past the end of the level it is being loaded into.
+	LevelMismatch,
+	Malformed(serde_json::Error),
+	/// The input does not start with [`LEVEL_MAGIC`] and a version number.
+	NotASave,
+	/// The save was written with a format version other than [`LEVEL_FORMAT_VERSION`].
+	Version(u16),
+}
+
+impl std::error::Error for SaveError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Malformed(err) => Some(err),
+			Self::LevelMismatch | Self::NotASave | Self::Version(_) => None,
+		}
+	}
+}
+
+impl std::fmt::Display for SaveError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::LevelMismatch => write!(f, "save game does not belong to this level"),
+			Self::Malformed(err) => write!(f, "save game is malformed: {err}"),
+			Self::NotASave => write!(f, "not a VileTech level save"),
+			Self::Version(v) => write!(
+				f,
+				"save game format version {v} is unsupported (expected {LEVEL_FORMAT_VERSION})"
+			),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use data::level::read::LineFlags;
+	use util::Id8;
+
+	use crate::world::{level::LineDef, LevelFormat, Vertex};
+
+	use super::*;
+
+	#[must_use]
+	fn level() -> Level {
+		let sector = |height_ceiling| Sector {
+			height_floor: 0.0,
+			height_ceiling,
+			tex_floor: Id8::try_from("FLOOR4_8").ok(),
+			tex_ceiling: Id8::try_from("CEIL3_5").ok(),
+			light_level: 160,
+			special: 0,
+			ids: vec![],
+			colormap: None,
+		};
+
+		Level {
+			format: LevelFormat::Doom,
+			vertices: vec![
+				Vertex(Vec4::new(0.0, 0.0, 0.0, 0.0)),
+				Vertex(Vec4::new(64.0, 0.0, 0.0, 0.0)),
+			],
+			linedefs: vec![LineDef {
+				vert_start: 0,
+				vert_end: 1,
+				flags: LineFlags::empty(),
+				flags_hexen: 0,
+				special: 1,
+				args: [0; 5],
+				ids: vec![],
+				side_right: 0,
+				side_left: Some(1),
+			}],
+			sidedefs: vec![
+				SideDef {
+					offset: IVec2::ZERO,
+					tex_top: Id8::try_from("BIGDOOR1").ok(),
+					tex_mid: None,
+					tex_bottom: None,
+					sector: 0,
+				},
+				SideDef {
+					offset: IVec2::ZERO,
+					tex_top: None,
+					tex_mid: None,
+					tex_bottom: None,
+					sector: 1,
+				},
+			],
+			sectors: vec![sector(128.0), sector(0.0)],
+			things: vec![],
+		}
+	}
+
+	#[test]
+	fn roundtrip() {
+		let base = level();
+		let mut current = base.clone();
+		// The door opens...
+		current.sectors[1].height_ceiling = 124.0;
+		current.sectors[1].light_level = 192;
+		current.sidedefs[0].offset = IVec2::new(8, 0);
+
+		let bytes = serialize_level(&base, &current);
+		let delta = LevelDelta::diff(&base, &current);
+		assert!(delta.vertices.is_empty());
+		assert_eq!(delta.sidedefs.len(), 1);
+		assert_eq!(delta.sectors.len(), 1);
+
+		let restored = deserialize_level(&base, &bytes).unwrap();
+		assert_eq!(restored, current);
+		assert_eq!(restored.sectors[1].height_ceiling, 124.0);
+		assert_eq!(restored.sectors[0], base.sectors[0]);
+	}
+
+	#[test]
+	fn rejection() {
+		let base = level();
+		let mut bytes = serialize_level(&base, &base);
+
+		assert!(matches!(
+			deserialize_level(&base, b"PWAD"),
+			Err(SaveError::NotASave)
+		));
+
+		bytes[LEVEL_MAGIC.len()] = bytes[LEVEL_MAGIC.len()].wrapping_add(1);
+
+		assert!(matches!(
+			deserialize_level(&base, &bytes),
+			Err(SaveError::Version(v)) if v == LEVEL_FORMAT_VERSION + 1
+		));
+
+		// A save from a level with more sectors than this one.
+		let mut other = base.clone();
+		other.sectors.push(other.sectors[0].clone());
+		let mut changed = other.clone();
+		changed.sectors[2].height_floor = 16.0;
+		let bytes = serialize_level(&other, &changed);
+
+		assert!(matches!(
+			deserialize_level(&base, &bytes),
+			Err(SaveError::LevelMismatch)
+		));
+	}
+}
@@ -0,0 +1,356 @@
+//! Bridge through which engine systems notify clientside scripts of events.
+//!
+//! Engine systems [push](EventBridge::push) typed [`Event`]s into a bounded
+//! queue; once per frame, [`pump`] drains it and invokes every subscribed
+//! callback. A scripting frontend exposes [`EventBridge::subscribe`] and
+//! [`EventBridge::unsubscribe`] to scripts (e.g. as `events.subscribe(name, fn)`
+//! and `events.unsubscribe(id)`), wrapping each script function in a [`Callback`].
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+/// The tracing target under which callback failures are reported.
+pub const LOG_TARGET: &str = "script";
+
+/// The key of the [`Sim`](crate::sim::Sim) PRNG stream drawn from by scripts'
+/// `random(min, max)`. See [`Sim::random`](crate::sim::Sim::random).
+pub const RNG_KEY: &str = "script";
+
+/// Each variant documents the shape of the plain table it marshals to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+	/// `{ name: string }`
+	LevelActivate { name: Box<str> },
+	/// `{ name: string }`
+	LevelExit { name: Box<str> },
+	/// `{ mounts: integer }`
+	LoadComplete { mounts: usize },
+	/// `{ line: string }`
+	ConsoleLine { line: Box<str> },
+	/// `{ delta: number }`, in seconds.
+	Tick { delta: f32 },
+}
+
+impl Event {
+	/// Every name accepted by [`EventBridge::subscribe`].
+	pub const NAMES: &'static [&'static str] = &[
+		"level_activate",
+		"level_exit",
+		"load_complete",
+		"console_line",
+		"tick",
+	];
+
+	/// The name scripts use to subscribe to this kind of event.
+	#[must_use]
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::LevelActivate { .. } => "level_activate",
+			Self::LevelExit { .. } => "level_exit",
+			Self::LoadComplete { .. } => "load_complete",
+			Self::ConsoleLine { .. } => "console_line",
+			Self::Tick { .. } => "tick",
+		}
+	}
+}
+
+/// Returned by [`EventBridge::subscribe`]; pass to [`EventBridge::unsubscribe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+impl SubscriptionId {
+	#[must_use]
+	pub fn to_raw(self) -> u64 {
+		self.0
+	}
+
+	#[must_use]
+	pub fn from_raw(raw: u64) -> Self {
+		Self(raw)
+	}
+}
+
+pub type CallbackError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A script function, already wrapped in a protected call by its frontend.
+pub type Callback = Box<dyn FnMut(&Event) -> Result<(), CallbackError> + Send + Sync>;
+
+/// See the [module-level documentation](self).
+#[derive(Resource)]
+pub struct EventBridge {
+	queue: VecDeque<Event>,
+	/// The maximum number of events that can be held in `queue` before old
+	/// elements get popped off the front to make room for new ones.
+	queue_cap: usize,
+	/// How many events have been dropped since the last pump.
+	dropped: usize,
+	/// Always in subscription order, since IDs are never re-used.
+	subs: Vec<Subscription>,
+	next_id: u64,
+}
+
+struct Subscription {
+	id: SubscriptionId,
+	event: &'static str,
+	callback: Callback,
+	/// Reset upon every successful invocation.
+	failures: u8,
+}
+
+impl EventBridge {
+	/// After this many consecutive errors, a callback gets unsubscribed.
+	pub const MAX_FAILURES: u8 = 3;
+
+	#[must_use]
+	pub fn new(queue_cap: usize) -> Self {
+		Self {
+			queue: VecDeque::with_capacity(queue_cap),
+			queue_cap,
+			dropped: 0,
+			subs: vec![],
+			next_id: 0,
+		}
+	}
+
+	/// Returns `None` if `event` is not one of [`Event::NAMES`].
+	pub fn subscribe(&mut self, event: &str, callback: Callback) -> Option<SubscriptionId> {
+		let event = Event::NAMES.iter().copied().find(|n| *n == event)?;
+		let id = SubscriptionId(self.next_id);
+		self.next_id += 1;
+
+		self.subs.push(Subscription {
+			id,
+			event,
+			callback,
+			failures: 0,
+		});
+
+		Some(id)
+	}
+
+	/// Returns `false` if no subscription exists under `id`.
+	pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+		let Ok(i) = self.subs.binary_search_by_key(&id.0, |sub| sub.id.0) else {
+			return false;
+		};
+
+		self.subs.remove(i);
+		true
+	}
+
+	/// If the queue is full, the oldest event gets dropped.
+	pub fn push(&mut self, event: Event) {
+		if self.queue_cap == 0 {
+			self.dropped += 1;
+			return;
+		}
+
+		while self.queue.len() >= self.queue_cap {
+			let _ = self.queue.pop_front();
+			self.dropped += 1;
+		}
+
+		self.queue.push_back(event);
+	}
+
+	/// Drains the queue, invoking callbacks for each event in subscription order.
+	/// Returns the number of callbacks invoked.
+	pub fn pump(&mut self) -> usize {
+		if self.dropped > 0 {
+			warn!(
+				target: LOG_TARGET,
+				"Script event queue overflowed; {} events dropped.", self.dropped
+			);
+
+			self.dropped = 0;
+		}
+
+		let mut invoked = 0;
+
+		while let Some(event) = self.queue.pop_front() {
+			let name = event.name();
+
+			for sub in self.subs.iter_mut().filter(|sub| sub.event == name) {
+				invoked += 1;
+
+				let Err(err) = (sub.callback)(&event) else {
+					sub.failures = 0;
+					continue;
+				};
+
+				sub.failures += 1;
+
+				error!(
+					target: LOG_TARGET,
+					"`{name}` callback {} failed ({}/{}): {err}",
+					sub.id.0,
+					sub.failures,
+					Self::MAX_FAILURES
+				);
+			}
+
+			self.subs.retain(|sub| {
+				if sub.failures < Self::MAX_FAILURES {
+					return true;
+				}
+
+				warn!(
+					target: LOG_TARGET,
+					"Unsubscribed `{}` callback {} after repeated failures.", sub.event, sub.id.0
+				);
+
+				false
+			});
+		}
+
+		invoked
+	}
+
+	#[must_use]
+	pub fn subscription_count(&self) -> usize {
+		self.subs.len()
+	}
+
+	#[must_use]
+	pub fn queue_len(&self) -> usize {
+		self.queue.len()
+	}
+}
+
+impl Default for EventBridge {
+	fn default() -> Self {
+		Self::new(256)
+	}
+}
+
+impl std::fmt::Debug for EventBridge {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("EventBridge")
+			.field("queue", &self.queue)
+			.field("queue_cap", &self.queue_cap)
+			.field("dropped", &self.dropped)
+			.field("subs", &self.subs.len())
+			.field("next_id", &self.next_id)
+			.finish()
+	}
+}
+
+/// Queues a [`Event::Tick`] and then pumps the bridge.
+/// Meant to be run once per frame in the client's `Update` schedule.
+pub fn pump(time: Res<Time>, mut bridge: ResMut<EventBridge>) {
+	bridge.push(Event::Tick {
+		delta: time.delta_seconds(),
+	});
+
+	let _ = bridge.pump();
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::{Arc, Mutex};
+
+	use super::*;
+
+	type Log = Arc<Mutex<Vec<(u8, Event)>>>;
+
+	#[must_use]
+	fn recorder(log: &Log, tag: u8) -> Callback {
+		let log = log.clone();
+
+		Box::new(move |event| {
+			log.lock().unwrap().push((tag, event.clone()));
+			Ok(())
+		})
+	}
+
+	#[test]
+	fn invocation_order() {
+		let log = Log::default();
+		let mut bridge = EventBridge::default();
+
+		bridge.subscribe("tick", recorder(&log, 0)).unwrap();
+		bridge.subscribe("console_line", recorder(&log, 1)).unwrap();
+		bridge.subscribe("tick", recorder(&log, 2)).unwrap();
+		assert!(bridge.subscribe("tock", recorder(&log, 3)).is_none());
+
+		bridge.push(Event::Tick { delta: 0.5 });
+		bridge.push(Event::ConsoleLine {
+			line: "map e1m1".into(),
+		});
+		assert_eq!(bridge.pump(), 3);
+		assert_eq!(bridge.queue_len(), 0);
+
+		let log = log.lock().unwrap();
+		let tags: Vec<_> = log.iter().map(|(tag, _)| *tag).collect();
+		assert_eq!(tags, [0, 2, 1]);
+		assert_eq!(log[0].1, Event::Tick { delta: 0.5 });
+		assert_eq!(
+			log[2].1,
+			Event::ConsoleLine {
+				line: "map e1m1".into()
+			}
+		);
+	}
+
+	#[test]
+	fn error_isolation() {
+		let log = Log::default();
+		let mut bridge = EventBridge::default();
+
+		bridge
+			.subscribe("tick", Box::new(|_| Err("always fails".into())))
+			.unwrap();
+		bridge.subscribe("tick", recorder(&log, 1)).unwrap();
+
+		for i in 0..(EventBridge::MAX_FAILURES as usize) {
+			assert_eq!(bridge.subscription_count(), 2);
+			bridge.push(Event::Tick { delta: 0.0 });
+			let _ = bridge.pump();
+			assert_eq!(log.lock().unwrap().len(), i + 1);
+		}
+
+		// The failing callback has been automatically unsubscribed.
+		assert_eq!(bridge.subscription_count(), 1);
+		bridge.push(Event::Tick { delta: 0.0 });
+		assert_eq!(bridge.pump(), 1);
+	}
+
+	#[test]
+	fn unsubscription() {
+		let log = Log::default();
+		let mut bridge = EventBridge::default();
+
+		let id0 = bridge
+			.subscribe("level_activate", recorder(&log, 0))
+			.unwrap();
+		let _ = bridge
+			.subscribe("level_activate", recorder(&log, 1))
+			.unwrap();
+		assert!(bridge.unsubscribe(id0));
+		assert!(!bridge.unsubscribe(id0));
+
+		bridge.push(Event::LevelActivate {
+			name: "MAP01".into(),
+		});
+		assert_eq!(bridge.pump(), 1);
+		assert_eq!(log.lock().unwrap()[0].0, 1);
+	}
+
+	#[test]
+	fn overflow() {
+		let log = Log::default();
+		let mut bridge = EventBridge::new(2);
+		bridge
+			.subscribe("load_complete", recorder(&log, 0))
+			.unwrap();
+
+		for mounts in 0..4 {
+			bridge.push(Event::LoadComplete { mounts });
+		}
+
+		assert_eq!(bridge.queue_len(), 2);
+		assert_eq!(bridge.pump(), 2);
+		assert_eq!(log.lock().unwrap()[0].1, Event::LoadComplete { mounts: 2 });
+	}
+}
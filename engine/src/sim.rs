@@ -7,26 +7,119 @@
 //! [actor]: crate::actor
 //! [world]: crate::world
 
+pub mod ai;
+pub mod damage;
+pub mod interp;
+pub mod movement;
+pub mod portal;
 pub mod skill;
+pub mod spatial;
+pub mod thing;
+pub mod weapon;
 
-use std::time::{Duration, Instant};
+use std::{
+	hash::{Hash, Hasher},
+	time::{Duration, Instant},
+};
 
 use bevy::prelude::*;
-use nanorand::WyRand;
 
-use crate::rng::RngCore;
+use crate::{
+	actor::{Blueprint, Readonly, StateMachine},
+	rng::{DoomRng, Prng, RngCore},
+	script,
+};
+
+/// Every gameplay PRNG stream. Serializable, for save games.
+pub type Rng = RngCore<DoomRng>;
 
 /// All gameplay simulation state.
 #[derive(Resource, Debug)]
 pub struct Sim {
 	timing: Timing,
-	_rng: RngCore<WyRand>,
+	rng: Rng,
+	/// Read by [`damage::apply_damage`] and anything else with skill-dependent behavior.
+	skill: skill::SkillDef,
+	/// `None` until a level's geometry has been loaded.
+	spatial: Option<spatial::SpatialQuery>,
 	/// Time spent in this hub thus far.
 	hub_ticks_elapsed: u64,
 	/// Time spent in this playthrough thus far.
 	ticks_elapsed: u64,
 }
 
+impl Sim {
+	/// The default PRNG gets seeded with `seed`. Every other named stream
+	/// (e.g. [`ai::RNG_KEY`]) gets seeded with `seed` mixed with its key.
+	#[must_use]
+	pub fn new(skill: skill::SkillDef, seed: u64) -> Self {
+		let mut rng = Rng::default();
+		rng.get_default().seed(seed);
+
+		for key in [ai::RNG_KEY, weapon::RNG_KEY, script::RNG_KEY] {
+			let mut hasher = rustc_hash::FxHasher::default();
+			key.hash(&mut hasher);
+			let mut prng = DoomRng::default();
+			prng.seed(seed ^ hasher.finish());
+			rng.add(key.to_string(), prng);
+		}
+
+		Self {
+			timing: Timing::default(),
+			rng,
+			skill,
+			spatial: None,
+			hub_ticks_elapsed: 0,
+			ticks_elapsed: 0,
+		}
+	}
+
+	/// See [`RngCore::state_hash`].
+	#[must_use]
+	pub fn rng_hash(&self) -> u64 {
+		self.rng.state_hash()
+	}
+
+	/// For writing a save game.
+	#[must_use]
+	pub fn rng(&self) -> &Rng {
+		&self.rng
+	}
+
+	/// For loading a save game. `rng` should come from [`Self::rng`].
+	pub fn restore_rng(&mut self, rng: Rng) {
+		self.rng = rng;
+	}
+
+	/// Backs the `random(min, max)` function exposed to scripts.
+	/// Draws from the [`script::RNG_KEY`] stream, so that scripts neither
+	/// depend on the wall clock nor perturb the streams used by engine systems.
+	#[must_use]
+	pub fn random(&mut self, min_incl: i64, max_incl: i64) -> i64 {
+		self.rng.get(script::RNG_KEY).range_i64(min_incl, max_incl)
+	}
+
+	#[must_use]
+	pub fn ticks_elapsed(&self) -> u64 {
+		self.ticks_elapsed
+	}
+
+	#[must_use]
+	pub fn spatial(&self) -> Option<&spatial::SpatialQuery> {
+		self.spatial.as_ref()
+	}
+
+	#[must_use]
+	pub fn spatial_mut(&mut self) -> Option<&mut spatial::SpatialQuery> {
+		self.spatial.as_mut()
+	}
+
+	/// To be called upon entering a level, and with `None` upon leaving it.
+	pub fn set_spatial(&mut self, spatial: Option<spatial::SpatialQuery>) {
+		self.spatial = spatial;
+	}
+}
+
 /// Separate from [`Sim`] for cleanliness.
 #[derive(Debug)]
 struct Timing {
@@ -91,3 +184,15 @@ pub fn tick(mut sim: ResMut<Sim>, mut fixed_time: ResMut<Time<Fixed>>) {
 		fixed_time.set_timestep(sim.timing.tick_interval());
 	}
 }
+
+/// Moves every active actor one tick further through its state sequence.
+pub fn advance_states(
+	blueprints: Res<Assets<Blueprint>>,
+	mut actors: Query<(&Readonly, &mut StateMachine), With<ActiveMarker>>,
+) {
+	for (readonly, mut fsm) in &mut actors {
+		if let Some(blueprint) = blueprints.get(&readonly.blueprint) {
+			fsm.tick(&blueprint.states);
+		}
+	}
+}
@@ -0,0 +1,926 @@
+//! Monster behavior: waking up, chasing a target, and deciding when to attack.
+//!
+//! This is vanilla Doom's logic (`A_Look`, `A_Chase`, `P_NewChaseDir`, et al.)
+//! translated onto the ECS. Each tick, [`think`] runs the [`Action`] of every
+//! monster which has just entered a new state. The logic itself lives in plain
+//! functions over a [`Thinker`] and its [`Surroundings`], so that it can be
+//! exercised without a [`World`].
+//!
+//! All randomness comes from the [`RNG_KEY`] stream, so that demos reproduce.
+
+use std::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_4, TAU};
+
+use bevy::{ecs::query::Has, prelude::*};
+
+use crate::{
+	actor::{
+		state::Action, Actor, Blueprint, Corpse, Health, PlayerPawn, Readonly, Solid, StateMachine,
+	},
+	rng::Prng,
+};
+
+use super::{
+	damage::{DamageEvent, DamageType},
+	interp::SimTransform,
+	spatial::{SectorIndex, SpatialQuery},
+	ActiveMarker, Sim,
+};
+
+/// The key of the [`Sim`]'s PRNG which all monster behavior draws from.
+pub const RNG_KEY: &str = "ai";
+
+/// Players this close to a monster get noticed even if they are behind it.
+pub const MELEE_RANGE: f32 = 64.0;
+
+/// The highest ledge a monster can walk up or down in one step.
+pub const MAX_STEP_HEIGHT: f32 = 24.0;
+
+/// Per-monster state for the look and chase logic.
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct AiState {
+	pub target: Option<Actor>,
+	/// `None` if the monster is not moving (vanilla's `DI_NODIR`).
+	pub move_dir: Option<MoveDir>,
+	/// Steps left to take in `move_dir` before picking a new direction.
+	pub move_count: i32,
+	/// Counts down once per [chase](chase); a monster can not make a
+	/// missile attack until this reaches 0.
+	pub reaction_time: i32,
+	/// From the spawning thing's ambush flag. An ambushing monster only wakes
+	/// up to noise if it can also see where the noise came from.
+	pub ambush: bool,
+	/// Set upon entering the `Missile` state sequence, so that the next chase
+	/// step moves the monster instead of letting it attack again right away.
+	pub just_attacked: bool,
+}
+
+impl AiState {
+	/// Shared by every monster in vanilla Doom.
+	pub const REACTION_TIME: i32 = 8;
+
+	#[must_use]
+	pub fn new(ambush: bool) -> Self {
+		Self {
+			reaction_time: Self::REACTION_TIME,
+			ambush,
+			..Default::default()
+		}
+	}
+}
+
+/// The eight directions in which monsters walk, counter-clockwise from east.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveDir {
+	East,
+	NorthEast,
+	North,
+	NorthWest,
+	West,
+	SouthWest,
+	South,
+	SouthEast,
+}
+
+impl MoveDir {
+	pub const ALL: [Self; 8] = [
+		Self::East,
+		Self::NorthEast,
+		Self::North,
+		Self::NorthWest,
+		Self::West,
+		Self::SouthWest,
+		Self::South,
+		Self::SouthEast,
+	];
+
+	#[must_use]
+	pub fn opposite(self) -> Self {
+		Self::ALL[(self as usize + 4) % 8]
+	}
+
+	#[must_use]
+	pub fn unit(self) -> Vec2 {
+		const D: f32 = FRAC_1_SQRT_2;
+
+		match self {
+			Self::East => Vec2::new(1.0, 0.0),
+			Self::NorthEast => Vec2::new(D, D),
+			Self::North => Vec2::new(0.0, 1.0),
+			Self::NorthWest => Vec2::new(-D, D),
+			Self::West => Vec2::new(-1.0, 0.0),
+			Self::SouthWest => Vec2::new(-D, -D),
+			Self::South => Vec2::new(0.0, -1.0),
+			Self::SouthEast => Vec2::new(D, -D),
+		}
+	}
+}
+
+/// An actor as seen by a monster, either as a potential target or as an obstacle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Body {
+	pub actor: Actor,
+	pub pos: Vec3,
+	pub radius: f32,
+	pub height: f32,
+}
+
+/// Which actor each sector last heard making noise (vanilla's `soundtarget`).
+#[derive(Resource, Debug, Default)]
+pub struct SoundAlerts {
+	/// One element per sector.
+	targets: Vec<Option<Actor>>,
+	/// Scratch space for [`SpatialQuery::flood_sound`].
+	traversed: Vec<u8>,
+}
+
+impl SoundAlerts {
+	/// Vanilla Doom's `P_NoiseAlert`. Every sector which the noise reaches
+	/// now considers `emitter` to be the last thing it heard.
+	pub fn noise_alert(&mut self, spatial: &SpatialQuery, origin: SectorIndex, emitter: Actor) {
+		self.targets.resize(spatial.sector_count(), None);
+		self.traversed.clear();
+		self.traversed.resize(spatial.sector_count(), 0);
+
+		spatial.flood_sound(origin, &mut self.traversed);
+
+		for (target, traversed) in self.targets.iter_mut().zip(&self.traversed) {
+			if *traversed != 0 {
+				*target = Some(emitter);
+			}
+		}
+	}
+
+	#[must_use]
+	pub fn heard(&self, sector: SectorIndex) -> Option<Actor> {
+		self.targets.get(usize::from(sector)).copied().flatten()
+	}
+
+	/// To be called upon leaving a level.
+	pub fn clear(&mut self) {
+		self.targets.clear();
+	}
+}
+
+/// Sent whenever an actor makes noise which can wake monsters (e.g. firing a weapon).
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct Noise {
+	pub emitter: Actor,
+	pub pos: Vec3,
+}
+
+/// Everything a monster's behavior reads besides the monster itself.
+#[derive(Debug, Clone, Copy)]
+pub struct Surroundings<'s> {
+	pub spatial: &'s SpatialQuery,
+	/// Every living player; these are what monsters target.
+	pub players: &'s [Body],
+	/// Every actor which blocks movement. May include the monster itself.
+	pub solids: &'s [Body],
+	pub alerts: &'s SoundAlerts,
+	/// From the skill's [`SkillDef::fast_monsters`](super::skill::SkillDef::fast_monsters).
+	pub fast: bool,
+}
+
+impl Surroundings<'_> {
+	#[must_use]
+	fn player(&self, actor: Actor) -> Option<&Body> {
+		self.players.iter().find(|p| p.actor == actor)
+	}
+}
+
+/// The monster whose behavior is being run.
+#[derive(Debug)]
+pub struct Thinker<'t> {
+	pub actor: Actor,
+	pub blueprint: &'t Blueprint,
+	pub fsm: &'t mut StateMachine,
+	pub transform: &'t mut SimTransform,
+	pub ai: &'t mut AiState,
+}
+
+impl Thinker<'_> {
+	#[must_use]
+	fn pos2(&self) -> Vec2 {
+		self.transform.pos.truncate()
+	}
+
+	#[must_use]
+	fn sees(&self, env: &Surroundings, body: &Body) -> bool {
+		env.spatial.line_of_sight(
+			eye(self.transform.pos, self.blueprint.height),
+			eye(body.pos, body.height),
+		)
+	}
+
+	fn enter(&mut self, label: &str) -> bool {
+		self.fsm.enter_label(&self.blueprint.states, label)
+	}
+}
+
+/// Runs the action of each state `me` has entered since the last call, including
+/// any states entered by those actions (e.g. [`look`] entering `See`).
+/// Damage dealt gets pushed onto `damages`.
+pub fn run_actions(
+	me: &mut Thinker,
+	env: &Surroundings,
+	prng: &mut impl Prng,
+	damages: &mut Vec<DamageEvent>,
+) {
+	// (RAT) Bounded so a cycle of states can not hang the sim.
+	for _ in 0..me.blueprint.states.len() {
+		if !me.fsm.take_entered() {
+			return;
+		}
+
+		let Some(action) = me
+			.blueprint
+			.states
+			.get(me.fsm.state)
+			.and_then(|def| def.action)
+		else {
+			continue;
+		};
+
+		match action {
+			Action::Look => {
+				let _ = look(me, env);
+			}
+			Action::Chase => chase(me, env, prng),
+			Action::FaceTarget | Action::MissileAttack => face_target(me, env),
+			Action::MeleeAttack => damages.extend(melee_attack(me, env, prng)),
+			// Weapon-only; see `super::weapon`.
+			Action::WeaponReady | Action::GunFlash | Action::FireHitscan => {}
+		}
+	}
+}
+
+/// Vanilla Doom's `A_Look`. Wakes up (entering the `See` state sequence) if
+/// a player was heard or if a player is in sight and not behind `me`.
+/// Returns `true` if `me` woke up.
+pub fn look(me: &mut Thinker, env: &Surroundings) -> bool {
+	let sector = env.spatial.sector_at(me.pos2());
+	let heard = env.alerts.heard(sector).and_then(|a| env.player(a));
+
+	let woke = match heard {
+		Some(body) if !me.ai.ambush || me.sees(env, body) => {
+			me.ai.target = Some(body.actor);
+			true
+		}
+		_ => look_for_players(me, env, false),
+	};
+
+	if woke {
+		let _ = me.enter("See");
+	}
+
+	woke
+}
+
+/// Vanilla Doom's `A_Chase`. Turns toward the current move direction, then
+/// either attacks (entering the `Melee` or `Missile` state sequence) or takes
+/// one step toward the target. If the target has died or gone away, `me` looks
+/// for a new one in all directions, and otherwise goes back to `Spawn`.
+///
+/// With [fast monsters](Surroundings::fast), the chasing state lasts half as
+/// long, and `me` can attack again without first taking any steps.
+pub fn chase(me: &mut Thinker, env: &Surroundings, prng: &mut impl Prng) {
+	if env.fast && me.fsm.tics > 0 {
+		me.fsm.tics = (me.fsm.tics / 2).max(1);
+	}
+
+	if me.ai.reaction_time > 0 {
+		me.ai.reaction_time -= 1;
+	}
+
+	if let Some(dir) = me.ai.move_dir {
+		me.transform.angle = turn_toward(me.transform.angle, dir);
+	}
+
+	let Some(target) = me.ai.target.and_then(|t| env.player(t)).copied() else {
+		me.ai.target = None;
+
+		if !look_for_players(me, env, true) {
+			let _ = me.enter("Spawn");
+		}
+
+		return;
+	};
+
+	if me.ai.just_attacked {
+		me.ai.just_attacked = false;
+
+		if !env.fast {
+			new_chase_dir(me, env, target.pos.truncate(), prng);
+		}
+
+		return;
+	}
+
+	if me.blueprint.states.find("Melee").is_some() && in_melee_range(me, env, &target) {
+		let _ = me.enter("Melee");
+		return;
+	}
+
+	if me.blueprint.states.find("Missile").is_some()
+		&& (env.fast || me.ai.move_count == 0)
+		&& should_fire(me, env, &target, prng)
+	{
+		let _ = me.enter("Missile");
+		me.ai.just_attacked = true;
+		return;
+	}
+
+	me.ai.move_count -= 1;
+
+	if me.ai.move_count < 0 || !step(me, env) {
+		new_chase_dir(me, env, target.pos.truncate(), prng);
+	}
+}
+
+/// Vanilla Doom's `A_FaceTarget`.
+pub fn face_target(me: &mut Thinker, env: &Surroundings) {
+	let Some(target) = me.ai.target.and_then(|t| env.player(t)) else {
+		return;
+	};
+
+	let delta = target.pos.truncate() - me.pos2();
+	me.transform.angle = delta.y.atan2(delta.x);
+}
+
+/// Faces the target and, if it is in melee range, hits it for
+/// [`Blueprint::melee_damage`] times a random number from 1 to 8.
+pub fn melee_attack(
+	me: &mut Thinker,
+	env: &Surroundings,
+	prng: &mut impl Prng,
+) -> Option<DamageEvent> {
+	face_target(me, env);
+
+	let target = me.ai.target.and_then(|t| env.player(t))?;
+
+	if !in_melee_range(me, env, target) {
+		return None;
+	}
+
+	let amount = me.blueprint.melee_damage * (prng.range_i64(1, 8) as i32);
+
+	Some(DamageEvent {
+		target: target.actor,
+		inflictor: Some(me.actor),
+		source: Some(me.actor),
+		amount,
+		damage_type: DamageType::Normal,
+	})
+}
+
+/// Vanilla Doom's `P_LookForPlayers`. Unless `all_around` is `true`, players
+/// behind `me` are ignored if they are farther away than [`MELEE_RANGE`].
+fn look_for_players(me: &mut Thinker, env: &Surroundings, all_around: bool) -> bool {
+	for player in env.players {
+		if !me.sees(env, player) {
+			continue;
+		}
+
+		if !all_around {
+			let to = player.pos.truncate() - me.pos2();
+			let facing = Vec2::new(me.transform.angle.cos(), me.transform.angle.sin());
+
+			if facing.dot(to) < 0.0 && to.length() > MELEE_RANGE {
+				continue;
+			}
+		}
+
+		me.ai.target = Some(player.actor);
+		return true;
+	}
+
+	false
+}
+
+#[must_use]
+fn in_melee_range(me: &Thinker, env: &Surroundings, target: &Body) -> bool {
+	let dist = target.pos.truncate().distance(me.pos2());
+	dist < me.blueprint.melee_range + target.radius && me.sees(env, target)
+}
+
+/// Vanilla Doom's `P_CheckMissileRange`: the farther away the target is,
+/// the less likely a missile attack gets made.
+#[must_use]
+fn should_fire(me: &Thinker, env: &Surroundings, target: &Body, prng: &mut impl Prng) -> bool {
+	if !me.sees(env, target) || me.ai.reaction_time > 0 {
+		return false;
+	}
+
+	let mut dist = target.pos.truncate().distance(me.pos2()) - MELEE_RANGE;
+
+	if me.blueprint.states.find("Melee").is_none() {
+		dist -= 128.0;
+	}
+
+	let dist = dist.min(200.0);
+	(prng.range_i64(0, 255) as f32) >= dist
+}
+
+/// Vanilla Doom's `P_NewChaseDir`. Tries to walk straight (or diagonally)
+/// toward `goal`, then along either axis toward it, then in the previous
+/// direction, then in every other direction in a random order, and only
+/// turns around as a last resort.
+fn new_chase_dir(me: &mut Thinker, env: &Surroundings, goal: Vec2, prng: &mut impl Prng) {
+	let old = me.ai.move_dir;
+	let turnaround = old.map(MoveDir::opposite);
+	let delta = goal - me.pos2();
+
+	let mut d1 = if delta.x > 10.0 {
+		Some(MoveDir::East)
+	} else if delta.x < -10.0 {
+		Some(MoveDir::West)
+	} else {
+		None
+	};
+
+	let mut d2 = if delta.y < -10.0 {
+		Some(MoveDir::South)
+	} else if delta.y > 10.0 {
+		Some(MoveDir::North)
+	} else {
+		None
+	};
+
+	if d1.is_some() && d2.is_some() {
+		let diagonal = match (delta.y < 0.0, delta.x > 0.0) {
+			(false, false) => MoveDir::NorthWest,
+			(false, true) => MoveDir::NorthEast,
+			(true, false) => MoveDir::SouthWest,
+			(true, true) => MoveDir::SouthEast,
+		};
+
+		if Some(diagonal) != turnaround && try_walk(me, env, diagonal, prng) {
+			return;
+		}
+	}
+
+	if prng.range_i64(0, 255) > 200 || delta.y.abs() > delta.x.abs() {
+		std::mem::swap(&mut d1, &mut d2);
+	}
+
+	if d1 == turnaround {
+		d1 = None;
+	}
+
+	if d2 == turnaround {
+		d2 = None;
+	}
+
+	for dir in [d1, d2, old].into_iter().flatten() {
+		if try_walk(me, env, dir, prng) {
+			return;
+		}
+	}
+
+	let mut search = MoveDir::ALL;
+
+	if prng.boolean() {
+		search.reverse();
+	}
+
+	for dir in search {
+		if Some(dir) != turnaround && try_walk(me, env, dir, prng) {
+			return;
+		}
+	}
+
+	if let Some(dir) = turnaround {
+		if try_walk(me, env, dir, prng) {
+			return;
+		}
+	}
+
+	me.ai.move_dir = None;
+}
+
+/// Vanilla Doom's `P_TryWalk`.
+fn try_walk(me: &mut Thinker, env: &Surroundings, dir: MoveDir, prng: &mut impl Prng) -> bool {
+	me.ai.move_dir = Some(dir);
+
+	if !step(me, env) {
+		return false;
+	}
+
+	me.ai.move_count = prng.range_i64(0, 15) as i32;
+	true
+}
+
+/// Vanilla Doom's `P_Move`: one step of [`Blueprint::speed`] in the current
+/// move direction, if nothing is in the way. Returns `false` if blocked.
+fn step(me: &mut Thinker, env: &Surroundings) -> bool {
+	let Some(dir) = me.ai.move_dir else {
+		return false;
+	};
+
+	let to = me.pos2() + dir.unit() * me.blueprint.speed;
+
+	let Some(floor) = try_move(me, env, to) else {
+		return false;
+	};
+
+	me.transform.pos = to.extend(floor);
+	true
+}
+
+/// Returns the floor height at `to` if `me` can move there.
+#[must_use]
+fn try_move(me: &Thinker, env: &Surroundings, to: Vec2) -> Option<f32> {
+	let radius = me.blueprint.radius;
+	let height = me.blueprint.height;
+	let z = me.transform.pos.z;
+
+	let opening = env.spatial.check_position(to, radius, true)?;
+
+	if opening.ceiling - opening.floor < height
+		|| opening.ceiling - z < height
+		|| opening.floor - z > MAX_STEP_HEIGHT
+		|| opening.floor - opening.dropoff > MAX_STEP_HEIGHT
+	{
+		return None;
+	}
+
+	let blocked = env.solids.iter().any(|solid| {
+		let reach = solid.radius + radius;
+
+		solid.actor != me.actor
+			&& (solid.pos.x - to.x).abs() < reach
+			&& (solid.pos.y - to.y).abs() < reach
+	});
+
+	(!blocked).then_some(opening.floor)
+}
+
+/// Vanilla Doom's chase turning: snaps `angle` to the nearest eighth of a turn,
+/// then turns one eighth of a turn toward `dir`.
+#[must_use]
+fn turn_toward(angle: f32, dir: MoveDir) -> f32 {
+	let current = ((angle.rem_euclid(TAU) / FRAC_PI_4).round() as i32) % 8;
+	let delta = (current - dir as i32 + 4).rem_euclid(8) - 4;
+	(current - delta.signum()).rem_euclid(8) as f32 * FRAC_PI_4
+}
+
+#[must_use]
+fn eye(pos: Vec3, height: f32) -> Vec3 {
+	pos + Vec3::Z * (height * 0.75)
+}
+
+// Systems /////////////////////////////////////////////////////////////////////
+
+/// Consumes every pending [`Noise`], alerting the sectors it reaches.
+pub fn propagate_noise(
+	sim: Res<Sim>,
+	mut alerts: ResMut<SoundAlerts>,
+	mut noises: EventReader<Noise>,
+) {
+	let Some(spatial) = sim.spatial() else {
+		noises.clear();
+		return;
+	};
+
+	for noise in noises.read() {
+		let origin = spatial.sector_at(noise.pos.truncate());
+		alerts.noise_alert(spatial, origin, noise.emitter);
+	}
+}
+
+/// Runs [`run_actions`] for every living monster, in order of entity ID.
+/// Intended to run every tick, after [`advance_states`](super::advance_states).
+#[allow(clippy::type_complexity)]
+pub fn think(
+	mut sim: ResMut<Sim>,
+	alerts: Res<SoundAlerts>,
+	blueprints: Res<Assets<Blueprint>>,
+	mut damages: EventWriter<DamageEvent>,
+	players: Query<
+		(&Readonly, &SimTransform, &Health),
+		(With<PlayerPawn>, With<ActiveMarker>, Without<AiState>),
+	>,
+	obstacles: Query<
+		(&Readonly, &SimTransform),
+		(With<Solid>, With<ActiveMarker>, Without<AiState>),
+	>,
+	mut monsters: Query<
+		(
+			Entity,
+			&Readonly,
+			&mut AiState,
+			&mut StateMachine,
+			&mut SimTransform,
+			Has<Solid>,
+		),
+		(With<ActiveMarker>, Without<Corpse>),
+	>,
+) {
+	let sim = &mut *sim;
+
+	let Some(spatial) = sim.spatial.as_ref() else {
+		return;
+	};
+
+	let fast = sim.skill.fast_monsters;
+	let prng = sim.rng.get(RNG_KEY);
+
+	let body = |readonly: &Readonly, transform: &SimTransform| -> Option<Body> {
+		let blueprint = blueprints.get(&readonly.blueprint)?;
+
+		Some(Body {
+			actor: readonly.id,
+			pos: transform.pos,
+			radius: blueprint.radius,
+			height: blueprint.height,
+		})
+	};
+
+	let targets: Vec<Body> = players
+		.iter()
+		.filter(|(_, _, health)| !health.is_dead())
+		.filter_map(|(readonly, transform, _)| body(readonly, transform))
+		.collect();
+
+	let mut solids: Vec<Body> = obstacles
+		.iter()
+		.filter_map(|(readonly, transform)| body(readonly, transform))
+		.chain(
+			monsters
+				.iter()
+				.filter(|(.., solid)| *solid)
+				.filter_map(|(_, readonly, _, _, transform, _)| body(readonly, transform)),
+		)
+		.collect();
+
+	let mut order: Vec<Entity> = monsters.iter().map(|(entity, ..)| entity).collect();
+	order.sort_unstable();
+
+	let mut events = vec![];
+
+	for entity in order {
+		let Ok((_, readonly, mut ai, mut fsm, mut transform, _)) = monsters.get_mut(entity) else {
+			continue;
+		};
+
+		let Some(blueprint) = blueprints.get(&readonly.blueprint) else {
+			continue;
+		};
+
+		let env = Surroundings {
+			spatial,
+			players: &targets,
+			solids: &solids,
+			alerts: &alerts,
+			fast,
+		};
+
+		let mut me = Thinker {
+			actor: readonly.id,
+			blueprint,
+			fsm: &mut fsm,
+			transform: &mut transform,
+			ai: &mut ai,
+		};
+
+		run_actions(&mut me, &env, prng, &mut events);
+
+		if let Some(solid) = solids.iter_mut().find(|s| s.actor == readonly.id) {
+			solid.pos = transform.pos;
+		}
+	}
+
+	damages.send_batch(events);
+}
+
+#[cfg(test)]
+mod test {
+	use nanorand::WyRand;
+
+	use crate::{
+		actor::state::{StateDef, StateTable},
+		gfx::style::RenderStyle,
+		sim::spatial::test::Lumps,
+	};
+
+	use super::*;
+
+	const PLAYER: Vec3 = Vec3::new(40.0, 64.0, 0.0);
+	const MONSTER: Vec3 = Vec3::new(240.0, 64.0, 0.0);
+	/// Stands between the monster and the player.
+	const PILLAR: Vec3 = Vec3::new(190.0, 64.0, 0.0);
+
+	/// `Spawn` looks, `See` chases, and `Melee` attacks.
+	#[must_use]
+	fn blueprint() -> Blueprint {
+		let mut states = StateTable::default();
+
+		let spawn = states.push(StateDef {
+			duration: 10,
+			next: Some(0),
+			action: Some(Action::Look),
+		});
+		let see = states.push(StateDef {
+			duration: 3,
+			next: Some(1),
+			action: Some(Action::Chase),
+		});
+		let melee = states.push(StateDef {
+			duration: 8,
+			next: Some(1),
+			action: Some(Action::MeleeAttack),
+		});
+
+		states.add_label("Spawn", spawn);
+		states.add_label("See", see);
+		states.add_label("Melee", melee);
+
+		Blueprint {
+			editor_num: 0,
+			spawn_num: 0,
+			health_starting: 60,
+			gib_health: -60,
+			pain_chance: 200,
+			states,
+			render_style: RenderStyle::Normal,
+			radius: 20.0,
+			height: 56.0,
+			speed: 8.0,
+			melee_range: 44.0,
+			melee_damage: 3,
+			hitscan_damage: 0,
+		}
+	}
+
+	#[must_use]
+	fn actor(index: u32) -> Actor {
+		Actor::new(Entity::from_raw(index))
+	}
+
+	#[must_use]
+	fn body(index: u32, pos: Vec3, radius: f32) -> Body {
+		Body {
+			actor: actor(index),
+			pos,
+			radius,
+			height: 56.0,
+		}
+	}
+
+	#[must_use]
+	fn prng(seed: u64) -> WyRand {
+		let mut prng = WyRand::default();
+		prng.seed(seed);
+		prng
+	}
+
+	/// Everything which `run` reports about one monster's behavior.
+	struct Outcome {
+		ai: AiState,
+		/// The monster's position at the end of every tick.
+		path: Vec<Vec3>,
+		damages: Vec<DamageEvent>,
+	}
+
+	/// Simulates one monster for `ticks` ticks. The player stands still in room A,
+	/// and the monster starts in room B, facing `angle`.
+	#[must_use]
+	fn run(
+		spatial: &SpatialQuery,
+		alerts: &SoundAlerts,
+		fast: bool,
+		ambush: bool,
+		angle: f32,
+		seed: u64,
+		ticks: usize,
+	) -> Outcome {
+		let bp = blueprint();
+		let mut fsm = StateMachine::new(&bp.states).unwrap();
+		let mut ai = AiState::new(ambush);
+		let mut prng = prng(seed);
+
+		let mut transform = SimTransform {
+			pos: MONSTER,
+			angle,
+		};
+
+		let players = [body(0, PLAYER, 16.0)];
+		let solids = [players[0], body(2, PILLAR, 12.0)];
+
+		let env = Surroundings {
+			spatial,
+			players: &players,
+			solids: &solids,
+			alerts,
+			fast,
+		};
+
+		let mut path = vec![];
+		let mut damages = vec![];
+
+		for _ in 0..ticks {
+			fsm.tick(&bp.states);
+
+			let mut me = Thinker {
+				actor: actor(1),
+				blueprint: &bp,
+				fsm: &mut fsm,
+				transform: &mut transform,
+				ai: &mut ai,
+			};
+
+			run_actions(&mut me, &env, &mut prng, &mut damages);
+			path.push(transform.pos);
+		}
+
+		Outcome { ai, path, damages }
+	}
+
+	#[test]
+	fn wake_on_sight() {
+		let alerts = SoundAlerts::default();
+
+		let spatial = Lumps::new(128).query(true, None);
+		let outcome = run(&spatial, &alerts, false, false, std::f32::consts::PI, 0, 10);
+		assert_eq!(outcome.ai.target, Some(actor(0)));
+
+		// Facing away, too far away to be noticed from behind.
+		let outcome = run(&spatial, &alerts, false, false, 0.0, 0, 10);
+		assert_eq!(outcome.ai.target, None);
+
+		// The door is shut.
+		let spatial = Lumps::new(0).query(true, None);
+		let outcome = run(&spatial, &alerts, false, false, std::f32::consts::PI, 0, 10);
+		assert_eq!(outcome.ai.target, None);
+	}
+
+	#[test]
+	fn wake_on_noise() {
+		// The door is open just enough to let noise through, but not sight.
+		let spatial = Lumps::new(24).query(true, None);
+		let mut alerts = SoundAlerts::default();
+		let origin = spatial.sector_at(PLAYER.truncate());
+		alerts.noise_alert(&spatial, origin, actor(0));
+		assert_eq!(
+			alerts.heard(spatial.sector_at(MONSTER.truncate())),
+			Some(actor(0))
+		);
+
+		let outcome = run(&spatial, &alerts, false, false, 0.0, 0, 10);
+		assert_eq!(outcome.ai.target, Some(actor(0)));
+
+		// An ambushing monster also needs to see where the noise came from.
+		let outcome = run(&spatial, &alerts, false, true, 0.0, 0, 10);
+		assert_eq!(outcome.ai.target, None);
+
+		// A shut door stops noise.
+		let spatial = Lumps::new(0).query(true, None);
+		let mut alerts = SoundAlerts::default();
+		alerts.noise_alert(&spatial, origin, actor(0));
+		assert_eq!(alerts.heard(spatial.sector_at(MONSTER.truncate())), None);
+	}
+
+	#[test]
+	fn chase_around_obstacle() {
+		let spatial = Lumps::new(128).query(true, None);
+		let alerts = SoundAlerts::default();
+		let angle = std::f32::consts::PI;
+		let outcome = run(&spatial, &alerts, false, false, angle, 0x5EED, 350);
+
+		let reach = 12.0 + 20.0;
+
+		for pos in &outcome.path {
+			assert!(
+				(pos.x - PILLAR.x).abs() >= reach || (pos.y - PILLAR.y).abs() >= reach,
+				"monster walked through the pillar at {pos}"
+			);
+		}
+
+		assert!(outcome.path.iter().any(|pos| pos.x < PILLAR.x - reach));
+		assert!(!outcome.damages.is_empty());
+		assert!(outcome.damages.iter().all(|d| d.target == actor(0)));
+
+		let again = run(&spatial, &alerts, false, false, angle, 0x5EED, 350);
+		assert_eq!(outcome.path, again.path);
+		assert_eq!(outcome.ai, again.ai);
+	}
+
+	#[test]
+	fn fast_monsters() {
+		let spatial = Lumps::new(128).query(true, None);
+		let alerts = SoundAlerts::default();
+		let angle = std::f32::consts::PI;
+
+		let past_pillar = |outcome: &Outcome| {
+			outcome
+				.path
+				.iter()
+				.position(|pos| pos.x < PILLAR.x - (12.0 + 20.0))
+		};
+
+		let normal = run(&spatial, &alerts, false, false, angle, 0x5EED, 350);
+		let fast = run(&spatial, &alerts, true, false, angle, 0x5EED, 350);
+		assert!(past_pillar(&fast).unwrap() < past_pillar(&normal).unwrap());
+		assert!(fast.damages.len() > normal.damages.len());
+	}
+}
@@ -0,0 +1,395 @@
+//! Application of damage to actors, and their transitions into pain and death.
+//!
+//! Armor is not yet accounted for.
+
+use bevy::prelude::*;
+
+use crate::{
+	actor::{Actor, Blueprint, Corpse, Health, Readonly, Solid, StateMachine},
+	rng::Prng,
+};
+
+use super::{skill::SkillDef, ActiveMarker, Sim};
+
+/// Health loss which is always lethal, and which ignores pain chance and
+/// the gib threshold. Inflicted by [`DamageType::Telefrag`].
+pub const TELEFRAG_DAMAGE: i32 = 1_000_000;
+
+/// Sent to request that an actor take damage. Consumed by [`apply_damage`].
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct DamageEvent {
+	pub target: Actor,
+	/// e.g. the projectile or puff which dealt the damage.
+	pub inflictor: Option<Actor>,
+	/// e.g. the actor which fired `inflictor`.
+	pub source: Option<Actor>,
+	pub amount: i32,
+	pub damage_type: DamageType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DamageType {
+	Normal,
+	/// Always kills; bypasses pain chance and the gib threshold.
+	Telefrag,
+	/// Script-defined. Currently treated the same as [`DamageType::Normal`].
+	Named(String),
+}
+
+/// Sent by [`apply_damage`] whenever an actor is killed.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct Obituary {
+	pub victim: Actor,
+	pub inflictor: Option<Actor>,
+	pub source: Option<Actor>,
+	pub damage_type: DamageType,
+}
+
+/// See [`damage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+	/// The target was already dead; nothing happened.
+	Ignored,
+	/// The target lost health but nothing else happened.
+	Hurt,
+	/// The target lost health and entered its `Pain` state sequence.
+	Pain,
+	/// The target entered its `Death` state sequence.
+	Death,
+	/// The target entered its `XDeath` state sequence.
+	ExtremeDeath,
+}
+
+impl Outcome {
+	#[must_use]
+	pub fn is_death(self) -> bool {
+		matches!(self, Self::Death | Self::ExtremeDeath)
+	}
+}
+
+/// Applies `amount` damage of the given type to an actor and moves it into
+/// its pain or death states as appropriate. Does not touch any other components;
+/// see [`apply_damage`] for that.
+///
+/// `amount` gets scaled by the skill's [damage factor](SkillDef::damage_factor).
+/// Pain chance is evaluated as in Doom, by comparing a random number in the
+/// range `0..=255` against [`Blueprint::pain_chance`], unless the skill has
+/// [`SkillDef::no_pain`] set. An actor which lacks the state label it would
+/// enter stays in its current state.
+pub fn damage(
+	skill: &SkillDef,
+	blueprint: &Blueprint,
+	health: &mut Health,
+	fsm: &mut StateMachine,
+	amount: i32,
+	damage_type: &DamageType,
+	prng: &mut impl Prng,
+) -> Outcome {
+	if health.is_dead() {
+		return Outcome::Ignored;
+	}
+
+	let telefrag = *damage_type == DamageType::Telefrag;
+	let amount = if telefrag {
+		TELEFRAG_DAMAGE
+	} else {
+		skill.scale_damage(amount)
+	};
+
+	health.current = health.current.saturating_sub(amount);
+
+	if health.is_dead() {
+		if !telefrag
+			&& health.current < blueprint.gib_health
+			&& fsm.enter_label(&blueprint.states, "XDeath")
+		{
+			return Outcome::ExtremeDeath;
+		}
+
+		let _ = fsm.enter_label(&blueprint.states, "Death");
+		return Outcome::Death;
+	}
+
+	if telefrag {
+		return Outcome::Hurt;
+	}
+
+	if !skill.no_pain
+		&& prng.range_i64(0, 255) < i64::from(blueprint.pain_chance)
+		&& fsm.enter_label(&blueprint.states, "Pain")
+	{
+		return Outcome::Pain;
+	}
+
+	Outcome::Hurt
+}
+
+/// Consumes every pending [`DamageEvent`]. Killed actors are marked as a
+/// [`Corpse`], stop being [`Solid`], and get an [`Obituary`] sent out.
+pub fn apply_damage(
+	mut cmds: Commands,
+	mut sim: ResMut<Sim>,
+	blueprints: Res<Assets<Blueprint>>,
+	mut damages: EventReader<DamageEvent>,
+	mut obituaries: EventWriter<Obituary>,
+	mut actors: Query<
+		(&Readonly, &mut Health, &mut StateMachine),
+		(With<ActiveMarker>, Without<Corpse>),
+	>,
+) {
+	for event in damages.read() {
+		let Ok((readonly, mut health, mut fsm)) = actors.get_mut(event.target.entity()) else {
+			continue;
+		};
+
+		let Some(blueprint) = blueprints.get(&readonly.blueprint) else {
+			continue;
+		};
+
+		let outcome = damage(
+			&sim.skill,
+			blueprint,
+			&mut health,
+			&mut fsm,
+			event.amount,
+			&event.damage_type,
+			sim.rng.get_default(),
+		);
+
+		if !outcome.is_death() {
+			continue;
+		}
+
+		cmds.entity(event.target.entity())
+			.insert(Corpse)
+			.remove::<Solid>();
+
+		obituaries.send(Obituary {
+			victim: event.target,
+			inflictor: event.inflictor,
+			source: event.source,
+			damage_type: event.damage_type.clone(),
+		});
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use nanorand::WyRand;
+
+	use crate::{
+		actor::state::{StateDef, StateTable},
+		gfx::style::RenderStyle,
+	};
+
+	use super::*;
+
+	/// A blueprint with 100 health and `Spawn`, `Pain`, `Death`, and `XDeath`
+	/// labels at states 0, 1, 2, and 4 respectively.
+	#[must_use]
+	fn blueprint(pain_chance: u16) -> Blueprint {
+		let mut states = StateTable::default();
+
+		let spawn = states.push(StateDef {
+			duration: 10,
+			next: Some(0),
+			action: None,
+		});
+		let pain = states.push(StateDef {
+			duration: 6,
+			next: Some(0),
+			action: None,
+		});
+		let death = states.push(StateDef {
+			duration: 5,
+			next: Some(3),
+			action: None,
+		});
+		let _ = states.push(StateDef {
+			duration: -1,
+			next: None,
+			action: None,
+		});
+		let xdeath = states.push(StateDef {
+			duration: -1,
+			next: None,
+			action: None,
+		});
+
+		states.add_label("Spawn", spawn);
+		states.add_label("Pain", pain);
+		states.add_label("Death", death);
+		states.add_label("XDeath", xdeath);
+
+		Blueprint {
+			editor_num: 0,
+			spawn_num: 0,
+			health_starting: 100,
+			gib_health: -100,
+			pain_chance,
+			states,
+			render_style: RenderStyle::Normal,
+			radius: 20.0,
+			height: 56.0,
+			speed: 0.0,
+			melee_range: 44.0,
+			melee_damage: 0,
+			hitscan_damage: 0,
+		}
+	}
+
+	#[must_use]
+	fn prng(seed: u64) -> WyRand {
+		let mut prng = WyRand::default();
+		prng.seed(seed);
+		prng
+	}
+
+	#[test]
+	fn pain_then_death() {
+		let bp = blueprint(256);
+		let mut health = Health::new(&bp);
+		let mut fsm = StateMachine::new(&bp.states).unwrap();
+		let mut prng = prng(0);
+
+		let outcome = damage(
+			&SkillDef::default(),
+			&bp,
+			&mut health,
+			&mut fsm,
+			30,
+			&DamageType::Normal,
+			&mut prng,
+		);
+		assert_eq!(outcome, Outcome::Pain);
+		assert_eq!(health.current, 70);
+		assert_eq!(fsm.state, 1);
+
+		for _ in 0..6 {
+			fsm.tick(&bp.states);
+		}
+
+		assert_eq!(fsm.state, 0);
+
+		let outcome = damage(
+			&SkillDef::default(),
+			&bp,
+			&mut health,
+			&mut fsm,
+			80,
+			&DamageType::Normal,
+			&mut prng,
+		);
+		assert_eq!(outcome, Outcome::Death);
+		assert_eq!(fsm.state, 2);
+
+		for _ in 0..5 {
+			fsm.tick(&bp.states);
+		}
+
+		assert_eq!(fsm.state, 3);
+		assert_eq!(fsm.tics, -1);
+
+		let outcome = damage(
+			&SkillDef::default(),
+			&bp,
+			&mut health,
+			&mut fsm,
+			10,
+			&DamageType::Normal,
+			&mut prng,
+		);
+		assert_eq!(outcome, Outcome::Ignored);
+	}
+
+	#[test]
+	fn overkill() {
+		let bp = blueprint(0);
+
+		let mut health = Health::new(&bp);
+		let mut fsm = StateMachine::new(&bp.states).unwrap();
+		let outcome = damage(
+			&SkillDef::default(),
+			&bp,
+			&mut health,
+			&mut fsm,
+			250,
+			&DamageType::Normal,
+			&mut prng(0),
+		);
+		assert_eq!(outcome, Outcome::ExtremeDeath);
+		assert_eq!(fsm.state, 4);
+
+		let mut health = Health::new(&bp);
+		let mut fsm = StateMachine::new(&bp.states).unwrap();
+		let outcome = damage(
+			&SkillDef::default(),
+			&bp,
+			&mut health,
+			&mut fsm,
+			1,
+			&DamageType::Telefrag,
+			&mut prng(0),
+		);
+		assert_eq!(outcome, Outcome::Death);
+		assert_eq!(fsm.state, 2);
+	}
+
+	#[test]
+	fn skill_factors() {
+		let bp = blueprint(256);
+		let [baby, ..] = SkillDef::classic();
+		let mut health = Health::new(&bp);
+		let mut fsm = StateMachine::new(&bp.states).unwrap();
+
+		let skill = SkillDef {
+			no_pain: true,
+			..baby
+		};
+
+		let outcome = damage(
+			&skill,
+			&bp,
+			&mut health,
+			&mut fsm,
+			30,
+			&DamageType::Normal,
+			&mut prng(0),
+		);
+		assert_eq!(outcome, Outcome::Hurt);
+		assert_eq!(health.current, 85);
+		assert_eq!(fsm.state, 0);
+	}
+
+	#[test]
+	fn pain_chance_determinism() {
+		#[must_use]
+		fn run(seed: u64) -> Vec<Outcome> {
+			let mut bp = blueprint(128);
+			bp.health_starting = 1000;
+			let mut health = Health::new(&bp);
+			let mut fsm = StateMachine::new(&bp.states).unwrap();
+			let mut prng = prng(seed);
+
+			(0..64)
+				.map(|_| {
+					damage(
+						&SkillDef::default(),
+						&bp,
+						&mut health,
+						&mut fsm,
+						1,
+						&DamageType::Normal,
+						&mut prng,
+					)
+				})
+				.collect()
+		}
+
+		let a = run(0x5EED);
+		let b = run(0x5EED);
+		assert_eq!(a, b);
+		assert!(a.contains(&Outcome::Pain));
+		assert!(a.contains(&Outcome::Hurt));
+	}
+}
@@ -0,0 +1,281 @@
+//! Smoothing of motion between sim ticks.
+//!
+//! The sim runs at a fixed rate (35 Hz by default) while rendering runs at the
+//! display's rate, so anything drawn straight from sim state visibly stutters.
+//! Instead, every entity the renderer cares about has its sim state snapshotted
+//! by [`snapshot`] at the start of each tick, and [`interpolate`] draws it at a
+//! blend between that snapshot and its current sim state.
+//!
+//! The sim works in Doom's coordinate space, which is Z-up; Bevy's is Y-up.
+//! Anything crossing from one to the other goes through [`doom_to_bevy`],
+//! [`yaw_to_bevy`], or their inverses.
+
+use std::{
+	f32::consts::{FRAC_PI_2, PI, TAU},
+	time::Duration,
+};
+
+use bevy::prelude::*;
+
+/// The console-facing switch for interpolation; `true` by default.
+/// Disabling it means everything gets drawn exactly where the sim last put it.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interpolation {
+	pub enabled: bool,
+}
+
+impl Default for Interpolation {
+	fn default() -> Self {
+		Self { enabled: true }
+	}
+}
+
+/// Where the sim considers an actor (or a camera) to be.
+/// Separate from the [`Transform`] used for drawing, which [`interpolate`] writes.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct SimTransform {
+	pub pos: Vec3,
+	/// In radians, counter-clockwise around the Z axis. 0 is east.
+	pub angle: f32,
+}
+
+impl SimTransform {
+	/// This transform in Bevy's space. See [`doom_to_bevy`] and [`yaw_to_bevy`].
+	#[must_use]
+	pub fn to_bevy(&self) -> Transform {
+		Transform::from_translation(doom_to_bevy(self.pos)).with_rotation(yaw_to_bevy(self.angle))
+	}
+}
+
+/// A copy of an entity's [`SimTransform`] as it was at the start of the current tick.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PrevTransform {
+	pub prev: SimTransform,
+	teleported: bool,
+}
+
+impl PrevTransform {
+	/// To be called whenever the sim moves an entity discontinuously
+	/// (e.g. through a teleporter), so that until the next tick it gets drawn
+	/// at its destination instead of being smeared across the level.
+	pub fn teleport(&mut self) {
+		self.teleported = true;
+	}
+
+	#[must_use]
+	pub fn teleported(&self) -> bool {
+		self.teleported
+	}
+}
+
+/// The floor and ceiling heights of a sector as the sim sees them.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlaneHeights {
+	pub floor: f32,
+	pub ceiling: f32,
+}
+
+/// A sector's [`PlaneHeights`] as they were at the start of the current tick.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PrevPlaneHeights(pub PlaneHeights);
+
+/// Written by [`interpolate`] for the renderer to read.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct DrawnPlaneHeights(pub PlaneHeights);
+
+/// Intended to run at the start of every tick, before anything else in the sim.
+pub fn snapshot(
+	mut transforms: Query<(&SimTransform, &mut PrevTransform)>,
+	mut planes: Query<(&PlaneHeights, &mut PrevPlaneHeights)>,
+) {
+	for (cur, mut prev) in &mut transforms {
+		*prev = PrevTransform {
+			prev: *cur,
+			teleported: false,
+		};
+	}
+
+	for (cur, mut prev) in &mut planes {
+		prev.0 = *cur;
+	}
+}
+
+/// Intended to run once per rendered frame, after the fixed-time update loop.
+pub fn interpolate(
+	interp: Res<Interpolation>,
+	fixed_time: Res<Time<Fixed>>,
+	mut transforms: Query<(&SimTransform, &PrevTransform, &mut Transform)>,
+	mut planes: Query<(&PlaneHeights, &PrevPlaneHeights, &mut DrawnPlaneHeights)>,
+) {
+	let alpha = if interp.enabled {
+		alpha(fixed_time.overstep(), fixed_time.timestep())
+	} else {
+		1.0
+	};
+
+	for (cur, prev, mut transform) in &mut transforms {
+		let blended = blend_transform(prev, cur, alpha).to_bevy();
+		transform.translation = blended.translation;
+		transform.rotation = blended.rotation;
+	}
+
+	for (cur, prev, mut drawn) in &mut planes {
+		drawn.0 = PlaneHeights {
+			floor: lerp(prev.0.floor, cur.floor, alpha),
+			ceiling: lerp(prev.0.ceiling, cur.ceiling, alpha),
+		};
+	}
+}
+
+// Coordinates /////////////////////////////////////////////////////////////////
+
+/// Converts a position in Doom's space to Bevy's. Doom's east (+X) stays +X,
+/// Doom's up (+Z) becomes +Y, and Doom's north (+Y) becomes -Z, which is
+/// the direction Bevy considers "forward".
+#[must_use]
+pub fn doom_to_bevy(pos: Vec3) -> Vec3 {
+	Vec3::new(pos.x, pos.z, -pos.y)
+}
+
+/// The inverse of [`doom_to_bevy`].
+#[must_use]
+pub fn bevy_to_doom(pos: Vec3) -> Vec3 {
+	Vec3::new(pos.x, -pos.z, pos.y)
+}
+
+/// Converts an angle in radians, counter-clockwise around Doom's Z axis with
+/// 0 facing east, to a rotation which turns Bevy's forward (-Z) to face it.
+#[must_use]
+pub fn yaw_to_bevy(angle: f32) -> Quat {
+	Quat::from_rotation_y(angle - FRAC_PI_2)
+}
+
+/// The inverse of [`yaw_to_bevy`]. Any pitch or roll in `rotation` is ignored.
+/// Always in the range `0.0..TAU`.
+#[must_use]
+pub fn yaw_from_bevy(rotation: Quat) -> f32 {
+	let forward = bevy_to_doom(rotation * Vec3::NEG_Z);
+	forward.y.atan2(forward.x).rem_euclid(TAU)
+}
+
+// Details /////////////////////////////////////////////////////////////////////
+
+/// How far between the last tick and the next one the current frame is,
+/// given how much time the fixed-time loop has accumulated without running a
+/// tick and how long a tick is. Always in the range `0.0..=1.0`.
+#[must_use]
+pub fn alpha(overstep: Duration, timestep: Duration) -> f32 {
+	if timestep.is_zero() {
+		return 1.0;
+	}
+
+	(overstep.as_secs_f64() / timestep.as_secs_f64()).clamp(0.0, 1.0) as f32
+}
+
+/// Interpolates between two angles in radians along the shortest arc,
+/// so that e.g. turning from 350 degrees to 10 degrees passes through 0.
+#[must_use]
+pub fn lerp_angle(from: f32, to: f32, alpha: f32) -> f32 {
+	let mut delta = (to - from).rem_euclid(TAU);
+
+	if delta > PI {
+		delta -= TAU;
+	}
+
+	(from + delta * alpha).rem_euclid(TAU)
+}
+
+#[must_use]
+pub fn lerp(from: f32, to: f32, alpha: f32) -> f32 {
+	from + (to - from) * alpha
+}
+
+/// If `prev` has been [teleported](PrevTransform::teleport), `cur` is returned as-is.
+#[must_use]
+pub fn blend_transform(prev: &PrevTransform, cur: &SimTransform, alpha: f32) -> SimTransform {
+	if prev.teleported {
+		return *cur;
+	}
+
+	SimTransform {
+		pos: prev.prev.pos.lerp(cur.pos, alpha),
+		angle: lerp_angle(prev.prev.angle, cur.angle, alpha),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn approx_eq(a: f32, b: f32) -> bool {
+		(a - b).abs() < 0.0001
+	}
+
+	/// Treats e.g. 0 and 359.99... degrees as equal.
+	fn angle_eq(a: f32, b: f32) -> bool {
+		let diff = (a - b).rem_euclid(TAU);
+		diff < 0.0001 || (TAU - diff) < 0.0001
+	}
+
+	#[test]
+	fn alpha_from_accumulator() {
+		let step = Duration::from_secs_f64(1.0 / 35.0);
+
+		assert_eq!(alpha(Duration::ZERO, step), 0.0);
+		assert!(approx_eq(alpha(step / 2, step), 0.5));
+		assert_eq!(alpha(step, step), 1.0);
+		assert_eq!(alpha(step * 2, step), 1.0);
+		assert_eq!(alpha(step, Duration::ZERO), 1.0);
+	}
+
+	#[test]
+	fn angle_shortest_arc() {
+		let deg = |d: f32| d.to_radians();
+
+		assert!(angle_eq(lerp_angle(deg(0.0), deg(90.0), 0.5), deg(45.0)));
+		// Wrapping around in either direction.
+		assert!(angle_eq(lerp_angle(deg(350.0), deg(10.0), 0.5), 0.0));
+		assert!(angle_eq(lerp_angle(deg(10.0), deg(350.0), 0.25), deg(5.0)));
+		assert!(angle_eq(lerp_angle(deg(350.0), deg(10.0), 1.0), deg(10.0)));
+		assert!(angle_eq(lerp_angle(deg(350.0), deg(10.0), 0.0), deg(350.0)));
+		// Exactly opposite; ties go counter-clockwise.
+		assert!(angle_eq(lerp_angle(0.0, PI, 0.5), PI / 2.0));
+	}
+
+	#[test]
+	fn coordinates() {
+		let pos = Vec3::new(64.0, -32.0, 16.0);
+		assert_eq!(doom_to_bevy(pos), Vec3::new(64.0, 16.0, 32.0));
+		assert_eq!(bevy_to_doom(doom_to_bevy(pos)), pos);
+
+		let deg = |d: f32| d.to_radians();
+
+		// Facing east, north, and west in Doom's space.
+		let east = yaw_to_bevy(0.0) * Vec3::NEG_Z;
+		assert!(east.abs_diff_eq(Vec3::X, 0.0001));
+		let north = yaw_to_bevy(deg(90.0)) * Vec3::NEG_Z;
+		assert!(north.abs_diff_eq(Vec3::NEG_Z, 0.0001));
+		let west = yaw_to_bevy(deg(180.0)) * Vec3::NEG_Z;
+		assert!(west.abs_diff_eq(Vec3::NEG_X, 0.0001));
+
+		for d in [0.0, 45.0, 90.0, 200.0, 359.0] {
+			assert!(angle_eq(yaw_from_bevy(yaw_to_bevy(deg(d))), deg(d)));
+		}
+	}
+
+	#[test]
+	fn teleport_suppression() {
+		let cur = SimTransform {
+			pos: Vec3::new(1024.0, 0.0, 0.0),
+			angle: 0.0,
+		};
+
+		let mut prev = PrevTransform::default();
+		let halfway = blend_transform(&prev, &cur, 0.5);
+		assert_eq!(halfway.pos, Vec3::new(512.0, 0.0, 0.0));
+
+		prev.teleport();
+		assert_eq!(blend_transform(&prev, &cur, 0.5), cur);
+		assert_eq!(blend_transform(&prev, &cur, 0.0), cur);
+	}
+}
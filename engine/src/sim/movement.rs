@@ -0,0 +1,138 @@
+//! Player pawn movement as a pure function of the pawn's state and one tick of input.
+//!
+//! Both the server and a predicting client (see [`crate::player::predict`])
+//! run [`step`], so it must not read anything besides its arguments; if it did,
+//! the two could disagree about where the same input leads.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// `forwardmove` values of vanilla's walking and running speeds, in that order.
+pub const FORWARD_MOVE: [i8; 2] = [0x19, 0x32];
+/// `sidemove` values of vanilla's walking and running speeds, in that order.
+pub const SIDE_MOVE: [i8; 2] = [0x18, 0x28];
+/// `turn` values of vanilla's keyboard turning speeds, walking and running in that order.
+pub const ANGLE_TURN: [i16; 2] = [640, 1280];
+
+/// Vanilla's `FRICTION`, for a pawn on the ground.
+pub const FRICTION: f32 = 0.906_25;
+/// Below this speed (in map units per tick), a pawn with no input stops dead.
+pub const STOP_SPEED: f32 = 0.0625;
+/// A pawn's speed along either horizontal axis never exceeds this.
+pub const MAX_MOVE: f32 = 30.0;
+
+/// Converts a `forwardmove` or `sidemove` value to map units per tick of thrust.
+const THRUST_SCALE: f32 = 2048.0 / 65536.0;
+/// `turn` is the upper half of a vanilla binary angle, so a full circle is 65536.
+const TURN_SCALE: f32 = TAU / 65536.0;
+
+/// One tick's worth of player input, mirroring a Doom `ticcmd_t`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TicCmd {
+	/// Positive is forward.
+	pub forward: i8,
+	/// Positive is rightward.
+	pub side: i8,
+	/// Positive is leftward, in Doom's angle units.
+	pub turn: i16,
+	pub buttons: u32,
+}
+
+/// The part of a player pawn's state which [`step`] reads and writes.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PawnState {
+	pub pos: Vec3,
+	/// In map units per tick.
+	pub vel: Vec3,
+	/// In radians, counter-clockwise around the Z axis. 0 is east.
+	pub yaw: f32,
+}
+
+/// Advances `state` by one tick of `cmd`: turning, then thrust, then moving
+/// by the resulting velocity, then friction.
+///
+/// Collision is not yet accounted for.
+#[must_use]
+pub fn step(state: PawnState, cmd: &TicCmd) -> PawnState {
+	let mut ret = state;
+
+	ret.yaw = (ret.yaw + (cmd.turn as f32) * TURN_SCALE).rem_euclid(TAU);
+
+	let (sin, cos) = ret.yaw.sin_cos();
+	let forward = Vec2::new(cos, sin);
+	let right = Vec2::new(sin, -cos);
+
+	let thrust =
+		forward * (cmd.forward as f32) * THRUST_SCALE + right * (cmd.side as f32) * THRUST_SCALE;
+
+	ret.vel.x = (ret.vel.x + thrust.x).clamp(-MAX_MOVE, MAX_MOVE);
+	ret.vel.y = (ret.vel.y + thrust.y).clamp(-MAX_MOVE, MAX_MOVE);
+	ret.pos += ret.vel;
+
+	let idle = cmd.forward == 0 && cmd.side == 0;
+
+	if idle && ret.vel.x.abs() < STOP_SPEED && ret.vel.y.abs() < STOP_SPEED {
+		ret.vel.x = 0.0;
+		ret.vel.y = 0.0;
+	} else {
+		ret.vel.x *= FRICTION;
+		ret.vel.y *= FRICTION;
+	}
+
+	ret
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn thrust_and_friction() {
+		let run = TicCmd {
+			forward: FORWARD_MOVE[1],
+			..Default::default()
+		};
+
+		let mut state = PawnState::default();
+
+		for _ in 0..35 {
+			state = step(state, &run);
+		}
+
+		// Running east from a standstill for one second.
+		assert!(state.pos.x > 400.0);
+		assert!(state.pos.y.abs() < 0.001);
+
+		// Friction caps the speed at which thrust and friction cancel out.
+		let terminal = 50.0 * THRUST_SCALE * FRICTION / (1.0 - FRICTION);
+		assert!(state.vel.x > 14.0 && state.vel.x < terminal);
+
+		for _ in 0..70 {
+			state = step(state, &TicCmd::default());
+		}
+
+		assert_eq!(state.vel, Vec3::ZERO);
+	}
+
+	#[test]
+	fn turning() {
+		let quarter = TicCmd {
+			turn: 16384,
+			..Default::default()
+		};
+
+		let state = step(PawnState::default(), &quarter);
+		assert!((state.yaw - TAU / 4.0).abs() < 0.0001);
+
+		let state = step(
+			state,
+			&TicCmd {
+				turn: -32768,
+				..Default::default()
+			},
+		);
+		assert!((state.yaw - TAU * 0.75).abs() < 0.0001);
+	}
+}
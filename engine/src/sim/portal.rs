@@ -0,0 +1,32 @@
+//! Placeholders for ZDoom's [portals], which join non-adjacent parts of a level
+//! (or a level and a skybox) so that each is visible through the other.
+//!
+//! [portals]: https://zdoom.org/wiki/Portals
+
+use bevy::prelude::*;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Portal {
+	pub kind: PortalKind,
+	/// The other end of this portal.
+	pub target: Entity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PortalKind {
+	/// e.g. via the `Line_SetPortal` special.
+	LineToLine,
+	/// A floor or ceiling which shows what is above or below another sector.
+	SectorToSector,
+	/// A floor or ceiling which shows a skybox viewpoint's surroundings.
+	Skybox,
+}
+
+/// Checks whether the linedef at index `linedef` has a portal linked to it,
+/// returning the portal's entity if so. Safe to call during BSP traversal.
+///
+/// Always returns `None` for now, since level setup does not spawn portals yet.
+#[must_use]
+pub fn try_activate_portal(_world: &World, _linedef: usize) -> Option<Entity> {
+	None
+}
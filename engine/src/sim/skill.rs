@@ -2,6 +2,17 @@
 
 use bevy::prelude::*;
 use bitflags::bitflags;
+use data::level::read::ThingFlags;
+use doomfront::{
+	rowan::ast::AstNode,
+	zdoom::{
+		self,
+		mapinfo::{self, ast},
+	},
+};
+use vfs::{Slot, VirtualFs};
+
+use crate::world::level::{Level, Thing};
 
 /// i.e., a difficulty setting.
 #[derive(Debug)]
@@ -42,6 +53,56 @@ pub enum SpawnFilter {
 	Udmf15,
 }
 
+impl SpawnFilter {
+	/// The corresponding bit in a thing's skill flags.
+	#[must_use]
+	pub fn bit(self) -> u16 {
+		1 << ((self as u16) - 1)
+	}
+
+	/// Expects a number in the range `1..=15`.
+	#[must_use]
+	pub fn from_number(num: i64) -> Option<Self> {
+		const ALL: [SpawnFilter; 15] = [
+			SpawnFilter::Baby,
+			SpawnFilter::Easy,
+			SpawnFilter::Normal,
+			SpawnFilter::Hard,
+			SpawnFilter::Nightmare,
+			SpawnFilter::Udmf6,
+			SpawnFilter::Udmf7,
+			SpawnFilter::Udmf8,
+			SpawnFilter::Udmf9,
+			SpawnFilter::Udmf10,
+			SpawnFilter::Udmf11,
+			SpawnFilter::Udmf12,
+			SpawnFilter::Udmf13,
+			SpawnFilter::Udmf14,
+			SpawnFilter::Udmf15,
+		];
+
+		usize::try_from(num - 1)
+			.ok()
+			.and_then(|i| ALL.get(i))
+			.copied()
+	}
+
+	/// Accepts the names ZDoom allows for MAPINFO's `SpawnFilter`,
+	/// compared ASCII case-insensitively.
+	#[must_use]
+	pub fn from_name(name: &str) -> Option<Self> {
+		[
+			("baby", Self::Baby),
+			("easy", Self::Easy),
+			("normal", Self::Normal),
+			("hard", Self::Hard),
+			("nightmare", Self::Nightmare),
+		]
+		.into_iter()
+		.find_map(|(n, filter)| name.eq_ignore_ascii_case(n).then_some(filter))
+	}
+}
+
 bitflags! {
 	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 	pub struct SkillFlags: u16 {
@@ -65,3 +126,416 @@ bitflags! {
 		const AUTO_USE_HEALTH = 1 << 12;
 	}
 }
+
+/// The parts of a difficulty setting which the playsim consults.
+/// Built from MAPINFO by [`SkillDef::from_mapinfo`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkillDef {
+	/// The identifier given in MAPINFO, e.g. `hard`.
+	pub name: String,
+	/// Shown in the skill selection menu. A localization key if prefixed with `$`.
+	pub menu_name: String,
+	/// Things are spawned at this skill if their skill flags include this bit.
+	pub spawn_filter_bit: u16,
+	/// Multiplies all damage taken by actors.
+	pub damage_factor: f32,
+	/// Multiplies ammunition given by pickups.
+	pub ammo_factor: f32,
+	/// See [`SkillFlags::FAST_MONSTERS`].
+	pub fast_monsters: bool,
+	/// In ticks. Dead monsters come back to life after at least this long.
+	/// 0 means that monsters never respawn.
+	pub respawn_time: u32,
+	/// How many times any one monster can respawn. 0 means "without limit".
+	pub respawn_limit: u32,
+	/// Actors never enter their `Pain` state sequence.
+	pub no_pain: bool,
+	/// Health items in the player's inventory get used automatically
+	/// upon taking otherwise-fatal damage.
+	pub auto_use_health: bool,
+}
+
+impl SkillDef {
+	/// MAPINFO gives `RespawnTime` in seconds.
+	const TICRATE: u32 = 35;
+
+	/// The five skills of the original Doom games, from "I'm too young to die"
+	/// to "Nightmare!", in menu order.
+	#[must_use]
+	pub fn classic() -> [Self; 5] {
+		let skill = |name: &str, filter: SpawnFilter| Self {
+			name: name.to_string(),
+			menu_name: format!("$SKILL_{}", name.to_ascii_uppercase()),
+			spawn_filter_bit: filter.bit(),
+			damage_factor: 1.0,
+			ammo_factor: 1.0,
+			fast_monsters: false,
+			respawn_time: 0,
+			respawn_limit: 0,
+			no_pain: false,
+			auto_use_health: false,
+		};
+
+		[
+			Self {
+				damage_factor: 0.5,
+				ammo_factor: 2.0,
+				auto_use_health: true,
+				..skill("baby", SpawnFilter::Baby)
+			},
+			skill("easy", SpawnFilter::Easy),
+			skill("normal", SpawnFilter::Normal),
+			skill("hard", SpawnFilter::Hard),
+			Self {
+				ammo_factor: 2.0,
+				fast_monsters: true,
+				respawn_time: 12 * Self::TICRATE,
+				..skill("nightmare", SpawnFilter::Nightmare)
+			},
+		]
+	}
+
+	/// [`Self::classic`], with every `skill` block under `root` applied on top
+	/// by [`Self::merge_mapinfo`].
+	#[must_use]
+	pub fn from_mapinfo(root: &mapinfo::SyntaxNode) -> Vec<Self> {
+		let mut ret = Self::classic().into();
+		Self::merge_mapinfo(&mut ret, root);
+		ret
+	}
+
+	/// [`Self::classic`], with the `skill` blocks of each mount's `MAPINFO` and
+	/// `ZMAPINFO` (at the top level of a directory or archive, or as a WAD lump)
+	/// applied on top by [`Self::merge_mapinfo`] in load order. The result is
+	/// in the order which the skill selection menu should list it in.
+	/// Files which can not be read as UTF-8 text are skipped.
+	#[must_use]
+	pub fn from_vfs(vfs: &VirtualFs) -> Vec<Self> {
+		let mut ret = Self::classic().into();
+
+		for mount in vfs.mounts() {
+			let files: Vec<_> = match mount.root {
+				Slot::Folder(slot) => vfs
+					.get_folder(slot)
+					.map(|folder| folder.files().collect())
+					.unwrap_or_default(),
+				Slot::File(slot) => vfs.get_file(slot).into_iter().collect(),
+			};
+
+			for file in files {
+				let is_mapinfo = file.name().file_prefix().is_some_and(|pfx| {
+					pfx.as_str().eq_ignore_ascii_case("mapinfo")
+						|| pfx.as_str().eq_ignore_ascii_case("zmapinfo")
+				});
+
+				if !is_mapinfo {
+					continue;
+				}
+
+				let Ok(source) = file.copy_string() else {
+					continue;
+				};
+
+				let ptree: mapinfo::ParseTree = doomfront::parse(
+					&source,
+					mapinfo::parse::file,
+					zdoom::lex::Context::NON_ZSCRIPT,
+				);
+
+				Self::merge_mapinfo(&mut ret, &ptree.cursor());
+			}
+		}
+
+		ret
+	}
+
+	/// Reads every `skill` block under `root` in order. A block with the same name
+	/// (ASCII case-insensitively) as an element of `skills` replaces that element
+	/// in place; any other gets appended, so that `skills` stays in menu order.
+	/// Unknown properties are ignored, as are malformed values
+	/// (leaving the property at its default).
+	pub fn merge_mapinfo(skills: &mut Vec<Self>, root: &mapinfo::SyntaxNode) {
+		for skilldef in root.children().filter_map(ast::SkillDef::cast) {
+			let skill = Self::from_ast(&skilldef);
+
+			match skills
+				.iter_mut()
+				.find(|s| s.name.eq_ignore_ascii_case(&skill.name))
+			{
+				Some(existing) => *existing = skill,
+				None => skills.push(skill),
+			}
+		}
+	}
+
+	#[must_use]
+	fn from_ast(skilldef: &ast::SkillDef) -> Self {
+		let name = skilldef
+			.name()
+			.map(|token| token.text().to_string())
+			.unwrap_or_default();
+
+		let mut ret = Self {
+			menu_name: name.clone(),
+			name,
+			spawn_filter_bit: SpawnFilter::Normal.bit(),
+			damage_factor: 1.0,
+			ammo_factor: 1.0,
+			fast_monsters: false,
+			respawn_time: 0,
+			respawn_limit: 0,
+			no_pain: false,
+			auto_use_health: false,
+		};
+
+		for prop in skilldef.properties() {
+			let name = prop.name();
+			let name = name.text();
+			let value = prop.values().next();
+			let number = value.as_ref().and_then(ast::Value::number);
+
+			if name.eq_ignore_ascii_case("DamageFactor") {
+				if let Some(num) = number {
+					ret.damage_factor = num as f32;
+				}
+			} else if name.eq_ignore_ascii_case("AmmoFactor") {
+				if let Some(num) = number {
+					ret.ammo_factor = num as f32;
+				}
+			} else if name.eq_ignore_ascii_case("SpawnFilter") {
+				let filter = match (&value, number) {
+					(_, Some(num)) => SpawnFilter::from_number(num as i64),
+					(Some(val), None) => SpawnFilter::from_name(val.token().text()),
+					(None, None) => None,
+				};
+
+				if let Some(filter) = filter {
+					ret.spawn_filter_bit = filter.bit();
+				}
+			} else if name.eq_ignore_ascii_case("Name") {
+				if let Some(val) = &value {
+					ret.menu_name = val.token().text().trim_matches('"').to_string();
+				}
+			} else if name.eq_ignore_ascii_case("FastMonsters") {
+				ret.fast_monsters = true;
+			} else if name.eq_ignore_ascii_case("RespawnTime") {
+				if let Some(num) = number {
+					ret.respawn_time = (num.max(0.0) as u32) * Self::TICRATE;
+				}
+			} else if name.eq_ignore_ascii_case("RespawnLimit") {
+				if let Some(num) = number {
+					ret.respawn_limit = num.max(0.0) as u32;
+				}
+			} else if name.eq_ignore_ascii_case("NoPain") {
+				ret.no_pain = true;
+			} else if name.eq_ignore_ascii_case("AutoUseHealth") {
+				ret.auto_use_health = true;
+			}
+		}
+
+		ret
+	}
+
+	#[must_use]
+	pub fn respawns_monsters(&self) -> bool {
+		self.respawn_time > 0
+	}
+
+	/// Whether a thing with the given flags gets spawned at this skill.
+	///
+	/// Binary-format levels only have bits for the first five skills, so a skill
+	/// with a higher [spawn filter](SpawnFilter) spawns what Nightmare would.
+	#[must_use]
+	pub fn spawns(&self, flags: ThingFlags) -> bool {
+		let bit = if self.spawn_filter_bit > SpawnFilter::Nightmare.bit() {
+			ThingFlags::SKILL_5
+		} else {
+			ThingFlags::from_bits_truncate(self.spawn_filter_bit)
+		};
+
+		flags.intersects(bit)
+	}
+
+	/// Scales `amount` by [`Self::damage_factor`]. Non-zero damage never gets
+	/// scaled down to zero.
+	#[must_use]
+	pub fn scale_damage(&self, amount: i32) -> i32 {
+		if amount <= 0 {
+			return amount;
+		}
+
+		((amount as f32 * self.damage_factor) as i32).max(1)
+	}
+}
+
+impl Default for SkillDef {
+	/// "Hurt me plenty".
+	fn default() -> Self {
+		let [_, _, normal, _, _] = Self::classic();
+		normal
+	}
+}
+
+/// The things of `level` which should have an actor spawned for them
+/// when starting a game at `skill`, in order.
+pub fn things_to_spawn<'l>(
+	level: &'l Level,
+	skill: &'l SkillDef,
+) -> impl Iterator<Item = &'l Thing> {
+	level
+		.things
+		.iter()
+		.filter(move |thing| skill.spawns(thing.flags))
+}
+
+#[cfg(test)]
+mod test {
+	use crate::world::LevelFormat;
+
+	use super::*;
+
+	#[test]
+	fn custom_skill() {
+		const SOURCE: &str = r#"
+skill hard
+{
+	DamageFactor = 2.0
+	SpawnFilter = Hard
+}
+
+skill lorem
+{
+	Name = "$SKILL_LOREM"
+	DamageFactor = 1.5
+	SpawnFilter = Hard
+	FastMonsters
+	RespawnTime = 30
+}
+"#;
+
+		let ptree: mapinfo::ParseTree = doomfront::parse(
+			SOURCE,
+			mapinfo::parse::file,
+			zdoom::lex::Context::NON_ZSCRIPT,
+		);
+		assert!(!ptree.any_errors());
+
+		let skills = SkillDef::from_mapinfo(&ptree.cursor());
+		assert_eq!(skills.len(), 6);
+		assert_eq!(skills[3].name, "hard");
+		assert_eq!(skills[3].damage_factor, 2.0);
+		assert_eq!(skills[4].name, "nightmare");
+
+		let lorem = &skills[5];
+		assert_eq!(lorem.name, "lorem");
+		assert_eq!(lorem.menu_name, "$SKILL_LOREM");
+		assert_eq!(lorem.damage_factor, 1.5);
+		assert_eq!(lorem.spawn_filter_bit, SpawnFilter::Hard.bit());
+		assert!(lorem.fast_monsters);
+		assert_eq!(lorem.respawn_time, 30 * 35);
+		assert!(!lorem.auto_use_health);
+		assert_eq!(lorem.scale_damage(10), 15);
+
+		let ptree: mapinfo::ParseTree =
+			doomfront::parse("", mapinfo::parse::file, zdoom::lex::Context::NON_ZSCRIPT);
+		let skills = SkillDef::from_mapinfo(&ptree.cursor());
+		assert_eq!(skills, SkillDef::classic());
+	}
+
+	#[test]
+	fn skills_from_vfs() {
+		let dir = std::env::temp_dir().join(format!("viletech_skills_{}", std::process::id()));
+		let mod_dir = dir.join("mod");
+		std::fs::create_dir_all(&mod_dir).unwrap();
+
+		std::fs::write(
+			mod_dir.join("zmapinfo.txt"),
+			"skill lorem { SpawnFilter = Hard }\nskill hard { DamageFactor = 2.0 }",
+		)
+		.unwrap();
+
+		std::fs::write(
+			dir.join("later.wad"),
+			vfs::build_pwad(&[(
+				"MAPINFO",
+				b"skill lorem { Name = \"Lorem\" }\nskill ipsum { FastMonsters }".to_vec(),
+			)]),
+		)
+		.unwrap();
+
+		let mut vfs = VirtualFs::default();
+		vfs.mount(&mod_dir, vfs::VPath::new("mod")).unwrap();
+		vfs.mount(&dir.join("later.wad"), vfs::VPath::new("later"))
+			.unwrap();
+
+		let skills = SkillDef::from_vfs(&vfs);
+		let names: Vec<_> = skills.iter().map(|s| s.name.as_str()).collect();
+		assert_eq!(names[3..], ["hard", "nightmare", "lorem", "ipsum"]);
+
+		assert_eq!(skills[3].damage_factor, 2.0);
+		// The later mount's block replaces the whole skill, not just one property.
+		assert_eq!(skills[5].menu_name, "Lorem");
+		assert_eq!(skills[5].spawn_filter_bit, SpawnFilter::Normal.bit());
+		assert!(skills[6].fast_monsters);
+
+		assert_eq!(
+			SkillDef::from_vfs(&VirtualFs::default()),
+			SkillDef::classic()
+		);
+
+		let _ = std::fs::remove_dir_all(&dir);
+	}
+
+	#[test]
+	fn spawn_filtering() {
+		let thing = |ednum, flags| Thing {
+			ednum,
+			pos: Vec3::ZERO,
+			angle: 0,
+			flags: ThingFlags::SINGLEPLAY | flags,
+			tid: 0,
+			special: 0,
+			args: [0; 5],
+		};
+
+		let every = ThingFlags::SKILL_1
+			| ThingFlags::SKILL_2
+			| ThingFlags::SKILL_3
+			| ThingFlags::SKILL_4
+			| ThingFlags::SKILL_5;
+
+		let level = Level {
+			format: LevelFormat::Doom,
+			vertices: vec![],
+			linedefs: vec![],
+			sidedefs: vec![],
+			sectors: vec![],
+			things: vec![
+				// Player 1 start.
+				thing(1, every),
+				// A shotgun guy, only on easier skills.
+				thing(9, ThingFlags::SKILL_1 | ThingFlags::SKILL_2),
+				// A baron, only on harder skills.
+				thing(3003, ThingFlags::SKILL_4 | ThingFlags::SKILL_5),
+			],
+		};
+
+		let [baby, _, _, hard, nightmare] = SkillDef::classic();
+
+		let ednums = |skill: &SkillDef| -> Vec<u16> {
+			things_to_spawn(&level, skill)
+				.map(|thing| thing.ednum)
+				.collect()
+		};
+
+		assert_eq!(ednums(&baby), [1, 9]);
+		assert_eq!(ednums(&hard), [1, 3003]);
+
+		let udmf7 = SkillDef {
+			spawn_filter_bit: SpawnFilter::Udmf7.bit(),
+			..nightmare.clone()
+		};
+
+		assert_eq!(ednums(&udmf7), ednums(&nightmare));
+	}
+}
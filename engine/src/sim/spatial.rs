@@ -0,0 +1,1253 @@
+//! Geometric queries against a level's BSP tree, for AI, hitscans, sound
+//! propagation, and anything else needing to know where things are or what
+//! they can see.
+
+use std::{
+	ops::Range,
+	sync::atomic::{self, AtomicU32},
+};
+
+use bevy::prelude::*;
+use data::level::{
+	read::{BspNodeChild, ExtendedNodes, LineFlags, SegDirection},
+	Error, RawLevel,
+};
+
+use crate::world::FSCALE;
+
+/// Built from a level's raw geometry and owned by the [`Sim`](super::Sim).
+///
+/// All queries take `&self` and never allocate. [`Self::line_of_sight`] marks
+/// lines it has already checked via per-line atomic stamps (Doom's `validcount`),
+/// so queries can also safely run in parallel.
+#[derive(Debug)]
+pub struct SpatialQuery {
+	lines: Vec<LineGeom>,
+	segs: Vec<SegGeom>,
+	subsectors: Vec<SubSectorGeom>,
+	/// The root is the last element. May be empty.
+	nodes: Vec<NodeGeom>,
+	sectors: Vec<SectorHeights>,
+	/// Parallel to `sectors`; indices into `lines` bordering each sector.
+	sector_lines: Vec<Vec<usize>>,
+	/// One bit per pair of sectors; see <https://doomwiki.org/wiki/Reject>.
+	reject: Option<Vec<u8>>,
+	/// Parallel to `lines`.
+	line_stamps: Vec<AtomicU32>,
+	stamp: AtomicU32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubSectorIndex(usize);
+
+impl From<SubSectorIndex> for usize {
+	fn from(value: SubSectorIndex) -> Self {
+		value.0
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SectorIndex(usize);
+
+impl From<SectorIndex> for usize {
+	fn from(value: SectorIndex) -> Self {
+		value.0
+	}
+}
+
+/// In map units. A sector is closed (like a shut door) if `ceiling <= floor`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SectorHeights {
+	pub floor: f32,
+	pub ceiling: f32,
+}
+
+/// The vertical space available at a position; see [`SpatialQuery::check_position`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Opening {
+	/// The highest floor touched.
+	pub floor: f32,
+	/// The lowest ceiling touched.
+	pub ceiling: f32,
+	/// The lowest floor touched.
+	pub dropoff: f32,
+}
+
+#[derive(Debug)]
+struct LineGeom {
+	v1: Vec2,
+	v2: Vec2,
+	front: usize,
+	/// `None` for one-sided lines, which always block sight.
+	back: Option<usize>,
+	flags: LineFlags,
+}
+
+impl LineGeom {
+	#[must_use]
+	fn sector(&self, direction: SegDirection) -> usize {
+		match direction {
+			SegDirection::Front => self.front,
+			SegDirection::Back => self.back.unwrap_or(self.front),
+		}
+	}
+}
+
+#[derive(Debug)]
+struct SegGeom {
+	v1: Vec2,
+	v2: Vec2,
+	/// `None` for a GL mini-seg.
+	line: Option<usize>,
+}
+
+#[derive(Debug)]
+struct SubSectorGeom {
+	segs: Range<usize>,
+	sector: usize,
+}
+
+#[derive(Debug)]
+struct NodeGeom {
+	origin: Vec2,
+	delta: Vec2,
+	/// Right (front) and left (back), in that order.
+	children: [BspNodeChild; 2],
+}
+
+/// Segs, sub-sectors, and nodes, in that order.
+type Bsp = (Vec<SegGeom>, Vec<SubSectorGeom>, Vec<NodeGeom>);
+
+/// State of one [`SpatialQuery::line_of_sight`] call.
+#[derive(Debug)]
+struct Trace {
+	origin: Vec2,
+	delta: Vec2,
+	end: Vec2,
+	z: f32,
+	/// Heights relative to `z`, per unit of the trace's length.
+	top_slope: f32,
+	bottom_slope: f32,
+	stamp: u32,
+}
+
+impl SpatialQuery {
+	/// If the level has a `ZNODES` lump, pass it as `znodes` and its tree gets
+	/// used in place of the level's `SEGS`, `SSECTORS`, and `NODES`, which then
+	/// go unread. `reject` is the level's `REJECT` lump, if it has one; if it
+	/// is shorter than it should be, missing bits are treated as unset (like in vanilla).
+	pub fn new(
+		level: &RawLevel,
+		znodes: Option<&ExtendedNodes>,
+		reject: Option<&[u8]>,
+	) -> Result<Self, Error> {
+		let side_sector = |linedef: usize, left: bool, sidedef: u16| -> Result<usize, Error> {
+			let Some(side) = level.sidedefs.get(sidedef as usize) else {
+				return Err(Error::InvalidLinedefSide {
+					linedef,
+					left,
+					sidedef: sidedef as usize,
+					sides_len: level.sidedefs.len(),
+				});
+			};
+
+			let sector = side.sector() as usize;
+
+			if sector >= level.sectors.len() {
+				return Err(Error::InvalidSidedefSector {
+					sidedef: sidedef as usize,
+					sector,
+					sectors_len: level.sectors.len(),
+				});
+			}
+
+			Ok(sector)
+		};
+
+		let mut lines = Vec::with_capacity(level.linedefs.len());
+
+		for (i, linedef) in level.linedefs.iter().enumerate() {
+			lines.push(LineGeom {
+				v1: vertex(level, linedef.start_vertex, "LINEDEFS")?,
+				v2: vertex(level, linedef.end_vertex, "LINEDEFS")?,
+				front: side_sector(i, false, linedef.right_side)?,
+				back: match linedef.left_side {
+					Some(s) => Some(side_sector(i, true, s)?),
+					None => None,
+				},
+				flags: linedef.flags,
+			});
+		}
+
+		let (segs, subsectors, nodes) = match znodes {
+			Some(ext) => Self::extended_bsp(level, ext, &lines)?,
+			None => Self::vanilla_bsp(level, &lines)?,
+		};
+
+		let sectors = level
+			.sectors
+			.iter()
+			.map(|sector| SectorHeights {
+				floor: sector.floor_height() as f32 * FSCALE,
+				ceiling: sector.ceiling_height() as f32 * FSCALE,
+			})
+			.collect();
+
+		let mut sector_lines = vec![vec![]; level.sectors.len()];
+
+		for (i, line) in lines.iter().enumerate() {
+			sector_lines[line.front].push(i);
+
+			if let Some(back) = line.back.filter(|b| *b != line.front) {
+				sector_lines[back].push(i);
+			}
+		}
+
+		Ok(Self {
+			line_stamps: lines.iter().map(|_| AtomicU32::new(0)).collect(),
+			lines,
+			segs,
+			subsectors,
+			nodes,
+			sectors,
+			sector_lines,
+			reject: reject.map(|r| r.to_vec()),
+			stamp: AtomicU32::new(0),
+		})
+	}
+
+	fn vanilla_bsp(level: &RawLevel, lines: &[LineGeom]) -> Result<Bsp, Error> {
+		let mut segs = Vec::with_capacity(level.segs.len());
+
+		for (i, seg) in level.segs.iter().enumerate() {
+			let line = seg.linedef() as usize;
+
+			if line >= lines.len() {
+				return Err(Error::InvalidSegLinedef {
+					seg: i,
+					linedef: line,
+					lines_len: lines.len(),
+				});
+			}
+
+			segs.push(SegGeom {
+				v1: vertex(level, seg.start_vertex(), "SEGS")?,
+				v2: vertex(level, seg.end_vertex(), "SEGS")?,
+				line: Some(line),
+			});
+		}
+
+		let mut subsectors = Vec::with_capacity(level.subsectors.len());
+
+		for (i, ssector) in level.subsectors.iter().enumerate() {
+			let range = ssector.segs();
+
+			let Some(first) = level
+				.segs
+				.get(range.start)
+				.filter(|_| range.end <= segs.len())
+			else {
+				return Err(Error::InvalidSubsectorSeg {
+					subsector: i,
+					seg: range.end.saturating_sub(1).max(range.start),
+					segs_len: segs.len(),
+				});
+			};
+
+			subsectors.push(SubSectorGeom {
+				segs: range,
+				sector: lines[first.linedef() as usize].sector(first.direction()),
+			});
+		}
+
+		let mut nodes = Vec::with_capacity(level.nodes.len());
+
+		for (i, node) in level.nodes.iter().enumerate() {
+			let children = [node.child_r(), node.child_l()];
+			check_children(i, children, subsectors.len(), level.nodes.len())?;
+
+			let [x, y] = node.seg_start();
+			let [dx, dy] = node.seg_delta();
+
+			nodes.push(NodeGeom {
+				origin: Vec2::new(x as f32, y as f32) * FSCALE,
+				delta: Vec2::new(dx as f32, dy as f32) * FSCALE,
+				children,
+			});
+		}
+
+		Ok((segs, subsectors, nodes))
+	}
+
+	/// Like [`Self::vanilla_bsp`], but GL mini-segs are kept (with no line),
+	/// since they help bound sub-sectors for [`Self::subsector_at_linear`].
+	fn extended_bsp(
+		level: &RawLevel,
+		ext: &ExtendedNodes,
+		lines: &[LineGeom],
+	) -> Result<Bsp, Error> {
+		let vert = |seg: usize, index: u32| -> Result<Vec2, Error> {
+			let i = index as usize;
+			let orig = ext.orig_vert_count as usize;
+
+			let pos = if i < orig {
+				level.vertices.get(i).map(|v| {
+					let [x, y] = v.position();
+					Vec2::new(x as f32, y as f32)
+				})
+			} else {
+				ext.new_vertices
+					.get(i - orig)
+					.map(|[x, y]| Vec2::new(*x as f32, *y as f32) / FRACUNIT)
+			};
+
+			pos.map(|p| p * FSCALE).ok_or(Error::InvalidSegVertex {
+				seg,
+				vertex: i,
+				verts_len: ext.vertex_count(),
+			})
+		};
+
+		let mut segs = Vec::with_capacity(ext.segs.len());
+
+		for (i, seg) in ext.segs.iter().enumerate() {
+			let line = seg.linedef.map(|l| l as usize);
+
+			if let Some(l) = line.filter(|l| *l >= lines.len()) {
+				return Err(Error::InvalidSegLinedef {
+					seg: i,
+					linedef: l,
+					lines_len: lines.len(),
+				});
+			}
+
+			segs.push(SegGeom {
+				v1: vert(i, seg.v_start)?,
+				v2: vert(i, seg.v_end)?,
+				line,
+			});
+		}
+
+		let mut subsectors = Vec::with_capacity(ext.subsectors.len());
+
+		for (i, range) in ext.subsectors.iter().enumerate() {
+			if range.end > segs.len() {
+				return Err(Error::InvalidSubsectorSeg {
+					subsector: i,
+					seg: range.end.saturating_sub(1).max(range.start),
+					segs_len: segs.len(),
+				});
+			}
+
+			let Some(first) = ext.segs[range.clone()]
+				.iter()
+				.find_map(|seg| seg.linedef.map(|l| (l, seg.direction)))
+			else {
+				return Err(Error::MalformedFile("ZNODES"));
+			};
+
+			subsectors.push(SubSectorGeom {
+				segs: range.clone(),
+				sector: lines[first.0 as usize].sector(first.1),
+			});
+		}
+
+		let mut nodes = Vec::with_capacity(ext.nodes.len());
+
+		for (i, node) in ext.nodes.iter().enumerate() {
+			let children = [node.child_r, node.child_l];
+			check_children(i, children, subsectors.len(), ext.nodes.len())?;
+
+			let [x, y] = node.seg_start;
+			let [dx, dy] = node.seg_delta;
+
+			nodes.push(NodeGeom {
+				origin: Vec2::new(x as f32, y as f32) / FRACUNIT * FSCALE,
+				delta: Vec2::new(dx as f32, dy as f32) / FRACUNIT * FSCALE,
+				children,
+			});
+		}
+
+		Ok((segs, subsectors, nodes))
+	}
+
+	/// Descends the BSP tree. If the level has no nodes (or they are cyclic),
+	/// each sub-sector gets tested in turn instead; if `pos` is outside all of
+	/// them, the first sub-sector is returned.
+	#[must_use]
+	pub fn subsector_at(&self, pos: Vec2) -> SubSectorIndex {
+		let Some(mut node) = self.nodes.len().checked_sub(1) else {
+			return self.subsector_at_linear(pos);
+		};
+
+		for _ in 0..self.nodes.len() {
+			let n = &self.nodes[node];
+
+			match n.children[point_side(n.origin, n.delta, pos)] {
+				BspNodeChild::SubSector(ss) => return SubSectorIndex(ss),
+				BspNodeChild::SubNode(sn) => node = sn,
+			}
+		}
+
+		self.subsector_at_linear(pos)
+	}
+
+	#[must_use]
+	pub fn sector_at(&self, pos: Vec2) -> SectorIndex {
+		self.sector_of(self.subsector_at(pos))
+	}
+
+	#[must_use]
+	pub fn sector_of(&self, subsector: SubSectorIndex) -> SectorIndex {
+		SectorIndex(self.subsectors[subsector.0].sector)
+	}
+
+	#[must_use]
+	pub fn sector_heights(&self, sector: SectorIndex) -> SectorHeights {
+		self.sectors[sector.0]
+	}
+
+	#[must_use]
+	pub fn sector_count(&self) -> usize {
+		self.sectors.len()
+	}
+
+	/// To be called whenever a sector's floor or ceiling moves, e.g. when a door opens.
+	pub fn set_sector_heights(&mut self, sector: SectorIndex, heights: SectorHeights) {
+		self.sectors[sector.0] = heights;
+	}
+
+	/// Vanilla Doom's sight check (`P_CheckSight`), between two points.
+	///
+	/// The `REJECT` table is consulted first, if there is one. Then the BSP tree
+	/// is walked from `from` to `to`; sight is blocked by any one-sided line or
+	/// closed sector along the way, or if the openings between the floors and
+	/// ceilings crossed leave no room for a straight line between the two points.
+	#[must_use]
+	pub fn line_of_sight(&self, from: Vec3, to: Vec3) -> bool {
+		let s1 = self.sector_at(from.truncate());
+		let s2 = self.sector_at(to.truncate());
+
+		if self.rejected(s1, s2) {
+			return false;
+		}
+
+		let mut trace = Trace {
+			origin: from.truncate(),
+			delta: (to - from).truncate(),
+			end: to.truncate(),
+			z: from.z,
+			top_slope: to.z - from.z,
+			bottom_slope: to.z - from.z,
+			stamp: self.next_stamp(),
+		};
+
+		let Some(root) = self.nodes.len().checked_sub(1) else {
+			return (0..self.subsectors.len()).all(|ss| self.cross_subsector(ss, &mut trace));
+		};
+
+		self.cross_node(root, &mut trace, self.nodes.len())
+	}
+
+	/// Where a hitscan attack from `from` to `to` first strikes level geometry,
+	/// as a fraction of the way there (from 0.0 to 1.0). One-sided lines always
+	/// stop the trace; two-sided lines do so if it passes them outside of the
+	/// opening between their floors and ceilings, which a closed sector lacks.
+	///
+	/// As in vanilla, floors and ceilings themselves are never struck.
+	/// Note that every line in the level is tested, since there is no blockmap yet.
+	#[must_use]
+	pub fn trace_geometry(&self, from: Vec3, to: Vec3) -> Option<f32> {
+		let origin = from.truncate();
+		let delta = (to - from).truncate();
+		let mut nearest: Option<f32> = None;
+
+		for line in &self.lines {
+			let Some(frac) = segment_intercept(origin, delta, line.v1, line.v2) else {
+				continue;
+			};
+
+			if nearest.is_some_and(|n| n <= frac) {
+				continue;
+			}
+
+			let blocks = match line.back {
+				None => true,
+				Some(back) => {
+					let front = self.sectors[line.front];
+					let back = self.sectors[back];
+					let z = from.z + (to.z - from.z) * frac;
+
+					z <= front.floor.max(back.floor) || z >= front.ceiling.min(back.ceiling)
+				}
+			};
+
+			if blocks {
+				nearest = Some(frac);
+			}
+		}
+
+		nearest
+	}
+
+	/// Vanilla Doom's `P_CheckPosition`, minus actor collision: whether a box
+	/// with half-width `radius` centered on `pos` can be there without crossing
+	/// a blocking line. One-sided and impassible lines always block; lines which
+	/// block monsters do so if `monster` is `true`. Returns `None` if blocked.
+	///
+	/// Note that every line in the level is tested, since there is no blockmap yet.
+	#[must_use]
+	pub fn check_position(&self, pos: Vec2, radius: f32, monster: bool) -> Option<Opening> {
+		let heights = self.sector_heights(self.sector_at(pos));
+
+		let mut opening = Opening {
+			floor: heights.floor,
+			ceiling: heights.ceiling,
+			dropoff: heights.floor,
+		};
+
+		let (min, max) = (pos - radius, pos + radius);
+
+		for line in &self.lines {
+			if !box_crosses_line(min, max, line.v1, line.v2) {
+				continue;
+			}
+
+			let Some(back) = line.back else {
+				return None;
+			};
+
+			if line.flags.contains(LineFlags::IMPASSIBLE)
+				|| (monster && line.flags.contains(LineFlags::BLOCK_MONS))
+			{
+				return None;
+			}
+
+			let front = self.sectors[line.front];
+			let back = self.sectors[back];
+
+			opening.ceiling = opening.ceiling.min(front.ceiling.min(back.ceiling));
+			opening.floor = opening.floor.max(front.floor.max(back.floor));
+			opening.dropoff = opening.dropoff.min(front.floor.min(back.floor));
+		}
+
+		Some(opening)
+	}
+
+	/// Vanilla Doom's `P_RecursiveSound`: floods outward from `origin` through
+	/// every two-sided line which is not closed off (e.g. by a shut door).
+	/// Noise passes through one [sound-blocking](LineFlags::BLOCK_SOUND) line,
+	/// but stops at the second.
+	///
+	/// `traversed` must have one zeroed element per sector. Afterwards, each
+	/// element is non-zero if the noise reached that sector.
+	pub fn flood_sound(&self, origin: SectorIndex, traversed: &mut [u8]) {
+		assert_eq!(
+			traversed.len(),
+			self.sectors.len(),
+			"sound traversal buffer length mismatch"
+		);
+
+		self.recursive_sound(origin.0, 0, traversed);
+	}
+
+	fn recursive_sound(&self, sector: usize, sound_blocks: u8, traversed: &mut [u8]) {
+		if traversed[sector] != 0 && traversed[sector] <= sound_blocks + 1 {
+			return;
+		}
+
+		traversed[sector] = sound_blocks + 1;
+
+		for &l in &self.sector_lines[sector] {
+			let line = &self.lines[l];
+
+			let Some(back) = line.back else {
+				continue;
+			};
+
+			let front_h = self.sectors[line.front];
+			let back_h = self.sectors[back];
+
+			if front_h.ceiling.min(back_h.ceiling) <= front_h.floor.max(back_h.floor) {
+				continue;
+			}
+
+			let other = if line.front == sector {
+				back
+			} else {
+				line.front
+			};
+
+			if !line.flags.contains(LineFlags::BLOCK_SOUND) {
+				self.recursive_sound(other, sound_blocks, traversed);
+			} else if sound_blocks == 0 {
+				self.recursive_sound(other, 1, traversed);
+			}
+		}
+	}
+
+	#[must_use]
+	fn subsector_at_linear(&self, pos: Vec2) -> SubSectorIndex {
+		let found = self.subsectors.iter().position(|ssector| {
+			self.segs[ssector.segs.clone()]
+				.iter()
+				.all(|seg| divline_side(seg.v1, seg.v2 - seg.v1, pos) != 1)
+		});
+
+		SubSectorIndex(found.unwrap_or(0))
+	}
+
+	#[must_use]
+	fn rejected(&self, s1: SectorIndex, s2: SectorIndex) -> bool {
+		let Some(reject) = &self.reject else {
+			return false;
+		};
+
+		let bit = s1.0 * self.sectors.len() + s2.0;
+
+		reject
+			.get(bit / 8)
+			.is_some_and(|byte| (byte & (1 << (bit % 8))) != 0)
+	}
+
+	#[must_use]
+	fn next_stamp(&self) -> u32 {
+		loop {
+			// (RAT) 0 is what every line starts with, so it can never be used
+			// to mark a line as checked.
+			let stamp = self
+				.stamp
+				.fetch_add(1, atomic::Ordering::Relaxed)
+				.wrapping_add(1);
+
+			if stamp != 0 {
+				return stamp;
+			}
+		}
+	}
+
+	/// Returns `false` if sight is blocked. `depth` guards against cyclic trees.
+	#[must_use]
+	fn cross_node(&self, node: usize, trace: &mut Trace, depth: usize) -> bool {
+		let n = &self.nodes[node];
+
+		let side = match divline_side(n.origin, n.delta, trace.origin) {
+			2 => 0,
+			s => s,
+		};
+
+		if !self.cross_child(n.children[side], trace, depth) {
+			return false;
+		}
+
+		if side == divline_side(n.origin, n.delta, trace.end) {
+			return true;
+		}
+
+		self.cross_child(n.children[side ^ 1], trace, depth)
+	}
+
+	#[must_use]
+	fn cross_child(&self, child: BspNodeChild, trace: &mut Trace, depth: usize) -> bool {
+		match child {
+			BspNodeChild::SubSector(ss) => self.cross_subsector(ss, trace),
+			BspNodeChild::SubNode(sn) => depth > 0 && self.cross_node(sn, trace, depth - 1),
+		}
+	}
+
+	#[must_use]
+	fn cross_subsector(&self, subsector: usize, trace: &mut Trace) -> bool {
+		for seg in &self.segs[self.subsectors[subsector].segs.clone()] {
+			let Some(l) = seg.line else {
+				continue;
+			};
+
+			if self.line_stamps[l].swap(trace.stamp, atomic::Ordering::Relaxed) == trace.stamp {
+				continue;
+			}
+
+			let line = &self.lines[l];
+
+			if divline_side(trace.origin, trace.delta, line.v1)
+				== divline_side(trace.origin, trace.delta, line.v2)
+			{
+				continue;
+			}
+
+			let ldelta = line.v2 - line.v1;
+
+			if divline_side(line.v1, ldelta, trace.origin)
+				== divline_side(line.v1, ldelta, trace.end)
+			{
+				continue;
+			}
+
+			let Some(back) = line.back else {
+				return false;
+			};
+
+			let front = self.sectors[line.front];
+			let back = self.sectors[back];
+
+			if front.floor == back.floor && front.ceiling == back.ceiling {
+				continue;
+			}
+
+			let open_top = front.ceiling.min(back.ceiling);
+			let open_bottom = front.floor.max(back.floor);
+
+			if open_bottom >= open_top {
+				return false;
+			}
+
+			let frac = intercept(trace, line.v1, ldelta).max(f32::EPSILON);
+
+			if front.floor != back.floor {
+				trace.bottom_slope = trace.bottom_slope.max((open_bottom - trace.z) / frac);
+			}
+
+			if front.ceiling != back.ceiling {
+				trace.top_slope = trace.top_slope.min((open_top - trace.z) / frac);
+			}
+
+			// (RAT) Vanilla blocks on `<=`, but its target is a bounding box
+			// rather than a single point, which would always fail that test.
+			if trace.top_slope < trace.bottom_slope {
+				return false;
+			}
+		}
+
+		true
+	}
+}
+
+/// 16.16 fixed-point to map units.
+const FRACUNIT: f32 = 65536.0;
+
+fn vertex(level: &RawLevel, index: u16, file: &'static str) -> Result<Vec2, Error> {
+	let Some(v) = level.vertices.get(index as usize) else {
+		return Err(Error::MalformedFile(file));
+	};
+
+	let [x, y] = v.position();
+	Ok(Vec2::new(x as f32, y as f32) * FSCALE)
+}
+
+fn check_children(
+	node: usize,
+	children: [BspNodeChild; 2],
+	ssectors_len: usize,
+	nodes_len: usize,
+) -> Result<(), Error> {
+	for (c, child) in children.iter().enumerate() {
+		match *child {
+			BspNodeChild::SubSector(ss) if ss >= ssectors_len => {
+				return Err(Error::InvalidNodeSubsector {
+					node,
+					left: c == 1,
+					ssector: ss,
+					ssectors_len,
+				});
+			}
+			BspNodeChild::SubNode(n) if n >= nodes_len => {
+				return Err(Error::InvalidSubnode {
+					node,
+					left: c == 1,
+					subnode: n,
+					nodes_len,
+				});
+			}
+			_ => {}
+		}
+	}
+
+	Ok(())
+}
+
+/// Vanilla's `R_PointOnSide`: 0 for the right (front) side
+/// of the line through `origin` along `delta`, 1 for the left (back).
+#[must_use]
+fn point_side(origin: Vec2, delta: Vec2, point: Vec2) -> usize {
+	let left = delta.y * (point.x - origin.x);
+	let right = (point.y - origin.y) * delta.x;
+
+	if right < left {
+		0
+	} else {
+		1
+	}
+}
+
+/// Vanilla's `P_DivlineSide`: like [`point_side`],
+/// but returns 2 if `point` is on the line.
+#[must_use]
+fn divline_side(origin: Vec2, delta: Vec2, point: Vec2) -> usize {
+	let left = delta.y * (point.x - origin.x);
+	let right = (point.y - origin.y) * delta.x;
+
+	if right < left {
+		0
+	} else if left == right {
+		2
+	} else {
+		1
+	}
+}
+
+/// Whether the line from `v1` to `v2` passes through the interior of the box
+/// from `min` to `max`. Merely touching its edge does not count.
+#[must_use]
+fn box_crosses_line(min: Vec2, max: Vec2, v1: Vec2, v2: Vec2) -> bool {
+	if max.x <= v1.x.min(v2.x)
+		|| min.x >= v1.x.max(v2.x)
+		|| max.y <= v1.y.min(v2.y)
+		|| min.y >= v1.y.max(v2.y)
+	{
+		return false;
+	}
+
+	let delta = v2 - v1;
+	let corners = [min, Vec2::new(min.x, max.y), max, Vec2::new(max.x, min.y)];
+	let sides = corners.map(|c| delta.perp_dot(c - v1));
+
+	sides.iter().any(|s| *s > 0.0) && sides.iter().any(|s| *s < 0.0)
+}
+
+/// Vanilla's `P_InterceptVector2`: how far along `trace` (from 0.0 to 1.0)
+/// it crosses the line through `origin` along `delta`.
+#[must_use]
+fn intercept(trace: &Trace, origin: Vec2, delta: Vec2) -> f32 {
+	let den = delta.y * trace.delta.x - delta.x * trace.delta.y;
+
+	if den == 0.0 {
+		return 0.0;
+	}
+
+	let num = (origin.x - trace.origin.x) * delta.y + (trace.origin.y - origin.y) * delta.x;
+	num / den
+}
+
+/// How far along the segment from `origin` to `origin + delta` (from 0.0 to 1.0)
+/// it crosses the segment from `v1` to `v2`, if it does at all.
+/// Parallel segments never cross.
+#[must_use]
+fn segment_intercept(origin: Vec2, delta: Vec2, v1: Vec2, v2: Vec2) -> Option<f32> {
+	let edge = v2 - v1;
+	let den = delta.perp_dot(edge);
+
+	if den == 0.0 {
+		return None;
+	}
+
+	let w = v1 - origin;
+	let t = w.perp_dot(edge) / den;
+	let u = w.perp_dot(delta) / den;
+
+	((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some(t)
+}
+
+#[cfg(test)]
+pub(super) mod test {
+	use data::level::{read, RawLineDefs, RawThings};
+
+	use super::*;
+
+	/// Three rectangular rooms in a row along the X axis, each 128 units tall:
+	/// room A (sector 0) spans `0..128`, a door (sector 1) spans `128..144`,
+	/// and room B (sector 2) spans `144..272`. Each sector is one sub-sector.
+	/// The root node partitions at x = 128, and its right child at x = 144.
+	pub(in crate::sim) struct Lumps {
+		verts: Vec<u8>,
+		lines: Vec<u8>,
+		sides: Vec<u8>,
+		sectors: Vec<u8>,
+		segs: Vec<u8>,
+		ssectors: Vec<u8>,
+		nodes: Vec<u8>,
+	}
+
+	impl Lumps {
+		#[must_use]
+		pub(in crate::sim) fn new(door_ceiling: i16) -> Self {
+			let mut verts = vec![];
+
+			for (x, y) in [
+				(0, 0),
+				(128, 0),
+				(144, 0),
+				(272, 0),
+				(272, 128),
+				(144, 128),
+				(128, 128),
+				(0, 128),
+			] {
+				push_i16s(&mut verts, &[x, y]);
+			}
+
+			// Start vertex, end vertex, right side, left side.
+			const LINES: &[[u16; 4]] = &[
+				[0, 7, 0, 0xFFFF],
+				[7, 6, 1, 0xFFFF],
+				[1, 0, 2, 0xFFFF],
+				[6, 1, 3, 4],
+				[6, 5, 5, 0xFFFF],
+				[2, 1, 6, 0xFFFF],
+				[5, 2, 7, 8],
+				[5, 4, 9, 0xFFFF],
+				[4, 3, 10, 0xFFFF],
+				[3, 2, 11, 0xFFFF],
+			];
+
+			let mut lines = vec![];
+
+			for &[v1, v2, right, left] in LINES {
+				let flags = if left == 0xFFFF { 0 } else { 4 };
+				push_u16s(&mut lines, &[v1, v2, flags, 0, 0, right, left]);
+			}
+
+			let mut sides = vec![];
+
+			for sector in [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2] {
+				push_i16s(&mut sides, &[0, 0]);
+				sides.extend_from_slice(&[0; 24]);
+				push_u16s(&mut sides, &[sector]);
+			}
+
+			let mut sectors = vec![];
+
+			for ceiling in [128, door_ceiling, 128] {
+				push_i16s(&mut sectors, &[0, ceiling]);
+				sectors.extend_from_slice(&[0; 16]);
+				push_u16s(&mut sectors, &[160, 0, 0]);
+			}
+
+			// Start vertex, end vertex, line, direction.
+			const SEGS: &[[u16; 4]] = &[
+				[0, 7, 0, 0],
+				[7, 6, 1, 0],
+				[6, 1, 3, 0],
+				[1, 0, 2, 0],
+				[1, 6, 3, 1],
+				[6, 5, 4, 0],
+				[5, 2, 6, 0],
+				[2, 1, 5, 0],
+				[2, 5, 6, 1],
+				[5, 4, 7, 0],
+				[4, 3, 8, 0],
+				[3, 2, 9, 0],
+			];
+
+			let mut segs = vec![];
+
+			for &[v1, v2, line, dir] in SEGS {
+				push_u16s(&mut segs, &[v1, v2, 0, line, dir, 0]);
+			}
+
+			let mut ssectors = vec![];
+			push_u16s(&mut ssectors, &[4, 0, 4, 4, 4, 8]);
+
+			const SUBSECTOR: u16 = 0x8000;
+			let mut nodes = vec![];
+
+			for (x, right, left) in [(144, SUBSECTOR | 2, SUBSECTOR | 1), (128, 0, SUBSECTOR)] {
+				push_i16s(&mut nodes, &[x, 0, 0, 128]);
+				push_i16s(&mut nodes, &[0; 8]);
+				push_u16s(&mut nodes, &[right, left]);
+			}
+
+			Self {
+				verts,
+				lines,
+				sides,
+				sectors,
+				segs,
+				ssectors,
+				nodes,
+			}
+		}
+
+		#[must_use]
+		pub(in crate::sim) fn query(
+			&self,
+			with_nodes: bool,
+			reject: Option<&[u8]>,
+		) -> SpatialQuery {
+			SpatialQuery::new(&self.raw(with_nodes), None, reject).unwrap()
+		}
+
+		#[must_use]
+		fn raw(&self, with_nodes: bool) -> RawLevel<'_> {
+			RawLevel {
+				linedefs: RawLineDefs::Doom(read::linedefs(&self.lines).unwrap()),
+				nodes: if with_nodes {
+					read::nodes(&self.nodes).unwrap()
+				} else {
+					&[]
+				},
+				sectors: read::sectors(&self.sectors).unwrap(),
+				segs: read::segs(&self.segs).unwrap(),
+				sidedefs: read::sidedefs(&self.sides).unwrap(),
+				subsectors: read::ssectors(&self.ssectors).unwrap(),
+				things: RawThings::Doom(&[]),
+				vertices: read::vertexes(&self.verts).unwrap(),
+			}
+		}
+	}
+
+	fn push_i16s(bytes: &mut Vec<u8>, values: &[i16]) {
+		for v in values {
+			bytes.extend_from_slice(&v.to_le_bytes());
+		}
+	}
+
+	fn push_u16s(bytes: &mut Vec<u8>, values: &[u16]) {
+		for v in values {
+			bytes.extend_from_slice(&v.to_le_bytes());
+		}
+	}
+
+	pub(in crate::sim) const EYE_A: Vec3 = Vec3::new(64.0, 64.0, 41.0);
+	pub(in crate::sim) const EYE_B: Vec3 = Vec3::new(208.0, 64.0, 41.0);
+
+	#[test]
+	fn subsector_lookup() {
+		let lumps = Lumps::new(128);
+
+		for with_nodes in [true, false] {
+			let query = lumps.query(with_nodes, None);
+
+			for (x, expected) in [(127.9, 0), (128.1, 1), (143.9, 1), (144.1, 2), (200.0, 2)] {
+				let ss = query.subsector_at(Vec2::new(x, 64.0));
+				assert_eq!(usize::from(ss), expected, "x = {x}, nodes: {with_nodes}");
+				assert_eq!(usize::from(query.sector_of(ss)), expected);
+			}
+		}
+
+		// Exactly on a partition line counts as being on its left (back).
+		let query = lumps.query(true, None);
+		assert_eq!(usize::from(query.sector_at(Vec2::new(128.0, 64.0))), 0);
+	}
+
+	/// The same tree as [`Lumps`], but as a node builder might write it to
+	/// `ZNODES`: room A's south wall is split at a new vertex (x = 64).
+	#[must_use]
+	fn extended_nodes() -> ExtendedNodes {
+		// Start vertex, end vertex, line, direction.
+		const SEGS: &[[u32; 4]] = &[
+			[0, 7, 0, 0],
+			[7, 6, 1, 0],
+			[6, 1, 3, 0],
+			[1, 8, 2, 0],
+			[8, 0, 2, 0],
+			[1, 6, 3, 1],
+			[6, 5, 4, 0],
+			[5, 2, 6, 0],
+			[2, 1, 5, 0],
+			[2, 5, 6, 1],
+			[5, 4, 7, 0],
+			[4, 3, 8, 0],
+			[3, 2, 9, 0],
+		];
+
+		ExtendedNodes {
+			format: read::ZNodesFormat::Normal,
+			compressed: false,
+			orig_vert_count: 8,
+			new_vertices: vec![[64 << 16, 0]],
+			subsectors: vec![0..5, 5..9, 9..13],
+			segs: SEGS
+				.iter()
+				.map(|&[v_start, v_end, line, dir]| read::ExtSegRaw {
+					v_start,
+					v_end,
+					partner: None,
+					linedef: Some(line),
+					direction: if dir == 0 {
+						SegDirection::Front
+					} else {
+						SegDirection::Back
+					},
+				})
+				.collect(),
+			nodes: [
+				(144, BspNodeChild::SubSector(2), BspNodeChild::SubSector(1)),
+				(128, BspNodeChild::SubNode(0), BspNodeChild::SubSector(0)),
+			]
+			.into_iter()
+			.map(|(x, right, left)| read::ExtNodeRaw {
+				seg_start: [x << 16, 0],
+				seg_delta: [0, 128 << 16],
+				aabb_r: [0; 4],
+				aabb_l: [0; 4],
+				child_r: right,
+				child_l: left,
+			})
+			.collect(),
+		}
+	}
+
+	#[test]
+	fn znodes_preferred() {
+		let lumps = Lumps::new(0);
+		let ext = extended_nodes();
+		// The vanilla tree is left out, so all lookups must go through `ext`.
+		let query = SpatialQuery::new(&lumps.raw(false), Some(&ext), None).unwrap();
+		assert_eq!(query.nodes.len(), 2);
+		assert_eq!(query.segs[3].v2, Vec2::new(64.0, 0.0) * FSCALE);
+
+		for (x, expected) in [(127.9, 0), (128.1, 1), (143.9, 1), (144.1, 2), (200.0, 2)] {
+			let ss = query.subsector_at(Vec2::new(x, 64.0));
+			assert_eq!(usize::from(ss), expected, "x = {x}");
+			assert_eq!(usize::from(query.sector_of(ss)), expected);
+		}
+
+		assert!(!query.line_of_sight(EYE_A, EYE_B));
+		assert!(query.line_of_sight(EYE_A, Vec3::new(100.0, 20.0, 41.0)));
+
+		let mut ext = extended_nodes();
+		ext.segs[4].v_start = 9;
+
+		assert!(matches!(
+			SpatialQuery::new(&lumps.raw(false), Some(&ext), None),
+			Err(Error::InvalidSegVertex {
+				seg: 4,
+				vertex: 9,
+				verts_len: 9,
+			})
+		));
+
+		let mut ext = extended_nodes();
+		ext.segs[0].linedef = Some(10);
+
+		assert!(matches!(
+			SpatialQuery::new(&lumps.raw(false), Some(&ext), None),
+			Err(Error::InvalidSegLinedef {
+				seg: 0,
+				linedef: 10,
+				lines_len: 10,
+			})
+		));
+	}
+
+	#[test]
+	fn door_blocks_sight() {
+		let lumps = Lumps::new(0);
+		let mut query = lumps.query(true, None);
+		assert!(!query.line_of_sight(EYE_A, EYE_B));
+		assert!(!query.line_of_sight(EYE_B, EYE_A));
+		assert!(query.line_of_sight(EYE_A, Vec3::new(100.0, 20.0, 41.0)));
+
+		let door = query.sector_at(Vec2::new(136.0, 64.0));
+
+		query.set_sector_heights(
+			door,
+			SectorHeights {
+				floor: 0.0,
+				ceiling: 128.0,
+			},
+		);
+
+		assert!(query.line_of_sight(EYE_A, EYE_B));
+		assert!(query.line_of_sight(EYE_B, EYE_A));
+
+		// Partly open: the door's lintel is below eye level,
+		// but a line low enough to the floor still passes.
+		query.set_sector_heights(
+			door,
+			SectorHeights {
+				floor: 0.0,
+				ceiling: 24.0,
+			},
+		);
+
+		assert!(!query.line_of_sight(EYE_A, EYE_B));
+
+		let low_a = Vec3::new(64.0, 64.0, 10.0);
+		let low_b = Vec3::new(208.0, 64.0, 10.0);
+		assert!(query.line_of_sight(low_a, low_b));
+
+		// Results must not depend on whether the tree is used.
+		let query = Lumps::new(128).query(false, None);
+		assert!(query.line_of_sight(EYE_A, EYE_B));
+		let query = lumps.query(false, None);
+		assert!(!query.line_of_sight(EYE_A, EYE_B));
+	}
+
+	#[test]
+	fn reject_early_out() {
+		// Sector 0 can not see sector 2: bit 0 * 3 + 2.
+		let lumps = Lumps::new(128);
+		let query = lumps.query(true, Some(&[0b0000_0100, 0]));
+		assert!(!query.line_of_sight(EYE_A, EYE_B));
+		assert!(query.line_of_sight(EYE_B, EYE_A));
+	}
+
+	#[test]
+	fn position_checks() {
+		let query = Lumps::new(128).query(true, None);
+
+		let opening = query.check_position(Vec2::new(64.0, 64.0), 20.0, true);
+		assert_eq!(
+			opening,
+			Some(Opening {
+				floor: 0.0,
+				ceiling: 128.0,
+				dropoff: 0.0,
+			})
+		);
+
+		// Overlapping the wall at y = 0.
+		assert!(query
+			.check_position(Vec2::new(64.0, 10.0), 20.0, true)
+			.is_none());
+		// Straddling the two-sided line into the door sector.
+		assert!(query
+			.check_position(Vec2::new(128.0, 64.0), 20.0, true)
+			.is_some());
+
+		let query = Lumps::new(0).query(true, None);
+		let opening = query
+			.check_position(Vec2::new(128.0, 64.0), 20.0, true)
+			.unwrap();
+		assert_eq!(opening.ceiling, 0.0);
+	}
+
+	#[test]
+	fn geometry_traces() {
+		let open = Lumps::new(128).query(true, None);
+		assert_eq!(open.trace_geometry(EYE_A, EYE_B), None);
+
+		// Into the one-sided wall at x = 0.
+		let frac = open
+			.trace_geometry(EYE_A, Vec3::new(-100.0, 64.0, 41.0))
+			.unwrap();
+		assert_eq!(frac, 64.0 / 164.0);
+
+		// The shut door's near side, at x = 128.
+		let shut = Lumps::new(0).query(true, None);
+		assert_eq!(shut.trace_geometry(EYE_A, EYE_B), Some(64.0 / 144.0));
+		assert_eq!(shut.trace_geometry(EYE_B, EYE_A), Some(64.0 / 144.0));
+
+		// Eye level is above the lintel of a partly open door,
+		// but a trace low enough to the floor gets through.
+		let ajar = Lumps::new(24).query(true, None);
+		assert_eq!(ajar.trace_geometry(EYE_A, EYE_B), Some(64.0 / 144.0));
+
+		let low_a = Vec3::new(64.0, 64.0, 10.0);
+		let low_b = Vec3::new(208.0, 64.0, 10.0);
+		assert_eq!(ajar.trace_geometry(low_a, low_b), None);
+	}
+
+	#[test]
+	fn sound_flood() {
+		let query = Lumps::new(128).query(true, None);
+		let mut traversed = [0; 3];
+		query.flood_sound(query.sector_at(EYE_A.truncate()), &mut traversed);
+		assert!(traversed.iter().all(|t| *t != 0));
+
+		let query = Lumps::new(0).query(true, None);
+		let mut traversed = [0; 3];
+		query.flood_sound(query.sector_at(EYE_A.truncate()), &mut traversed);
+		assert_eq!(traversed, [1, 0, 0]);
+	}
+}
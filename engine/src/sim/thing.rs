@@ -0,0 +1,316 @@
+//! Turning a level's [things](Thing) into actors and spawn points when it gets loaded.
+
+use bevy::prelude::*;
+use data::{level::read::ThingFlags, EditorNum};
+use rustc_hash::FxHashMap;
+use tracing::warn;
+
+use crate::{
+	actor::{Actor, Blueprint, Health, Readonly, Solid, StateMachine},
+	world::level::Thing,
+};
+
+use super::{
+	ai::AiState,
+	interp::{PrevTransform, SimTransform},
+	skill::SkillDef,
+	ActiveMarker,
+};
+
+/// Maps a thing's editor number to the blueprint which gets spawned for it.
+/// Inserting a number which is already present replaces its blueprint, so that
+/// a later load order entry can override an earlier one.
+#[derive(Resource, Debug, Default)]
+pub struct EditorNums(FxHashMap<EditorNum, Handle<Blueprint>>);
+
+impl EditorNums {
+	pub fn insert(&mut self, editor_num: EditorNum, blueprint: Handle<Blueprint>) {
+		self.0.insert(editor_num, blueprint);
+	}
+
+	#[must_use]
+	pub fn get(&self, editor_num: EditorNum) -> Option<&Handle<Blueprint>> {
+		self.0.get(&editor_num)
+	}
+}
+
+/// Where a player's pawn gets spawned in a single-player or cooperative game.
+/// Editor numbers 1 through 4.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerStart {
+	/// Zero-based; player 1 is 0.
+	pub player: u8,
+}
+
+/// Where a player's pawn may get spawned in a deathmatch game. Editor number 11.
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct DeathmatchStart;
+
+/// Where a teleporter line sends whatever crosses it. Editor number 14.
+#[derive(Component, Debug)]
+#[component(storage = "SparseSet")]
+pub struct TeleportDest;
+
+/// Spawns an entity for every thing in `things` present in single-player games
+/// at `skill`. Player and deathmatch starts and teleport destinations become
+/// markers; anything else gets an actor from the blueprint `ednums` maps it to.
+/// Things with an editor number that maps to nothing get skipped with a warning.
+pub fn spawn_things(
+	cmds: &mut Commands,
+	blueprints: &Assets<Blueprint>,
+	ednums: &EditorNums,
+	things: &[Thing],
+	skill: &SkillDef,
+) {
+	let mut spawn_order = 0;
+
+	for thing in things {
+		if !thing.flags.contains(ThingFlags::SINGLEPLAY) || !skill.spawns(thing.flags) {
+			continue;
+		}
+
+		let transform = SimTransform {
+			pos: thing.pos,
+			angle: (thing.angle as f32).to_radians(),
+		};
+
+		match thing.ednum {
+			1..=4 => {
+				cmds.spawn((
+					PlayerStart {
+						player: (thing.ednum - 1) as u8,
+					},
+					transform,
+				));
+				continue;
+			}
+			11 => {
+				cmds.spawn((DeathmatchStart, transform));
+				continue;
+			}
+			14 => {
+				cmds.spawn((TeleportDest, transform));
+				continue;
+			}
+			_ => {}
+		}
+
+		let Some((handle, blueprint)) = ednums
+			.get(thing.ednum)
+			.and_then(|handle| blueprints.get(handle).map(|bp| (handle, bp)))
+		else {
+			warn!(
+				"Skipping thing with unknown editor number {} at {}.",
+				thing.ednum, thing.pos
+			);
+			continue;
+		};
+
+		let ambush = thing.flags.contains(ThingFlags::AMBUSH);
+
+		if spawn_actor(
+			cmds,
+			handle.clone(),
+			blueprint,
+			transform,
+			ambush,
+			spawn_order,
+			0,
+		)
+		.is_some()
+		{
+			spawn_order += 1;
+		}
+	}
+}
+
+/// Spawns an active actor from `blueprint`, which `handle` must refer to.
+/// Blueprints with a `See` state label are taken to be monsters; their actors
+/// are [`Solid`] and get an [`AiState`], which `ambush` gets passed to.
+///
+/// Returns `None` without spawning anything if `blueprint` has no states.
+pub fn spawn_actor(
+	cmds: &mut Commands,
+	handle: Handle<Blueprint>,
+	blueprint: &Blueprint,
+	transform: SimTransform,
+	ambush: bool,
+	spawn_order: u32,
+	spawn_tick: u32,
+) -> Option<Actor> {
+	let Some(machine) = StateMachine::new(&blueprint.states) else {
+		warn!(
+			"Blueprint with editor number {} has no states; not spawning it.",
+			blueprint.editor_num
+		);
+		return None;
+	};
+
+	let mut prev = PrevTransform::default();
+	prev.prev = transform;
+
+	let mut ent = cmds.spawn((
+		machine,
+		Health::new(blueprint),
+		transform,
+		prev,
+		transform.to_bevy(),
+		ActiveMarker,
+	));
+
+	let id = Actor::new(ent.id());
+
+	ent.insert(Readonly {
+		blueprint: handle,
+		id,
+		spawn_order,
+		spawn_tick,
+	});
+
+	if blueprint.states.find("See").is_some() {
+		ent.insert((AiState::new(ambush), Solid));
+	}
+
+	Some(id)
+}
+
+#[cfg(test)]
+mod test {
+	use bevy::ecs::system::CommandQueue;
+
+	use crate::{
+		actor::state::{StateDef, StateTable},
+		gfx::style::RenderStyle,
+	};
+
+	use super::*;
+
+	fn blueprint(editor_num: EditorNum, labels: &[&str]) -> Blueprint {
+		let mut states = StateTable::default();
+
+		let state = states.push(StateDef {
+			duration: -1,
+			next: None,
+			action: None,
+		});
+
+		for label in labels {
+			states.add_label(*label, state);
+		}
+
+		Blueprint {
+			editor_num,
+			spawn_num: 0,
+			health_starting: 20,
+			gib_health: -20,
+			pain_chance: 200,
+			states,
+			render_style: RenderStyle::Normal,
+			radius: 20.0,
+			height: 56.0,
+			speed: 8.0,
+			melee_range: 44.0,
+			melee_damage: 0,
+			hitscan_damage: 0,
+		}
+	}
+
+	fn thing(ednum: u16, flags: ThingFlags) -> Thing {
+		Thing {
+			ednum,
+			pos: Vec3::new(64.0, -32.0, 0.0),
+			angle: 90,
+			flags,
+			tid: 0,
+			special: 0,
+			args: [0; 5],
+		}
+	}
+
+	#[test]
+	fn smoke() {
+		let mut world = World::new();
+		let mut blueprints = Assets::<Blueprint>::default();
+		let mut ednums = EditorNums::default();
+
+		ednums.insert(3004, blueprints.add(blueprint(3004, &["Spawn", "See"])));
+		ednums.insert(2014, blueprints.add(blueprint(2014, &["Spawn"])));
+
+		let [_, _, normal, _, _] = SkillDef::classic();
+		let every = ThingFlags::SINGLEPLAY | ThingFlags::SKILL_3;
+
+		let things = [
+			thing(1, every),
+			thing(11, every),
+			thing(14, every),
+			// A zombieman, lying in ambush.
+			thing(3004, every | ThingFlags::AMBUSH),
+			// A health bonus.
+			thing(2014, every),
+			// Nothing is mapped to this.
+			thing(9999, every),
+			// Multiplayer-only.
+			thing(3004, ThingFlags::SKILL_3 | ThingFlags::COOP),
+			// Not on this skill.
+			thing(3004, ThingFlags::SINGLEPLAY | ThingFlags::SKILL_5),
+		];
+
+		let mut queue = CommandQueue::default();
+		let mut cmds = Commands::new(&mut queue, &world);
+		spawn_things(&mut cmds, &blueprints, &ednums, &things, &normal);
+		queue.apply(&mut world);
+
+		let starts = world
+			.query::<(&PlayerStart, &SimTransform)>()
+			.iter(&world)
+			.map(|(start, xform)| (*start, *xform))
+			.collect::<Vec<_>>();
+
+		assert_eq!(starts.len(), 1);
+		assert_eq!(starts[0].0.player, 0);
+		assert!((starts[0].1.angle - std::f32::consts::FRAC_PI_2).abs() < 0.0001);
+
+		assert_eq!(world.query::<&DeathmatchStart>().iter(&world).count(), 1);
+		assert_eq!(world.query::<&TeleportDest>().iter(&world).count(), 1);
+
+		let mut actors = world
+			.query::<(&Readonly, Option<&AiState>, Has<Solid>)>()
+			.iter(&world)
+			.map(|(readonly, ai, solid)| (readonly.spawn_order, ai.cloned(), solid))
+			.collect::<Vec<_>>();
+
+		actors.sort_by_key(|(order, _, _)| *order);
+
+		assert_eq!(actors.len(), 2);
+		assert!(actors[0].1.as_ref().is_some_and(|ai| ai.ambush));
+		assert!(actors[0].2);
+		assert!(actors[1].1.is_none());
+		assert!(!actors[1].2);
+	}
+
+	/// Actors get drawn in Bevy's space, not the sim's.
+	#[test]
+	fn drawn_transform() {
+		let mut world = World::new();
+		let mut blueprints = Assets::<Blueprint>::default();
+		let mut ednums = EditorNums::default();
+
+		ednums.insert(2014, blueprints.add(blueprint(2014, &["Spawn"])));
+
+		let [_, _, normal, _, _] = SkillDef::classic();
+		let things = [thing(2014, ThingFlags::SINGLEPLAY | ThingFlags::SKILL_3)];
+
+		let mut queue = CommandQueue::default();
+		let mut cmds = Commands::new(&mut queue, &world);
+		spawn_things(&mut cmds, &blueprints, &ednums, &things, &normal);
+		queue.apply(&mut world);
+
+		let (sim, drawn) = world.query::<(&SimTransform, &Transform)>().single(&world);
+
+		// 64 units east and 32 units south in Doom's space, facing north.
+		assert_eq!(sim.pos, Vec3::new(64.0, -32.0, 0.0));
+		assert_eq!(drawn.translation, Vec3::new(64.0, 0.0, 32.0));
+		assert!(drawn.forward().abs_diff_eq(Vec3::NEG_Z, 0.0001));
+	}
+}
@@ -0,0 +1,723 @@
+//! Player weapons: their view sprite states, firing, and hitscan attacks.
+//!
+//! A weapon is a [`Blueprint`] whose [state table](crate::actor::StateTable)
+//! drives the first-person view sprite (vanilla's "psprite") rather than an
+//! actor in the world. It is expected to have at least `Ready` and `Fire`
+//! labels, and optionally `Flash`. Each tick, [`fire_weapons`] runs
+//! [`tick_weapon`] for every player with a [`WeaponSlot`]; the logic itself
+//! lives in plain functions so that it can be exercised without a [`World`].
+//!
+//! Ammo is not yet accounted for.
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+
+use crate::{
+	actor::{state::Action, Actor, Blueprint, Corpse, Readonly, Solid, StateMachine},
+	rng::Prng,
+};
+
+use super::{
+	ai::{Body, Noise},
+	damage::{DamageEvent, DamageType},
+	interp::{PrevTransform, SimTransform},
+	spatial::SpatialQuery,
+	ActiveMarker, Sim,
+};
+
+/// The key of the [`Sim`]'s PRNG which weapon damage draws from.
+pub const RNG_KEY: &str = "weapon";
+
+/// Vanilla's `MISSILERANGE`: how far a hitscan attack reaches, in map units.
+pub const HITSCAN_RANGE: f32 = 2048.0;
+
+/// Vanilla's `VIEWHEIGHT`: how far above a player's feet the camera sits,
+/// and thus where hitscan attacks are traced from.
+pub const VIEW_HEIGHT: f32 = 41.0;
+
+/// Vanilla's `MAXBOB`: the furthest the view sprite can be bobbed, in pixels.
+pub const MAX_BOB: f32 = 16.0;
+
+/// A puff gets spawned this many map units short of where a hitscan attack
+/// struck a wall, as in vanilla, so that it is not drawn inside the wall.
+pub const PUFF_BACKOFF: f32 = 4.0;
+
+/// The weapon a player is holding, and where its view sprite is in its states.
+#[derive(Component, Debug)]
+pub struct WeaponSlot {
+	pub weapon: Handle<Blueprint>,
+	pub psprite: StateMachine,
+	/// Playback of the weapon's `Flash` sequence, if it is being shown.
+	pub flash: Option<StateMachine>,
+	/// Set by [`Action::WeaponReady`]; cleared upon entering `Fire`.
+	pub ready: bool,
+	/// Where the view sprite has been bobbed to; see [`weapon_bob`].
+	pub bob: Vec2,
+}
+
+impl WeaponSlot {
+	/// Starts the view sprite at the `Ready` label.
+	/// Returns `None` if `blueprint` (which `weapon` points to) has no such label.
+	#[must_use]
+	pub fn new(weapon: Handle<Blueprint>, blueprint: &Blueprint) -> Option<Self> {
+		let mut psprite = StateMachine::new(&blueprint.states)?;
+
+		if !psprite.enter_label(&blueprint.states, "Ready") {
+			return None;
+		}
+
+		Some(Self {
+			weapon,
+			psprite,
+			flash: None,
+			ready: false,
+			bob: Vec2::ZERO,
+		})
+	}
+}
+
+/// The parts of a player's input which concern their weapon. Written every tick
+/// by whatever drives the player, be it local input, the network, or a demo.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq)]
+pub struct WeaponInput {
+	/// Whether the `attack` action is held.
+	pub attack: bool,
+	/// Of the camera, in radians. Positive is upwards.
+	pub pitch: f32,
+}
+
+/// Sent wherever a hitscan attack strikes level geometry.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct Puff {
+	pub pos: Vec3,
+}
+
+/// See [`hitscan`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hit {
+	Actor { actor: Actor, pos: Vec3 },
+	Geometry { pos: Vec3 },
+}
+
+/// The player whose weapon is being run.
+#[derive(Debug)]
+pub struct Wielder<'w> {
+	pub actor: Actor,
+	pub weapon: &'w Blueprint,
+	pub slot: &'w mut WeaponSlot,
+	/// Where hitscan attacks get traced from.
+	pub eye: Vec3,
+	/// See [`SimTransform::angle`].
+	pub angle: f32,
+	pub input: WeaponInput,
+}
+
+/// Everything produced by one [`tick_weapon`] call.
+#[derive(Debug, Default)]
+pub struct Effects {
+	pub damages: Vec<DamageEvent>,
+	pub puffs: Vec<Puff>,
+	/// Whether any attack was made, in which case monsters should hear it.
+	pub fired: bool,
+}
+
+/// Advances the view sprite (and its flash) by one tick, runs the action of
+/// each state entered, and enters `Fire` if the weapon is ready and `attack`
+/// is held. A flash ends upon reaching a state with no successor.
+pub fn tick_weapon(
+	me: &mut Wielder,
+	spatial: &SpatialQuery,
+	bodies: &[Body],
+	prng: &mut impl Prng,
+	effects: &mut Effects,
+) {
+	let states = &me.weapon.states;
+
+	me.slot.psprite.tick(states);
+
+	if let Some(flash) = &mut me.slot.flash {
+		flash.tick(states);
+
+		if flash.tics == -1 {
+			me.slot.flash = None;
+		}
+	}
+
+	// (RAT) Bounded so a cycle of states can not hang the sim.
+	for _ in 0..=states.len() {
+		if me.slot.psprite.take_entered() {
+			match states.get(me.slot.psprite.state).and_then(|def| def.action) {
+				Some(Action::WeaponReady) => me.slot.ready = true,
+				Some(Action::GunFlash) => me.slot.flash = StateMachine::at_label(states, "Flash"),
+				Some(Action::FireHitscan) => fire_hitscan(me, spatial, bodies, prng, effects),
+				_ => {}
+			}
+
+			continue;
+		}
+
+		if me.slot.ready && me.input.attack && me.slot.psprite.enter_label(states, "Fire") {
+			me.slot.ready = false;
+			continue;
+		}
+
+		return;
+	}
+}
+
+fn fire_hitscan(
+	me: &Wielder,
+	spatial: &SpatialQuery,
+	bodies: &[Body],
+	prng: &mut impl Prng,
+	effects: &mut Effects,
+) {
+	effects.fired = true;
+
+	let dir = aim(me.angle, me.input.pitch);
+
+	match hitscan(spatial, me.actor, me.eye, dir, HITSCAN_RANGE, bodies) {
+		Some(Hit::Actor { actor, .. }) => {
+			effects.damages.push(DamageEvent {
+				target: actor,
+				inflictor: None,
+				source: Some(me.actor),
+				amount: me.weapon.hitscan_damage * (prng.range_i64(1, 3) as i32),
+				damage_type: DamageType::Normal,
+			});
+		}
+		Some(Hit::Geometry { pos }) => effects.puffs.push(Puff { pos }),
+		None => {}
+	}
+}
+
+/// A unit vector pointing along `angle` (see [`SimTransform::angle`])
+/// and `pitch` (see [`WeaponInput::pitch`]).
+#[must_use]
+pub fn aim(angle: f32, pitch: f32) -> Vec3 {
+	Vec3::new(
+		angle.cos() * pitch.cos(),
+		angle.sin() * pitch.cos(),
+		pitch.sin(),
+	)
+}
+
+/// Traces a hitscan attack from `from` along `dir` (a unit vector) for up to
+/// `range` map units. Whichever is struck first between level geometry and
+/// one of `bodies` (other than `shooter`) is what gets hit.
+///
+/// A geometry hit's position is pulled back by [`PUFF_BACKOFF`].
+#[must_use]
+pub fn hitscan(
+	spatial: &SpatialQuery,
+	shooter: Actor,
+	from: Vec3,
+	dir: Vec3,
+	range: f32,
+	bodies: &[Body],
+) -> Option<Hit> {
+	let wall = spatial
+		.trace_geometry(from, from + dir * range)
+		.map(|frac| frac * range);
+
+	let body = bodies
+		.iter()
+		.filter(|body| body.actor != shooter)
+		.filter_map(|body| cylinder_entry(from, dir, range, body).map(|dist| (body.actor, dist)))
+		.fold(
+			None,
+			|nearest: Option<(Actor, f32)>, (actor, dist)| match nearest {
+				Some((_, n)) if n <= dist => nearest,
+				_ => Some((actor, dist)),
+			},
+		);
+
+	match (body, wall) {
+		(Some((actor, dist)), wall) if wall.map_or(true, |w| dist < w) => Some(Hit::Actor {
+			actor,
+			pos: from + dir * dist,
+		}),
+		(_, Some(dist)) => Some(Hit::Geometry {
+			pos: from + dir * (dist - PUFF_BACKOFF).max(0.0),
+		}),
+		_ => None,
+	}
+}
+
+/// How far along `dir` (a unit vector) from `from` the cylinder of `body` gets
+/// entered, if it does so within `range` map units. The cylinder stands on
+/// `body.pos`, with the body's radius and height.
+#[must_use]
+pub fn cylinder_entry(from: Vec3, dir: Vec3, range: f32, body: &Body) -> Option<f32> {
+	let rel = from.truncate() - body.pos.truncate();
+	let dir2 = dir.truncate();
+	let a = dir2.length_squared();
+	let c = rel.length_squared() - body.radius * body.radius;
+
+	// The span of the trace within the infinitely tall cylinder...
+	let (mut near, mut far) = if a <= f32::EPSILON {
+		if c > 0.0 {
+			return None;
+		}
+
+		(f32::NEG_INFINITY, f32::INFINITY)
+	} else {
+		let b = rel.dot(dir2);
+		let disc = b * b - a * c;
+
+		if disc < 0.0 {
+			return None;
+		}
+
+		let root = disc.sqrt();
+		((-b - root) / a, (-b + root) / a)
+	};
+
+	// ...narrowed down to the span between its bottom and top.
+	let (bottom, top) = (body.pos.z, body.pos.z + body.height);
+
+	if dir.z.abs() <= f32::EPSILON {
+		if from.z < bottom || from.z > top {
+			return None;
+		}
+	} else {
+		let (b, t) = ((bottom - from.z) / dir.z, (top - from.z) / dir.z);
+		near = near.max(b.min(t));
+		far = far.min(b.max(t));
+	}
+
+	if near > far || far < 0.0 {
+		return None;
+	}
+
+	let dist = near.max(0.0);
+	(dist <= range).then_some(dist)
+}
+
+/// Vanilla's view sprite bob. `speed` is how far the player moved across the
+/// ground in the last tick, in map units; `tick` is the number of ticks elapsed.
+/// The result is in the virtual screen's pixels, with positive Y downwards.
+/// One full side-to-side sway takes 64 ticks, during which the sprite dips twice.
+#[must_use]
+pub fn weapon_bob(speed: f32, tick: u64) -> Vec2 {
+	let bob = (speed * speed / 4.0).min(MAX_BOB);
+	let angle = (tick % 64) as f32 * (TAU / 64.0);
+	Vec2::new(bob * angle.cos(), bob * (angle % PI).sin())
+}
+
+// Systems /////////////////////////////////////////////////////////////////////
+
+/// Runs [`tick_weapon`] for every living player with a [`WeaponSlot`], in order
+/// of entity ID. Intended to run every tick, after players have moved, so that
+/// the distance moved since the tick started can be used for [`weapon_bob`].
+#[allow(clippy::type_complexity)]
+pub fn fire_weapons(
+	mut sim: ResMut<Sim>,
+	blueprints: Res<Assets<Blueprint>>,
+	mut damages: EventWriter<DamageEvent>,
+	mut puffs: EventWriter<Puff>,
+	mut noises: EventWriter<Noise>,
+	targets: Query<(&Readonly, &SimTransform), (With<Solid>, With<ActiveMarker>)>,
+	mut wielders: Query<
+		(
+			Entity,
+			&Readonly,
+			&SimTransform,
+			Option<&PrevTransform>,
+			&WeaponInput,
+			&mut WeaponSlot,
+		),
+		(With<ActiveMarker>, Without<Corpse>),
+	>,
+) {
+	let sim = &mut *sim;
+	let tick = sim.ticks_elapsed;
+
+	let Some(spatial) = sim.spatial.as_ref() else {
+		return;
+	};
+
+	let prng = sim.rng.get(RNG_KEY);
+
+	let bodies: Vec<Body> = targets
+		.iter()
+		.filter_map(|(readonly, transform)| {
+			let blueprint = blueprints.get(&readonly.blueprint)?;
+
+			Some(Body {
+				actor: readonly.id,
+				pos: transform.pos,
+				radius: blueprint.radius,
+				height: blueprint.height,
+			})
+		})
+		.collect();
+
+	let mut order: Vec<Entity> = wielders.iter().map(|(entity, ..)| entity).collect();
+	order.sort_unstable();
+
+	let mut effects = Effects::default();
+
+	for entity in order {
+		let Ok((_, readonly, transform, prev, input, mut slot)) = wielders.get_mut(entity) else {
+			continue;
+		};
+
+		let Some(weapon) = blueprints.get(&slot.weapon) else {
+			continue;
+		};
+
+		if slot.ready {
+			let speed = prev.map_or(0.0, |p| (transform.pos - p.prev.pos).truncate().length());
+			slot.bob = weapon_bob(speed, tick);
+		}
+
+		let mut me = Wielder {
+			actor: readonly.id,
+			weapon,
+			slot: &mut slot,
+			eye: transform.pos + Vec3::Z * VIEW_HEIGHT,
+			angle: transform.angle,
+			input: *input,
+		};
+
+		effects.fired = false;
+		tick_weapon(&mut me, spatial, &bodies, prng, &mut effects);
+
+		if effects.fired {
+			noises.send(Noise {
+				emitter: readonly.id,
+				pos: transform.pos,
+			});
+		}
+	}
+
+	damages.send_batch(effects.damages);
+	puffs.send_batch(effects.puffs);
+}
+
+#[cfg(test)]
+mod test {
+	use nanorand::WyRand;
+
+	use crate::{
+		actor::{
+			state::{StateDef, StateTable},
+			Health,
+		},
+		gfx::style::RenderStyle,
+		sim::{
+			damage::{apply_damage, Obituary},
+			skill::SkillDef,
+			spatial::test::{Lumps, EYE_A},
+		},
+	};
+
+	use super::*;
+
+	/// `Ready` loops on itself; `Fire` starts the `Flash`, then fires
+	/// four ticks later and goes back to `Ready` after six more.
+	#[must_use]
+	fn pistol() -> Blueprint {
+		let mut states = StateTable::default();
+
+		let ready = states.push(StateDef {
+			duration: 1,
+			next: Some(0),
+			action: Some(Action::WeaponReady),
+		});
+		let fire = states.push(StateDef {
+			duration: 4,
+			next: Some(2),
+			action: Some(Action::GunFlash),
+		});
+		let _ = states.push(StateDef {
+			duration: 6,
+			next: Some(0),
+			action: Some(Action::FireHitscan),
+		});
+		let flash = states.push(StateDef {
+			duration: 7,
+			next: None,
+			action: None,
+		});
+
+		states.add_label("Ready", ready);
+		states.add_label("Fire", fire);
+		states.add_label("Flash", flash);
+
+		Blueprint {
+			hitscan_damage: 5,
+			states,
+			..body_blueprint()
+		}
+	}
+
+	/// Has one state, which it stays in forever.
+	#[must_use]
+	fn body_blueprint() -> Blueprint {
+		let mut states = StateTable::default();
+
+		let spawn = states.push(StateDef {
+			duration: -1,
+			next: None,
+			action: None,
+		});
+
+		states.add_label("Spawn", spawn);
+
+		Blueprint {
+			editor_num: 0,
+			spawn_num: 0,
+			health_starting: 100,
+			gib_health: -100,
+			pain_chance: 0,
+			states,
+			render_style: RenderStyle::Normal,
+			radius: 20.0,
+			height: 56.0,
+			speed: 0.0,
+			melee_range: 44.0,
+			melee_damage: 0,
+			hitscan_damage: 0,
+		}
+	}
+
+	#[must_use]
+	fn actor(index: u32) -> Actor {
+		Actor::new(Entity::from_raw(index))
+	}
+
+	#[must_use]
+	fn body(index: u32, pos: Vec3) -> Body {
+		Body {
+			actor: actor(index),
+			pos,
+			radius: 20.0,
+			height: 56.0,
+		}
+	}
+
+	#[test]
+	fn cylinder_picking() {
+		let target = body(1, Vec3::new(100.0, 0.0, 0.0));
+		let eye = Vec3::new(0.0, 0.0, 41.0);
+
+		assert_eq!(cylinder_entry(eye, Vec3::X, 2048.0, &target), Some(80.0));
+		assert_eq!(cylinder_entry(eye, Vec3::X, 50.0, &target), None);
+		assert_eq!(cylinder_entry(eye, Vec3::NEG_X, 2048.0, &target), None);
+
+		// Passing beside it, and over it.
+		let beside = Vec3::new(0.0, 30.0, 41.0);
+		assert_eq!(cylinder_entry(beside, Vec3::X, 2048.0, &target), None);
+		let over = Vec3::new(0.0, 0.0, 100.0);
+		assert_eq!(cylinder_entry(over, Vec3::X, 2048.0, &target), None);
+
+		// Straight down through its top.
+		let above = Vec3::new(100.0, 0.0, 100.0);
+		assert_eq!(
+			cylinder_entry(above, Vec3::NEG_Z, 2048.0, &target),
+			Some(44.0)
+		);
+	}
+
+	#[test]
+	fn hitscan_nearest_wins() {
+		let open = Lumps::new(128).query(true, None);
+		let shooter = body(0, EYE_A - Vec3::Z * VIEW_HEIGHT);
+		let near = body(1, Vec3::new(100.0, 64.0, 0.0));
+		let far = body(2, Vec3::new(200.0, 64.0, 0.0));
+
+		let hit = hitscan(
+			&open,
+			actor(0),
+			EYE_A,
+			Vec3::X,
+			2048.0,
+			&[far, shooter, near],
+		);
+		assert_eq!(
+			hit,
+			Some(Hit::Actor {
+				actor: actor(1),
+				pos: Vec3::new(80.0, 64.0, 41.0),
+			})
+		);
+
+		let hit = hitscan(&open, actor(0), EYE_A, Vec3::X, 2048.0, &[far]);
+		assert!(matches!(hit, Some(Hit::Actor { actor, .. }) if actor == far.actor));
+
+		// The shut door stands between the shooter and the target. The puff gets
+		// spawned at the door's near side, pulled back towards the shooter.
+		let shut = Lumps::new(0).query(true, None);
+		let hit = hitscan(&shut, actor(0), EYE_A, Vec3::X, 2048.0, &[far]);
+		assert_eq!(
+			hit,
+			Some(Hit::Geometry {
+				pos: Vec3::new(128.0 - PUFF_BACKOFF, 64.0, 41.0),
+			})
+		);
+
+		// Nothing is in range.
+		assert_eq!(hitscan(&open, actor(0), EYE_A, Vec3::X, 32.0, &[far]), None);
+	}
+
+	#[test]
+	fn bob() {
+		#[track_caller]
+		fn assert_near(a: Vec2, b: Vec2) {
+			assert!(a.abs_diff_eq(b, 0.0001), "{a} != {b}");
+		}
+
+		assert_eq!(weapon_bob(0.0, 13), Vec2::ZERO);
+
+		// Walking speed gives a bob below the maximum.
+		assert_near(weapon_bob(4.0, 0), Vec2::new(4.0, 0.0));
+
+		// Running gets capped to the maximum.
+		assert_near(weapon_bob(30.0, 0), Vec2::new(MAX_BOB, 0.0));
+		assert_near(weapon_bob(30.0, 16), Vec2::new(0.0, MAX_BOB));
+		assert_near(weapon_bob(30.0, 32), Vec2::new(-MAX_BOB, 0.0));
+		assert_near(weapon_bob(30.0, 48), Vec2::new(0.0, MAX_BOB));
+		assert_eq!(weapon_bob(30.0, 64 + 5), weapon_bob(30.0, 5));
+	}
+
+	#[test]
+	fn fire_sequence() {
+		let spatial = Lumps::new(128).query(true, None);
+		let bp = pistol();
+		let mut slot = WeaponSlot::new(Handle::default(), &bp).unwrap();
+		let bodies = [body(1, Vec3::new(200.0, 64.0, 0.0))];
+		let mut prng = WyRand::default();
+		prng.seed(0);
+		let mut effects = Effects::default();
+
+		let mut tick = |slot: &mut WeaponSlot, attack: bool, effects: &mut Effects| {
+			let mut me = Wielder {
+				actor: actor(0),
+				weapon: &bp,
+				slot,
+				eye: EYE_A,
+				angle: 0.0,
+				input: WeaponInput { attack, pitch: 0.0 },
+			};
+
+			tick_weapon(&mut me, &spatial, &bodies, &mut prng, effects);
+		};
+
+		tick(&mut slot, false, &mut effects);
+		assert!(slot.ready);
+		assert!(slot.flash.is_none());
+
+		tick(&mut slot, true, &mut effects);
+		assert!(!slot.ready);
+		assert_eq!(slot.psprite.state, 1);
+		assert!(slot.flash.is_some());
+		assert!(!effects.fired);
+
+		for _ in 0..4 {
+			tick(&mut slot, false, &mut effects);
+		}
+
+		assert!(effects.fired);
+		assert_eq!(effects.damages.len(), 1);
+		assert_eq!(effects.damages[0].target, actor(1));
+		assert!([5, 10, 15].contains(&effects.damages[0].amount));
+
+		for _ in 0..3 {
+			tick(&mut slot, false, &mut effects);
+		}
+
+		assert!(slot.flash.is_none());
+
+		// Holding `attack` does nothing until the weapon is ready again.
+		for _ in 0..2 {
+			tick(&mut slot, true, &mut effects);
+			assert_eq!(slot.psprite.state, 2);
+		}
+
+		tick(&mut slot, true, &mut effects);
+		assert_eq!(slot.psprite.state, 1);
+		assert_eq!(effects.damages.len(), 1);
+	}
+
+	/// Drives a player's `attack` input through the ECS, and checks that the
+	/// monster across the room gets hurt.
+	#[test]
+	fn fire_at_target() {
+		let mut world = World::new();
+
+		let mut sim = Sim::new(SkillDef::default(), 0x5EED);
+		sim.set_spatial(Some(Lumps::new(128).query(true, None)));
+		world.insert_resource(sim);
+		world.init_resource::<Events<DamageEvent>>();
+		world.init_resource::<Events<Puff>>();
+		world.init_resource::<Events<Noise>>();
+		world.init_resource::<Events<Obituary>>();
+
+		let mut blueprints = Assets::<Blueprint>::default();
+		let pistol_bp = pistol();
+		let slot = WeaponSlot::new(Handle::default(), &pistol_bp).unwrap();
+		let pistol = blueprints.add(pistol_bp);
+		let body_bp = blueprints.add(body_blueprint());
+
+		let mut spawn = |pos: Vec3, angle: f32| {
+			let entity = world.spawn_empty().id();
+			let bp = blueprints.get(&body_bp).unwrap();
+
+			world.entity_mut(entity).insert((
+				Readonly {
+					blueprint: body_bp.clone(),
+					id: Actor::new(entity),
+					spawn_order: 0,
+					spawn_tick: 0,
+				},
+				SimTransform { pos, angle },
+				Health::new(bp),
+				StateMachine::new(&bp.states).unwrap(),
+				Solid,
+				ActiveMarker,
+			));
+
+			entity
+		};
+
+		let player = spawn(EYE_A - Vec3::Z * VIEW_HEIGHT, 0.0);
+		let monster = spawn(Vec3::new(200.0, 64.0, 0.0), std::f32::consts::PI);
+
+		world.insert_resource(blueprints);
+
+		world.entity_mut(player).insert((
+			WeaponSlot {
+				weapon: pistol,
+				..slot
+			},
+			WeaponInput {
+				attack: true,
+				pitch: 0.0,
+			},
+		));
+
+		let mut schedule = Schedule::default();
+		schedule.add_systems((fire_weapons, apply_damage).chain());
+
+		for _ in 0..6 {
+			schedule.run(&mut world);
+		}
+
+		let damages: Vec<_> = world
+			.resource::<Events<DamageEvent>>()
+			.iter_current_update_events()
+			.cloned()
+			.collect();
+
+		assert_eq!(damages.len(), 1);
+		assert_eq!(damages[0].target, Actor::new(monster));
+		assert_eq!(damages[0].source, Some(Actor::new(player)));
+
+		let health = world.get::<Health>(monster).unwrap();
+		assert_eq!(health.current, 100 - damages[0].amount);
+		assert_eq!(world.resource::<Events<Noise>>().len(), 1);
+	}
+}
@@ -1,6 +1,9 @@
 //! Abstraction for text-based UI, used by the headless server and client's console.
 
+use std::path::Path;
+
 use util::lazy_regex;
+use vfs::VPath;
 
 /// This combines storage for text-based commands and aliases with a parser
 /// for matching against those commands, allowing both the client's console
@@ -32,58 +35,19 @@ impl<C: Command> Terminal<C> {
 
 	pub fn submit(&self, string: &str) -> Vec<C::Output> {
 		let mut ret = Vec::<_>::default();
-		let mut string = string.to_owned();
-
-		// "Recursive" alias expansion, no more than 8 levels deep.
-
-		for _ in 0..8 {
-			let mut s = String::default();
-
-			for token in string.split_whitespace() {
-				if let Some(alias) = self.find_alias(token) {
-					s.push_str(&alias.expanded);
-				} else {
-					s.push(' ');
-					s.push_str(token);
-				}
-			}
-
-			string = s;
-		}
-
-		let inputs = string.split(';');
+		let string = self.expand_aliases(string);
 
-		for input in inputs {
-			let input = input.trim();
-			let mut tokens = input.splitn(2, ' ');
-
-			let key = if let Some(k) = tokens.next() {
-				k
-			} else {
+		for input in Self::split_inputs(&string) {
+			let Some(args) = Self::tokenize(input) else {
 				continue;
 			};
 
-			let args = if let Some(a) = tokens.next() { a } else { "" };
-			let args_iter = lazy_regex!(r#"'([^']+)'|"([^"]+)"|([^'" ]+) *"#).captures_iter(args);
-			let mut args = vec![key];
-
-			for arg in args_iter {
-				let arg_match = match arg.get(1).or_else(|| arg.get(2)).or_else(|| arg.get(3)) {
-					Some(a) => a,
-					None => {
-						continue;
-					}
-				};
-
-				args.push(arg_match.as_str());
-			}
-
 			match self.find_command(args[0]) {
 				Some(cmd) => {
 					ret.push(cmd.call(CommandArgs::new(args)));
 				}
 				None => {
-					(self.command_not_found)(key);
+					(self.command_not_found)(args[0]);
 				}
 			};
 		}
@@ -91,6 +55,26 @@ impl<C: Command> Terminal<C> {
 		ret
 	}
 
+	/// Runs each line of `source` as though it were [submitted](Self::submit),
+	/// skipping those which are blank or start with `//`. A line which fails does
+	/// not stop the lines after it from running; each failure gets handed to
+	/// `output` annotated with `origin` and a line number.
+	///
+	/// Two commands get special treatment here, and are recognized even if they
+	/// come out of an alias expansion:
+	/// - `exec` lines are resolved via [`CommandOutput::read`] and run in place,
+	/// up to [`EXEC_DEPTH_MAX`] files deep.
+	/// - `alias` lines with both a name and a string register that alias
+	/// immediately, so that the lines after it can already use it.
+	pub fn exec_lines(
+		&mut self,
+		source: &str,
+		origin: &str,
+		output: &mut dyn CommandOutput<C::Output>,
+	) {
+		self.exec_lines_at(source, origin, output, 0);
+	}
+
 	pub fn register_command(&mut self, id: &'static str, command: C, enabled: bool) {
 		debug_assert!(!id.is_empty());
 		debug_assert!(Self::id_valid(id));
@@ -174,8 +158,156 @@ impl<C: Command> Terminal<C> {
 		self.aliases.iter().find(|a| key == a.alias)
 	}
 
+	/// Returns the ID of every enabled command and the name of every alias
+	/// which starts with `partial`, sorted and de-duplicated.
+	#[must_use]
+	pub fn complete(&self, partial: &str) -> Vec<&str> {
+		let cmds = self
+			.commands
+			.iter()
+			.filter(|wrapper| wrapper.enabled && wrapper.id.starts_with(partial))
+			.map(|wrapper| wrapper.id);
+
+		let aliases = self
+			.aliases
+			.iter()
+			.filter(|a| a.alias.starts_with(partial))
+			.map(|a| a.alias.as_str());
+
+		let mut ret: Vec<&str> = cmds.chain(aliases).collect();
+		ret.sort_unstable();
+		ret.dedup();
+		ret
+	}
+
 	// Internal implementation details /////////////////////////////////////////////
 
+	fn exec_lines_at(
+		&mut self,
+		source: &str,
+		origin: &str,
+		output: &mut dyn CommandOutput<C::Output>,
+		depth: usize,
+	) {
+		for (i, line) in source.lines().enumerate() {
+			let line = line.trim();
+
+			if line.is_empty() || line.starts_with("//") {
+				continue;
+			}
+
+			let error = |kind| ExecError {
+				origin: origin.to_string(),
+				line: i + 1,
+				kind,
+			};
+
+			let expanded = self.expand_aliases(line);
+
+			for input in Self::split_inputs(&expanded) {
+				let Some(args) = Self::tokenize(input) else {
+					continue;
+				};
+
+				match args[0] {
+					EXEC_COMMAND if args.len() >= 2 => {
+						if (depth + 1) >= EXEC_DEPTH_MAX {
+							output.error(error(ExecErrorKind::DepthExceeded));
+							continue;
+						}
+
+						match output.read(args[1]) {
+							Ok(nested) => self.exec_lines_at(&nested, args[1], output, depth + 1),
+							Err(reason) => output.error(error(ExecErrorKind::Read {
+								path: args[1].to_string(),
+								reason,
+							})),
+						}
+					}
+					ALIAS_COMMAND if args.len() >= 3 => {
+						let args = CommandArgs::new(args);
+						self.register_alias(args[1].to_string(), args.concat(2));
+					}
+					key => match self.find_command(key) {
+						Some(cmd) => output.output(cmd.call(CommandArgs::new(args))),
+						None => output.error(error(ExecErrorKind::UnknownCommand(key.to_string()))),
+					},
+				}
+			}
+		}
+	}
+
+	/// "Recursive" alias expansion, no more than 8 levels deep.
+	#[must_use]
+	fn expand_aliases(&self, string: &str) -> String {
+		let mut string = string.to_owned();
+
+		for _ in 0..8 {
+			let mut s = String::default();
+
+			for token in string.split_whitespace() {
+				s.push(' ');
+
+				if let Some(alias) = self.find_alias(token) {
+					s.push_str(&alias.expanded);
+				} else {
+					s.push_str(token);
+				}
+			}
+
+			string = s;
+		}
+
+		string
+	}
+
+	/// Splits `string` at every `;` which is not between quotes.
+	#[must_use]
+	fn split_inputs(string: &str) -> Vec<&str> {
+		let mut ret = vec![];
+		let mut quote = None;
+		let mut start = 0;
+
+		for (i, c) in string.char_indices() {
+			match (c, quote) {
+				('\'' | '"', None) => quote = Some(c),
+				(c, Some(q)) if c == q => quote = None,
+				(';', None) => {
+					ret.push(&string[start..i]);
+					start = i + 1;
+				}
+				_ => {}
+			}
+		}
+
+		ret.push(&string[start..]);
+		ret
+	}
+
+	/// Splits one `;`-delimited input into a command key followed by its arguments.
+	/// Returns `None` if `input` is blank.
+	#[must_use]
+	fn tokenize(input: &str) -> Option<Vec<&str>> {
+		let mut tokens = input.trim().splitn(2, ' ');
+		let key = tokens.next().filter(|k| !k.is_empty())?;
+		let args = tokens.next().unwrap_or("");
+		let args_iter = lazy_regex!(r#"'([^']+)'|"([^"]+)"|([^'" ]+) *"#).captures_iter(args);
+		let mut ret = vec![key];
+
+		for arg in args_iter {
+			let arg_match = match arg.get(1).or_else(|| arg.get(2)).or_else(|| arg.get(3)) {
+				Some(a) => a,
+				None => {
+					continue;
+				}
+			};
+
+			ret.push(arg_match.as_str());
+		}
+
+		Some(ret)
+	}
+
 	/// Valid command IDs must contain at least two characters,
 	/// and must begin with one ASCII letter or number.
 	#[must_use]
@@ -184,6 +316,14 @@ impl<C: Command> Terminal<C> {
 	}
 }
 
+/// The ID of the command which [`Terminal::exec_lines`] runs files with.
+pub const EXEC_COMMAND: &str = "exec";
+/// The ID of the command which [`Terminal::exec_lines`] registers aliases with.
+pub const ALIAS_COMMAND: &str = "alias";
+/// How many files deep a chain of `exec` lines can go before being cut off,
+/// including the file at the top of the chain.
+pub const EXEC_DEPTH_MAX: usize = 8;
+
 pub trait Command {
 	type Output;
 
@@ -397,6 +537,74 @@ impl<'a> CommandArgs<'a> {
 	}
 }
 
+/// Receives everything produced by [`Terminal::exec_lines`].
+pub trait CommandOutput<O> {
+	fn output(&mut self, output: O);
+
+	fn error(&mut self, error: ExecError);
+
+	/// Resolves the path operand of an `exec` line to the contents of a file.
+	/// The returned error gets reported to [`Self::error`].
+	fn read(&mut self, path: &str) -> Result<String, String>;
+}
+
+/// A line of a [`Terminal::exec_lines`] source which could not be run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecError {
+	/// Usually a path to the file containing the line.
+	pub origin: String,
+	/// One-based.
+	pub line: usize,
+	pub kind: ExecErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecErrorKind {
+	UnknownCommand(String),
+	/// An `exec` line's file could not be [read](CommandOutput::read).
+	Read {
+		path: String,
+		reason: String,
+	},
+	/// An `exec` line would have gone past [`EXEC_DEPTH_MAX`].
+	DepthExceeded,
+}
+
+impl std::error::Error for ExecError {}
+
+impl std::fmt::Display for ExecError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}:{}: ", self.origin, self.line)?;
+
+		match &self.kind {
+			ExecErrorKind::UnknownCommand(key) => write!(f, "unknown command: `{key}`"),
+			ExecErrorKind::Read { path, reason } => {
+				write!(f, "failed to read `{path}`: {reason}")
+			}
+			ExecErrorKind::DepthExceeded => {
+				write!(f, "`exec` nested more than {EXEC_DEPTH_MAX} files deep")
+			}
+		}
+	}
+}
+
+/// Resolves the operand of an `exec` command to the contents of a file.
+/// `path` gets looked up in `vfs` first. If it is not found there and is an
+/// absolute path, it gets read from the real file system instead.
+pub fn read_exec_file(vfs: &vfs::VirtualFs, path: &str) -> Result<String, String> {
+	if let Some(fref) = vfs.lookup(VPath::new(path)).and_then(|r| r.into_file()) {
+		let mut guard = fref.lock();
+		let bytes = guard.read().map_err(|err| err.to_string())?;
+		return String::from_utf8(bytes.into_owned()).map_err(|err| err.to_string());
+	}
+
+	if Path::new(path).is_absolute() {
+		return std::fs::read_to_string(path).map_err(|err| err.to_string());
+	}
+
+	Err("file not found".to_string())
+}
+
 #[derive(Debug)]
 pub struct Alias {
 	pub alias: String,
@@ -409,3 +617,157 @@ struct CommandWrapper<C: Command> {
 	enabled: bool,
 	command: C,
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[derive(Debug)]
+	struct Echo;
+
+	impl Command for Echo {
+		type Output = String;
+
+		fn call(&self, args: CommandArgs) -> Self::Output {
+			args.concat(0)
+		}
+	}
+
+	#[derive(Debug, Default)]
+	struct Sink {
+		files: Vec<(&'static str, &'static str)>,
+		outputs: Vec<String>,
+		errors: Vec<ExecError>,
+	}
+
+	impl CommandOutput<String> for Sink {
+		fn output(&mut self, output: String) {
+			self.outputs.push(output);
+		}
+
+		fn error(&mut self, error: ExecError) {
+			self.errors.push(error);
+		}
+
+		fn read(&mut self, path: &str) -> Result<String, String> {
+			self.files
+				.iter()
+				.find(|(p, _)| *p == path)
+				.map(|(_, source)| source.to_string())
+				.ok_or_else(|| "file not found".to_string())
+		}
+	}
+
+	#[must_use]
+	fn terminal() -> Terminal<Echo> {
+		let mut terminal = Terminal::new(|_| {});
+		terminal.register_command("echo", Echo, true);
+		terminal
+	}
+
+	#[test]
+	fn exec_aliases() {
+		let mut terminal = terminal();
+		terminal.register_alias("greet".to_string(), "echo hello".to_string());
+
+		let mut sink = Sink {
+			files: vec![("nested.cfg", "echo nested")],
+			..Default::default()
+		};
+
+		terminal.exec_lines(
+			"// A comment.\n\
+			greet world\n\
+			\n\
+			alias both \"echo first; greet\"\n\
+			both\n\
+			alias run \"exec nested.cfg\"\n\
+			run",
+			"autoexec.cfg",
+			&mut sink,
+		);
+
+		assert!(sink.errors.is_empty(), "{:#?}", sink.errors);
+		assert_eq!(
+			sink.outputs,
+			[
+				"echo hello world",
+				"echo first",
+				"echo hello",
+				"echo nested"
+			]
+		);
+		assert_eq!(
+			terminal.find_alias("both").unwrap().expanded,
+			"echo first; greet"
+		);
+	}
+
+	#[test]
+	fn exec_line_numbers() {
+		let mut terminal = terminal();
+
+		let mut sink = Sink {
+			files: vec![("inner.cfg", "echo a\n\n   bogus\n")],
+			..Default::default()
+		};
+
+		terminal.exec_lines(
+			"echo 1\nnope 2\n// nope 3\nexec inner.cfg\nexec missing.cfg\necho 6",
+			"outer.cfg",
+			&mut sink,
+		);
+
+		assert_eq!(sink.outputs, ["echo 1", "echo a", "echo 6"]);
+		assert_eq!(
+			sink.errors,
+			[
+				ExecError {
+					origin: "outer.cfg".to_string(),
+					line: 2,
+					kind: ExecErrorKind::UnknownCommand("nope".to_string()),
+				},
+				ExecError {
+					origin: "inner.cfg".to_string(),
+					line: 3,
+					kind: ExecErrorKind::UnknownCommand("bogus".to_string()),
+				},
+				ExecError {
+					origin: "outer.cfg".to_string(),
+					line: 5,
+					kind: ExecErrorKind::Read {
+						path: "missing.cfg".to_string(),
+						reason: "file not found".to_string(),
+					},
+				},
+			]
+		);
+
+		assert_eq!(
+			sink.errors[0].to_string(),
+			"outer.cfg:2: unknown command: `nope`"
+		);
+	}
+
+	#[test]
+	fn exec_depth_cap() {
+		let mut terminal = terminal();
+
+		let mut sink = Sink {
+			files: vec![("self.cfg", "echo again\nexec self.cfg")],
+			..Default::default()
+		};
+
+		terminal.exec_lines("exec self.cfg", "autoexec.cfg", &mut sink);
+
+		assert_eq!(sink.outputs.len(), EXEC_DEPTH_MAX - 1);
+		assert_eq!(
+			sink.errors,
+			[ExecError {
+				origin: "self.cfg".to_string(),
+				line: 2,
+				kind: ExecErrorKind::DepthExceeded,
+			}]
+		);
+	}
+}
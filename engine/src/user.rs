@@ -21,7 +21,10 @@ use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
-use crate::frontend::LoadOrderPreset;
+use crate::{
+	devgui::DevGuiConfig,
+	frontend::{IwadConfig, LoadOrderPreset},
+};
 
 pub use self::{dirs::*, error::*, pref::*, profile::*};
 
@@ -102,11 +105,29 @@ impl UserCore {
 		&mut self.global_cfg
 	}
 
+	/// Benchmark reports from [`crate::bench`] get written here.
+	#[must_use]
+	pub fn bench_dir(&self) -> PathBuf {
+		self.user_dir.join("bench")
+	}
+
+	/// Screenshots taken by the client get written here.
+	#[must_use]
+	pub fn screenshot_dir(&self) -> PathBuf {
+		self.user_dir.join("screenshots")
+	}
+
 	#[must_use]
 	pub fn globalcfg_path(&self) -> PathBuf {
 		self.user_dir.join(GLOBALCFG_FILENAME)
 	}
 
+	/// Console commands which get run at startup; see [`crate::terminal::Terminal::exec_lines`].
+	#[must_use]
+	pub fn autoexec_path(&self) -> PathBuf {
+		self.user_dir.join(AUTOEXEC_FILENAME)
+	}
+
 	// Internal ////////////////////////////////////////////////////////////////
 
 	/// Returns `Ok(None)` if the global config file does not exist.
@@ -143,6 +164,7 @@ impl UserCore {
 			load_order_presets: VecDeque::from([LoadOrderPreset::new()]),
 			cur_load_order_preset: 0,
 			dev_mode: false,
+			iwads: IwadConfig::default(),
 		};
 
 		let text = toml::ser::to_string_pretty(&ret).expect("failed to serialize global config");
@@ -529,10 +551,17 @@ pub struct GlobalConfig {
 	pub cur_load_order_preset: usize,
 	#[serde(default)]
 	pub dev_mode: bool,
+	/// IWAD search directories and the results of scanning them.
+	#[serde(default)]
+	pub iwads: IwadConfig,
+	/// Docking and per-panel state, so that it survives restarts.
+	#[serde(default)]
+	pub devgui: DevGuiConfig,
 }
 
 /// Lives directly under the user info directory.
 const GLOBALCFG_FILENAME: &str = "global.toml";
+pub const AUTOEXEC_FILENAME: &str = "autoexec.cfg";
 
 /// An error-mapping helper for brevity.
 fn mkdir(path: impl AsRef<Path>) -> Result<(), Error> {
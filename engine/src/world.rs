@@ -4,6 +4,7 @@
 //! this module sub-tree is only for symbols that are useful to both the sim
 //! and the level editor.
 
+pub mod level;
 pub mod line;
 pub mod mesh;
 pub mod sector;
@@ -0,0 +1,1288 @@
+//! A representation of level data which is the same regardless of
+//! [which format](LevelFormat) the level was read from.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use data::level::{
+	read::{LineFlags, SectorRaw, SideDefRaw, ThingFlags},
+	special::{self, Activation, LineSpecial, LineSpecialDef},
+	udmf::{self, KeyVal, Value},
+	RawLevel, RawLineDefs, RawThings,
+};
+use serde::{Deserialize, Serialize};
+use util::Id8;
+
+use super::{LevelFormat, UdmfNamespace, Vertex, FSCALE};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Level {
+	pub format: LevelFormat,
+	pub vertices: Vec<Vertex>,
+	pub linedefs: Vec<LineDef>,
+	pub sidedefs: Vec<SideDef>,
+	pub sectors: Vec<Sector>,
+	pub things: Vec<Thing>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDef {
+	/// An index into [`Level::vertices`].
+	pub vert_start: usize,
+	/// An index into [`Level::vertices`].
+	pub vert_end: usize,
+	/// For a Hexen-format level, only the flags which Hexen shares with Doom.
+	pub flags: LineFlags,
+	/// The whole flags field of a Hexen-format linedef, including the bits which
+	/// decide how its special gets activated. Zero for any other format.
+	pub flags_hexen: u16,
+	pub special: u16,
+	/// For a Doom-format level, the first argument is the line's sector tag
+	/// and the rest are always zero.
+	pub args: [i32; 5],
+	/// a.k.a. "tags". Empty if the line has none; for a Doom-format level,
+	/// this holds the line's tag if it is non-zero.
+	pub ids: Vec<i32>,
+	/// An index into [`Level::sidedefs`]. a.k.a. the linedef's "front".
+	pub side_right: usize,
+	/// An index into [`Level::sidedefs`]. a.k.a. the linedef's "back".
+	pub side_left: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SideDef {
+	pub offset: IVec2,
+	pub tex_top: Option<Id8>,
+	pub tex_mid: Option<Id8>,
+	pub tex_bottom: Option<Id8>,
+	/// An index into [`Level::sectors`].
+	pub sector: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sector {
+	pub height_floor: f32,
+	pub height_ceiling: f32,
+	pub tex_floor: Option<Id8>,
+	pub tex_ceiling: Option<Id8>,
+	pub light_level: u16,
+	pub special: u16,
+	/// a.k.a. "tags". See [`LineDef::ids`].
+	pub ids: Vec<i32>,
+	/// The lump name of a colormap from between `C_START` and `C_END` markers,
+	/// sampled in place of the global `COLORMAP`. See [`assign_colormaps`]
+	/// and [`crate::asset::boom_colormaps`].
+	pub colormap: Option<Id8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thing {
+	pub ednum: u16,
+	/// Z is always zero for a Doom-format level.
+	pub pos: Vec3,
+	/// In degrees. 0 is east, north is 90, et cetera.
+	pub angle: u16,
+	pub flags: ThingFlags,
+	/// The "thing ID" by which scripts can refer to this thing. 0 means "none".
+	pub tid: i32,
+	pub special: u16,
+	pub args: [i32; 5],
+}
+
+const LINE_SET_IDENTIFICATION: u16 = 121;
+
+/// `level` is considered to be in the [Hexen format](LevelFormat::Extended)
+/// if its linedefs are; its things are assumed to be in the same format.
+#[must_use]
+pub fn level_from_binary(level: &RawLevel) -> Level {
+	let format = match level.linedefs {
+		RawLineDefs::Doom(_) => LevelFormat::Doom,
+		RawLineDefs::Ext(_) => LevelFormat::Extended,
+	};
+
+	let linedefs = match level.linedefs {
+		RawLineDefs::Doom(linedefs) => linedefs
+			.iter()
+			.map(|linedef| {
+				let tag = linedef.trigger() as i32;
+
+				LineDef {
+					vert_start: linedef.start_vertex() as usize,
+					vert_end: linedef.end_vertex() as usize,
+					flags: linedef.flags(),
+					flags_hexen: 0,
+					special: linedef.special(),
+					args: [tag, 0, 0, 0, 0],
+					ids: nonzero_tag(tag),
+					side_right: linedef.right_side() as usize,
+					side_left: linedef.left_side().map(|s| s as usize),
+				}
+			})
+			.collect(),
+		RawLineDefs::Ext(linedefs) => linedefs
+			.iter()
+			.map(|linedef| {
+				let mut special = linedef.special() as u16;
+				let mut args = linedef.args().map(|arg| arg as i32);
+				let mut ids = vec![];
+
+				// (RAT) Hexen-format lines have no tag field; ZDoom gives a line an
+				// ID with this special instead, then clears the special.
+				if special == LINE_SET_IDENTIFICATION {
+					ids = nonzero_tag(args[0] | (args[4] << 8));
+					special = 0;
+					args = [0; 5];
+				}
+
+				LineDef {
+					vert_start: linedef.start_vertex() as usize,
+					vert_end: linedef.end_vertex() as usize,
+					flags: linedef.flags(),
+					flags_hexen: linedef.raw_flags(),
+					special,
+					args,
+					ids,
+					side_right: linedef.right_side() as usize,
+					side_left: linedef.left_side().map(|s| s as usize),
+				}
+			})
+			.collect(),
+	};
+
+	let things = match level.things {
+		RawThings::Doom(things) => things
+			.iter()
+			.map(|thing| {
+				let [x, y] = thing.position();
+
+				Thing {
+					ednum: thing.editor_num(),
+					pos: Vec3::new(x as f32, y as f32, 0.0) * FSCALE,
+					angle: thing.angle(),
+					flags: thing.flags(),
+					tid: 0,
+					special: 0,
+					args: [0; 5],
+				}
+			})
+			.collect(),
+		RawThings::Ext(things) => things
+			.iter()
+			.map(|thing| {
+				let [x, y, z] = thing.position();
+
+				Thing {
+					ednum: thing.editor_num(),
+					pos: Vec3::new(x as f32, y as f32, z as f32) * FSCALE,
+					angle: thing.angle(),
+					flags: thing.flags(),
+					tid: thing.tid() as i32,
+					special: thing.special() as u16,
+					args: thing.args().map(|arg| arg as i32),
+				}
+			})
+			.collect(),
+	};
+
+	let mut ret = Level {
+		format,
+		vertices: level.vertices.iter().map(|v| Vertex::from(*v)).collect(),
+		linedefs,
+		sidedefs: level.sidedefs.iter().map(SideDef::from).collect(),
+		sectors: level.sectors.iter().map(Sector::from).collect(),
+		things,
+	};
+
+	if format == LevelFormat::Doom {
+		assign_colormaps(&mut ret);
+	}
+
+	ret
+}
+
+const BOOM_TRANSFER_HEIGHTS: u16 = 242;
+
+/// Boom's line special 242 ("create fake ceiling and floor") names a colormap
+/// with the middle texture of its front side; every sector sharing the line's
+/// tag gets [`Sector::colormap`] set to it. A line without a middle texture
+/// leaves its tagged sectors as they are.
+///
+/// (RAT) Boom also selects colormaps for the regions below the fake floor and
+/// above the fake ceiling with the lower and upper textures; those require
+/// fake flats, which are not yet supported.
+pub fn assign_colormaps(level: &mut Level) {
+	for linedef in &level.linedefs {
+		if linedef.special != BOOM_TRANSFER_HEIGHTS {
+			continue;
+		}
+
+		let Some(&tag) = linedef.ids.first() else {
+			continue;
+		};
+
+		let Some(colormap) = level
+			.sidedefs
+			.get(linedef.side_right)
+			.and_then(|side| side.tex_mid)
+		else {
+			continue;
+		};
+
+		for sector in level.sectors.iter_mut().filter(|s| s.ids.contains(&tag)) {
+			sector.colormap = Some(colormap);
+		}
+	}
+}
+
+impl From<&SideDefRaw> for SideDef {
+	fn from(value: &SideDefRaw) -> Self {
+		let [x, y] = value.offset();
+
+		Self {
+			offset: IVec2::new(x as i32, y as i32),
+			tex_top: value.top_texture(),
+			tex_mid: value.mid_texture(),
+			tex_bottom: value.bottom_texture(),
+			sector: value.sector() as usize,
+		}
+	}
+}
+
+impl From<&SectorRaw> for Sector {
+	fn from(value: &SectorRaw) -> Self {
+		Self {
+			height_floor: (value.floor_height() as f32) * FSCALE,
+			height_ceiling: (value.ceiling_height() as f32) * FSCALE,
+			tex_floor: value.floor_texture(),
+			tex_ceiling: value.ceiling_texture(),
+			light_level: value.light_level(),
+			special: value.special(),
+			ids: nonzero_tag(value.trigger() as i32),
+			colormap: None,
+		}
+	}
+}
+
+/// Translates each of `level`'s line specials according to the numbering scheme
+/// its format uses. The returned specials are in the same order as `level.linedefs`.
+///
+/// Unknown specials are not an error, but all of them get reported together in
+/// one warning which names the level with `name`.
+#[must_use]
+pub fn line_specials(level: &Level, name: &str) -> Vec<LineSpecialDef> {
+	let mut unknown = BTreeMap::<u16, usize>::new();
+
+	let specials = level
+		.linedefs
+		.iter()
+		.map(|linedef| {
+			let def = match level.format {
+				// TODO: Heretic and Strife number their specials differently from Doom.
+				LevelFormat::Doom
+				| LevelFormat::Udmf(
+					UdmfNamespace::Doom
+					| UdmfNamespace::Heretic
+					| UdmfNamespace::Strife
+					| UdmfNamespace::ZDoomTranslated,
+				) => special::from_doom(linedef.special, linedef.ids.first().copied().unwrap_or(0)),
+				LevelFormat::Extended => special::from_hexen(
+					linedef.special,
+					linedef.args,
+					Activation::from_hexen_flags(linedef.flags_hexen),
+				),
+				LevelFormat::Udmf(_) => special::from_hexen(
+					linedef.special,
+					linedef.args,
+					Activation::from_udmf(linedef.flags),
+				),
+			};
+
+			if let LineSpecial::Unknown(num) = def.special {
+				*unknown.entry(num).or_default() += 1;
+			}
+
+			def
+		})
+		.collect();
+
+	if !unknown.is_empty() {
+		let total = unknown.values().sum::<usize>();
+
+		let nums = unknown
+			.iter()
+			.map(|(num, count)| format!("{num} (x{count})"))
+			.collect::<Vec<_>>()
+			.join(", ");
+
+		warn!("level `{name}` has {total} line(s) with unknown specials: {nums}");
+	}
+
+	specials
+}
+
+/// A texture or flat which a [`Level`] names but which no mount provides.
+/// See [`check_textures`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingTexture {
+	pub name: Id8,
+	/// Every part of the level which names this texture, in level order.
+	pub referrers: Vec<TextureReferrer>,
+}
+
+/// Each variant's field is an index into [`Level::sidedefs`] or [`Level::sectors`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureReferrer {
+	SideTop(usize),
+	SideMid(usize),
+	SideBottom(usize),
+	SectorFloor(usize),
+	SectorCeiling(usize),
+}
+
+impl std::fmt::Display for MissingTexture {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "texture `{}` not found; used by ", self.name)?;
+
+		for (i, referrer) in self.referrers.iter().enumerate() {
+			if i > 0 {
+				write!(f, ", ")?;
+			}
+
+			referrer.fmt(f)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl std::fmt::Display for TextureReferrer {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::SideTop(i) => write!(f, "sidedef {i} (upper)"),
+			Self::SideMid(i) => write!(f, "sidedef {i} (middle)"),
+			Self::SideBottom(i) => write!(f, "sidedef {i} (lower)"),
+			Self::SectorFloor(i) => write!(f, "sector {i} (floor)"),
+			Self::SectorCeiling(i) => write!(f, "sector {i} (ceiling)"),
+		}
+	}
+}
+
+/// Meant to be run once `level` has been fully assembled. Every sidedef texture
+/// rejected by `texture_exists` and every sector flat rejected by `flat_exists`
+/// gets replaced with `placeholder`, so that nothing downstream has to handle
+/// a texture failing to resolve.
+///
+/// One warning (naming the level with `name`) is logged per distinct missing
+/// name, and these are also returned in order of first reference.
+///
+/// The middle texture of a Boom [colormap transfer](assign_colormaps) line
+/// names a colormap rather than a texture, and so is never checked.
+pub fn check_textures(
+	level: &mut Level,
+	name: &str,
+	placeholder: Id8,
+	mut texture_exists: impl FnMut(&Id8) -> bool,
+	mut flat_exists: impl FnMut(&Id8) -> bool,
+) -> Vec<MissingTexture> {
+	let mut missing: Vec<MissingTexture> = vec![];
+
+	let mut note = |texname: Id8, referrer| match missing.iter_mut().find(|m| m.name == texname) {
+		Some(m) => m.referrers.push(referrer),
+		None => missing.push(MissingTexture {
+			name: texname,
+			referrers: vec![referrer],
+		}),
+	};
+
+	let colormap_sides: Vec<usize> = if level.format == LevelFormat::Doom {
+		level
+			.linedefs
+			.iter()
+			.filter(|linedef| linedef.special == BOOM_TRANSFER_HEIGHTS)
+			.map(|linedef| linedef.side_right)
+			.collect()
+	} else {
+		vec![]
+	};
+
+	for (i, side) in level.sidedefs.iter_mut().enumerate() {
+		let mid = if colormap_sides.contains(&i) {
+			None
+		} else {
+			Some((&mut side.tex_mid, TextureReferrer::SideMid(i)))
+		};
+
+		let parts = [
+			Some((&mut side.tex_top, TextureReferrer::SideTop(i))),
+			mid,
+			Some((&mut side.tex_bottom, TextureReferrer::SideBottom(i))),
+		];
+
+		for (tex, referrer) in parts.into_iter().flatten() {
+			if let Some(texname) = *tex {
+				if !texture_exists(&texname) {
+					note(texname, referrer);
+					*tex = Some(placeholder);
+				}
+			}
+		}
+	}
+
+	for (i, sector) in level.sectors.iter_mut().enumerate() {
+		let parts = [
+			(&mut sector.tex_floor, TextureReferrer::SectorFloor(i)),
+			(&mut sector.tex_ceiling, TextureReferrer::SectorCeiling(i)),
+		];
+
+		for (tex, referrer) in parts {
+			if let Some(texname) = *tex {
+				if !flat_exists(&texname) {
+					note(texname, referrer);
+					*tex = Some(placeholder);
+				}
+			}
+		}
+	}
+
+	for m in &missing {
+		warn!("level `{name}`: {m}");
+	}
+
+	missing
+}
+
+#[must_use]
+fn nonzero_tag(tag: i32) -> Vec<i32> {
+	if tag != 0 {
+		vec![tag]
+	} else {
+		vec![]
+	}
+}
+
+// UDMF ////////////////////////////////////////////////////////////////////////
+
+/// Builds a [`Level`] from the content of a UDMF `TEXTMAP` lump, or of a
+/// `.udmf` file in a VileTech package.
+///
+/// Only a missing or unknown namespace is fatal. Malformed blocks are skipped,
+/// and the errors they raised are returned alongside the level.
+pub fn parse_textmap(source: &str) -> Result<(Level, Vec<udmf::Error>), udmf::Error> {
+	let sink = udmf::parse::<TextmapSink>(source, ())?;
+	Ok((sink.level, sink.errors))
+}
+
+#[derive(Debug)]
+struct TextmapSink {
+	level: Level,
+	errors: Vec<udmf::Error>,
+}
+
+impl udmf::Sink for TextmapSink {
+	type Context = ();
+
+	type LineDef = LineDef;
+	type SectorDef = Sector;
+	type SideDef = SideDef;
+	type ThingDef = Thing;
+	type Vertex = Vertex;
+
+	fn with_namespace(namespace: &str, _: ()) -> Option<Self> {
+		const NAMESPACES: &[(&str, UdmfNamespace)] = &[
+			("doom", UdmfNamespace::Doom),
+			("eternity", UdmfNamespace::Eternity),
+			("heretic", UdmfNamespace::Heretic),
+			("hexen", UdmfNamespace::Hexen),
+			("strife", UdmfNamespace::Strife),
+			("vavoom", UdmfNamespace::Vavoom),
+			("zdoom", UdmfNamespace::ZDoom),
+			("zdoomtranslated", UdmfNamespace::ZDoomTranslated),
+		];
+
+		let (_, namespace) = NAMESPACES
+			.iter()
+			.find(|(name, _)| namespace.eq_ignore_ascii_case(name))?;
+
+		Some(Self {
+			level: Level {
+				format: LevelFormat::Udmf(*namespace),
+				vertices: vec![],
+				linedefs: vec![],
+				sidedefs: vec![],
+				sectors: vec![],
+				things: vec![],
+			},
+			errors: vec![],
+		})
+	}
+
+	fn start_linedef(&mut self) -> LineDef {
+		LineDef {
+			vert_start: 0,
+			vert_end: 0,
+			flags: LineFlags::empty(),
+			flags_hexen: 0,
+			special: 0,
+			args: [0; 5],
+			ids: vec![],
+			side_right: 0,
+			side_left: None,
+		}
+	}
+
+	fn linedef_property(&mut self, linedef: &mut LineDef, kvp: KeyVal) {
+		read_linedef_field(linedef, kvp);
+	}
+
+	fn finish_linedef(&mut self, linedef: LineDef) {
+		self.level.linedefs.push(linedef);
+	}
+
+	fn start_sectordef(&mut self) -> Sector {
+		Sector {
+			height_floor: 0.0,
+			height_ceiling: 0.0,
+			tex_floor: None,
+			tex_ceiling: None,
+			light_level: 160,
+			special: 0,
+			ids: vec![],
+			colormap: None,
+		}
+	}
+
+	fn sectordef_property(&mut self, sector: &mut Sector, kvp: KeyVal) {
+		read_sectordef_field(sector, kvp);
+	}
+
+	fn finish_sectordef(&mut self, sector: Sector) {
+		self.level.sectors.push(sector);
+	}
+
+	fn start_sidedef(&mut self) -> SideDef {
+		SideDef {
+			offset: IVec2::ZERO,
+			tex_top: None,
+			tex_mid: None,
+			tex_bottom: None,
+			sector: 0,
+		}
+	}
+
+	fn sidedef_property(&mut self, sidedef: &mut SideDef, kvp: KeyVal) {
+		read_sidedef_field(sidedef, kvp);
+	}
+
+	fn finish_sidedef(&mut self, sidedef: SideDef) {
+		self.level.sidedefs.push(sidedef);
+	}
+
+	fn start_thingdef(&mut self) -> Thing {
+		Thing {
+			ednum: 0,
+			pos: Vec3::ZERO,
+			angle: 0,
+			flags: ThingFlags::empty(),
+			tid: 0,
+			special: 0,
+			args: [0; 5],
+		}
+	}
+
+	fn thingdef_property(&mut self, thing: &mut Thing, kvp: KeyVal) {
+		read_thingdef_field(thing, kvp);
+	}
+
+	fn finish_thingdef(&mut self, thing: Thing) {
+		self.level.things.push(thing);
+	}
+
+	fn start_vertex(&mut self) -> Vertex {
+		Vertex(Vec4::ZERO)
+	}
+
+	fn vertex_property(&mut self, vertex: &mut Vertex, kvp: KeyVal) {
+		let KeyVal { key, val } = kvp;
+
+		let Some(float) = float_value(val) else {
+			return;
+		};
+
+		if key.eq_ignore_ascii_case("x") {
+			vertex.x = float * FSCALE;
+		} else if key.eq_ignore_ascii_case("y") {
+			vertex.y = float * FSCALE;
+		}
+	}
+
+	fn finish_vertex(&mut self, vertex: Vertex) {
+		self.level.vertices.push(vertex);
+	}
+
+	fn parse_error(&mut self, error: udmf::Error) {
+		self.errors.push(error);
+	}
+}
+
+/// Applies one field of a UDMF `linedef` block to `linedef`.
+/// Fields without an equivalent in [`LineDef`], and values of the wrong type,
+/// are ignored.
+pub fn read_linedef_field(linedef: &mut LineDef, kvp: KeyVal) {
+	let KeyVal { key, val } = kvp;
+
+	if read_ids(&mut linedef.ids, key, val) {
+		return;
+	}
+
+	const FLAGS: &[(&str, LineFlags)] = &[
+		("blocking", LineFlags::IMPASSIBLE),
+		("blockmonsters", LineFlags::BLOCK_MONS),
+		("twosided", LineFlags::TWO_SIDED),
+		("dontpegtop", LineFlags::UPPER_UNPEGGED),
+		("dontpegbottom", LineFlags::LOWER_UNPEGGED),
+		("secret", LineFlags::SECRET),
+		("blocksound", LineFlags::BLOCK_SOUND),
+		("dontdraw", LineFlags::UNMAPPED),
+		("mapped", LineFlags::PRE_MAPPED),
+		("passuse", LineFlags::PASS_USE),
+	];
+
+	if let Some((_, flag)) = FLAGS.iter().find(|(k, _)| key.eq_ignore_ascii_case(k)) {
+		linedef.flags.set(*flag, val == Value::True);
+		return;
+	}
+
+	let Some(int) = int_value(val) else {
+		return;
+	};
+
+	if key.eq_ignore_ascii_case("v1") {
+		linedef.vert_start = int as usize;
+	} else if key.eq_ignore_ascii_case("v2") {
+		linedef.vert_end = int as usize;
+	} else if key.eq_ignore_ascii_case("sidefront") {
+		linedef.side_right = int as usize;
+	} else if key.eq_ignore_ascii_case("sideback") {
+		linedef.side_left = (int >= 0).then_some(int as usize);
+	} else if key.eq_ignore_ascii_case("special") {
+		linedef.special = int as u16;
+	} else if let Some(i) = arg_index(key) {
+		linedef.args[i] = int;
+	}
+}
+
+/// Applies one field of a UDMF `sector` block to `sector`.
+/// Fields without an equivalent in [`Sector`], and values of the wrong type,
+/// are ignored.
+pub fn read_sectordef_field(sector: &mut Sector, kvp: KeyVal) {
+	let KeyVal { key, val } = kvp;
+
+	if read_ids(&mut sector.ids, key, val) {
+		return;
+	}
+
+	if key.eq_ignore_ascii_case("texturefloor") {
+		if let Value::String(lit) = val {
+			sector.tex_floor = Id8::try_from(unquote(lit)).ok();
+		}
+	} else if key.eq_ignore_ascii_case("textureceiling") {
+		if let Value::String(lit) = val {
+			sector.tex_ceiling = Id8::try_from(unquote(lit)).ok();
+		}
+	} else if let Some(int) = int_value(val) {
+		if key.eq_ignore_ascii_case("heightfloor") {
+			sector.height_floor = (int as f32) * FSCALE;
+		} else if key.eq_ignore_ascii_case("heightceiling") {
+			sector.height_ceiling = (int as f32) * FSCALE;
+		} else if key.eq_ignore_ascii_case("lightlevel") {
+			sector.light_level = int.clamp(0, u16::MAX as i32) as u16;
+		} else if key.eq_ignore_ascii_case("special") {
+			sector.special = int as u16;
+		}
+	}
+}
+
+/// Applies one field of a UDMF `sidedef` block to `sidedef`.
+/// Fields without an equivalent in [`SideDef`], and values of the wrong type,
+/// are ignored. A texture of `-` means "none".
+pub fn read_sidedef_field(sidedef: &mut SideDef, kvp: KeyVal) {
+	let KeyVal { key, val } = kvp;
+
+	if let Value::String(lit) = val {
+		let tex = match unquote(lit) {
+			"-" => None,
+			name => Id8::try_from(name).ok(),
+		};
+
+		if key.eq_ignore_ascii_case("texturetop") {
+			sidedef.tex_top = tex;
+		} else if key.eq_ignore_ascii_case("texturemiddle") {
+			sidedef.tex_mid = tex;
+		} else if key.eq_ignore_ascii_case("texturebottom") {
+			sidedef.tex_bottom = tex;
+		}
+	} else if let Some(int) = int_value(val) {
+		if key.eq_ignore_ascii_case("offsetx") {
+			sidedef.offset.x = int;
+		} else if key.eq_ignore_ascii_case("offsety") {
+			sidedef.offset.y = int;
+		} else if key.eq_ignore_ascii_case("sector") {
+			sidedef.sector = int as usize;
+		}
+	}
+}
+
+/// Applies one field of a UDMF `thing` block to `thing`.
+/// Fields without an equivalent in [`Thing`], and values of the wrong type,
+/// are ignored.
+pub fn read_thingdef_field(thing: &mut Thing, kvp: KeyVal) {
+	const FLAGS: &[(&str, ThingFlags)] = &[
+		("skill1", ThingFlags::SKILL_1),
+		("skill2", ThingFlags::SKILL_2),
+		("skill3", ThingFlags::SKILL_3),
+		("skill4", ThingFlags::SKILL_4),
+		("skill5", ThingFlags::SKILL_5),
+		("ambush", ThingFlags::AMBUSH),
+		("single", ThingFlags::SINGLEPLAY),
+		("dm", ThingFlags::DEATHMATCH),
+		("coop", ThingFlags::COOP),
+		("friend", ThingFlags::FRIEND),
+		("dormant", ThingFlags::DORMANT),
+		("class1", ThingFlags::CLASS_1),
+		("class2", ThingFlags::CLASS_2),
+		("class3", ThingFlags::CLASS_3),
+	];
+
+	let KeyVal { key, val } = kvp;
+
+	if let Some((_, flag)) = FLAGS.iter().find(|(k, _)| key.eq_ignore_ascii_case(k)) {
+		thing.flags.set(*flag, val == Value::True);
+		return;
+	}
+
+	if key.eq_ignore_ascii_case("x") {
+		if let Some(x) = float_value(val) {
+			thing.pos.x = x * FSCALE;
+		}
+	} else if key.eq_ignore_ascii_case("y") {
+		if let Some(y) = float_value(val) {
+			thing.pos.y = y * FSCALE;
+		}
+	} else if key.eq_ignore_ascii_case("height") {
+		if let Some(z) = float_value(val) {
+			thing.pos.z = z * FSCALE;
+		}
+	} else if let Some(int) = int_value(val) {
+		if key.eq_ignore_ascii_case("type") {
+			thing.ednum = int as u16;
+		} else if key.eq_ignore_ascii_case("angle") {
+			thing.angle = int.rem_euclid(360) as u16;
+		} else if key.eq_ignore_ascii_case("id") {
+			thing.tid = int;
+		} else if key.eq_ignore_ascii_case("special") {
+			thing.special = int as u16;
+		} else if let Some(i) = arg_index(key) {
+			thing.args[i] = int;
+		}
+	}
+}
+
+/// Handles `id` and ZDoom's `moreids` for linedefs and sectors alike, returning
+/// `true` if `key` was either. The explicit `id` always comes first in `ids`.
+///
+/// (RAT) UDMF's default ID is -1, so that is treated as "no ID" wherever it
+/// appears, but any other negative ID is as valid as a positive one.
+fn read_ids(ids: &mut Vec<i32>, key: &str, val: Value) -> bool {
+	if key.eq_ignore_ascii_case("id") {
+		if let Some(id) = int_value(val).filter(|id| *id != -1) {
+			ids.retain(|i| *i != id);
+			ids.insert(0, id);
+		}
+	} else if key.eq_ignore_ascii_case("moreids") {
+		if let Value::String(lit) = val {
+			for id in unquote(lit).split_ascii_whitespace() {
+				let Ok(id) = id.parse::<i32>() else {
+					continue;
+				};
+
+				if id != -1 && !ids.contains(&id) {
+					ids.push(id);
+				}
+			}
+		}
+	} else {
+		return false;
+	}
+
+	true
+}
+
+#[must_use]
+fn int_value(val: Value) -> Option<i32> {
+	let Value::Int(lit) = val else {
+		return None;
+	};
+
+	match lit.strip_prefix("0x") {
+		Some(hex) => i32::from_str_radix(hex, 16).ok(),
+		None => lit.parse().ok(),
+	}
+}
+
+/// UDMF allows integer literals wherever a float is expected.
+#[must_use]
+fn float_value(val: Value) -> Option<f32> {
+	match val {
+		Value::Float(lit) => lit.parse().ok(),
+		Value::Int(_) => int_value(val).map(|int| int as f32),
+		_ => None,
+	}
+}
+
+#[must_use]
+fn arg_index(key: &str) -> Option<usize> {
+	let digit = key.strip_prefix("arg")?.parse::<usize>().ok()?;
+	(digit < 5).then_some(digit)
+}
+
+/// UDMF string values are passed along with their quotation marks.
+#[must_use]
+fn unquote(lit: &str) -> &str {
+	lit.strip_prefix('"')
+		.and_then(|l| l.strip_suffix('"'))
+		.unwrap_or(lit)
+}
+
+#[cfg(test)]
+mod test {
+	use data::level::read;
+
+	use super::*;
+
+	/// One square sector with two things in it; the map is otherwise empty.
+	#[test]
+	fn binary_conversion() {
+		let mut verts = vec![];
+
+		for v in [0_i16, 0, 0, 64, 64, 64, 64, 0] {
+			verts.extend_from_slice(&v.to_le_bytes());
+		}
+
+		let mut lines = vec![];
+
+		for (i, (v1, v2)) in [(0_u16, 1_u16), (1, 2), (2, 3), (3, 0)]
+			.into_iter()
+			.enumerate()
+		{
+			// Impassible and upper-unpegged; the last line has tag 7.
+			let tag = if i == 3 { 7 } else { 0 };
+
+			for v in [v1, v2, 0b1001, 0, tag, 0, 0xFFFF] {
+				lines.extend_from_slice(&v.to_le_bytes());
+			}
+		}
+
+		let mut sides = vec![0; 30];
+		sides[4..12].copy_from_slice(b"STARTAN2");
+
+		let mut sectors = vec![];
+		sectors.extend_from_slice(&0_i16.to_le_bytes());
+		sectors.extend_from_slice(&128_i16.to_le_bytes());
+		sectors.extend_from_slice(&[0; 16]);
+
+		for v in [160_u16, 0, 0] {
+			sectors.extend_from_slice(&v.to_le_bytes());
+		}
+
+		let mut things = vec![];
+
+		// Skills 1-5; then the same but also "multiplayer only" and ambush.
+		for (ednum, flags) in [(1_u16, 0b0111_i16), (3004, 0b1_1111)] {
+			things.extend_from_slice(&32_i16.to_le_bytes());
+			things.extend_from_slice(&32_i16.to_le_bytes());
+			things.extend_from_slice(&90_u16.to_le_bytes());
+			things.extend_from_slice(&ednum.to_le_bytes());
+			things.extend_from_slice(&flags.to_le_bytes());
+		}
+
+		let raw = RawLevel {
+			linedefs: RawLineDefs::Doom(read::linedefs(&lines).unwrap()),
+			nodes: &[],
+			sectors: read::sectors(&sectors).unwrap(),
+			segs: &[],
+			sidedefs: read::sidedefs(&sides).unwrap(),
+			subsectors: &[],
+			things: RawThings::Doom(read::things(&things).unwrap()),
+			vertices: read::vertexes(&verts).unwrap(),
+		};
+
+		let level = level_from_binary(&raw);
+
+		assert_eq!(level.format, LevelFormat::Doom);
+		assert_eq!(level.linedefs.len(), 4);
+		assert_eq!(level.things.len(), 2);
+
+		let line = &level.linedefs[3];
+		assert_eq!(
+			line.flags,
+			LineFlags::IMPASSIBLE | LineFlags::UPPER_UNPEGGED
+		);
+		assert_eq!(line.ids, [7]);
+		assert!(level.linedefs[0].ids.is_empty());
+		assert_eq!(line.args, [7, 0, 0, 0, 0]);
+		assert_eq!(line.side_left, None);
+
+		let player = level.things[0];
+		assert!(player.flags.contains(
+			ThingFlags::SINGLEPLAY
+				| ThingFlags::COOP
+				| ThingFlags::DEATHMATCH
+				| ThingFlags::CLASS_1
+		));
+		assert!(!player.flags.contains(ThingFlags::AMBUSH));
+		assert_eq!(player.pos, Vec3::new(32.0, 32.0, 0.0) * FSCALE);
+
+		let monster = level.things[1];
+		assert!(monster
+			.flags
+			.contains(ThingFlags::AMBUSH | ThingFlags::COOP));
+		assert!(!monster.flags.contains(ThingFlags::SINGLEPLAY));
+
+		assert_eq!(
+			level.sidedefs[0].tex_top.as_deref().map(|t| t.as_str()),
+			Some("STARTAN2")
+		);
+		assert_eq!(level.sectors[0].height_ceiling, 128.0 * FSCALE);
+	}
+
+	#[test]
+	fn special_translation() {
+		let linedef = |special, flags_hexen, args: [i32; 5]| LineDef {
+			vert_start: 0,
+			vert_end: 1,
+			flags: LineFlags::empty(),
+			flags_hexen,
+			special,
+			args,
+			ids: (args[0] != 0).then_some(args[0]).into_iter().collect(),
+			side_right: 0,
+			side_left: None,
+		};
+
+		let mut level = Level {
+			format: LevelFormat::Doom,
+			vertices: vec![],
+			linedefs: vec![linedef(97, 0, [4, 0, 0, 0, 0]), linedef(666, 0, [0; 5])],
+			sidedefs: vec![],
+			sectors: vec![],
+			things: vec![],
+		};
+
+		let specials = line_specials(&level, "MAP01");
+		assert!(matches!(specials[0].special, LineSpecial::Teleport(_)));
+		assert_eq!(specials[0].tag, 4);
+		assert_eq!(specials[1].special, LineSpecial::Unknown(666));
+
+		// Hexen-format: `Teleport`, player-crossed and repeatable.
+		level.format = LevelFormat::Extended;
+		level.linedefs = vec![linedef(70, 0x0200, [0, 4, 0, 0, 0])];
+
+		let specials = line_specials(&level, "MAP01");
+		assert_eq!(specials[0].tag, 4);
+		assert_eq!(
+			specials[0].activation,
+			Activation::PLAYER_CROSS | Activation::REPEAT
+		);
+	}
+
+	/// Hexen-format specials, translated from the bytes of `LINEDEFS` and `THINGS` lumps.
+	#[test]
+	fn hexen_lumps() {
+		let mut lines = vec![];
+
+		for (flags, special, args) in [
+			// `Teleport` to sector 4; player-crossed and repeatable.
+			(0x0200_u16, 70_u8, [0_u8, 4, 0, 0, 0]),
+			// `Door_Raise` on sector 5; used by players or monsters.
+			(0x0004 | 0x0400 | 0x2000, 12, [5, 16, 34, 0, 0]),
+			// `Line_SetIdentification` with line ID 265.
+			(0, 121, [9, 0, 0, 0, 1]),
+		] {
+			lines.extend_from_slice(&0_u16.to_le_bytes());
+			lines.extend_from_slice(&1_u16.to_le_bytes());
+			lines.extend_from_slice(&flags.to_le_bytes());
+			lines.push(special);
+			lines.extend_from_slice(&args);
+			lines.extend_from_slice(&0_u16.to_le_bytes());
+			lines.extend_from_slice(&0xFFFF_u16.to_le_bytes());
+		}
+
+		let mut things = vec![];
+		things.extend_from_slice(&7_i16.to_le_bytes());
+
+		for v in [32_i16, -32, 16] {
+			things.extend_from_slice(&v.to_le_bytes());
+		}
+
+		things.extend_from_slice(&90_u16.to_le_bytes());
+		things.extend_from_slice(&3004_u16.to_le_bytes());
+		things.extend_from_slice(&0x0107_i16.to_le_bytes());
+		things.extend_from_slice(&[80, 1, 2, 3, 4, 5]);
+
+		let raw = RawLevel {
+			linedefs: RawLineDefs::Ext(read::linedefs_ext(&lines).unwrap()),
+			nodes: &[],
+			sectors: &[],
+			segs: &[],
+			sidedefs: &[],
+			subsectors: &[],
+			things: RawThings::Ext(read::things_ext(&things).unwrap()),
+			vertices: &[],
+		};
+
+		let level = level_from_binary(&raw);
+		assert_eq!(level.format, LevelFormat::Extended);
+
+		assert_eq!(level.linedefs[1].flags, LineFlags::TWO_SIDED);
+		assert_eq!(level.linedefs[1].args, [5, 16, 34, 0, 0]);
+		assert_eq!(level.linedefs[2].special, 0);
+		assert_eq!(level.linedefs[2].ids, [265]);
+
+		let thing = &level.things[0];
+		assert_eq!(thing.tid, 7);
+		assert_eq!(thing.special, 80);
+		assert_eq!(thing.args, [1, 2, 3, 4, 5]);
+		assert!(thing
+			.flags
+			.contains(ThingFlags::SINGLEPLAY | ThingFlags::SKILL_3));
+
+		let specials = line_specials(&level, "MAP01");
+
+		assert!(matches!(specials[0].special, LineSpecial::Teleport(_)));
+		assert_eq!(specials[0].tag, 4);
+		assert_eq!(
+			specials[0].activation,
+			Activation::PLAYER_CROSS | Activation::REPEAT
+		);
+
+		let LineSpecial::Door(door) = specials[1].special else {
+			panic!("expected a door, got: {:?}", specials[1].special);
+		};
+
+		assert_eq!(door.kind, special::DoorKind::OpenWaitClose);
+		assert_eq!(door.speed, 16);
+		// 34 eighths of a second.
+		assert_eq!(door.delay, 148);
+		assert_eq!(specials[1].tag, 5);
+		assert_eq!(
+			specials[1].activation,
+			Activation::PLAYER_USE | Activation::MONSTER_USE
+		);
+
+		assert_eq!(specials[2].special, LineSpecial::None);
+	}
+
+	#[test]
+	fn boom_colormap_transfer() {
+		let sector = |ids: Vec<i32>| Sector {
+			height_floor: 0.0,
+			height_ceiling: 128.0,
+			tex_floor: None,
+			tex_ceiling: None,
+			light_level: 160,
+			special: 0,
+			ids,
+			colormap: None,
+		};
+
+		let mut level = Level {
+			format: LevelFormat::Doom,
+			vertices: vec![],
+			linedefs: vec![LineDef {
+				vert_start: 0,
+				vert_end: 1,
+				flags: LineFlags::empty(),
+				flags_hexen: 0,
+				special: 242,
+				args: [3, 0, 0, 0, 0],
+				ids: vec![3],
+				side_right: 0,
+				side_left: None,
+			}],
+			sidedefs: vec![SideDef {
+				offset: IVec2::ZERO,
+				tex_top: None,
+				tex_mid: Id8::try_from("WATERMAP").ok(),
+				tex_bottom: None,
+				sector: 0,
+			}],
+			sectors: vec![sector(vec![]), sector(vec![3])],
+			things: vec![],
+		};
+
+		assign_colormaps(&mut level);
+		assert_eq!(level.sectors[0].colormap, None);
+		assert_eq!(
+			level.sectors[1].colormap.as_deref().map(|t| t.as_str()),
+			Some("WATERMAP")
+		);
+	}
+
+	#[test]
+	fn missing_textures() {
+		let side = |tex_mid: &str| SideDef {
+			offset: IVec2::ZERO,
+			tex_top: None,
+			tex_mid: Id8::try_from(tex_mid).ok(),
+			tex_bottom: Id8::try_from("STARTAN2").ok(),
+			sector: 0,
+		};
+
+		let mut level = Level {
+			format: LevelFormat::Doom,
+			vertices: vec![],
+			linedefs: vec![],
+			sidedefs: vec![side("STARTAN2"), side("NOSUCHTX")],
+			sectors: vec![Sector {
+				height_floor: 0.0,
+				height_ceiling: 128.0,
+				tex_floor: Id8::try_from("FLOOR4_8").ok(),
+				tex_ceiling: Id8::try_from("CEIL3_5").ok(),
+				light_level: 160,
+				special: 0,
+				ids: vec![],
+				colormap: None,
+			}],
+			things: vec![],
+		};
+
+		let placeholder = Id8::try_from("-NOTEX-").unwrap();
+
+		let missing = check_textures(
+			&mut level,
+			"MAP01",
+			placeholder,
+			|tex| tex == "STARTAN2",
+			|flat| flat == "FLOOR4_8" || flat == "CEIL3_5",
+		);
+
+		assert_eq!(missing.len(), 1);
+		assert_eq!(missing[0].name.as_str(), "NOSUCHTX");
+		assert_eq!(missing[0].referrers, [TextureReferrer::SideMid(1)]);
+		assert_eq!(level.sidedefs[1].tex_mid, Some(placeholder));
+		assert_eq!(
+			level.sidedefs[0].tex_mid.as_deref().map(|t| t.as_str()),
+			Some("STARTAN2")
+		);
+		assert_eq!(
+			missing[0].to_string(),
+			"texture `NOSUCHTX` not found; used by sidedef 1 (middle)"
+		);
+	}
+
+	#[test]
+	fn udmf_ids() {
+		let mut sector = Sector {
+			height_floor: 0.0,
+			height_ceiling: 0.0,
+			tex_floor: None,
+			tex_ceiling: None,
+			light_level: 160,
+			special: 0,
+			ids: vec![],
+			colormap: None,
+		};
+
+		let kvp = |key, val| KeyVal { key, val };
+
+		read_sectordef_field(&mut sector, kvp("moreids", Value::String("\"4 5\"")));
+		read_sectordef_field(&mut sector, kvp("id", Value::Int("3")));
+		read_sectordef_field(&mut sector, kvp("lightlevel", Value::Int("192")));
+		assert_eq!(sector.ids, [3, 4, 5]);
+		assert_eq!(sector.light_level, 192);
+
+		let mut linedef = LineDef {
+			vert_start: 0,
+			vert_end: 1,
+			flags: LineFlags::empty(),
+			flags_hexen: 0,
+			special: 0,
+			args: [0; 5],
+			ids: vec![],
+			side_right: 0,
+			side_left: None,
+		};
+
+		read_linedef_field(&mut linedef, kvp("ID", Value::Int("-1")));
+		assert!(linedef.ids.is_empty());
+		read_linedef_field(&mut linedef, kvp("id", Value::Int("-2")));
+		read_linedef_field(&mut linedef, kvp("moreids", Value::String("\"-2 -1 12\"")));
+		read_linedef_field(&mut linedef, kvp("arg2", Value::Int("0x10")));
+		assert_eq!(linedef.ids, [-2, 12]);
+		assert_eq!(linedef.args, [0, 0, 16, 0, 0]);
+	}
+
+	#[test]
+	fn udmf_textmap() {
+		const TEXTMAP: &str = r#"
+namespace = "ZDoom";
+
+vertex { x = 0.0; y = 0.0; }
+vertex { x = 0; y = 64.5; }
+
+linedef { v1 = 0; v2 = 1; sidefront = 0; blocking = true; id = 3; }
+
+sidedef { sector = 0; texturemiddle = "STARTAN2"; texturetop = "-"; offsetx = -8; }
+
+sector {
+	heightceiling = 128;
+	texturefloor = "FLOOR4_8";
+	textureceiling = "CEIL3_5";
+}
+
+thing { type = 3004; x = 32.0; y = -16.0; height = 8; angle = 450; single = true; skill3 = true; }
+
+bogus { }
+"#;
+
+		let (level, errors) = parse_textmap(TEXTMAP).unwrap();
+
+		assert_eq!(level.format, LevelFormat::Udmf(UdmfNamespace::ZDoom));
+		assert_eq!(errors.len(), 1);
+		assert_eq!(level.vertices.len(), 2);
+		assert_eq!(level.vertices[1].y, 64.5 * FSCALE);
+
+		let line = &level.linedefs[0];
+		assert_eq!((line.vert_start, line.vert_end), (0, 1));
+		assert_eq!(line.flags, LineFlags::IMPASSIBLE);
+		assert_eq!(line.ids, [3]);
+		assert_eq!(line.side_left, None);
+
+		let side = &level.sidedefs[0];
+		assert_eq!(
+			side.tex_mid.as_deref().map(|t| t.as_str()),
+			Some("STARTAN2")
+		);
+		assert_eq!(side.tex_top, None);
+		assert_eq!(side.offset, IVec2::new(-8, 0));
+
+		let sector = &level.sectors[0];
+		assert_eq!(sector.height_ceiling, 128.0 * FSCALE);
+		assert_eq!(sector.light_level, 160);
+		assert_eq!(
+			sector.tex_floor.as_deref().map(|t| t.as_str()),
+			Some("FLOOR4_8")
+		);
+
+		let thing = level.things[0];
+		assert_eq!(thing.ednum, 3004);
+		assert_eq!(thing.pos, Vec3::new(32.0, -16.0, 8.0) * FSCALE);
+		assert_eq!(thing.angle, 90);
+		assert_eq!(thing.flags, ThingFlags::SINGLEPLAY | ThingFlags::SKILL_3);
+
+		assert!(matches!(
+			parse_textmap("namespace = \"quake\";"),
+			Err(udmf::Error::InvalidNamespace(_))
+		));
+	}
+}
@@ -40,8 +40,8 @@ where
 
 	// Generate one adjacency graph per sector.
 
-	raw.linedefs.par_iter().for_each(|linedef| {
-		let i_r = linedef.right_side() as usize;
+	raw.linedefs.iter().par_bridge().for_each(|linedef| {
+		let i_r = linedef.right_side as usize;
 		let side_r = &raw.sidedefs[i_r];
 		let sector_r = side_r.sector() as usize;
 		let mut sgraph = sgraphs
@@ -49,20 +49,20 @@ where
 			.or_insert(SectorGraph::new(sector_r));
 		sgraph.add_verts(
 			raw,
-			linedef.start_vertex(),
-			linedef.end_vertex(),
+			linedef.start_vertex,
+			linedef.end_vertex,
 			LineSide::Right,
 		);
 
-		if let Some(i_l) = linedef.left_side().map(|i| i as usize) {
+		if let Some(i_l) = linedef.left_side.map(|i| i as usize) {
 			let side_l = &raw.sidedefs[i_l];
 			let sector_l = side_l.sector() as usize;
 
 			if sector_l == sector_r {
 				sgraph.add_verts(
 					raw,
-					linedef.start_vertex(),
-					linedef.end_vertex(),
+					linedef.start_vertex,
+					linedef.end_vertex,
 					LineSide::Left,
 				);
 			} else {
@@ -74,8 +74,8 @@ where
 
 				sgraph.add_verts(
 					raw,
-					linedef.start_vertex(),
-					linedef.end_vertex(),
+					linedef.start_vertex,
+					linedef.end_vertex,
 					LineSide::Left,
 				);
 			}
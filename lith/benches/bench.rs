@@ -1,9 +1,16 @@
 use cranelift::prelude::settings::OptLevel;
 use dashmap::DashMap;
-use lithica::{compile, Compiler};
+use lithica::{compile, runtime::Runtime, Compiler};
 use util::{pushvec::PushVec, rstring::RString};
 
-criterion::criterion_group!(benches, cranelift_ops, toolchain, string_interning);
+criterion::criterion_group!(
+	benches,
+	cranelift_ops,
+	toolchain,
+	declaration,
+	string_interning,
+	script_budget
+);
 criterion::criterion_main!(benches);
 
 fn cranelift_ops(crit: &mut criterion::Criterion) {
@@ -86,6 +93,8 @@ fn toolchain(crit: &mut criterion::Criterion) {
 			let mut compiler = Compiler::new(compile::Config {
 				opt: OptLevel::None,
 				hotswap: false,
+				budget_checks: true,
+				enable_verifier: None,
 			});
 
 			compiler.finish_registration();
@@ -98,3 +107,112 @@ fn toolchain(crit: &mut criterion::Criterion) {
 
 	grp.finish();
 }
+
+/// Compares declaring a synthetic library of many files on one thread against
+/// declaring it on every available thread. Only the declaration pass is timed.
+fn declaration(crit: &mut criterion::Criterion) {
+	const FILES: usize = 256;
+	const FNS_PER_FILE: usize = 64;
+
+	let source: String = (0..FNS_PER_FILE)
+		.map(|i| format!("function f{i}(): i32;\n"))
+		.collect();
+
+	let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+	let mut grp = crit.benchmark_group("Declaration");
+
+	for threads in [1, max_threads] {
+		let pool = rayon::ThreadPoolBuilder::new()
+			.num_threads(threads)
+			.build()
+			.unwrap();
+
+		grp.bench_function(format!("{FILES} Files, {threads} Thread(s)"), |bencher| {
+			bencher.iter_custom(|iters| {
+				pool.install(|| {
+					let mut total = std::time::Duration::ZERO;
+
+					for _ in 0..iters {
+						let mut compiler = Compiler::new(compile::Config::default());
+
+						compiler
+							.register_lib(
+								compile::LibMeta {
+									name: "bench".to_string(),
+									version: lithica::Version::V0_0_0,
+									native: false,
+								},
+								|ftree| {
+									let root = ftree.add_folder(ftree.root(), "bench");
+
+									for i in 0..FILES {
+										ftree.add_file(root, &format!("file{i}.lith"), &source);
+									}
+
+									Ok(root)
+								},
+							)
+							.unwrap();
+
+						compiler.finish_registration();
+
+						let start = std::time::Instant::now();
+						compile::declare_symbols(&mut compiler);
+						total += start.elapsed();
+					}
+
+					total
+				})
+			});
+		});
+	}
+
+	grp.finish();
+}
+
+fn script_budget(crit: &mut criterion::Criterion) {
+	const ITERS: u64 = 10_000;
+
+	fn work(i: u64) -> u64 {
+		std::hint::black_box(i.wrapping_mul(0x9E37_79B9_7F4A_7C15).rotate_left(17))
+	}
+
+	let mut compiler = Compiler::new(compile::Config {
+		opt: OptLevel::None,
+		hotswap: false,
+		budget_checks: true,
+		enable_verifier: None,
+	});
+
+	compiler.finish_registration();
+	compile::declare_symbols(&mut compiler);
+	compile::semantic_check(&mut compiler);
+	let mut runtime = compile::finalize(compiler, false, false).runtime;
+	runtime.begin_tic(std::time::Duration::from_secs(3600));
+	let rt: *mut Runtime = std::ptr::addr_of_mut!(runtime);
+
+	let mut grp = crit.benchmark_group("Script Budget");
+
+	grp.bench_function("Loop, Unchecked", |bencher| {
+		bencher.iter(|| {
+			for i in 0..ITERS {
+				let _ = work(i);
+			}
+		});
+	});
+
+	grp.bench_function("Loop, Safepoint per Iteration", |bencher| {
+		bencher.iter(|| {
+			for i in 0..ITERS {
+				unsafe {
+					lithica::runtime::safepoint(rt);
+				}
+
+				let _ = work(i);
+			}
+		});
+	});
+
+	grp.finish();
+	runtime.end_tic();
+}
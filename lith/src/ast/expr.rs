@@ -7,7 +7,7 @@ use doomfront::{
 
 use crate::{Syntax, SyntaxNode, SyntaxToken};
 
-use super::{Annotation, ArgList, CoreElement, Item, LitToken, Name, TypeSpec};
+use super::{Annotation, ArgList, BlockLabel, CoreElement, Item, LitToken, Name, TypeSpec};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
@@ -451,6 +451,11 @@ pub struct ExprBlock(SyntaxNode);
 simple_astnode!(Syntax, ExprBlock, Syntax::ExprBlock);
 
 impl ExprBlock {
+	#[must_use]
+	pub fn label(&self) -> Option<BlockLabel> {
+		self.0.first_child().and_then(BlockLabel::cast)
+	}
+
 	pub fn innards(&self) -> impl Iterator<Item = CoreElement> {
 		self.0.children().filter_map(CoreElement::cast)
 	}
@@ -696,7 +701,13 @@ impl FieldDecl {
 
 	#[must_use]
 	pub fn type_spec(&self) -> TypeSpec {
-		TypeSpec::cast(self.0.last_child().unwrap()).unwrap()
+		self.0.children().find_map(TypeSpec::cast).unwrap()
+	}
+
+	/// The expression after `=`, if any.
+	#[must_use]
+	pub fn initializer(&self) -> Option<Expr> {
+		self.0.children().find_map(Expr::cast)
 	}
 }
 
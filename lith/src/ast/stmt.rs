@@ -12,6 +12,7 @@ pub enum Statement {
 	Bind(StmtBind),
 	Break(StmtBreak),
 	Continue(StmtContinue),
+	Defer(StmtDefer),
 	Expr(StmtExpr),
 	Return(StmtReturn),
 }
@@ -28,6 +29,7 @@ impl AstNode for Statement {
 			Syntax::StmtBind
 				| Syntax::StmtBreak
 				| Syntax::StmtContinue
+				| Syntax::StmtDefer
 				| Syntax::StmtExpr
 				| Syntax::StmtReturn
 		)
@@ -41,6 +43,7 @@ impl AstNode for Statement {
 			Syntax::StmtBind => Some(Self::Bind(StmtBind(node))),
 			Syntax::StmtBreak => Some(Self::Break(StmtBreak(node))),
 			Syntax::StmtContinue => Some(Self::Continue(StmtContinue(node))),
+			Syntax::StmtDefer => Some(Self::Defer(StmtDefer(node))),
 			Syntax::StmtExpr => Some(Self::Expr(StmtExpr(node))),
 			Syntax::StmtReturn => Some(Self::Return(StmtReturn(node))),
 			_ => None,
@@ -52,6 +55,7 @@ impl AstNode for Statement {
 			Self::Bind(inner) => inner.syntax(),
 			Self::Break(inner) => inner.syntax(),
 			Self::Continue(inner) => inner.syntax(),
+			Self::Defer(inner) => inner.syntax(),
 			Self::Expr(inner) => inner.syntax(),
 			Self::Return(inner) => inner.syntax(),
 		}
@@ -163,6 +167,37 @@ impl StmtContinue {
 	}
 }
 
+// Defer ///////////////////////////////////////////////////////////////////////
+
+/// Wraps a node tagged [`Syntax::StmtDefer`].
+///
+/// The body runs whenever control leaves the enclosing block, by any path;
+/// multiple deferred statements in one block run in reverse order of declaration.
+/// Names in the body resolve where the statement is declared, but locals are
+/// read when the body runs, so it sees any assignments made after the `defer`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StmtDefer(SyntaxNode);
+
+simple_astnode!(Syntax, StmtDefer, Syntax::StmtDefer);
+
+impl StmtDefer {
+	/// The returned token is always tagged [`Syntax::KwDefer`].
+	#[must_use]
+	pub fn keyword(&self) -> SyntaxToken {
+		let ret = self.0.first_token().unwrap();
+		debug_assert_eq!(ret.kind(), Syntax::KwDefer);
+		ret
+	}
+
+	pub fn body(&self) -> AstResult<Expr> {
+		self.0
+			.children()
+			.find_map(Expr::cast)
+			.ok_or(AstError::Missing)
+	}
+}
+
 // Expression //////////////////////////////////////////////////////////////////
 
 /// Wraps a node tagged [`Syntax::StmtExpr`].
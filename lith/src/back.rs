@@ -1,8 +1,16 @@
 //! Details of Lithica's [Cranelift](cranelift)-based backend.
 
-use std::{any::TypeId, hash::BuildHasherDefault};
+use std::hash::BuildHasherDefault;
 
-use cranelift::codegen::ir::UserExternalName;
+use cranelift::codegen::{
+	data_value::DataValue,
+	ir::{self, ExternalName, UserExternalName},
+};
+use cranelift_interpreter::{
+	environment::FunctionStore,
+	interpreter::{Interpreter, InterpreterError, InterpreterState},
+	step::ControlFlow,
+};
 use cranelift_module::{FuncId, Module};
 use rustc_hash::FxHashMap;
 
@@ -10,7 +18,7 @@ use crate::{
 	compile::{self, module::JitModule},
 	runtime::Runtime,
 	types::{FxDashMap, IrOPtr},
-	Compiler,
+	Compiler, ValVec,
 };
 
 /// The complete set of possible compilation artifacts which can be emitted by [`finalize`].
@@ -25,6 +33,56 @@ pub struct Compilation {
 	/// Pretty-printed Cranelift VCode,
 	/// which resembles the final generated machine instructions.
 	pub disasm: Option<FxHashMap<FuncId, String>>,
+	reports: Vec<FuncReport>,
+}
+
+impl Compilation {
+	/// One report per JIT-compiled function, from the function with the
+	/// most instructions to the one with the fewest.
+	#[must_use]
+	pub fn function_report(&self) -> Vec<FuncReport> {
+		let mut ret = self.reports.clone();
+		ret.sort_by(|a, b| b.instructions.cmp(&a.instructions));
+		ret
+	}
+}
+
+/// Size and complexity metrics for one function, taken from its CLIF before it
+/// gets passed to Cranelift's optimizer, for finding functions which are
+/// unlikely to be inlined or which bloat the module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuncReport {
+	pub id: FuncId,
+	/// As written in the function's declaration.
+	/// Monomorphized instances of the same function share a name.
+	pub name: String,
+	pub blocks: usize,
+	pub instructions: usize,
+	pub stack_slots: usize,
+	/// The sum of the sizes of all of the function's sized stack slots.
+	pub stack_bytes: u32,
+}
+
+impl FuncReport {
+	#[must_use]
+	pub(crate) fn new(id: FuncId, name: String, clif: &ir::Function) -> Self {
+		let blocks = clif.layout.blocks().count();
+
+		let instructions = clif
+			.layout
+			.blocks()
+			.map(|block| clif.layout.block_insts(block).count())
+			.sum();
+
+		Self {
+			id,
+			name,
+			blocks,
+			instructions,
+			stack_slots: clif.sized_stack_slots.len() + clif.dynamic_stack_slots.len(),
+			stack_bytes: clif.sized_stack_slots.values().map(|slot| slot.size).sum(),
+		}
+	}
 }
 
 /// [`Compilation::clif`] will only be `Some` if `emit_clif` is `true`.
@@ -56,7 +114,7 @@ pub fn finalize(mut compiler: Compiler, emit_clif: bool, disasm: bool) -> Compil
 		None
 	};
 
-	jit_compile_functions(
+	let reports = jit_compile_functions(
 		&compiler,
 		&mut module,
 		ir,
@@ -69,17 +127,10 @@ pub fn finalize(mut compiler: Compiler, emit_clif: bool, disasm: bool) -> Compil
 		.expect("JIT definition finalization failed");
 
 	Compilation {
-		runtime: Runtime {
-			_function_rti: FxHashMap::default(),
-			_data_rti: FxHashMap::default(),
-			_type_rti: FxHashMap::default(),
-			module,
-
-			userdata: std::ptr::null_mut(),
-			userdata_t: TypeId::of::<()>(),
-		},
+		runtime: Runtime::new(module),
 		clif: clif_map,
 		disasm: disasm_map,
+		reports,
 	}
 }
 
@@ -89,13 +140,15 @@ fn jit_compile_functions(
 	ir: FxDashMap<UserExternalName, FunctionIr>,
 	mut clif_map: Option<&mut FxHashMap<FuncId, String>>,
 	mut disasm_map: Option<&mut FxHashMap<FuncId, String>>,
-) {
+) -> Vec<FuncReport> {
 	let mut ctx = module.make_context();
 	let want_disasm = disasm_map.is_some();
+	let mut reports = Vec::with_capacity(ir.len());
 
-	for (_, FunctionIr { id, ptr, .. }) in ir.into_iter() {
+	for (_, FunctionIr { id, ptr, name, .. }) in ir.into_iter() {
 		let clif = unsafe { ptr.read() };
 		ctx.set_disasm(want_disasm);
+		reports.push(FuncReport::new(id, name, &clif));
 
 		if let Some(m) = clif_map.as_mut() {
 			let mut buf = String::new();
@@ -117,11 +170,134 @@ fn jit_compile_functions(
 
 		module.clear_context(&mut ctx);
 	}
+
+	reports
 }
 
 #[derive(Debug)]
 pub(crate) struct FunctionIr {
 	pub(crate) id: FuncId,
 	pub(crate) ptr: IrOPtr,
+	/// For [`FuncReport::name`].
+	pub(crate) name: String,
 	pub(crate) _sig_hash: u64,
 }
+
+// Compile-time evaluation //////////////////////////////////////////////////////
+
+/// How many instructions [`const_eval`] may interpret before giving up.
+pub const CEVAL_FUEL: u64 = 10_000;
+
+/// Failure modes of [`const_eval`].
+#[derive(Debug)]
+pub enum CEvalError {
+	/// The function, or one of the functions it calls, has no IR
+	/// (e.g. because it is native or has not been defined yet).
+	NotFound(UserExternalName),
+	/// The chain of calls out of the function nests more deeply than 255 levels.
+	Recursion,
+	/// Interpretation used up all [`CEVAL_FUEL`]; the function may be looping infinitely.
+	Runaway,
+	/// The function trapped, e.g. by dividing by zero.
+	Trap(String),
+	/// Any other failure of Cranelift's interpreter.
+	Interpret(String),
+}
+
+impl std::error::Error for CEvalError {}
+
+impl std::fmt::Display for CEvalError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NotFound(name) => write!(f, "no IR found for function `{name}`"),
+			Self::Recursion => write!(f, "compile-time evaluation recurred too deeply"),
+			Self::Runaway => write!(f, "compile-time execution took too long to finish"),
+			Self::Trap(trap) => write!(f, "compile-time execution trapped: {trap}"),
+			Self::Interpret(err) => write!(f, "compile-time interpretation failed: {err}"),
+		}
+	}
+}
+
+/// Runs the IR of `func` (and of every function it calls) through Cranelift's
+/// interpreter, so that constant contexts do not require emitting machine code.
+/// The JIT module is never touched.
+pub fn const_eval(
+	compiler: &Compiler,
+	func: &UserExternalName,
+	args: &[DataValue],
+) -> Result<ValVec, CEvalError> {
+	const_eval_in(&compiler.ir, func, args)
+}
+
+pub(crate) fn const_eval_in(
+	ir: &FxDashMap<UserExternalName, FunctionIr>,
+	func: &UserExternalName,
+	args: &[DataValue],
+) -> Result<ValVec, CEvalError> {
+	let mut reachable = vec![];
+	gather_callees(ir, func, 0, &mut reachable)?;
+
+	let mut store = FunctionStore::default();
+
+	for (name, clif) in &reachable {
+		// (RAT) The interpreter resolves calls by the display form of
+		// the callee's name, which is the same as `UserExternalName`'s.
+		store.add(name.to_string(), clif);
+	}
+
+	let state = InterpreterState::default().with_function_store(store);
+	let mut interpreter = Interpreter::new(state).with_fuel(Some(CEVAL_FUEL));
+
+	match interpreter.call_by_name(&func.to_string(), args) {
+		Ok(ControlFlow::Return(rets)) => Ok(rets),
+		Ok(ControlFlow::Trap(trap)) => Err(CEvalError::Trap(format!("{trap:?}"))),
+		Ok(_) => Err(CEvalError::Interpret(
+			"function exited without returning".to_string(),
+		)),
+		Err(InterpreterError::FuelExhausted) => Err(CEvalError::Runaway),
+		Err(err) => Err(CEvalError::Interpret(err.to_string())),
+	}
+}
+
+/// Collects `name` and everything it calls (transitively) into `out`.
+/// Functions get cloned out of `ir` so that none of its shards stay locked
+/// while interpreting, since sema may be defining other functions in parallel.
+fn gather_callees(
+	ir: &FxDashMap<UserExternalName, FunctionIr>,
+	name: &UserExternalName,
+	depth: u8,
+	out: &mut Vec<(UserExternalName, ir::Function)>,
+) -> Result<(), CEvalError> {
+	if out.iter().any(|(n, _)| n == name) {
+		return Ok(());
+	}
+
+	let Some(next_depth) = depth.checked_add(1) else {
+		return Err(CEvalError::Recursion);
+	};
+
+	let Some(fn_ir) = ir.get(name) else {
+		return Err(CEvalError::NotFound(name.clone()));
+	};
+
+	let clif = ir::Function::clone(&fn_ir.ptr);
+	drop(fn_ir);
+
+	let callees: Vec<UserExternalName> = clif
+		.dfg
+		.ext_funcs
+		.values()
+		.filter_map(|ext| match ext.name {
+			ExternalName::User(uname) => Some(clif.params.user_named_funcs()[uname].clone()),
+			_ => None,
+		})
+		.collect();
+
+	out.push((name.clone(), clif));
+
+	for callee in &callees {
+		gather_callees(ir, callee, next_depth, out)?;
+	}
+
+	Ok(())
+}
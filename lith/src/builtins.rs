@@ -223,6 +223,30 @@ pub(crate) fn primitive_type(
 					tsys::Primitive::IName,
 				)
 			}),
+		"vec2" => ctx
+			.sym_cache
+			.vec2_t
+			.as_ptr()
+			.map(TypePtr::new)
+			.unwrap_or_else(|| {
+				lazy_init(ctx, &ctx.sym_cache.vec2_t, arg_list, tsys::Primitive::Vec2)
+			}),
+		"vec3" => ctx
+			.sym_cache
+			.vec3_t
+			.as_ptr()
+			.map(TypePtr::new)
+			.unwrap_or_else(|| {
+				lazy_init(ctx, &ctx.sym_cache.vec3_t, arg_list, tsys::Primitive::Vec3)
+			}),
+		"vec4" => ctx
+			.sym_cache
+			.vec4_t
+			.as_ptr()
+			.map(TypePtr::new)
+			.unwrap_or_else(|| {
+				lazy_init(ctx, &ctx.sym_cache.vec4_t, arg_list, tsys::Primitive::Vec4)
+			}),
 		"never_t" => ctx
 			.sym_cache
 			.never_t
@@ -289,6 +313,14 @@ pub(crate) enum Index {
 	TypeOf,
 	RttiOf,
 	GcUsage,
+	/// See [`crate::front::vector::dot`].
+	Dot,
+	/// See [`crate::front::vector::cross`].
+	Cross,
+	/// See [`crate::front::vector::length`].
+	Length,
+	/// See [`crate::front::vector::normalize`].
+	Normalize,
 	__Last,
 }
 
@@ -17,6 +17,7 @@ use cranelift::{
 };
 use crossbeam::channel::{Receiver, Sender};
 use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
 
 use crate::{
 	ast,
@@ -33,7 +34,7 @@ use crate::{
 };
 
 pub use crate::{
-	back::finalize,
+	back::{const_eval, finalize, CEvalError, Compilation, FuncReport, CEVAL_FUEL},
 	front::{decl::declare_symbols, sema::semantic_check},
 };
 
@@ -76,6 +77,51 @@ pub struct Config {
 	pub opt: OptLevel,
 	/// Whether the JIT backend should allow function re-definition.
 	pub hotswap: bool,
+	/// Whether to emit safepoints which check the [runtime's per-tic budget](
+	/// crate::runtime::Runtime::begin_tic). Only worth disabling for benchmarks.
+	pub budget_checks: bool,
+	/// Whether Cranelift should verify each function's IR before emitting it.
+	/// If `None`, the backend leaves this to the frontend's semantic pass.
+	pub enable_verifier: Option<bool>,
+}
+
+impl Config {
+	#[must_use]
+	pub fn with_opt(mut self, opt: OptLevel) -> Self {
+		self.opt = opt;
+		self
+	}
+
+	/// Note that enabling hotswapping also makes the JIT backend emit
+	/// position-independent code.
+	#[must_use]
+	pub fn with_hotswap(mut self, hotswap: bool) -> Self {
+		self.hotswap = hotswap;
+		self
+	}
+
+	#[must_use]
+	pub fn with_budget_checks(mut self, budget_checks: bool) -> Self {
+		self.budget_checks = budget_checks;
+		self
+	}
+
+	#[must_use]
+	pub fn with_verifier(mut self, enable: Option<bool>) -> Self {
+		self.enable_verifier = enable;
+		self
+	}
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			opt: OptLevel::None,
+			hotswap: false,
+			budget_checks: true,
+			enable_verifier: None,
+		}
+	}
 }
 
 /// Note that a Lithica library is *not* a compilation unit.
@@ -291,6 +337,27 @@ impl Compiler {
 		guard.push(issue);
 	}
 
+	/// Files get processed in parallel, so issues arrive in an order which depends
+	/// on thread interleaving. This restores a deterministic order by stably
+	/// sorting issues by the position of their file in the file tree; issues
+	/// from one file were already raised in order, since each file is
+	/// processed by only one thread.
+	pub(crate) fn order_issues(&mut self) {
+		let graph = &self.ftree.graph;
+
+		let file_order: FxHashMap<&str, usize> = graph
+			.node_indices()
+			.map(|ix| (graph[ix].path(), ix.index()))
+			.collect();
+
+		self.issues.get_mut().sort_by_key(|issue| {
+			file_order
+				.get(issue.id.path.as_str())
+				.copied()
+				.unwrap_or(usize::MAX)
+		});
+	}
+
 	#[must_use]
 	pub(crate) fn any_errors(&self) -> bool {
 		let guard = self.issues.lock();
@@ -99,6 +99,9 @@ pub(crate) struct SymCache {
 	pub(crate) f32_t: TypeNPtr,
 	pub(crate) f64_t: TypeNPtr,
 	pub(crate) iname_t: TypeNPtr,
+	pub(crate) vec2_t: TypeNPtr,
+	pub(crate) vec3_t: TypeNPtr,
+	pub(crate) vec4_t: TypeNPtr,
 	pub(crate) never_t: TypeNPtr,
 }
 
@@ -120,6 +123,9 @@ impl Default for SymCache {
 			f32_t: TypeNPtr::null(),
 			f64_t: TypeNPtr::null(),
 			iname_t: TypeNPtr::null(),
+			vec2_t: TypeNPtr::null(),
+			vec3_t: TypeNPtr::null(),
+			vec4_t: TypeNPtr::null(),
 			never_t: TypeNPtr::null(),
 		}
 	}
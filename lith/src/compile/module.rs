@@ -10,10 +10,36 @@ use cranelift_jit::{JITBuilder, JITModule};
 use cranelift_module::{DataId, FuncId, Linkage, Module};
 
 use crate::{
-	compile::{NativeFunc, RunTimeNativeFunc},
-	Compiler,
+	compile::{Config, NativeFunc, RunTimeNativeFunc},
+	runtime, Compiler,
 };
 
+/// The Cranelift settings with which [`JitModule::new`] creates its builder.
+#[must_use]
+pub(crate) fn jit_flags(cfg: &Config) -> [(&'static str, &'static str); 5] {
+	let o_lvl = match cfg.opt {
+		OptLevel::None => "none",
+		OptLevel::Speed => "speed",
+		OptLevel::SpeedAndSize => "speed_and_size",
+	};
+
+	let bool_str = |b: bool| if b { "true" } else { "false" };
+
+	[
+		("use_colocated_libcalls", "false"),
+		("preserve_frame_pointers", "true"),
+		// Hotswapping relies on redefined functions being relocatable.
+		("is_pic", bool_str(cfg.hotswap)),
+		("opt_level", o_lvl),
+		// Unless overridden, the responsibility to run the CLIF verifier falls
+		// to the semantic pass in the frontend, not the backend.
+		(
+			"enable_verifier",
+			bool_str(cfg.enable_verifier.unwrap_or(false)),
+		),
+	]
+}
+
 /// Newtype providing `Send` and `Sync` implementations around a [`JITModule`],
 /// and ensure that JIT memory gets freed at the correct time.
 #[derive(Debug)]
@@ -22,34 +48,16 @@ pub(crate) struct JitModule(MaybeUninit<JITModule>);
 impl JitModule {
 	#[must_use]
 	pub(crate) fn new(compiler: &Compiler) -> Self {
-		let o_lvl = match compiler.cfg.opt {
-			OptLevel::None => "none",
-			OptLevel::Speed => "speed",
-			OptLevel::SpeedAndSize => "speed_and_size",
-		};
-
 		let mut builder = JITBuilder::with_flags(
-			&[
-				("use_colocated_libcalls", "false"),
-				("preserve_frame_pointers", "true"),
-				(
-					"is_pic",
-					if compiler.cfg.hotswap {
-						"true"
-					} else {
-						"false"
-					},
-				),
-				("opt_level", o_lvl),
-				// The responsibility to run the CLIF verifier falls to
-				// the semantic pass in the frontend, not the backend.
-				("enable_verifier", "false"),
-			],
+			&jit_flags(&compiler.cfg),
 			cranelift_module::default_libcall_names(),
 		)
 		.expect("JIT module builder creation failed");
 
-		// TODO: runtime intrinsics need to be registered here.
+		builder.symbol(
+			runtime::SAFEPOINT_SYMBOL,
+			runtime::safepoint_slow as *const u8,
+		);
 
 		for (name, nfn) in compiler.native.functions.iter() {
 			match nfn {
@@ -69,6 +77,14 @@ impl JitModule {
 		let ptr_t = module.isa().pointer_type();
 		let mut signature = module.make_signature();
 
+		signature.params.push(AbiParam::new(ptr_t));
+
+		let _ = module
+			.declare_function(runtime::SAFEPOINT_SYMBOL, Linkage::Import, &signature)
+			.expect("declaration of the safepoint intrinsic to a JIT module failed");
+
+		module.clear_signature(&mut signature);
+
 		for (name, nfn) in compiler.native.functions.iter() {
 			let (params, returns) = match nfn {
 				NativeFunc::CompileOrRunTime(_, rt) => {
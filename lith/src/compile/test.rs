@@ -14,6 +14,8 @@ fn end_to_end() {
 	let mut compiler = Compiler::new(Config {
 		opt: OptLevel::None,
 		hotswap: false,
+		budget_checks: true,
+		enable_verifier: None,
 	});
 
 	compiler.finish_registration();
@@ -40,3 +42,228 @@ fn end_to_end() {
 
 	let _ = crate::compile::finalize(compiler, true, true);
 }
+
+#[test]
+fn jit_flags() {
+	#[must_use]
+	fn flag(cfg: &Config, name: &str) -> &'static str {
+		module::jit_flags(cfg)
+			.into_iter()
+			.find_map(|(n, v)| (n == name).then_some(v))
+			.unwrap()
+	}
+
+	let cfg = Config::default().with_opt(OptLevel::Speed);
+	assert_eq!(flag(&cfg, "opt_level"), "speed");
+	assert_eq!(flag(&cfg, "is_pic"), "false");
+	assert_eq!(flag(&cfg, "enable_verifier"), "false");
+
+	let cfg = cfg.with_hotswap(true).with_verifier(Some(true));
+	assert_eq!(flag(&cfg, "is_pic"), "true");
+	assert_eq!(flag(&cfg, "enable_verifier"), "true");
+}
+
+#[test]
+fn function_report() {
+	use cranelift::{
+		codegen::{
+			ir::{self, StackSlotData, StackSlotKind},
+			isa::CallConv,
+		},
+		prelude::*,
+	};
+	use cranelift_module::FuncId;
+
+	/// Returns its argument plus `adds`, one increment at a time.
+	#[must_use]
+	fn build(adds: usize, stack_slot: bool) -> ir::Function {
+		let mut sig = Signature::new(CallConv::SystemV);
+		sig.params.push(AbiParam::new(types::I32));
+		sig.returns.push(AbiParam::new(types::I32));
+
+		let mut func = ir::Function::with_name_signature(ir::UserFuncName::default(), sig);
+		let mut fctx = FunctionBuilderContext::new();
+		let mut builder = FunctionBuilder::new(&mut func, &mut fctx);
+
+		let entry = builder.create_block();
+		builder.append_block_params_for_function_params(entry);
+		builder.switch_to_block(entry);
+		builder.seal_block(entry);
+
+		if stack_slot {
+			let _ = builder
+				.create_sized_stack_slot(StackSlotData::new(StackSlotKind::ExplicitSlot, 16));
+		}
+
+		let mut val = builder.block_params(entry)[0];
+
+		for _ in 0..adds {
+			val = builder.ins().iadd_imm(val, 1);
+		}
+
+		builder.ins().return_(&[val]);
+		builder.finalize();
+		func
+	}
+
+	let small = FuncReport::new(FuncId::from_u32(0), "small".to_string(), &build(1, false));
+	let large = FuncReport::new(FuncId::from_u32(1), "large".to_string(), &build(32, true));
+
+	assert_eq!(small.blocks, 1);
+	assert_eq!(small.instructions, 2);
+	assert_eq!(small.stack_slots, 0);
+	assert_eq!(large.instructions, 33);
+	assert_eq!(large.stack_slots, 1);
+	assert_eq!(large.stack_bytes, 16);
+	assert_ne!(small.instructions, large.instructions);
+}
+
+#[test]
+fn const_eval() {
+	use cranelift::{
+		codegen::{
+			data_value::DataValue,
+			ir::{self, UserExternalName},
+			isa::CallConv,
+		},
+		prelude::*,
+	};
+	use cranelift_module::FuncId;
+
+	use crate::{
+		back::{self, CEvalError, FunctionIr},
+		types::{FxDashMap, IrOPtr},
+	};
+
+	/// Returns `2 + 2` plus whatever each function in `calls` returns.
+	#[must_use]
+	fn build(index: u32, calls: &[u32]) -> ir::Function {
+		let mut sig = Signature::new(CallConv::SystemV);
+		sig.returns.push(AbiParam::new(types::I32));
+
+		let mut func = ir::Function::with_name_signature(ir::UserFuncName::user(0, index), sig);
+		let mut fctx = FunctionBuilderContext::new();
+		let mut builder = FunctionBuilder::new(&mut func, &mut fctx);
+
+		let entry = builder.create_block();
+		builder.switch_to_block(entry);
+		builder.seal_block(entry);
+
+		let two = builder.ins().iconst(types::I32, 2);
+		let mut val = builder.ins().iadd(two, two);
+
+		for callee in calls {
+			let mut sig = Signature::new(CallConv::SystemV);
+			sig.returns.push(AbiParam::new(types::I32));
+			let signature = builder.import_signature(sig);
+
+			let name = builder
+				.func
+				.declare_imported_user_function(UserExternalName::new(0, *callee));
+
+			let fref = builder.import_function(ir::ExtFuncData {
+				name: ir::ExternalName::user(name),
+				signature,
+				colocated: true,
+			});
+
+			let call = builder.ins().call(fref, &[]);
+			let ret = builder.inst_results(call)[0];
+			val = builder.ins().iadd(val, ret);
+		}
+
+		builder.ins().return_(&[val]);
+		builder.finalize();
+		func
+	}
+
+	// Declared first so that it outlives `ir`, which drops its functions in place.
+	let arena = bumpalo::Bump::new();
+	let ir = FxDashMap::default();
+
+	for (index, calls) in [(0, &[][..]), (1, &[0]), (2, &[2]), (3, &[4])] {
+		ir.insert(
+			UserExternalName::new(0, index),
+			FunctionIr {
+				id: FuncId::from_u32(index),
+				ptr: IrOPtr::alloc(&arena, build(index, calls)),
+				name: format!("f{index}"),
+				_sig_hash: 0,
+			},
+		);
+	}
+
+	let four = back::const_eval_in(&ir, &UserExternalName::new(0, 0), &[]).unwrap();
+	assert_eq!(four.as_slice(), [DataValue::I32(4)]);
+
+	let eight = back::const_eval_in(&ir, &UserExternalName::new(0, 1), &[]).unwrap();
+	assert_eq!(eight.as_slice(), [DataValue::I32(8)]);
+
+	assert!(matches!(
+		back::const_eval_in(&ir, &UserExternalName::new(0, 2), &[]),
+		Err(CEvalError::Runaway)
+	));
+
+	assert!(matches!(
+		back::const_eval_in(&ir, &UserExternalName::new(0, 3), &[]),
+		Err(CEvalError::NotFound(_))
+	));
+}
+
+/// Issues must come out in the same order no matter how many threads the
+/// declaration pass gets spread across.
+#[test]
+fn issue_order() {
+	#[must_use]
+	fn declare(threads: usize) -> Vec<String> {
+		let pool = rayon::ThreadPoolBuilder::new()
+			.num_threads(threads)
+			.build()
+			.unwrap();
+
+		pool.install(|| {
+			let mut compiler = Compiler::new(Config::default());
+
+			compiler
+				.register_lib(
+					LibMeta {
+						name: "issue_order".to_string(),
+						version: Version::V0_0_0,
+						native: false,
+					},
+					|ftree| {
+						let root = ftree.add_folder(ftree.root(), "issue_order");
+
+						for name in ["a", "b", "c"] {
+							ftree.add_file(
+								root,
+								&format!("{name}.lith"),
+								&format!("function __{name}(); function {name}__();"),
+							);
+						}
+
+						Ok(root)
+					},
+				)
+				.unwrap();
+
+			compiler.finish_registration();
+			crate::compile::declare_symbols(&mut compiler);
+			assert!(compiler.failed());
+			compiler
+				.drain_issues()
+				.map(|iss| format!("{}@{:?}: {}", iss.id.path, iss.id.span, iss.message))
+				.collect()
+		})
+	}
+
+	let serial = declare(1);
+	assert_eq!(serial.len(), 6);
+
+	for (i, rendered) in serial.iter().enumerate() {
+		let file = ["a", "b", "c"][i / 2];
+		assert!(rendered.contains(&format!("/issue_order/{file}.lith@")));
+	}
+
+	assert_eq!(declare(4), serial);
+}
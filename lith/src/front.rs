@@ -11,6 +11,7 @@ pub(crate) mod lower;
 pub(crate) mod sema;
 pub(crate) mod sym;
 pub(crate) mod tsys;
+pub(crate) mod vector;
 
 use doomfront::rowan::{ast::AstNode, TextRange};
 
@@ -107,6 +107,10 @@ pub(super) fn builtin_fndecl(
 				inner: NativeFunc::RunTime(builtins::GC_USAGE),
 			};
 		}
+		"dot" => datum.kind = FunctionKind::Intrinsic(builtins::Index::Dot),
+		"cross" => datum.kind = FunctionKind::Intrinsic(builtins::Index::Cross),
+		"length" => datum.kind = FunctionKind::Intrinsic(builtins::Index::Length),
+		"normalize" => datum.kind = FunctionKind::Intrinsic(builtins::Index::Normalize),
 		other => panic!("unknown baselib builtin name: `{other}`"),
 	}
 }
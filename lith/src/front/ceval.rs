@@ -1,20 +1,26 @@
 //! **C**ompile-time **eval**uation routines.
 
-use cranelift::codegen::data_value::DataValue;
-use doomfront::rowan::ast::AstNode;
-use smallvec::smallvec;
+use cranelift::codegen::{
+	data_value::DataValue,
+	ir::immediates::{Ieee32, Ieee64},
+};
+use doomfront::rowan::{ast::AstNode, TextRange};
+use smallvec::{smallvec, SmallVec};
 
 use crate::{
-	ast,
+	ast, builtins,
 	compile::{CompileTimeNativeFunc, NativeFunc},
 	issue::{self, Issue},
-	types::Scope,
+	types::{Scope, TypePtr},
+	SyntaxToken,
 };
 
 use super::{
 	func,
 	sema::{CEval, CeValue, SemaContext},
 	sym::{self, FunctionKind, SymDatum, Symbol},
+	tsys::{self, FieldDef, Layout, LayoutError, Primitive, Structure, TypeDatum, TypeDef},
+	vector::VecConst,
 };
 
 // Expression evaluation ///////////////////////////////////////////////////////
@@ -40,17 +46,17 @@ pub(super) fn expr(ctx: &SemaContext, depth: u8, env: &Scope, ast: ast::Expr) ->
 		ast::Expr::Call(e_call) => expr_call(ctx, next_depth, env, e_call),
 		ast::Expr::Ident(e_ident) => expr_ident(ctx, env, e_ident),
 		ast::Expr::Literal(e_lit) => expr_literal(ctx, e_lit),
-		ast::Expr::Aggregate(_)
-		| ast::Expr::Binary(_)
+		ast::Expr::Aggregate(e_agg) => expr_aggregate(ctx, e_agg),
+		ast::Expr::Struct(e_struct) => expr_struct(ctx, next_depth, env, e_struct),
+		ast::Expr::Type(e_type) => expr_type(ctx, next_depth, env, e_type),
+		ast::Expr::Binary(_)
 		| ast::Expr::Block(_)
 		| ast::Expr::Construct(_)
 		| ast::Expr::Field(_)
 		| ast::Expr::Group(_)
 		| ast::Expr::Index(_)
 		| ast::Expr::Postfix(_)
-		| ast::Expr::Prefix(_)
-		| ast::Expr::Struct(_)
-		| ast::Expr::Type(_) => unimplemented!(),
+		| ast::Expr::Prefix(_) => unimplemented!(),
 	}
 }
 
@@ -123,9 +129,315 @@ fn expr_call(ctx: &SemaContext, depth: u8, env: &Scope, ast: ast::ExprCall) -> C
 		FunctionKind::Internal { inner, .. } => {
 			try_call_internal(ctx, &callable_sym, d_fn, ast, inner)
 		}
+		FunctionKind::Intrinsic(index) => try_call_intrinsic(ctx, depth, env, *index, ast),
 	}
 }
 
+/// Only vector literals (e.g. `.{1.0, 2.0, 3.0}` or `.{1, 2}`) are currently supported.
+fn expr_aggregate(ctx: &SemaContext, ast: ast::ExprAggregate) -> CEval {
+	let unsupported = |span: TextRange| {
+		ctx.raise(
+			Issue::new(
+				ctx.path,
+				span,
+				issue::Level::Error(issue::Error::Unimplemented),
+			)
+			.with_message_static("only vector literals are currently supported as aggregates")
+			.with_note_static(
+				"each element must be a floating-point or unsuffixed integer literal",
+			),
+		);
+
+		CEval::Err
+	};
+
+	let mut lanes = SmallVec::<[f32; 4]>::new();
+
+	for init in ast.initializers() {
+		let span = init.syntax().text_range();
+
+		let ast::AggregateInit::Anon(anon) = init else {
+			return unsupported(span);
+		};
+
+		let Ok(ast::Expr::Literal(e_lit)) = anon.expr() else {
+			return unsupported(span);
+		};
+
+		let token = e_lit.token();
+
+		if let Some(Ok(float)) = token.float() {
+			let (ast::FloatLit::NoSuffix(f) | ast::FloatLit::F32(f) | ast::FloatLit::F64(f)) =
+				float;
+			lanes.push(f as f32);
+		} else if let Some(Ok(ast::IntLit::NoSuffix(i))) = token.int() {
+			// Unsuffixed integers, e.g. `.{1, 2}`, coerce to floats.
+			lanes.push(i as f32);
+		} else {
+			return unsupported(span);
+		}
+	}
+
+	let ftype = match lanes.len() {
+		2 => ctx.sym_cache.vec2_t.clone(),
+		3 => ctx.sym_cache.vec3_t.clone(),
+		4 => ctx.sym_cache.vec4_t.clone(),
+		_ => {
+			ctx.raise(
+				Issue::new(
+					ctx.path,
+					ast.syntax().text_range(),
+					issue::Level::Error(issue::Error::Unimplemented),
+				)
+				.with_message_static("vector literals must have between 2 and 4 elements"),
+			);
+
+			return CEval::Err;
+		}
+	};
+
+	CEval::Value(CeValue {
+		data: smallvec![VecConst::new(&lanes).unwrap().to_data()],
+		ftype: ftype.into(),
+	})
+}
+
+fn expr_struct(ctx: &SemaContext, depth: u8, env: &Scope, ast: ast::ExprStruct) -> CEval {
+	let mut fields = vec![];
+	let mut inits = vec![];
+	let mut failed = false;
+
+	for innard in ast.innards() {
+		let field = match innard {
+			ast::StructInnard::Field(field) => field,
+			ast::StructInnard::Annotation(_) | ast::StructInnard::Item(_) => {
+				ctx.raise(
+					Issue::new(
+						ctx.path,
+						innard.syntax().text_range(),
+						issue::Level::Error(issue::Error::Unimplemented),
+					)
+					.with_message_static(
+						"only field declarations are currently supported in structure expressions",
+					),
+				);
+
+				failed = true;
+				continue;
+			}
+		};
+
+		let name = field.name();
+		let name_ix = ctx.names.intern(&name);
+
+		if let Some((_, prev, _)) = fields.iter().find(|(ix, _, _)| *ix == name_ix) {
+			let prev: &SyntaxToken = prev;
+
+			ctx.raise(
+				Issue::new(
+					ctx.path,
+					name.text_range(),
+					issue::Level::Error(issue::Error::Redeclare),
+				)
+				.with_message(format!("attempt to re-declare field `{}`", name.text()))
+				.with_label_static(ctx.path, prev.text_range(), "previous declaration is here"),
+			);
+
+			failed = true;
+			continue;
+		}
+
+		let tspec = field.type_spec();
+
+		let Some(texpr) = tspec.into_expr() else {
+			ctx.raise(
+				Issue::new(
+					ctx.path,
+					tspec.syntax().text_range(),
+					issue::Level::Error(issue::Error::Unimplemented),
+				)
+				.with_message_static("structure fields of type `type_t` are not supported"),
+			);
+
+			failed = true;
+			continue;
+		};
+
+		let texpr_span = texpr.syntax().text_range();
+
+		let ftype = match expr(ctx, depth, env, texpr) {
+			CEval::Type(t) => t,
+			CEval::Err => {
+				failed = true;
+				continue;
+			}
+			CEval::Function(_) | CEval::Value(_) => {
+				ctx.raise(
+					Issue::new(
+						ctx.path,
+						texpr_span,
+						issue::Level::Error(issue::Error::AssignTypeMismatch),
+					)
+					.with_message_static("structure field type specifiers must be types"),
+				);
+
+				failed = true;
+				continue;
+			}
+		};
+
+		if let Some(e_init) = field.initializer() {
+			let init_span = e_init.syntax().text_range();
+
+			let init = match expr(ctx, depth, env, e_init) {
+				CEval::Value(cev) => cev,
+				CEval::Err => {
+					failed = true;
+					continue;
+				}
+				CEval::Function(_) | CEval::Type(_) => {
+					ctx.raise(
+						Issue::new(
+							ctx.path,
+							init_span,
+							issue::Level::Error(issue::Error::AssignTypeMismatch),
+						)
+						.with_message_static("structure field initializers must be values"),
+					);
+
+					failed = true;
+					continue;
+				}
+			};
+
+			match coerce(&init, ftype) {
+				Ok(coerced) => inits.push((fields.len(), coerced.data)),
+				Err(err) => {
+					ctx.raise(
+						Issue::new(
+							ctx.path,
+							init_span,
+							issue::Level::Error(err.issue_code(issue::Error::AssignTypeMismatch)),
+						)
+						.with_message_static(err.message()),
+					);
+
+					failed = true;
+					continue;
+				}
+			}
+		}
+
+		fields.push((name_ix, name, ftype));
+	}
+
+	if failed {
+		return CEval::Err;
+	}
+
+	let defs = fields
+		.iter()
+		.enumerate()
+		.map(|(i, (name, _, tspec))| FieldDef {
+			name: *name,
+			tspec: *tspec,
+			init: inits
+				.iter()
+				.find_map(|(ii, data)| (*ii == i).then_some(data.as_slice())),
+		});
+
+	match Structure::new(defs) {
+		Ok(structure) => CEval::Type(ctx.intern_type(TypeDef {
+			symbol: None,
+			datum: TypeDatum::Structure(structure),
+		})),
+		Err(LayoutError::TooLarge) => {
+			type_too_large(ctx, ast.syntax().text_range());
+			CEval::Err
+		}
+	}
+}
+
+/// Only array type prefixes (e.g. `[4]i32`) are currently supported.
+fn expr_type(ctx: &SemaContext, depth: u8, env: &Scope, ast: ast::ExprType) -> CEval {
+	let mut ret = match expr(ctx, depth, env, ast.inner().unwrap()) {
+		CEval::Type(t) => t,
+		CEval::Err => return CEval::Err,
+		CEval::Function(_) | CEval::Value(_) => {
+			ctx.raise(
+				Issue::new(
+					ctx.path,
+					ast.syntax().text_range(),
+					issue::Level::Error(issue::Error::AssignTypeMismatch),
+				)
+				.with_message_static("type prefixes can only be applied to types"),
+			);
+
+			return CEval::Err;
+		}
+	};
+
+	// (RAT) `[2][3]i32` is an array of two arrays of three `i32`s,
+	// so the prefix nearest to the inner type gets applied first.
+	let prefixes = ast.prefixes().collect::<SmallVec<[_; 2]>>();
+
+	for prefix in prefixes.into_iter().rev() {
+		let ast::TypePrefix::Array(arr) = prefix;
+		let e_len = arr.length().unwrap();
+		let len_span = e_len.syntax().text_range();
+
+		let len = match expr(ctx, depth, env, e_len) {
+			CEval::Value(cev) => match &cev.ftype.datum {
+				TypeDatum::Primitive(prim) => {
+					int_value(*prim, &cev.data[0]).and_then(|int| usize::try_from(int).ok())
+				}
+				_ => None,
+			},
+			CEval::Err => return CEval::Err,
+			CEval::Function(_) | CEval::Type(_) => None,
+		};
+
+		let Some(len) = len else {
+			ctx.raise(
+				Issue::new(
+					ctx.path,
+					len_span,
+					issue::Level::Error(issue::Error::AssignTypeMismatch),
+				)
+				.with_message_static("array lengths must be non-negative integers"),
+			);
+
+			return CEval::Err;
+		};
+
+		if Layout::array(ret.layout(), len).is_err() {
+			type_too_large(ctx, ast.syntax().text_range());
+			return CEval::Err;
+		}
+
+		ret = ctx.intern_type(TypeDef {
+			symbol: None,
+			datum: TypeDatum::Array { inner: ret, len },
+		});
+	}
+
+	CEval::Type(ret)
+}
+
+fn type_too_large(ctx: &SemaContext, span: TextRange) {
+	ctx.raise(
+		Issue::new(
+			ctx.path,
+			span,
+			issue::Level::Error(issue::Error::TypeTooLarge),
+		)
+		.with_message(format!(
+			"type is larger than the maximum of {} bytes",
+			tsys::MAX_SIZE
+		)),
+	);
+}
+
 fn expr_ident(ctx: &SemaContext, env: &Scope, ast: ast::ExprIdent) -> CEval {
 	let token = ast.token();
 	let name_ix = ctx.names.intern(&token);
@@ -154,14 +466,78 @@ fn expr_literal(ctx: &SemaContext, ast: ast::ExprLit) -> CEval {
 	let token = ast.token();
 
 	if let Some(res_int) = token.int() {
-		match res_int {
-			Ok(_) => todo!(),
-			Err(_) => todo!(),
-		}
+		let Ok(lit) = res_int else {
+			ctx.raise(
+				Issue::new(
+					ctx.path,
+					token.text_range(),
+					issue::Level::Error(issue::Error::ParseInt),
+				)
+				.with_message_static("invalid integer literal"),
+			);
+
+			return CEval::Err;
+		};
+
+		let (ftype, prim) = match lit {
+			ast::IntLit::NoSuffix(_) | ast::IntLit::I32(_) => {
+				(&ctx.sym_cache.i32_t, Primitive::I32)
+			}
+			ast::IntLit::I8(_) => (&ctx.sym_cache.i8_t, Primitive::I8),
+			ast::IntLit::I16(_) => (&ctx.sym_cache.i16_t, Primitive::I16),
+			ast::IntLit::I64(_) => (&ctx.sym_cache.i64_t, Primitive::I64),
+			ast::IntLit::I128(_) => (&ctx.sym_cache.i128_t, Primitive::I128),
+			ast::IntLit::U8(_) => (&ctx.sym_cache.u8_t, Primitive::U8),
+			ast::IntLit::U16(_) => (&ctx.sym_cache.u16_t, Primitive::U16),
+			ast::IntLit::U32(_) => (&ctx.sym_cache.u32_t, Primitive::U32),
+			ast::IntLit::U64(_) => (&ctx.sym_cache.u64_t, Primitive::U64),
+			ast::IntLit::U128(_) => (&ctx.sym_cache.u128_t, Primitive::U128),
+		};
+
+		let Some(data) = i128::try_from(u128::from(lit))
+			.ok()
+			.and_then(|int| int_data(prim, int))
+		else {
+			ctx.raise(
+				Issue::new(
+					ctx.path,
+					token.text_range(),
+					issue::Level::Error(issue::Error::IntConvert),
+				)
+				.with_message_static("integer literal is out of range for its type"),
+			);
+
+			return CEval::Err;
+		};
+
+		CEval::Value(CeValue {
+			data: smallvec![data],
+			ftype: ftype.clone().into(),
+		})
 	} else if let Some(res_float) = token.float() {
-		match res_float {
-			Ok(_) => todo!(),
-			Err(_) => todo!(),
+		let Ok(lit) = res_float else {
+			ctx.raise(
+				Issue::new(
+					ctx.path,
+					token.text_range(),
+					issue::Level::Error(issue::Error::ParseFloat),
+				)
+				.with_message_static("invalid floating-point literal"),
+			);
+
+			return CEval::Err;
+		};
+
+		// (RAT) Like ZScript's `double`, an unsuffixed literal is 64 bits wide.
+		match lit {
+			ast::FloatLit::F32(f) => CEval::Value(CeValue {
+				data: smallvec![DataValue::F32(Ieee32::with_float(f as f32))],
+				ftype: ctx.sym_cache.f32_t.clone().into(),
+			}),
+			ast::FloatLit::NoSuffix(f) | ast::FloatLit::F64(f) => CEval::Value(CeValue {
+				data: smallvec![DataValue::F64(Ieee64::with_float(f))],
+				ftype: ctx.sym_cache.f64_t.clone().into(),
+			}),
 		}
 	} else if let Some(b) = token.bool() {
 		CEval::Value(CeValue {
@@ -180,6 +556,134 @@ fn expr_literal(ctx: &SemaContext, ast: ast::ExprLit) -> CEval {
 	}
 }
 
+// Coercion ////////////////////////////////////////////////////////////////////
+
+/// Why a value could not be [implicitly converted](coerce) to another type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CoerceError {
+	/// An integer did not fit in the range of the target integral type.
+	OutOfRange,
+	/// Floating-point values only become integers through an explicit cast.
+	FloatToInt,
+	/// There is no implicit conversion between these types at all.
+	Mismatch,
+}
+
+impl CoerceError {
+	#[must_use]
+	pub(crate) fn issue_code(self, mismatch: issue::Error) -> issue::Error {
+		match self {
+			Self::OutOfRange | Self::FloatToInt => issue::Error::IntConvert,
+			Self::Mismatch => mismatch,
+		}
+	}
+
+	#[must_use]
+	pub(crate) fn message(self) -> &'static str {
+		match self {
+			Self::OutOfRange => "integer value is out of range for the target type",
+			Self::FloatToInt => "floating-point values require an explicit cast to become integers",
+			Self::Mismatch => "no implicit conversion exists between these types",
+		}
+	}
+}
+
+/// Applies the implicit conversions which are allowed (as in ZScript)
+/// when assigning `value` to a container of type `target`, or passing it
+/// as an argument to a parameter of type `target`:
+/// - a value of type `target` is passed through unchanged
+/// - integers widen to either floating-point type
+/// - integers convert to any other integral type whose range holds their value
+/// - `f32` widens to `f64`
+///
+/// Anything else (e.g. `f64` to `i32`) requires an explicit cast.
+pub(crate) fn coerce(value: &CeValue, target: TypePtr) -> Result<CeValue, CoerceError> {
+	if value.ftype == target {
+		return Ok(CeValue {
+			data: value.data.clone(),
+			ftype: target,
+		});
+	}
+
+	let (TypeDatum::Primitive(from), TypeDatum::Primitive(to)) =
+		(&value.ftype.datum, &target.datum)
+	else {
+		return Err(CoerceError::Mismatch);
+	};
+
+	let (from, to) = (*from, *to);
+	let datum = &value.data[0];
+
+	let data = if from.is_integral() {
+		// `None` only if a `u128` is too large to be an `i128`,
+		// in which case it is also too large for any other type.
+		let int = int_value(from, datum).ok_or(CoerceError::OutOfRange)?;
+
+		match to {
+			Primitive::F32 => DataValue::F32(Ieee32::with_float(int as f32)),
+			Primitive::F64 => DataValue::F64(Ieee64::with_float(int as f64)),
+			_ if to.is_integral() => int_data(to, int).ok_or(CoerceError::OutOfRange)?,
+			_ => return Err(CoerceError::Mismatch),
+		}
+	} else {
+		match (from, datum, to) {
+			(Primitive::F32, DataValue::F32(float), Primitive::F64) => {
+				DataValue::F64(Ieee64::with_float(float.as_f32() as f64))
+			}
+			(Primitive::F32 | Primitive::F64, _, _) if to.is_integral() => {
+				return Err(CoerceError::FloatToInt)
+			}
+			_ => return Err(CoerceError::Mismatch),
+		}
+	};
+
+	Ok(CeValue {
+		data: smallvec![data],
+		ftype: target,
+	})
+}
+
+/// Reads the integer out of `datum`, treating its bits as signed or unsigned
+/// according to `prim`. Returns `None` if `prim` is not integral or does not
+/// match the width of `datum`, or if an unsigned value exceeds [`i128::MAX`].
+#[must_use]
+fn int_value(prim: Primitive, datum: &DataValue) -> Option<i128> {
+	match (prim, datum) {
+		(Primitive::I8, DataValue::I8(i)) => Some(*i as i128),
+		(Primitive::U8, DataValue::I8(i)) => Some(*i as u8 as i128),
+		(Primitive::I16, DataValue::I16(i)) => Some(*i as i128),
+		(Primitive::U16, DataValue::I16(i)) => Some(*i as u16 as i128),
+		(Primitive::I32, DataValue::I32(i)) => Some(*i as i128),
+		(Primitive::U32, DataValue::I32(i)) => Some(*i as u32 as i128),
+		(Primitive::I64, DataValue::I64(i)) => Some(*i as i128),
+		(Primitive::U64, DataValue::I64(i)) => Some(*i as u64 as i128),
+		(Primitive::I128, DataValue::I128(i)) => Some(*i),
+		(Primitive::U128, DataValue::I128(i)) => (*i >= 0).then_some(*i),
+		_ => None,
+	}
+}
+
+/// The inverse of [`int_value`]. Returns `None` if `int` is outside the range
+/// of `prim`, or if `prim` is not integral.
+#[must_use]
+fn int_data(prim: Primitive, int: i128) -> Option<DataValue> {
+	let ret = match prim {
+		Primitive::I8 => DataValue::I8(i8::try_from(int).ok()?),
+		Primitive::U8 => DataValue::I8(u8::try_from(int).ok()? as i8),
+		Primitive::I16 => DataValue::I16(i16::try_from(int).ok()?),
+		Primitive::U16 => DataValue::I16(u16::try_from(int).ok()? as i16),
+		Primitive::I32 => DataValue::I32(i32::try_from(int).ok()?),
+		Primitive::U32 => DataValue::I32(u32::try_from(int).ok()? as i32),
+		Primitive::I64 => DataValue::I64(i64::try_from(int).ok()?),
+		Primitive::U64 => DataValue::I64(u64::try_from(int).ok()? as i64),
+		Primitive::I128 => DataValue::I128(int),
+		Primitive::U128 => DataValue::I128(u128::try_from(int).ok()? as i128),
+		_ => return None,
+	};
+
+	Some(ret)
+}
+
 // Function evaluation /////////////////////////////////////////////////////////
 
 fn try_call_ir(
@@ -259,6 +763,106 @@ fn try_call_ir(
 	todo!()
 }
 
+fn try_call_intrinsic(
+	ctx: &SemaContext,
+	depth: u8,
+	env: &Scope,
+	index: builtins::Index,
+	e_call: ast::ExprCall,
+) -> CEval {
+	let arg_list = e_call.arg_list().unwrap();
+	let mut args = SmallVec::<[(VecConst, CeValue); 2]>::new();
+
+	for arg in arg_list.iter() {
+		let span = arg.syntax().text_range();
+
+		let CEval::Value(cev) = expr(ctx, depth, env, arg.expr().unwrap()) else {
+			return CEval::Err;
+		};
+
+		let lanes = match &cev.ftype.datum {
+			TypeDatum::Primitive(prim) => prim.lanes(),
+			_ => None,
+		};
+
+		let Some(vconst) = lanes.and_then(|l| VecConst::from_data(&cev.data[0], l)) else {
+			ctx.raise(
+				Issue::new(ctx.path, span, issue::Level::Error(issue::Error::ArgType))
+					.with_message_static("vector intrinsics only accept vector arguments"),
+			);
+
+			return CEval::Err;
+		};
+
+		args.push((vconst, cev));
+	}
+
+	let arity = match index {
+		builtins::Index::Dot | builtins::Index::Cross => 2,
+		_ => 1,
+	};
+
+	if args.len() != arity {
+		ctx.raise(
+			Issue::new(
+				ctx.path,
+				arg_list.syntax().text_range(),
+				issue::Level::Error(issue::Error::ArgCount),
+			)
+			.with_message(format!(
+				"expected {arity} argument(s), found {}",
+				args.len()
+			)),
+		);
+
+		return CEval::Err;
+	}
+
+	let scalar = |f: f32| {
+		CEval::Value(CeValue {
+			data: smallvec![DataValue::F32(Ieee32::with_float(f))],
+			ftype: ctx.sym_cache.f32_t.clone().into(),
+		})
+	};
+
+	let vector = |v: VecConst, ftype: TypePtr| {
+		CEval::Value(CeValue {
+			data: smallvec![v.to_data()],
+			ftype,
+		})
+	};
+
+	let mismatch = || {
+		ctx.raise(
+			Issue::new(
+				ctx.path,
+				arg_list.syntax().text_range(),
+				issue::Level::Error(issue::Error::ArgType),
+			)
+			.with_message_static("mismatched vector argument types"),
+		);
+
+		CEval::Err
+	};
+
+	match (index, args.as_slice()) {
+		(builtins::Index::Dot, [(l, _), (r, _)]) => {
+			if l.lanes().len() == r.lanes().len() {
+				scalar(l.dot(*r))
+			} else {
+				mismatch()
+			}
+		}
+		(builtins::Index::Cross, [(l, cev), (r, _)]) => match l.cross(*r) {
+			Some(v) => vector(v, cev.ftype),
+			None => mismatch(),
+		},
+		(builtins::Index::Length, [(v, _)]) => scalar(v.length()),
+		(builtins::Index::Normalize, [(v, cev)]) => vector(v.normalize(), cev.ftype),
+		(other, _) => unreachable!("not a vector intrinsic: {other:?}"),
+	}
+}
+
 fn try_call_internal(
 	ctx: &SemaContext,
 	sym: &Symbol,
@@ -290,3 +894,67 @@ fn try_call_internal(
 		}
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use crate::types::TypeOPtr;
+
+	use super::*;
+
+	#[must_use]
+	fn prim_t(arena: &bumpalo::Bump, prim: Primitive) -> TypeOPtr {
+		TypeOPtr::alloc(
+			arena,
+			TypeDef {
+				symbol: None,
+				datum: TypeDatum::Primitive(prim),
+			},
+		)
+	}
+
+	#[test]
+	fn coercion() {
+		let arena = bumpalo::Bump::new();
+		let i32_t = prim_t(&arena, Primitive::I32);
+		let u8_t = prim_t(&arena, Primitive::U8);
+		let f32_t = prim_t(&arena, Primitive::F32);
+		let f64_t = prim_t(&arena, Primitive::F64);
+
+		let int = |i: i32| CeValue {
+			data: smallvec![DataValue::I32(i)],
+			ftype: TypePtr::from(&i32_t),
+		};
+
+		let cev = coerce(&int(200), TypePtr::from(&u8_t)).unwrap();
+		assert_eq!(cev.data[0], DataValue::I8(200_u8 as i8));
+		assert!(u8_t == cev.ftype);
+		assert_eq!(int_value(Primitive::U8, &cev.data[0]), Some(200));
+
+		assert_eq!(
+			coerce(&int(300), TypePtr::from(&u8_t)).unwrap_err(),
+			CoerceError::OutOfRange
+		);
+		assert_eq!(
+			coerce(&int(-1), TypePtr::from(&u8_t)).unwrap_err(),
+			CoerceError::OutOfRange
+		);
+
+		let cev = coerce(&int(3), TypePtr::from(&f32_t)).unwrap();
+		assert_eq!(cev.data[0], DataValue::F32(Ieee32::with_float(3.0)));
+		let cev = coerce(&int(3), TypePtr::from(&f64_t)).unwrap();
+		assert_eq!(cev.data[0], DataValue::F64(Ieee64::with_float(3.0)));
+
+		let float = CeValue {
+			data: smallvec![DataValue::F32(Ieee32::with_float(2.5))],
+			ftype: TypePtr::from(&f32_t),
+		};
+
+		let cev = coerce(&float, TypePtr::from(&f64_t)).unwrap();
+		assert_eq!(cev.data[0], DataValue::F64(Ieee64::with_float(2.5)));
+
+		assert_eq!(
+			coerce(&float, TypePtr::from(&i32_t)).unwrap_err(),
+			CoerceError::FloatToInt
+		);
+	}
+}
@@ -29,10 +29,15 @@ pub fn declare_symbols(compiler: &mut Compiler) {
 	debug_assert!(!compiler.any_errors());
 	debug_assert!(!compiler.failed);
 
+	// Libraries get declared one after another, but all files within a library
+	// are declared in parallel. Each file's symbols go into a scope of its own,
+	// so no coordination between files is needed.
 	for (_, (lib, lib_root)) in compiler.libs.iter().enumerate() {
 		ftree_recur(compiler, lib, *lib_root);
 	}
 
+	compiler.order_issues();
+
 	if compiler.any_errors() {
 		compiler.failed = true;
 	} else {
@@ -224,11 +229,14 @@ fn declare_function(ctx: &FrontendContext, scope: &mut Scope, ast: ast::Function
 					));
 				}
 			}
-			FunctionKind::Internal { uext_name, .. } => {
-				let m = match uext_name.namespace {
-					crate::CLNS_BUILTIN => "builtin",
-					crate::CLNS_NATIVE => "native",
-					_ => unreachable!(),
+			FunctionKind::Internal { .. } | FunctionKind::Intrinsic(_) => {
+				let m = match &datum.kind {
+					FunctionKind::Internal { uext_name, .. } => match uext_name.namespace {
+						crate::CLNS_BUILTIN => "builtin",
+						crate::CLNS_NATIVE => "native",
+						_ => unreachable!(),
+					},
+					_ => "builtin",
 				};
 
 				if let Some(body) = ast.body() {
@@ -7,9 +7,9 @@ use std::{
 
 use cranelift::{
 	codegen::ir::{self, UserExternalName},
-	prelude::{AbiParam, FunctionBuilder},
+	prelude::{AbiParam, FunctionBuilder, InstBuilder, IntCC, MemFlags},
 };
-use cranelift_module::Module;
+use cranelift_module::{FuncOrDataId, Module};
 use crossbeam::utils::Backoff;
 use doomfront::rowan::ast::AstNode;
 use petgraph::prelude::DiGraph;
@@ -20,6 +20,7 @@ use crate::{
 	back::FunctionIr,
 	front::{lower, sym::FunctionKind},
 	issue::{self, Issue},
+	runtime,
 	types::{IrOPtr, IrPtr, Scope, TypePtr},
 	SyntaxNode,
 };
@@ -143,11 +144,16 @@ pub(super) fn monomorphize(
 		for param in &datum.params {
 			let ast_param = ast_params.next().unwrap();
 
-			let cev = match (args.next(), ast_param.default()) {
+			let (cev, arg_span) = match (args.next(), ast_param.default()) {
 				(Some(arg), None) | (Some(arg), Some(_)) => {
-					ceval::expr(ctx, 0, env, arg.expr().unwrap())
+					let e_arg = arg.expr().unwrap();
+					let span = e_arg.syntax().text_range();
+					(ceval::expr(ctx, 0, env, e_arg), span)
 				}
-				(None, Some(e_default)) => ceval::expr(ctx, 0, env, e_default),
+				(None, Some(e_default)) => (
+					ceval::expr(ctx, 0, env, e_default),
+					e_call.syntax().text_range(),
+				),
 				(None, None) => {
 					// TODO: raise an error.
 					continue;
@@ -168,10 +174,25 @@ pub(super) fn monomorphize(
 					ParamType::Type => todo!(),
 					ParamType::Normal(_) => todo!(),
 				},
-				CEval::Value(_) => match &param.ptype {
+				CEval::Value(cev) => match &param.ptype {
 					ParamType::Any => todo!(),
 					ParamType::Type => todo!(),
-					ParamType::Normal(_) => todo!(),
+					ParamType::Normal(tspec) => {
+						if tspec.as_ptr().is_none() {
+							continue;
+						}
+
+						if let Err(err) = ceval::coerce(&cev, tspec.clone().into()) {
+							ctx.raise(
+								Issue::new(
+									ctx.path,
+									arg_span,
+									issue::Level::Error(err.issue_code(issue::Error::ArgType)),
+								)
+								.with_message_static(err.message()),
+							);
+						}
+					}
 				},
 			}
 		}
@@ -198,10 +219,10 @@ fn define(
 	signature.params.push(AbiParam::new(ctx.ptr_t));
 
 	for mono_param in mono_sig.params {
-		get_abi_params(&mut signature.params, &mono_param);
+		get_abi_params(&mut signature.params, &mono_param, ctx.simd);
 	}
 
-	get_abi_params(&mut signature.returns, &mono_sig.ret_t);
+	get_abi_params(&mut signature.returns, &mono_sig.ret_t, ctx.simd);
 
 	let mut tlat = Translator {
 		ctx,
@@ -209,9 +230,13 @@ fn define(
 		builder: FunctionBuilder::new(&mut cctx.func, &mut fctx),
 		_cflow: DiGraph::default(),
 		_next_var: 0,
+		defers: DeferStack::default(),
 	};
 
 	let body = ast.body().unwrap();
+	let name = ast
+		.name()
+		.map_or(String::new(), |token| token.text().to_string());
 
 	let uextname = UserExternalName {
 		namespace: ctx.file_ix.index() as u32,
@@ -235,6 +260,10 @@ fn define(
 	tlat.builder.switch_to_block(blk_entry);
 	tlat.builder.seal_block(blk_entry);
 
+	if ctx.cfg.budget_checks {
+		tlat.safepoint();
+	}
+
 	let mut body_scope = outer_scope.clone();
 
 	for innard in body.innards() {
@@ -274,6 +303,10 @@ fn define(
 		}
 	}
 
+	// Falling off the end of the body is an exit edge like any other.
+	tlat.emit_defers(0);
+	tlat.defers.pop_to(0);
+
 	if tlat.failed {
 		// TODO: inform other threads that definition failed.
 		return Err(());
@@ -323,6 +356,7 @@ fn define(
 		FunctionIr {
 			id: fn_id,
 			ptr: ir_ptr,
+			name,
 			_sig_hash: sig_hash,
 		},
 	);
@@ -332,21 +366,17 @@ fn define(
 
 // Miscellaneous details ///////////////////////////////////////////////////////
 
-fn get_abi_params(p: &mut Vec<AbiParam>, tdef: &TypeDef) {
+fn get_abi_params(p: &mut Vec<AbiParam>, tdef: &TypeDef, simd: bool) {
 	match &tdef.datum {
-		TypeDatum::_Array { inner, len } => {
+		TypeDatum::Array { inner, len } => {
 			for _ in 0..*len {
-				get_abi_params(p, inner);
+				get_abi_params(p, inner, simd);
 			}
 		}
-		TypeDatum::Primitive(prim) => {
-			if let Some(abi_t) = prim.abi() {
-				p.push(AbiParam::new(abi_t));
-			}
-		}
-		TypeDatum::_Structure(structure) => {
+		TypeDatum::Primitive(prim) => prim.abi_params(simd, p),
+		TypeDatum::Structure(structure) => {
 			for field in &structure.fields {
-				get_abi_params(p, field.tspec.as_ref());
+				get_abi_params(p, &field.tspec, simd);
 			}
 		}
 	}
@@ -363,6 +393,113 @@ pub(super) struct Translator<'c> {
 	pub(super) builder: FunctionBuilder<'c>,
 	pub(super) _cflow: DiGraph<FlowBlock, Flow>,
 	pub(super) _next_var: u32,
+	/// Each deferred statement is paired with the scope it was declared in.
+	pub(super) defers: DeferStack<(ast::StmtDefer, Scope)>,
+}
+
+impl Translator<'_> {
+	/// Lowers, newest first, the body of every deferred statement which is
+	/// declared past `depth` (see [`DeferStack::depth`]) at the current position.
+	///
+	/// Every exit edge must call this before it branches: the end of a block
+	/// with the depth at which that block started, and `return` with zero.
+	/// Once loops get lowered, `break` and `continue` must use the depth at
+	/// which the loop's body started, so that a loop body's deferred statements
+	/// run on every iteration.
+	///
+	/// Deferred bodies are emitted anew at each exit rather than shared,
+	/// so no exceptional control flow is involved, and locals they use are read
+	/// at the point of exit rather than where `defer` was declared.
+	pub(super) fn emit_defers(&mut self, depth: usize) {
+		let pending = self.defers.unwinding(depth).cloned().collect::<Vec<_>>();
+
+		for (s_defer, scope) in pending {
+			lower::defer_body(self, scope, s_defer);
+		}
+	}
+
+	/// Emits a check against the [runtime's per-tic budget](runtime::Runtime::begin_tic)
+	/// at the current position. Gets emitted at function entry, and must also be
+	/// emitted at every loop back-edge once loops get lowered.
+	///
+	/// The fast path is a decrement of a counter in the [`runtime::Runtime`];
+	/// the intrinsic which samples the clock only gets called once it runs out.
+	pub(super) fn safepoint(&mut self) {
+		debug_assert!(self.ctx.cfg.budget_checks);
+
+		let blk_entry = self.builder.func.layout.entry_block().unwrap();
+		let rt = self.builder.block_params(blk_entry)[0];
+		let flags = MemFlags::trusted();
+		let offs = runtime::COUNTDOWN_OFFSET;
+
+		let countdown = self.builder.ins().load(ir::types::I32, flags, rt, offs);
+		let countdown = self.builder.ins().iadd_imm(countdown, -1);
+		self.builder.ins().store(flags, countdown, rt, offs);
+		let expired = self
+			.builder
+			.ins()
+			.icmp_imm(IntCC::SignedLessThanOrEqual, countdown, 0);
+
+		let blk_slow = self.builder.create_block();
+		let blk_cont = self.builder.create_block();
+		self.builder.set_cold_block(blk_slow);
+		self.builder
+			.ins()
+			.brif(expired, blk_slow, &[], blk_cont, &[]);
+
+		self.builder.switch_to_block(blk_slow);
+		self.builder.seal_block(blk_slow);
+
+		let intrinsic = {
+			let mut guard = self.ctx.module.lock();
+
+			let Some(FuncOrDataId::Func(id)) = guard.get_name(runtime::SAFEPOINT_SYMBOL) else {
+				unreachable!("safepoint intrinsic was not declared")
+			};
+
+			guard.declare_func_in_func(id, self.builder.func)
+		};
+
+		self.builder.ins().call(intrinsic, &[rt]);
+		self.builder.ins().jump(blk_cont, &[]);
+
+		self.builder.switch_to_block(blk_cont);
+		self.builder.seal_block(blk_cont);
+	}
+}
+
+/// Deferred statements still in scope at the current point of lowering,
+/// in order of declaration. See [`Translator::emit_defers`].
+#[derive(Debug)]
+pub(super) struct DeferStack<T>(Vec<T>);
+
+impl<T> DeferStack<T> {
+	pub(super) fn push(&mut self, defer: T) {
+		self.0.push(defer);
+	}
+
+	/// To be taken upon entering a block, and later passed to
+	/// [`Self::unwinding`] or [`Self::pop_to`].
+	#[must_use]
+	pub(super) fn depth(&self) -> usize {
+		self.0.len()
+	}
+
+	/// Yields everything pushed past `depth`, in the order it must run.
+	pub(super) fn unwinding(&self, depth: usize) -> impl Iterator<Item = &T> {
+		self.0[depth..].iter().rev()
+	}
+
+	/// To be called upon leaving a block with the depth taken upon entering it.
+	pub(super) fn pop_to(&mut self, depth: usize) {
+		self.0.truncate(depth);
+	}
+}
+
+impl<T> Default for DeferStack<T> {
+	fn default() -> Self {
+		Self(vec![])
+	}
 }
 
 #[derive(Debug)]
@@ -382,3 +519,48 @@ pub(super) enum Flow {
 	_Break { to: BlockIx, break_t: TypePtr },
 	_Return { ret_t: TypePtr },
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn defer_order() {
+		let mut defers = DeferStack::default();
+		let mut emitted = vec![];
+
+		// function f() { defer a; defer b; { defer c; if x { return; } } return; }
+		defers.push("a");
+		defers.push("b");
+		let block = defers.depth();
+		defers.push("c");
+
+		// The early `return` unwinds everything, without popping anything.
+		emitted.extend(defers.unwinding(0).copied());
+		assert_eq!(emitted, ["c", "b", "a"]);
+		emitted.clear();
+
+		// Falling out of the inner block only runs what it declared.
+		emitted.extend(defers.unwinding(block).copied());
+		defers.pop_to(block);
+		assert_eq!(emitted, ["c"]);
+		emitted.clear();
+
+		emitted.extend(defers.unwinding(0).copied());
+		defers.pop_to(0);
+		assert_eq!(emitted, ["b", "a"]);
+		assert_eq!(defers.depth(), 0);
+		emitted.clear();
+
+		// A loop body's block gets entered and left once per iteration,
+		// so its deferred statements run every time.
+		for _ in 0..3 {
+			let body = defers.depth();
+			defers.push("d");
+			emitted.extend(defers.unwinding(body).copied());
+			defers.pop_to(body);
+		}
+
+		assert_eq!(emitted, ["d", "d", "d"]);
+	}
+}
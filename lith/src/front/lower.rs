@@ -13,7 +13,7 @@ use crate::{
 use super::{
 	ceval,
 	func::Translator,
-	sema::{CEval, SemaContext},
+	sema::{self, CEval, SemaContext},
 	sym::{LocalVar, Location, SymDatum, Symbol},
 };
 
@@ -39,6 +39,9 @@ pub(super) fn statement(tlat: &mut Translator, scope: &mut Scope, ast: ast::Stat
 		ast::Statement::Bind(s_bind) => {
 			lower_stmt_bind(tlat, scope, s_bind);
 		}
+		ast::Statement::Defer(s_defer) => {
+			lower_stmt_defer(tlat, scope, s_defer);
+		}
 		ast::Statement::Break(_)
 		| ast::Statement::Expr(_)
 		| ast::Statement::Continue(_)
@@ -46,6 +49,97 @@ pub(super) fn statement(tlat: &mut Translator, scope: &mut Scope, ast: ast::Stat
 	}
 }
 
+fn lower_stmt_defer(tlat: &mut Translator, scope: &Scope, ast: ast::StmtDefer) {
+	let escapes = sema::defer_escapes(&ast);
+
+	for escape in &escapes {
+		tlat.ctx.raise(
+			Issue::new(
+				tlat.ctx.path,
+				escape.syntax().text_range(),
+				issue::Level::Error(issue::Error::IllegalDeferExit),
+			)
+			.with_message_static("control flow cannot leave the body of a deferred statement")
+			.with_label_static(
+				tlat.ctx.path,
+				ast.keyword().text_range(),
+				"deferred statement begins here",
+			),
+		);
+	}
+
+	if !escapes.is_empty() {
+		tlat.failed = true;
+		return;
+	}
+
+	let Ok(body) = ast.body() else {
+		return;
+	};
+
+	let e_block = match body {
+		ast::Expr::Block(e_block) => e_block,
+		other => {
+			tlat.ctx.raise(
+				Issue::new(
+					tlat.ctx.path,
+					other.syntax().text_range(),
+					issue::Level::Error(issue::Error::Unimplemented),
+				)
+				.with_message_static(
+					"only blocks are currently supported as deferred statement bodies",
+				),
+			);
+
+			tlat.failed = true;
+			return;
+		}
+	};
+
+	if let Some(innard) = e_block
+		.innards()
+		.find(|innard| !matches!(innard, ast::CoreElement::Statement(_)))
+	{
+		tlat.ctx.raise(
+			Issue::new(
+				tlat.ctx.path,
+				innard.syntax().text_range(),
+				issue::Level::Error(issue::Error::Unimplemented),
+			)
+			.with_message_static(
+				"items and annotations in deferred statement bodies are not yet supported",
+			),
+		);
+
+		tlat.failed = true;
+		return;
+	}
+
+	tlat.defers.push((ast, scope.clone()));
+}
+
+/// Lowers the body of `ast` at the current position. Only called by
+/// [`Translator::emit_defers`], once per exit edge; the body was already
+/// checked when the deferred statement itself was lowered.
+pub(super) fn defer_body(tlat: &mut Translator, mut scope: Scope, ast: ast::StmtDefer) {
+	let Ok(ast::Expr::Block(e_block)) = ast.body() else {
+		unreachable!()
+	};
+
+	let depth = tlat.defers.depth();
+
+	for innard in e_block.innards() {
+		let ast::CoreElement::Statement(stmt) = innard else {
+			unreachable!()
+		};
+
+		statement(tlat, &mut scope, stmt);
+	}
+
+	tlat.emit_defers(depth);
+	tlat.defers.pop_to(depth);
+}
+
 fn lower_stmt_bind(tlat: &mut Translator, scope: &mut Scope, ast: ast::StmtBind) {
 	let pat = ast.pattern().unwrap();
 
@@ -7,7 +7,7 @@ use cranelift::{
 	prelude::{FunctionBuilderContext, Signature},
 };
 use cranelift_module::Module;
-use doomfront::rowan::{ast::AstNode, TextSize};
+use doomfront::rowan::{ast::AstNode, TextSize, WalkEvent};
 use parking_lot::Mutex;
 
 use crate::{
@@ -16,7 +16,7 @@ use crate::{
 	filetree::{self, FileIx},
 	issue::{self, Issue},
 	types::{Scope, SymPtr, TypeOPtr, TypePtr},
-	Compiler, ParseTree, ValVec,
+	Compiler, ParseTree, Syntax, ValVec,
 };
 
 use super::{
@@ -49,6 +49,7 @@ pub fn semantic_check(compiler: &mut Compiler) {
 	}
 
 	let ptr_t = module.isa().pointer_type();
+	let simd = super::vector::simd_supported(module.isa());
 	let module = Mutex::new(module);
 
 	// First, define and cache primitive types.
@@ -80,6 +81,7 @@ pub fn semantic_check(compiler: &mut Compiler) {
 				module: &module,
 				lctxs: &lctxs,
 				ptr_t,
+				simd,
 			},
 			file_ix: file_prim,
 			path: path.as_str(),
@@ -91,6 +93,13 @@ pub fn semantic_check(compiler: &mut Compiler) {
 
 	// Finally, start initializing container values and defining crucial functions.
 
+	// (RAT) Unlike declaration, this pass is not yet parallel. Primitive types
+	// must be defined and cached before anything else can be checked, and
+	// checking any other container is not yet implemented. Once it is, the
+	// per-thread arenas and `lctxs` already allow checking files in parallel.
+
+	compiler.order_issues();
+
 	if compiler.any_errors() {
 		compiler.failed = true;
 	} else {
@@ -250,12 +259,25 @@ fn check_symconst(ctx: &SemaContext, ast: ast::SymConst, sym_id: SymbolId) {
 					unreachable!()
 				};
 
-				if *tspec != cevalue.ftype {
-					// TODO: raise an error.
-					return;
-				}
+				let coerced = match ceval::coerce(&cevalue, tspec.clone().into()) {
+					Ok(c) => c,
+					Err(err) => {
+						ctx.raise(
+							Issue::new(
+								ctx.path,
+								init_span,
+								issue::Level::Error(
+									err.issue_code(issue::Error::AssignTypeMismatch),
+								),
+							)
+							.with_message_static(err.message()),
+						);
+
+						return;
+					}
+				};
 
-				for val in cevalue.data {
+				for val in coerced.data {
 					let _ = init_val.push(val);
 				}
 			}
@@ -318,6 +340,9 @@ pub struct ThreadContext<'c> {
 	pub(crate) module: &'c Mutex<JitModule>,
 	pub(crate) lctxs: &'c Vec<Mutex<LowerContext>>,
 	pub(crate) ptr_t: cranelift::codegen::ir::Type,
+	/// Whether vector types get lowered to Cranelift's SIMD types.
+	/// See [`super::vector::simd_supported`].
+	pub(crate) simd: bool,
 }
 
 pub(crate) struct LowerContext {
@@ -385,6 +410,65 @@ impl<'c> SemaContext<'c> {
 	}
 }
 
+/// Returns every `return`, `break`, and `continue` statement in the body of
+/// `defer` which would leave that body. Function declarations nested in the
+/// body are not searched, since their control flow is their own.
+///
+/// Only labeled blocks can currently be targeted from inside a deferred body;
+/// anything unlabeled targets an enclosing loop or function, both of which
+/// are outside of it.
+#[must_use]
+pub(super) fn defer_escapes(defer: &ast::StmtDefer) -> Vec<ast::Statement> {
+	let mut ret = vec![];
+	let mut preorder = defer.syntax().preorder();
+
+	while let Some(event) = preorder.next() {
+		let WalkEvent::Enter(node) = event else {
+			continue;
+		};
+
+		if node.kind() == Syntax::FunctionDecl {
+			preorder.skip_subtree();
+			continue;
+		}
+
+		let Some(stmt) = ast::Statement::cast(node) else {
+			continue;
+		};
+
+		let label = match &stmt {
+			ast::Statement::Return(_) => {
+				ret.push(stmt);
+				continue;
+			}
+			ast::Statement::Break(s_break) => s_break.block_label(),
+			ast::Statement::Continue(s_continue) => s_continue.label(),
+			ast::Statement::Bind(_) | ast::Statement::Defer(_) | ast::Statement::Expr(_) => {
+				continue;
+			}
+		};
+
+		let Some(target) = label.and_then(|l| l.ident().ok()) else {
+			ret.push(stmt);
+			continue;
+		};
+
+		let target_inside = stmt
+			.syntax()
+			.ancestors()
+			.take_while(|node| node != defer.syntax())
+			.filter_map(ast::ExprBlock::cast)
+			.filter_map(|e_block| e_block.label())
+			.any(|l| l.ident().is_ok_and(|ident| ident.text() == target.text()));
+
+		if !target_inside {
+			ret.push(stmt);
+		}
+	}
+
+	ret
+}
+
 #[cfg(test)]
 #[test]
 fn srcloc_roundtrip() {
@@ -400,3 +484,60 @@ fn srcloc_roundtrip() {
 
 	assert_eq!((file_ix, offs), (700 + 784, 731_933));
 }
+
+#[cfg(test)]
+#[test]
+fn illegal_defer_exits() {
+	use doomfront::testing::assert_no_errors;
+
+	const SOURCE: &str = r#"
+function lorem() {
+	defer { return; }
+	defer { let ipsum: i32 = 0; }
+	defer {
+		break;
+		continue ::dolor::;
+	}
+	defer {
+		function sit_amet() {
+			return;
+		}
+	}
+	defer { defer { return 0; } }
+}
+"#;
+
+	let ptree: ParseTree =
+		doomfront::parse(SOURCE, crate::parse::file, crate::LexContext::default());
+	assert_no_errors(&ptree);
+
+	let defers = ptree
+		.cursor()
+		.children()
+		.flat_map(|node| node.descendants())
+		.filter_map(ast::StmtDefer::cast)
+		.filter(|defer| {
+			defer
+				.syntax()
+				.parent()
+				.is_some_and(|p| p.kind() == Syntax::FunctionBody)
+		})
+		.collect::<Vec<_>>();
+
+	assert_eq!(defers.len(), 5);
+
+	let escapes = defer_escapes(&defers[0]);
+	assert_eq!(escapes.len(), 1);
+	assert!(matches!(escapes[0], ast::Statement::Return(_)));
+	assert!(defer_escapes(&defers[1]).is_empty());
+
+	let escapes = defer_escapes(&defers[2]);
+	assert_eq!(escapes.len(), 2);
+	assert!(matches!(escapes[0], ast::Statement::Break(_)));
+	assert!(matches!(escapes[1], ast::Statement::Continue(_)));
+
+	// A nested function's `return` is its own business...
+	assert!(defer_escapes(&defers[3]).is_empty());
+	// ...but a nested deferred statement's is not.
+	assert_eq!(defer_escapes(&defers[4]).len(), 1);
+}
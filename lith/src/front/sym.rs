@@ -10,6 +10,7 @@ use smallvec::SmallVec;
 use util::pushvec::PushVec;
 
 use crate::{
+	builtins,
 	compile::{intern::NameIx, NativeFunc},
 	filetree::FileIx,
 	types::{Scope, TypeNPtr, TypePtr},
@@ -165,6 +166,9 @@ pub(crate) enum FunctionKind {
 		uext_name: UserExternalName,
 		inner: NativeFunc,
 	},
+	/// Function is a compiler builtin which never gets called, since it gets
+	/// lowered directly to instructions at each call site instead.
+	Intrinsic(builtins::Index),
 }
 
 unsafe impl Send for FunctionKind {}
@@ -1,14 +1,22 @@
 //! Data structures for representing Lithica types in the frontend.
 
-use cranelift::codegen::ir::types as abi_t;
+use cranelift::{
+	codegen::{data_value::DataValue, ir::types as abi_t},
+	prelude::AbiParam,
+};
 
 use crate::{
 	compile::intern::NameIx,
-	types::{AbiType, SymPtr, TypeNPtr, TypePtr},
+	types::{AbiType, SymPtr, TypePtr},
 };
 
 use super::sym::Visibility;
 
+/// No type may be larger than this many bytes. Every value of a type must fit
+/// in one Cranelift stack slot, and copying one should never be costly enough
+/// to stall a tic.
+pub(crate) const MAX_SIZE: usize = 1024 * 64;
+
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct TypeDef {
 	pub(crate) symbol: Option<SymPtr>,
@@ -17,9 +25,75 @@ pub struct TypeDef {
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub(crate) enum TypeDatum {
-	_Array { inner: TypePtr, len: usize },
+	Array { inner: TypePtr, len: usize },
 	Primitive(Primitive),
-	_Structure(Structure),
+	Structure(Structure),
+}
+
+impl TypeDef {
+	#[must_use]
+	pub(crate) fn layout(&self) -> Layout {
+		match &self.datum {
+			TypeDatum::Array { inner, len } => {
+				let inner = inner.layout();
+
+				Layout {
+					size: inner.size * len,
+					align: inner.align,
+				}
+			}
+			TypeDatum::Primitive(prim) => prim.layout(),
+			TypeDatum::Structure(structure) => structure.layout,
+		}
+	}
+
+	/// Writes the bytes which a new value of this type starts with.
+	/// `out` must be exactly as long as this type's [size](Self::layout).
+	pub(crate) fn write_default(&self, out: &mut [u8]) {
+		debug_assert_eq!(out.len(), self.layout().size);
+
+		match &self.datum {
+			TypeDatum::Array { inner, .. } => {
+				let stride = inner.layout().size;
+
+				if stride == 0 {
+					return;
+				}
+
+				for elem in out.chunks_exact_mut(stride) {
+					inner.write_default(elem);
+				}
+			}
+			TypeDatum::Primitive(_) => out.fill(0),
+			TypeDatum::Structure(structure) => out.copy_from_slice(&structure.default),
+		}
+	}
+}
+
+/// The size and alignment of a type, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Layout {
+	pub(crate) size: usize,
+	pub(crate) align: usize,
+}
+
+impl Layout {
+	/// Fails if the array would be larger than [`MAX_SIZE`].
+	pub(crate) fn array(inner: Self, len: usize) -> Result<Self, LayoutError> {
+		match inner.size.checked_mul(len) {
+			Some(size) if size <= MAX_SIZE => Ok(Self {
+				size,
+				align: inner.align,
+			}),
+			_ => Err(LayoutError::TooLarge),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LayoutError {
+	/// The type would be larger than [`MAX_SIZE`].
+	TooLarge,
 }
 
 // Primitive ///////////////////////////////////////////////////////////////////
@@ -42,6 +116,12 @@ pub(crate) enum Primitive {
 	F32,
 	F64,
 	IName,
+	/// Two [`Self::F32`] lanes. See [`crate::front::vector`].
+	Vec2,
+	/// Three [`Self::F32`] lanes. See [`crate::front::vector`].
+	Vec3,
+	/// Four [`Self::F32`] lanes. See [`crate::front::vector`].
+	Vec4,
 }
 
 impl Primitive {
@@ -56,20 +136,312 @@ impl Primitive {
 			Self::I128 | Self::U128 => Some(abi_t::I128),
 			Self::F32 => Some(abi_t::F32),
 			Self::F64 => Some(abi_t::F64),
+			// (RAT) Cranelift's SIMD support is limited to 128-bit vectors,
+			// so the smaller vector types get padded out to four lanes.
+			Self::Vec2 | Self::Vec3 | Self::Vec4 => Some(abi_t::F32X4),
+		}
+	}
+
+	#[must_use]
+	pub(crate) fn layout(self) -> Layout {
+		let size = match self {
+			Self::Never | Self::Void => 0,
+			Self::Bool | Self::I8 | Self::U8 => 1,
+			Self::I16 | Self::U16 => 2,
+			Self::I32 | Self::U32 | Self::F32 | Self::IName => 4,
+			Self::I64 | Self::U64 | Self::F64 => 8,
+			// Vectors take up four lanes for the same reason as in `Self::abi`.
+			Self::I128 | Self::U128 | Self::Vec2 | Self::Vec3 | Self::Vec4 => 16,
+		};
+
+		Layout {
+			size,
+			align: size.max(1),
+		}
+	}
+
+	#[must_use]
+	pub(crate) fn is_integral(self) -> bool {
+		matches!(
+			self,
+			Self::I8
+				| Self::I16 | Self::I32
+				| Self::I64 | Self::I128
+				| Self::U8 | Self::U16
+				| Self::U32 | Self::U64
+				| Self::U128
+		)
+	}
+
+	/// The number of meaningful lanes in a vector type, or `None` for a scalar.
+	#[must_use]
+	pub(crate) fn lanes(self) -> Option<u8> {
+		match self {
+			Self::Vec2 => Some(2),
+			Self::Vec3 => Some(3),
+			Self::Vec4 => Some(4),
+			_ => None,
+		}
+	}
+
+	/// Like [`Self::abi`], but if `simd` is `false`, vector types get
+	/// passed as one [`Self::F32`] per meaningful lane.
+	pub(crate) fn abi_params(self, simd: bool, out: &mut Vec<AbiParam>) {
+		match (self.lanes(), simd) {
+			(Some(lanes), false) => {
+				for _ in 0..lanes {
+					out.push(AbiParam::new(abi_t::F32));
+				}
+			}
+			_ => {
+				if let Some(abi_t) = self.abi() {
+					out.push(AbiParam::new(abi_t));
+				}
+			}
 		}
 	}
 }
 
 // Structure ///////////////////////////////////////////////////////////////////
 
+/// Fields are laid out in declaration order, each at the next offset aligned
+/// for its type (as in C), and the whole is padded out to its alignment.
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub(crate) struct Structure {
 	pub(crate) fields: Vec<Field>,
+	pub(crate) layout: Layout,
+	/// The bytes which a new value of this type starts with. Zeroed except
+	/// for fields with initializers, and any defaults of nested structures.
+	pub(crate) default: Box<[u8]>,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub(crate) struct Field {
 	pub(crate) name: NameIx,
-	pub(crate) tspec: TypeNPtr,
+	pub(crate) tspec: TypePtr,
+	/// In bytes, from the start of the structure.
+	pub(crate) offset: usize,
 	pub(crate) _visibility: Visibility,
 }
+
+/// One field's worth of input to [`Structure::new`].
+#[derive(Debug)]
+pub(crate) struct FieldDef<'i> {
+	pub(crate) name: NameIx,
+	pub(crate) tspec: TypePtr,
+	/// A compile-time evaluated initializer, already coerced to `tspec`.
+	pub(crate) init: Option<&'i [DataValue]>,
+}
+
+impl Structure {
+	/// Fails if the structure would be larger than [`MAX_SIZE`].
+	pub(crate) fn new<'i>(
+		defs: impl IntoIterator<Item = FieldDef<'i>>,
+	) -> Result<Self, LayoutError> {
+		let mut fields = vec![];
+		let mut inits = vec![];
+		let mut size = 0_usize;
+		let mut align = 1;
+
+		for def in defs {
+			let f_layout = def.tspec.layout();
+			let offset = size.next_multiple_of(f_layout.align);
+
+			size = offset
+				.checked_add(f_layout.size)
+				.filter(|s| *s <= MAX_SIZE)
+				.ok_or(LayoutError::TooLarge)?;
+
+			align = align.max(f_layout.align);
+
+			if let Some(init) = def.init {
+				inits.push((fields.len(), init));
+			}
+
+			fields.push(Field {
+				name: def.name,
+				tspec: def.tspec,
+				offset,
+				_visibility: Visibility::default(),
+			});
+		}
+
+		let size = size.next_multiple_of(align);
+
+		if size > MAX_SIZE {
+			return Err(LayoutError::TooLarge);
+		}
+
+		let mut default = vec![0; size].into_boxed_slice();
+
+		for field in &fields {
+			let end = field.offset + field.tspec.layout().size;
+			field.tspec.write_default(&mut default[field.offset..end]);
+		}
+
+		for (i, init) in inits {
+			let field = &fields[i];
+			let mut offset = field.offset;
+
+			for datum in init {
+				let end = offset + datum.ty().bytes() as usize;
+				datum.write_to_slice_le(&mut default[offset..end]);
+				offset = end;
+			}
+		}
+
+		Ok(Self {
+			fields,
+			layout: Layout { size, align },
+			default,
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use doomfront::rowan::{GreenNode, GreenToken};
+
+	use crate::{compile::intern::NameInterner, types::TypeOPtr, Syntax, SyntaxNode};
+
+	use super::*;
+
+	#[must_use]
+	fn alloc_t(arena: &bumpalo::Bump, datum: TypeDatum) -> TypePtr {
+		TypePtr::from(&TypeOPtr::alloc(
+			arena,
+			TypeDef {
+				symbol: None,
+				datum,
+			},
+		))
+	}
+
+	#[must_use]
+	fn names(interner: &NameInterner, count: usize) -> Vec<NameIx> {
+		let tokens = (0..count).map(|i| GreenToken::new(Syntax::Ident.into(), &format!("f{i}")));
+		let node = GreenNode::new(Syntax::FileRoot.into(), tokens.map(Into::into));
+
+		SyntaxNode::new_root(node)
+			.children_with_tokens()
+			.map(|elem| interner.intern(&elem.into_token().unwrap()))
+			.collect()
+	}
+
+	#[test]
+	fn struct_layout() {
+		let arena = bumpalo::Bump::new();
+		let interner = NameInterner::default();
+		let n = names(&interner, 3);
+
+		let bool_t = alloc_t(&arena, TypeDatum::Primitive(Primitive::Bool));
+		let u8_t = alloc_t(&arena, TypeDatum::Primitive(Primitive::U8));
+		let i16_t = alloc_t(&arena, TypeDatum::Primitive(Primitive::I16));
+		let i32_t = alloc_t(&arena, TypeDatum::Primitive(Primitive::I32));
+
+		// `struct { f0: u8; f1: i32 = 7; }`
+		let init = [DataValue::I32(7)];
+
+		let inner = Structure::new([
+			FieldDef {
+				name: n[0],
+				tspec: u8_t,
+				init: None,
+			},
+			FieldDef {
+				name: n[1],
+				tspec: i32_t,
+				init: Some(&init),
+			},
+		])
+		.unwrap();
+
+		assert_eq!(inner.fields[1].offset, 4);
+		assert_eq!(inner.layout, Layout { size: 8, align: 4 });
+		assert_eq!(&inner.default[..], [0, 0, 0, 0, 7, 0, 0, 0]);
+
+		let inner_t = alloc_t(&arena, TypeDatum::Structure(inner));
+		let arr_t = alloc_t(
+			&arena,
+			TypeDatum::Array {
+				inner: i16_t,
+				len: 3,
+			},
+		);
+
+		// `struct { f0: bool; f1: <the above>; f2: [3]i16; }`
+		let outer = Structure::new([
+			FieldDef {
+				name: n[0],
+				tspec: bool_t,
+				init: None,
+			},
+			FieldDef {
+				name: n[1],
+				tspec: inner_t,
+				init: None,
+			},
+			FieldDef {
+				name: n[2],
+				tspec: arr_t,
+				init: None,
+			},
+		])
+		.unwrap();
+
+		let offsets = outer.fields.iter().map(|f| f.offset).collect::<Vec<_>>();
+		assert_eq!(offsets, [0, 4, 12]);
+		// 18 bytes of fields, padded out to the inner structure's alignment.
+		assert_eq!(outer.layout, Layout { size: 20, align: 4 });
+		// The inner structure's initializer carries over.
+		assert_eq!(outer.default[8], 7);
+		assert_eq!(outer.default.iter().map(|b| *b as usize).sum::<usize>(), 7);
+	}
+
+	#[test]
+	fn size_limit() {
+		let arena = bumpalo::Bump::new();
+		let interner = NameInterner::default();
+		let n = names(&interner, 2);
+
+		let u8_t = alloc_t(&arena, TypeDatum::Primitive(Primitive::U8));
+		let i64_t = alloc_t(&arena, TypeDatum::Primitive(Primitive::I64));
+
+		assert!(Layout::array(i64_t.layout(), MAX_SIZE / 8).is_ok());
+
+		assert_eq!(
+			Layout::array(i64_t.layout(), (MAX_SIZE / 8) + 1),
+			Err(LayoutError::TooLarge)
+		);
+
+		assert_eq!(
+			Layout::array(i64_t.layout(), usize::MAX),
+			Err(LayoutError::TooLarge)
+		);
+
+		let half = |len| alloc_t(&arena, TypeDatum::Array { inner: u8_t, len });
+
+		let fields = |len0, len1| {
+			[
+				FieldDef {
+					name: n[0],
+					tspec: half(len0),
+					init: None,
+				},
+				FieldDef {
+					name: n[1],
+					tspec: half(len1),
+					init: None,
+				},
+			]
+		};
+
+		let exact = Structure::new(fields(MAX_SIZE / 2, MAX_SIZE / 2)).unwrap();
+		assert_eq!(exact.layout.size, MAX_SIZE);
+
+		assert_eq!(
+			Structure::new(fields(MAX_SIZE / 2, (MAX_SIZE / 2) + 1)).unwrap_err(),
+			LayoutError::TooLarge
+		);
+	}
+}
@@ -0,0 +1,771 @@
+//! Lowering and compile-time evaluation of the built-in vector types:
+//! [`Primitive::Vec2`], [`Primitive::Vec3`], and [`Primitive::Vec4`].
+//!
+//! Where [`simd_supported`] is `true`, every vector type is lowered to a single
+//! [`types::F32X4`] value, and any lanes past the vector's length are never read.
+//! Elsewhere, each lane becomes its own [`types::F32`] value. Either way, every
+//! operator and intrinsic gets lowered to instructions inline, never to a call.
+//!
+//! [`Primitive::Vec2`]: super::tsys::Primitive::Vec2
+//! [`Primitive::Vec3`]: super::tsys::Primitive::Vec3
+//! [`Primitive::Vec4`]: super::tsys::Primitive::Vec4
+
+use cranelift::{
+	codegen::{
+		data_value::DataValue,
+		ir::{ConstantData, Endianness},
+		isa::TargetIsa,
+	},
+	prelude::{types, FunctionBuilder, InstBuilder, MemFlags, Value},
+};
+use smallvec::SmallVec;
+
+use crate::builtins;
+
+/// (RAT) SSE2 and NEON are baseline features of their architectures, so there
+/// is no need to query the host's features any further than this.
+#[must_use]
+pub(crate) fn simd_supported(isa: &dyn TargetIsa) -> bool {
+	matches!(isa.name(), "x64" | "aarch64")
+}
+
+/// An SSA representation of a vector-typed value.
+#[derive(Debug, Clone)]
+pub(crate) enum VecValue {
+	Simd { value: Value, len: u8 },
+	Scalar(SmallVec<[Value; 4]>),
+}
+
+impl VecValue {
+	#[must_use]
+	pub(crate) fn len(&self) -> u8 {
+		match self {
+			Self::Simd { len, .. } => *len,
+			Self::Scalar(lanes) => lanes.len() as u8,
+		}
+	}
+}
+
+/// Component-wise operators between two vectors of the same type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VecBinOp {
+	Add,
+	Sub,
+	Mul,
+}
+
+/// The result of lowering a call to a vector [intrinsic](intrinsic).
+#[derive(Debug, Clone)]
+pub(crate) enum Lowered {
+	Scalar(Value),
+	Vector(VecValue),
+}
+
+// Lowering ////////////////////////////////////////////////////////////////////
+
+/// `lanes` must have a length between 2 and 4 (inclusive).
+#[must_use]
+pub(crate) fn constant(builder: &mut FunctionBuilder, simd: bool, lanes: &[f32]) -> VecValue {
+	debug_assert!((2..=4).contains(&lanes.len()));
+
+	if !simd {
+		return VecValue::Scalar(lanes.iter().map(|f| builder.ins().f32const(*f)).collect());
+	}
+
+	let mut bytes = [0; 16];
+
+	for (i, f) in lanes.iter().enumerate() {
+		bytes[(i * 4)..((i + 1) * 4)].copy_from_slice(&f.to_le_bytes());
+	}
+
+	let handle = builder
+		.func
+		.dfg
+		.constants
+		.insert(ConstantData::from(bytes.to_vec()));
+
+	VecValue::Simd {
+		value: builder.ins().vconst(types::F32X4, handle),
+		len: lanes.len() as u8,
+	}
+}
+
+/// Builds a vector out of runtime [`types::F32`] values.
+/// `lanes` must have a length between 2 and 4 (inclusive).
+#[must_use]
+pub(crate) fn from_lanes(builder: &mut FunctionBuilder, simd: bool, lanes: &[Value]) -> VecValue {
+	debug_assert!((2..=4).contains(&lanes.len()));
+
+	if !simd {
+		return VecValue::Scalar(lanes.iter().copied().collect());
+	}
+
+	let mut value = builder.ins().splat(types::F32X4, lanes[0]);
+
+	for (i, lane) in lanes.iter().enumerate().skip(1) {
+		value = builder.ins().insertlane(value, *lane, i as u8);
+	}
+
+	VecValue::Simd {
+		value,
+		len: lanes.len() as u8,
+	}
+}
+
+#[must_use]
+pub(crate) fn binary(
+	builder: &mut FunctionBuilder,
+	op: VecBinOp,
+	lhs: &VecValue,
+	rhs: &VecValue,
+) -> VecValue {
+	debug_assert_eq!(lhs.len(), rhs.len());
+
+	zip(builder, lhs, rhs, |builder, l, r| match op {
+		VecBinOp::Add => builder.ins().fadd(l, r),
+		VecBinOp::Sub => builder.ins().fsub(l, r),
+		VecBinOp::Mul => builder.ins().fmul(l, r),
+	})
+}
+
+/// Multiplies every lane of `vector` by the [`types::F32`] `scalar`.
+#[must_use]
+pub(crate) fn scale(builder: &mut FunctionBuilder, vector: &VecValue, scalar: Value) -> VecValue {
+	let splat = splat_like(builder, vector, scalar);
+	zip(builder, vector, &splat, |builder, v, s| {
+		builder.ins().fmul(v, s)
+	})
+}
+
+/// Divides every lane of `vector` by the [`types::F32`] `scalar`.
+#[must_use]
+pub(crate) fn div_scalar(
+	builder: &mut FunctionBuilder,
+	vector: &VecValue,
+	scalar: Value,
+) -> VecValue {
+	let splat = splat_like(builder, vector, scalar);
+	zip(builder, vector, &splat, |builder, v, s| {
+		builder.ins().fdiv(v, s)
+	})
+}
+
+#[must_use]
+pub(crate) fn neg(builder: &mut FunctionBuilder, vector: &VecValue) -> VecValue {
+	match vector {
+		VecValue::Simd { value, len } => VecValue::Simd {
+			value: builder.ins().fneg(*value),
+			len: *len,
+		},
+		VecValue::Scalar(lanes) => {
+			VecValue::Scalar(lanes.iter().map(|l| builder.ins().fneg(*l)).collect())
+		}
+	}
+}
+
+/// Returns a [`types::F32`].
+#[must_use]
+pub(crate) fn dot(builder: &mut FunctionBuilder, lhs: &VecValue, rhs: &VecValue) -> Value {
+	debug_assert_eq!(lhs.len(), rhs.len());
+	let product = binary(builder, VecBinOp::Mul, lhs, rhs);
+	horizontal_sum(builder, &product)
+}
+
+/// Only valid for three-lane vectors.
+#[must_use]
+pub(crate) fn cross(builder: &mut FunctionBuilder, lhs: &VecValue, rhs: &VecValue) -> VecValue {
+	debug_assert_eq!(lhs.len(), 3);
+	debug_assert_eq!(rhs.len(), 3);
+
+	const YZX: [u8; 4] = [1, 2, 0, 3];
+	const ZXY: [u8; 4] = [2, 0, 1, 3];
+
+	match (lhs, rhs) {
+		(VecValue::Simd { value: l, .. }, VecValue::Simd { value: r, .. }) => {
+			let l_yzx = swizzle(builder, *l, YZX);
+			let l_zxy = swizzle(builder, *l, ZXY);
+			let r_yzx = swizzle(builder, *r, YZX);
+			let r_zxy = swizzle(builder, *r, ZXY);
+			let a = builder.ins().fmul(l_yzx, r_zxy);
+			let b = builder.ins().fmul(l_zxy, r_yzx);
+
+			VecValue::Simd {
+				value: builder.ins().fsub(a, b),
+				len: 3,
+			}
+		}
+		(VecValue::Scalar(l), VecValue::Scalar(r)) => {
+			let mut lanes = SmallVec::new();
+
+			for (j, k) in YZX.into_iter().zip(ZXY).take(3) {
+				let (j, k) = (j as usize, k as usize);
+				let a = builder.ins().fmul(l[j], r[k]);
+				let b = builder.ins().fmul(l[k], r[j]);
+				lanes.push(builder.ins().fsub(a, b));
+			}
+
+			VecValue::Scalar(lanes)
+		}
+		_ => unreachable!("mixed SIMD and scalar vector representations"),
+	}
+}
+
+/// Returns a [`types::F32`].
+#[must_use]
+pub(crate) fn length(builder: &mut FunctionBuilder, vector: &VecValue) -> Value {
+	let sq = dot(builder, vector, vector);
+	builder.ins().sqrt(sq)
+}
+
+#[must_use]
+pub(crate) fn normalize(builder: &mut FunctionBuilder, vector: &VecValue) -> VecValue {
+	let len = length(builder, vector);
+	div_scalar(builder, vector, len)
+}
+
+/// Lowers a call to one of the vector intrinsics declared in the baselib.
+/// Panics if `index` is not a vector intrinsic or `args` are of the wrong count;
+/// the frontend is expected to have checked both already.
+#[must_use]
+pub(crate) fn intrinsic(
+	builder: &mut FunctionBuilder,
+	index: builtins::Index,
+	args: &[VecValue],
+) -> Lowered {
+	match (index, args) {
+		(builtins::Index::Dot, [lhs, rhs]) => Lowered::Scalar(dot(builder, lhs, rhs)),
+		(builtins::Index::Cross, [lhs, rhs]) => Lowered::Vector(cross(builder, lhs, rhs)),
+		(builtins::Index::Length, [arg]) => Lowered::Scalar(length(builder, arg)),
+		(builtins::Index::Normalize, [arg]) => Lowered::Vector(normalize(builder, arg)),
+		(other, _) => unreachable!("invalid vector intrinsic call: {other:?}"),
+	}
+}
+
+// Lowering details ////////////////////////////////////////////////////////////
+
+#[must_use]
+fn zip(
+	builder: &mut FunctionBuilder,
+	lhs: &VecValue,
+	rhs: &VecValue,
+	mut function: impl FnMut(&mut FunctionBuilder, Value, Value) -> Value,
+) -> VecValue {
+	match (lhs, rhs) {
+		(VecValue::Simd { value: l, len }, VecValue::Simd { value: r, .. }) => VecValue::Simd {
+			value: function(builder, *l, *r),
+			len: *len,
+		},
+		(VecValue::Scalar(l), VecValue::Scalar(r)) => VecValue::Scalar(
+			l.iter()
+				.zip(r.iter())
+				.map(|(l, r)| function(builder, *l, *r))
+				.collect(),
+		),
+		_ => unreachable!("mixed SIMD and scalar vector representations"),
+	}
+}
+
+#[must_use]
+fn splat_like(builder: &mut FunctionBuilder, vector: &VecValue, scalar: Value) -> VecValue {
+	match vector {
+		VecValue::Simd { len, .. } => VecValue::Simd {
+			value: builder.ins().splat(types::F32X4, scalar),
+			len: *len,
+		},
+		VecValue::Scalar(lanes) => VecValue::Scalar(SmallVec::from_elem(scalar, lanes.len())),
+	}
+}
+
+#[must_use]
+fn horizontal_sum(builder: &mut FunctionBuilder, vector: &VecValue) -> Value {
+	let lanes: SmallVec<[Value; 4]> = match vector {
+		VecValue::Simd { value, len } => (0..*len)
+			.map(|i| builder.ins().extractlane(*value, i))
+			.collect(),
+		VecValue::Scalar(lanes) => lanes.clone(),
+	};
+
+	let mut sum = lanes[0];
+
+	for lane in &lanes[1..] {
+		sum = builder.ins().fadd(sum, *lane);
+	}
+
+	sum
+}
+
+/// Rearranges the lanes of a [`types::F32X4`], such that lane `i` of the output
+/// is lane `lanes[i]` of the input.
+#[must_use]
+fn swizzle(builder: &mut FunctionBuilder, vector: Value, lanes: [u8; 4]) -> Value {
+	// (RAT) Cranelift only offers shuffling on bytes.
+	let flags = MemFlags::new().with_endianness(Endianness::Little);
+	let bytes = builder.ins().bitcast(types::I8X16, flags, vector);
+
+	let mask: Vec<u8> = lanes
+		.iter()
+		.flat_map(|lane| (0..4).map(move |byte| (lane * 4) + byte))
+		.collect();
+
+	let mask = builder.func.dfg.immediates.push(ConstantData::from(mask));
+	let shuffled = builder.ins().shuffle(bytes, bytes, mask);
+	builder.ins().bitcast(types::F32X4, flags, shuffled)
+}
+
+// Compile-time evaluation /////////////////////////////////////////////////////
+
+/// A vector value known at compile time. Lanes past its length are always zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct VecConst {
+	lanes: [f32; 4],
+	len: u8,
+}
+
+impl VecConst {
+	/// Returns `None` if `lanes` does not have a length between 2 and 4 (inclusive).
+	#[must_use]
+	pub(crate) fn new(lanes: &[f32]) -> Option<Self> {
+		if !(2..=4).contains(&lanes.len()) {
+			return None;
+		}
+
+		let mut ret = Self {
+			lanes: [0.0; 4],
+			len: lanes.len() as u8,
+		};
+
+		ret.lanes[..lanes.len()].copy_from_slice(lanes);
+		Some(ret)
+	}
+
+	#[must_use]
+	pub(crate) fn lanes(&self) -> &[f32] {
+		&self.lanes[..(self.len as usize)]
+	}
+
+	#[must_use]
+	pub(crate) fn from_data(data: &DataValue, len: u8) -> Option<Self> {
+		let DataValue::V128(bytes) = data else {
+			return None;
+		};
+
+		let mut lanes = [0.0; 4];
+
+		for (i, lane) in lanes.iter_mut().enumerate().take(len as usize) {
+			let mut b = [0; 4];
+			b.copy_from_slice(&bytes[(i * 4)..((i + 1) * 4)]);
+			*lane = f32::from_le_bytes(b);
+		}
+
+		Self::new(&lanes[..(len as usize)])
+	}
+
+	#[must_use]
+	pub(crate) fn to_data(self) -> DataValue {
+		let mut bytes = [0; 16];
+
+		for (i, f) in self.lanes.iter().enumerate() {
+			bytes[(i * 4)..((i + 1) * 4)].copy_from_slice(&f.to_le_bytes());
+		}
+
+		DataValue::V128(bytes)
+	}
+
+	#[must_use]
+	pub(crate) fn binary(self, op: VecBinOp, other: Self) -> Self {
+		self.zip(other, |l, r| match op {
+			VecBinOp::Add => l + r,
+			VecBinOp::Sub => l - r,
+			VecBinOp::Mul => l * r,
+		})
+	}
+
+	#[must_use]
+	pub(crate) fn scale(self, scalar: f32) -> Self {
+		self.map(|l| l * scalar)
+	}
+
+	#[must_use]
+	pub(crate) fn div_scalar(self, scalar: f32) -> Self {
+		self.map(|l| l / scalar)
+	}
+
+	#[must_use]
+	pub(crate) fn neg(self) -> Self {
+		self.map(|l| -l)
+	}
+
+	#[must_use]
+	pub(crate) fn dot(self, other: Self) -> f32 {
+		debug_assert_eq!(self.len, other.len);
+
+		self.lanes()
+			.iter()
+			.zip(other.lanes())
+			.fold(0.0, |acc, (l, r)| acc + (l * r))
+	}
+
+	/// Returns `None` unless both vectors have three lanes.
+	#[must_use]
+	pub(crate) fn cross(self, other: Self) -> Option<Self> {
+		if self.len != 3 || other.len != 3 {
+			return None;
+		}
+
+		let [lx, ly, lz, _] = self.lanes;
+		let [rx, ry, rz, _] = other.lanes;
+
+		Self::new(&[
+			(ly * rz) - (lz * ry),
+			(lz * rx) - (lx * rz),
+			(lx * ry) - (ly * rx),
+		])
+	}
+
+	#[must_use]
+	pub(crate) fn length(self) -> f32 {
+		self.dot(self).sqrt()
+	}
+
+	#[must_use]
+	pub(crate) fn normalize(self) -> Self {
+		self.div_scalar(self.length())
+	}
+
+	#[must_use]
+	fn map(self, mut function: impl FnMut(f32) -> f32) -> Self {
+		let mut ret = self;
+
+		for lane in &mut ret.lanes[..(self.len as usize)] {
+			*lane = function(*lane);
+		}
+
+		ret
+	}
+
+	#[must_use]
+	fn zip(self, other: Self, mut function: impl FnMut(f32, f32) -> f32) -> Self {
+		debug_assert_eq!(self.len, other.len);
+		let mut ret = self;
+
+		for (i, lane) in ret.lanes[..(self.len as usize)].iter_mut().enumerate() {
+			*lane = function(*lane, other.lanes[i]);
+		}
+
+		ret
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use cranelift::{
+		codegen::ir,
+		prelude::{AbiParam, FunctionBuilderContext},
+	};
+	use cranelift_jit::{JITBuilder, JITModule};
+	use cranelift_module::{default_libcall_names, Linkage, Module};
+	use glam::Vec4;
+
+	use super::*;
+
+	const A: Vec4 = Vec4::new(1.5, -2.0, 3.25, 0.5);
+	const B: Vec4 = Vec4::new(-4.0, 0.75, 2.0, 8.0);
+
+	struct Jit {
+		module: JITModule,
+		simd: bool,
+	}
+
+	impl Jit {
+		#[must_use]
+		fn new(natives: &[(&str, *const u8)]) -> Self {
+			let mut builder = JITBuilder::new(default_libcall_names()).unwrap();
+
+			for (name, ptr) in natives {
+				builder.symbol(*name, *ptr);
+			}
+
+			let module = JITModule::new(builder);
+			let simd = simd_supported(module.isa());
+			Self { module, simd }
+		}
+
+		/// `body` receives a builder positioned in the entry block
+		/// and the function's parameters, and returns its return values.
+		/// Returns the finished function's address and its relocation count.
+		#[must_use]
+		fn define(
+			&mut self,
+			params: &[ir::Type],
+			returns: &[ir::Type],
+			body: impl FnOnce(&mut FunctionBuilder, &mut JITModule, &[Value]) -> Vec<Value>,
+		) -> (*const u8, usize) {
+			let mut ctx = self.module.make_context();
+			let mut fctx = FunctionBuilderContext::new();
+
+			for p in params {
+				ctx.func.signature.params.push(AbiParam::new(*p));
+			}
+
+			for r in returns {
+				ctx.func.signature.returns.push(AbiParam::new(*r));
+			}
+
+			let id = self
+				.module
+				.declare_anonymous_function(&ctx.func.signature)
+				.unwrap();
+
+			{
+				let mut builder = FunctionBuilder::new(&mut ctx.func, &mut fctx);
+				let entry = builder.create_block();
+				builder.append_block_params_for_function_params(entry);
+				builder.switch_to_block(entry);
+				builder.seal_block(entry);
+
+				let args = builder.block_params(entry).to_vec();
+				let rets = body(&mut builder, &mut self.module, &args);
+				builder.ins().return_(&rets);
+				builder.finalize();
+			}
+
+			self.module.define_function(id, &mut ctx).unwrap();
+			let relocs = ctx.compiled_code().unwrap().buffer.relocs().len();
+			self.module.clear_context(&mut ctx);
+			self.module.finalize_definitions().unwrap();
+
+			(self.module.get_finalized_function(id), relocs)
+		}
+	}
+
+	#[must_use]
+	fn approx_eq(a: Vec4, b: Vec4) -> bool {
+		a.abs_diff_eq(b, 0.0001)
+	}
+
+	#[test]
+	#[cfg(target_arch = "x86_64")]
+	fn operators() {
+		let mut jit = Jit::new(&[]);
+		assert!(jit.simd);
+
+		type VecVec = extern "C" fn(Vec4, Vec4) -> Vec4;
+		type VecScalar = extern "C" fn(Vec4, f32) -> Vec4;
+
+		let vv = [types::F32X4, types::F32X4];
+		let vs = [types::F32X4, types::F32];
+		let v = |args: &[Value], i: usize| VecValue::Simd {
+			value: args[i],
+			len: 4,
+		};
+
+		for (op, expected) in [
+			(VecBinOp::Add, A + B),
+			(VecBinOp::Sub, A - B),
+			(VecBinOp::Mul, A * B),
+		] {
+			let (ptr, _) = jit.define(&vv, &[types::F32X4], |builder, _, args| {
+				let VecValue::Simd { value, .. } = binary(builder, op, &v(args, 0), &v(args, 1))
+				else {
+					unreachable!()
+				};
+
+				vec![value]
+			});
+
+			let func: VecVec = unsafe { std::mem::transmute(ptr) };
+			assert!(approx_eq(func(A, B), expected), "{op:?}");
+		}
+
+		let (ptr, _) = jit.define(&vs, &[types::F32X4], |builder, _, args| {
+			let VecValue::Simd { value, .. } = scale(builder, &v(args, 0), args[1]) else {
+				unreachable!()
+			};
+
+			vec![value]
+		});
+
+		let func: VecScalar = unsafe { std::mem::transmute(ptr) };
+		assert!(approx_eq(func(A, 2.5), A * 2.5));
+
+		let (ptr, _) = jit.define(&vs, &[types::F32X4], |builder, _, args| {
+			let VecValue::Simd { value, .. } = div_scalar(builder, &v(args, 0), args[1]) else {
+				unreachable!()
+			};
+
+			vec![value]
+		});
+
+		let func: VecScalar = unsafe { std::mem::transmute(ptr) };
+		assert!(approx_eq(func(A, 4.0), A / 4.0));
+
+		let (ptr, _) = jit.define(&vv, &[types::F32X4], |builder, _, args| {
+			let VecValue::Simd { value, .. } = neg(builder, &v(args, 0)) else {
+				unreachable!()
+			};
+
+			vec![value]
+		});
+
+		let func: VecVec = unsafe { std::mem::transmute(ptr) };
+		assert!(approx_eq(func(A, B), -A));
+	}
+
+	#[test]
+	#[cfg(target_arch = "x86_64")]
+	fn intrinsics() {
+		use glam::Vec3A;
+
+		let mut jit = Jit::new(&[]);
+		assert!(jit.simd);
+
+		let vv = [types::F32X4, types::F32X4];
+		let v3 = |args: &[Value], i: usize| VecValue::Simd {
+			value: args[i],
+			len: 3,
+		};
+
+		let (ptr, relocs) = jit.define(&vv, &[types::F32], |builder, _, args| {
+			vec![dot(builder, &v3(args, 0), &v3(args, 1))]
+		});
+
+		// No libcalls means nothing to relocate.
+		assert_eq!(relocs, 0);
+		let func: extern "C" fn(Vec4, Vec4) -> f32 = unsafe { std::mem::transmute(ptr) };
+		assert!((func(A, B) - A.truncate().dot(B.truncate())).abs() < 0.0001);
+
+		let (ptr, _) = jit.define(&vv, &[types::F32X4], |builder, _, args| {
+			let VecValue::Simd { value, .. } = cross(builder, &v3(args, 0), &v3(args, 1)) else {
+				unreachable!()
+			};
+
+			vec![value]
+		});
+
+		let func: extern "C" fn(Vec3A, Vec3A) -> Vec3A = unsafe { std::mem::transmute(ptr) };
+		let (a, b) = (Vec3A::from(A.truncate()), Vec3A::from(B.truncate()));
+		assert!(func(a, b).abs_diff_eq(a.cross(b), 0.0001));
+
+		let (ptr, _) = jit.define(&vv, &[types::F32], |builder, _, args| {
+			vec![length(builder, &v3(args, 0))]
+		});
+
+		let func: extern "C" fn(Vec3A, Vec3A) -> f32 = unsafe { std::mem::transmute(ptr) };
+		assert!((func(a, b) - a.length()).abs() < 0.0001);
+
+		let (ptr, _) = jit.define(&vv, &[types::F32X4], |builder, _, args| {
+			let VecValue::Simd { value, .. } = normalize(builder, &v3(args, 0)) else {
+				unreachable!()
+			};
+
+			vec![value]
+		});
+
+		let func: extern "C" fn(Vec3A, Vec3A) -> Vec3A = unsafe { std::mem::transmute(ptr) };
+		assert!(func(a, b).abs_diff_eq(a.normalize(), 0.0001));
+	}
+
+	#[test]
+	#[cfg(target_arch = "x86_64")]
+	fn native_roundtrip() {
+		use crate::interop::Native;
+		use glam::Vec3A;
+
+		extern "C" fn double(v: Vec3A) -> Vec3A {
+			v * 2.0
+		}
+
+		const NAME: &str = "lith_test_vec_double";
+		let mut jit = Jit::new(&[(NAME, double as *const u8)]);
+		assert!(jit.simd);
+
+		let repr = <Vec3A as Native>::REPR;
+
+		let (ptr, _) = jit.define(&[repr], &[repr], |builder, module, args| {
+			let mut sig = module.make_signature();
+			sig.params.push(AbiParam::new(repr));
+			sig.returns.push(AbiParam::new(repr));
+
+			let id = module
+				.declare_function(NAME, Linkage::Import, &sig)
+				.unwrap();
+			let fref = module.declare_func_in_func(id, builder.func);
+			let call = builder.ins().call(fref, &[args[0]]);
+			let ret = builder.inst_results(call)[0];
+
+			let VecValue::Simd { value, .. } = neg(builder, &VecValue::Simd { value: ret, len: 3 })
+			else {
+				unreachable!()
+			};
+
+			vec![value]
+		});
+
+		let func: extern "C" fn(Vec3A) -> Vec3A = unsafe { std::mem::transmute(ptr) };
+		let v = Vec3A::new(1.0, -2.0, 0.5);
+		assert_eq!(func(v), -(v * 2.0));
+	}
+
+	#[test]
+	fn scalar_fallback() {
+		type Func = extern "C" fn(f32, f32, f32, f32, f32, f32) -> f32;
+
+		let mut jit = Jit::new(&[]);
+		let (a, b) = (A.truncate(), B.truncate());
+		let expected = a.cross(b);
+
+		// (RAT) Multiple return values do not map onto any Rust type,
+		// so each lane of the cross product is checked by its own function.
+		for (i, expected) in expected.to_array().into_iter().enumerate() {
+			let (ptr, relocs) = jit.define(&[types::F32; 6], &[types::F32], |builder, _, args| {
+				let lhs = from_lanes(builder, false, &args[0..3]);
+				let rhs = from_lanes(builder, false, &args[3..6]);
+
+				let VecValue::Scalar(lanes) = cross(builder, &lhs, &rhs) else {
+					unreachable!()
+				};
+
+				vec![lanes[i]]
+			});
+
+			assert_eq!(relocs, 0);
+			let func: Func = unsafe { std::mem::transmute(ptr) };
+			let lane = func(a.x, a.y, a.z, b.x, b.y, b.z);
+			assert!((lane - expected).abs() < 0.0001);
+		}
+
+		let (ptr, _) = jit.define(&[types::F32; 6], &[types::F32], |builder, _, args| {
+			let lhs = from_lanes(builder, false, &args[0..3]);
+			let rhs = from_lanes(builder, false, &args[3..6]);
+			vec![dot(builder, &lhs, &rhs)]
+		});
+
+		let func: Func = unsafe { std::mem::transmute(ptr) };
+		assert!((func(a.x, a.y, a.z, b.x, b.y, b.z) - a.dot(b)).abs() < 0.0001);
+	}
+
+	#[test]
+	fn const_folding() {
+		let a = VecConst::new(&[A.x, A.y, A.z]).unwrap();
+		let b = VecConst::new(&[B.x, B.y, B.z]).unwrap();
+		let (ga, gb) = (A.truncate(), B.truncate());
+
+		let from = |c: VecConst| glam::Vec3::from_slice(c.lanes());
+
+		assert_eq!(from(a.binary(VecBinOp::Add, b)), ga + gb);
+		assert_eq!(from(a.binary(VecBinOp::Sub, b)), ga - gb);
+		assert_eq!(from(a.binary(VecBinOp::Mul, b)), ga * gb);
+		assert_eq!(from(a.scale(3.0)), ga * 3.0);
+		assert_eq!(from(a.div_scalar(3.0)), ga / 3.0);
+		assert_eq!(from(a.neg()), -ga);
+		assert_eq!(a.dot(b), ga.dot(gb));
+		assert_eq!(from(a.cross(b).unwrap()), ga.cross(gb));
+		assert!((a.length() - ga.length()).abs() < 0.0001);
+		assert!(from(a.normalize()).abs_diff_eq(ga.normalize(), 0.0001));
+
+		let data = a.to_data();
+		assert_eq!(VecConst::from_data(&data, 3), Some(a));
+		assert!(VecConst::new(&[1.0]).is_none());
+		assert!(VecConst::new(&[1.0, 2.0]).unwrap().cross(b).is_none());
+	}
+}
@@ -169,6 +169,33 @@ unsafe impl Native for core::arch::x86_64::__m256d {
 	const REPR: AbiType = types::F64X4;
 }
 
+/// Corresponds to Lithica's `vec4`.
+#[cfg(target_arch = "x86_64")]
+unsafe impl Native for glam::Vec4 {
+	const REPR: AbiType = types::F32X4;
+}
+
+/// Corresponds to Lithica's `vec3`, whose fourth lane is padding.
+///
+/// (RAT) [`glam::Vec3`] is a three-float aggregate, which has no single
+/// Cranelift type and gets passed differently from a SIMD register.
+#[cfg(target_arch = "x86_64")]
+unsafe impl Native for glam::Vec3A {
+	const REPR: AbiType = types::F32X4;
+}
+
+#[cfg(target_arch = "x86_64")]
+const _STATIC_ASSERT_GLAM_SIMD: () = {
+	use std::mem::{align_of, size_of};
+
+	use core::arch::x86_64::__m128;
+
+	assert!(size_of::<glam::Vec4>() == size_of::<__m128>());
+	assert!(align_of::<glam::Vec4>() == align_of::<__m128>());
+	assert!(size_of::<glam::Vec3A>() == size_of::<__m128>());
+	assert!(align_of::<glam::Vec3A>() == align_of::<__m128>());
+};
+
 const _STATIC_ASSERT_RC_WIDTH: () = {
 	assert!(std::mem::size_of::<std::rc::Rc<i32>>() == std::mem::size_of::<isize>());
 	assert!(std::mem::size_of::<std::sync::Arc<i32>>() == std::mem::size_of::<isize>());
@@ -194,6 +194,10 @@ pub enum Error {
 	/// Tried to call, for instance, a symbolic constant.
 	IllegalCall,
 	IllegalConstInit,
+	/// A `return`, `break`, or `continue` statement would leave the body of
+	/// a deferred statement, which only runs while control is already leaving
+	/// the block enclosing it.
+	IllegalDeferExit,
 	IllegalFnQual,
 	/// A function marked `native` has a body block.
 	IllegalFnBody,
@@ -223,6 +227,8 @@ pub enum Error {
 	Redeclare,
 	SymbolKindMismatch,
 	SymbolNotFound,
+	/// A type would be larger than the compiler's limit on type sizes.
+	TypeTooLarge,
 	/// A script attempted to use a feature that LithC does not support yet.
 	Unimplemented,
 	UnknownAnnotation,
@@ -277,7 +277,14 @@ fn field_decl(p: &mut Parser<Syntax>) {
 	trivia_0plus(p);
 	type_spec(p, false);
 	trivia_0plus(p);
-	p.expect(Syntax::Semicolon, Syntax::Semicolon, &[&["TODO"]]);
+
+	if p.eat(Syntax::Eq, Syntax::Eq) {
+		trivia_0plus(p);
+		let _ = expr(p, true);
+		trivia_0plus(p);
+	}
+
+	p.expect(Syntax::Semicolon, Syntax::Semicolon, &[&["`=`", "`;`"]]);
 	p.close(mark, Syntax::FieldDecl);
 }
 
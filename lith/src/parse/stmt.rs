@@ -74,6 +74,19 @@ pub(super) fn statement(p: &mut Parser<Syntax>, mark: OpenMark) {
 			p.expect(Syntax::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
 			p.close(mark, Syntax::StmtContinue);
 		}
+		t @ Syntax::KwDefer => {
+			p.advance(t);
+			trivia_0plus(p);
+
+			let block_end = super::expr(p, true);
+
+			if !block_end {
+				trivia_0plus(p);
+				p.expect(Syntax::Semicolon, Syntax::Semicolon, &[&["`;`"]]);
+			}
+
+			p.close(mark, Syntax::StmtDefer);
+		}
 		_ => {
 			let block_end = super::expr(p, true);
 
@@ -345,6 +345,7 @@ fn expr_struct_smoke() {
 		const LOREM_IPSUM: dolor = sit_amet;
 
 		lorem: ipsum;
+		dolor: sit_amet = 0;
 
 		function lorem_ipsum();
 	}"#;
@@ -373,10 +374,20 @@ fn expr_struct_smoke() {
 		panic!()
 	};
 
-	let ast::StructInnard::Field(_) = innards.next().unwrap() else {
+	let ast::StructInnard::Field(field) = innards.next().unwrap() else {
 		panic!()
 	};
 
+	assert!(field.initializer().is_none());
+
+	let ast::StructInnard::Field(field) = innards.next().unwrap() else {
+		panic!()
+	};
+
+	assert_eq!(field.name().text(), "dolor");
+	assert!(field.type_spec().into_expr().is_some());
+	assert!(matches!(field.initializer(), Some(ast::Expr::Literal(_))));
+
 	let ast::StructInnard::Item(_) = innards.next().unwrap() else {
 		panic!()
 	};
@@ -456,6 +467,38 @@ fn stmt_continue_smoke() {
 	}
 }
 
+#[test]
+fn stmt_defer_smoke() {
+	const SAMPLES: &[&str] = &[
+		"defer {}",
+		"defer { lorem(); }",
+		"defer{ipsum();}",
+		"defer dolor();",
+		"defer sit_amet ;",
+	];
+
+	for sample in SAMPLES {
+		let ptree: ParseTree = doomfront::parse(
+			sample,
+			|p| {
+				let mark = p.open();
+				super::statement(p, mark);
+			},
+			LexContext::default(),
+		);
+
+		assert_no_errors(&ptree);
+
+		if prettyprint_maybe(ptree.cursor()) {
+			eprintln!();
+		}
+
+		let stmt = ast::StmtDefer::cast(ptree.cursor()).unwrap();
+		assert_eq!(stmt.keyword().text(), "defer");
+		let _ = stmt.body().unwrap();
+	}
+}
+
 #[test]
 fn stmt_bind_smoke() {
 	const SAMPLES: &[&str] = &[
@@ -10,7 +10,7 @@ use rustc_hash::FxHasher;
 
 use crate::{
 	interop::{self, Interop, Ret2},
-	runtime::Runtime,
+	runtime::{self, CallError, Runtime},
 };
 
 use super::Handle;
@@ -42,20 +42,12 @@ impl Function {
 pub struct TFn<'f, F: Interop>(pub(crate) &'f Function, PhantomData<F>);
 
 impl TFn<'_, fn(*mut Runtime)> {
-	#[allow(clippy::result_unit_err)]
-	pub fn call(&self, rt: &mut Runtime) -> Result<(), ()> {
-		let j = cee_scape::call_with_sigsetjmp(false, |_| unsafe {
+	/// Does nothing if this function is [suspended](Runtime::is_suspended).
+	pub fn call(&self, rt: &mut Runtime) -> Result<(), CallError> {
+		runtime::call_guarded(rt, self.0.id, |rt| unsafe {
 			let func = *self.0.ptr.cast::<fn(*mut Runtime)>();
-
-			func(std::ptr::addr_of_mut!(*rt));
-
-			0
-		});
-
-		match j {
-			0 => Ok(()),
-			_ => Err(()),
-		}
+			func(rt);
+		})
 	}
 }
 
@@ -68,26 +60,21 @@ macro_rules! tfn_impl {
 				$tie<$($ret),+>: interop::Return,
 				$($ret: interop::Native),+,
 			{
-				#[allow(clippy::result_unit_err)]
+				/// Returns zeroed values if this function is
+				/// [suspended](Runtime::is_suspended).
 				pub fn call(
 					&self,
 					rt: &mut Runtime,
 					$($paramname: $param),+,
-				) -> Result<$tie<$($ret),+>, ()> {
+				) -> Result<$tie<$($ret),+>, CallError> {
 					let mut ret = unsafe { std::mem::zeroed() };
 
-					let j = cee_scape::call_with_sigsetjmp(false, |_| unsafe {
+					runtime::call_guarded(rt, self.0.id, |rt| unsafe {
 						let func = *self.0.ptr.cast::<fn(*mut Runtime, $($param),+) -> $tie<$($ret),+>>();
+						ret = func(rt, $($paramname),+);
+					})?;
 
-						ret = func(std::ptr::addr_of_mut!(*rt), $($paramname),+);
-
-						0
-					});
-
-					match j {
-						0 => Ok(ret),
-						_ => Err(()),
-					}
+					Ok(ret)
 				}
 			}
 		)+
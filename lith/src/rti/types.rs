@@ -114,6 +114,9 @@ pub enum PrimitiveType {
 	U128,
 	F32,
 	F64,
+	Vec2,
+	Vec3,
+	Vec4,
 
 	Void,
 }
@@ -1,9 +1,17 @@
+mod budget;
+
 use std::any::TypeId;
 
 use rustc_hash::FxHashMap;
 
 use crate::{compile::module::JitModule, rti};
 
+pub use self::budget::{safepoint, BudgetExceeded, CallError, SAMPLE_INTERVAL};
+
+pub(crate) use self::budget::{
+	call_guarded, safepoint_slow, Budget, COUNTDOWN_OFFSET, SAFEPOINT_SYMBOL, UNWIND_BUDGET,
+};
+
 /// Context for Lithica execution.
 ///
 /// Fully re-entrant; Lith has no global state.
@@ -19,6 +27,8 @@ pub struct Runtime {
 
 	pub(crate) userdata: *mut (),
 	pub(crate) userdata_t: TypeId,
+
+	pub(crate) budget: Budget,
 }
 
 // SAFETY: these impls are only needed for `userdata`, which is never dereferenced
@@ -28,6 +38,21 @@ unsafe impl Send for Runtime {}
 unsafe impl Sync for Runtime {}
 
 impl Runtime {
+	#[must_use]
+	pub(crate) fn new(module: JitModule) -> Self {
+		Self {
+			_function_rti: FxHashMap::default(),
+			_data_rti: FxHashMap::default(),
+			_type_rti: FxHashMap::default(),
+			module,
+
+			userdata: std::ptr::null_mut(),
+			userdata_t: TypeId::of::<()>(),
+
+			budget: Budget::default(),
+		}
+	}
+
 	/// Panics if `T` is not the current type of the userdata pointer stored using
 	/// [`Self::set_userdata`].
 	#[must_use]
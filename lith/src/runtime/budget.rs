@@ -0,0 +1,386 @@
+//! Per-tic accounting of time spent executing JIT code.
+//!
+//! The host sets a deadline via [`Runtime::begin_tic`]; JIT code checks it at
+//! safepoints (function entry and loop back-edges). A safepoint's fast path is
+//! only a decrement of [`Budget::countdown`]; the clock only gets sampled once
+//! every [`SAMPLE_INTERVAL`] safepoints.
+//!
+//! When the deadline has passed, the current call unwinds back to its
+//! [`TFn::call`](crate::rti::TFn) site, which returns
+//! [`CallError::BudgetExceeded`]. The entry point of that call gets a strike;
+//! once it has [`Budget::strike_limit`] strikes, it is suspended, and any calls
+//! to it return zeroed values until [`Runtime::reset_strikes`] is called.
+
+use std::time::{Duration, Instant};
+
+use cee_scape::SigJmpBuf;
+use cranelift_module::FuncId;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use super::Runtime;
+
+/// How many safepoints are passed between each sampling of the clock.
+pub const SAMPLE_INTERVAL: i32 = 1024;
+
+/// The name under which [`safepoint_slow`] gets registered to the JIT module.
+pub(crate) const SAFEPOINT_SYMBOL: &str = "__lith_safepoint";
+
+/// The value passed to `siglongjmp` to unwind due to an exhausted budget.
+/// Any other non-zero value indicates a trap.
+pub(crate) const UNWIND_BUDGET: i32 = 0x42554447;
+
+/// The offset of [`Budget::countdown`] from the start of a [`Runtime`],
+/// for use by safepoints emitted into JIT code.
+pub(crate) const COUNTDOWN_OFFSET: i32 =
+	(std::mem::offset_of!(Runtime, budget) + std::mem::offset_of!(Budget, countdown)) as i32;
+
+#[derive(Debug)]
+pub(crate) struct Budget {
+	/// Decremented by every safepoint; the clock is only sampled when this
+	/// reaches zero. Must be an `i32`, since JIT code accesses it directly.
+	pub(crate) countdown: i32,
+	/// `None` if no tic is in progress, in which case nothing gets unwound.
+	pub(crate) deadline: Option<Instant>,
+	/// Set by a [`TFn::call`](crate::rti::TFn) before entering JIT code.
+	/// `None` if JIT code was entered some other way, in which case an
+	/// exhausted budget can not be acted upon.
+	pub(crate) jmpbuf: Option<SigJmpBuf>,
+	/// The entry point of the JIT call currently in progress.
+	pub(crate) entry: Option<FuncId>,
+	/// Filled in by [`safepoint_slow`] before unwinding.
+	pub(crate) exceeded: Option<BudgetExceeded>,
+	pub(crate) strikes: FxHashMap<FuncId, u32>,
+	pub(crate) suspended: FxHashSet<FuncId>,
+	pub(crate) strike_limit: u32,
+}
+
+impl Default for Budget {
+	fn default() -> Self {
+		Self {
+			countdown: SAMPLE_INTERVAL,
+			deadline: None,
+			jmpbuf: None,
+			entry: None,
+			exceeded: None,
+			strikes: FxHashMap::default(),
+			suspended: FxHashSet::default(),
+			strike_limit: 3,
+		}
+	}
+}
+
+/// Structured information about a JIT call which was unwound because it
+/// exhausted the budget of the current tic.
+#[derive(Debug)]
+pub struct BudgetExceeded {
+	/// The entry point of the unwound call.
+	pub function: FuncId,
+	/// How far past the deadline execution was when the unwind began.
+	/// Granularity is bounded by [`SAMPLE_INTERVAL`].
+	pub overrun: Duration,
+	/// How many strikes `function` has had since the last call to
+	/// [`Runtime::reset_strikes`], including this one.
+	pub strikes: u32,
+	/// `true` if this strike caused `function` to become suspended.
+	pub suspended: bool,
+	/// Captured at the safepoint which began the unwind. Left unresolved,
+	/// since resolution is expensive and only needed if the trace gets printed.
+	pub backtrace: backtrace::Backtrace,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"script function {:?} exceeded the tic budget by {}us (strike {})",
+			self.function,
+			self.overrun.as_micros(),
+			self.strikes
+		)?;
+
+		if self.suspended {
+			write!(f, "; suspended")?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Why a call into JIT code via [`TFn::call`](crate::rti::TFn) did not return normally.
+#[derive(Debug)]
+pub enum CallError {
+	/// The called code hit a trap.
+	Trap,
+	/// Boxed since it is large and expected to be rare.
+	BudgetExceeded(Box<BudgetExceeded>),
+}
+
+impl std::error::Error for CallError {}
+
+impl std::fmt::Display for CallError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Trap => write!(f, "script execution trapped"),
+			Self::BudgetExceeded(be) => be.fmt(f),
+		}
+	}
+}
+
+/// The slow path of a safepoint, called by JIT code when
+/// [`Budget::countdown`] reaches zero. Does not return if the deadline of the
+/// current tic has passed and there is a call site to unwind to.
+///
+/// # Safety
+///
+/// `rt` must be valid and not aliased by any live reference.
+pub(crate) unsafe extern "C" fn safepoint_slow(rt: *mut Runtime) {
+	let budget = &mut (*rt).budget;
+	budget.countdown = SAMPLE_INTERVAL;
+
+	let Some(deadline) = budget.deadline else {
+		return;
+	};
+
+	let now = Instant::now();
+
+	if now < deadline {
+		return;
+	}
+
+	let (Some(jmpbuf), Some(entry)) = (budget.jmpbuf, budget.entry) else {
+		return;
+	};
+
+	let strikes = budget.strikes.entry(entry).or_default();
+	*strikes += 1;
+	let strikes = *strikes;
+	let suspended = strikes >= budget.strike_limit;
+
+	if suspended {
+		budget.suspended.insert(entry);
+	}
+
+	budget.exceeded = Some(BudgetExceeded {
+		function: entry,
+		overrun: now - deadline,
+		strikes,
+		suspended,
+		backtrace: backtrace::Backtrace::new_unresolved(),
+	});
+
+	// (RAT) Nothing with a destructor is alive in this frame; everything
+	// above was moved into the runtime.
+	cee_scape::siglongjmp(jmpbuf, UNWIND_BUDGET)
+}
+
+/// Shared by every [`TFn::call`](crate::rti::TFn) implementation.
+/// `f` is not called at all if `function` is suspended.
+pub(crate) fn call_guarded(
+	rt: &mut Runtime,
+	function: FuncId,
+	f: impl FnOnce(*mut Runtime),
+) -> Result<(), CallError> {
+	if rt.is_suspended(function) {
+		return Ok(());
+	}
+
+	// (RAT) A native function may call back into JIT code. The innermost call
+	// site is where unwinding stops (so no native frames get skipped), but the
+	// strike goes to the top-level entry point, since that is what the host
+	// can actually stop calling.
+	let prev_jmpbuf = rt.budget.jmpbuf;
+	let prev_entry = rt.budget.entry;
+	rt.budget.entry = Some(prev_entry.unwrap_or(function));
+
+	let rt_ptr = std::ptr::addr_of_mut!(*rt);
+
+	let j = cee_scape::call_with_sigsetjmp(false, |jmpbuf| unsafe {
+		(*rt_ptr).budget.jmpbuf = Some(jmpbuf);
+		f(rt_ptr);
+		0
+	});
+
+	rt.budget.jmpbuf = prev_jmpbuf;
+	rt.budget.entry = prev_entry;
+
+	match j {
+		0 => Ok(()),
+		UNWIND_BUDGET => {
+			let be = rt
+				.budget
+				.exceeded
+				.take()
+				.expect("budget unwind did not leave a report");
+
+			Err(CallError::BudgetExceeded(Box::new(be)))
+		}
+		_ => Err(CallError::Trap),
+	}
+}
+
+/// The Rust-side equivalent of the safepoints which get emitted into JIT code,
+/// for use by native functions which may run for a long time.
+///
+/// # Safety
+///
+/// `rt` must be the pointer which was passed to the calling native function.
+pub unsafe fn safepoint(rt: *mut Runtime) {
+	let countdown = &mut (*rt).budget.countdown;
+	*countdown -= 1;
+
+	if *countdown <= 0 {
+		safepoint_slow(rt);
+	}
+}
+
+impl Runtime {
+	/// Starts accounting for a new tic. Calls made via [`TFn::call`](crate::rti::TFn)
+	/// will be unwound once `budget` has elapsed from now.
+	pub fn begin_tic(&mut self, budget: Duration) {
+		self.budget.deadline = Some(Instant::now() + budget);
+		self.budget.countdown = SAMPLE_INTERVAL;
+	}
+
+	/// Calls made after this and before the next [`Self::begin_tic`] are
+	/// never unwound for taking too long.
+	pub fn end_tic(&mut self) {
+		self.budget.deadline = None;
+	}
+
+	/// Forgets all strikes and lifts all suspensions.
+	/// The host should call this on level change.
+	pub fn reset_strikes(&mut self) {
+		self.budget.strikes.clear();
+		self.budget.suspended.clear();
+	}
+
+	/// How many strikes a function may receive before it is suspended.
+	/// The default is 3. Does not affect functions which are already suspended.
+	pub fn set_strike_limit(&mut self, limit: u32) {
+		self.budget.strike_limit = limit.max(1);
+	}
+
+	#[must_use]
+	pub fn strikes(&self, function: FuncId) -> u32 {
+		self.budget.strikes.get(&function).copied().unwrap_or(0)
+	}
+
+	#[must_use]
+	pub fn is_suspended(&self, function: FuncId) -> bool {
+		self.budget.suspended.contains(&function)
+	}
+
+	/// Iterates over every function which is currently suspended.
+	pub fn suspended(&self) -> impl Iterator<Item = FuncId> + '_ {
+		self.budget.suspended.iter().copied()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{hint::black_box, marker::PhantomData};
+
+	use crate::{
+		compile::{module::JitModule, Config},
+		rti::{Function, TFn},
+		Compiler, OptLevel,
+	};
+
+	use super::*;
+
+	#[must_use]
+	fn runtime() -> Runtime {
+		let compiler = Compiler::new(Config {
+			opt: OptLevel::None,
+			hotswap: false,
+			budget_checks: true,
+			enable_verifier: None,
+		});
+
+		Runtime::new(JitModule::new(&compiler))
+	}
+
+	fn spin(rt: *mut Runtime) {
+		loop {
+			unsafe {
+				safepoint(rt);
+			}
+		}
+	}
+
+	fn quick(rt: *mut Runtime) {
+		for _ in 0..(SAMPLE_INTERVAL * 4) {
+			unsafe {
+				safepoint(rt);
+			}
+		}
+
+		unsafe {
+			*(*rt).userdata::<u32>() += 1;
+		}
+	}
+
+	#[test]
+	fn slow_unwinds_and_suspends() {
+		let mut rt = runtime();
+		rt.set_strike_limit(2);
+
+		let ptr: fn(*mut Runtime) = spin;
+		let func = Function {
+			ptr: std::ptr::addr_of!(ptr).cast(),
+			id: FuncId::from_u32(0),
+			sig_hash: 0, // Unused.
+		};
+		let tfn = TFn::<fn(*mut Runtime)>(black_box(&func), PhantomData);
+
+		for strike in 1..=2 {
+			rt.begin_tic(Duration::from_millis(1));
+
+			let Err(CallError::BudgetExceeded(be)) = tfn.call(&mut rt) else {
+				panic!("expected budget to be exceeded");
+			};
+
+			assert_eq!(be.function, func.id());
+			assert_eq!(be.strikes, strike);
+			assert_eq!(be.suspended, strike == 2);
+			rt.end_tic();
+		}
+
+		assert!(rt.is_suspended(func.id()));
+
+		// Calls to a suspended function return immediately.
+		rt.begin_tic(Duration::from_millis(1));
+		assert!(tfn.call(&mut rt).is_ok());
+		rt.end_tic();
+
+		// Level change.
+		rt.reset_strikes();
+		assert_eq!(rt.strikes(func.id()), 0);
+		assert!(!rt.is_suspended(func.id()));
+	}
+
+	#[test]
+	fn fast_unaffected() {
+		let mut rt = runtime();
+		let mut counter = 0_u32;
+		rt.set_userdata(std::ptr::addr_of_mut!(counter));
+
+		let ptr: fn(*mut Runtime) = quick;
+		let func = Function {
+			ptr: std::ptr::addr_of!(ptr).cast(),
+			id: FuncId::from_u32(1),
+			sig_hash: 0, // Unused.
+		};
+		let tfn = TFn::<fn(*mut Runtime)>(&func, PhantomData);
+
+		for _ in 0..8 {
+			rt.begin_tic(Duration::from_secs(1));
+			tfn.call(&mut rt).unwrap();
+			rt.end_tic();
+		}
+
+		assert_eq!(counter, 8);
+		assert_eq!(rt.strikes(func.id()), 0);
+		assert_eq!(rt.suspended().count(), 0);
+	}
+}
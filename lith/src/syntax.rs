@@ -68,6 +68,11 @@ pub enum Syntax {
 	StmtBreak,
 	/// `'continue' blocklabel? ';'`
 	StmtContinue,
+	/// `'defer' expr ';'`
+	///
+	/// As with [`Syntax::StmtExpr`], the trailing semicolon is optional
+	/// if the expression ends with a curly-brace-delimited block.
+	StmtDefer,
 	/// `expr ';'`
 	///
 	/// The trailing semicolon is optional if the expression
@@ -152,6 +157,9 @@ pub enum Syntax {
 	/// `continue`; used in [continue statements](Syntax::StmtContinue).
 	#[token("continue")]
 	KwContinue,
+	/// `defer`; used in [deferred statements](Syntax::StmtDefer).
+	#[token("defer")]
+	KwDefer,
 	/// `function`; used in [function declarations](Syntax::FunctionDecl).
 	#[token("function")]
 	KwFunction,
@@ -509,6 +517,7 @@ impl std::fmt::Display for Syntax {
 			Self::StmtBind => write!(f, "binding statement"),
 			Self::StmtBreak => write!(f, "`break` statement"),
 			Self::StmtContinue => write!(f, "`continue` statement"),
+			Self::StmtDefer => write!(f, "`defer` statement"),
 			Self::StmtExpr => write!(f, "expression statement"),
 			Self::StmtReturn => write!(f, "`return` statement"),
 			Self::PatGrouped => write!(f, "grouped pattern"),
@@ -535,6 +544,7 @@ impl std::fmt::Display for Syntax {
 			Self::KwBreak => write!(f, "`break`"),
 			Self::KwConst => write!(f, "`const`"),
 			Self::KwContinue => write!(f, "`continue`"),
+			Self::KwDefer => write!(f, "`defer`"),
 			Self::KwFunction => write!(f, "`function`"),
 			Self::KwLet => write!(f, "`let`"),
 			Self::KwReturn => write!(f, "`return`"),
@@ -0,0 +1,96 @@
+//! Text chat between players and the server operator.
+
+use std::{
+	collections::{HashMap, VecDeque},
+	time::{Duration, Instant},
+};
+
+use viletech::{net::msg::ServerMessage, tracing::info};
+
+use crate::ServerCore;
+
+/// Used as [`ServerMessage::ChatBroadcast::from`] for messages sent via the
+/// server's own console.
+pub const SERVER_ID: u64 = u64::MAX;
+
+/// Longer messages get truncated, not rejected.
+pub const MAX_CHARS: usize = 256;
+
+/// A client may send at most [`RATE_LIMIT_COUNT`] messages within this span of time.
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(10);
+pub const RATE_LIMIT_COUNT: usize = 5;
+
+#[derive(Debug, Default)]
+pub struct Chat {
+	/// When each client's recent messages were received.
+	/// Only times within [`RATE_LIMIT_WINDOW`] of the latest message are kept.
+	history: HashMap<u64, VecDeque<Instant>>,
+}
+
+impl Chat {
+	/// Returns `None` if the message was dropped, either because the sender hit
+	/// the rate limit or because nothing was left of it after sanitization.
+	#[must_use]
+	pub fn receive(&mut self, from: u64, text: &str, now: Instant) -> Option<ServerMessage> {
+		let times = self.history.entry(from).or_default();
+
+		while times
+			.front()
+			.is_some_and(|t| now.duration_since(*t) >= RATE_LIMIT_WINDOW)
+		{
+			times.pop_front();
+		}
+
+		if times.len() >= RATE_LIMIT_COUNT {
+			info!("Chat rate limit hit for client {from}");
+			return None;
+		}
+
+		times.push_back(now);
+
+		let text = sanitize(text);
+
+		if text.is_empty() {
+			return None;
+		}
+
+		Some(ServerMessage::ChatBroadcast { from, text })
+	}
+
+	/// Call when a client disconnects.
+	pub fn forget(&mut self, client: u64) {
+		self.history.remove(&client);
+	}
+}
+
+impl ServerCore {
+	/// Handles a [`ClientMessage::ChatMessage`], broadcasting it to every client
+	/// unless it gets dropped by [`Chat::receive`].
+	///
+	/// [`ClientMessage::ChatMessage`]: viletech::net::msg::ClientMessage::ChatMessage
+	pub fn receive_chat(&mut self, from: u64, text: &str) {
+		if let Some(msg) = self.chat.receive(from, text, Instant::now()) {
+			self.broadcast(msg);
+		}
+	}
+}
+
+/// A message from the server operator. Not subject to rate limiting.
+#[must_use]
+pub fn from_server(text: &str) -> ServerMessage {
+	ServerMessage::ChatBroadcast {
+		from: SERVER_ID,
+		text: sanitize(text),
+	}
+}
+
+/// Strips ASCII control characters and truncates to [`MAX_CHARS`].
+#[must_use]
+pub fn sanitize(text: &str) -> String {
+	text.chars()
+		.filter(|c| !c.is_ascii_control())
+		.take(MAX_CHARS)
+		.collect::<String>()
+		.trim()
+		.to_string()
+}
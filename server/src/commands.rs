@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, time::Duration};
 
 use indoc::formatdoc;
 use viletech::{
@@ -7,7 +7,7 @@ use viletech::{
 	util::duration_to_hhmmss,
 };
 
-use crate::ServerCore;
+use crate::{chat, ServerCore};
 
 pub enum Request {
 	None,
@@ -112,6 +112,25 @@ pub fn _cmd_args(args: CommandArgs) -> Request {
 	Request::None
 }
 
+pub fn _cmd_chat(args: CommandArgs) -> Request {
+	if args.name_only() || args.help_requested() {
+		println!(
+			"Usage: {} <message>\r\n\r\n\
+			Sends a chat message to all connected clients.",
+			args.command_name()
+		);
+		return Request::None;
+	}
+
+	let text = args.concat(1);
+
+	_req_callback(move |core| {
+		let msg = chat::from_server(&text);
+		info!("[Server] {text}");
+		core.broadcast(msg);
+	})
+}
+
 pub fn _cmd_help(args: CommandArgs) -> Request {
 	if args.help_requested() {
 		println!(
@@ -157,6 +176,33 @@ pub fn _cmd_quit(args: CommandArgs) -> Request {
 	Request::Exit
 }
 
+pub fn _cmd_status(args: CommandArgs) -> Request {
+	if args.help_requested() {
+		println!(
+			"Prints the server's uptime, players, current map, and dataset checksum.\r\n\
+			This is the same information served by `--status-port`."
+		);
+		return Request::None;
+	}
+
+	_req_callback(|core| {
+		let status = core.status();
+		let (hh, mm, ss) = duration_to_hhmmss(Duration::from_secs(status.uptime_secs));
+
+		info!(
+			"Uptime: {hh:02}:{mm:02}:{ss:02}\r\n\
+			Players: {}/{} {:?}\r\n\
+			Map: {}\r\n\
+			Dataset checksum: {}",
+			status.player_count(),
+			status.max_clients,
+			status.players,
+			status.map.as_deref().unwrap_or("(lobby)"),
+			status.dataset_checksum.as_deref().unwrap_or("(none)"),
+		);
+	})
+}
+
 pub fn _cmd_uptime(args: CommandArgs) -> Request {
 	if args.help_requested() {
 		println!("Prints the current cumulative uptime of the application.");
@@ -1,15 +1,35 @@
 //! VileTech Dedicated Server
 
+mod chat;
 mod commands;
+mod manifest;
+mod pawn;
+mod status;
 
-use std::{error::Error, time::Instant};
+use std::{
+	collections::HashMap,
+	error::Error,
+	path::PathBuf,
+	sync::{Arc, RwLock},
+	time::Instant,
+};
 
 use bevy::prelude::*;
 use clap::Parser;
 use indoc::printdoc;
-use viletech::{terminal::Terminal, util::duration_to_hhmmss};
+use viletech::{
+	bench::BenchSpec,
+	net::{
+		manifest::{ContentManifest, ExtraMountPolicy},
+		msg::ServerMessage,
+	},
+	player::predict::Authority,
+	terminal::{CommandOutput, ExecError, Terminal},
+	user::AUTOEXEC_FILENAME,
+	util::duration_to_hhmmss,
+};
 
-use commands::Command;
+use commands::{Command, Request};
 
 #[must_use]
 pub fn version_string() -> String {
@@ -20,6 +40,26 @@ pub fn version_string() -> String {
 pub struct ServerCore {
 	pub start_time: Instant,
 	pub terminal: Terminal<Command>,
+	pub max_clients: usize,
+	/// Shared with the status endpoint thread, if there is one.
+	/// See [`ServerCore::refresh_status`].
+	pub status: status::SharedSnapshot,
+	pub chat: chat::Chat,
+	/// Messages waiting to be sent to every connected client.
+	pub outbox: Vec<ServerMessage>,
+	/// Everything the server has loaded, in load order.
+	pub manifest: ContentManifest,
+	/// Set by `--strict-mounts`.
+	pub extra_mounts: ExtraMountPolicy,
+	/// Each connected client's pawn, keyed by client ID.
+	pub pawns: HashMap<u64, Authority>,
+}
+
+impl ServerCore {
+	/// Queues `msg` to be sent to every connected client.
+	pub fn broadcast(&mut self, msg: ServerMessage) {
+		self.outbox.push(msg);
+	}
 }
 
 #[derive(clap::Parser, Debug)]
@@ -56,6 +96,90 @@ struct LaunchArgs {
 	/// If not set, this defaults to 6666.
 	#[clap(long, value_parser, default_value_t = 6666)]
 	port: u16,
+	/// Serves a JSON status report over UDP on this port
+	///
+	/// Send the datagram `status` to receive uptime, players, the current map,
+	/// and the dataset checksum. Requests are rate-limited per address.
+	#[clap(long, value_parser)]
+	status_port: Option<u16>,
+	/// Rejects clients which have loaded content that the server has not
+	///
+	/// By default, such clients are admitted and a warning gets logged.
+	/// Clients missing content or with different versions of it are always rejected.
+	#[arg(long)]
+	strict_mounts: bool,
+	/// Runs a benchmark, writes a JSON report to the working directory, and exits.
+	///
+	/// Expects `;`-separated `key=value` pairs, such as
+	/// `load=doom2.wad,mymod.pk3;ticks=350;seed=1`.
+	/// Either `ticks` or `demo` is required.
+	#[clap(long)]
+	bench: Option<BenchSpec>,
+	/// Skips running `autoexec.cfg` from the user directory at startup.
+	#[arg(long)]
+	no_autoexec: bool,
+}
+
+/// Runs `autoexec.cfg` from the user directory, if there is one.
+/// Returns `false` if one of its commands requested that the server exit.
+fn autoexec(core: &mut ServerCore) -> bool {
+	struct Sink {
+		dir: PathBuf,
+		requests: Vec<Request>,
+	}
+
+	impl CommandOutput<Request> for Sink {
+		fn output(&mut self, output: Request) {
+			self.requests.push(output);
+		}
+
+		fn error(&mut self, error: ExecError) {
+			error!("{error}");
+		}
+
+		/// Relative paths are resolved against the user directory.
+		fn read(&mut self, path: &str) -> Result<String, String> {
+			std::fs::read_to_string(self.dir.join(path)).map_err(|err| err.to_string())
+		}
+	}
+
+	let portable = viletech::user::user_dir_portable();
+	let home = viletech::user::user_dir_home();
+
+	let Some(dir) = viletech::user::select_user_dir(&portable, &home) else {
+		return true;
+	};
+
+	let path = dir.join(AUTOEXEC_FILENAME);
+
+	let source = match std::fs::read_to_string(&path) {
+		Ok(s) => s,
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => return true,
+		Err(err) => {
+			error!("Failed to read `{}`: {err}", path.display());
+			return true;
+		}
+	};
+
+	info!("Running: {}", path.display());
+
+	let mut sink = Sink {
+		dir,
+		requests: vec![],
+	};
+
+	core.terminal
+		.exec_lines(&source, AUTOEXEC_FILENAME, &mut sink);
+
+	for request in sink.requests {
+		match request {
+			Request::None => {}
+			Request::Exit => return false,
+			Request::Callback(func) => (func)(core),
+		}
+	}
+
+	true
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
@@ -76,9 +200,54 @@ VileTech Server {s_vers}
 		return Ok(());
 	}
 
+	viletech::crash::install(viletech::crash::default_dir(), |_| {});
 	viletech::thread_pool_init(args.threads);
+
+	if let Some(spec) = &args.bench {
+		let report = viletech::bench::run_headless(spec)?;
+		println!("{report}");
+		let path = report.write_json(&std::env::current_dir()?)?;
+		println!("Report written to: {}", path.display());
+		return Ok(());
+	}
+
 	viletech::log::init_diag(&version_string())?;
 
+	let mut core = ServerCore {
+		start_time,
+		terminal: Terminal::new(|key| {
+			info!("Unknown command: {key}");
+		}),
+		max_clients: args.max_clients,
+		status: Arc::new(RwLock::new(status::Snapshot::default())),
+		chat: chat::Chat::default(),
+		outbox: vec![],
+		// TODO: Populate this once the server loads content.
+		manifest: ContentManifest::default(),
+		extra_mounts: if args.strict_mounts {
+			ExtraMountPolicy::Reject
+		} else {
+			ExtraMountPolicy::Warn
+		},
+		pawns: HashMap::new(),
+	};
+
+	if !args.no_autoexec && !autoexec(&mut core) {
+		return Ok(());
+	}
+
+	core.refresh_status();
+
+	if let Some(port) = args.status_port {
+		let endpoint = status::spawn(port, core.status.clone())?;
+
+		// TODO: Fold this into the lobby loop once the server has one.
+		while !endpoint.is_finished() {
+			std::thread::sleep(status::REFRESH_INTERVAL);
+			core.refresh_status();
+		}
+	}
+
 	// (RAT) In my experience, a runtime log is much more informative if it
 	// states the duration for which the program executed.
 	let uptime = start_time.elapsed();
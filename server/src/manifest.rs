@@ -0,0 +1,40 @@
+//! Verifying that connecting clients have loaded the same content as the server.
+
+use viletech::{
+	net::{
+		manifest::{ManifestReport, Verdict},
+		msg::ServerMessage,
+	},
+	tracing::{info, warn},
+};
+
+use crate::ServerCore;
+
+impl ServerCore {
+	/// Sent to each client as it connects.
+	#[must_use]
+	pub fn manifest_message(&self) -> ServerMessage {
+		ServerMessage::ContentManifest(self.manifest.clone())
+	}
+
+	/// Handles a [`ClientMessage::ManifestReport`].
+	/// Returns `false` if the sending client should be disconnected.
+	///
+	/// [`ClientMessage::ManifestReport`]: viletech::net::msg::ClientMessage::ManifestReport
+	#[must_use]
+	pub fn receive_manifest_report(&self, from: u64, report: &ManifestReport) -> bool {
+		match report.verdict(self.extra_mounts) {
+			Verdict::Match => true,
+			Verdict::Warn => {
+				warn!("Client {from} has loaded content the server has not:\r\n{report}");
+				true
+			}
+			Verdict::Reject => {
+				info!(
+					"Rejecting client {from}; its content differs from the server's:\r\n{report}"
+				);
+				false
+			}
+		}
+	}
+}
@@ -0,0 +1,30 @@
+//! Authoritative movement of each client's pawn, driven only by that client's input.
+
+use viletech::{net::msg::PlayerState, player::predict::Input};
+
+use crate::ServerCore;
+
+impl ServerCore {
+	/// Handles a [`ClientMessage::Inputs`].
+	///
+	/// [`ClientMessage::Inputs`]: viletech::net::msg::ClientMessage::Inputs
+	pub fn receive_inputs(&mut self, from: u64, inputs: &[Input]) {
+		// TODO: Start new pawns at a player start once the server loads levels.
+		self.pawns.entry(from).or_default().receive(inputs);
+	}
+
+	/// To be sent to `client` as part of each [`ServerMessage::TickUpdate`].
+	/// `None` if none of its input has been processed yet.
+	///
+	/// [`ServerMessage::TickUpdate`]: viletech::net::msg::ServerMessage::TickUpdate
+	#[must_use]
+	pub fn player_state(&self, client: u64) -> Option<PlayerState> {
+		let pawn = self.pawns.get(&client)?;
+		Some(PlayerState::new(pawn.state(), pawn.ack()?))
+	}
+
+	/// Call when a client disconnects.
+	pub fn forget_pawn(&mut self, client: u64) {
+		self.pawns.remove(&client);
+	}
+}
@@ -0,0 +1,237 @@
+//! A read-only, machine-readable status endpoint for server operators.
+//!
+//! When the server is launched with `--status-port`, a thread listens on that
+//! UDP port. Any datagram consisting of exactly [`REQUEST`] receives a reply
+//! containing a JSON-serialized [`Snapshot`]. Anything else is ignored.
+
+use std::{
+	collections::HashMap,
+	io,
+	net::{SocketAddr, UdpSocket},
+	sync::{Arc, RwLock},
+	thread::JoinHandle,
+	time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use viletech::tracing::{error, info, warn};
+
+use crate::ServerCore;
+
+/// The only request the status endpoint understands.
+pub const REQUEST: &[u8] = b"status";
+
+/// A client asking more often than this gets ignored.
+pub const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The most replies the endpoint will send within one [`MIN_INTERVAL`],
+/// regardless of how many different addresses are asking.
+pub const MAX_REPLIES_PER_INTERVAL: usize = 64;
+
+/// How often the main thread updates the [`SharedSnapshot`] while nothing
+/// else is driving [`ServerCore::refresh_status`].
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Everything reported by the status endpoint and the `status` command.
+/// Gathered by [`ServerCore::status`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Snapshot {
+	pub version: String,
+	pub uptime_secs: u64,
+	pub max_clients: usize,
+	pub players: Vec<String>,
+	/// `None` while in the lobby.
+	pub map: Option<String>,
+	/// Hex-encoded. `None` if no data has been loaded.
+	pub dataset_checksum: Option<String>,
+}
+
+impl Snapshot {
+	#[must_use]
+	pub fn player_count(&self) -> usize {
+		self.players.len()
+	}
+
+	#[must_use]
+	pub fn to_json(&self) -> String {
+		#[derive(Serialize)]
+		struct Output<'s> {
+			#[serde(flatten)]
+			snapshot: &'s Snapshot,
+			player_count: usize,
+		}
+
+		serde_json::to_string(&Output {
+			snapshot: self,
+			player_count: self.player_count(),
+		})
+		.expect("status snapshot serialization failed")
+	}
+}
+
+/// Written to by the main thread each lobby tick; read by the status thread.
+pub type SharedSnapshot = Arc<RwLock<Snapshot>>;
+
+/// Binds to `port` on all interfaces and serves `snapshot` until the process exits.
+pub fn spawn(port: u16, snapshot: SharedSnapshot) -> io::Result<JoinHandle<()>> {
+	let socket = UdpSocket::bind(("0.0.0.0", port))?;
+	info!("Status endpoint listening on UDP port {port}.");
+
+	std::thread::Builder::new()
+		.name("viletech-status".to_string())
+		.spawn(move || serve(socket, snapshot))
+}
+
+fn serve(socket: UdpSocket, snapshot: SharedSnapshot) {
+	let mut limiter = RateLimiter::default();
+	let mut buf = [0; 64];
+
+	loop {
+		let (len, addr) = match socket.recv_from(&mut buf) {
+			Ok(pair) => pair,
+			Err(err) => {
+				warn!("Status endpoint failed to receive a request: {err}");
+				continue;
+			}
+		};
+
+		if &buf[..len] != REQUEST || !limiter.admit(addr, Instant::now()) {
+			continue;
+		}
+
+		let json = match snapshot.read() {
+			Ok(guard) => guard.to_json(),
+			Err(_) => {
+				error!("Status snapshot lock was poisoned; stopping the status endpoint.");
+				return;
+			}
+		};
+
+		if let Err(err) = socket.send_to(json.as_bytes(), addr) {
+			warn!("Status endpoint failed to reply to {addr}: {err}");
+		}
+	}
+}
+
+#[derive(Debug, Default)]
+struct RateLimiter {
+	last_reply: HashMap<SocketAddr, Instant>,
+	window_start: Option<Instant>,
+	window_replies: usize,
+}
+
+impl RateLimiter {
+	#[must_use]
+	fn admit(&mut self, addr: SocketAddr, now: Instant) -> bool {
+		if self
+			.window_start
+			.map_or(true, |start| now.duration_since(start) >= MIN_INTERVAL)
+		{
+			self.window_start = Some(now);
+			self.window_replies = 0;
+			self.last_reply
+				.retain(|_, last| now.duration_since(*last) < MIN_INTERVAL);
+		}
+
+		if self.window_replies >= MAX_REPLIES_PER_INTERVAL {
+			return false;
+		}
+
+		if self
+			.last_reply
+			.get(&addr)
+			.is_some_and(|last| now.duration_since(*last) < MIN_INTERVAL)
+		{
+			return false;
+		}
+
+		self.last_reply.insert(addr, now);
+		self.window_replies += 1;
+		true
+	}
+}
+
+impl ServerCore {
+	#[must_use]
+	pub fn status(&self) -> Snapshot {
+		Snapshot {
+			version: env!("CARGO_PKG_VERSION").to_string(),
+			uptime_secs: self.start_time.elapsed().as_secs(),
+			max_clients: self.max_clients,
+			players: vec![],
+			map: None,
+			dataset_checksum: None,
+		}
+	}
+
+	/// Call once per lobby tick so that the status endpoint stays current.
+	pub fn refresh_status(&self) {
+		let status = self.status();
+
+		if let Ok(mut guard) = self.status.write() {
+			*guard = status;
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use viletech::terminal::Terminal;
+
+	use super::*;
+
+	#[test]
+	fn json() {
+		let snapshot = Snapshot {
+			version: "0.0.0".to_string(),
+			uptime_secs: 3723,
+			max_clients: 8,
+			players: vec!["Flynn".to_string(), "Chex \"Warrior\"".to_string()],
+			map: Some("MAP01".to_string()),
+			dataset_checksum: None,
+		};
+
+		assert_eq!(
+			snapshot.to_json(),
+			r#"{"version":"0.0.0","uptime_secs":3723,"max_clients":8,"players":["Flynn","Chex \"Warrior\""],"map":"MAP01","dataset_checksum":null,"player_count":2}"#
+		);
+	}
+
+	#[test]
+	fn rate_limit() {
+		let mut limiter = RateLimiter::default();
+		let a: SocketAddr = "127.0.0.1:1000".parse().unwrap();
+		let b: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+		let t0 = Instant::now();
+
+		assert!(limiter.admit(a, t0));
+		assert!(!limiter.admit(a, t0 + Duration::from_millis(500)));
+		assert!(limiter.admit(b, t0 + Duration::from_millis(500)));
+		assert!(limiter.admit(a, t0 + MIN_INTERVAL));
+	}
+
+	#[test]
+	fn refresh() {
+		let mut core = ServerCore {
+			start_time: Instant::now(),
+			terminal: Terminal::new(|_| {}),
+			max_clients: 8,
+			status: SharedSnapshot::default(),
+			chat: crate::chat::Chat::default(),
+			outbox: vec![],
+			manifest: Default::default(),
+			extra_mounts: Default::default(),
+			pawns: Default::default(),
+		};
+
+		core.refresh_status();
+		let first = core.status.read().unwrap().clone();
+		assert_eq!(first.uptime_secs, 0);
+		assert_eq!(first.max_clients, 8);
+
+		core.start_time -= Duration::from_secs(90);
+		core.refresh_status();
+		let second = core.status.read().unwrap().clone();
+		assert!(second.uptime_secs >= 90);
+	}
+}
@@ -0,0 +1,225 @@
+//! Audio-related representations.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::Error;
+
+/// See <https://doomwiki.org/wiki/GENMIDI>.
+///
+/// The instrument bank used by DMX to play MIDI through an OPL2 (or OPL3) chip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenMidi {
+	/// Always [`Self::INSTRUMENT_COUNT`] long. The first 128 are for General MIDI's
+	/// melodic programs, in order; the rest are for the percussion notes from
+	/// [`Self::PERCUSSION_FIRST`] onwards, on MIDI channel 10.
+	pub instruments: Vec<OplInstrument>,
+}
+
+impl GenMidi {
+	pub const MAGIC: &'static [u8; 8] = b"#OPL_II#";
+	pub const INSTRUMENT_COUNT: usize = 175;
+	/// The MIDI note of the first percussion instrument (acoustic bass drum).
+	pub const PERCUSSION_FIRST: u8 = 35;
+
+	const INSTRUMENT_SIZE: usize = 36;
+	const NAME_SIZE: usize = 32;
+	const EXPECTED_SIZE: usize =
+		Self::MAGIC.len() + Self::INSTRUMENT_COUNT * (Self::INSTRUMENT_SIZE + Self::NAME_SIZE);
+
+	pub fn new(bytes: &[u8]) -> Result<Self, Error> {
+		if bytes.len() < Self::MAGIC.len() {
+			return Err(Error::MissingHeader {
+				expected: Self::MAGIC.len(),
+			});
+		}
+
+		if !bytes.starts_with(Self::MAGIC) {
+			return Err(Error::InvalidHeader {
+				details: "GENMIDI does not start with `#OPL_II#`",
+			});
+		}
+
+		if bytes.len() < Self::EXPECTED_SIZE {
+			return Err(Error::SizeMismatch {
+				expected: Self::EXPECTED_SIZE,
+				actual: bytes.len(),
+			});
+		}
+
+		let instrs = &bytes[Self::MAGIC.len()..];
+		let names = &instrs[(Self::INSTRUMENT_COUNT * Self::INSTRUMENT_SIZE)..];
+
+		let instruments = instrs
+			.chunks_exact(Self::INSTRUMENT_SIZE)
+			.zip(names.chunks_exact(Self::NAME_SIZE))
+			.take(Self::INSTRUMENT_COUNT)
+			.map(|(instr, name)| OplInstrument::read(instr, name))
+			.collect();
+
+		Ok(Self { instruments })
+	}
+
+	/// `program` is a General MIDI program number, counting from 0.
+	#[must_use]
+	pub fn melodic(&self, program: u8) -> Option<&OplInstrument> {
+		(program < 128).then(|| &self.instruments[program as usize])
+	}
+
+	/// Returns `None` if `note` has no percussion instrument.
+	#[must_use]
+	pub fn percussion(&self, note: u8) -> Option<&OplInstrument> {
+		let i = note.checked_sub(Self::PERCUSSION_FIRST)? as usize;
+		self.instruments.get(128 + i)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OplInstrument {
+	pub flags: OplInstrumentFlags,
+	pub fine_tuning: u8,
+	/// Only meaningful with [`OplInstrumentFlags::FIXED_PITCH`].
+	pub fixed_note: u8,
+	/// The second voice only gets used with [`OplInstrumentFlags::DOUBLE_VOICE`].
+	pub voices: [OplVoice; 2],
+	pub name: String,
+}
+
+impl OplInstrument {
+	#[must_use]
+	fn read(instr: &[u8], name: &[u8]) -> Self {
+		let name_len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+
+		Self {
+			flags: OplInstrumentFlags::from_bits_truncate(LittleEndian::read_u16(instr)),
+			fine_tuning: instr[2],
+			fixed_note: instr[3],
+			voices: [
+				OplVoice::read(&instr[4..20]),
+				OplVoice::read(&instr[20..36]),
+			],
+			name: String::from_utf8_lossy(&name[..name_len]).into_owned(),
+		}
+	}
+}
+
+bitflags::bitflags! {
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub struct OplInstrumentFlags: u16 {
+		/// Always play [`OplInstrument::fixed_note`], regardless of the MIDI note.
+		const FIXED_PITCH = 1 << 0;
+		/// Unused by DMX.
+		const DELAYED_VIBRATO = 1 << 1;
+		/// Play both of [`OplInstrument::voices`] at once.
+		const DOUBLE_VOICE = 1 << 2;
+	}
+}
+
+/// One two-operator OPL voice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OplVoice {
+	pub modulator: OplOperator,
+	/// The value of the feedback/connection register (`0xC0`).
+	pub feedback: u8,
+	pub carrier: OplOperator,
+	/// Added to each MIDI note before it gets played with this voice.
+	pub base_note_offset: i16,
+}
+
+impl OplVoice {
+	#[must_use]
+	fn read(bytes: &[u8]) -> Self {
+		Self {
+			modulator: OplOperator::read(&bytes[0..6]),
+			feedback: bytes[6],
+			carrier: OplOperator::read(&bytes[7..13]),
+			// Byte 13 is unused.
+			base_note_offset: LittleEndian::read_i16(&bytes[14..16]),
+		}
+	}
+}
+
+/// The values of one OPL operator's registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OplOperator {
+	/// Tremolo, vibrato, sustain, key scale rate, and frequency multiplier (`0x20`).
+	pub tremolo: u8,
+	/// Attack and decay rates (`0x60`).
+	pub attack: u8,
+	/// Sustain level and release rate (`0x80`).
+	pub sustain: u8,
+	/// Waveform select (`0xE0`).
+	pub waveform: u8,
+	/// Key scale level; the top two bits of register `0x40`.
+	pub scale: u8,
+	/// Output level; the bottom six bits of register `0x40`.
+	pub level: u8,
+}
+
+impl OplOperator {
+	#[must_use]
+	fn read(bytes: &[u8]) -> Self {
+		Self {
+			tremolo: bytes[0],
+			attack: bytes[1],
+			sustain: bytes[2],
+			waveform: bytes[3],
+			scale: bytes[4],
+			level: bytes[5],
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn genmidi() {
+		let mut bytes = GenMidi::MAGIC.to_vec();
+		bytes.resize(GenMidi::EXPECTED_SIZE, 0);
+
+		// Program 0, acoustic grand piano: double-voiced, with a base note offset.
+		let instr = &mut bytes[8..44];
+		instr[0] = 0b100;
+		instr[2] = 128;
+		instr[4..10].copy_from_slice(&[0x21, 0xF0, 0xF3, 0x01, 0x40, 0x1F]);
+		instr[10] = 0x0E;
+		instr[34..36].copy_from_slice(&(-12_i16).to_le_bytes());
+
+		// The last instrument: percussion note 81, open triangle.
+		let instr = &mut bytes[(8 + 174 * 36)..(8 + 175 * 36)];
+		instr[0] = 0b1;
+		instr[3] = 60;
+
+		let names = 8 + 175 * 36;
+		bytes[names..(names + 13)].copy_from_slice(b"Acoustic Gran");
+
+		let genmidi = GenMidi::new(&bytes).unwrap();
+		assert_eq!(genmidi.instruments.len(), GenMidi::INSTRUMENT_COUNT);
+
+		let piano = genmidi.melodic(0).unwrap();
+		assert_eq!(piano.flags, OplInstrumentFlags::DOUBLE_VOICE);
+		assert_eq!(piano.fine_tuning, 128);
+		assert_eq!(piano.name, "Acoustic Gran");
+		assert_eq!(piano.voices[0].modulator.attack, 0xF0);
+		assert_eq!(piano.voices[0].modulator.level, 0x1F);
+		assert_eq!(piano.voices[0].feedback, 0x0E);
+		assert_eq!(piano.voices[1].base_note_offset, -12);
+
+		let triangle = genmidi.percussion(81).unwrap();
+		assert_eq!(triangle.flags, OplInstrumentFlags::FIXED_PITCH);
+		assert_eq!(triangle.fixed_note, 60);
+		assert!(genmidi.percussion(34).is_none());
+		assert!(genmidi.percussion(82).is_none());
+		assert!(genmidi.melodic(128).is_none());
+
+		assert!(matches!(
+			GenMidi::new(&bytes[..100]),
+			Err(Error::SizeMismatch { .. })
+		));
+		assert!(matches!(
+			GenMidi::new(b"#OPL_I##"),
+			Err(Error::InvalidHeader { .. })
+		));
+	}
+}
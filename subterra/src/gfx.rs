@@ -1,9 +1,11 @@
 //! Graphics-related representations.
 
+pub mod font;
+
 use std::io::Cursor;
 
 use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
-use util::{io::CursorExt, Id8};
+use util::Id8;
 
 use crate::Error;
 
@@ -215,13 +217,16 @@ impl std::ops::DerefMut for PatchTable {
 impl PatchTable {
 	/// Returns `Ok(None)` if the given PNAMES lump is valid,
 	/// but reports itself to have 0 records in it.
+	///
+	/// Each name must be 1 to 8 printable ASCII characters, padded with NUL bytes.
+	/// TEXTUREx refers to patches by their index in this table, so a malformed
+	/// name fails the whole lump rather than getting skipped.
 	pub fn new(bytes: &[u8]) -> Result<Option<Self>, Error> {
 		const RECORD_SIZE: usize = 8;
 
-		let mut invalid = false;
-
-		invalid |= bytes.len() < RECORD_SIZE;
-		invalid |= ((bytes.len() - 4) % RECORD_SIZE) != 0;
+		if bytes.len() < 4 {
+			return Err(Error::MissingHeader { expected: 4 });
+		}
 
 		let len = LittleEndian::read_u32(bytes) as usize;
 
@@ -229,30 +234,40 @@ impl PatchTable {
 			return Ok(None);
 		}
 
-		let expected = (len * RECORD_SIZE) + 4;
-		invalid |= bytes.len() != expected;
+		let expected = len.saturating_mul(RECORD_SIZE).saturating_add(4);
 
-		if invalid {
+		if bytes.len() != expected {
 			return Err(Error::SizeMismatch {
 				expected,
 				actual: bytes.len(),
 			});
 		}
 
-		let mut ret = Vec::with_capacity(len);
-		let mut pos = 4;
-
-		while pos < bytes.len() {
-			let raw = bytemuck::from_bytes::<[u8; RECORD_SIZE]>(&bytes[pos..(pos + RECORD_SIZE)]);
-
-			if let Some(pname) = util::read_id8(*raw) {
-				ret.push(pname);
-			}
+		bytes[4..]
+			.chunks_exact(RECORD_SIZE)
+			.enumerate()
+			.map(|(index, raw)| {
+				let raw = *bytemuck::from_bytes::<[u8; RECORD_SIZE]>(raw);
+				let name_len = raw.iter().position(|b| *b == 0).unwrap_or(RECORD_SIZE);
+
+				if name_len == 0 {
+					return Err(Error::InvalidRecord {
+						index,
+						details: "patch name is empty",
+					});
+				}
 
-			pos += RECORD_SIZE;
-		}
+				if !raw[..name_len].iter().all(|b| (0x20..=0x7E).contains(b)) {
+					return Err(Error::InvalidRecord {
+						index,
+						details: "patch name is not printable ASCII",
+					});
+				}
 
-		Ok(Some(Self(ret)))
+				Ok(util::read_id8(raw).unwrap())
+			})
+			.collect::<Result<Vec<_>, _>>()
+			.map(|pnames| Some(Self(pnames)))
 	}
 }
 
@@ -372,9 +387,21 @@ impl<'a> PictureReader<'a> {
 	}
 
 	/// `callback`'s first two parameters are a row and column index respectively.
-	pub fn read<F>(mut self, palette: &Palette, colormap: &ColorMap, mut callback: F)
+	pub fn read<F>(self, palette: &Palette, colormap: &ColorMap, mut callback: F)
 	where
 		F: FnMut(u32, u32, Rgb8),
+	{
+		self.read_indexed(|row, col, map_entry| {
+			let pal_entry = colormap[map_entry as usize];
+			callback(row, col, palette[pal_entry as usize]);
+		});
+	}
+
+	/// Like [`Self::read`], but `callback` receives palette indices as they are
+	/// stored in the picture, without applying a colormap or palette.
+	pub fn read_indexed<F>(mut self, mut callback: F)
+	where
+		F: FnMut(u32, u32, u8),
 	{
 		let mut cursor_pix = Cursor::new(self.bytes);
 		self.cursor_h.set_position(self.checkpoint);
@@ -396,11 +423,9 @@ impl<'a> PictureReader<'a> {
 
 				for ii in 0..(pixel_count as usize) {
 					let map_entry = cursor_pix.read_u8().unwrap();
-					let pal_entry = colormap[map_entry as usize];
-					let pixel = palette[pal_entry as usize];
 					let row = i as u32;
 					let col = (ii as u32) + (row_start as u32);
-					callback(row, col, pixel);
+					callback(row, col, map_entry);
 				}
 
 				let _ = cursor_pix.read_u8().unwrap(); // Dummy
@@ -448,32 +473,16 @@ pub struct TexPatch {
 }
 
 impl TextureX {
+	/// Returns `Ok(None)` if the given TEXTUREx lump is valid,
+	/// but reports itself to have 0 textures in it.
+	///
+	/// Patch indices are not checked against any [`PatchTable`] here, since a
+	/// TEXTUREx lump may be paired with a PNAMES lump from a later mount.
 	pub fn new(bytes: &[u8]) -> Result<Option<Self>, Error> {
-		#[repr(C)]
-		#[derive(Debug, Clone, Copy, PartialEq, Eq, bytemuck::AnyBitPattern)]
-		struct RawMapTexture {
-			name: [u8; 8],
-			/// C boolean, unused.
-			masked: i32,
-			width: i16,
-			height: i16,
-			/// Unused.
-			col_dir: i32,
-			patch_count: i16,
-		}
-
-		#[repr(C)]
-		#[derive(Debug, Clone, Copy, PartialEq, Eq, bytemuck::AnyBitPattern)]
-		struct RawMapPatch {
-			origin_x: i16,
-			origin_y: i16,
-			/// Index into [`PatchTable`].
-			patch: i16,
-			/// Unused.
-			stepdir: i16,
-			/// Unused.
-			colormap: i16,
-		}
+		/// Name, "masked" boolean, width, height, column directory, patch count.
+		const TEX_HEADER_SIZE: usize = 8 + 4 + 2 + 2 + 4 + 2;
+		/// X and Y origin, PNAMES index, "stepdir" and colormap (both unused).
+		const PATCH_SIZE: usize = 2 + 2 + 2 + 2 + 2;
 
 		if bytes.len() < 4 {
 			return Err(Error::MissingHeader { expected: 4 });
@@ -485,14 +494,20 @@ impl TextureX {
 			return Ok(None);
 		}
 
-		let mut curs_maptex = Cursor::new(bytes);
-		curs_maptex.set_position(curs_maptex.position() + 4);
+		let offsets_end = num_textures.saturating_mul(4).saturating_add(4);
+
+		if offsets_end > bytes.len() {
+			return Err(Error::MissingRecord {
+				expected: offsets_end,
+				actual: bytes.len(),
+			});
+		}
 
 		let mut ret = Vec::with_capacity(num_textures);
 
-		for _ in 0..num_textures {
-			let start = curs_maptex.read_i32::<LittleEndian>().unwrap() as usize;
-			let end = start + 22;
+		for offset in bytes[4..offsets_end].chunks_exact(4) {
+			let start = LittleEndian::read_u32(offset) as usize;
+			let end = start.saturating_add(TEX_HEADER_SIZE);
 
 			if end > bytes.len() {
 				return Err(Error::MissingRecord {
@@ -501,50 +516,30 @@ impl TextureX {
 				});
 			}
 
-			let mut curs_patch = Cursor::new(bytes);
-			curs_patch.set_position(start as u64);
-
-			let raw_tex = RawMapTexture {
-				name: *curs_patch.read_from_bytes::<[u8; 8]>(),
-				masked: curs_patch.read_i32::<LittleEndian>().unwrap(),
-				width: curs_patch.read_i16::<LittleEndian>().unwrap(),
-				height: curs_patch.read_i16::<LittleEndian>().unwrap(),
-				col_dir: curs_patch.read_i32::<LittleEndian>().unwrap(),
-				patch_count: curs_patch.read_i16::<LittleEndian>().unwrap(),
-			};
-
-			let patch_count = raw_tex.patch_count as usize;
-
-			debug_assert_eq!(curs_patch.position(), end as u64);
-
-			let mut patches = Vec::with_capacity(patch_count);
-
-			for _ in 0..patch_count {
-				let end = curs_patch.position() + (std::mem::size_of::<RawMapPatch>() as u64);
-
-				if end as usize > bytes.len() {
-					return Err(Error::MissingRecord {
-						expected: end as usize,
-						actual: bytes.len(),
-					});
-				}
-
-				let range = (curs_patch.position() as usize)..(end as usize);
-				let raw_patch = bytemuck::from_bytes::<RawMapPatch>(&bytes[range]);
+			let raw_tex = &bytes[start..end];
+			let patch_count = LittleEndian::read_u16(&raw_tex[20..22]) as usize;
+			let patches_end = end + (patch_count * PATCH_SIZE);
 
-				patches.push(TexPatch {
-					origin_x: raw_patch.origin_x as i32,
-					origin_y: raw_patch.origin_y as i32,
-					index: raw_patch.patch as usize,
+			if patches_end > bytes.len() {
+				return Err(Error::MissingRecord {
+					expected: patches_end,
+					actual: bytes.len(),
 				});
-
-				curs_patch.set_position(end);
 			}
 
+			let patches = bytes[end..patches_end]
+				.chunks_exact(PATCH_SIZE)
+				.map(|raw_patch| TexPatch {
+					origin_x: LittleEndian::read_i16(&raw_patch[0..2]) as i32,
+					origin_y: LittleEndian::read_i16(&raw_patch[2..4]) as i32,
+					index: LittleEndian::read_u16(&raw_patch[4..6]) as usize,
+				})
+				.collect();
+
 			ret.push(PatchedTex {
-				name: util::read_id8(raw_tex.name).unwrap_or_default(),
-				size_x: raw_tex.width as u32,
-				size_y: raw_tex.height as u32,
+				name: util::read_id8(*bytemuck::from_bytes(&raw_tex[0..8])).unwrap_or_default(),
+				size_x: LittleEndian::read_u16(&raw_tex[12..14]) as u32,
+				size_y: LittleEndian::read_u16(&raw_tex[14..16]) as u32,
 				patches,
 			});
 		}
@@ -564,4 +559,105 @@ mod test {
 		assert_eq!(reader.width(), 24);
 		assert_eq!(reader.height(), 29);
 	}
+
+	#[test]
+	fn patch_table() {
+		let mut bytes = 3_u32.to_le_bytes().to_vec();
+		bytes.extend_from_slice(b"WALL00_1");
+		bytes.extend_from_slice(b"DOOR2_4\0");
+		bytes.extend_from_slice(b"W13_1\0\0\0");
+
+		let pnames = PatchTable::new(&bytes).unwrap().unwrap();
+		assert_eq!(pnames.len(), 3);
+		assert_eq!(pnames[0].as_str(), "WALL00_1");
+		assert_eq!(pnames[1].as_str(), "DOOR2_4");
+		assert_eq!(pnames[2].as_str(), "W13_1");
+
+		assert!(PatchTable::new(&0_u32.to_le_bytes()).unwrap().is_none());
+
+		assert!(matches!(
+			PatchTable::new(&bytes[..20]),
+			Err(Error::SizeMismatch { .. })
+		));
+
+		assert!(matches!(
+			PatchTable::new(&bytes[..2]),
+			Err(Error::MissingHeader { .. })
+		));
+
+		bytes[12] = b'\0';
+
+		assert!(matches!(
+			PatchTable::new(&bytes),
+			Err(Error::InvalidRecord { index: 1, .. })
+		));
+
+		bytes[12] = b'D';
+		bytes[22] = 0x07;
+
+		assert!(matches!(
+			PatchTable::new(&bytes),
+			Err(Error::InvalidRecord { index: 2, .. })
+		));
+
+		// Spaces are printable, and some PWADs' patch names have them.
+		bytes[22] = b' ';
+		let pnames = PatchTable::new(&bytes).unwrap().unwrap();
+		assert_eq!(pnames[2].as_str(), "W1 _1");
+	}
+
+	#[test]
+	fn texturex() {
+		let mut bytes = vec![];
+		bytes.extend_from_slice(&2_u32.to_le_bytes());
+		bytes.extend_from_slice(&12_u32.to_le_bytes());
+		bytes.extend_from_slice(&54_u32.to_le_bytes());
+
+		// 128x72, with two patches.
+		bytes.extend_from_slice(b"BIGDOOR1");
+		bytes.extend_from_slice(&[0; 4]);
+		bytes.extend_from_slice(&128_u16.to_le_bytes());
+		bytes.extend_from_slice(&72_u16.to_le_bytes());
+		bytes.extend_from_slice(&[0; 4]);
+		bytes.extend_from_slice(&2_u16.to_le_bytes());
+
+		for (x, y, index) in [(0_i16, 0_i16, 3_u16), (64, -8, 4)] {
+			bytes.extend_from_slice(&x.to_le_bytes());
+			bytes.extend_from_slice(&y.to_le_bytes());
+			bytes.extend_from_slice(&index.to_le_bytes());
+			bytes.extend_from_slice(&[0; 4]);
+		}
+
+		// 64x128, with no patches.
+		bytes.extend_from_slice(b"SKY1\0\0\0\0");
+		bytes.extend_from_slice(&[0; 4]);
+		bytes.extend_from_slice(&64_u16.to_le_bytes());
+		bytes.extend_from_slice(&128_u16.to_le_bytes());
+		bytes.extend_from_slice(&[0; 4]);
+		bytes.extend_from_slice(&0_u16.to_le_bytes());
+
+		let texx = TextureX::new(&bytes).unwrap().unwrap();
+		assert_eq!(texx.len(), 2);
+
+		assert_eq!(texx[0].name.as_str(), "BIGDOOR1");
+		assert_eq!((texx[0].size_x, texx[0].size_y), (128, 72));
+		assert_eq!(texx[0].patches.len(), 2);
+		assert_eq!(texx[0].patches[1].origin_x, 64);
+		assert_eq!(texx[0].patches[1].origin_y, -8);
+		assert_eq!(texx[0].patches[1].index, 4);
+
+		assert_eq!(texx[1].name.as_str(), "SKY1");
+		assert_eq!((texx[1].size_x, texx[1].size_y), (64, 128));
+		assert!(texx[1].patches.is_empty());
+
+		assert!(matches!(
+			TextureX::new(&bytes[..8]),
+			Err(Error::MissingRecord { expected: 12, .. })
+		));
+
+		assert!(matches!(
+			TextureX::new(&bytes[..40]),
+			Err(Error::MissingRecord { .. })
+		));
+	}
 }
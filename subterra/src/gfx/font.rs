@@ -0,0 +1,838 @@
+//! Bitmap fonts.
+//!
+//! Supported sources:
+//! - ZDoom's [FON1 and FON2](https://zdoom.org/wiki/FON2) lumps.
+//! - Sets of picture-format lumps, one per character (e.g. Doom's `STCFNxxx`).
+//!
+//! Every glyph gets packed into a single [`Atlas`] of palette indices. Index 0 is
+//! always transparent, and the rest index into [`Font::palette`], which can be
+//! recolored with [`Font::translate`].
+
+use std::io::Cursor;
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use crate::Error;
+
+use super::{Palette, PictureReader, Rgb8};
+
+/// Glyphs get packed into rows of an [`Atlas`] at least this many pixels wide.
+pub const ATLAS_WIDTH: u32 = 256;
+/// The number of transparent pixels between each glyph in an [`Atlas`].
+pub const ATLAS_PADDING: u32 = 1;
+
+#[derive(Debug, Clone)]
+pub struct Font {
+	kind: FontKind,
+	height: u32,
+	/// Added to the advance of every glyph. May be negative.
+	kerning: i32,
+	/// Used for spaces if the font has no glyph for them.
+	space_width: u32,
+	/// Indexed by Latin-1 character code.
+	glyphs: Box<[Option<Glyph>; 256]>,
+	/// Drawn in place of any character the font has no glyph for.
+	fallback: Glyph,
+	atlas: Atlas,
+	palette: Vec<Rgb8>,
+}
+
+/// Which format a [`Font`] was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontKind {
+	/// Monospace, with generated grayscale shading.
+	Fon1,
+	/// Proportional, with its own palette.
+	Fon2,
+	/// One picture-format lump per character, using the game's palette.
+	Patches,
+}
+
+/// Where a glyph is in its font's [`Atlas`], and how it affects layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+	pub x: u32,
+	pub y: u32,
+	/// Zero for glyphs which are entirely transparent (e.g. spaces).
+	pub width: u32,
+	/// Zero for glyphs which are entirely transparent (e.g. spaces).
+	pub height: u32,
+	/// How far the pen moves right after this glyph, before kerning.
+	pub advance: u32,
+}
+
+/// Every glyph in a [`Font`], as palette indices. Index 0 is transparent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Atlas {
+	pub width: u32,
+	pub height: u32,
+	/// Row-major.
+	pub pixels: Vec<u8>,
+}
+
+/// One glyph positioned by [`Font::layout`]. Coordinates are unscaled and
+/// relative to the top-left corner of the laid-out text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlyphPlacement {
+	pub ch: char,
+	pub x: i32,
+	pub y: i32,
+	pub glyph: Glyph,
+	/// `true` if the font has no glyph for `ch`,
+	/// in which case `glyph` is the fallback box.
+	pub fallback: bool,
+}
+
+/// A gradient onto which [`Font::translate`] maps a font's shades,
+/// in the manner of ZDoom's [`TEXTCOLO`](https://zdoom.org/wiki/TEXTCOLO).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorRange {
+	/// What the darkest shade in the font becomes.
+	pub start: Rgb8,
+	/// What the brightest shade in the font becomes.
+	pub end: Rgb8,
+}
+
+impl Font {
+	#[must_use]
+	pub fn is_fon1(bytes: &[u8]) -> bool {
+		bytes.starts_with(b"FON1")
+	}
+
+	#[must_use]
+	pub fn is_fon2(bytes: &[u8]) -> bool {
+		bytes.starts_with(b"FON2")
+	}
+
+	/// See <https://zdoom.org/wiki/FON1>.
+	pub fn from_fon1(bytes: &[u8]) -> Result<Self, Error> {
+		const HEADER_SIZE: usize = 4 + 2 + 2;
+
+		if bytes.len() < HEADER_SIZE {
+			return Err(Error::MissingHeader {
+				expected: HEADER_SIZE,
+			});
+		}
+
+		if !Self::is_fon1(bytes) {
+			return Err(Error::InvalidHeader {
+				details: "missing `FON1` magic number",
+			});
+		}
+
+		let mut cursor = Cursor::new(bytes);
+		cursor.set_position(4);
+		let width = cursor.read_u16::<LittleEndian>().unwrap() as usize;
+		let height = cursor.read_u16::<LittleEndian>().unwrap() as usize;
+
+		if width == 0 || height == 0 {
+			return Err(Error::InvalidHeader {
+				details: "character width or height is 0",
+			});
+		}
+
+		let glyph_size = width * height;
+		let (pixels, _) =
+			unpack_byterun1(&bytes[HEADER_SIZE..], glyph_size * 256).map_err(|expected| {
+				Error::MissingRecord {
+					expected: HEADER_SIZE + expected,
+					actual: bytes.len(),
+				}
+			})?;
+
+		let mut raws = Vec::with_capacity(256);
+
+		for (code, chunk) in pixels.chunks_exact(glyph_size).enumerate() {
+			raws.push(RawGlyph {
+				code: code as u8,
+				width: width as u32,
+				height: height as u32,
+				advance: width as u32,
+				pixels: chunk.to_vec(),
+			});
+		}
+
+		// (RAT) FON1 has no palette; its pixels are shades from dark to bright.
+		let brightest = pixels.iter().copied().max().unwrap_or(0).max(1) as u32;
+		let palette = (0..=brightest)
+			.map(|i| {
+				let v = ((i * 255) / brightest) as u8;
+				Rgb8 { r: v, g: v, b: v }
+			})
+			.collect();
+
+		Ok(Self::assemble(
+			FontKind::Fon1,
+			height as u32,
+			0,
+			Some(width as u32),
+			raws,
+			palette,
+		))
+	}
+
+	/// See <https://zdoom.org/wiki/FON2>.
+	pub fn from_fon2(bytes: &[u8]) -> Result<Self, Error> {
+		const HEADER_SIZE: usize = 4 + 2 + 1 + 1 + 1 + 1 + 1 + 1;
+
+		if bytes.len() < HEADER_SIZE {
+			return Err(Error::MissingHeader {
+				expected: HEADER_SIZE,
+			});
+		}
+
+		if !Self::is_fon2(bytes) {
+			return Err(Error::InvalidHeader {
+				details: "missing `FON2` magic number",
+			});
+		}
+
+		let mut cursor = Cursor::new(bytes);
+		cursor.set_position(4);
+		let height = cursor.read_u16::<LittleEndian>().unwrap() as usize;
+		let first = cursor.read_u8().unwrap();
+		let last = cursor.read_u8().unwrap();
+		let constant_width = cursor.read_u8().unwrap() != 0;
+		let _shading = cursor.read_u8().unwrap();
+		let palette_len = cursor.read_u8().unwrap() as usize + 1;
+		let flags = cursor.read_u8().unwrap();
+
+		if last < first {
+			return Err(Error::InvalidHeader {
+				details: "last character precedes first character",
+			});
+		}
+
+		let count = (last - first) as usize + 1;
+
+		let expected = HEADER_SIZE
+			+ if (flags & 1) != 0 { 2 } else { 0 }
+			+ if constant_width { 2 } else { count * 2 }
+			+ (palette_len * 3);
+
+		if bytes.len() < expected {
+			return Err(Error::MissingRecord {
+				expected,
+				actual: bytes.len(),
+			});
+		}
+
+		let kerning = if (flags & 1) != 0 {
+			cursor.read_i16::<LittleEndian>().unwrap() as i32
+		} else {
+			0
+		};
+
+		let widths: Vec<u32> = if constant_width {
+			vec![cursor.read_u16::<LittleEndian>().unwrap() as u32; count]
+		} else {
+			(0..count)
+				.map(|_| cursor.read_u16::<LittleEndian>().unwrap() as u32)
+				.collect()
+		};
+
+		let palette: Vec<Rgb8> = (0..palette_len)
+			.map(|_| Rgb8 {
+				r: cursor.read_u8().unwrap(),
+				g: cursor.read_u8().unwrap(),
+				b: cursor.read_u8().unwrap(),
+			})
+			.collect();
+
+		let mut pos = cursor.position() as usize;
+		let mut raws = Vec::with_capacity(count);
+		let mut space_width = None;
+
+		for (i, width) in widths.into_iter().enumerate() {
+			let code = first + (i as u8);
+
+			if code == b' ' {
+				space_width = Some(width);
+			}
+
+			if width == 0 {
+				continue;
+			}
+
+			let (pixels, consumed) = unpack_byterun1(&bytes[pos..], width as usize * height)
+				.map_err(|expected| Error::MissingRecord {
+					expected: pos + expected,
+					actual: bytes.len(),
+				})?;
+
+			pos += consumed;
+
+			raws.push(RawGlyph {
+				code,
+				width,
+				height: height as u32,
+				advance: width,
+				pixels,
+			});
+		}
+
+		Ok(Self::assemble(
+			FontKind::Fon2,
+			height as u32,
+			kerning,
+			space_width.filter(|w| *w != 0),
+			raws,
+			palette,
+		))
+	}
+
+	/// Composes a font out of one [picture](PictureReader) per character,
+	/// such as Doom's `STCFNxxx` lumps (see [`stcfn_char`]). Characters outside
+	/// of Latin-1 are ignored.
+	pub fn from_patches<'b>(
+		patches: impl IntoIterator<Item = (char, &'b [u8])>,
+		palette: &Palette,
+	) -> Result<Self, Error> {
+		// Maps game palette indices to font palette indices, since the latter
+		// reserves 0 for transparency. 0 means unassigned.
+		let mut remap = [0_u8; 256];
+		let mut font_pal = vec![Rgb8 { r: 0, g: 0, b: 0 }];
+		let mut raws = vec![];
+		let mut height = 0;
+
+		for (ch, bytes) in patches {
+			let Ok(code) = u8::try_from(u32::from(ch)) else {
+				continue;
+			};
+
+			let reader = PictureReader::new(bytes)?;
+			let width = reader.width() as u32;
+			let h = reader.height() as u32;
+			let mut pixels = vec![0; (width * h) as usize];
+
+			reader.read_indexed(|x, y, index| {
+				if y >= h {
+					return;
+				}
+
+				if remap[index as usize] == 0 && font_pal.len() < 256 {
+					remap[index as usize] = font_pal.len() as u8;
+					font_pal.push(palette[index as usize]);
+				}
+
+				pixels[(y * width + x) as usize] = remap[index as usize];
+			});
+
+			height = height.max(h);
+
+			raws.push(RawGlyph {
+				code,
+				width,
+				height: h,
+				advance: width,
+				pixels,
+			});
+		}
+
+		raws.sort_by_key(|raw| raw.code);
+		raws.dedup_by_key(|raw| raw.code);
+
+		Ok(Self::assemble(
+			FontKind::Patches,
+			height,
+			0,
+			None,
+			raws,
+			font_pal,
+		))
+	}
+
+	#[must_use]
+	fn assemble(
+		kind: FontKind,
+		height: u32,
+		kerning: i32,
+		space_width: Option<u32>,
+		mut raws: Vec<RawGlyph>,
+		palette: Vec<Rgb8>,
+	) -> Self {
+		let brightest = palette
+			.iter()
+			.enumerate()
+			.skip(1)
+			.max_by_key(|(_, rgb)| luminance(**rgb))
+			.map_or(1, |(i, _)| i as u8);
+
+		// (RAT) The fallback is an outlined box roughly the proportions of a
+		// capital letter, so that missing glyphs are obvious but unobtrusive.
+		let fb_height = height.max(3);
+		let fb_width = (fb_height / 2).max(3);
+		let mut fb_pixels = vec![0; (fb_width * fb_height) as usize];
+
+		for y in 0..fb_height {
+			for x in 0..fb_width {
+				if x == 0 || y == 0 || x == (fb_width - 1) || y == (fb_height - 1) {
+					fb_pixels[(y * fb_width + x) as usize] = brightest;
+				}
+			}
+		}
+
+		raws.push(RawGlyph {
+			code: 0,
+			width: fb_width,
+			height: fb_height,
+			advance: fb_width,
+			pixels: fb_pixels,
+		});
+
+		// Shelf-pack everything in character code order, fallback last.
+
+		let atlas_width = raws
+			.iter()
+			.map(|raw| raw.width)
+			.max()
+			.unwrap_or(0)
+			.max(ATLAS_WIDTH);
+
+		let mut placed = Vec::with_capacity(raws.len());
+		let (mut x, mut y, mut row_height) = (0, 0, 0);
+
+		for raw in &raws {
+			if raw.is_blank() {
+				placed.push(Glyph {
+					x: 0,
+					y: 0,
+					width: 0,
+					height: 0,
+					advance: raw.advance,
+				});
+
+				continue;
+			}
+
+			if (x + raw.width) > atlas_width {
+				x = 0;
+				y += row_height + ATLAS_PADDING;
+				row_height = 0;
+			}
+
+			placed.push(Glyph {
+				x,
+				y,
+				width: raw.width,
+				height: raw.height,
+				advance: raw.advance,
+			});
+
+			x += raw.width + ATLAS_PADDING;
+			row_height = row_height.max(raw.height);
+		}
+
+		let mut atlas = Atlas {
+			width: atlas_width,
+			height: y + row_height,
+			pixels: vec![0; (atlas_width * (y + row_height)) as usize],
+		};
+
+		for (raw, glyph) in raws.iter().zip(&placed) {
+			for row in 0..glyph.height {
+				let src = (row * raw.width) as usize;
+				let dst = ((glyph.y + row) * atlas.width + glyph.x) as usize;
+
+				atlas.pixels[dst..(dst + glyph.width as usize)]
+					.copy_from_slice(&raw.pixels[src..(src + raw.width as usize)]);
+			}
+		}
+
+		let fallback = placed.pop().unwrap();
+		raws.pop();
+
+		let mut glyphs = Box::new([None; 256]);
+
+		for (raw, glyph) in raws.iter().zip(placed) {
+			glyphs[raw.code as usize] = Some(glyph);
+		}
+
+		let space_width = space_width.unwrap_or_else(|| {
+			glyphs[b'N' as usize].map_or(fb_width, |g: Glyph| g.advance.div_ceil(2))
+		});
+
+		Self {
+			kind,
+			height,
+			kerning,
+			space_width,
+			glyphs,
+			fallback,
+			atlas,
+			palette,
+		}
+	}
+
+	#[must_use]
+	pub fn kind(&self) -> FontKind {
+		self.kind
+	}
+
+	/// The height of one line of text, in pixels.
+	#[must_use]
+	pub fn height(&self) -> u32 {
+		self.height
+	}
+
+	#[must_use]
+	pub fn kerning(&self) -> i32 {
+		self.kerning
+	}
+
+	#[must_use]
+	pub fn atlas(&self) -> &Atlas {
+		&self.atlas
+	}
+
+	/// Index 0 is always meant to be transparent.
+	#[must_use]
+	pub fn palette(&self) -> &[Rgb8] {
+		&self.palette
+	}
+
+	/// See [`Self::glyph`].
+	#[must_use]
+	pub fn fallback(&self) -> &Glyph {
+		&self.fallback
+	}
+
+	/// Returns `None` if `ch` is outside of Latin-1 or this font has no glyph for it.
+	/// If an ASCII letter is missing, the opposite case gets tried too, since
+	/// Doom's own fonts have no lowercase letters.
+	#[must_use]
+	pub fn glyph(&self, ch: char) -> Option<&Glyph> {
+		let code = u8::try_from(u32::from(ch)).ok()?;
+
+		self.glyphs[code as usize].as_ref().or_else(|| {
+			if code.is_ascii_lowercase() {
+				self.glyphs[code.to_ascii_uppercase() as usize].as_ref()
+			} else if code.is_ascii_uppercase() {
+				self.glyphs[code.to_ascii_lowercase() as usize].as_ref()
+			} else {
+				None
+			}
+		})
+	}
+
+	#[must_use]
+	fn advance(&self, ch: char) -> u32 {
+		match self.glyph(ch) {
+			Some(glyph) => glyph.advance,
+			None if ch == ' ' => self.space_width,
+			None => self.fallback.advance,
+		}
+	}
+
+	/// The width and height of `text`'s bounding box when drawn at `scale`.
+	/// Each `\n` begins a new line; other control characters are ignored.
+	#[must_use]
+	pub fn measure(&self, text: &str, scale: u32) -> (u32, u32) {
+		if text.is_empty() {
+			return (0, 0);
+		}
+
+		let mut width = 0;
+		let mut lines = 0;
+
+		for line in text.split('\n') {
+			lines += 1;
+
+			let mut count = 0;
+			let mut w = 0;
+
+			for ch in line.chars().filter(|c| !c.is_control()) {
+				w += self.advance(ch) as i32;
+				count += 1;
+			}
+
+			if count > 0 {
+				w += self.kerning * (count - 1);
+			}
+
+			width = width.max(w.max(0) as u32);
+		}
+
+		(width * scale, lines * self.height * scale)
+	}
+
+	/// Yields a placement for every visible glyph in `text`.
+	/// Characters this font has no glyph for are placed as the [fallback box].
+	/// Each `\n` begins a new line; other control characters are ignored.
+	///
+	/// [fallback box]: Self::fallback
+	pub fn layout<'a>(&'a self, text: &'a str) -> impl Iterator<Item = GlyphPlacement> + 'a {
+		let mut pen_x = 0;
+		let mut pen_y = 0;
+
+		text.chars().filter_map(move |ch| {
+			if ch == '\n' {
+				pen_x = 0;
+				pen_y += self.height as i32;
+				return None;
+			}
+
+			if ch.is_control() {
+				return None;
+			}
+
+			let x = pen_x;
+			pen_x += self.advance(ch) as i32 + self.kerning;
+
+			let (glyph, fallback) = match self.glyph(ch) {
+				Some(g) => (*g, false),
+				None if ch == ' ' => return None,
+				None => (self.fallback, true),
+			};
+
+			if glyph.width == 0 || glyph.height == 0 {
+				return None;
+			}
+
+			Some(GlyphPlacement {
+				ch,
+				x,
+				y: pen_y,
+				glyph,
+				fallback,
+			})
+		})
+	}
+
+	/// Returns a copy of [`Self::palette`] wherein each color's luminance
+	/// relative to the rest of the palette decides where it falls in `range`.
+	/// Index 0 is left as-is.
+	#[must_use]
+	pub fn translate(&self, range: ColorRange) -> Vec<Rgb8> {
+		let lums = self.palette.iter().skip(1).map(|rgb| luminance(*rgb));
+		let min = lums.clone().min().unwrap_or(0);
+		let max = lums.max().unwrap_or(0);
+
+		let lerp = |a: u8, b: u8, t: u32, span: u32| -> u8 {
+			let (a, b) = (a as i32, b as i32);
+			(a + ((b - a) * t as i32) / span as i32) as u8
+		};
+
+		self.palette
+			.iter()
+			.enumerate()
+			.map(|(i, rgb)| {
+				if i == 0 {
+					return *rgb;
+				}
+
+				let (t, span) = if max == min {
+					(1, 1)
+				} else {
+					(luminance(*rgb) - min, max - min)
+				};
+
+				Rgb8 {
+					r: lerp(range.start.r, range.end.r, t, span),
+					g: lerp(range.start.g, range.end.g, t, span),
+					b: lerp(range.start.b, range.end.b, t, span),
+				}
+			})
+			.collect()
+	}
+}
+
+/// Given the name of a lump like `STCFN065`, returns the character it
+/// provides a glyph for (in this case, `A`).
+#[must_use]
+pub fn stcfn_char(lump_name: &str) -> Option<char> {
+	let digits = lump_name
+		.get(..5)
+		.filter(|pfx| pfx.eq_ignore_ascii_case("STCFN"))
+		.map(|_| &lump_name[5..])?;
+
+	digits.parse::<u8>().ok().map(char::from)
+}
+
+// Details /////////////////////////////////////////////////////////////////////
+
+#[derive(Debug)]
+struct RawGlyph {
+	code: u8,
+	width: u32,
+	height: u32,
+	advance: u32,
+	pixels: Vec<u8>,
+}
+
+impl RawGlyph {
+	#[must_use]
+	fn is_blank(&self) -> bool {
+		self.width == 0 || self.height == 0 || self.pixels.iter().all(|p| *p == 0)
+	}
+}
+
+/// Approximate perceived brightness, in the range `0..=255_000`.
+#[must_use]
+fn luminance(rgb: Rgb8) -> u32 {
+	(rgb.r as u32 * 299) + (rgb.g as u32 * 587) + (rgb.b as u32 * 114)
+}
+
+/// Decodes [ByteRun1](https://en.wikipedia.org/wiki/PackBits)-compressed data
+/// until `len` bytes have been output. Returns the output and the number of
+/// input bytes consumed. The `Err` variant gives how many input bytes were needed.
+fn unpack_byterun1(bytes: &[u8], len: usize) -> Result<(Vec<u8>, usize), usize> {
+	/// The most output one input byte can produce: a two-byte run yields up to 128.
+	const MAX_RATIO: usize = 64;
+
+	// (RAT) `len` comes from header fields, so a malformed lump could otherwise
+	// have this try to allocate far more than the machine has.
+	if len > bytes.len().saturating_mul(MAX_RATIO) {
+		return Err(len.div_ceil(MAX_RATIO));
+	}
+
+	let mut ret = Vec::with_capacity(len);
+	let mut pos = 0;
+
+	while ret.len() < len {
+		let Some(&code) = bytes.get(pos) else {
+			return Err(pos + 1);
+		};
+
+		pos += 1;
+		let code = code as i8;
+
+		if code >= 0 {
+			let run = code as usize + 1;
+
+			let Some(literal) = bytes.get(pos..(pos + run)) else {
+				return Err(pos + run);
+			};
+
+			ret.extend_from_slice(literal);
+			pos += run;
+		} else if code != -128 {
+			let Some(&b) = bytes.get(pos) else {
+				return Err(pos + 1);
+			};
+
+			ret.resize(ret.len() + (-(code as isize)) as usize + 1, b);
+			pos += 1;
+		}
+	}
+
+	ret.truncate(len);
+	Ok((ret, pos))
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Height 3; `A` is 2 wide, `B` is 3 wide, `C` is 1 wide. Kerning is 1.
+	#[must_use]
+	fn fon2() -> Vec<u8> {
+		let mut ret = b"FON2".to_vec();
+		ret.extend_from_slice(&3_u16.to_le_bytes()); // Height
+		ret.extend_from_slice(&[b'A', b'C', 0, 0]); // First, last, constant width, shading
+		ret.extend_from_slice(&[2, 1]); // Palette size - 1, flags
+		ret.extend_from_slice(&1_i16.to_le_bytes()); // Kerning
+
+		for w in [2_u16, 3, 1] {
+			ret.extend_from_slice(&w.to_le_bytes());
+		}
+
+		ret.extend_from_slice(&[0, 0, 0, 128, 128, 128, 255, 255, 255]);
+
+		// `A`: one literal run of 6.
+		ret.extend_from_slice(&[5, 1, 2, 2, 1, 1, 2]);
+		// `B`: one repeat run of 9.
+		ret.extend_from_slice(&[(-8_i8) as u8, 2]);
+		// `C`: a literal run of 1 and then a repeat run of 2.
+		ret.extend_from_slice(&[0, 1, (-1_i8) as u8, 2]);
+
+		ret
+	}
+
+	#[test]
+	fn fon2_decode() {
+		let font = Font::from_fon2(&fon2()).unwrap();
+
+		assert_eq!(font.kind(), FontKind::Fon2);
+		assert_eq!(font.height(), 3);
+		assert_eq!(font.kerning(), 1);
+		assert_eq!(font.palette().len(), 3);
+
+		let a = *font.glyph('A').unwrap();
+		let b = *font.glyph('B').unwrap();
+		let c = *font.glyph('C').unwrap();
+
+		assert_eq!((a.x, a.y, a.width, a.height, a.advance), (0, 0, 2, 3, 2));
+		assert_eq!((b.x, b.y, b.width, b.height, b.advance), (3, 0, 3, 3, 3));
+		assert_eq!((c.x, c.y, c.width, c.height, c.advance), (7, 0, 1, 3, 1));
+		assert_eq!(font.glyph('b'), Some(&b));
+		assert!(font.glyph('D').is_none());
+
+		let fb = *font.fallback();
+		assert_eq!((fb.x, fb.y, fb.width, fb.height), (9, 0, 3, 3));
+
+		let atlas = font.atlas();
+		assert_eq!((atlas.width, atlas.height), (ATLAS_WIDTH, 3));
+		assert_eq!(&atlas.pixels[0..10], &[1, 2, 0, 2, 2, 2, 0, 1, 0, 2]);
+		assert_eq!(&atlas.pixels[256..264], &[2, 1, 0, 2, 2, 2, 0, 2]);
+		assert_eq!(&atlas.pixels[512..520], &[1, 2, 0, 2, 2, 2, 0, 2]);
+	}
+
+	#[test]
+	fn huge_declared_size() {
+		let mut fon1 = b"FON1".to_vec();
+		fon1.extend_from_slice(&u16::MAX.to_le_bytes()); // Width
+		fon1.extend_from_slice(&u16::MAX.to_le_bytes()); // Height
+		fon1.extend_from_slice(&[(-127_i8) as u8, 0]);
+
+		assert!(matches!(
+			Font::from_fon1(&fon1),
+			Err(Error::MissingRecord { .. })
+		));
+
+		assert!(unpack_byterun1(&[(-127_i8) as u8, 0], 128).is_ok());
+		assert_eq!(unpack_byterun1(&[(-127_i8) as u8, 0], 129), Err(3));
+	}
+
+	#[test]
+	fn measure_and_layout() {
+		let font = Font::from_fon2(&fon2()).unwrap();
+
+		// Advances are 2 + 3 + 3 (fallback) + 1, plus 3 kerning.
+		assert_eq!(font.measure("AB?C", 1), (12, 3));
+		assert_eq!(font.measure("AB?C", 2), (24, 6));
+		assert_eq!(font.measure("A\nBC", 1), (5, 6));
+		assert_eq!(font.measure("", 1), (0, 0));
+
+		let placements: Vec<_> = font.layout("A?\nB").collect();
+		assert_eq!(placements.len(), 3);
+		assert_eq!((placements[0].x, placements[0].y), (0, 0));
+		assert!(!placements[0].fallback);
+		assert_eq!((placements[1].x, placements[1].y), (3, 0));
+		assert!(placements[1].fallback);
+		assert_eq!(placements[1].glyph, *font.fallback());
+		assert_eq!((placements[2].x, placements[2].y), (0, 3));
+	}
+
+	#[test]
+	fn translate() {
+		let font = Font::from_fon2(&fon2()).unwrap();
+
+		let red = font.translate(ColorRange {
+			start: Rgb8 { r: 64, g: 0, b: 0 },
+			end: Rgb8 { r: 255, g: 0, b: 0 },
+		});
+
+		assert_eq!(red[0], font.palette()[0]);
+		assert_eq!(red[1], Rgb8 { r: 64, g: 0, b: 0 });
+		assert_eq!(red[2], Rgb8 { r: 255, g: 0, b: 0 });
+	}
+
+	#[test]
+	fn stcfn() {
+		assert_eq!(stcfn_char("STCFN065"), Some('A'));
+		assert_eq!(stcfn_char("stcfn033"), Some('!'));
+		assert_eq!(stcfn_char("STCFN"), None);
+		assert_eq!(stcfn_char("STBAR"), None);
+	}
+}
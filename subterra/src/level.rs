@@ -1,6 +1,7 @@
 //! Code used for reading, storing, manipulating, and writing Doom levels.
 
 pub mod read;
+pub mod special;
 pub mod udmf;
 pub mod znbx;
 
@@ -11,7 +12,7 @@ use crate::EditorNum;
 /// Exists only to bundle multiple raw level data types to simplify other interfaces.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RawLevel<'r> {
-	pub linedefs: &'r [read::LineDefRaw],
+	pub linedefs: RawLineDefs<'r>,
 	pub nodes: &'r [read::NodeRaw],
 	pub sectors: &'r [read::SectorRaw],
 	pub segs: &'r [read::SegRaw],
@@ -21,6 +22,65 @@ pub struct RawLevel<'r> {
 	pub vertices: &'r [read::VertexRaw],
 }
 
+/// See [`RawLevel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawLineDefs<'r> {
+	Doom(&'r [read::LineDefRaw]),
+	Ext(&'r [read::LineDefExtRaw]),
+}
+
+impl<'r> RawLineDefs<'r> {
+	#[must_use]
+	pub fn len(&self) -> usize {
+		match self {
+			Self::Doom(linedefs) => linedefs.len(),
+			Self::Ext(linedefs) => linedefs.len(),
+		}
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns `None` if `index` is out of bounds.
+	#[must_use]
+	pub fn get(&self, index: usize) -> Option<LineDefGeom> {
+		match self {
+			Self::Doom(linedefs) => linedefs.get(index).map(|l| LineDefGeom {
+				start_vertex: l.start_vertex(),
+				end_vertex: l.end_vertex(),
+				flags: l.flags(),
+				right_side: l.right_side(),
+				left_side: l.left_side(),
+			}),
+			Self::Ext(linedefs) => linedefs.get(index).map(|l| LineDefGeom {
+				start_vertex: l.start_vertex(),
+				end_vertex: l.end_vertex(),
+				flags: l.flags(),
+				right_side: l.right_side(),
+				left_side: l.left_side(),
+			}),
+		}
+	}
+
+	pub fn iter(self) -> impl ExactSizeIterator<Item = LineDefGeom> + 'r {
+		(0..self.len()).map(move |i| self.get(i).unwrap())
+	}
+}
+
+/// The parts of a linedef which both [`read::LineDefRaw`] and
+/// [`read::LineDefExtRaw`] have, with their endianness already converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineDefGeom {
+	pub start_vertex: u16,
+	pub end_vertex: u16,
+	/// For a Hexen-format linedef, only the flags it shares with Doom.
+	pub flags: read::LineFlags,
+	pub right_side: u16,
+	pub left_side: Option<u16>,
+}
+
 /// See [`RawLevel`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RawThings<'r> {
@@ -59,6 +119,18 @@ pub enum Error {
 		linedef: usize,
 		lines_len: usize,
 	},
+	/// A GL seg tried to reference a non-existent partner seg.
+	InvalidSegPartner {
+		seg: usize,
+		partner: usize,
+		segs_len: usize,
+	},
+	/// A seg tried to reference a non-existent vertex.
+	InvalidSegVertex {
+		seg: usize,
+		vertex: usize,
+		verts_len: usize,
+	},
 	/// A BSP node tried to reference a non-existent child node.
 	InvalidSubnode {
 		node: usize,
@@ -113,6 +185,12 @@ pub enum Error {
 		which: SideTexture,
 		name: Id8,
 	},
+	/// A compressed extended node lump could not be inflated.
+	ZNodesDecompress(std::io::Error),
+	/// The first four bytes of an extended node lump were not any known signature.
+	ZNodesSignature([u8; 4]),
+	/// A compressed extended node lump inflated past [`read::ZNODES_INFLATE_LIMIT`].
+	ZNodesTooLarge,
 }
 
 impl std::fmt::Display for Error {
@@ -160,6 +238,28 @@ impl std::fmt::Display for Error {
 				but only {lines_len} linedefs exist"
 				)
 			}
+			Self::InvalidSegPartner {
+				seg,
+				partner,
+				segs_len,
+			} => {
+				write!(
+					f,
+					"seg {seg} references partner seg {partner}, \
+				but only {segs_len} segs exist"
+				)
+			}
+			Self::InvalidSegVertex {
+				seg,
+				vertex,
+				verts_len,
+			} => {
+				write!(
+					f,
+					"seg {seg} references vertex {vertex}, \
+				but only {verts_len} vertices exist"
+				)
+			}
 			Self::InvalidSidedefSector {
 				sidedef,
 				sector,
@@ -236,6 +336,23 @@ impl std::fmt::Display for Error {
 					"eidedef {sidedef} references non-existent {which} texture `{name}`"
 				)
 			}
+			Self::ZNodesDecompress(err) => {
+				write!(f, "failed to inflate compressed extended nodes: {err}")
+			}
+			Self::ZNodesSignature(sig) => {
+				write!(
+					f,
+					"unknown extended node signature: `{}`",
+					String::from_utf8_lossy(sig)
+				)
+			}
+			Self::ZNodesTooLarge => {
+				write!(
+					f,
+					"compressed extended nodes inflate past the limit of {} bytes",
+					read::ZNODES_INFLATE_LIMIT
+				)
+			}
 		}
 	}
 }
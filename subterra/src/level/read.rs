@@ -2,8 +2,9 @@
 //!
 //! ["map lumps"]: https://doomwiki.org/wiki/Lump#Standard_lumps
 
-use std::ops::Range;
+use std::{collections::HashMap, ops::Range};
 
+use smallvec::SmallVec;
 use util::{read_id8, Id8};
 
 use super::Error;
@@ -12,8 +13,9 @@ use super::Error;
 
 pub mod prelude {
 	pub use super::{
-		BspNodeChild, LineDefRaw, NodeRaw, SSectorRaw, SectorRaw, SegDirection, SegRaw, SideDefRaw,
-		ThingExtRaw, ThingFlags, ThingRaw, VertexRaw,
+		BspNodeChild, ExtNodeRaw, ExtSegRaw, ExtendedNodes, LineDefExtRaw, LineDefRaw, NodeRaw,
+		SSectorRaw, SectorRaw, SegDirection, SegRaw, SideDefRaw, ThingExtRaw, ThingFlags, ThingRaw,
+		VertexRaw,
 	};
 }
 
@@ -187,6 +189,88 @@ pub fn linedefs_lossy_mut(lump: &mut [u8]) -> Result<&mut [LineDefRaw], Error> {
 	Ok(bytemuck::cast_slice_mut(subslice))
 }
 
+// LINEDEFS, extended //////////////////////////////////////////////////////////
+
+/// See <https://doomwiki.org/wiki/Linedef#Hexen_format>. Acquired via [`linedefs_ext`].
+/// These are cast directly from the bytes of a WAD's lump;
+/// attached methods automatically convert from Little Endian.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, bytemuck::AnyBitPattern)]
+pub struct LineDefExtRaw {
+	v_start: u16,
+	v_end: u16,
+	flags: u16,
+	special: u8,
+	args: [u8; 5],
+	right: u16,
+	left: u16,
+}
+
+impl LineDefExtRaw {
+	/// To be used as an index into a slice of [`VertexRaw`].
+	#[must_use]
+	pub fn start_vertex(&self) -> u16 {
+		u16::from_le(self.v_start)
+	}
+
+	/// To be used as an index into a slice of [`VertexRaw`].
+	#[must_use]
+	pub fn end_vertex(&self) -> u16 {
+		u16::from_le(self.v_end)
+	}
+
+	/// Only the flags which Hexen shares with Doom. Bits 9 through 13 decide how
+	/// the line's special gets activated instead; see [`Self::raw_flags`].
+	#[must_use]
+	pub fn flags(&self) -> LineFlags {
+		LineFlags::from_bits_truncate((u16::from_le(self.flags) & 0x01FF) as u32)
+	}
+
+	/// The whole flags field, activation bits included.
+	#[must_use]
+	pub fn raw_flags(&self) -> u16 {
+		u16::from_le(self.flags)
+	}
+
+	#[must_use]
+	pub fn special(&self) -> u8 {
+		self.special
+	}
+
+	#[must_use]
+	pub fn args(&self) -> [u8; 5] {
+		self.args
+	}
+
+	/// a.k.a. the linedef's "front". To be used as an index into a slice of [`SideDefRaw`].
+	#[must_use]
+	pub fn right_side(&self) -> u16 {
+		u16::from_le(self.right)
+	}
+
+	/// a.k.a. the linedef's "back". To be used as an index into a slice of [`SideDefRaw`].
+	/// Returns `None` if the LE bytes of this value match the bit pattern `0xFFFF`.
+	#[must_use]
+	pub fn left_side(&self) -> Option<u16> {
+		let s = u16::from_le(self.left);
+		(s != 0xFFFF).then_some(s)
+	}
+}
+
+/// Casts a slice of raw bytes to extended line definitions (without allocating).
+/// Returns [`Error::MalformedFile`] if the length of `lump` is not divisible by 16.
+pub fn linedefs_ext(lump: &[u8]) -> Result<&[LineDefExtRaw], Error> {
+	if lump.is_empty() {
+		return Err(Error::EmptyFile("LINEDEFS (extended)"));
+	}
+
+	if (lump.len() % std::mem::size_of::<LineDefExtRaw>()) != 0 {
+		return Err(Error::MalformedFile("LINEDEFS (extended)"));
+	}
+
+	Ok(bytemuck::cast_slice(lump))
+}
+
 // NODES ///////////////////////////////////////////////////////////////////////
 
 /// See <https://doomwiki.org/wiki/Node>. Acquired via [`nodes`].
@@ -249,7 +333,7 @@ impl NodeRaw {
 }
 
 /// See [`NodeRaw`].
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BspNodeChild {
 	SubSector(usize),
@@ -787,6 +871,9 @@ impl ThingRaw {
 
 		// TODO: Strife thing flag support.
 
+		// (RAT) Doom has no player classes; things appear regardless of class.
+		flags.insert(ThingFlags::CLASS_1 | ThingFlags::CLASS_2 | ThingFlags::CLASS_3);
+
 		if (f & (1 << 0)) != 0 {
 			flags.insert(ThingFlags::SKILL_1 | ThingFlags::SKILL_2);
 		}
@@ -803,9 +890,11 @@ impl ThingRaw {
 			flags.insert(ThingFlags::AMBUSH);
 		}
 
-		if (f & (1 << 4)) != 0 {
-			flags.insert(ThingFlags::COOP);
-		} else {
+		// Bit 4 means "multiplayer only"; a thing is otherwise in every game mode
+		// until bits 5 (Boom's "not in deathmatch") or 6 ("not in co-op") rule it out.
+		flags.insert(ThingFlags::COOP | ThingFlags::DEATHMATCH);
+
+		if (f & (1 << 4)) == 0 {
 			flags.insert(ThingFlags::SINGLEPLAY);
 		}
 
@@ -911,8 +1000,6 @@ pub fn things_lossy_mut(lump: &mut [u8]) -> Result<&mut [ThingRaw], Error> {
 /// See <https://doomwiki.org/wiki/Thing#Hexen_format>. Acquired via [`things`].
 /// These are cast directly from the bytes of a WAD's lump;
 /// attached methods automatically convert from Little Endian.
-///
-/// Please note that due to padding, this type cannot be cast to mutable slices.
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, bytemuck::AnyBitPattern)]
 pub struct ThingExtRaw {
@@ -923,10 +1010,17 @@ pub struct ThingExtRaw {
 	angle: u16,
 	ednum: u16,
 	flags: i16,
+	special: u8,
 	args: [u8; 5],
 }
 
 impl ThingExtRaw {
+	/// The "thing ID" by which scripts can refer to this thing. 0 means "none".
+	#[must_use]
+	pub fn tid(&self) -> i16 {
+		i16::from_le(self.tid)
+	}
+
 	/// Returns, in order, X, Y, and Z coordinates.
 	#[must_use]
 	pub fn position(&self) -> [i16; 3] {
@@ -1000,6 +1094,12 @@ impl ThingExtRaw {
 		flags
 	}
 
+	/// The action special run when this thing dies (or is picked up).
+	#[must_use]
+	pub fn special(&self) -> u8 {
+		self.special
+	}
+
 	#[must_use]
 	pub fn args(&self) -> [u8; 5] {
 		self.args
@@ -1007,7 +1107,7 @@ impl ThingExtRaw {
 }
 
 /// Casts a slice of raw bytes to extended thing definitions (without allocating).
-/// Returns [`Error::MalformedFile`] if the length of `lump` is not divisible by 160.
+/// Returns [`Error::MalformedFile`] if the length of `lump` is not divisible by 20.
 pub fn things_ext(lump: &[u8]) -> Result<&[ThingExtRaw], Error> {
 	if lump.is_empty() {
 		return Err(Error::EmptyFile("THINGS (extended)"));
@@ -1033,6 +1133,23 @@ pub fn things_ext_lossy(lump: &[u8]) -> Result<&[ThingExtRaw], Error> {
 	Ok(bytemuck::cast_slice(subslice))
 }
 
+/// Maps each non-zero [TID](ThingExtRaw::tid) to the indices into `things` of
+/// every thing bearing it, in ascending order. For use by ACS' `Thing_*` functions.
+#[must_use]
+pub fn build_tid_index(things: &[ThingExtRaw]) -> HashMap<i16, SmallVec<[usize; 2]>> {
+	let mut ret = HashMap::<i16, SmallVec<[usize; 2]>>::new();
+
+	for (i, thing) in things.iter().enumerate() {
+		let tid = thing.tid();
+
+		if tid != 0 {
+			ret.entry(tid).or_default().push(i);
+		}
+	}
+
+	ret
+}
+
 // VERTEXES ////////////////////////////////////////////////////////////////////
 
 /// See <https://doomwiki.org/wiki/Vertex>. Acquired via [`vertexes`].
@@ -1111,3 +1228,591 @@ pub fn vertexes_lossy_mut(lump: &mut [u8]) -> &mut [VertexRaw] {
 	let subslice = &mut lump[..(count * sz)];
 	bytemuck::cast_slice_mut(subslice)
 }
+
+// ZNODES //////////////////////////////////////////////////////////////////////
+
+/// The most bytes which a compressed extended node lump is allowed to inflate to.
+/// Guards against a malicious (or broken) lump exhausting memory.
+pub const ZNODES_INFLATE_LIMIT: usize = 64 * 1024 * 1024;
+
+/// See <https://zdoom.org/wiki/Node#ZDoom_extended_nodes>. Acquired via [`znodes`].
+///
+/// Unlike the vanilla lumps, these get decoded into owned, native-endian data,
+/// since they may be compressed and their records are not fixed-size across formats.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtendedNodes {
+	pub format: ZNodesFormat,
+	/// `true` if the lump's signature began with `Z` rather than `X`.
+	pub compressed: bool,
+	/// The number of vertices in the level's `VERTEXES` lump. Seg vertex indices
+	/// at or past this number refer to [`Self::new_vertices`].
+	pub orig_vert_count: u32,
+	/// Vertices created by the node builder, in 16.16 fixed-point.
+	pub new_vertices: Vec<[i32; 2]>,
+	/// Each element is the range of [`Self::segs`] belonging to one subsector.
+	pub subsectors: Vec<Range<usize>>,
+	pub segs: Vec<ExtSegRaw>,
+	pub nodes: Vec<ExtNodeRaw>,
+}
+
+impl ExtendedNodes {
+	/// The total number of vertices which seg indices can refer to.
+	#[must_use]
+	pub fn vertex_count(&self) -> usize {
+		self.orig_vert_count as usize + self.new_vertices.len()
+	}
+}
+
+/// See [`ExtendedNodes::format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ZNodesFormat {
+	/// `XNOD` or `ZNOD`.
+	Normal,
+	/// `XGLN` or `ZGLN`. Segs carry partners, and mini-segs are included.
+	Gl,
+	/// `XGL2` or `ZGL2`. As with [`Self::Gl`], but linedef indices are 32 bits wide.
+	Gl2,
+	/// `XGL3` or `ZGL3`. As with [`Self::Gl2`], but node partition lines
+	/// are in 16.16 fixed-point.
+	Gl3,
+}
+
+impl ZNodesFormat {
+	/// Returns the format, and whether the rest of the lump is compressed.
+	#[must_use]
+	pub fn from_signature(sig: [u8; 4]) -> Option<(Self, bool)> {
+		let format = match &sig[1..] {
+			b"NOD" => Self::Normal,
+			b"GLN" => Self::Gl,
+			b"GL2" => Self::Gl2,
+			b"GL3" => Self::Gl3,
+			_ => return None,
+		};
+
+		match sig[0] {
+			b'X' => Some((format, false)),
+			b'Z' => Some((format, true)),
+			_ => None,
+		}
+	}
+
+	#[must_use]
+	pub fn is_gl(self) -> bool {
+		!matches!(self, Self::Normal)
+	}
+
+	#[must_use]
+	fn seg_size(self) -> usize {
+		match self {
+			Self::Normal | Self::Gl => 11,
+			Self::Gl2 | Self::Gl3 => 13,
+		}
+	}
+
+	#[must_use]
+	fn node_size(self) -> usize {
+		match self {
+			Self::Normal | Self::Gl | Self::Gl2 => 32,
+			Self::Gl3 => 40,
+		}
+	}
+}
+
+/// See [`ExtendedNodes::segs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtSegRaw {
+	pub v_start: u32,
+	/// GL formats do not store this; it gets taken from the start vertex of
+	/// the next seg in the same subsector.
+	pub v_end: u32,
+	/// The seg running along the other side of the same linedef.
+	/// Always `None` for [`ZNodesFormat::Normal`].
+	pub partner: Option<u32>,
+	/// `None` for a GL "mini-seg", which runs along no linedef.
+	pub linedef: Option<u32>,
+	pub direction: SegDirection,
+}
+
+/// See [`ExtendedNodes::nodes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtNodeRaw {
+	/// In 16.16 fixed-point, regardless of format.
+	pub seg_start: [i32; 2],
+	/// In 16.16 fixed-point, regardless of format.
+	pub seg_delta: [i32; 2],
+	/// Top, bottom, left, right.
+	pub aabb_r: [i16; 4],
+	pub aabb_l: [i16; 4],
+	pub child_r: BspNodeChild,
+	pub child_l: BspNodeChild,
+}
+
+/// Reads a ZDoom extended node lump, inflating it first if its signature
+/// starts with `Z`. Every index read gets checked against the counts which
+/// precede it in the lump; linedef indices are left for the caller to check.
+///
+/// Returns [`Error::MalformedFile`] if the lump ends early or has trailing
+/// segs owned by no subsector, [`Error::ZNodesSignature`] if the format is
+/// unrecognized, and [`Error::ZNodesTooLarge`] if inflating it would take more
+/// than [`ZNODES_INFLATE_LIMIT`] bytes.
+pub fn znodes(lump: &[u8]) -> Result<ExtendedNodes, Error> {
+	use std::io::Read;
+
+	if lump.is_empty() {
+		return Err(Error::EmptyFile("ZNODES"));
+	}
+
+	if lump.len() < 4 {
+		return Err(Error::MalformedFile("ZNODES"));
+	}
+
+	let sig = [lump[0], lump[1], lump[2], lump[3]];
+
+	let Some((format, compressed)) = ZNodesFormat::from_signature(sig) else {
+		return Err(Error::ZNodesSignature(sig));
+	};
+
+	let inflated;
+
+	let body = if compressed {
+		let mut buf = vec![];
+
+		flate2::read::ZlibDecoder::new(&lump[4..])
+			.take(ZNODES_INFLATE_LIMIT as u64 + 1)
+			.read_to_end(&mut buf)
+			.map_err(Error::ZNodesDecompress)?;
+
+		if buf.len() > ZNODES_INFLATE_LIMIT {
+			return Err(Error::ZNodesTooLarge);
+		}
+
+		inflated = buf;
+		&inflated[..]
+	} else {
+		&lump[4..]
+	};
+
+	let mut reader = ZNodesReader { bytes: body };
+
+	let orig_vert_count = reader.u32()?;
+	let new_vert_count = reader.count(8)?;
+	let mut new_vertices = Vec::with_capacity(new_vert_count);
+
+	for _ in 0..new_vert_count {
+		new_vertices.push([reader.i32()?, reader.i32()?]);
+	}
+
+	let ssector_count = reader.count(4)?;
+	let mut subsectors = Vec::with_capacity(ssector_count);
+	let mut seg0 = 0_usize;
+
+	for _ in 0..ssector_count {
+		let seg_count = reader.u32()? as usize;
+		let end = seg0.saturating_add(seg_count);
+		subsectors.push(seg0..end);
+		seg0 = end;
+	}
+
+	let seg_count = reader.count(format.seg_size())?;
+
+	if let Some((i, ssector)) = subsectors
+		.iter()
+		.enumerate()
+		.find(|(_, ss)| ss.end > seg_count)
+	{
+		return Err(Error::InvalidSubsectorSeg {
+			subsector: i,
+			seg: ssector.end - 1,
+			segs_len: seg_count,
+		});
+	}
+
+	if seg0 != seg_count {
+		return Err(Error::MalformedFile("ZNODES"));
+	}
+
+	let verts_len = orig_vert_count as usize + new_vertices.len();
+	let mut segs = Vec::with_capacity(seg_count);
+
+	for i in 0..seg_count {
+		let v_start = reader.u32()?;
+
+		let (v_end, partner) = if format.is_gl() {
+			let partner = reader.u32()?;
+			(v_start, (partner != u32::MAX).then_some(partner))
+		} else {
+			(reader.u32()?, None)
+		};
+
+		let linedef = match format {
+			ZNodesFormat::Normal | ZNodesFormat::Gl => {
+				let l = reader.u16()?;
+				(l != u16::MAX).then_some(l as u32)
+			}
+			ZNodesFormat::Gl2 | ZNodesFormat::Gl3 => {
+				let l = reader.u32()?;
+				(l != u32::MAX).then_some(l)
+			}
+		};
+
+		let direction = if reader.u8()? == 0 {
+			SegDirection::Front
+		} else {
+			SegDirection::Back
+		};
+
+		for vertex in [v_start, v_end] {
+			if vertex as usize >= verts_len {
+				return Err(Error::InvalidSegVertex {
+					seg: i,
+					vertex: vertex as usize,
+					verts_len,
+				});
+			}
+		}
+
+		if let Some(p) = partner.filter(|p| *p as usize >= seg_count) {
+			return Err(Error::InvalidSegPartner {
+				seg: i,
+				partner: p as usize,
+				segs_len: seg_count,
+			});
+		}
+
+		segs.push(ExtSegRaw {
+			v_start,
+			v_end,
+			partner,
+			linedef,
+			direction,
+		});
+	}
+
+	if format.is_gl() {
+		// Each GL seg ends where the next one in its subsector starts.
+		for ssector in subsectors.iter().filter(|ss| !ss.is_empty()) {
+			for i in ssector.clone() {
+				let next = if (i + 1) < ssector.end {
+					i + 1
+				} else {
+					ssector.start
+				};
+
+				segs[i].v_end = segs[next].v_start;
+			}
+		}
+	}
+
+	let node_count = reader.count(format.node_size())?;
+	let mut nodes = Vec::with_capacity(node_count);
+
+	for i in 0..node_count {
+		let (seg_start, seg_delta) = if format == ZNodesFormat::Gl3 {
+			(
+				[reader.i32()?, reader.i32()?],
+				[reader.i32()?, reader.i32()?],
+			)
+		} else {
+			(
+				[(reader.i16()? as i32) << 16, (reader.i16()? as i32) << 16],
+				[(reader.i16()? as i32) << 16, (reader.i16()? as i32) << 16],
+			)
+		};
+
+		let mut aabbs = [[0_i16; 4]; 2];
+
+		for coord in aabbs.iter_mut().flatten() {
+			*coord = reader.i16()?;
+		}
+
+		let mut children = [BspNodeChild::SubNode(0); 2];
+
+		for (c, child) in children.iter_mut().enumerate() {
+			let raw = reader.u32()?;
+			let left = c == 1;
+
+			*child = if (raw & 0x8000_0000) != 0 {
+				let ssector = (raw & 0x7FFF_FFFF) as usize;
+
+				if ssector >= subsectors.len() {
+					return Err(Error::InvalidNodeSubsector {
+						node: i,
+						left,
+						ssector,
+						ssectors_len: subsectors.len(),
+					});
+				}
+
+				BspNodeChild::SubSector(ssector)
+			} else {
+				let subnode = raw as usize;
+
+				if subnode >= node_count {
+					return Err(Error::InvalidSubnode {
+						node: i,
+						left,
+						subnode,
+						nodes_len: node_count,
+					});
+				}
+
+				BspNodeChild::SubNode(subnode)
+			};
+		}
+
+		nodes.push(ExtNodeRaw {
+			seg_start,
+			seg_delta,
+			aabb_r: aabbs[0],
+			aabb_l: aabbs[1],
+			child_r: children[0],
+			child_l: children[1],
+		});
+	}
+
+	Ok(ExtendedNodes {
+		format,
+		compressed,
+		orig_vert_count,
+		new_vertices,
+		subsectors,
+		segs,
+		nodes,
+	})
+}
+
+/// Little-endian reads over the body of an extended node lump, which fail
+/// with [`Error::MalformedFile`] rather than panicking if the lump ends early.
+struct ZNodesReader<'b> {
+	bytes: &'b [u8],
+}
+
+impl ZNodesReader<'_> {
+	fn take<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+		if self.bytes.len() < N {
+			return Err(Error::MalformedFile("ZNODES"));
+		}
+
+		let (taken, rest) = self.bytes.split_at(N);
+		self.bytes = rest;
+		Ok(taken.try_into().unwrap())
+	}
+
+	fn u8(&mut self) -> Result<u8, Error> {
+		self.take::<1>().map(|b| b[0])
+	}
+
+	fn u16(&mut self) -> Result<u16, Error> {
+		self.take().map(u16::from_le_bytes)
+	}
+
+	fn i16(&mut self) -> Result<i16, Error> {
+		self.take().map(i16::from_le_bytes)
+	}
+
+	fn u32(&mut self) -> Result<u32, Error> {
+		self.take().map(u32::from_le_bytes)
+	}
+
+	fn i32(&mut self) -> Result<i32, Error> {
+		self.take().map(i32::from_le_bytes)
+	}
+
+	/// Reads a 32-bit element count, and checks that enough bytes remain to
+	/// hold that many elements of `elem_size` before anything gets allocated.
+	fn count(&mut self, elem_size: usize) -> Result<usize, Error> {
+		let count = self.u32()? as usize;
+
+		if (self.bytes.len() / elem_size) < count {
+			return Err(Error::MalformedFile("ZNODES"));
+		}
+
+		Ok(count)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn tid_index() {
+		#[must_use]
+		fn thing(tid: i16) -> ThingExtRaw {
+			ThingExtRaw {
+				tid: tid.to_le(),
+				x: 0,
+				y: 0,
+				z: 0,
+				angle: 0,
+				ednum: 0,
+				flags: 0,
+				special: 0,
+				args: [0; 5],
+			}
+		}
+
+		let things = [thing(5), thing(0), thing(7), thing(5)];
+		let index = build_tid_index(&things);
+
+		assert_eq!(index.len(), 2);
+		assert_eq!(index[&5].as_slice(), &[0, 3]);
+		assert_eq!(index[&7].as_slice(), &[2]);
+		assert!(!index.contains_key(&0));
+	}
+
+	#[test]
+	fn hexen_linedefs() {
+		let mut lump = vec![];
+		lump.extend_from_slice(&3_u16.to_le_bytes());
+		lump.extend_from_slice(&4_u16.to_le_bytes());
+		// Two-sided, repeatable, activated by use, and monsters can activate it.
+		lump.extend_from_slice(&(0x0004_u16 | 0x0200 | 0x0400 | 0x2000).to_le_bytes());
+		lump.push(12);
+		lump.extend_from_slice(&[1, 16, 128, 0, 0]);
+		lump.extend_from_slice(&7_u16.to_le_bytes());
+		lump.extend_from_slice(&0xFFFF_u16.to_le_bytes());
+
+		let linedefs = linedefs_ext(&lump).unwrap();
+		assert_eq!(linedefs.len(), 1);
+
+		let line = &linedefs[0];
+		assert_eq!(line.start_vertex(), 3);
+		assert_eq!(line.end_vertex(), 4);
+		assert_eq!(line.flags(), LineFlags::TWO_SIDED);
+		assert_eq!(line.raw_flags(), 0x2604);
+		assert_eq!(line.special(), 12);
+		assert_eq!(line.args(), [1, 16, 128, 0, 0]);
+		assert_eq!(line.right_side(), 7);
+		assert_eq!(line.left_side(), None);
+
+		assert!(matches!(
+			linedefs_ext(&lump[..14]),
+			Err(Error::MalformedFile(_))
+		));
+	}
+
+	#[test]
+	fn znodes() {
+		use std::io::Write;
+
+		fn push_u32s(bytes: &mut Vec<u8>, values: &[u32]) {
+			for v in values {
+				bytes.extend_from_slice(&v.to_le_bytes());
+			}
+		}
+
+		fn push_i16s(bytes: &mut Vec<u8>, values: &[i16]) {
+			for v in values {
+				bytes.extend_from_slice(&v.to_le_bytes());
+			}
+		}
+
+		const SUBSECTOR: u32 = 0x8000_0000;
+
+		// 4 original vertices, and one new one at (32.5, 0).
+		let mut body = vec![];
+		push_u32s(&mut body, &[4, 1, (32 << 16) | 0x8000, 0]);
+		// 2 subsectors, with 2 segs and 1 seg.
+		push_u32s(&mut body, &[2, 2, 1]);
+		push_u32s(&mut body, &[3]);
+
+		for (v1, v2, line, side) in [(0, 4, 0, 0), (4, 1, 0, 0), (1, 2, 1, 1)] {
+			push_u32s(&mut body, &[v1, v2]);
+			body.extend_from_slice(&(line as u16).to_le_bytes());
+			body.push(side);
+		}
+
+		push_u32s(&mut body, &[1]);
+		push_i16s(&mut body, &[32, 0, 0, 64]);
+		push_i16s(&mut body, &[64, 0, 32, 64, 64, 0, 0, 32]);
+		push_u32s(&mut body, &[SUBSECTOR, SUBSECTOR | 1]);
+
+		let xnod = [b"XNOD".as_slice(), &body].concat();
+		let nodes = super::znodes(&xnod).unwrap();
+
+		assert_eq!(nodes.format, ZNodesFormat::Normal);
+		assert!(!nodes.compressed);
+		assert_eq!(nodes.vertex_count(), 5);
+		assert_eq!(nodes.new_vertices, [[(32 << 16) | 0x8000, 0]]);
+		assert_eq!(nodes.subsectors, [0..2, 2..3]);
+		assert_eq!(nodes.segs[1].v_start, 4);
+		assert_eq!(nodes.segs[1].v_end, 1);
+		assert_eq!(nodes.segs[2].linedef, Some(1));
+		assert_eq!(nodes.segs[2].direction, SegDirection::Back);
+		assert!(nodes.segs.iter().all(|seg| seg.partner.is_none()));
+		assert_eq!(nodes.nodes[0].seg_start, [32 << 16, 0]);
+		assert_eq!(nodes.nodes[0].seg_delta, [0, 64 << 16]);
+		assert_eq!(nodes.nodes[0].child_r, BspNodeChild::SubSector(0));
+		assert_eq!(nodes.nodes[0].child_l, BspNodeChild::SubSector(1));
+
+		// The same data, compressed.
+		let mut encoder =
+			flate2::write::ZlibEncoder::new(b"ZNOD".to_vec(), flate2::Compression::default());
+		encoder.write_all(&body).unwrap();
+		let znod = encoder.finish().unwrap();
+		let inflated = super::znodes(&znod).unwrap();
+
+		assert!(inflated.compressed);
+		assert_eq!(
+			ExtendedNodes {
+				compressed: false,
+				..inflated
+			},
+			nodes
+		);
+
+		assert!(matches!(
+			super::znodes(&xnod[..(xnod.len() - 3)]),
+			Err(Error::MalformedFile("ZNODES"))
+		));
+		assert!(matches!(
+			super::znodes(&znod[..(znod.len() / 2)]),
+			Err(Error::ZNodesDecompress(_) | Error::MalformedFile("ZNODES"))
+		));
+		assert!(matches!(
+			super::znodes(b"ZZZZ\0\0\0\0"),
+			Err(Error::ZNodesSignature(_))
+		));
+
+		// GL segs get their end vertex from the next seg in the subsector,
+		// and carry partners; `0xFFFF` marks a mini-seg.
+		let mut gl = b"XGLN".to_vec();
+		push_u32s(&mut gl, &[3, 0, 1, 3, 3]);
+
+		for (v1, partner, line) in [(0, u32::MAX, 0), (1, 2, 0xFFFF), (2, 1, 1)] {
+			push_u32s(&mut gl, &[v1, partner]);
+			gl.extend_from_slice(&(line as u16).to_le_bytes());
+			gl.push(0);
+		}
+
+		push_u32s(&mut gl, &[0]);
+		let nodes = super::znodes(&gl).unwrap();
+
+		assert_eq!(nodes.format, ZNodesFormat::Gl);
+		assert_eq!(
+			nodes.segs.iter().map(|s| s.v_end).collect::<Vec<_>>(),
+			[1, 2, 0]
+		);
+		assert_eq!(nodes.segs[0].partner, None);
+		assert_eq!(nodes.segs[1].partner, Some(2));
+		assert_eq!(nodes.segs[1].linedef, None);
+
+		// Seg 2's partner now points past the end of the segs.
+		let partner = 4 + 20 + (11 * 2) + 4;
+		gl[partner..(partner + 4)].copy_from_slice(&9_u32.to_le_bytes());
+
+		assert!(matches!(
+			super::znodes(&gl),
+			Err(Error::InvalidSegPartner {
+				seg: 2,
+				partner: 9,
+				..
+			})
+		));
+	}
+}
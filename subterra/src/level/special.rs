@@ -0,0 +1,2106 @@
+//! Translation of [line specials] from every numbering scheme into one [`LineSpecial`].
+//!
+//! There are three schemes:
+//! - Doom's own numbers, which Boom and MBF21 extended. See [`from_doom`].
+//! - Boom's "generalized" numbers, from `0x2F80` upwards, which pack a special's
+//!   parameters into bit fields. See [`from_boom_generalized`].
+//! - Hexen's numbers, which take their parameters from five arguments; used by
+//!   Hexen-format levels and most UDMF namespaces. See [`from_hexen`].
+//!
+//! Parameters follow Hexen's conventions regardless of the source scheme:
+//! speeds are in eighths of a map unit per tic, delays are in tics, and heights
+//! are in map units.
+//!
+//! [line specials]: https://doomwiki.org/wiki/Linedef_type
+
+use super::read::LineFlags;
+
+/// A [`LineSpecial`] alongside what can set it off and what it acts upon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineSpecialDef {
+	pub special: LineSpecial,
+	pub activation: Activation,
+	/// Which sectors are affected, or which line is the destination of a
+	/// [line teleporter](TeleportDest::Line). For a door-style manual special,
+	/// this is 0, meaning "the sector behind the activated line".
+	pub tag: i32,
+}
+
+bitflags::bitflags! {
+	/// What can set off a line special, and how.
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+	pub struct Activation: u16 {
+		const PLAYER_CROSS = 1 << 0;
+		const PLAYER_USE = 1 << 1;
+		const PLAYER_PUSH = 1 << 2;
+		/// Hit by one of a player's attacks.
+		const PLAYER_IMPACT = 1 << 3;
+		const MONSTER_CROSS = 1 << 4;
+		const MONSTER_USE = 1 << 5;
+		const MONSTER_PUSH = 1 << 6;
+		/// Hit by one of a monster's attacks.
+		const MONSTER_IMPACT = 1 << 7;
+		const MISSILE_CROSS = 1 << 8;
+		/// If unset, the line's special is cleared after it is first activated.
+		const REPEAT = 1 << 9;
+	}
+}
+
+impl Activation {
+	/// From the flags field of a Hexen-format linedef,
+	/// including ZDoom's "monsters can activate" bit.
+	#[must_use]
+	pub fn from_hexen_flags(flags: u16) -> Self {
+		let monsters = (flags & 0x2000) != 0;
+		let spac = (flags >> 10) & 0b111;
+
+		let (player, monster) = match spac {
+			0 => (Self::PLAYER_CROSS, Self::MONSTER_CROSS),
+			1 | 6 => (Self::PLAYER_USE, Self::MONSTER_USE),
+			2 => (Self::empty(), Self::MONSTER_CROSS),
+			3 => (Self::PLAYER_IMPACT, Self::MONSTER_IMPACT),
+			4 => (Self::PLAYER_PUSH, Self::MONSTER_PUSH),
+			5 => (Self::MISSILE_CROSS, Self::empty()),
+			_ => (Self::empty(), Self::empty()),
+		};
+
+		let mut ret = player;
+
+		if monsters || spac == 2 {
+			ret |= monster;
+		}
+
+		if (flags & 0x0200) != 0 {
+			ret |= Self::REPEAT;
+		}
+
+		ret
+	}
+
+	/// From the activation-related flags of a UDMF linedef.
+	#[must_use]
+	pub fn from_udmf(flags: LineFlags) -> Self {
+		const PAIRS: &[(LineFlags, Activation)] = &[
+			(LineFlags::ALLOW_PLAYER_CROSS, Activation::PLAYER_CROSS),
+			(LineFlags::ALLOW_PLAYER_USE, Activation::PLAYER_USE),
+			(LineFlags::ALLOW_PLAYER_PUSH, Activation::PLAYER_PUSH),
+			(LineFlags::IMPACT, Activation::PLAYER_IMPACT),
+			(LineFlags::ALLOW_MONS_CROSS, Activation::MONSTER_CROSS),
+			(LineFlags::ALLOW_MONS_USE, Activation::MONSTER_USE),
+			(LineFlags::ALLOW_MONS_PUSH, Activation::MONSTER_PUSH),
+			(LineFlags::ALLOW_PROJ_CROSS, Activation::MISSILE_CROSS),
+			(LineFlags::REPEAT_SPECIAL, Activation::REPEAT),
+		];
+
+		let mut ret = Self::empty();
+
+		for (flag, activation) in PAIRS {
+			if flags.contains(*flag) {
+				ret |= *activation;
+			}
+		}
+
+		ret
+	}
+}
+
+/// Every kind of line special the sim knows how to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineSpecial {
+	/// Special number 0. Nothing happens.
+	None,
+	Door(Door),
+	Floor(Floor),
+	Ceiling(Ceiling),
+	Lift(Lift),
+	/// Stops any [perpetual lifts](LiftTarget::Perpetual) in the tagged sectors.
+	LiftStop,
+	Stairs(Stairs),
+	Crusher(Crusher),
+	/// Stops any crushers in the tagged sectors.
+	CrusherStop,
+	Donut(Donut),
+	Teleport(Teleport),
+	Light(Light),
+	Exit(Exit),
+	Scroll(Scroll),
+	/// Holds the number which could not be translated.
+	Unknown(u16),
+}
+
+impl LineSpecial {
+	#[must_use]
+	pub fn is_unknown(&self) -> bool {
+		matches!(self, Self::Unknown(_))
+	}
+}
+
+/// See <https://zdoom.org/wiki/LOCKDEFS>. Every scheme's locks are translated
+/// to the numbers ZDoom's default lock definitions use for Doom.
+pub type LockNum = u16;
+
+/// Lock numbers used when translating Doom and Boom locked doors.
+pub mod locks {
+	use super::LockNum;
+
+	pub const RED_CARD: LockNum = 1;
+	pub const BLUE_CARD: LockNum = 2;
+	pub const YELLOW_CARD: LockNum = 3;
+	pub const RED_SKULL: LockNum = 4;
+	pub const BLUE_SKULL: LockNum = 5;
+	pub const YELLOW_SKULL: LockNum = 6;
+	pub const ANY: LockNum = 100;
+	pub const ALL_SIX: LockNum = 101;
+	/// Red keycard or red skull key.
+	pub const RED: LockNum = 129;
+	/// Blue keycard or blue skull key.
+	pub const BLUE: LockNum = 130;
+	/// Yellow keycard or yellow skull key.
+	pub const YELLOW: LockNum = 131;
+	/// One key of each color.
+	pub const ALL_THREE: LockNum = 229;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Door {
+	pub kind: DoorKind,
+	pub speed: u16,
+	/// How long the door waits before reversing.
+	/// Always 0 for [`DoorKind::Open`] and [`DoorKind::Close`].
+	pub delay: u16,
+	pub lock: Option<LockNum>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DoorKind {
+	OpenWaitClose,
+	Open,
+	CloseWaitOpen,
+	Close,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Floor {
+	pub target: FloorTarget,
+	/// The direction of travel. Ignored if `target` is [`FloorTarget::Absolute`].
+	pub up: bool,
+	pub speed: u16,
+	/// Added to the height which `target` resolves to.
+	pub offset: i32,
+	pub crush: bool,
+	pub change: Option<TextureChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FloorTarget {
+	HighestNeighborFloor,
+	LowestNeighborFloor,
+	/// The nearest neighboring floor in the direction of travel.
+	NextNeighborFloor,
+	LowestNeighborCeiling,
+	/// The sector's own ceiling.
+	Ceiling,
+	ShortestLowerTexture,
+	/// This distance from the floor's starting height, in the direction of travel.
+	/// Boom's texture-changing specials which do not move are given a distance of 0.
+	Relative(i32),
+	Absolute(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ceiling {
+	pub target: CeilingTarget,
+	/// The direction of travel. Ignored if `target` is [`CeilingTarget::Absolute`].
+	pub up: bool,
+	pub speed: u16,
+	/// Added to the height which `target` resolves to.
+	pub offset: i32,
+	pub crush: bool,
+	pub change: Option<TextureChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CeilingTarget {
+	HighestNeighborCeiling,
+	LowestNeighborCeiling,
+	/// The nearest neighboring ceiling in the direction of travel.
+	NextNeighborCeiling,
+	HighestNeighborFloor,
+	/// The sector's own floor.
+	Floor,
+	ShortestUpperTexture,
+	/// This distance from the ceiling's starting height, in the direction of travel.
+	Relative(i32),
+	Absolute(i32),
+}
+
+/// Applied to the moving plane once it reaches its destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureChange {
+	pub model: ChangeModel,
+	pub kind: ChangeKind,
+}
+
+/// Which sector the texture (and possibly the type) gets copied from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChangeModel {
+	/// The front sector of the activated line.
+	Trigger,
+	/// The neighboring sector whose height is the destination.
+	Numeric,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChangeKind {
+	/// Copy the texture and clear the sector's special.
+	ZeroType,
+	Texture,
+	TextureAndType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lift {
+	pub target: LiftTarget,
+	pub speed: u16,
+	/// How long the lift waits before returning.
+	pub delay: u16,
+}
+
+/// Where a lift travels before waiting and returning to its starting height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LiftTarget {
+	LowestNeighborFloor,
+	/// The nearest neighboring floor below.
+	NextLowerFloor,
+	LowestNeighborCeiling,
+	HighestNeighborFloor,
+	/// Negative values go down.
+	Relative(i32),
+	/// Moves between the lowest and highest neighboring floors until stopped.
+	Perpetual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stairs {
+	pub up: bool,
+	/// The height of each step.
+	pub step: u16,
+	pub speed: u16,
+	/// How long each step waits before the next one starts moving.
+	pub delay: u16,
+	/// If `false`, the staircase stops at a sector with a different floor texture.
+	pub ignore_texture: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Crusher {
+	pub speed: u16,
+	pub silent: bool,
+	/// Dealt to things caught by the crusher.
+	pub damage: u16,
+}
+
+/// See <https://doomwiki.org/wiki/Donut>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Donut {
+	pub pillar_speed: u16,
+	pub slime_speed: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Teleport {
+	pub dest: TeleportDest,
+	/// No fog gets spawned, and the teleported thing keeps its momentum.
+	pub silent: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TeleportDest {
+	/// A teleport destination thing in a tagged sector.
+	Sector,
+	/// A thing with this TID. If the tag is non-zero,
+	/// only things in tagged sectors are considered.
+	Thing(i32),
+	/// The line with the tag, keeping the teleported thing's position relative
+	/// to it. If `reversed`, the thing comes out the other way around.
+	Line { reversed: bool },
+}
+
+/// Changes to the light level of all tagged sectors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Light {
+	Set(u16),
+	/// Added to the current light level.
+	Change(i32),
+	/// The brightest light level amongst neighboring sectors.
+	MaxNeighbor,
+	/// The darkest light level amongst neighboring sectors.
+	MinNeighbor,
+	/// Times are in tics.
+	Strobe {
+		bright: u16,
+		dark: u16,
+	},
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Exit {
+	Normal,
+	Secret,
+	/// To the level with this number, at the player start with this position number.
+	Map {
+		map: i32,
+		position: i32,
+	},
+	EndGame,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scroll {
+	/// The activated line's textures scroll constantly. Speeds are in
+	/// 64ths of a map unit per tic; positive X is leftward and positive Y is upward.
+	Wall { x: i32, y: i32 },
+	/// The scroll vector comes from the front sidedef's offsets.
+	/// If not `tagged`, the activated line itself scrolls.
+	Offsets { mode: ScrollMode, tagged: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScrollMode {
+	Constant,
+	/// Scrolls in proportion to changes in the height of the tagged sector.
+	Displacement,
+	/// Scroll speed changes in proportion to changes in the height of the tagged sector.
+	Accelerative,
+}
+
+// Doom, Boom, MBF21 ///////////////////////////////////////////////////////////
+
+/// Numbers from this up are Boom generalized specials; see [`from_boom_generalized`].
+pub const BOOM_GENERALIZED_START: u16 = GEN_CRUSHER;
+
+const GEN_CRUSHER: u16 = 0x2F80;
+const GEN_STAIRS: u16 = 0x3000;
+const GEN_LIFT: u16 = 0x3400;
+const GEN_LOCKED: u16 = 0x3800;
+const GEN_DOOR: u16 = 0x3C00;
+const GEN_CEILING: u16 = 0x4000;
+const GEN_FLOOR: u16 = 0x6000;
+
+const DOOR_NORMAL: u16 = 16;
+const DOOR_BLAZE: u16 = 64;
+const DOOR_WAIT: u16 = 150;
+const DOOR_WAIT_LONG: u16 = 35 * 30;
+const FLOOR_SLOW: u16 = 8;
+const FLOOR_TURBO: u16 = 32;
+/// Used by the plat-driven floors which change textures.
+const PLAT_CHANGE: u16 = 4;
+const LIFT_NORMAL: u16 = 32;
+const LIFT_BLAZE: u16 = 64;
+const LIFT_PERPETUAL: u16 = 8;
+const LIFT_WAIT: u16 = 35 * 3;
+const CEILING_SPEED: u16 = 8;
+const CRUSHER_SLOW: u16 = 8;
+const CRUSHER_FAST: u16 = 16;
+const CRUSH_DAMAGE: u16 = 10;
+const STAIRS_SLOW: u16 = 2;
+const STAIRS_TURBO: u16 = 32;
+const DONUT_SPEED: u16 = 4;
+const STROBE_BRIGHT: u16 = 5;
+const STROBE_DARK: u16 = 35;
+
+/// Translates a special number from a Doom-format level (or a UDMF level in
+/// the `doom` namespace). Vanilla, Boom's extended and generalized specials,
+/// and MBF21's additions are all covered.
+#[must_use]
+pub fn from_doom(num: u16, tag: i32) -> LineSpecialDef {
+	if let Some(def) = from_boom_generalized(num, tag) {
+		return def;
+	}
+
+	if num == 0 {
+		return LineSpecialDef {
+			special: LineSpecial::None,
+			activation: Activation::empty(),
+			tag,
+		};
+	}
+
+	let Some((trigger, special)) = doom_table(num) else {
+		return LineSpecialDef {
+			special: LineSpecial::Unknown(num),
+			activation: Activation::empty(),
+			tag,
+		};
+	};
+
+	let (players, monsters) = match num {
+		1 | 4 | 10 | 39 | 46 | 88 | 97 | 174 | 195 | 207..=210 | 243 | 244 | 262 | 263 => {
+			(true, true)
+		}
+		125 | 126 | 264..=269 => (false, true),
+		_ => (true, false),
+	};
+
+	trigger.def(special, players, monsters, tag)
+}
+
+/// Returns `None` if `num` is less than [`BOOM_GENERALIZED_START`].
+/// See <https://doomwiki.org/wiki/Generalized_linedef>.
+#[must_use]
+pub fn from_boom_generalized(num: u16, tag: i32) -> Option<LineSpecialDef> {
+	if num < GEN_CRUSHER {
+		return None;
+	}
+
+	let trigger = Trigger::from_bits(num);
+	let speed = ((num >> 3) & 0b11) as usize;
+
+	let (monsters, special) = if num >= GEN_FLOOR {
+		let (monsters, change) = gen_change(num);
+
+		let target = match (num >> 7) & 0b111 {
+			0 => FloorTarget::HighestNeighborFloor,
+			1 => FloorTarget::LowestNeighborFloor,
+			2 => FloorTarget::NextNeighborFloor,
+			3 => FloorTarget::LowestNeighborCeiling,
+			4 => FloorTarget::Ceiling,
+			5 => FloorTarget::ShortestLowerTexture,
+			6 => FloorTarget::Relative(24),
+			_ => FloorTarget::Relative(32),
+		};
+
+		let special = LineSpecial::Floor(Floor {
+			target,
+			up: (num & (1 << 6)) != 0,
+			speed: [8, 16, 32, 64][speed],
+			offset: 0,
+			crush: (num & (1 << 12)) != 0,
+			change,
+		});
+
+		(monsters, special)
+	} else if num >= GEN_CEILING {
+		let (monsters, change) = gen_change(num);
+
+		let target = match (num >> 7) & 0b111 {
+			0 => CeilingTarget::HighestNeighborCeiling,
+			1 => CeilingTarget::LowestNeighborCeiling,
+			2 => CeilingTarget::NextNeighborCeiling,
+			3 => CeilingTarget::HighestNeighborFloor,
+			4 => CeilingTarget::Floor,
+			5 => CeilingTarget::ShortestUpperTexture,
+			6 => CeilingTarget::Relative(24),
+			_ => CeilingTarget::Relative(32),
+		};
+
+		let special = LineSpecial::Ceiling(Ceiling {
+			target,
+			up: (num & (1 << 6)) != 0,
+			speed: [8, 16, 32, 64][speed],
+			offset: 0,
+			crush: (num & (1 << 12)) != 0,
+			change,
+		});
+
+		(monsters, special)
+	} else if num >= GEN_DOOR {
+		let kind = match (num >> 5) & 0b11 {
+			0 => DoorKind::OpenWaitClose,
+			1 => DoorKind::Open,
+			2 => DoorKind::CloseWaitOpen,
+			_ => DoorKind::Close,
+		};
+
+		let delay = match kind {
+			DoorKind::OpenWaitClose | DoorKind::CloseWaitOpen => {
+				[35, DOOR_WAIT, DOOR_WAIT * 2, DOOR_WAIT * 7][((num >> 8) & 0b11) as usize]
+			}
+			DoorKind::Open | DoorKind::Close => 0,
+		};
+
+		let special = LineSpecial::Door(Door {
+			kind,
+			speed: [16, 32, 64, 128][speed],
+			delay,
+			lock: None,
+		});
+
+		(((num >> 7) & 1) != 0, special)
+	} else if num >= GEN_LOCKED {
+		let (kind, delay) = if ((num >> 5) & 1) == 0 {
+			(DoorKind::OpenWaitClose, DOOR_WAIT)
+		} else {
+			(DoorKind::Open, 0)
+		};
+
+		let either = ((num >> 9) & 1) != 0;
+
+		let lock = match ((num >> 6) & 0b111, either) {
+			(0, _) => locks::ANY,
+			(1 | 4, true) => locks::RED,
+			(2 | 5, true) => locks::BLUE,
+			(3 | 6, true) => locks::YELLOW,
+			(_, true) => locks::ALL_THREE,
+			(1, false) => locks::RED_CARD,
+			(2, false) => locks::BLUE_CARD,
+			(3, false) => locks::YELLOW_CARD,
+			(4, false) => locks::RED_SKULL,
+			(5, false) => locks::BLUE_SKULL,
+			(6, false) => locks::YELLOW_SKULL,
+			(_, false) => locks::ALL_SIX,
+		};
+
+		let special = LineSpecial::Door(Door {
+			kind,
+			speed: [16, 32, 64, 128][speed],
+			delay,
+			lock: Some(lock),
+		});
+
+		(false, special)
+	} else if num >= GEN_LIFT {
+		let target = match (num >> 8) & 0b11 {
+			0 => LiftTarget::LowestNeighborFloor,
+			1 => LiftTarget::NextLowerFloor,
+			2 => LiftTarget::LowestNeighborCeiling,
+			_ => LiftTarget::Perpetual,
+		};
+
+		let special = LineSpecial::Lift(Lift {
+			target,
+			speed: [16, 32, 64, 128][speed],
+			delay: [35, 35 * 3, 35 * 5, 35 * 10][((num >> 6) & 0b11) as usize],
+		});
+
+		(((num >> 5) & 1) != 0, special)
+	} else if num >= GEN_STAIRS {
+		let special = LineSpecial::Stairs(Stairs {
+			up: ((num >> 8) & 1) != 0,
+			step: [4, 8, 16, 24][((num >> 6) & 0b11) as usize],
+			speed: [2, 4, 16, 32][speed],
+			delay: 0,
+			ignore_texture: ((num >> 9) & 1) != 0,
+		});
+
+		(((num >> 5) & 1) != 0, special)
+	} else {
+		let special = LineSpecial::Crusher(Crusher {
+			speed: [8, 16, 32, 64][speed],
+			silent: ((num >> 6) & 1) != 0,
+			damage: CRUSH_DAMAGE,
+		});
+
+		(((num >> 5) & 1) != 0, special)
+	};
+
+	Some(trigger.def(special, true, monsters, tag))
+}
+
+/// For generalized floors and ceilings, bit 5 is the change model if the change
+/// field is non-zero, and the "monsters can activate" bit otherwise.
+#[must_use]
+fn gen_change(num: u16) -> (bool, Option<TextureChange>) {
+	let bit5 = ((num >> 5) & 1) != 0;
+
+	let kind = match (num >> 10) & 0b11 {
+		0 => return (bit5, None),
+		1 => ChangeKind::ZeroType,
+		2 => ChangeKind::Texture,
+		_ => ChangeKind::TextureAndType,
+	};
+
+	let model = if bit5 {
+		ChangeModel::Numeric
+	} else {
+		ChangeModel::Trigger
+	};
+
+	(false, Some(TextureChange { model, kind }))
+}
+
+/// Doom's activation types, in the order of Boom's generalized trigger field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Trigger {
+	W1,
+	WR,
+	S1,
+	SR,
+	G1,
+	GR,
+	D1,
+	DR,
+	/// Not activated at all; e.g. scrollers.
+	Passive,
+}
+
+impl Trigger {
+	#[must_use]
+	fn from_bits(num: u16) -> Self {
+		[
+			Self::W1,
+			Self::WR,
+			Self::S1,
+			Self::SR,
+			Self::G1,
+			Self::GR,
+			Self::D1,
+			Self::DR,
+		][(num & 0b111) as usize]
+	}
+
+	#[must_use]
+	fn def(self, special: LineSpecial, players: bool, monsters: bool, tag: i32) -> LineSpecialDef {
+		let (player, monster) = match self {
+			Self::W1 | Self::WR => (Activation::PLAYER_CROSS, Activation::MONSTER_CROSS),
+			Self::S1 | Self::SR | Self::D1 | Self::DR => {
+				(Activation::PLAYER_USE, Activation::MONSTER_USE)
+			}
+			Self::G1 | Self::GR => (Activation::PLAYER_IMPACT, Activation::MONSTER_IMPACT),
+			Self::Passive => (Activation::empty(), Activation::empty()),
+		};
+
+		let mut activation = Activation::empty();
+
+		if players {
+			activation |= player;
+		}
+
+		if monsters {
+			activation |= monster;
+		}
+
+		if matches!(self, Self::WR | Self::SR | Self::GR | Self::DR) {
+			activation |= Activation::REPEAT;
+		}
+
+		let tag = if matches!(self, Self::D1 | Self::DR) {
+			0
+		} else {
+			tag
+		};
+
+		LineSpecialDef {
+			special,
+			activation,
+			tag,
+		}
+	}
+}
+
+#[must_use]
+fn doom_table(num: u16) -> Option<(Trigger, LineSpecial)> {
+	use Trigger::*;
+
+	#[must_use]
+	fn door_def(kind: DoorKind, speed: u16) -> Door {
+		let delay = match kind {
+			DoorKind::OpenWaitClose => DOOR_WAIT,
+			DoorKind::CloseWaitOpen => DOOR_WAIT_LONG,
+			DoorKind::Open | DoorKind::Close => 0,
+		};
+
+		Door {
+			kind,
+			speed,
+			delay,
+			lock: None,
+		}
+	}
+
+	#[must_use]
+	fn door(kind: DoorKind, speed: u16) -> LineSpecial {
+		LineSpecial::Door(door_def(kind, speed))
+	}
+
+	#[must_use]
+	fn locked(kind: DoorKind, speed: u16, lock: LockNum) -> LineSpecial {
+		LineSpecial::Door(Door {
+			lock: Some(lock),
+			..door_def(kind, speed)
+		})
+	}
+
+	#[must_use]
+	fn floor(up: bool, target: FloorTarget, speed: u16) -> Floor {
+		Floor {
+			target,
+			up,
+			speed,
+			offset: 0,
+			crush: false,
+			change: None,
+		}
+	}
+
+	/// All of the non-crushing ceiling specials move at the same speed.
+	#[must_use]
+	fn ceiling(up: bool, target: CeilingTarget) -> Ceiling {
+		Ceiling {
+			target,
+			up,
+			speed: CEILING_SPEED,
+			offset: 0,
+			crush: false,
+			change: None,
+		}
+	}
+
+	#[must_use]
+	fn raise(target: FloorTarget) -> LineSpecial {
+		LineSpecial::Floor(floor(true, target, FLOOR_SLOW))
+	}
+
+	#[must_use]
+	fn lower(target: FloorTarget) -> LineSpecial {
+		LineSpecial::Floor(floor(false, target, FLOOR_SLOW))
+	}
+
+	#[must_use]
+	fn change(model: ChangeModel, kind: ChangeKind) -> Option<TextureChange> {
+		Some(TextureChange { model, kind })
+	}
+
+	#[must_use]
+	fn lift(target: LiftTarget, speed: u16) -> LineSpecial {
+		LineSpecial::Lift(Lift {
+			target,
+			speed,
+			delay: LIFT_WAIT,
+		})
+	}
+
+	#[must_use]
+	fn crusher(speed: u16, silent: bool) -> LineSpecial {
+		LineSpecial::Crusher(Crusher {
+			speed,
+			silent,
+			damage: CRUSH_DAMAGE,
+		})
+	}
+
+	#[must_use]
+	fn stairs(speed: u16, step: u16) -> LineSpecial {
+		LineSpecial::Stairs(Stairs {
+			up: true,
+			step,
+			speed,
+			delay: 0,
+			ignore_texture: false,
+		})
+	}
+
+	#[must_use]
+	fn teleport(dest: TeleportDest, silent: bool) -> LineSpecial {
+		LineSpecial::Teleport(Teleport { dest, silent })
+	}
+
+	use CeilingTarget as CT;
+	use ChangeKind as CK;
+	use ChangeModel as CM;
+	use DoorKind as DK;
+	use FloorTarget as FT;
+	use LineSpecial as LS;
+
+	const DONUT: LineSpecial = LineSpecial::Donut(Donut {
+		pillar_speed: DONUT_SPEED,
+		slime_speed: DONUT_SPEED,
+	});
+
+	const STROBE: LineSpecial = LineSpecial::Light(Light::Strobe {
+		bright: STROBE_BRIGHT,
+		dark: STROBE_DARK,
+	});
+
+	// Raises the floor to the ceiling, minus 8; as used by the vanilla crush specials.
+	let floor_crush = Floor {
+		offset: -8,
+		crush: true,
+		..floor(true, FT::LowestNeighborCeiling, FLOOR_SLOW)
+	};
+
+	// Lowers the floor to 8 above the highest neighboring floor; "turbo lower".
+	let floor_turbo_lower = Floor {
+		offset: 8,
+		..floor(false, FT::HighestNeighborFloor, FLOOR_TURBO)
+	};
+
+	// Raises the floor to the next higher one, changing its texture and clearing its type.
+	let floor_plat_next = Floor {
+		change: change(CM::Trigger, CK::ZeroType),
+		..floor(true, FT::NextNeighborFloor, PLAT_CHANGE)
+	};
+
+	// Raises the floor by a fixed amount, changing its texture.
+	let floor_plat_by = |dist| Floor {
+		change: change(CM::Trigger, CK::Texture),
+		..floor(true, FT::Relative(dist), PLAT_CHANGE)
+	};
+
+	let floor_24_change = Floor {
+		change: change(CM::Trigger, CK::TextureAndType),
+		..floor(true, FT::Relative(24), FLOOR_SLOW)
+	};
+
+	let floor_lowest_change = Floor {
+		change: change(CM::Numeric, CK::TextureAndType),
+		..floor(false, FT::LowestNeighborFloor, FLOOR_SLOW)
+	};
+
+	// Boom's texture-changing specials don't move anything.
+	let change_only = |model| Floor {
+		change: change(model, CK::TextureAndType),
+		..floor(true, FT::Relative(0), FLOOR_SLOW)
+	};
+
+	let floor_next_turbo = floor(true, FT::NextNeighborFloor, FLOOR_TURBO);
+
+	let ceiling_8_above = Ceiling {
+		offset: 8,
+		..ceiling(false, CT::Floor)
+	};
+
+	let ret = match num {
+		// Doors
+		1 => (DR, door(DK::OpenWaitClose, DOOR_NORMAL)),
+		2 => (W1, door(DK::Open, DOOR_NORMAL)),
+		3 => (W1, door(DK::Close, DOOR_NORMAL)),
+		4 => (W1, door(DK::OpenWaitClose, DOOR_NORMAL)),
+		16 => (W1, door(DK::CloseWaitOpen, DOOR_NORMAL)),
+		26 => (DR, locked(DK::OpenWaitClose, DOOR_NORMAL, locks::BLUE)),
+		27 => (DR, locked(DK::OpenWaitClose, DOOR_NORMAL, locks::YELLOW)),
+		28 => (DR, locked(DK::OpenWaitClose, DOOR_NORMAL, locks::RED)),
+		29 => (S1, door(DK::OpenWaitClose, DOOR_NORMAL)),
+		31 => (D1, door(DK::Open, DOOR_NORMAL)),
+		32 => (D1, locked(DK::Open, DOOR_NORMAL, locks::BLUE)),
+		33 => (D1, locked(DK::Open, DOOR_NORMAL, locks::RED)),
+		34 => (D1, locked(DK::Open, DOOR_NORMAL, locks::YELLOW)),
+		42 => (SR, door(DK::Close, DOOR_NORMAL)),
+		46 => (GR, door(DK::Open, DOOR_NORMAL)),
+		50 => (S1, door(DK::Close, DOOR_NORMAL)),
+		61 => (SR, door(DK::Open, DOOR_NORMAL)),
+		63 => (SR, door(DK::OpenWaitClose, DOOR_NORMAL)),
+		75 => (WR, door(DK::Close, DOOR_NORMAL)),
+		76 => (WR, door(DK::CloseWaitOpen, DOOR_NORMAL)),
+		86 => (WR, door(DK::Open, DOOR_NORMAL)),
+		90 => (WR, door(DK::OpenWaitClose, DOOR_NORMAL)),
+		99 => (SR, locked(DK::Open, DOOR_BLAZE, locks::BLUE)),
+		103 => (S1, door(DK::Open, DOOR_NORMAL)),
+		105 => (WR, door(DK::OpenWaitClose, DOOR_BLAZE)),
+		106 => (WR, door(DK::Open, DOOR_BLAZE)),
+		107 => (WR, door(DK::Close, DOOR_BLAZE)),
+		108 => (W1, door(DK::OpenWaitClose, DOOR_BLAZE)),
+		109 => (W1, door(DK::Open, DOOR_BLAZE)),
+		110 => (W1, door(DK::Close, DOOR_BLAZE)),
+		111 => (S1, door(DK::OpenWaitClose, DOOR_BLAZE)),
+		112 => (S1, door(DK::Open, DOOR_BLAZE)),
+		113 => (S1, door(DK::Close, DOOR_BLAZE)),
+		114 => (SR, door(DK::OpenWaitClose, DOOR_BLAZE)),
+		115 => (SR, door(DK::Open, DOOR_BLAZE)),
+		116 => (SR, door(DK::Close, DOOR_BLAZE)),
+		117 => (DR, door(DK::OpenWaitClose, DOOR_BLAZE)),
+		118 => (D1, door(DK::Open, DOOR_BLAZE)),
+		133 => (S1, locked(DK::Open, DOOR_BLAZE, locks::BLUE)),
+		134 => (SR, locked(DK::Open, DOOR_BLAZE, locks::RED)),
+		135 => (S1, locked(DK::Open, DOOR_BLAZE, locks::RED)),
+		136 => (SR, locked(DK::Open, DOOR_BLAZE, locks::YELLOW)),
+		137 => (S1, locked(DK::Open, DOOR_BLAZE, locks::YELLOW)),
+		175 => (S1, door(DK::CloseWaitOpen, DOOR_NORMAL)),
+		196 => (SR, door(DK::CloseWaitOpen, DOOR_NORMAL)),
+		// Floors
+		5 => (W1, raise(FT::LowestNeighborCeiling)),
+		14 => (S1, LS::Floor(floor_plat_by(32))),
+		15 => (S1, LS::Floor(floor_plat_by(24))),
+		18 => (S1, raise(FT::NextNeighborFloor)),
+		19 => (W1, lower(FT::HighestNeighborFloor)),
+		20 => (S1, LS::Floor(floor_plat_next)),
+		22 => (W1, LS::Floor(floor_plat_next)),
+		23 => (S1, lower(FT::LowestNeighborFloor)),
+		24 => (G1, raise(FT::LowestNeighborCeiling)),
+		30 => (W1, raise(FT::ShortestLowerTexture)),
+		36 => (W1, LS::Floor(floor_turbo_lower)),
+		37 => (W1, LS::Floor(floor_lowest_change)),
+		38 => (W1, lower(FT::LowestNeighborFloor)),
+		45 => (SR, lower(FT::HighestNeighborFloor)),
+		47 => (G1, LS::Floor(floor_plat_next)),
+		55 => (S1, LS::Floor(floor_crush)),
+		56 => (W1, LS::Floor(floor_crush)),
+		58 => (W1, raise(FT::Relative(24))),
+		59 => (W1, LS::Floor(floor_24_change)),
+		60 => (SR, lower(FT::LowestNeighborFloor)),
+		64 => (SR, raise(FT::LowestNeighborCeiling)),
+		65 => (SR, LS::Floor(floor_crush)),
+		66 => (SR, LS::Floor(floor_plat_by(24))),
+		67 => (SR, LS::Floor(floor_plat_by(32))),
+		68 => (SR, LS::Floor(floor_plat_next)),
+		69 => (SR, raise(FT::NextNeighborFloor)),
+		70 => (SR, LS::Floor(floor_turbo_lower)),
+		71 => (S1, LS::Floor(floor_turbo_lower)),
+		78 => (SR, LS::Floor(change_only(CM::Numeric))),
+		82 => (WR, lower(FT::LowestNeighborFloor)),
+		83 => (WR, lower(FT::HighestNeighborFloor)),
+		84 => (WR, LS::Floor(floor_lowest_change)),
+		91 => (WR, raise(FT::LowestNeighborCeiling)),
+		92 => (WR, raise(FT::Relative(24))),
+		93 => (WR, LS::Floor(floor_24_change)),
+		94 => (WR, LS::Floor(floor_crush)),
+		95 => (WR, LS::Floor(floor_plat_next)),
+		96 => (WR, raise(FT::ShortestLowerTexture)),
+		98 => (WR, LS::Floor(floor_turbo_lower)),
+		101 => (S1, raise(FT::LowestNeighborCeiling)),
+		102 => (S1, lower(FT::HighestNeighborFloor)),
+		119 => (W1, raise(FT::NextNeighborFloor)),
+		128 => (WR, raise(FT::NextNeighborFloor)),
+		129 => (WR, LS::Floor(floor_next_turbo)),
+		130 => (W1, LS::Floor(floor_next_turbo)),
+		131 => (S1, LS::Floor(floor_next_turbo)),
+		132 => (SR, LS::Floor(floor_next_turbo)),
+		140 => (S1, raise(FT::Relative(512))),
+		142 => (W1, raise(FT::Relative(512))),
+		143 => (W1, LS::Floor(floor_plat_by(24))),
+		144 => (W1, LS::Floor(floor_plat_by(32))),
+		147 => (WR, raise(FT::Relative(512))),
+		148 => (WR, LS::Floor(floor_plat_by(24))),
+		149 => (WR, LS::Floor(floor_plat_by(32))),
+		153 => (W1, LS::Floor(change_only(CM::Trigger))),
+		154 => (WR, LS::Floor(change_only(CM::Trigger))),
+		158 => (S1, raise(FT::ShortestLowerTexture)),
+		159 => (S1, LS::Floor(floor_lowest_change)),
+		160 => (S1, LS::Floor(floor_24_change)),
+		161 => (S1, raise(FT::Relative(24))),
+		176 => (SR, raise(FT::ShortestLowerTexture)),
+		177 => (SR, LS::Floor(floor_lowest_change)),
+		178 => (SR, raise(FT::Relative(512))),
+		179 => (SR, LS::Floor(floor_24_change)),
+		180 => (SR, raise(FT::Relative(24))),
+		189 => (S1, LS::Floor(change_only(CM::Trigger))),
+		190 => (SR, LS::Floor(change_only(CM::Trigger))),
+		219 => (W1, lower(FT::NextNeighborFloor)),
+		220 => (WR, lower(FT::NextNeighborFloor)),
+		221 => (S1, lower(FT::NextNeighborFloor)),
+		222 => (SR, lower(FT::NextNeighborFloor)),
+		// Ceilings
+		40 => (W1, LS::Ceiling(ceiling(true, CT::HighestNeighborCeiling))),
+		41 => (S1, LS::Ceiling(ceiling(false, CT::Floor))),
+		43 => (SR, LS::Ceiling(ceiling(false, CT::Floor))),
+		44 => (W1, LS::Ceiling(ceiling_8_above)),
+		72 => (WR, LS::Ceiling(ceiling_8_above)),
+		145 => (W1, LS::Ceiling(ceiling(false, CT::Floor))),
+		151 => (WR, LS::Ceiling(ceiling(true, CT::HighestNeighborCeiling))),
+		152 => (WR, LS::Ceiling(ceiling(false, CT::Floor))),
+		166 => (S1, LS::Ceiling(ceiling(true, CT::HighestNeighborCeiling))),
+		167 => (S1, LS::Ceiling(ceiling_8_above)),
+		186 => (SR, LS::Ceiling(ceiling(true, CT::HighestNeighborCeiling))),
+		187 => (SR, LS::Ceiling(ceiling_8_above)),
+		199 => (W1, LS::Ceiling(ceiling(false, CT::LowestNeighborCeiling))),
+		200 => (W1, LS::Ceiling(ceiling(false, CT::HighestNeighborFloor))),
+		201 => (WR, LS::Ceiling(ceiling(false, CT::LowestNeighborCeiling))),
+		202 => (WR, LS::Ceiling(ceiling(false, CT::HighestNeighborFloor))),
+		203 => (S1, LS::Ceiling(ceiling(false, CT::LowestNeighborCeiling))),
+		204 => (S1, LS::Ceiling(ceiling(false, CT::HighestNeighborFloor))),
+		205 => (SR, LS::Ceiling(ceiling(false, CT::LowestNeighborCeiling))),
+		206 => (SR, LS::Ceiling(ceiling(false, CT::HighestNeighborFloor))),
+		// Crushers
+		6 => (W1, crusher(CRUSHER_FAST, false)),
+		25 => (W1, crusher(CRUSHER_SLOW, false)),
+		49 => (S1, crusher(CRUSHER_SLOW, false)),
+		57 => (W1, LS::CrusherStop),
+		73 => (WR, crusher(CRUSHER_SLOW, false)),
+		74 => (WR, LS::CrusherStop),
+		77 => (WR, crusher(CRUSHER_FAST, false)),
+		141 => (W1, crusher(CRUSHER_SLOW, true)),
+		150 => (WR, crusher(CRUSHER_SLOW, true)),
+		164 => (S1, crusher(CRUSHER_FAST, false)),
+		165 => (S1, crusher(CRUSHER_SLOW, true)),
+		168 => (S1, LS::CrusherStop),
+		183 => (SR, crusher(CRUSHER_FAST, false)),
+		184 => (SR, crusher(CRUSHER_SLOW, false)),
+		185 => (SR, crusher(CRUSHER_SLOW, true)),
+		188 => (SR, LS::CrusherStop),
+		// Stairs
+		7 => (S1, stairs(STAIRS_SLOW, 8)),
+		8 => (W1, stairs(STAIRS_SLOW, 8)),
+		100 => (W1, stairs(STAIRS_TURBO, 16)),
+		127 => (S1, stairs(STAIRS_TURBO, 16)),
+		256 => (WR, stairs(STAIRS_SLOW, 8)),
+		257 => (WR, stairs(STAIRS_TURBO, 16)),
+		258 => (SR, stairs(STAIRS_SLOW, 8)),
+		259 => (SR, stairs(STAIRS_TURBO, 16)),
+		// Donuts
+		9 => (S1, DONUT),
+		146 => (W1, DONUT),
+		155 => (WR, DONUT),
+		191 => (SR, DONUT),
+		// Lifts
+		10 => (W1, lift(LiftTarget::LowestNeighborFloor, LIFT_NORMAL)),
+		21 => (S1, lift(LiftTarget::LowestNeighborFloor, LIFT_NORMAL)),
+		53 => (W1, lift(LiftTarget::Perpetual, LIFT_PERPETUAL)),
+		54 => (W1, LS::LiftStop),
+		62 => (SR, lift(LiftTarget::LowestNeighborFloor, LIFT_NORMAL)),
+		87 => (WR, lift(LiftTarget::Perpetual, LIFT_PERPETUAL)),
+		88 => (WR, lift(LiftTarget::LowestNeighborFloor, LIFT_NORMAL)),
+		89 => (WR, LS::LiftStop),
+		120 => (WR, lift(LiftTarget::LowestNeighborFloor, LIFT_BLAZE)),
+		121 => (W1, lift(LiftTarget::LowestNeighborFloor, LIFT_BLAZE)),
+		122 => (S1, lift(LiftTarget::LowestNeighborFloor, LIFT_BLAZE)),
+		123 => (SR, lift(LiftTarget::LowestNeighborFloor, LIFT_BLAZE)),
+		162 => (S1, lift(LiftTarget::Perpetual, LIFT_PERPETUAL)),
+		163 => (S1, LS::LiftStop),
+		181 => (SR, lift(LiftTarget::Perpetual, LIFT_PERPETUAL)),
+		182 => (SR, LS::LiftStop),
+		// Teleporters
+		39 => (W1, teleport(TeleportDest::Sector, false)),
+		97 => (WR, teleport(TeleportDest::Sector, false)),
+		125 => (W1, teleport(TeleportDest::Sector, false)),
+		126 => (WR, teleport(TeleportDest::Sector, false)),
+		174 => (S1, teleport(TeleportDest::Sector, false)),
+		195 => (SR, teleport(TeleportDest::Sector, false)),
+		207 => (W1, teleport(TeleportDest::Sector, true)),
+		208 => (WR, teleport(TeleportDest::Sector, true)),
+		209 => (S1, teleport(TeleportDest::Sector, true)),
+		210 => (SR, teleport(TeleportDest::Sector, true)),
+		243 => (W1, teleport(TeleportDest::Line { reversed: false }, true)),
+		244 => (WR, teleport(TeleportDest::Line { reversed: false }, true)),
+		262 => (W1, teleport(TeleportDest::Line { reversed: true }, true)),
+		263 => (WR, teleport(TeleportDest::Line { reversed: true }, true)),
+		264 => (W1, teleport(TeleportDest::Line { reversed: true }, true)),
+		265 => (WR, teleport(TeleportDest::Line { reversed: true }, true)),
+		266 => (W1, teleport(TeleportDest::Line { reversed: false }, true)),
+		267 => (WR, teleport(TeleportDest::Line { reversed: false }, true)),
+		268 => (W1, teleport(TeleportDest::Sector, true)),
+		269 => (WR, teleport(TeleportDest::Sector, true)),
+		// Lights
+		12 => (W1, LS::Light(Light::MaxNeighbor)),
+		13 => (W1, LS::Light(Light::Set(255))),
+		17 => (W1, STROBE),
+		35 => (W1, LS::Light(Light::Set(35))),
+		79 => (WR, LS::Light(Light::Set(35))),
+		80 => (WR, LS::Light(Light::MaxNeighbor)),
+		81 => (WR, LS::Light(Light::Set(255))),
+		104 => (W1, LS::Light(Light::MinNeighbor)),
+		138 => (SR, LS::Light(Light::Set(255))),
+		139 => (SR, LS::Light(Light::Set(35))),
+		156 => (WR, STROBE),
+		157 => (WR, LS::Light(Light::MinNeighbor)),
+		169 => (S1, LS::Light(Light::MaxNeighbor)),
+		170 => (S1, LS::Light(Light::Set(35))),
+		171 => (S1, LS::Light(Light::Set(255))),
+		172 => (S1, STROBE),
+		173 => (S1, LS::Light(Light::MinNeighbor)),
+		192 => (SR, LS::Light(Light::MaxNeighbor)),
+		193 => (SR, STROBE),
+		194 => (SR, LS::Light(Light::MinNeighbor)),
+		// Exits
+		11 => (S1, LS::Exit(Exit::Normal)),
+		51 => (S1, LS::Exit(Exit::Secret)),
+		52 => (W1, LS::Exit(Exit::Normal)),
+		124 => (W1, LS::Exit(Exit::Secret)),
+		197 => (G1, LS::Exit(Exit::Normal)),
+		198 => (G1, LS::Exit(Exit::Secret)),
+		// Scrollers
+		48 => (Passive, LS::Scroll(Scroll::Wall { x: 64, y: 0 })),
+		85 => (Passive, LS::Scroll(Scroll::Wall { x: -64, y: 0 })),
+		255 => (
+			Passive,
+			LS::Scroll(Scroll::Offsets {
+				mode: ScrollMode::Constant,
+				tagged: false,
+			}),
+		),
+		1024 => (
+			Passive,
+			LS::Scroll(Scroll::Offsets {
+				mode: ScrollMode::Constant,
+				tagged: true,
+			}),
+		),
+		1025 => (
+			Passive,
+			LS::Scroll(Scroll::Offsets {
+				mode: ScrollMode::Displacement,
+				tagged: true,
+			}),
+		),
+		1026 => (
+			Passive,
+			LS::Scroll(Scroll::Offsets {
+				mode: ScrollMode::Accelerative,
+				tagged: true,
+			}),
+		),
+		_ => return None,
+	};
+
+	Some(ret)
+}
+
+// Hexen, UDMF /////////////////////////////////////////////////////////////////
+
+/// Translates a special number from a Hexen-format level (or a UDMF level in a
+/// namespace using Hexen's numbering). Unlike [`from_doom`], activation is not
+/// implied by the special, so it must be provided.
+#[must_use]
+pub fn from_hexen(num: u16, args: [i32; 5], activation: Activation) -> LineSpecialDef {
+	#[must_use]
+	fn arg_u16(arg: i32) -> u16 {
+		arg.clamp(0, u16::MAX as i32) as u16
+	}
+
+	/// Hexen door delays are in eighths of a second.
+	#[must_use]
+	fn octics(arg: i32) -> u16 {
+		arg_u16(arg.saturating_mul(35) / 8)
+	}
+
+	#[must_use]
+	fn floor(up: bool, target: FloorTarget, speed: i32) -> LineSpecial {
+		LineSpecial::Floor(Floor {
+			target,
+			up,
+			speed: arg_u16(speed),
+			offset: 0,
+			crush: false,
+			change: None,
+		})
+	}
+
+	#[must_use]
+	fn ceiling(up: bool, target: CeilingTarget, speed: i32) -> LineSpecial {
+		LineSpecial::Ceiling(Ceiling {
+			target,
+			up,
+			speed: arg_u16(speed),
+			offset: 0,
+			crush: false,
+			change: None,
+		})
+	}
+
+	#[must_use]
+	fn lift(target: LiftTarget, speed: i32, delay: i32) -> LineSpecial {
+		LineSpecial::Lift(Lift {
+			target,
+			speed: arg_u16(speed),
+			delay: arg_u16(delay),
+		})
+	}
+
+	#[must_use]
+	fn stairs(up: bool, args: [i32; 5]) -> LineSpecial {
+		LineSpecial::Stairs(Stairs {
+			up,
+			step: arg_u16(args[2]),
+			speed: arg_u16(args[1]),
+			delay: arg_u16(args[3]),
+			ignore_texture: false,
+		})
+	}
+
+	#[must_use]
+	fn door(kind: DoorKind, args: [i32; 5], delay: i32, lock: Option<LockNum>) -> LineSpecial {
+		let delay = match kind {
+			DoorKind::OpenWaitClose | DoorKind::CloseWaitOpen => octics(delay),
+			DoorKind::Open | DoorKind::Close => 0,
+		};
+
+		LineSpecial::Door(Door {
+			kind,
+			speed: arg_u16(args[1]),
+			delay,
+			lock,
+		})
+	}
+
+	use CeilingTarget as CT;
+	use DoorKind as DK;
+	use FloorTarget as FT;
+	use LineSpecial as LS;
+
+	let [a0, a1, a2, a3, a4] = args;
+	let mut tag = a0;
+
+	let special = match num {
+		0 => LS::None,
+		// Doors
+		10 => door(DK::Close, args, 0, None),
+		11 => door(DK::Open, args, 0, None),
+		12 => door(DK::OpenWaitClose, args, a2, None),
+		13 => {
+			let kind = if a2 != 0 { DK::OpenWaitClose } else { DK::Open };
+
+			door(kind, args, a2, Some(arg_u16(a3)))
+		}
+		202 => {
+			let kind = match a2 & 0b11 {
+				0 => DK::OpenWaitClose,
+				1 => DK::Open,
+				2 => DK::CloseWaitOpen,
+				_ => DK::Close,
+			};
+
+			let lock = (a4 != 0).then(|| arg_u16(a4));
+			door(kind, args, a3, lock)
+		}
+		// Floors
+		20 => floor(false, FT::Relative(a2), a1),
+		21 => floor(false, FT::LowestNeighborFloor, a1),
+		22 => floor(false, FT::NextNeighborFloor, a1),
+		23 => floor(true, FT::Relative(a2), a1),
+		24 => floor(true, FT::HighestNeighborFloor, a1),
+		25 => floor(true, FT::NextNeighborFloor, a1),
+		28 => LS::Floor(Floor {
+			target: FT::Ceiling,
+			up: true,
+			speed: arg_u16(a1),
+			offset: -8,
+			crush: a2 > 0,
+			change: None,
+		}),
+		35 => floor(true, FT::Relative(a2.saturating_mul(8)), a1),
+		36 => floor(false, FT::Relative(a2.saturating_mul(8)), a1),
+		37 => floor(true, FT::Absolute(if a3 != 0 { -a2 } else { a2 }), a1),
+		250 => LS::Donut(Donut {
+			pillar_speed: arg_u16(a1),
+			slime_speed: arg_u16(a2),
+		}),
+		// Ceilings
+		40 => ceiling(false, CT::Relative(a2), a1),
+		41 => ceiling(true, CT::Relative(a2), a1),
+		43 => LS::Ceiling(Ceiling {
+			target: CT::Floor,
+			up: false,
+			speed: arg_u16(a1),
+			offset: 8,
+			crush: a2 > 0,
+			change: None,
+		}),
+		47 => ceiling(true, CT::Absolute(if a3 != 0 { -a2 } else { a2 }), a1),
+		// Crushers
+		42 => LS::Crusher(Crusher {
+			speed: arg_u16(a1),
+			silent: false,
+			damage: arg_u16(a2),
+		}),
+		44 => LS::CrusherStop,
+		// Stairs
+		26 => stairs(false, args),
+		27 | 217 => stairs(true, args),
+		// Lifts
+		60 => lift(LiftTarget::Perpetual, a1, a2),
+		61 => LS::LiftStop,
+		62 => lift(LiftTarget::LowestNeighborFloor, a1, a2),
+		63 => lift(LiftTarget::Relative(-a3.saturating_mul(8)), a1, a2),
+		64 => lift(LiftTarget::HighestNeighborFloor, a1, a2),
+		65 => lift(LiftTarget::Relative(a3.saturating_mul(8)), a1, a2),
+		// Teleporters
+		70 | 71 => {
+			let dest = if a0 != 0 {
+				TeleportDest::Thing(a0)
+			} else {
+				TeleportDest::Sector
+			};
+
+			tag = if num == 70 { a1 } else { a2 };
+
+			LS::Teleport(Teleport {
+				dest,
+				silent: num == 71,
+			})
+		}
+		215 => {
+			tag = a1;
+
+			LS::Teleport(Teleport {
+				dest: TeleportDest::Line { reversed: a2 != 0 },
+				silent: true,
+			})
+		}
+		// Lights
+		110 => LS::Light(Light::Change(a1)),
+		111 => LS::Light(Light::Change(-a1)),
+		112 => LS::Light(Light::Set(arg_u16(a1))),
+		232 => LS::Light(Light::Strobe {
+			bright: arg_u16(a1),
+			dark: arg_u16(a2),
+		}),
+		233 => LS::Light(Light::MinNeighbor),
+		234 => LS::Light(Light::MaxNeighbor),
+		// Exits
+		74 => {
+			tag = 0;
+
+			LS::Exit(Exit::Map {
+				map: a0,
+				position: a1,
+			})
+		}
+		75 => {
+			tag = 0;
+			LS::Exit(Exit::EndGame)
+		}
+		243 => {
+			tag = 0;
+			LS::Exit(Exit::Normal)
+		}
+		244 => {
+			tag = 0;
+			LS::Exit(Exit::Secret)
+		}
+		// Scrollers
+		100..=103 => {
+			tag = 0;
+
+			let (x, y) = match num {
+				100 => (a0, 0),
+				101 => (-a0, 0),
+				102 => (0, a0),
+				_ => (0, -a0),
+			};
+
+			LS::Scroll(Scroll::Wall { x, y })
+		}
+		other => LS::Unknown(other),
+	};
+
+	LineSpecialDef {
+		special,
+		activation,
+		tag,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	const W1: Activation = Activation::PLAYER_CROSS;
+	const WR: Activation = W1.union(Activation::REPEAT);
+	const S1: Activation = Activation::PLAYER_USE;
+	const SR: Activation = S1.union(Activation::REPEAT);
+	const G1: Activation = Activation::PLAYER_IMPACT;
+
+	#[test]
+	fn doom() {
+		const CASES: &[(u16, Activation, LineSpecial)] = &[
+			(0, Activation::empty(), LineSpecial::None),
+			(
+				1,
+				Activation::PLAYER_USE
+					.union(Activation::MONSTER_USE)
+					.union(Activation::REPEAT),
+				LineSpecial::Door(Door {
+					kind: DoorKind::OpenWaitClose,
+					speed: 16,
+					delay: 150,
+					lock: None,
+				}),
+			),
+			(
+				16,
+				W1,
+				LineSpecial::Door(Door {
+					kind: DoorKind::CloseWaitOpen,
+					speed: 16,
+					delay: 1050,
+					lock: None,
+				}),
+			),
+			(
+				28,
+				S1.union(Activation::REPEAT),
+				LineSpecial::Door(Door {
+					kind: DoorKind::OpenWaitClose,
+					speed: 16,
+					delay: 150,
+					lock: Some(locks::RED),
+				}),
+			),
+			(
+				99,
+				SR,
+				LineSpecial::Door(Door {
+					kind: DoorKind::Open,
+					speed: 64,
+					delay: 0,
+					lock: Some(locks::BLUE),
+				}),
+			),
+			(
+				117,
+				SR,
+				LineSpecial::Door(Door {
+					kind: DoorKind::OpenWaitClose,
+					speed: 64,
+					delay: 150,
+					lock: None,
+				}),
+			),
+			(
+				5,
+				W1,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::LowestNeighborCeiling,
+					up: true,
+					speed: 8,
+					offset: 0,
+					crush: false,
+					change: None,
+				}),
+			),
+			(
+				20,
+				S1,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::NextNeighborFloor,
+					up: true,
+					speed: 4,
+					offset: 0,
+					crush: false,
+					change: Some(TextureChange {
+						model: ChangeModel::Trigger,
+						kind: ChangeKind::ZeroType,
+					}),
+				}),
+			),
+			(
+				36,
+				W1,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::HighestNeighborFloor,
+					up: false,
+					speed: 32,
+					offset: 8,
+					crush: false,
+					change: None,
+				}),
+			),
+			(
+				37,
+				W1,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::LowestNeighborFloor,
+					up: false,
+					speed: 8,
+					offset: 0,
+					crush: false,
+					change: Some(TextureChange {
+						model: ChangeModel::Numeric,
+						kind: ChangeKind::TextureAndType,
+					}),
+				}),
+			),
+			(
+				55,
+				S1,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::LowestNeighborCeiling,
+					up: true,
+					speed: 8,
+					offset: -8,
+					crush: true,
+					change: None,
+				}),
+			),
+			(
+				67,
+				SR,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::Relative(32),
+					up: true,
+					speed: 4,
+					offset: 0,
+					crush: false,
+					change: Some(TextureChange {
+						model: ChangeModel::Trigger,
+						kind: ChangeKind::Texture,
+					}),
+				}),
+			),
+			(
+				140,
+				S1,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::Relative(512),
+					up: true,
+					speed: 8,
+					offset: 0,
+					crush: false,
+					change: None,
+				}),
+			),
+			(
+				221,
+				S1,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::NextNeighborFloor,
+					up: false,
+					speed: 8,
+					offset: 0,
+					crush: false,
+					change: None,
+				}),
+			),
+			(
+				44,
+				W1,
+				LineSpecial::Ceiling(Ceiling {
+					target: CeilingTarget::Floor,
+					up: false,
+					speed: 8,
+					offset: 8,
+					crush: false,
+					change: None,
+				}),
+			),
+			(
+				202,
+				WR,
+				LineSpecial::Ceiling(Ceiling {
+					target: CeilingTarget::HighestNeighborFloor,
+					up: false,
+					speed: 8,
+					offset: 0,
+					crush: false,
+					change: None,
+				}),
+			),
+			(
+				6,
+				W1,
+				LineSpecial::Crusher(Crusher {
+					speed: 16,
+					silent: false,
+					damage: 10,
+				}),
+			),
+			(
+				141,
+				W1,
+				LineSpecial::Crusher(Crusher {
+					speed: 8,
+					silent: true,
+					damage: 10,
+				}),
+			),
+			(74, WR, LineSpecial::CrusherStop),
+			(
+				8,
+				W1,
+				LineSpecial::Stairs(Stairs {
+					up: true,
+					step: 8,
+					speed: 2,
+					delay: 0,
+					ignore_texture: false,
+				}),
+			),
+			(
+				127,
+				S1,
+				LineSpecial::Stairs(Stairs {
+					up: true,
+					step: 16,
+					speed: 32,
+					delay: 0,
+					ignore_texture: false,
+				}),
+			),
+			(
+				9,
+				S1,
+				LineSpecial::Donut(Donut {
+					pillar_speed: 4,
+					slime_speed: 4,
+				}),
+			),
+			(
+				10,
+				W1.union(Activation::MONSTER_CROSS),
+				LineSpecial::Lift(Lift {
+					target: LiftTarget::LowestNeighborFloor,
+					speed: 32,
+					delay: 105,
+				}),
+			),
+			(
+				123,
+				SR,
+				LineSpecial::Lift(Lift {
+					target: LiftTarget::LowestNeighborFloor,
+					speed: 64,
+					delay: 105,
+				}),
+			),
+			(
+				87,
+				WR,
+				LineSpecial::Lift(Lift {
+					target: LiftTarget::Perpetual,
+					speed: 8,
+					delay: 105,
+				}),
+			),
+			(54, W1, LineSpecial::LiftStop),
+			(
+				126,
+				Activation::MONSTER_CROSS.union(Activation::REPEAT),
+				LineSpecial::Teleport(Teleport {
+					dest: TeleportDest::Sector,
+					silent: false,
+				}),
+			),
+			(
+				263,
+				WR.union(Activation::MONSTER_CROSS),
+				LineSpecial::Teleport(Teleport {
+					dest: TeleportDest::Line { reversed: true },
+					silent: true,
+				}),
+			),
+			(12, W1, LineSpecial::Light(Light::MaxNeighbor)),
+			(104, W1, LineSpecial::Light(Light::MinNeighbor)),
+			(139, SR, LineSpecial::Light(Light::Set(35))),
+			(
+				17,
+				W1,
+				LineSpecial::Light(Light::Strobe {
+					bright: 5,
+					dark: 35,
+				}),
+			),
+			(11, S1, LineSpecial::Exit(Exit::Normal)),
+			(124, W1, LineSpecial::Exit(Exit::Secret)),
+			(198, G1, LineSpecial::Exit(Exit::Secret)),
+			(
+				48,
+				Activation::empty(),
+				LineSpecial::Scroll(Scroll::Wall { x: 64, y: 0 }),
+			),
+			(
+				1025,
+				Activation::empty(),
+				LineSpecial::Scroll(Scroll::Offsets {
+					mode: ScrollMode::Displacement,
+					tagged: true,
+				}),
+			),
+			(666, Activation::empty(), LineSpecial::Unknown(666)),
+		];
+
+		for (num, activation, special) in CASES {
+			let def = from_doom(*num, 3);
+			assert_eq!(def.special, *special, "special {num} mistranslated");
+			assert_eq!(
+				def.activation, *activation,
+				"special {num} has wrong activation"
+			);
+		}
+
+		// Manual specials act on the sector behind the line, regardless of tag.
+		assert_eq!(from_doom(31, 3).tag, 0);
+		assert_eq!(from_doom(31, 3).activation, S1);
+		assert_eq!(from_doom(29, 3).tag, 3);
+	}
+
+	#[test]
+	fn boom_generalized() {
+		// Floor: W1, normal speed, up, to lowest neighbor ceiling, monsters allowed.
+		let num = 0x6000 | (1 << 3) | (1 << 5) | (1 << 6) | (3 << 7);
+		assert_eq!(num, 0x61E8);
+
+		let def = from_doom(num, 7);
+		assert_eq!(def.tag, 7);
+		assert_eq!(
+			def.activation,
+			Activation::PLAYER_CROSS | Activation::MONSTER_CROSS
+		);
+		assert_eq!(
+			def.special,
+			LineSpecial::Floor(Floor {
+				target: FloorTarget::LowestNeighborCeiling,
+				up: true,
+				speed: 16,
+				offset: 0,
+				crush: false,
+				change: None,
+			})
+		);
+
+		// Floor: SR, turbo, down by 32, numeric model, change texture and type, crush.
+		let num = 0x6000 | 3 | (3 << 3) | (1 << 5) | (7 << 7) | (3 << 10) | (1 << 12);
+		assert_eq!(num, 0x7FBB);
+
+		let def = from_boom_generalized(num, 1).unwrap();
+		assert_eq!(def.activation, SR);
+		assert_eq!(
+			def.special,
+			LineSpecial::Floor(Floor {
+				target: FloorTarget::Relative(32),
+				up: false,
+				speed: 64,
+				offset: 0,
+				crush: true,
+				change: Some(TextureChange {
+					model: ChangeModel::Numeric,
+					kind: ChangeKind::TextureAndType,
+				}),
+			})
+		);
+
+		// Ceiling: G1, slow, up, to highest neighbor ceiling.
+		let num = 0x4000 | 4 | (1 << 6);
+		assert_eq!(num, 0x4044);
+
+		let def = from_boom_generalized(num, 1).unwrap();
+		assert_eq!(def.activation, G1);
+		assert_eq!(
+			def.special,
+			LineSpecial::Ceiling(Ceiling {
+				target: CeilingTarget::HighestNeighborCeiling,
+				up: true,
+				speed: 8,
+				offset: 0,
+				crush: false,
+				change: None,
+			})
+		);
+
+		// Door: DR, fast, close-wait-open, monsters allowed, 30-second delay.
+		let num = 0x3C00 | 7 | (2 << 3) | (2 << 5) | (1 << 7) | (3 << 8);
+		assert_eq!(num, 0x3FD7);
+
+		let def = from_boom_generalized(num, 9).unwrap();
+		assert_eq!(def.tag, 0);
+		assert_eq!(
+			def.activation,
+			Activation::PLAYER_USE | Activation::MONSTER_USE | Activation::REPEAT
+		);
+		assert_eq!(
+			def.special,
+			LineSpecial::Door(Door {
+				kind: DoorKind::CloseWaitOpen,
+				speed: 64,
+				delay: 1050,
+				lock: None,
+			})
+		);
+
+		// Locked door: S1, normal speed, open-stay, blue skull, skull and card equivalent.
+		let num = 0x3800 | 2 | (1 << 3) | (1 << 5) | (5 << 6) | (1 << 9);
+		assert_eq!(num, 0x3B6A);
+
+		let def = from_boom_generalized(num, 1).unwrap();
+		assert_eq!(def.activation, S1);
+		assert_eq!(
+			def.special,
+			LineSpecial::Door(Door {
+				kind: DoorKind::Open,
+				speed: 32,
+				delay: 0,
+				lock: Some(locks::BLUE),
+			})
+		);
+
+		// The same without equivalence, and with every key required.
+		assert_eq!(
+			from_boom_generalized(num & !(1 << 9), 1).unwrap().special,
+			LineSpecial::Door(Door {
+				kind: DoorKind::Open,
+				speed: 32,
+				delay: 0,
+				lock: Some(locks::BLUE_SKULL),
+			})
+		);
+
+		assert_eq!(
+			from_boom_generalized((num & !(7 << 6)) | (7 << 6), 1)
+				.unwrap()
+				.special,
+			LineSpecial::Door(Door {
+				kind: DoorKind::Open,
+				speed: 32,
+				delay: 0,
+				lock: Some(locks::ALL_THREE),
+			})
+		);
+
+		// Lift: WR, turbo, 5-second delay, to next lower floor.
+		let num = 0x3400 | 1 | (3 << 3) | (2 << 6) | (1 << 8);
+		assert_eq!(num, 0x3599);
+
+		let def = from_boom_generalized(num, 1).unwrap();
+		assert_eq!(def.activation, WR);
+		assert_eq!(
+			def.special,
+			LineSpecial::Lift(Lift {
+				target: LiftTarget::NextLowerFloor,
+				speed: 128,
+				delay: 175,
+			})
+		);
+
+		// Stairs: S1, slow, monsters allowed, step 16, up, ignore texture.
+		let num = 0x3000 | 2 | (1 << 5) | (2 << 6) | (1 << 8) | (1 << 9);
+		assert_eq!(num, 0x33A2);
+
+		let def = from_boom_generalized(num, 1).unwrap();
+		assert_eq!(def.activation, S1 | Activation::MONSTER_USE);
+		assert_eq!(
+			def.special,
+			LineSpecial::Stairs(Stairs {
+				up: true,
+				step: 16,
+				speed: 2,
+				delay: 0,
+				ignore_texture: true,
+			})
+		);
+
+		// Crusher: GR, fast, silent.
+		let num = 0x2F80 | 5 | (2 << 3) | (1 << 6);
+		assert_eq!(num, 0x2FD5);
+
+		let def = from_boom_generalized(num, 1).unwrap();
+		assert_eq!(def.activation, G1 | Activation::REPEAT);
+		assert_eq!(
+			def.special,
+			LineSpecial::Crusher(Crusher {
+				speed: 32,
+				silent: true,
+				damage: 10,
+			})
+		);
+
+		assert!(from_boom_generalized(GEN_CRUSHER - 1, 1).is_none());
+	}
+
+	#[test]
+	fn hexen() {
+		const CASES: &[(u16, [i32; 5], i32, LineSpecial)] = &[
+			(
+				12,
+				[4, 16, 34, 0, 0],
+				4,
+				LineSpecial::Door(Door {
+					kind: DoorKind::OpenWaitClose,
+					speed: 16,
+					delay: 148,
+					lock: None,
+				}),
+			),
+			(
+				13,
+				[0, 32, 0, 130, 0],
+				0,
+				LineSpecial::Door(Door {
+					kind: DoorKind::Open,
+					speed: 32,
+					delay: 0,
+					lock: Some(locks::BLUE),
+				}),
+			),
+			(
+				20,
+				[2, 8, 64, 0, 0],
+				2,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::Relative(64),
+					up: false,
+					speed: 8,
+					offset: 0,
+					crush: false,
+					change: None,
+				}),
+			),
+			(
+				35,
+				[2, 8, 4, 0, 0],
+				2,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::Relative(32),
+					up: true,
+					speed: 8,
+					offset: 0,
+					crush: false,
+					change: None,
+				}),
+			),
+			(
+				37,
+				[2, 8, 16, 1, 0],
+				2,
+				LineSpecial::Floor(Floor {
+					target: FloorTarget::Absolute(-16),
+					up: true,
+					speed: 8,
+					offset: 0,
+					crush: false,
+					change: None,
+				}),
+			),
+			(
+				43,
+				[5, 8, 20, 0, 0],
+				5,
+				LineSpecial::Ceiling(Ceiling {
+					target: CeilingTarget::Floor,
+					up: false,
+					speed: 8,
+					offset: 8,
+					crush: true,
+					change: None,
+				}),
+			),
+			(
+				42,
+				[5, 16, 10, 0, 0],
+				5,
+				LineSpecial::Crusher(Crusher {
+					speed: 16,
+					silent: false,
+					damage: 10,
+				}),
+			),
+			(
+				62,
+				[1, 32, 105, 0, 0],
+				1,
+				LineSpecial::Lift(Lift {
+					target: LiftTarget::LowestNeighborFloor,
+					speed: 32,
+					delay: 105,
+				}),
+			),
+			(
+				63,
+				[1, 32, 35, 3, 0],
+				1,
+				LineSpecial::Lift(Lift {
+					target: LiftTarget::Relative(-24),
+					speed: 32,
+					delay: 35,
+				}),
+			),
+			(
+				217,
+				[6, 4, 8, 0, 0],
+				6,
+				LineSpecial::Stairs(Stairs {
+					up: true,
+					step: 8,
+					speed: 4,
+					delay: 0,
+					ignore_texture: false,
+				}),
+			),
+			(
+				70,
+				[0, 9, 0, 0, 0],
+				9,
+				LineSpecial::Teleport(Teleport {
+					dest: TeleportDest::Sector,
+					silent: false,
+				}),
+			),
+			(
+				71,
+				[12, 0, 0, 0, 0],
+				0,
+				LineSpecial::Teleport(Teleport {
+					dest: TeleportDest::Thing(12),
+					silent: true,
+				}),
+			),
+			(
+				215,
+				[1, 2, 1, 0, 0],
+				2,
+				LineSpecial::Teleport(Teleport {
+					dest: TeleportDest::Line { reversed: true },
+					silent: true,
+				}),
+			),
+			(
+				111,
+				[3, 16, 0, 0, 0],
+				3,
+				LineSpecial::Light(Light::Change(-16)),
+			),
+			(
+				232,
+				[3, 5, 35, 0, 0],
+				3,
+				LineSpecial::Light(Light::Strobe {
+					bright: 5,
+					dark: 35,
+				}),
+			),
+			(
+				74,
+				[7, 1, 0, 0, 0],
+				0,
+				LineSpecial::Exit(Exit::Map {
+					map: 7,
+					position: 1,
+				}),
+			),
+			(244, [0; 5], 0, LineSpecial::Exit(Exit::Secret)),
+			(
+				101,
+				[64, 0, 0, 0, 0],
+				0,
+				LineSpecial::Scroll(Scroll::Wall { x: -64, y: 0 }),
+			),
+			(80, [1, 0, 0, 0, 0], 1, LineSpecial::Unknown(80)),
+		];
+
+		for (num, args, tag, special) in CASES {
+			let def = from_hexen(*num, *args, S1);
+			assert_eq!(def.special, *special, "special {num} mistranslated");
+			assert_eq!(def.tag, *tag, "special {num} has wrong tag");
+			assert_eq!(def.activation, S1);
+		}
+	}
+
+	#[test]
+	fn activation() {
+		// Player use, repeatable, with ZDoom's monster activation bit.
+		assert_eq!(
+			Activation::from_hexen_flags(0x0200 | (1 << 10) | 0x2000),
+			Activation::PLAYER_USE | Activation::MONSTER_USE | Activation::REPEAT
+		);
+
+		assert_eq!(
+			Activation::from_hexen_flags(2 << 10),
+			Activation::MONSTER_CROSS
+		);
+
+		assert_eq!(
+			Activation::from_udmf(LineFlags::ALLOW_PLAYER_CROSS | LineFlags::REPEAT_SPECIAL),
+			WR
+		);
+	}
+}
@@ -124,6 +124,100 @@ pub fn parse<S: Sink>(source: &str, sink_ctx: S::Context) -> Result<S, Error> {
 	Ok(sink)
 }
 
+/// Parses `source` with a [`Sink`] which discards everything, asserting that
+/// every reported span and every [`KeyVal`] lies within `source`.
+///
+/// This is what doomfront's `udmf_textmap` fuzz target runs.
+#[doc(hidden)]
+pub fn fuzz(source: &str) {
+	struct Checker {
+		bounds: std::ops::Range<usize>,
+	}
+
+	impl Checker {
+		fn check_kvp(&self, kvp: KeyVal) {
+			let in_bounds = |s: &str| {
+				let start = s.as_ptr() as usize;
+				self.bounds.start <= start && (start + s.len()) <= self.bounds.end
+			};
+
+			assert!(in_bounds(kvp.key), "key `{}` is not in the source", kvp.key);
+
+			if let Value::String(v) | Value::Float(v) | Value::Int(v) = kvp.val {
+				assert!(in_bounds(v), "value `{v}` is not in the source");
+			}
+		}
+	}
+
+	impl Sink for Checker {
+		type Context = std::ops::Range<usize>;
+
+		type LineDef = ();
+		type SectorDef = ();
+		type SideDef = ();
+		type ThingDef = ();
+		type Vertex = ();
+
+		fn with_namespace(_: &str, bounds: Self::Context) -> Option<Self> {
+			Some(Self { bounds })
+		}
+
+		fn start_linedef(&mut self) {}
+
+		fn linedef_property(&mut self, _: &mut (), kvp: KeyVal) {
+			self.check_kvp(kvp);
+		}
+
+		fn finish_linedef(&mut self, _: ()) {}
+
+		fn start_sectordef(&mut self) {}
+
+		fn sectordef_property(&mut self, _: &mut (), kvp: KeyVal) {
+			self.check_kvp(kvp);
+		}
+
+		fn finish_sectordef(&mut self, _: ()) {}
+
+		fn start_sidedef(&mut self) {}
+
+		fn sidedef_property(&mut self, _: &mut (), kvp: KeyVal) {
+			self.check_kvp(kvp);
+		}
+
+		fn finish_sidedef(&mut self, _: ()) {}
+
+		fn start_thingdef(&mut self) {}
+
+		fn thingdef_property(&mut self, _: &mut (), kvp: KeyVal) {
+			self.check_kvp(kvp);
+		}
+
+		fn finish_thingdef(&mut self, _: ()) {}
+
+		fn start_vertex(&mut self) {}
+
+		fn vertex_property(&mut self, _: &mut (), kvp: KeyVal) {
+			self.check_kvp(kvp);
+		}
+
+		fn finish_vertex(&mut self, _: ()) {}
+
+		fn parse_error(&mut self, error: Error) {
+			let len = self.bounds.end - self.bounds.start;
+
+			if let Error::Lex(span) | Error::Parse { span, .. } = &error {
+				assert!(
+					span.start <= span.end && span.end <= len,
+					"{error} is out of bounds (source length: {len})"
+				);
+			}
+		}
+	}
+
+	let bounds = source.as_ptr() as usize..(source.as_ptr() as usize + source.len());
+	let _ = parse::<Checker>(source, bounds);
+}
+
 #[derive(Debug)]
 pub enum Error {
 	InvalidNamespace(String),
@@ -12,6 +12,8 @@
 
 #[cfg(feature = "acs")]
 pub mod acs;
+#[cfg(feature = "audio")]
+pub mod audio;
 #[cfg(feature = "graphics")]
 pub mod gfx;
 pub mod level;
@@ -27,6 +29,7 @@ pub type SpawnNum = u16;
 #[derive(Debug)]
 pub enum Error {
 	InvalidHeader { details: &'static str },
+	InvalidRecord { index: usize, details: &'static str },
 	MissingHeader { expected: usize },
 	MissingRecord { expected: usize, actual: usize },
 	SizeMismatch { expected: usize, actual: usize },
@@ -40,6 +43,9 @@ impl std::fmt::Display for Error {
 			Self::InvalidHeader { details } => {
 				write!(f, "data header is malformed: {details}")
 			}
+			Self::InvalidRecord { index, details } => {
+				write!(f, "record {index} is malformed: {details}")
+			}
 			Self::MissingHeader { expected } => {
 				write!(f, "expected at least {expected} bytes for a header")
 			}
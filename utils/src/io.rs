@@ -6,7 +6,7 @@ use std::{
 };
 
 use bytemuck::AnyBitPattern;
-use byteorder::{ByteOrder, LittleEndian};
+use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
 
 /// Strongly-typed cursor, for easy migration of pointer arithmetic code.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -119,6 +119,26 @@ pub trait CursorExt {
 	/// See [`bytemuck::from_bytes`]. Advances the cursor by the size of `A`.
 	#[must_use]
 	fn read_from_bytes<A: AnyBitPattern>(&mut self) -> &A;
+
+	/// Advances the cursor by 2 bytes. Panics if fewer than that remain.
+	#[must_use]
+	fn read_le_u16(&mut self) -> u16;
+
+	/// Advances the cursor by 2 bytes. Panics if fewer than that remain.
+	#[must_use]
+	fn read_le_i16(&mut self) -> i16;
+
+	/// Advances the cursor by 4 bytes. Panics if fewer than that remain.
+	#[must_use]
+	fn read_le_u32(&mut self) -> u32;
+
+	/// Advances the cursor by 4 bytes. Panics if fewer than that remain.
+	#[must_use]
+	fn read_le_i32(&mut self) -> i32;
+
+	/// Advances the cursor by 8 bytes. Panics if fewer than that remain.
+	#[must_use]
+	fn read_le_u64(&mut self) -> u64;
 }
 
 impl<T> CursorExt for Cursor<T>
@@ -135,6 +155,31 @@ where
 		self.advance(size as u64);
 		bytemuck::from_bytes(&self.get_ref().as_ref()[pos..(pos + size)])
 	}
+
+	fn read_le_u16(&mut self) -> u16 {
+		self.read_u16::<LittleEndian>()
+			.expect("`CursorExt::read_le_u16` out of bounds")
+	}
+
+	fn read_le_i16(&mut self) -> i16 {
+		self.read_i16::<LittleEndian>()
+			.expect("`CursorExt::read_le_i16` out of bounds")
+	}
+
+	fn read_le_u32(&mut self) -> u32 {
+		self.read_u32::<LittleEndian>()
+			.expect("`CursorExt::read_le_u32` out of bounds")
+	}
+
+	fn read_le_i32(&mut self) -> i32 {
+		self.read_i32::<LittleEndian>()
+			.expect("`CursorExt::read_le_i32` out of bounds")
+	}
+
+	fn read_le_u64(&mut self) -> u64 {
+		self.read_u64::<LittleEndian>()
+			.expect("`CursorExt::read_le_u64` out of bounds")
+	}
 }
 
 /// Checks for a 4-byte magic number.
@@ -232,3 +277,92 @@ pub fn is_7z(bytes: &[u8]) -> bool {
 		&& bytes[4] == 0x27
 		&& bytes[5] == 0x1C
 }
+
+/// Checks for the 8-byte header of Doom's digitized sound format (as used by
+/// DMX): a format number of 3, a sample rate, and a sample count which does not
+/// exceed the remaining length of `bytes`.
+/// Ensure the given slice starts at the file's beginning.
+#[must_use]
+pub fn is_doom_sfx(bytes: &[u8]) -> bool {
+	if bytes.len() < 8 || bytes[0..2] != [0x03, 0x00] {
+		return false;
+	}
+
+	let sample_rate = LittleEndian::read_u16(&bytes[2..4]);
+	let sample_count = LittleEndian::read_u32(&bytes[4..8]);
+
+	(4000..=48000).contains(&sample_rate)
+		&& sample_count > 0
+		&& (sample_count as usize) <= (bytes.len() - 8)
+}
+
+/// Adapted from SLADE's `DoomPCSpeakerDataFormat::isThisFormat`.
+#[must_use]
+pub fn is_pc_speaker_sound(bytes: &[u8]) -> bool {
+	if bytes.len() < 4 {
+		return false;
+	}
+
+	// (SLADE) The first two bytes must always be NUL.
+	if bytes[0] > 0 || bytes[1] > 0 {
+		return false;
+	}
+
+	// (SLADE) Next is the number of samples (LE uint16_t), and each sample
+	// is a single byte, so the size can be checked easily.
+	let sample_count = 4 + LittleEndian::read_u16(&bytes[2..4]) as usize;
+	bytes.len() == sample_count
+}
+
+/// Checks for the 4-byte magic number of the DMX MUS music format.
+/// Ensure the given slice starts at the file's beginning.
+#[must_use]
+pub fn is_dmx_mus(bytes: &[u8]) -> bool {
+	bytes.len() >= 4 && matches!(&bytes[0..4], b"MUS\x1A")
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn cursor_le_reads() {
+		const BYTES: &[u8] = &[
+			0x34, 0x12, // u16
+			0xFE, 0xFF, // i16
+			0x78, 0x56, 0x34, 0x12, // u32
+			0xFF, 0xFF, 0xFF, 0xFF, // i32
+			0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, // u64
+		];
+
+		let mut cursor = Cursor::new(BYTES);
+		assert_eq!(cursor.read_le_u16(), 0x1234);
+		assert_eq!(cursor.read_le_i16(), -2);
+		assert_eq!(cursor.read_le_u32(), 0x12345678);
+		assert_eq!(cursor.read_le_i32(), -1);
+		assert_eq!(cursor.read_le_u64(), 0x8000_0000_0000_0001);
+		assert_eq!(cursor.position(), BYTES.len() as u64);
+	}
+
+	#[test]
+	fn sound_detection() {
+		let mut sfx = vec![0x03, 0x00, 0x11, 0x2B, 0x04, 0x00, 0x00, 0x00];
+		sfx.extend_from_slice(&[0x80; 4]);
+		assert!(is_doom_sfx(&sfx));
+		assert!(!is_doom_sfx(&sfx[..11]));
+		assert!(!is_pc_speaker_sound(&sfx));
+		assert!(!is_dmx_mus(&sfx));
+
+		sfx[2..4].copy_from_slice(&0_u16.to_le_bytes());
+		assert!(!is_doom_sfx(&sfx));
+
+		let pcspk = [0x00, 0x00, 0x03, 0x00, 0x10, 0x20, 0x30];
+		assert!(is_pc_speaker_sound(&pcspk));
+		assert!(!is_pc_speaker_sound(&pcspk[..6]));
+		assert!(!is_doom_sfx(&pcspk));
+
+		assert!(is_dmx_mus(b"MUS\x1A\x00\x00"));
+		assert!(!is_dmx_mus(b"MUS"));
+		assert!(!is_dmx_mus(b"MThd"));
+	}
+}
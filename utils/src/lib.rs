@@ -154,7 +154,93 @@ impl SendTracker {
 /// has persisted through Doom's descendant source ports (for whatever reason).
 /// For compatibility purposes, VileTech sometimes needs to pretend that there's
 /// no game data namespacing and look up the last loaded thing with a certain name.
-pub type Id8 = arrayvec::ArrayString<{ std::mem::size_of::<char>() * 8 }>;
+///
+/// Derived comparisons are exact; compare against a string for a case-insensitive
+/// comparison which ignores trailing NULs, or [normalize](Id8::normalized) first.
+///
+/// Before this was a newtype, `Id8` was an alias for [`arrayvec::ArrayString`],
+/// so `==` against a `str` was exact and [`Id8::as_str`] kept trailing NULs.
+/// Both behaviors have changed; use `id8.as_str() == "..."` where an exact
+/// comparison is needed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Id8(arrayvec::ArrayString<{ std::mem::size_of::<char>() * 8 }>);
+
+impl Id8 {
+	#[must_use]
+	pub fn new() -> Self {
+		Self(arrayvec::ArrayString::new())
+	}
+
+	/// Returns a copy converted to ASCII uppercase, with any trailing NULs
+	/// replaced such that the result is padded with NULs up to 8 bytes.
+	#[must_use]
+	pub fn normalized(&self) -> Self {
+		let mut ret = Self::new();
+
+		for c in self.as_str().chars() {
+			ret.0.push(c.to_ascii_uppercase());
+		}
+
+		while ret.0.len() < 8 {
+			ret.0.push('\0');
+		}
+
+		ret
+	}
+
+	/// The content of this ID with trailing NULs trimmed.
+	///
+	/// Note that this differs from [`arrayvec::ArrayString::as_str`], which is
+	/// still reachable through [`Deref`](std::ops::Deref) and yields any NULs.
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		self.0.trim_end_matches('\0')
+	}
+}
+
+impl<'s> TryFrom<&'s str> for Id8 {
+	type Error = arrayvec::CapacityError<&'s str>;
+
+	/// Fails if `string` is longer than this type's capacity.
+	fn try_from(string: &'s str) -> Result<Self, Self::Error> {
+		arrayvec::ArrayString::from(string).map(Self)
+	}
+}
+
+impl std::ops::Deref for Id8 {
+	type Target = arrayvec::ArrayString<{ std::mem::size_of::<char>() * 8 }>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl std::ops::DerefMut for Id8 {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl std::fmt::Display for Id8 {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.as_str())
+	}
+}
+
+impl PartialEq<str> for Id8 {
+	/// ASCII case-insensitive, and ignores trailing NULs on both sides.
+	fn eq(&self, other: &str) -> bool {
+		self.as_str()
+			.eq_ignore_ascii_case(other.trim_end_matches('\0'))
+	}
+}
+
+impl PartialEq<&str> for Id8 {
+	fn eq(&self, other: &&str) -> bool {
+		self == *other
+	}
+}
 
 /// Returns `None` if `id8` starts with a NUL.
 /// Return values have no trailing NUL bytes.
@@ -232,3 +318,33 @@ impl From<u32> for Ascii4 {
 		Self(value)
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn id8_comparison() {
+		let id8 = read_id8(*b"troo\0\0\0\0").unwrap();
+		assert!(id8 == "TROO");
+		assert!(id8 == "troo\0");
+		assert!(id8 != "TROOA");
+
+		let padded = Id8::try_from("troo\0\0\0\0").unwrap();
+		assert!(padded == "TROO");
+		assert_eq!(padded.as_str(), "troo");
+		assert_eq!(padded.to_string(), "troo");
+	}
+
+	#[test]
+	fn id8_normalization() {
+		let id8 = Id8::try_from("Troo").unwrap();
+		let norm = id8.normalized();
+		assert_eq!(norm.len(), 8);
+		assert_eq!(norm.as_str(), "TROO");
+		assert_eq!(norm, Id8::try_from("TROO\0\0\0\0").unwrap());
+		assert_eq!(norm.normalized(), norm);
+		assert_eq!(read_id8(*b"TROO\0\0\0\0").unwrap().normalized(), norm);
+		assert!(norm == "troo");
+	}
+}
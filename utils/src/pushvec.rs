@@ -8,7 +8,7 @@
 
 use std::{
 	cell::UnsafeCell,
-	ops::Index,
+	ops::{Index, IndexMut},
 	sync::atomic::{AtomicUsize, Ordering},
 };
 
@@ -31,11 +31,21 @@ impl<T> PushVec<T> {
 	}
 
 	/// Return an `Iterator` over the elements of the vec.
-	pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
-		// FIXME this could be written to be a little more efficient probably,
-		// if we made it read each pointer only once.  On the other hand, that
-		// could make a reversed iterator less efficient?
-		(0..self.len()).map(|i| unsafe { self.get_unchecked(i) })
+	pub fn iter(&self) -> Iter<'_, T> {
+		Iter {
+			inner: self,
+			remaining: 0..self.len(),
+		}
+	}
+
+	/// Return an `Iterator` over mutable references to the elements of the vec.
+	pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+		let len = self.len();
+
+		IterMut {
+			inner: self,
+			remaining: 0..len,
+		}
 	}
 
 	/// Get the length of the array.
@@ -177,6 +187,32 @@ impl<T> Index<usize> for PushVec<T> {
 	}
 }
 
+impl<T> IndexMut<usize> for PushVec<T> {
+	fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+		assert!(idx < self.len());
+		let (array, offset) = indices(idx);
+		// No ordering concerns here, since `&mut self` rules out a concurrent push.
+		let ptr = unsafe { *self.data[array as usize].get() };
+		unsafe { &mut *ptr.add(offset) }
+	}
+}
+
+impl<T> Extend<T> for PushVec<T> {
+	fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+		for val in iter {
+			let _ = self.push(val);
+		}
+	}
+}
+
+impl<T> FromIterator<T> for PushVec<T> {
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+		let mut ret = Self::new();
+		ret.extend(iter);
+		ret
+	}
+}
+
 impl<T> Drop for PushVec<T> {
 	fn drop(&mut self) {
 		// First we'll drop all the `T` in a slightly sloppy way.
@@ -190,6 +226,102 @@ impl<T> Drop for PushVec<T> {
 	}
 }
 
+/// An [`Iterator`] over references to the values in a [`PushVec`].
+#[derive(Debug)]
+pub struct Iter<'v, T> {
+	inner: &'v PushVec<T>,
+	remaining: std::ops::Range<usize>,
+}
+
+impl<'v, T> Iterator for Iter<'v, T> {
+	type Item = &'v T;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		// SAFETY: `remaining` ends at a length which was acquired upon creation.
+		self.remaining
+			.next()
+			.map(|idx| unsafe { self.inner.get_unchecked(idx) })
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.remaining.size_hint()
+	}
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		self.remaining
+			.next_back()
+			.map(|idx| unsafe { self.inner.get_unchecked(idx) })
+	}
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'v, T> IntoIterator for &'v PushVec<T> {
+	type Item = &'v T;
+
+	type IntoIter = Iter<'v, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+/// An [`Iterator`] over mutable references to the values in a [`PushVec`].
+#[derive(Debug)]
+pub struct IterMut<'v, T> {
+	inner: &'v mut PushVec<T>,
+	remaining: std::ops::Range<usize>,
+}
+
+impl<'v, T> IterMut<'v, T> {
+	#[must_use]
+	fn get(&mut self, idx: usize) -> &'v mut T {
+		let (array, offset) = indices(idx);
+
+		// SAFETY: the mutable borrow of the vec rules out a concurrent push,
+		// and every index in `remaining` gets yielded at most once, so the
+		// returned references never alias.
+		unsafe {
+			let ptr = *self.inner.data[array as usize].get();
+			&mut *ptr.add(offset)
+		}
+	}
+}
+
+impl<'v, T> Iterator for IterMut<'v, T> {
+	type Item = &'v mut T;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let idx = self.remaining.next()?;
+		Some(self.get(idx))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.remaining.size_hint()
+	}
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		let idx = self.remaining.next_back()?;
+		Some(self.get(idx))
+	}
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<'v, T> IntoIterator for &'v mut PushVec<T> {
+	type Item = &'v mut T;
+
+	type IntoIter = IterMut<'v, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_mut()
+	}
+}
+
 /// An [`Iterator`] for the values contained in the [`PushVec`].
 #[derive(Debug)]
 pub struct IntoIter<T> {
@@ -390,6 +522,24 @@ mod test {
 		assert_eq!(vec, ve2);
 	}
 
+	#[test]
+	fn test_mutation_and_collecting() {
+		let mut v: PushVec<usize> = (0..20).collect();
+		v.extend(20..40);
+		assert_eq!(v.len(), 40);
+
+		for n in &mut v {
+			*n *= 2;
+		}
+
+		v[0] = 1;
+		assert_eq!(v[0], 1);
+		assert_eq!(v[39], 78);
+		assert_eq!(v.iter().next_back(), Some(&78));
+		assert_eq!((&v).into_iter().len(), 40);
+		assert_eq!(v.iter_mut().nth(10).copied(), Some(20));
+	}
+
 	#[test]
 	fn test_parallel_pushing() {
 		use std::sync::Arc;
@@ -120,7 +120,19 @@ impl std::hash::Hash for RString {
 
 impl Borrow<str> for RString {
 	fn borrow(&self) -> &str {
-		self.as_ref()
+		self.as_str()
+	}
+}
+
+impl AsRef<str> for RString {
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl AsRef<[u8]> for RString {
+	fn as_ref(&self) -> &[u8] {
+		&self.0.slice
 	}
 }
 
@@ -171,6 +183,16 @@ fn soundness() {
 	set.insert(rstring.clone());
 	assert!(set.contains(&rstring));
 	assert!(set.contains("/patience/is::a::virtue"));
+
+	assert_eq!(rstring.to_string(), "/patience/is::a::virtue");
+	assert_eq!(AsRef::<[u8]>::as_ref(&rstring), b"/patience/is::a::virtue");
+
+	let mut sorted = [RString::new("b"), RString::new("ab"), RString::new("a")];
+	sorted.sort();
+	assert_eq!(
+		sorted,
+		[RString::new("a"), RString::new("ab"), RString::new("b")]
+	);
 }
 
 // Details /////////////////////////////////////////////////////////////////////
@@ -6,6 +6,8 @@ criterion::criterion_group!(benches, operations);
 criterion::criterion_main!(benches);
 
 fn operations(crit: &mut criterion::Criterion) {
+	synthetic(crit);
+
 	let Some(freedoom2) = freedoom2_path() else {
 		return;
 	};
@@ -47,6 +49,41 @@ fn operations(crit: &mut criterion::Criterion) {
 	grp.finish();
 }
 
+/// Lookup times should not depend on the position of an entry in its folder.
+fn synthetic(crit: &mut criterion::Criterion) {
+	const LUMP_COUNT: usize = 50_000;
+
+	let path = std::env::temp_dir().join(format!("viletech-fs-bench-{}.wad", std::process::id()));
+
+	let lumps = (0..LUMP_COUNT)
+		.map(|i| (format!("L{i:05}"), [0_u8; 0]))
+		.collect::<Vec<_>>();
+
+	std::fs::write(&path, viletech_fs::build_pwad(&lumps)).unwrap();
+
+	let mut vfs = VirtualFs::default();
+	let result = vfs.mount(&path, VPath::new("synth"));
+	std::fs::remove_file(&path).unwrap();
+	result.unwrap();
+
+	let mut grp = crit.benchmark_group("Lookup, 50k Entries");
+
+	for (label, vpath) in [
+		("First", "/synth/L00000"),
+		("Middle", "/synth/L25000"),
+		("Last", "/synth/L49999"),
+		("Missing", "/synth/L50000"),
+	] {
+		grp.bench_function(label, |bencher| {
+			bencher.iter(|| {
+				let _ = std::hint::black_box(vfs.lookup(VPath::new(vpath)));
+			});
+		});
+	}
+
+	grp.finish();
+}
+
 #[must_use]
 fn freedoom2_path() -> Option<PathBuf> {
 	let Ok(evar) = std::env::var("VILETECHFS_SAMPLE_DIR") else {
@@ -0,0 +1,175 @@
+//! A byte-denominated semaphore for bounding the memory used by parallel file processing.
+//!
+//! Processing every file in a mount in parallel can need a decode buffer many
+//! times each file's size. Left unbounded, a load order of large archives can
+//! have thousands of these in flight at once.
+
+use std::sync::atomic::{self, AtomicUsize};
+
+use parking_lot::{Condvar, Mutex};
+use rayon::prelude::*;
+
+use crate::{FileRef, FolderRef};
+
+/// Tracks the number of bytes of intermediate buffers currently alive across
+/// all tasks sharing it, and blocks any task which would push that number past
+/// a limit until enough other tasks have finished.
+#[derive(Debug)]
+pub struct MemBudget {
+	limit: usize,
+	live: Mutex<usize>,
+	freed: Condvar,
+	peak: AtomicUsize,
+}
+
+impl MemBudget {
+	/// Used by [`Self::default_limit`] if physical memory can not be determined.
+	pub const FALLBACK_LIMIT: usize = 1024 * 1024 * 1024;
+
+	#[must_use]
+	pub fn new(limit: usize) -> Self {
+		Self {
+			limit: limit.max(1),
+			live: Mutex::new(0),
+			freed: Condvar::new(),
+			peak: AtomicUsize::new(0),
+		}
+	}
+
+	/// One quarter of the machine's physical memory,
+	/// or [`Self::FALLBACK_LIMIT`] if that can not be determined.
+	#[must_use]
+	pub fn default_limit() -> usize {
+		match physical_memory() {
+			Some(total) => total / 4,
+			None => Self::FALLBACK_LIMIT,
+		}
+	}
+
+	/// Blocks the calling thread until `bytes` can be reserved without exceeding
+	/// the limit. The reservation lasts until the returned guard is dropped.
+	///
+	/// A request larger than the whole budget waits until nothing else is
+	/// reserved and then proceeds alone, so it can never deadlock. Callers should
+	/// check [`BudgetGuard::is_oversized`] and warn the user if it returns `true`.
+	#[must_use]
+	pub fn reserve(&self, bytes: usize) -> BudgetGuard<'_> {
+		let mut live = self.live.lock();
+
+		if bytes > self.limit {
+			while *live != 0 {
+				self.freed.wait(&mut live);
+			}
+		} else {
+			while (*live + bytes) > self.limit {
+				self.freed.wait(&mut live);
+			}
+		}
+
+		*live += bytes;
+		self.peak.fetch_max(*live, atomic::Ordering::Relaxed);
+
+		BudgetGuard {
+			budget: self,
+			bytes,
+		}
+	}
+
+	#[must_use]
+	pub fn limit(&self) -> usize {
+		self.limit
+	}
+
+	/// The number of bytes currently reserved.
+	#[must_use]
+	pub fn live(&self) -> usize {
+		*self.live.lock()
+	}
+
+	/// The highest number of bytes that were ever reserved at once.
+	#[must_use]
+	pub fn peak(&self) -> usize {
+		self.peak.load(atomic::Ordering::Relaxed)
+	}
+}
+
+impl Default for MemBudget {
+	fn default() -> Self {
+		Self::new(Self::default_limit())
+	}
+}
+
+/// Releases a reservation made via [`MemBudget::reserve`] when dropped.
+#[derive(Debug)]
+pub struct BudgetGuard<'b> {
+	budget: &'b MemBudget,
+	bytes: usize,
+}
+
+impl BudgetGuard<'_> {
+	#[must_use]
+	pub fn bytes(&self) -> usize {
+		self.bytes
+	}
+
+	/// Returns `true` if this reservation is larger than the entire budget
+	/// and so had to wait for every other reservation to be released.
+	#[must_use]
+	pub fn is_oversized(&self) -> bool {
+		self.bytes > self.budget.limit
+	}
+}
+
+impl Drop for BudgetGuard<'_> {
+	fn drop(&mut self) {
+		let mut live = self.budget.live.lock();
+		*live -= self.bytes;
+		drop(live);
+		self.budget.freed.notify_all();
+	}
+}
+
+impl<'vfs> FolderRef<'vfs> {
+	/// Runs `op` over every file in this folder (not recursively) in parallel,
+	/// reserving each file's [size](crate::VFile::size) against `budget` for
+	/// the duration of its call. Results are in the same order as [`Self::files`].
+	/// `op` is also given the reservation, so it can warn about
+	/// [oversized](BudgetGuard::is_oversized) files.
+	///
+	/// (RAT) Larger files are started first, so that the budget is less likely
+	/// to be fragmented by many small reservations when a large one arrives.
+	pub fn par_files_budgeted<F, R>(&self, budget: &MemBudget, op: F) -> Vec<R>
+	where
+		F: Fn(FileRef<'vfs>, &BudgetGuard) -> R + Send + Sync,
+		R: Send,
+	{
+		let mut files: Vec<_> = self.files().enumerate().collect();
+		files.sort_by_key(|(_, vfile)| std::cmp::Reverse(vfile.size()));
+
+		let mut ret: Vec<_> = files
+			.into_par_iter()
+			.map(|(i, vfile)| {
+				let guard = budget.reserve(vfile.size());
+				(i, op(vfile, &guard))
+			})
+			.collect();
+
+		ret.sort_unstable_by_key(|(i, _)| *i);
+		ret.into_iter().map(|(_, r)| r).collect()
+	}
+}
+
+#[cfg(target_os = "linux")]
+#[must_use]
+fn physical_memory() -> Option<usize> {
+	let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+	let line = meminfo.lines().find(|l| l.starts_with("MemTotal:"))?;
+	let kib = line.split_whitespace().nth(1)?.parse::<usize>().ok()?;
+	kib.checked_mul(1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+#[must_use]
+fn physical_memory() -> Option<usize> {
+	None
+}
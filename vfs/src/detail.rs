@@ -3,15 +3,17 @@
 use std::{
 	borrow::Cow,
 	fs::File,
+	hash::{Hash, Hasher},
 	io::{Read, Seek, SeekFrom},
 	ops::Range,
-	sync::Arc,
+	sync::{Arc, Weak},
 };
 
 use flate2::read::DeflateDecoder;
 use parking_lot::RwLock;
+use rustc_hash::{FxHashMap, FxHasher};
 
-use super::{Error, FolderSlot, VirtualFs};
+use super::{DedupStats, Error, FolderSlot, VirtualFs};
 
 pub(super) fn path_append(vfs: &VirtualFs, buf: &mut String, slot: FolderSlot) {
 	let folder = &vfs.folders[slot];
@@ -50,7 +52,8 @@ pub(super) fn decompress(bytes: Cow<[u8]>, compression: Compression) -> Result<C
 pub(crate) enum Reader {
 	/// e.g. lump in a WAD, or entry in a zip archive.
 	File(File),
-	Memory(Vec<u8>),
+	/// Possibly shared with other readers; see [`Interner`].
+	Memory(Arc<[u8]>),
 	/// e.g. entry in a zip archive nested within another zip archive.
 	_Super(ReaderLayer),
 }
@@ -63,7 +66,7 @@ impl Reader {
 	) -> Result<Cow<[u8]>, Error> {
 		let bytes = match self {
 			Self::File(ref mut fh) => Cow::Owned(Self::read_from_file(fh, span)?),
-			Self::Memory(bytes) => Cow::Borrowed(bytes.as_slice()),
+			Self::Memory(bytes) => Cow::Borrowed(&bytes[..]),
 			Self::_Super(layer) => {
 				let mut guard = layer.parent.write();
 				let cow = guard.read(layer.span.clone(), layer.compression)?;
@@ -100,3 +103,39 @@ pub(crate) enum Compression {
 	Xz,
 	Zstd,
 }
+
+/// Backs [`VirtualFs::set_dedup`]. Buffers are held weakly, so that removing
+/// every file sharing a buffer still frees that buffer.
+#[derive(Debug, Default)]
+pub(crate) struct Interner {
+	/// Keyed by content hash. Each bucket resolves collisions by comparing bytes.
+	table: FxHashMap<u64, Vec<Weak<[u8]>>>,
+	pub(crate) stats: DedupStats,
+}
+
+impl Interner {
+	/// If an identical payload has already been interned, `bytes` gets dropped
+	/// and the existing buffer is returned in its place.
+	#[must_use]
+	pub(crate) fn intern(&mut self, bytes: Vec<u8>) -> Arc<[u8]> {
+		let mut hasher = FxHasher::default();
+		bytes.hash(&mut hasher);
+		let bucket = self.table.entry(hasher.finish()).or_default();
+		bucket.retain(|weak| weak.strong_count() > 0);
+
+		let existing = bucket
+			.iter()
+			.filter_map(Weak::upgrade)
+			.find(|arc| arc[..] == bytes[..]);
+
+		if let Some(arc) = existing {
+			self.stats.files_deduped += 1;
+			self.stats.bytes_saved += bytes.len();
+			return arc;
+		}
+
+		let arc = Arc::<[u8]>::from(bytes);
+		bucket.push(Arc::downgrade(&arc));
+		arc
+	}
+}
@@ -0,0 +1,231 @@
+//! A map from hashed virtual paths to entries, so that [`VirtualFs::lookup`]
+//! does not need to scan every folder along a path.
+
+use rustc_hash::FxHashMap;
+
+use super::{FolderSlot, Slot, VPath, VirtualFs};
+
+/// Keys are [`PathHash`]es of full paths. Only entries reachable by a lookup
+/// are present; an entry shadowed by a sibling of the same (case-insensitive)
+/// name is left out, as is everything under a shadowed folder.
+#[derive(Debug, Default)]
+pub(crate) struct PathIndex(FxHashMap<u64, Slot>);
+
+impl PathIndex {
+	#[must_use]
+	pub(crate) fn get(&self, hash: PathHash) -> Option<Slot> {
+		self.0.get(&hash.0).copied()
+	}
+
+	pub(crate) fn clear(&mut self) {
+		self.0.clear();
+	}
+}
+
+/// FNV-1a over ASCII-lowercased path components, each preceded by a `/`.
+/// (RAT) Hand-rolled rather than using `FxHasher`, since the state for each
+/// folder gets copied and extended once per child.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PathHash(u64);
+
+impl PathHash {
+	pub(crate) const ROOT: Self = Self(0xcbf2_9ce4_8422_2325);
+
+	#[must_use]
+	pub(crate) fn push(self, component: &str) -> Self {
+		const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+		let mut h = self.0;
+
+		for byte in std::iter::once(b'/').chain(component.bytes()) {
+			h ^= u64::from(byte.to_ascii_lowercase());
+			h = h.wrapping_mul(PRIME);
+		}
+
+		Self(h)
+	}
+
+	#[must_use]
+	pub(crate) fn of(vpath: &VPath) -> Self {
+		vpath
+			.components()
+			.fold(Self::ROOT, |h, comp| h.push(comp.as_str()))
+	}
+}
+
+impl VirtualFs {
+	/// Adds all reachable children of the folder at `slot` (which has path hash
+	/// `hash`) to the index, recursively. Existing keys are never overwritten,
+	/// mirroring how [`Self::lookup`] prefers subfolders over files and earlier
+	/// children over later ones.
+	pub(crate) fn index_children(&mut self, slot: FolderSlot, hash: PathHash) {
+		let folder = &self.folders[slot];
+		let mut to_recur = vec![];
+
+		for &sfslot in &folder.subfolders {
+			let sfhash = hash.push(&self.folders[sfslot].name);
+
+			if let std::collections::hash_map::Entry::Vacant(vac) = self.index.0.entry(sfhash.0) {
+				vac.insert(Slot::Folder(sfslot));
+				to_recur.push((sfslot, sfhash));
+			}
+		}
+
+		for &fslot in &folder.files {
+			let fhash = hash.push(&self.files[fslot].name);
+			self.index.0.entry(fhash.0).or_insert(Slot::File(fslot));
+		}
+
+		for (sfslot, sfhash) in to_recur {
+			self.index_children(sfslot, sfhash);
+		}
+	}
+
+	/// Rebuilds the index from scratch. Linear in the size of the tree, so this is
+	/// meant for batched removals; single removals use [`Self::unindex`] and
+	/// [`Self::reindex_name`] instead.
+	pub(crate) fn reindex(&mut self) {
+		self.index.clear();
+		self.index_children(self.root, PathHash::ROOT);
+	}
+
+	/// Removes `slot` and everything reachable under it from the index.
+	/// Must be called before `slot` gets removed from the tree.
+	pub(crate) fn unindex(&mut self, slot: Slot) {
+		let (name, parent) = match slot {
+			Slot::File(fslot) => (&self.files[fslot].name, self.files[fslot].parent),
+			Slot::Folder(oslot) => (
+				&self.folders[oslot].name,
+				self.folders[oslot].parent.unwrap(),
+			),
+		};
+
+		let hash = self.folder_hash(parent).push(name);
+
+		// (RAT) A shadowed entry has nothing under it in the index either.
+		if self.index.get(hash) != Some(slot) {
+			return;
+		}
+
+		self.index.0.remove(&hash.0);
+
+		if let Slot::Folder(oslot) = slot {
+			self.unindex_children(oslot, hash);
+		}
+	}
+
+	fn unindex_children(&mut self, slot: FolderSlot, hash: PathHash) {
+		let folder = &self.folders[slot];
+		let mut to_recur = vec![];
+
+		for &sfslot in &folder.subfolders {
+			let sfhash = hash.push(&self.folders[sfslot].name);
+
+			if self.index.get(sfhash) == Some(Slot::Folder(sfslot)) {
+				self.index.0.remove(&sfhash.0);
+				to_recur.push((sfslot, sfhash));
+			}
+		}
+
+		for &fslot in &folder.files {
+			let fhash = hash.push(&self.files[fslot].name);
+
+			if self.index.get(fhash) == Some(Slot::File(fslot)) {
+				self.index.0.remove(&fhash.0);
+			}
+		}
+
+		for (sfslot, sfhash) in to_recur {
+			self.unindex_children(sfslot, sfhash);
+		}
+	}
+
+	/// Indexes whichever child of the folder at `parent` is now reachable by
+	/// `name`, if any. Call after removing a child by that name, since it may
+	/// have been shadowing a sibling.
+	pub(crate) fn reindex_name(&mut self, parent: FolderSlot, name: &str) {
+		let hash = self.folder_hash(parent).push(name);
+
+		if self.index.get(hash).is_some() {
+			return;
+		}
+
+		let folder = &self.folders[parent];
+
+		let sfslot = folder
+			.subfolders
+			.iter()
+			.copied()
+			.find(|sfslot| self.folders[*sfslot].name.eq_ignore_ascii_case(name));
+
+		let fslot = folder
+			.files
+			.iter()
+			.copied()
+			.find(|fslot| self.files[*fslot].name.eq_ignore_ascii_case(name));
+
+		if let Some(sfslot) = sfslot {
+			self.index.0.insert(hash.0, Slot::Folder(sfslot));
+			self.index_children(sfslot, hash);
+		} else if let Some(fslot) = fslot {
+			self.index.0.insert(hash.0, Slot::File(fslot));
+		}
+	}
+
+	#[must_use]
+	fn folder_hash(&self, slot: FolderSlot) -> PathHash {
+		let mut names = vec![];
+		let mut current = slot;
+
+		while let Some(parent) = self.folders[current].parent {
+			names.push(self.folders[current].name.as_str());
+			current = parent;
+		}
+
+		names
+			.into_iter()
+			.rev()
+			.fold(PathHash::ROOT, |hash, name| hash.push(name))
+	}
+
+	/// Guards against hash collisions by walking up from `slot` and comparing
+	/// the name of each ancestor against the components of `vpath`.
+	#[must_use]
+	pub(crate) fn slot_has_path(&self, slot: Slot, vpath: &VPath) -> bool {
+		let mut components = vpath.as_str().rsplit('/').filter(|c| !c.is_empty());
+
+		let (mut name, mut parent) = match slot {
+			Slot::File(fslot) => {
+				let file = &self.files[fslot];
+				(file.name.as_str(), file.parent)
+			}
+			Slot::Folder(oslot) => {
+				let folder = &self.folders[oslot];
+
+				let Some(parent) = folder.parent else {
+					return components.next().is_none();
+				};
+
+				(folder.name.as_str(), parent)
+			}
+		};
+
+		loop {
+			if !components
+				.next()
+				.is_some_and(|comp| comp.eq_ignore_ascii_case(name))
+			{
+				return false;
+			}
+
+			let folder = &self.folders[parent];
+
+			let Some(grandparent) = folder.parent else {
+				return components.next().is_none();
+			};
+
+			name = folder.name.as_str();
+			parent = grandparent;
+		}
+	}
+}
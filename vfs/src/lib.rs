@@ -5,10 +5,15 @@
 //! into one tree so that reading from them is more convenient at all other levels
 //! of the engine, without exposing any details of the user's underlying machine.
 
+mod budget;
 mod detail;
+mod index;
 mod mount;
 mod path;
 mod refs;
+mod snapshot;
+mod stats;
+mod watch;
 
 #[cfg(test)]
 mod test;
@@ -28,9 +33,12 @@ use slotmap::{new_key_type, HopSlotMap};
 use util::SmallString;
 use zip_structs::zip_error::ZipReadError;
 
-use self::detail::{Compression, Reader};
+use self::{
+	detail::{Compression, Interner, Reader},
+	index::{PathHash, PathIndex},
+};
 
-pub use self::{path::*, refs::*};
+pub use self::{budget::*, path::*, refs::*, snapshot::*, stats::*, watch::*};
 
 #[derive(Debug)]
 pub struct VirtualFs {
@@ -38,6 +46,11 @@ pub struct VirtualFs {
 	pub(crate) mounts: Vec<MountInfo>,
 	pub(crate) files: HopSlotMap<FileSlot, VFile>,
 	pub(crate) folders: HopSlotMap<FolderSlot, VFolder>,
+	pub(crate) index: PathIndex,
+	/// `Some` if deduplication is enabled; see [`Self::set_dedup`].
+	pub(crate) dedup: Option<Interner>,
+	/// See [`Self::mount_stats`].
+	pub(crate) stats_cache: Mutex<Option<Vec<MountStats>>>,
 }
 
 impl VirtualFs {
@@ -74,13 +87,23 @@ impl VirtualFs {
 			return Err(Error::MountSymlink);
 		}
 
+		self.invalidate_stats();
+
 		match mount::mount(self, &canon, mount_point.as_str()) {
 			Ok(mntinfo) => {
+				// Only the new subtree needs indexing, since mount points
+				// are unique and no existing entry can shadow it.
+				self.index_children(self.root, PathHash::ROOT);
 				self.mounts.push(mntinfo);
 				Ok(())
 			}
 			Err(err) => {
-				let to_clean = match self.lookup(mount_point) {
+				// The partially-mounted subtree has not been indexed yet.
+				let to_clean = match self.lookup_recur(
+					self.root,
+					&self.folders[self.root],
+					mount_point.components(),
+				) {
 					Some(Ref::File(iref)) => Some(Slot::File(iref.slot)),
 					Some(Ref::Folder(oref)) => Some(Slot::Folder(oref.slot)),
 					None => None,
@@ -118,39 +141,58 @@ impl VirtualFs {
 
 	/// Returns `true` if a file was removed.
 	pub fn remove_file_by_slot(&mut self, slot: FileSlot) -> bool {
-		let ret = self.files.remove(slot).is_some();
+		if !self.files.contains_key(slot) {
+			return false;
+		}
+
+		self.unindex(Slot::File(slot));
+		let removed = self.files.remove(slot).unwrap();
+		self.folders[removed.parent].files.shift_remove(&slot);
+		self.invalidate_stats();
 
 		if let Some(p) = self.mounts.iter().position(|mntinfo| mntinfo.root == slot) {
 			self.mounts.remove(p);
 		}
 
-		ret
+		self.reindex_name(removed.parent, &removed.name);
+		true
 	}
 
 	pub fn remove_folder_by_slot(&mut self, slot: FolderSlot) {
 		assert_ne!(slot, self.root, "root folder cannot be removed");
+
+		let parent = self.folders[slot].parent.unwrap();
+		let name = self.folders[slot].name.clone();
+
+		self.unindex(Slot::Folder(slot));
 		self.remove_folder_recur(slot);
+		self.invalidate_stats();
 
 		if let Some(p) = self.mounts.iter().position(|mntinfo| mntinfo.root == slot) {
 			self.mounts.remove(p);
 		}
+
+		self.reindex_name(parent, &name);
 	}
 
 	fn remove_folder_recur(&mut self, oslot: FolderSlot) {
 		let parent_slot = self.folders[oslot].parent.unwrap();
 		let parent = &mut self.folders[parent_slot];
-		let did_remove = parent.subfolders.remove(&oslot);
+		let did_remove = parent.subfolders.shift_remove(&oslot);
 		debug_assert!(did_remove);
 
-		let subfolders = std::mem::take(&mut self.folders[oslot].subfolders);
+		self.free_folder_recur(oslot);
+	}
 
-		for slot in subfolders {
-			self.remove_folder_recur(slot);
-			let removed = self.folders.remove(slot);
-			debug_assert!(removed.is_some());
+	/// Unlike [`Self::remove_folder_recur`], does not detach `oslot` from its parent.
+	fn free_folder_recur(&mut self, oslot: FolderSlot) {
+		let removed = self.folders.remove(oslot).unwrap();
+
+		for slot in removed.subfolders {
+			self.free_folder_recur(slot);
 		}
 
-		for islot in self.folders[oslot].files.iter().copied() {
+		for islot in removed.files {
 			let removed = self.files.remove(islot);
 			debug_assert!(removed.is_some());
 		}
@@ -176,6 +218,7 @@ impl VirtualFs {
 				Slot::File(islot) => {
 					let removed = self.files.remove(islot);
 					debug_assert!(removed.is_some());
+					self.folders[self.root].files.shift_remove(&islot);
 				}
 				Slot::Folder(oslot) => {
 					self.remove_folder_recur(oslot);
@@ -183,11 +226,67 @@ impl VirtualFs {
 			}
 		}
 
+		self.invalidate_stats();
+		self.prune_empty_folders();
+		self.reindex();
 		Ok(())
 	}
 
+	/// Removes every folder with no files and no subfolders. Removing a folder
+	/// can leave its parent empty, so this repeats until no empty folders remain.
+	/// The root and the root folders of mounts are never removed.
+	///
+	/// Returns the number of folders removed.
+	pub fn prune_empty_folders(&mut self) -> usize {
+		let mut removed = 0;
+
+		loop {
+			let empty = self
+				.folders
+				.iter()
+				.filter(|(slot, vfolder)| {
+					*slot != self.root && vfolder.child_count() == 0 && !self.folder_is_mount(*slot)
+				})
+				.map(|(slot, _)| slot)
+				.collect::<Vec<_>>();
+
+			if empty.is_empty() {
+				break;
+			}
+
+			removed += empty.len();
+
+			for slot in empty {
+				let vfolder = self.folders.remove(slot).unwrap();
+				let parent = vfolder.parent.unwrap();
+				self.folders[parent].subfolders.shift_remove(&slot);
+			}
+		}
+
+		if removed > 0 {
+			self.invalidate_stats();
+			self.reindex();
+		}
+
+		removed
+	}
+
+	/// Path comparison is ASCII case-insensitive.
+	/// Expect this to take constant time unless two paths' hashes collide.
 	pub fn lookup<'vfs: 'p, 'p>(&'vfs self, vpath: &'p VPath) -> Option<Ref<'vfs>> {
-		self.lookup_recur(self.root, &self.folders[self.root], vpath.components())
+		if vpath.components().next().is_none() {
+			return Some(Ref::Folder(self.root()));
+		}
+
+		match self.index.get(PathHash::of(vpath))? {
+			Slot::File(slot) if self.slot_has_path(Slot::File(slot), vpath) => {
+				self.get_file(slot).map(Ref::File)
+			}
+			Slot::Folder(slot) if self.slot_has_path(Slot::Folder(slot), vpath) => {
+				self.get_folder(slot).map(Ref::Folder)
+			}
+			_ => self.lookup_recur(self.root, &self.folders[self.root], vpath.components()),
+		}
 	}
 
 	#[must_use]
@@ -262,7 +361,10 @@ impl VirtualFs {
 	}
 
 	/// Each virtual file backed by a physical file reads its slice into a buffer
-	/// belonging exclusively to that virtual file.
+	/// belonging exclusively to that virtual file, unless [deduplication] is
+	/// enabled, in which case files with identical content share one buffer.
+	///
+	/// [deduplication]: Self::set_dedup
 	pub fn ingest_all(&mut self) {
 		#[must_use]
 		fn ingest(
@@ -282,6 +384,15 @@ impl VirtualFs {
 				.map(|cow| cow.into_owned())
 		}
 
+		#[must_use]
+		fn intern(dedup: &mut Option<Interner>, bytes: Vec<u8>) -> Arc<[u8]> {
+			match dedup {
+				Some(interner) => interner.intern(bytes),
+				None => bytes.into(),
+			}
+		}
+
+		self.invalidate_stats();
 		let mut vfiles = self.files.values_mut();
 
 		let Some(vfile0) = vfiles.next() else {
@@ -293,6 +404,7 @@ impl VirtualFs {
 
 		if let Some(bytes) = ingest(&mut guard, vfile0.span(), vfile0.compression) {
 			vfile0.span = 0..(bytes.len() as u32);
+			let bytes = intern(&mut self.dedup, bytes);
 			vfile0.reader = Arc::new(Mutex::new(Reader::Memory(bytes)));
 			vfile0.compression = Compression::None;
 		}
@@ -310,14 +422,36 @@ impl VirtualFs {
 
 			if let Some(bytes) = ingest(&mut guard, vfile.span(), vfile.compression) {
 				vfile.span = 0..(bytes.len() as u32);
+				let bytes = intern(&mut self.dedup, bytes);
 				vfile.reader = Arc::new(Mutex::new(Reader::Memory(bytes)));
 				vfile.compression = Compression::None;
 			}
 		}
 	}
 
+	/// Opt into (or out of) content deduplication during [`Self::ingest_all`].
+	/// Disabling it also discards the accumulated [`DedupStats`].
+	pub fn set_dedup(&mut self, enabled: bool) {
+		match (enabled, self.dedup.is_some()) {
+			(true, false) => self.dedup = Some(Interner::default()),
+			(false, true) => self.dedup = None,
+			_ => {}
+		}
+	}
+
+	/// Always zeroed if deduplication is not [enabled](Self::set_dedup).
+	#[must_use]
+	pub fn dedup_stats(&self) -> DedupStats {
+		self.dedup
+			.as_ref()
+			.map(|interner| interner.stats)
+			.unwrap_or_default()
+	}
+
 	/// Changes the names of all files and folders to be ASCII lowercase.
 	pub fn normalize_names(&mut self) {
+		self.invalidate_stats();
+
 		self.files.values_mut().par_bridge().for_each(|vfile| {
 			vfile.name.make_ascii_lowercase();
 		});
@@ -358,6 +492,20 @@ impl VirtualFs {
 		})
 	}
 
+	/// Yields every file whose [extension](VPath::extension) matches any in
+	/// `exts`, ASCII case-insensitively, in one pass. Elements of `exts` should
+	/// not have a leading `.`. Files without an extension are never yielded.
+	pub fn files_with_extensions<'e>(
+		&'e self,
+		exts: &'e [&str],
+	) -> impl Iterator<Item = FileRef<'e>> + 'e {
+		self.files().filter(|file| {
+			file.name()
+				.extension()
+				.is_some_and(|ext| exts.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+		})
+	}
+
 	pub fn folders(&self) -> impl Iterator<Item = FolderRef> {
 		self.folders.iter().map(|(k, v)| FolderRef {
 			vfs: self,
@@ -381,6 +529,91 @@ impl VirtualFs {
 		self.folders.clear();
 		self.files.clear();
 		self.root = self.folders.insert(root);
+		self.index.clear();
+		self.invalidate_stats();
+	}
+
+	/// Builds a new VFS with a copy of every mount of `self`, followed by a copy
+	/// of every mount of `other`. A mount of `other` at a mount point which `self`
+	/// also has (e.g. basedata) is assumed to be the same mount and is skipped.
+	/// Deduplication starts out disabled.
+	///
+	/// Beware that this is expensive, since every file and folder entry gets
+	/// copied. File readers are shared by reference count with the source, so
+	/// neither `self` nor `other` needs to outlive the result.
+	#[must_use]
+	pub fn merged(&self, other: &Self) -> Self {
+		let mut ret = Self::default();
+
+		let others = other.mounts.iter().filter(|theirs| {
+			!self.mounts.iter().any(|ours| {
+				ours.mount_point
+					.as_str()
+					.eq_ignore_ascii_case(theirs.mount_point.as_str())
+			})
+		});
+
+		let mounts = self
+			.mounts
+			.iter()
+			.map(|mntinfo| (self, mntinfo))
+			.chain(others.map(|mntinfo| (other, mntinfo)));
+
+		for (src, mntinfo) in mounts {
+			let root = match mntinfo.root {
+				Slot::File(islot) => Slot::File(ret.copy_file(src, islot, ret.root)),
+				Slot::Folder(oslot) => Slot::Folder(ret.copy_folder(src, oslot, ret.root)),
+			};
+
+			ret.mounts.push(MountInfo {
+				real_path: mntinfo.real_path.clone(),
+				mount_point: mntinfo.mount_point.clone(),
+				root,
+				format: mntinfo.format,
+			});
+		}
+
+		ret.reindex();
+		ret
+	}
+
+	fn copy_file(&mut self, src: &Self, islot: FileSlot, parent: FolderSlot) -> FileSlot {
+		let vfile = &src.files[islot];
+
+		let ret = self.files.insert(VFile {
+			name: vfile.name.clone(),
+			parent,
+			reader: vfile.reader.clone(),
+			span: vfile.span.clone(),
+			compression: vfile.compression,
+		});
+
+		self.folders[parent].files.insert(ret);
+		ret
+	}
+
+	fn copy_folder(&mut self, src: &Self, oslot: FolderSlot, parent: FolderSlot) -> FolderSlot {
+		let vfolder = &src.folders[oslot];
+
+		let ret = self.folders.insert(VFolder {
+			name: vfolder.name.clone(),
+			parent: Some(parent),
+			files: IndexSet::default(),
+			subfolders: IndexSet::default(),
+			kind: vfolder.kind,
+		});
+
+		self.folders[parent].subfolders.insert(ret);
+
+		for &islot in &vfolder.files {
+			let _ = self.copy_file(src, islot, ret);
+		}
+
+		for &sfslot in &vfolder.subfolders {
+			let _ = self.copy_folder(src, sfslot, ret);
+		}
+
+		ret
 	}
 }
 
@@ -401,10 +634,22 @@ impl Default for VirtualFs {
 			mounts: vec![],
 			files: HopSlotMap::default(),
 			folders,
+			index: PathIndex::default(),
+			dedup: None,
+			stats_cache: Mutex::new(None),
 		}
 	}
 }
 
+/// See [`VirtualFs::dedup_stats`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DedupStats {
+	/// How many ingested files were given an existing buffer instead of their own.
+	pub files_deduped: usize,
+	/// The sum of the sizes of all buffers which were discarded as duplicates.
+	pub bytes_saved: usize,
+}
+
 /// Metadata for a file subtree registered using [`VirtualFs::mount`].
 #[derive(Debug)]
 pub struct MountInfo {
@@ -571,6 +816,39 @@ impl From<FolderSlot> for Slot {
 	}
 }
 
+/// Builds a PWAD out of `(name, content)` pairs, for tests and benchmarks
+/// which need a WAD with specific contents without shipping one.
+#[doc(hidden)]
+#[must_use]
+pub fn build_pwad<N: AsRef<str>, B: AsRef<[u8]>>(lumps: &[(N, B)]) -> Vec<u8> {
+	let data_len: usize = lumps.iter().map(|(_, bytes)| bytes.as_ref().len()).sum();
+	let mut ret = Vec::with_capacity(12 + data_len + lumps.len() * 16);
+
+	ret.extend_from_slice(b"PWAD");
+	ret.extend_from_slice(&(lumps.len() as i32).to_le_bytes());
+	ret.extend_from_slice(&((12 + data_len) as i32).to_le_bytes());
+
+	for (_, bytes) in lumps {
+		ret.extend_from_slice(bytes.as_ref());
+	}
+
+	let mut filepos = 12;
+
+	for (name, bytes) in lumps {
+		let name = name.as_ref();
+		let mut name8 = [0; 8];
+		name8[..name.len()].copy_from_slice(name.as_bytes());
+
+		ret.extend_from_slice(&(filepos as i32).to_le_bytes());
+		ret.extend_from_slice(&(bytes.as_ref().len() as i32).to_le_bytes());
+		ret.extend_from_slice(&name8);
+
+		filepos += bytes.as_ref().len();
+	}
+
+	ret
+}
+
 #[derive(Debug)]
 pub enum Error {
 	Canonicalize(std::io::Error),
@@ -581,15 +859,18 @@ pub enum Error {
 	FileOpen(std::io::Error),
 	FileRead(std::io::Error),
 	Metadata(std::io::Error),
+	MountIndex(usize),
 	MountPointDuplicate,
 	MountPointEmpty,
 	MountPointInvalidChars,
 	MountSymlink,
 	NotFound,
 	Seek(std::io::Error),
+	ThreadSpawn(std::io::Error),
 	Utf8(FromUtf8Error),
 	VFolderRead,
 	Wad(wadload::Error),
+	WatchNonDirectory,
 	Zip(ZipReadError),
 }
 
@@ -611,6 +892,7 @@ impl std::fmt::Display for Error {
 			Self::FileOpen(err) => write!(f, "failed to open a physical file handle: {err}"),
 			Self::FileRead(err) => write!(f, "failed to read a physical file: {err}"),
 			Self::Metadata(err) => write!(f, "failed to retrieve physical file metadata: {err}"),
+			Self::MountIndex(index) => write!(f, "no mount exists at index {index}"),
 			Self::MountPointDuplicate => {
 				write!(f, "attempt a mount using an already-present mount point")
 			}
@@ -618,10 +900,12 @@ impl std::fmt::Display for Error {
 			Self::MountPointInvalidChars => write!(f, "given mount point has invalid characters"),
 			Self::NotFound => write!(f, "no entry found by the given path"),
 			Self::Seek(err) => write!(f, "failed to seek a physical file handle: {err}"),
+			Self::ThreadSpawn(err) => write!(f, "failed to spawn a watcher thread: {err}"),
 			Self::MountSymlink => write!(f, "attempted to mount a symbolic link"),
 			Self::Utf8(err) => write!(f, "failed to read UTF-8 text from a virtual file: {err}"),
 			Self::VFolderRead => write!(f, "attempted to read byte content of a virtual folder"),
 			Self::Wad(err) => write!(f, "WAD read error: {err}"),
+			Self::WatchNonDirectory => write!(f, "only directory mounts can be watched"),
 			Self::Zip(err) => write!(f, "zip archive read error: {err}"),
 		}
 	}
@@ -21,7 +21,7 @@ use super::{
 
 pub(super) fn mount(vfs: &mut VirtualFs, real: &Path, mpoint: &str) -> Result<MountInfo, Error> {
 	if real.is_dir() {
-		let oslot = mount_dir(vfs, real, vfs.root)?;
+		let oslot = mount_dir(vfs, real, vfs.root, mpoint.into())?;
 
 		return Ok(MountInfo {
 			real_path: real.to_path_buf(),
@@ -59,7 +59,7 @@ pub(super) fn mount(vfs: &mut VirtualFs, real: &Path, mpoint: &str) -> Result<Mo
 	}
 
 	let islot = vfs.files.insert(VFile {
-		name: real.file_name().unwrap().to_string_lossy().into(),
+		name: mpoint.into(),
 		parent: vfs.root,
 		reader: Arc::new(Mutex::new(Reader::File(fh))),
 		span: 0..(len as u32),
@@ -76,15 +76,18 @@ pub(super) fn mount(vfs: &mut VirtualFs, real: &Path, mpoint: &str) -> Result<Mo
 	})
 }
 
+/// `name` is that of the new virtual folder, which is only different from that
+/// of the real directory if the latter is the root of a mount.
 fn mount_dir(
 	vfs: &mut VirtualFs,
 	real: &Path,
 	parent_slot: FolderSlot,
+	name: SmallString,
 ) -> Result<FolderSlot, Error> {
 	let d_reader = std::fs::read_dir(real).map_err(Error::DirRead)?;
 
 	let oslot = vfs.folders.insert(VFolder {
-		name: real.file_name().unwrap().to_string_lossy().into(),
+		name,
 		parent: Some(parent_slot),
 		files: indexmap::indexset![],
 		subfolders: indexmap::indexset![],
@@ -96,7 +99,8 @@ fn mount_dir(
 		let path = d_ent.path();
 
 		if path.is_dir() {
-			let _ = mount_dir(vfs, &path, oslot)?;
+			let name = path.file_name().unwrap().to_string_lossy().into();
+			let _ = mount_dir(vfs, &path, oslot, name)?;
 			continue;
 		}
 
@@ -176,14 +180,14 @@ fn mount_wad_blob(
 	parent_slot: FolderSlot,
 	bytes: Vec<u8>,
 ) -> Result<FolderSlot, Error> {
-	let arc = Arc::new(Mutex::new(Reader::Memory(bytes)));
+	let arc = Arc::new(Mutex::new(Reader::Memory(bytes.into())));
 	let guard = arc.lock();
 
 	let Reader::Memory(blob) = std::ops::Deref::deref(&guard) else {
 		unreachable!()
 	};
 
-	let cursor = Cursor::new(blob.as_slice());
+	let cursor = Cursor::new(&blob[..]);
 	let w_reader = wadload::DirReader::new(cursor).map_err(Error::Wad)?;
 
 	let oslot = vfs.folders.insert(VFolder {
@@ -350,9 +354,22 @@ fn wad_magic(magic: &[u8]) -> bool {
 		)
 }
 
+/// If the file is shorter than 8 bytes, the rest of the magic number is zeroed.
+/// Leaves the cursor at the start of the file.
 fn magic_and_length(fh: &mut File) -> Result<([u8; 8], u64), Error> {
 	let mut buf = [0; 8];
-	fh.read_exact(&mut buf).map_err(Error::FileRead)?;
+	let mut read = 0;
+
+	while read < buf.len() {
+		match fh.read(&mut buf[read..]) {
+			Ok(0) => break,
+			Ok(n) => read += n,
+			Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+			Err(err) => return Err(Error::FileRead(err)),
+		}
+	}
+
 	let r = fh.seek(SeekFrom::End(0)).map_err(Error::Seek)?;
+	fh.seek(SeekFrom::Start(0)).map_err(Error::Seek)?;
 	Ok((buf, r))
 }
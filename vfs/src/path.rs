@@ -4,17 +4,30 @@ use std::hash::{Hash, Hasher};
 
 use util::Id8;
 
-/// Components are separated by `/` like in Unixes.
+/// Components are separated by `/` like in Unixes. Construction replaces any
+/// `\` with `/`, so Windows-style paths can be given as well.
 #[derive(Debug, Clone)]
 pub struct VPathBuf(String);
 
 impl VPathBuf {
+	/// Every `\` in `string` is replaced with `/`. This is intended, so that
+	/// Windows-style paths can be given as-is; no VFS entry name contains `\`.
 	#[must_use]
-	pub fn new(string: String) -> Self {
+	pub fn new(mut string: String) -> Self {
+		if string.contains('\\') {
+			string = string.replace('\\', "/");
+		}
+
 		Self(string)
 	}
 }
 
+impl AsRef<VPath> for VPathBuf {
+	fn as_ref(&self) -> &VPath {
+		self
+	}
+}
+
 impl std::borrow::Borrow<VPath> for VPathBuf {
 	fn borrow(&self) -> &VPath {
 		VPath::new(self.0.as_str())
@@ -49,6 +62,18 @@ impl<'p> PartialEq<&'p VPath> for VPathBuf {
 
 impl Eq for VPathBuf {}
 
+impl PartialOrd for VPathBuf {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for VPathBuf {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		self.as_str().cmp(other.as_str())
+	}
+}
+
 impl Hash for VPathBuf {
 	fn hash<H: Hasher>(&self, state: &mut H) {
 		self.as_str().hash(state);
@@ -57,7 +82,7 @@ impl Hash for VPathBuf {
 
 impl<S: AsRef<str>> From<S> for VPathBuf {
 	fn from(value: S) -> Self {
-		Self(value.as_ref().to_owned())
+		Self::new(value.as_ref().to_owned())
 	}
 }
 
@@ -92,7 +117,11 @@ impl<'s> FromIterator<&'s VPath> for VPathBuf {
 		Self(buf)
 	}
 }
+
 /// [`VPathBuf`]'s counterpart to [`std::path::Path`].
+///
+/// Unlike [`VPathBuf`], this does not normalize separators, since it is only
+/// ever a view of existing text; `\` is never treated as a separator.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct VPath(str);
@@ -122,49 +151,148 @@ impl VPath {
 			.map(Self::new)
 	}
 
+	/// The number of non-empty `/`-delimited segments in this path.
+	/// The root path and the empty path both have no components.
+	#[must_use]
+	pub fn component_count(&self) -> usize {
+		self.components().count()
+	}
+
+	/// Returns `true` if this path is `parent` itself, or starts with `parent`
+	/// followed by a `/`. Comparison is case-sensitive. A trailing separator on
+	/// `parent` is ignored, so every absolute path is a child of the root.
+	#[must_use]
+	pub fn is_child_of(&self, parent: &VPath) -> bool {
+		let parent = parent.0.trim_end_matches('/');
+
+		let Some(rest) = self.0.strip_prefix(parent) else {
+			return false;
+		};
+
+		rest.is_empty() || rest.starts_with('/')
+	}
+
+	/// Finds the longest prefix of whole components shared by `a` and `b`.
+	/// The returned path is a slice of `a`. Two absolute paths with no components
+	/// in common share the root; an absolute and a relative path share nothing.
+	#[must_use]
+	pub fn common_prefix<'a>(a: &'a VPath, b: &'a VPath) -> &'a VPath {
+		if a.is_absolute() != b.is_absolute() {
+			return Self::new("");
+		}
+
+		let mut b_comps = b.components();
+		let mut end = 0;
+		let mut offs = 0;
+
+		for seg in a.0.split('/') {
+			let seg_end = offs + seg.len();
+
+			if !seg.is_empty() {
+				if b_comps.next() != Some(Self::new(seg)) {
+					break;
+				}
+
+				end = seg_end;
+			}
+
+			offs = seg_end + 1;
+		}
+
+		if end == 0 && a.is_absolute() && b.is_absolute() {
+			return Self::new("/");
+		}
+
+		Self::new(&a.0[..end])
+	}
+
 	/// The same functionality as [`std::path::Path::file_name`].
+	/// Trailing separators are ignored; the root path has no file name.
 	#[must_use]
 	pub fn file_name(&self) -> Option<&Self> {
-		self.0.rsplit('/').next().map(Self::new)
+		let trimmed = self.0.trim_end_matches('/');
+
+		if trimmed.is_empty() {
+			return None;
+		}
+
+		trimmed.rsplit('/').next().map(Self::new)
 	}
 
 	/// The same functionality as [`std::path::Path::file_prefix`].
 	#[must_use]
 	pub fn file_prefix(&self) -> Option<&Self> {
-		self.file_name()
-			.and_then(|fname| fname.as_str().split('.').next().map(Self::new))
+		let name = self.file_name()?.as_str();
+
+		match name.strip_prefix('.') {
+			Some(rest) => match rest.split_once('.') {
+				Some((pfx, _)) => Some(Self::new(&name[..(pfx.len() + 1)])),
+				None => Some(Self::new(name)),
+			},
+			None => name.split('.').next().map(Self::new),
+		}
 	}
 
 	/// The same functionality as [`std::path::Path::file_stem`].
 	#[must_use]
 	pub fn file_stem(&self) -> Option<&Self> {
-		let Some(name) = self.file_name() else {
-			return None;
-		};
-
-		let Some((stem, _ext)) = name.as_str().rsplit_once('.') else {
-			return Some(name);
-		};
+		let name = self.file_name()?;
 
-		Some(Self::new(stem))
+		match name.as_str().rsplit_once('.') {
+			Some((stem, _ext)) if !stem.is_empty() => Some(Self::new(stem)),
+			_ => Some(name),
+		}
 	}
 
 	/// The same functionality as [`std::path::Path::extension`].
 	#[must_use]
 	pub fn extension(&self) -> Option<&str> {
-		let mut rsplit = self.0.rsplit('.');
-		let ret = rsplit.next();
+		let name = self.file_name()?.as_str();
 
-		match rsplit.next() {
-			Some(_) => ret,
-			None => None,
+		match name.rsplit_once('.') {
+			Some((stem, ext)) if !stem.is_empty() => Some(ext),
+			_ => None,
 		}
 	}
 
 	/// The same functionality as [`std::path::Path::parent`].
+	/// The parent of a relative path with one component is the empty path.
 	#[must_use]
 	pub fn parent(&self) -> Option<&Self> {
-		self.0.rsplitn(2, '/').last().map(Self::new)
+		let trimmed = self.0.trim_end_matches('/');
+
+		if trimmed.is_empty() {
+			return None;
+		}
+
+		match trimmed.rsplit_once('/') {
+			Some((head, _)) => {
+				let head = head.trim_end_matches('/');
+
+				if head.is_empty() {
+					Some(Self::new("/"))
+				} else {
+					Some(Self::new(head))
+				}
+			}
+			None => Some(Self::new("")),
+		}
+	}
+
+	/// Appends `component` to this path, adding a separator between them
+	/// if needed. Any `\` in `component` is treated as a separator.
+	#[must_use]
+	pub fn join(&self, component: impl AsRef<str>) -> VPathBuf {
+		let component = component.as_ref().trim_start_matches(['/', '\\']);
+		let mut buf = String::with_capacity(self.0.len() + 1 + component.len());
+		buf.push_str(&self.0);
+
+		if !buf.is_empty() && !buf.ends_with('/') {
+			buf.push('/');
+		}
+
+		buf.push_str(component);
+		VPathBuf::new(buf)
 	}
 
 	/// Returns the ["stem"](std::path::Path::file_stem) of this path,
@@ -207,6 +335,18 @@ impl<'p> PartialEq<VPathBuf> for &'p VPath {
 	}
 }
 
+impl AsRef<VPath> for VPath {
+	fn as_ref(&self) -> &VPath {
+		self
+	}
+}
+
+impl AsRef<str> for VPath {
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
 impl ToOwned for VPath {
 	type Owned = VPathBuf;
 
@@ -3,10 +3,11 @@
 use std::{borrow::Cow, sync::Arc};
 
 use parking_lot::MutexGuard;
+use regex::Regex;
 
 use crate::{
 	detail::{self, Reader},
-	Error, FileSlot, FolderSlot, Slot, VFile, VFolder, VPathBuf, VirtualFs,
+	Error, FileSlot, FolderSlot, Slot, VFile, VFolder, VPath, VPathBuf, VirtualFs,
 };
 
 /// A reference to a [`VFile`] or [`VFolder`].
@@ -167,6 +168,28 @@ impl<'vfs> FileRef<'vfs> {
 		}
 	}
 
+	/// Reads this file's entire content into a new string.
+	/// An empty file yields an empty string rather than [`Error::EmptyRead`].
+	pub fn copy_string(&self) -> Result<String, Error> {
+		if self.is_empty() {
+			return Ok(String::new());
+		}
+
+		let bytes = self.lock().read()?.into_owned();
+		String::from_utf8(bytes).map_err(Error::Utf8)
+	}
+
+	/// Returns `Ok(false)` if this file is empty or its content is not valid UTF-8.
+	pub fn contains_regex(&self, regex: &Regex) -> Result<bool, Error> {
+		if self.is_empty() {
+			return Ok(false);
+		}
+
+		let mut guard = self.lock();
+		let bytes = guard.read()?;
+		Ok(std::str::from_utf8(&bytes).is_ok_and(|text| regex.is_match(text)))
+	}
+
 	#[must_use]
 	pub fn vfs(&self) -> &VirtualFs {
 		self.vfs
@@ -222,6 +245,17 @@ impl<'vfs> FolderRef<'vfs> {
 		VPathBuf::new(buf)
 	}
 
+	/// Like [`VirtualFs::lookup`], but `vpath` gets resolved relative to this
+	/// folder. An empty path or `/` yields this folder. Each component is still
+	/// matched ASCII-case-insensitively.
+	pub fn lookup<'p>(&self, vpath: &'p VPath) -> Option<Ref<'vfs>>
+	where
+		'vfs: 'p,
+	{
+		self.vfs
+			.lookup_recur(self.slot, self.vfolder, vpath.components())
+	}
+
 	pub fn subfolders(&self) -> impl Iterator<Item = FolderRef<'vfs>> {
 		self.vfolder
 			.subfolders
@@ -242,6 +276,40 @@ impl<'vfs> FolderRef<'vfs> {
 		})
 	}
 
+	/// Yields the files between the first one named `start` and the first one
+	/// named `end` after it, exclusive of both, like the namespace markers of a
+	/// WAD (e.g. `F_START` and `F_END`). Names are matched ASCII-case-insensitively.
+	/// Yields nothing if either marker is missing.
+	pub fn namespace(&self, start: &str, end: &str) -> impl Iterator<Item = FileRef<'vfs>> {
+		let vfs = self.vfs;
+		let files = &self.vfolder.files;
+		let is_named =
+			|slot: &FileSlot, name: &str| vfs.files[*slot].name.eq_ignore_ascii_case(name);
+
+		let range = files
+			.iter()
+			.position(|slot| is_named(slot, start))
+			.and_then(|s| {
+				files
+					.iter()
+					.skip(s + 1)
+					.position(|slot| is_named(slot, end))
+					.map(|len| (s + 1)..(s + 1 + len))
+			})
+			.unwrap_or_default();
+
+		files
+			.iter()
+			.skip(range.start)
+			.take(range.len())
+			.copied()
+			.map(move |fslot| FileRef {
+				vfs,
+				slot: fslot,
+				vfile: &vfs.files[fslot],
+			})
+	}
+
 	/// Yields [`Ref::Folder`]s to all subfolders
 	/// and then [`Ref::File`]s to all child files.
 	pub fn children(&self) -> impl Iterator<Item = Ref<'vfs>> {
@@ -0,0 +1,278 @@
+//! Point-in-time records of VFS content, for finding out what a remount changed,
+//! and a way of keeping data prepared from files in step with those changes.
+
+use std::{
+	hash::{Hash, Hasher},
+	sync::Arc,
+};
+
+use rustc_hash::{FxHashMap, FxHasher};
+
+use super::{Error, FileRef, FolderRef, Ref, Slot, VPath, VPathBuf, VirtualFs};
+
+/// Maps the virtual path of every file under some set of folders to a hash of
+/// its content. Comparing two of these taken before and after a remount
+/// reveals which files were changed, added, or removed.
+#[derive(Debug, Default)]
+pub struct Snapshot {
+	files: FxHashMap<VPathBuf, u64>,
+}
+
+impl Snapshot {
+	/// Reads (and hashes) every file under `folder`, recursively.
+	pub fn new(folder: FolderRef) -> Result<Self, Error> {
+		let mut ret = Self::default();
+		ret.visit_folder(folder)?;
+		Ok(ret)
+	}
+
+	fn visit_folder(&mut self, folder: FolderRef) -> Result<(), Error> {
+		for vfile in folder.files() {
+			self.visit_file(vfile)?;
+		}
+
+		for subfolder in folder.subfolders() {
+			self.visit_folder(subfolder)?;
+		}
+
+		Ok(())
+	}
+
+	fn visit_file(&mut self, vfile: FileRef) -> Result<(), Error> {
+		let mut hasher = FxHasher::default();
+
+		if !vfile.is_empty() {
+			vfile.lock().read()?.hash(&mut hasher);
+		}
+
+		self.files.insert(vfile.path(), hasher.finish());
+		Ok(())
+	}
+
+	/// `self` is assumed to be older than `newer`.
+	#[must_use]
+	pub fn diff(&self, newer: &Self) -> SnapshotDiff {
+		let mut ret = SnapshotDiff::default();
+
+		for (path, hash) in &self.files {
+			match newer.files.get(path) {
+				Some(h) if h == hash => {}
+				Some(_) => ret.changed.push(path.clone()),
+				None => ret.removed.push(path.clone()),
+			}
+		}
+
+		for path in newer.files.keys() {
+			if !self.files.contains_key(path) {
+				ret.added.push(path.clone());
+			}
+		}
+
+		ret.changed.sort();
+		ret.added.sort();
+		ret.removed.sort();
+
+		ret
+	}
+
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.files.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.files.is_empty()
+	}
+}
+
+/// See [`Snapshot::diff`]. Every path list is sorted.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SnapshotDiff {
+	pub changed: Vec<VPathBuf>,
+	pub added: Vec<VPathBuf>,
+	pub removed: Vec<VPathBuf>,
+}
+
+impl SnapshotDiff {
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+	}
+
+	/// Every path which was changed, added, or removed.
+	pub fn all(&self) -> impl Iterator<Item = &VPath> {
+		self.changed
+			.iter()
+			.chain(self.added.iter())
+			.chain(self.removed.iter())
+			.map(|p| p.as_ref())
+	}
+
+	/// Is `path` itself in this diff, or is it a folder containing anything
+	/// in this diff? The latter is needed for data (e.g. levels) which
+	/// get prepared from a whole directory of files.
+	#[must_use]
+	pub fn touches(&self, path: &VPath) -> bool {
+		self.all().any(|p| p.is_child_of(path))
+	}
+}
+
+/// Maps the virtual path of a file to data prepared from it. Each datum is
+/// behind an [`Arc`] so that handles to it survive [`Self::apply`] calls
+/// for diffs which do not touch its source.
+#[derive(Debug)]
+pub struct PrepMap<T> {
+	entries: FxHashMap<VPathBuf, Arc<T>>,
+}
+
+impl<T> PrepMap<T> {
+	/// Runs `prep` over every file under `folder` (recursively), keeping every
+	/// `Some` result. Returns the number of times `prep` was called.
+	pub fn prep_all<F>(&mut self, folder: FolderRef, mut prep: F) -> usize
+	where
+		F: FnMut(FileRef) -> Option<T>,
+	{
+		self.prep_all_recur(folder, &mut prep)
+	}
+
+	fn prep_all_recur<F>(&mut self, folder: FolderRef, prep: &mut F) -> usize
+	where
+		F: FnMut(FileRef) -> Option<T>,
+	{
+		let mut ret = 0;
+
+		for vfile in folder.files() {
+			self.prep_one(vfile, prep);
+			ret += 1;
+		}
+
+		for subfolder in folder.subfolders() {
+			ret += self.prep_all_recur(subfolder, prep);
+		}
+
+		ret
+	}
+
+	/// Drops data prepared from every removed file in `diff`, and re-runs `prep`
+	/// only over changed and added files. Everything else is left untouched.
+	/// Returns the number of times `prep` was called.
+	pub fn apply<F>(&mut self, vfs: &VirtualFs, diff: &SnapshotDiff, mut prep: F) -> usize
+	where
+		F: FnMut(FileRef) -> Option<T>,
+	{
+		let mut ret = 0;
+
+		for path in &diff.removed {
+			self.entries.remove(path);
+		}
+
+		for path in diff.changed.iter().chain(diff.added.iter()) {
+			match vfs.lookup(path).and_then(Ref::into_file) {
+				Some(vfile) => {
+					self.prep_one(vfile, &mut prep);
+					ret += 1;
+				}
+				None => {
+					self.entries.remove(path);
+				}
+			}
+		}
+
+		ret
+	}
+
+	fn prep_one<F>(&mut self, vfile: FileRef, prep: &mut F)
+	where
+		F: FnMut(FileRef) -> Option<T>,
+	{
+		let path = vfile.path();
+
+		match prep(vfile) {
+			Some(datum) => {
+				self.entries.insert(path, Arc::new(datum));
+			}
+			None => {
+				self.entries.remove(&path);
+			}
+		}
+	}
+
+	#[must_use]
+	pub fn get(&self, path: &VPath) -> Option<&Arc<T>> {
+		self.entries.get(path)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&VPath, &Arc<T>)> {
+		self.entries.iter().map(|(k, v)| (k.as_ref(), v))
+	}
+
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+}
+
+impl<T> Default for PrepMap<T> {
+	fn default() -> Self {
+		Self {
+			entries: FxHashMap::default(),
+		}
+	}
+}
+
+impl VirtualFs {
+	/// Takes a [`Snapshot`] of everything under the mount at `index`.
+	pub fn snapshot(&self, index: usize) -> Result<Snapshot, Error> {
+		let Some(mntinfo) = self.mounts.get(index) else {
+			return Err(Error::MountIndex(index));
+		};
+
+		match mntinfo.root {
+			Slot::File(islot) => {
+				let mut ret = Snapshot::default();
+				ret.visit_file(self.get_file(islot).unwrap())?;
+				Ok(ret)
+			}
+			Slot::Folder(oslot) => Snapshot::new(self.get_folder(oslot).unwrap()),
+		}
+	}
+
+	/// Unmounts the mount at `index` and mounts its real path again under the
+	/// same mount point and at the same position in [`Self::mounts`], so that
+	/// any changes to it on the real file system get picked up.
+	/// Returns what changed since `before`, which can be given to [`PrepMap::apply`].
+	///
+	/// `before` has to have been [taken](Self::snapshot) when the mount's content
+	/// was last used (e.g. prepared), rather than just before calling this, since
+	/// content which is not ingested gets read from the real file system lazily.
+	///
+	/// If the re-mount fails, the old mount stays removed.
+	pub fn remount(&mut self, index: usize, before: &Snapshot) -> Result<SnapshotDiff, Error> {
+		let Some(mntinfo) = self.mounts.get(index) else {
+			return Err(Error::MountIndex(index));
+		};
+
+		let real_path = mntinfo.real_path.clone();
+		let mount_point = mntinfo.mount_point.clone();
+
+		match mntinfo.root {
+			Slot::File(islot) => {
+				self.remove_file_by_slot(islot);
+			}
+			Slot::Folder(oslot) => self.remove_folder_by_slot(oslot),
+		}
+
+		let name = VPath::new(mount_point.as_str().trim_start_matches('/'));
+		self.mount(&real_path, name)?;
+		let remounted = self.mounts.pop().unwrap();
+		self.mounts.insert(index, remounted);
+
+		Ok(before.diff(&self.snapshot(index)?))
+	}
+}
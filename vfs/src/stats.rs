@@ -0,0 +1,265 @@
+//! Size and count breakdowns of subtrees, so that a load which takes up far
+//! more memory than expected can be traced back to a mount or folder.
+
+use rayon::prelude::*;
+
+use super::{
+	detail::Reader, FileRef, FileSlot, FolderRef, MountInfo, Ref, Slot, VPath, VPathBuf, VirtualFs,
+};
+
+/// Aggregated over every file and folder under some folder; see
+/// [`VirtualFs::dir_stats`] and [`VirtualFs::mount_stats`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DirStats {
+	pub files: usize,
+	/// Not including the folder these statistics were taken from.
+	pub folders: usize,
+	/// The sum of every file's [size](super::VFile::size).
+	pub bytes: usize,
+	/// How much of [`Self::bytes`] is held in memory, having been
+	/// [ingested](VirtualFs::ingest_all). The rest gets read from the physical
+	/// file system whenever it is needed. Buffers shared through
+	/// [deduplication](VirtualFs::set_dedup) are counted once per file.
+	pub bytes_in_memory: usize,
+	/// The path and size of the biggest file. On a tie, the first one found wins.
+	pub largest: Option<(VPathBuf, usize)>,
+	pub kinds: KindCounts,
+}
+
+impl DirStats {
+	#[must_use]
+	pub fn bytes_on_disk(&self) -> usize {
+		self.bytes - self.bytes_in_memory
+	}
+}
+
+/// [`DirStats`] for the subtree under one [`MountInfo`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountStats {
+	pub mount_point: VPathBuf,
+	pub stats: DirStats,
+}
+
+/// A coarse guess at what a file holds, made from its extension alone so that
+/// taking statistics never has to read any file content. Extensionless files
+/// (i.e. most WAD lumps) are always [`FileKind::Binary`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+	Text,
+	Binary,
+	Image,
+	Audio,
+}
+
+impl FileKind {
+	#[must_use]
+	pub fn guess(name: &VPath) -> Self {
+		const KINDS: &[(&[&str], FileKind)] = &[
+			(
+				&[
+					"txt", "cfg", "ini", "md", "toml", "json", "xml", "lith", "zs", "zsc", "acs",
+					"dec", "wgsl",
+				],
+				FileKind::Text,
+			),
+			(
+				&["png", "jpg", "jpeg", "bmp", "tga", "gif", "pcx", "webp"],
+				FileKind::Image,
+			),
+			(
+				&["wav", "ogg", "flac", "mp3", "mid", "midi", "mus", "sf2"],
+				FileKind::Audio,
+			),
+		];
+
+		let Some(ext) = name.extension() else {
+			return Self::Binary;
+		};
+
+		KINDS
+			.iter()
+			.find(|(exts, _)| exts.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+			.map_or(Self::Binary, |(_, kind)| *kind)
+	}
+}
+
+/// How many files of each [`FileKind`] are in a subtree.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KindCounts {
+	pub text: usize,
+	pub binary: usize,
+	pub image: usize,
+	pub audio: usize,
+}
+
+impl KindCounts {
+	#[must_use]
+	pub fn get(&self, kind: FileKind) -> usize {
+		match kind {
+			FileKind::Text => self.text,
+			FileKind::Binary => self.binary,
+			FileKind::Image => self.image,
+			FileKind::Audio => self.audio,
+		}
+	}
+
+	fn add(&mut self, kind: FileKind) {
+		match kind {
+			FileKind::Text => self.text += 1,
+			FileKind::Binary => self.binary += 1,
+			FileKind::Image => self.image += 1,
+			FileKind::Audio => self.audio += 1,
+		}
+	}
+
+	#[must_use]
+	fn merge(self, other: Self) -> Self {
+		Self {
+			text: self.text + other.text,
+			binary: self.binary + other.binary,
+			image: self.image + other.image,
+			audio: self.audio + other.audio,
+		}
+	}
+}
+
+impl VirtualFs {
+	/// One element per mount, in mount order. Computed in parallel upon the
+	/// first call and cached until the next mount, unmount, removal,
+	/// or [ingestion](Self::ingest_all).
+	#[must_use]
+	pub fn mount_stats(&self) -> Vec<MountStats> {
+		let mut cache = self.stats_cache.lock();
+
+		if let Some(stats) = cache.as_ref() {
+			return stats.clone();
+		}
+
+		let stats = self
+			.mounts
+			.par_iter()
+			.map(|mntinfo| MountStats {
+				mount_point: mntinfo.mount_point.clone(),
+				stats: self.mount_tally(mntinfo).finish(self),
+			})
+			.collect::<Vec<_>>();
+
+		*cache = Some(stats.clone());
+		stats
+	}
+
+	/// Returns `None` if nothing exists at `vpath`. If `vpath` leads to a file,
+	/// the returned statistics are only for that file. Never cached.
+	#[must_use]
+	pub fn dir_stats(&self, vpath: &VPath) -> Option<DirStats> {
+		match self.lookup(vpath)? {
+			Ref::File(fref) => Some(Tally::file(&fref).finish(self)),
+			Ref::Folder(oref) => Some(oref.stats()),
+		}
+	}
+
+	#[must_use]
+	fn mount_tally(&self, mntinfo: &MountInfo) -> Tally {
+		match mntinfo.root {
+			Slot::File(islot) => Tally::file(&self.get_file(islot).unwrap()),
+			Slot::Folder(oslot) => Tally::folder(self.get_folder(oslot).unwrap()),
+		}
+	}
+
+	pub(crate) fn invalidate_stats(&mut self) {
+		*self.stats_cache.get_mut() = None;
+	}
+}
+
+impl FolderRef<'_> {
+	/// See [`VirtualFs::dir_stats`].
+	#[must_use]
+	pub fn stats(&self) -> DirStats {
+		Tally::folder(*self).finish(self.vfs)
+	}
+}
+
+/// [`DirStats`] before the largest file's path gets resolved,
+/// so that paths are not built for every file along the way.
+#[derive(Debug, Default)]
+struct Tally {
+	files: usize,
+	folders: usize,
+	bytes: usize,
+	bytes_in_memory: usize,
+	largest: Option<(FileSlot, usize)>,
+	kinds: KindCounts,
+}
+
+impl Tally {
+	#[must_use]
+	fn file(fref: &FileRef) -> Self {
+		let size = fref.size();
+		let in_memory = matches!(*fref.vfile.reader.lock(), Reader::Memory(_));
+		let mut kinds = KindCounts::default();
+		kinds.add(FileKind::guess(fref.name()));
+
+		Self {
+			files: 1,
+			folders: 0,
+			bytes: size,
+			bytes_in_memory: if in_memory { size } else { 0 },
+			largest: Some((fref.slot, size)),
+			kinds,
+		}
+	}
+
+	#[must_use]
+	fn folder(oref: FolderRef) -> Self {
+		let files = oref
+			.vfolder
+			.files
+			.par_iter()
+			.map(|islot| Self::file(&oref.vfs.get_file(*islot).unwrap()))
+			.reduce(Self::default, Self::merge);
+
+		let subfolders = oref
+			.vfolder
+			.subfolders
+			.par_iter()
+			.map(|oslot| {
+				let mut tally = Self::folder(oref.vfs.get_folder(*oslot).unwrap());
+				tally.folders += 1;
+				tally
+			})
+			.reduce(Self::default, Self::merge);
+
+		files.merge(subfolders)
+	}
+
+	#[must_use]
+	fn merge(self, other: Self) -> Self {
+		let largest = match (self.largest, other.largest) {
+			(Some(l), Some(r)) => Some(if r.1 > l.1 { r } else { l }),
+			(l, r) => l.or(r),
+		};
+
+		Self {
+			files: self.files + other.files,
+			folders: self.folders + other.folders,
+			bytes: self.bytes + other.bytes,
+			bytes_in_memory: self.bytes_in_memory + other.bytes_in_memory,
+			largest,
+			kinds: self.kinds.merge(other.kinds),
+		}
+	}
+
+	#[must_use]
+	fn finish(self, vfs: &VirtualFs) -> DirStats {
+		DirStats {
+			files: self.files,
+			folders: self.folders,
+			bytes: self.bytes,
+			bytes_in_memory: self.bytes_in_memory,
+			largest: self
+				.largest
+				.map(|(islot, size)| (vfs.get_file(islot).unwrap().path(), size)),
+			kinds: self.kinds,
+		}
+	}
+}
@@ -41,6 +41,97 @@ fn vpath_smoke() {
 	assert_eq!(folder.path(), VPathBuf::from("/viletech/shaders"));
 }
 
+#[test]
+fn vpath_ops() {
+	let vpb = VPathBuf::from("\\freedoom2\\MAP01\\THINGS");
+	assert_eq!(vpb.as_str(), "/freedoom2/MAP01/THINGS");
+	assert_eq!(vpb.parent().unwrap(), VPath::new("/freedoom2/MAP01"));
+	assert_eq!(vpb.file_name().unwrap(), VPath::new("THINGS"));
+
+	let joined = VPath::new("/viletech").join("shaders/sky.wgsl");
+	assert_eq!(joined, VPath::new("/viletech/shaders/sky.wgsl"));
+	assert_eq!(
+		VPath::new("/viletech/").join("/shaders"),
+		VPath::new("/viletech/shaders")
+	);
+	assert_eq!(joined.extension(), Some("wgsl"));
+	assert_eq!(VPath::new("/lorem.d/ipsum").extension(), None);
+	assert_eq!(VPath::new("/.hidden").extension(), None);
+	assert_eq!(
+		VPath::new("/.hidden").file_stem().unwrap().as_str(),
+		".hidden"
+	);
+
+	assert_eq!(VPath::new("/viletech").parent().unwrap(), VPath::new("/"));
+	assert_eq!(VPath::new("linedefs").parent().unwrap(), VPath::new(""));
+	assert!(VPath::new("/").parent().is_none());
+	assert!(VPath::new("/").file_name().is_none());
+
+	let mut sorted = vec![
+		VPathBuf::from("/b"),
+		VPathBuf::from("/a/z"),
+		VPathBuf::from("/a"),
+	];
+	sorted.sort();
+	assert_eq!(
+		sorted,
+		[VPath::new("/a"), VPath::new("/a/z"), VPath::new("/b")]
+	);
+}
+
+#[test]
+fn vpath_separators() {
+	let expected = VPath::new("/freedoom2/MAP01/THINGS");
+
+	assert_eq!(
+		VPathBuf::new("\\freedoom2\\MAP01\\THINGS".to_string()),
+		expected
+	);
+	assert_eq!(VPathBuf::from("/freedoom2\\MAP01/THINGS"), expected);
+	assert_eq!(VPath::new("\\freedoom2").join("MAP01\\THINGS"), expected);
+	assert_eq!(VPath::new("/freedoom2/").join("\\MAP01/THINGS"), expected);
+
+	// A borrowed path is never rewritten.
+	assert_eq!(VPath::new("\\freedoom2").as_str(), "\\freedoom2");
+	assert_ne!(VPath::new("\\freedoom2"), VPathBuf::from("\\freedoom2"));
+}
+
+#[test]
+fn vpath_relations() {
+	let lmp = VPath::new("/freedoom2/MAP01/THINGS");
+	assert_eq!(lmp.component_count(), 3);
+	assert_eq!(VPath::new("/").component_count(), 0);
+	assert_eq!(VPath::new("//a//b/").component_count(), 2);
+
+	assert!(lmp.is_child_of(VPath::new("/freedoom2")));
+	assert!(lmp.is_child_of(VPath::new("/freedoom2/")));
+	assert!(lmp.is_child_of(VPath::new("/freedoom2/MAP01/THINGS")));
+	assert!(lmp.is_child_of(VPath::new("/")));
+	assert!(!lmp.is_child_of(VPath::new("/freedoom")));
+	assert!(!lmp.is_child_of(VPath::new("/freedoom2/MAP01/THINGS/x")));
+
+	assert_eq!(
+		VPath::common_prefix(lmp, VPath::new("/freedoom2/MAP01/LINEDEFS")),
+		VPath::new("/freedoom2/MAP01")
+	);
+	assert_eq!(
+		VPath::common_prefix(lmp, VPath::new("/freedoom2/MAP0")),
+		VPath::new("/freedoom2")
+	);
+	assert_eq!(
+		VPath::common_prefix(lmp, VPath::new("/viletech")),
+		VPath::new("/")
+	);
+	assert_eq!(
+		VPath::common_prefix(VPath::new("a/b"), VPath::new("a/c")),
+		VPath::new("a")
+	);
+	assert_eq!(
+		VPath::common_prefix(lmp, VPath::new("freedoom2")),
+		VPath::new("")
+	);
+}
+
 #[test]
 fn mount_smoke() {
 	let Some(vfs) = sample_vfs() else {
@@ -112,6 +203,765 @@ fn read_smoke() {
 	);
 }
 
+#[test]
+fn folder_lookup() {
+	let vfs = basedata_vfs();
+
+	let folder = vfs
+		.lookup(VPath::new("/viletech"))
+		.unwrap()
+		.into_folder()
+		.unwrap();
+
+	let shader = folder.lookup(VPath::new("SHADERS/Sky.WGSL")).unwrap();
+	assert_eq!(shader.path(), VPathBuf::from("/viletech/shaders/sky.wgsl"));
+
+	let shaders = folder
+		.lookup(VPath::new("shaders"))
+		.unwrap()
+		.into_folder()
+		.unwrap();
+	assert!(shaders.lookup(VPath::new("terrain.wgsl")).is_some());
+
+	assert_eq!(
+		folder
+			.lookup(VPath::new(""))
+			.unwrap()
+			.into_folder()
+			.unwrap(),
+		folder
+	);
+	assert!(folder.lookup(VPath::new("viletech")).is_none());
+	assert!(folder.lookup(VPath::new("shaders/nonexistent")).is_none());
+}
+
+#[test]
+fn text_read() {
+	let vfs = basedata_vfs();
+
+	let meta = vfs
+		.lookup(VPath::new("/viletech/meta.toml"))
+		.unwrap()
+		.into_file()
+		.unwrap();
+
+	let text = meta.copy_string().unwrap();
+	assert!(text.starts_with("id = 'viletech'"));
+
+	let pattern = regex::Regex::new(r"(?m)^\[\[lithica\]\]$").unwrap();
+	assert!(meta.contains_regex(&pattern).unwrap());
+
+	let pattern = regex::Regex::new("^zscript").unwrap();
+	assert!(!meta.contains_regex(&pattern).unwrap());
+
+	let png = vfs
+		.lookup(VPath::new("/viletech/viletech.png"))
+		.unwrap()
+		.into_file()
+		.unwrap();
+
+	assert!(matches!(png.copy_string(), Err(Error::Utf8(_))));
+	assert!(!png.contains_regex(&pattern).unwrap());
+}
+
+#[test]
+fn files_with_extensions() {
+	const CONTENT: &[u8] = b"consectetur adipiscing elit";
+
+	let dir = TempTree::new(
+		"exts",
+		&[
+			("lorem.png", CONTENT),
+			("ipsum.WAD", CONTENT),
+			("dolor.txt", CONTENT),
+			("sit", CONTENT),
+		],
+	);
+
+	let mut vfs = VirtualFs::default();
+	vfs.mount(dir.path(), VPath::new("exts")).unwrap();
+
+	let mut found = vfs
+		.files_with_extensions(&["png", "wad"])
+		.map(|file| file.name().as_str().to_string())
+		.collect::<Vec<_>>();
+
+	found.sort();
+	assert_eq!(found, ["ipsum.WAD", "lorem.png"]);
+	assert_eq!(vfs.files_with_extensions(&[]).count(), 0);
+}
+
+#[test]
+fn dedup() {
+	const CONTENT: &[u8] = b"sed do eiusmod tempor incididunt";
+
+	let dir = TempTree::new(
+		"dedup",
+		&[
+			("lorem.txt", CONTENT),
+			("ipsum.txt", CONTENT),
+			("dolor.txt", b"ut labore et dolore magna aliqua"),
+		],
+	);
+
+	let mut vfs = VirtualFs::default();
+	vfs.set_dedup(true);
+	vfs.mount(dir.path(), VPath::new("dedup")).unwrap();
+	vfs.ingest_all();
+
+	#[must_use]
+	fn buffer(vfs: &VirtualFs, path: &str) -> Arc<[u8]> {
+		let file = vfs.lookup(VPath::new(path)).unwrap().into_file().unwrap();
+		let guard = file.vfile.reader.lock();
+
+		let Reader::Memory(bytes) = std::ops::Deref::deref(&guard) else {
+			panic!("`{path}` was not ingested");
+		};
+
+		bytes.clone()
+	}
+
+	let lorem = buffer(&vfs, "/dedup/lorem.txt");
+	let ipsum = buffer(&vfs, "/dedup/ipsum.txt");
+	let dolor = buffer(&vfs, "/dedup/dolor.txt");
+
+	assert!(Arc::ptr_eq(&lorem, &ipsum));
+	assert!(!Arc::ptr_eq(&lorem, &dolor));
+	assert_eq!(&lorem[..], CONTENT);
+
+	assert_eq!(
+		vfs.dedup_stats(),
+		DedupStats {
+			files_deduped: 1,
+			bytes_saved: CONTENT.len(),
+		}
+	);
+
+	vfs.set_dedup(false);
+	assert_eq!(vfs.dedup_stats(), DedupStats::default());
+}
+
+#[test]
+fn lookup_index() {
+	const LUMP_COUNT: usize = 50_000;
+
+	let path = std::env::temp_dir().join(format!("viletech-fs-index-{}.wad", std::process::id()));
+	std::fs::write(&path, synthetic_wad(LUMP_COUNT)).unwrap();
+
+	let mut vfs = VirtualFs::default();
+	let result = vfs.mount(&path, VPath::new("synth"));
+	std::fs::remove_file(&path).unwrap();
+	result.unwrap();
+
+	for i in (0..LUMP_COUNT).step_by(7) {
+		let lump = vfs
+			.lookup(VPath::new(&format!("/SYNTH/l{i:05}")))
+			.unwrap()
+			.into_file()
+			.unwrap();
+
+		assert_eq!(lump.name().as_str(), format!("L{i:05}"));
+	}
+
+	assert!(vfs.lookup(VPath::new("/synth/L50000")).is_none());
+	assert!(vfs.lookup(VPath::new("/synth/L00000/L00000")).is_none());
+	assert!(vfs.lookup(VPath::new("/L00000")).is_none());
+	assert_eq!(
+		vfs.lookup(VPath::new("/")).unwrap().path(),
+		VPathBuf::from("/")
+	);
+}
+
+#[test]
+fn lookup_index_removal() {
+	let mut vfs = basedata_vfs();
+
+	let path = std::env::temp_dir().join(format!("viletech-fs-rm-{}.wad", std::process::id()));
+	std::fs::write(&path, synthetic_wad(4)).unwrap();
+	let result = vfs.mount(&path, VPath::new("synth"));
+	std::fs::remove_file(&path).unwrap();
+	result.unwrap();
+
+	let lump = vfs
+		.lookup(VPath::new("/synth/L00002"))
+		.unwrap()
+		.into_file()
+		.unwrap()
+		.slot;
+
+	assert!(vfs.remove_file_by_slot(lump));
+	assert!(vfs.lookup(VPath::new("/synth/L00002")).is_none());
+	assert!(vfs.lookup(VPath::new("/synth/L00003")).is_some());
+
+	// Removing a file un-shadows any later sibling with the same name.
+	let dup = TempTree::new(
+		"rm-dup",
+		&[("dup.wad", &build_pwad(&[("DUP", b"a"), ("DUP", b"b")]))],
+	);
+	vfs.mount(&dup.join("dup.wad"), VPath::new("dup")).unwrap();
+	let first = vfs
+		.lookup(VPath::new("/dup/DUP"))
+		.unwrap()
+		.into_file()
+		.unwrap();
+	assert_eq!(first.copy_string().unwrap(), "a");
+	let first = first.slot;
+
+	assert!(vfs.remove_file_by_slot(first));
+	let second = vfs
+		.lookup(VPath::new("/dup/dup"))
+		.unwrap()
+		.into_file()
+		.unwrap();
+	assert_eq!(second.copy_string().unwrap(), "b");
+
+	vfs.retain(|mntinfo| mntinfo.mount_point.as_str() != "/synth")
+		.unwrap();
+	assert!(vfs.lookup(VPath::new("/synth")).is_none());
+	assert!(vfs.lookup(VPath::new("/synth/L00003")).is_none());
+	assert!(vfs.lookup(VPath::new("/viletech/meta.toml")).is_some());
+
+	// Removing a folder un-shadows any later sibling with the same name.
+	let shaders = vfs
+		.lookup(VPath::new("/viletech/shaders"))
+		.unwrap()
+		.into_folder()
+		.unwrap()
+		.slot;
+
+	let viletech = vfs.folders[shaders].parent.unwrap();
+
+	let shadowed = vfs.folders.insert(VFolder {
+		name: SmallString::from("SHADERS"),
+		parent: Some(viletech),
+		files: indexmap::indexset![],
+		subfolders: indexmap::indexset![],
+		kind: FolderKind::Directory,
+	});
+
+	vfs.folders[viletech].subfolders.insert(shadowed);
+	vfs.reindex();
+	assert_eq!(
+		vfs.lookup(VPath::new("/viletech/shaders"))
+			.unwrap()
+			.into_folder()
+			.unwrap()
+			.slot,
+		shaders
+	);
+
+	vfs.remove_folder_by_slot(shaders);
+	assert!(vfs
+		.lookup(VPath::new("/viletech/shaders/sky.wgsl"))
+		.is_none());
+	assert_eq!(
+		vfs.lookup(VPath::new("/viletech/shaders"))
+			.unwrap()
+			.into_folder()
+			.unwrap()
+			.slot,
+		shadowed
+	);
+
+	vfs.clear();
+	assert!(vfs.lookup(VPath::new("/viletech")).is_none());
+	assert!(vfs.lookup(VPath::new("/")).is_some());
+}
+
+#[test]
+fn namespace() {
+	let dir = TempTree::new(
+		"namespace",
+		&[(
+			"ns.wad",
+			&build_pwad(&[
+				("PLAYPAL", b"0".as_slice()),
+				("c_start", b""),
+				("WATERMAP", b"1"),
+				("LAVAMAP", b"2"),
+				("C_END", b""),
+				("F_START", b""),
+				("FLOOR0_1", b"3"),
+			]),
+		)],
+	);
+
+	let mut vfs = VirtualFs::default();
+	vfs.mount(&dir.join("ns.wad"), VPath::new("ns")).unwrap();
+
+	let wad = vfs
+		.lookup(VPath::new("/ns"))
+		.unwrap()
+		.into_folder()
+		.unwrap();
+
+	let names = |start, end| {
+		wad.namespace(start, end)
+			.map(|fref| fref.name().as_str().to_string())
+			.collect::<Vec<_>>()
+	};
+
+	assert_eq!(names("C_START", "C_END"), ["WATERMAP", "LAVAMAP"]);
+	// No `F_END`.
+	assert!(names("F_START", "F_END").is_empty());
+	// `C_START` only comes after.
+	assert!(names("C_END", "C_START").is_empty());
+	assert!(names("S_START", "S_END").is_empty());
+}
+
+#[test]
+fn prune_empty_folders() {
+	let mut vfs = basedata_vfs();
+
+	let real = TempTree::new(
+		"prune",
+		&[("b/c/lorem.txt", b"lorem"), ("ipsum.txt", b"ipsum")],
+	);
+	vfs.mount(real.path(), VPath::new("a")).unwrap();
+
+	let lorem = vfs
+		.lookup(VPath::new("/a/b/c/lorem.txt"))
+		.unwrap()
+		.into_file()
+		.unwrap()
+		.slot;
+
+	assert!(vfs.remove_file_by_slot(lorem));
+	assert!(vfs.lookup(VPath::new("/a/b/c")).is_some());
+
+	assert_eq!(vfs.prune_empty_folders(), 2);
+	assert!(vfs.lookup(VPath::new("/a/b/c")).is_none());
+	assert!(vfs.lookup(VPath::new("/a/b")).is_none());
+	assert!(vfs.lookup(VPath::new("/a/ipsum.txt")).is_some());
+	assert_eq!(vfs.prune_empty_folders(), 0);
+
+	vfs.retain(|mntinfo| mntinfo.mount_point.as_str() != "/a")
+		.unwrap();
+	assert!(vfs.lookup(VPath::new("/a")).is_none());
+	assert!(vfs.lookup(VPath::new("/")).is_some());
+	assert!(vfs.lookup(VPath::new("/viletech/meta.toml")).is_some());
+}
+
+#[test]
+fn stats() {
+	let base = TempTree::new(
+		"stats",
+		&[
+			("a/lorem.txt", b"lorem"),
+			("a/sub/ipsum.png", &[0; 10]),
+			("a/sub/DOLOR", &[0; 20]),
+			("a/sit.wav", &[0; 3]),
+			("b/amet.txt", b"amet"),
+		],
+	);
+
+	let mut vfs = VirtualFs::default();
+	vfs.mount(&base.join("a"), VPath::new("a")).unwrap();
+	vfs.mount(&base.join("b"), VPath::new("b")).unwrap();
+
+	let mstats = vfs.mount_stats();
+	assert_eq!(mstats.len(), 2);
+	assert_eq!(mstats[0].mount_point, VPath::new("/a"));
+
+	assert_eq!(
+		mstats[0].stats,
+		DirStats {
+			files: 4,
+			folders: 1,
+			bytes: 38,
+			bytes_in_memory: 0,
+			largest: Some((VPathBuf::from("/a/sub/DOLOR"), 20)),
+			kinds: KindCounts {
+				text: 1,
+				binary: 1,
+				image: 1,
+				audio: 1,
+			},
+		}
+	);
+
+	assert_eq!(mstats[1].stats.files, 1);
+	assert_eq!(mstats[1].stats.bytes, 4);
+
+	// Per-mount totals have to account for every byte in the tree.
+	let total = vfs.files().map(|file| file.size()).sum::<usize>();
+	let root = vfs.dir_stats(VPath::new("/")).unwrap();
+	assert_eq!(root.bytes, total);
+	assert_eq!(mstats.iter().map(|m| m.stats.bytes).sum::<usize>(), total);
+	assert_eq!(root.folders, 3);
+
+	let sub = vfs.dir_stats(VPath::new("/a/sub")).unwrap();
+	assert_eq!((sub.files, sub.folders, sub.bytes), (2, 0, 30));
+	let file = vfs.dir_stats(VPath::new("/a/lorem.txt")).unwrap();
+	assert_eq!((file.files, file.bytes, file.kinds.text), (1, 5, 1));
+	assert!(vfs.dir_stats(VPath::new("/a/consectetur")).is_none());
+
+	assert!(vfs.stats_cache.lock().is_some());
+	vfs.ingest_all();
+	assert!(vfs.stats_cache.lock().is_none());
+	let mstats = vfs.mount_stats();
+	assert_eq!(mstats[0].stats.bytes_in_memory, 38);
+	assert_eq!(mstats[0].stats.bytes_on_disk(), 0);
+
+	vfs.retain(|mntinfo| mntinfo.mount_point.as_str() != "/b")
+		.unwrap();
+	assert!(vfs.stats_cache.lock().is_none());
+	let mstats = vfs.mount_stats();
+	assert_eq!(mstats.len(), 1);
+	assert_eq!(mstats[0].mount_point, VPath::new("/a"));
+}
+
+#[test]
+fn merged() {
+	let base = TempTree::new(
+		"merge",
+		&[
+			("a/sub/lorem.txt", b"lorem"),
+			("b/ipsum.txt", b"ipsum"),
+			("c/dolor.txt", b"dolor"),
+		],
+	);
+
+	let mut ours = VirtualFs::default();
+	let mut theirs = VirtualFs::default();
+	ours.mount(&base.join("a"), VPath::new("a")).unwrap();
+	theirs.mount(&base.join("b"), VPath::new("b")).unwrap();
+	// Shares a mount point with one of ours, so gets left out.
+	theirs.mount(&base.join("c"), VPath::new("a")).unwrap();
+
+	let vfs = ours.merged(&theirs);
+	drop(ours);
+	drop(theirs);
+
+	let mount_points: Vec<_> = vfs
+		.mounts()
+		.iter()
+		.map(|mntinfo| mntinfo.mount_point.as_str())
+		.collect();
+	assert_eq!(mount_points, ["/a", "/b"]);
+
+	let lorem = vfs
+		.lookup(VPath::new("/a/sub/lorem.txt"))
+		.unwrap()
+		.into_file()
+		.unwrap();
+	assert_eq!(lorem.copy_string().unwrap(), "lorem");
+	assert!(vfs.lookup(VPath::new("/b/ipsum.txt")).is_some());
+	assert!(vfs.lookup(VPath::new("/a/dolor.txt")).is_none());
+	assert_eq!(vfs.file_count(), 2);
+}
+
+#[test]
+fn budget_oversized() {
+	let budget = MemBudget::new(64);
+
+	// A file larger than the entire budget must still get processed (alone).
+	[16, 1024, 16, 16].into_par_iter().for_each(|bytes| {
+		let guard = budget.reserve(bytes);
+		assert_eq!(guard.is_oversized(), bytes > 64);
+		std::thread::sleep(std::time::Duration::from_millis(5));
+	});
+
+	assert_eq!(budget.live(), 0);
+	assert_eq!(budget.peak(), 1024);
+}
+
+#[test]
+fn budget_sequential() {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	let files: Vec<_> = (1..=32)
+		.map(|i| (format!("f{i:02}.bin"), vec![i as u8; i * 8]))
+		.collect();
+	let files: Vec<_> = files
+		.iter()
+		.map(|(name, bytes)| (name.as_str(), bytes.as_slice()))
+		.collect();
+	let base = TempTree::new("budget", &files);
+
+	let mut vfs = VirtualFs::default();
+	vfs.mount(base.path(), VPath::new("budget")).unwrap();
+	let folder = vfs
+		.lookup(VPath::new("/budget"))
+		.unwrap()
+		.into_folder()
+		.unwrap();
+
+	let process = |budget: &MemBudget| {
+		let in_flight = AtomicUsize::new(0);
+		let max_in_flight = AtomicUsize::new(0);
+
+		let results = folder.par_files_budgeted(budget, |vfile, guard| {
+			assert_eq!(guard.bytes(), vfile.size());
+			let n = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+			max_in_flight.fetch_max(n, Ordering::SeqCst);
+			let bytes = vfile.lock().read().unwrap().into_owned();
+			std::thread::sleep(std::time::Duration::from_millis(1));
+			in_flight.fetch_sub(1, Ordering::SeqCst);
+			(vfile.name().to_string(), bytes.len())
+		});
+
+		(results, max_in_flight.into_inner())
+	};
+
+	let (generous, _) = process(&MemBudget::new(usize::MAX));
+	let tiny = MemBudget::new(1);
+	let (sequential, max_in_flight) = process(&tiny);
+
+	assert_eq!(generous.len(), 32);
+	assert_eq!(generous, sequential);
+	assert_eq!(max_in_flight, 1);
+	assert_eq!(tiny.peak(), 32 * 8);
+	assert_eq!(tiny.live(), 0);
+
+	// Results follow the folder's own order, regardless of processing order.
+	let names: Vec<_> = folder.files().map(|f| f.name().to_string()).collect();
+	let result_names: Vec<_> = generous.into_iter().map(|(name, _)| name).collect();
+	assert_eq!(names, result_names);
+}
+
+#[test]
+fn snapshot_diff() {
+	let base = TempTree::new(
+		"snapshot",
+		&[
+			("mnt/KEPT", b"kept"),
+			("mnt/CHANGED", b"changed"),
+			("mnt/REMOVED", b"removed"),
+			("mnt/MAP01/THINGS", b"things"),
+			("other/lorem.txt", b"lorem"),
+		],
+	);
+
+	let mut vfs = VirtualFs::default();
+	vfs.mount(&base.join("mnt"), VPath::new("mnt")).unwrap();
+	vfs.mount(&base.join("other"), VPath::new("other")).unwrap();
+
+	let older = vfs.snapshot(0).unwrap();
+	assert_eq!(older.len(), 4);
+	assert!(older.diff(&older).is_empty());
+
+	std::fs::write(base.join("mnt/CHANGED"), b"CHANGED").unwrap();
+	std::fs::write(base.join("mnt/MAP01/THINGS"), b"THINGS").unwrap();
+	std::fs::write(base.join("mnt/ADDED"), b"added").unwrap();
+	std::fs::remove_file(base.join("mnt/REMOVED")).unwrap();
+
+	let diff = vfs.remount(0, &older).unwrap();
+
+	assert_eq!(
+		diff.changed,
+		[
+			VPathBuf::from("/mnt/CHANGED"),
+			VPathBuf::from("/mnt/MAP01/THINGS")
+		]
+	);
+	assert_eq!(diff.added, [VPathBuf::from("/mnt/ADDED")]);
+	assert_eq!(diff.removed, [VPathBuf::from("/mnt/REMOVED")]);
+	assert!(diff.touches(VPath::new("/mnt/MAP01")));
+	assert!(!diff.touches(VPath::new("/mnt/KEPT")));
+
+	// The remounted mount keeps its place in the load order.
+	let mount_points: Vec<_> = vfs
+		.mounts()
+		.iter()
+		.map(|mntinfo| mntinfo.mount_point.as_str())
+		.collect();
+	assert_eq!(mount_points, ["/mnt", "/other"]);
+	assert!(vfs.lookup(VPath::new("/mnt/ADDED")).is_some());
+	assert!(vfs.lookup(VPath::new("/mnt/REMOVED")).is_none());
+	assert!(vfs.lookup(VPath::new("/other/lorem.txt")).is_some());
+	assert!(matches!(vfs.remount(2, &older), Err(Error::MountIndex(2))));
+}
+
+#[test]
+fn prep_map_reload() {
+	use std::{cell::Cell, sync::Arc};
+
+	fn pwad(beta: &str) -> Vec<u8> {
+		build_pwad(&[("DSALPHA", "alpha"), ("DSBETA", beta), ("DSGAMMA", "gamma")])
+	}
+
+	let base = TempTree::new(
+		"prep-map",
+		&[("lorem.txt", b"lorem"), ("sounds.wad", &pwad("beta"))],
+	);
+	let wad_path = base.join("sounds.wad");
+
+	let mut vfs = VirtualFs::default();
+	vfs.mount(&base.join("lorem.txt"), VPath::new("lorem.txt"))
+		.unwrap();
+	vfs.mount(&wad_path, VPath::new("sounds")).unwrap();
+
+	let prep_count = Cell::new(0);
+	let prep = |vfile: FileRef| {
+		prep_count.set(prep_count.get() + 1);
+		Some(vfile.copy_string().unwrap())
+	};
+
+	let mut sounds = PrepMap::default();
+	let folder = vfs
+		.lookup(VPath::new("/sounds"))
+		.unwrap()
+		.into_folder()
+		.unwrap();
+	assert_eq!(sounds.prep_all(folder, prep), 3);
+	assert_eq!(sounds.len(), 3);
+	let snapshot = vfs.snapshot(1).unwrap();
+
+	let before: Vec<_> = ["/sounds/DSALPHA", "/sounds/DSBETA", "/sounds/DSGAMMA"]
+		.into_iter()
+		.map(|path| sounds.get(VPath::new(path)).unwrap().clone())
+		.collect();
+
+	// Only the middle lump changes.
+	std::fs::write(&wad_path, pwad("BETA")).unwrap();
+	prep_count.set(0);
+
+	let diff = vfs.remount(1, &snapshot).unwrap();
+	assert_eq!(sounds.apply(&vfs, &diff, prep), 1);
+	assert_eq!(prep_count.get(), 1);
+	assert_eq!(sounds.len(), 3);
+
+	let after: Vec<_> = ["/sounds/DSALPHA", "/sounds/DSBETA", "/sounds/DSGAMMA"]
+		.into_iter()
+		.map(|path| sounds.get(VPath::new(path)).unwrap().clone())
+		.collect();
+
+	assert!(Arc::ptr_eq(&before[0], &after[0]));
+	assert!(!Arc::ptr_eq(&before[1], &after[1]));
+	assert!(Arc::ptr_eq(&before[2], &after[2]));
+	assert_eq!(after[1].as_str(), "BETA");
+}
+
+#[test]
+fn watch_debounce() {
+	let quiet = std::time::Duration::from_millis(250);
+	let mut debouncer = watch::Debouncer::new(quiet);
+	let start = std::time::Instant::now();
+
+	assert!(!debouncer.due(start));
+
+	debouncer.poke(start);
+	assert!(!debouncer.due(start + quiet / 2));
+	// Another change within the quiet period pushes the request back.
+	debouncer.poke(start + quiet / 2);
+	assert!(!debouncer.due(start + quiet));
+	assert!(debouncer.due(start + quiet / 2 + quiet));
+	// Only one request per burst.
+	assert!(!debouncer.due(start + quiet * 4));
+}
+
+#[test]
+fn watch_mount() {
+	use std::time::Duration;
+
+	let base = TempTree::new(
+		"watch",
+		&[("a/lorem.txt", b"lorem"), ("b/ipsum.txt", b"ipsum")],
+	);
+
+	let mut vfs = VirtualFs::default();
+	vfs.mount(&base.join("a"), VPath::new("a")).unwrap();
+	vfs.mount(&base.join("b"), VPath::new("b")).unwrap();
+
+	assert!(matches!(vfs.watch_mount(2), Err(Error::MountIndex(2))));
+
+	let handle = WatchHandle::new(
+		1,
+		vfs.mounts()[1].real_path.clone(),
+		Duration::from_millis(10),
+		Duration::from_millis(100),
+	)
+	.unwrap();
+	assert_eq!(handle.mount(), 1);
+	assert!(handle.receiver().try_recv().is_err());
+
+	// Several writes in quick succession, as an editor might make for one save.
+	for i in 0..3 {
+		let content = "ipsum".repeat(i + 2);
+		std::fs::write(base.join("b/ipsum.txt"), content).unwrap();
+		std::fs::write(base.join(&format!("b/dolor{i}.txt")), b"dolor").unwrap();
+	}
+
+	let req = handle
+		.receiver()
+		.recv_timeout(Duration::from_secs(5))
+		.unwrap();
+	assert_eq!(req, ReloadRequest { mount: 1 });
+	assert!(handle
+		.receiver()
+		.recv_timeout(Duration::from_millis(500))
+		.is_err());
+}
+
+#[test]
+fn watch_non_directory() {
+	let wad = synthetic_wad(1);
+	let base = TempTree::new("watch-wad", &[("synth.wad", &wad)]);
+	let mut vfs = VirtualFs::default();
+	vfs.mount(&base.join("synth.wad"), VPath::new("synth"))
+		.unwrap();
+	assert!(matches!(vfs.watch_mount(0), Err(Error::WatchNonDirectory)));
+}
+
+/// Real files under a fresh directory in the system's temporary directory,
+/// which gets deleted when this is dropped.
+#[derive(Debug)]
+struct TempTree(PathBuf);
+
+impl TempTree {
+	/// `files` are `(path, content)` pairs. Paths are relative to the new
+	/// directory, and their parent directories get created as needed.
+	#[must_use]
+	fn new(name: &str, files: &[(&str, &[u8])]) -> Self {
+		let root = std::env::temp_dir().join(format!("viletech-fs-{name}-{}", std::process::id()));
+		let _ = std::fs::remove_dir_all(&root);
+
+		for (path, content) in files {
+			let path = root.join(path);
+			std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+			std::fs::write(path, content).unwrap();
+		}
+
+		Self(root)
+	}
+
+	#[must_use]
+	fn path(&self) -> &Path {
+		&self.0
+	}
+
+	#[must_use]
+	fn join(&self, path: &str) -> PathBuf {
+		self.0.join(path)
+	}
+}
+
+impl Drop for TempTree {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_dir_all(&self.0);
+	}
+}
+
+/// A PWAD with `lump_count` empty lumps named `L00000`, `L00001`, and so on.
+#[must_use]
+fn synthetic_wad(lump_count: usize) -> Vec<u8> {
+	let lumps = (0..lump_count)
+		.map(|i| (format!("L{i:05}"), [0_u8; 0]))
+		.collect::<Vec<_>>();
+
+	build_pwad(&lumps)
+}
+
+/// Only mounts the engine's basedata, so that tests which use this never get skipped.
+#[must_use]
+fn basedata_vfs() -> VirtualFs {
+	let mut vfs = VirtualFs::default();
+	let basedata = Path::new(env!("CARGO_MANIFEST_DIR")).join("../assets/viletech");
+	vfs.mount(&basedata, VPath::new("viletech")).unwrap();
+	vfs
+}
+
 #[must_use]
 fn sample_vfs() -> Option<VirtualFs> {
 	let mut vfs = VirtualFs::default();
@@ -0,0 +1,211 @@
+//! Watching mounted directories for changes, so their content can be reloaded live.
+//!
+//! The real directory gets polled rather than subscribed to, so that this works
+//! the same on every platform without any OS-specific dependencies. Editors tend
+//! to write several times per save (truncate, write, rename, touch metadata...),
+//! so changes are debounced; a [`ReloadRequest`] only goes out once a mount has
+//! gone quiet for [`WatchHandle::QUIET`].
+
+use std::{
+	path::{Path, PathBuf},
+	sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+	thread::JoinHandle,
+	time::{Duration, Instant, SystemTime},
+};
+
+use super::{Error, MountFormat, VirtualFs};
+
+/// Yielded by a [`WatchHandle`] when something under its mount has changed.
+/// [`Self::mount`] is the index of the mount which changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReloadRequest {
+	pub mount: usize,
+}
+
+/// Returned by [`VirtualFs::watch_mount`].
+/// Watching stops when this is dropped.
+#[derive(Debug)]
+pub struct WatchHandle {
+	mount: usize,
+	receiver: Receiver<ReloadRequest>,
+	/// The polling thread exits once this drops.
+	stop: Option<Sender<()>>,
+	thread: Option<JoinHandle<()>>,
+}
+
+impl WatchHandle {
+	/// How often the watched directory gets checked for changes.
+	pub const POLL: Duration = Duration::from_millis(100);
+	/// How long a mount must go without any changes
+	/// before a [`ReloadRequest`] gets sent for it.
+	pub const QUIET: Duration = Duration::from_millis(250);
+
+	pub(crate) fn new(
+		mount: usize,
+		real_path: PathBuf,
+		poll: Duration,
+		quiet: Duration,
+	) -> Result<Self, Error> {
+		let (stop_sender, stop_receiver) = mpsc::channel();
+		let (req_sender, req_receiver) = mpsc::channel();
+
+		let thread = std::thread::Builder::new()
+			.name(format!("viletech: mount watcher {mount}"))
+			.spawn(move || watch(mount, &real_path, poll, quiet, &stop_receiver, &req_sender))
+			.map_err(Error::ThreadSpawn)?;
+
+		Ok(Self {
+			mount,
+			receiver: req_receiver,
+			stop: Some(stop_sender),
+			thread: Some(thread),
+		})
+	}
+
+	/// The index of the watched mount, as per [`VirtualFs::mounts`].
+	#[must_use]
+	pub fn mount(&self) -> usize {
+		self.mount
+	}
+
+	#[must_use]
+	pub fn receiver(&self) -> &Receiver<ReloadRequest> {
+		&self.receiver
+	}
+}
+
+impl Drop for WatchHandle {
+	fn drop(&mut self) {
+		drop(self.stop.take());
+
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl VirtualFs {
+	/// Starts watching the real directory behind the mount at `index` for changes.
+	/// Each [`ReloadRequest`] yielded by the returned handle's receiver is meant
+	/// to be answered by the caller re-mounting (or otherwise reloading) it.
+	///
+	/// Note that the watch follows the index rather than the mount itself,
+	/// so it should be dropped if the load order changes.
+	pub fn watch_mount(&self, index: usize) -> Result<WatchHandle, Error> {
+		let Some(mntinfo) = self.mounts.get(index) else {
+			return Err(Error::MountIndex(index));
+		};
+
+		if mntinfo.format != MountFormat::Directory {
+			return Err(Error::WatchNonDirectory);
+		}
+
+		WatchHandle::new(
+			index,
+			mntinfo.real_path.clone(),
+			WatchHandle::POLL,
+			WatchHandle::QUIET,
+		)
+	}
+}
+
+// Details /////////////////////////////////////////////////////////////////////
+
+/// Body of the thread behind a [`WatchHandle`].
+/// Returns once either `stop` or `requests` disconnects.
+fn watch(
+	mount: usize,
+	root: &Path,
+	poll: Duration,
+	quiet: Duration,
+	stop: &Receiver<()>,
+	requests: &Sender<ReloadRequest>,
+) {
+	let mut debouncer = Debouncer::new(quiet);
+	let mut prev = snapshot(root);
+
+	loop {
+		match stop.recv_timeout(poll) {
+			Err(RecvTimeoutError::Timeout) => {}
+			Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+		}
+
+		let now = Instant::now();
+		let current = snapshot(root);
+
+		if current != prev {
+			prev = current;
+			debouncer.poke(now);
+		}
+
+		if debouncer.due(now) && requests.send(ReloadRequest { mount }).is_err() {
+			return;
+		}
+	}
+}
+
+/// Every file and directory under `root` (recursively), sorted by path.
+/// Anything which can not be read is left out, so that it counts as a change
+/// if it becomes readable later.
+#[must_use]
+pub(crate) fn snapshot(root: &Path) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+	let mut ret = vec![];
+	snapshot_recur(root, &mut ret);
+	ret.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+	ret
+}
+
+fn snapshot_recur(dir: &Path, out: &mut Vec<(PathBuf, Option<SystemTime>, u64)>) {
+	let Ok(entries) = std::fs::read_dir(dir) else {
+		return;
+	};
+
+	for entry in entries.filter_map(Result::ok) {
+		let Ok(metadata) = entry.metadata() else {
+			continue;
+		};
+
+		let path = entry.path();
+
+		if metadata.is_dir() {
+			snapshot_recur(&path, out);
+		}
+
+		out.push((path, metadata.modified().ok(), metadata.len()));
+	}
+}
+
+/// Kept free of threads and clocks so its behavior can be tested deterministically.
+#[derive(Debug)]
+pub(crate) struct Debouncer {
+	quiet: Duration,
+	/// When the latest change not yet reported was seen.
+	pending: Option<Instant>,
+}
+
+impl Debouncer {
+	#[must_use]
+	pub(crate) fn new(quiet: Duration) -> Self {
+		Self {
+			quiet,
+			pending: None,
+		}
+	}
+
+	pub(crate) fn poke(&mut self, now: Instant) {
+		self.pending = Some(now);
+	}
+
+	/// Returns `true` at most once per burst of [pokes](Self::poke),
+	/// and only after a full quiet period has elapsed since the last of them.
+	#[must_use]
+	pub(crate) fn due(&mut self, now: Instant) -> bool {
+		match self.pending {
+			Some(last) if now.saturating_duration_since(last) >= self.quiet => {
+				self.pending = None;
+				true
+			}
+			_ => false,
+		}
+	}
+}
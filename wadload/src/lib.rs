@@ -420,6 +420,14 @@ mod test {
 
 	use super::*;
 
+	/// Guards against `util/serde` not being forwarded by this crate's feature.
+	#[test]
+	#[cfg(feature = "serde")]
+	fn serde() {
+		fn assert_impls<T: serde::Serialize + serde::de::DeserializeOwned>() {}
+		assert_impls::<DirEntry>();
+	}
+
 	#[test]
 	fn smoke() {
 		let sample = Path::new(env!("CARGO_WORKSPACE_DIR")).join("sample/freedoom1.wad");